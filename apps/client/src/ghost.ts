@@ -0,0 +1,41 @@
+// Practice-mode ghost frames, as packed by the WASM extract_ghost (fp::encode_ghost).
+
+/** One sampled tick of a previous run. Positions are in pixels. */
+export interface GhostFrame {
+  tick: number;
+  x: number;
+  y: number;
+  /** -1 left, 1 right. */
+  facing: number;
+  /** -1 unarmed, otherwise a weapon id. */
+  weapon: number;
+  alive: boolean;
+}
+
+/** Bytes per frame (fp::GHOST_FRAME_BYTES). */
+export const GHOST_FRAME_BYTES = 14;
+
+const GHOST_ALIVE = 1;
+const GHOST_FACING_LEFT = 2;
+/** Fixed-point scale of x and y (fp::ONE). */
+const FP_ONE = 256;
+
+/**
+ * Iterate the frames of an extract_ghost buffer in tick order, without copying it:
+ * tick u32, x and y fixed-point i32, weapon i8, flags u8, all little-endian.
+ * A trailing partial frame is dropped.
+ */
+export function* ghostFrames(bytes: Uint8Array): Generator<GhostFrame> {
+  const view = new DataView(bytes.buffer, bytes.byteOffset, bytes.byteLength);
+  for (let off = 0; off + GHOST_FRAME_BYTES <= bytes.length; off += GHOST_FRAME_BYTES) {
+    const flags = view.getUint8(off + 13);
+    yield {
+      tick: view.getUint32(off, true),
+      x: view.getInt32(off + 4, true) / FP_ONE,
+      y: view.getInt32(off + 8, true) / FP_ONE,
+      weapon: view.getInt8(off + 12),
+      facing: flags & GHOST_FACING_LEFT ? -1 : 1,
+      alive: (flags & GHOST_ALIVE) !== 0,
+    };
+  }
+}
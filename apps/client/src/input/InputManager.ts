@@ -7,6 +7,7 @@ export interface KeyBindings {
   right: [string, string];
   jump: [string, string];
   shoot: [string, string];
+  dash: [string, string];
   taunt: [string, string];
 }
 
@@ -17,6 +18,7 @@ const DEFAULT_BINDINGS: KeyBindings = {
   right: ["KeyD", "ArrowRight"],
   jump: ["KeyW", "ArrowUp"],
   shoot: ["Space", "Mouse0"],
+  dash: ["KeyE", "Mouse2"],
   taunt: ["KeyS", "ArrowDown"],
 };
 
@@ -95,6 +97,7 @@ export class InputManager {
       right: [...DEFAULT_BINDINGS.right],
       jump: [...DEFAULT_BINDINGS.jump],
       shoot: [...DEFAULT_BINDINGS.shoot],
+      dash: [...DEFAULT_BINDINGS.dash],
       taunt: [...DEFAULT_BINDINGS.taunt],
     };
     this.saveBindings();
@@ -114,12 +117,14 @@ export class InputManager {
     const right = this.isPressed("right");
     const jump = this.isPressed("jump");
     const shoot = this.isPressed("shoot");
+    const dash = this.isPressed("dash");
     const taunt = this.isPressed("taunt");
 
     if (left) buttons |= Button.Left;
     if (right) buttons |= Button.Right;
     if (jump) buttons |= Button.Jump;
     if (shoot) buttons |= Button.Shoot;
+    if (dash) buttons |= Button.Dash;
     if (taunt) buttons |= Button.Taunt;
 
     let aimX = 0;
@@ -145,6 +150,7 @@ export class InputManager {
           right: migrate(p.right, DEFAULT_BINDINGS.right),
           jump: migrate(p.jump, DEFAULT_BINDINGS.jump),
           shoot: migrate(p.shoot, DEFAULT_BINDINGS.shoot),
+          dash: migrate(p.dash, DEFAULT_BINDINGS.dash),
           taunt: migrate(p.taunt ?? p.crouch, DEFAULT_BINDINGS.taunt),
         };
       }
@@ -156,6 +162,7 @@ export class InputManager {
       right: [...DEFAULT_BINDINGS.right],
       jump: [...DEFAULT_BINDINGS.jump],
       shoot: [...DEFAULT_BINDINGS.shoot],
+      dash: [...DEFAULT_BINDINGS.dash],
       taunt: [...DEFAULT_BINDINGS.taunt],
     };
   }
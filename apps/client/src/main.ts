@@ -520,8 +520,9 @@ function updateControlsHint(bindings: KeyBindings) {
     const right = friendlyKeyName(bindings.right[0]);
     const jump = friendlyKeyName(bindings.jump[0]);
     const shoot = friendlyKeyName(bindings.shoot[0]);
+    const dash = friendlyKeyName(bindings.dash[0]);
     const taunt = friendlyKeyName(bindings.taunt[0]);
-    scene.setControlsHint(`${left}/${right} move  ${jump} jump  ${shoot} shoot  ${taunt} taunt`);
+    scene.setControlsHint(`${left}/${right} move  ${jump} jump  ${shoot} shoot  ${dash} dash  ${taunt} taunt`);
   }
 }
 
@@ -551,7 +552,7 @@ window.addEventListener("keydown", (e) => {
 
   const bindings = im.getBindings();
   const newCode = e.code;
-  const actions: (keyof KeyBindings)[] = ["left", "right", "jump", "shoot", "taunt"];
+  const actions: (keyof KeyBindings)[] = ["left", "right", "jump", "shoot", "dash", "taunt"];
 
   // Duplicate detection: if another slot already has this key, clear it
   for (const action of actions) {
@@ -584,7 +585,7 @@ window.addEventListener("mousedown", (e) => {
 
   const bindings = im.getBindings();
   const newCode = `Mouse${e.button}`;
-  const actions: (keyof KeyBindings)[] = ["left", "right", "jump", "shoot", "taunt"];
+  const actions: (keyof KeyBindings)[] = ["left", "right", "jump", "shoot", "dash", "taunt"];
 
   for (const action of actions) {
     for (let s = 0; s < 2; s++) {
@@ -26,6 +26,7 @@ interface RawPlayerState {
   grounded: boolean;
   stateFlags: number;
   respawnTimer: number;
+  invincibilitySource?: number; // why Invincible is set: 0 none, 1 respawn, 2 start grace, 3 scripted
   weapon?: number | null;
   ammo?: number;
   jumpsLeft?: number;
@@ -34,6 +35,7 @@ interface RawPlayerState {
   stompedBy?: number | null;
   stompingOn?: number | null;
   stompShakeProgress?: number;
+  staminaPct?: number; // wall stamina 0-100 (stamina ruleset only)
 }
 
 interface RawProjectile {
@@ -53,6 +55,7 @@ interface RawWeaponPickup {
   y: number;
   weapon: number;
   respawnTimer: number;
+  activeTimer?: number; // ticks until an uncollected pickup despawns (pickup despawn ruleset only)
 }
 
 interface ServerMessage {
@@ -84,6 +87,7 @@ interface ServerMessage {
   arenaRight?: number;
   matchOver?: boolean;
   deathLingerTimer?: number;
+  finalBlow?: boolean; // linger started by the match-deciding kill (slow-motion finish)
   lastButtons?: [number, number];
 }
 
@@ -80,7 +80,7 @@ export class PredictionManager {
 
     if (serverTick >= this.predictedTick) {
       // Server is ahead or caught up — no replay needed
-      this.wasmState.import_state(serverState);
+      if (!this.importServerState(serverState)) return;
       if (this.wasmState.tick() !== serverTick) {
         this.recreateFromState(serverState, serverTick);
       }
@@ -96,7 +96,7 @@ export class PredictionManager {
     if (gap > MAX_REPLAY) {
       // Normal during round transitions; only log for debugging
       // console.log(`[Prediction] gap ${gap} ticks, snapping to server tick ${serverTick}`);
-      this.wasmState.import_state(serverState);
+      if (!this.importServerState(serverState)) return;
       if (this.wasmState.tick() !== serverTick) {
         this.recreateFromState(serverState, serverTick);
       }
@@ -108,7 +108,7 @@ export class PredictionManager {
     }
 
     // Rollback to server state and replay unconfirmed inputs
-    this.wasmState.import_state(serverState);
+    if (!this.importServerState(serverState)) return;
 
     if (this.wasmState.tick() !== serverTick) {
       this.recreateFromState(serverState, serverTick);
@@ -135,11 +135,25 @@ export class PredictionManager {
     this._cacheValid = false;
   }
 
+  /**
+   * Import a server snapshot. The WASM side rejects impossible states; keep the current
+   * prediction rather than adopting garbage.
+   */
+  private importServerState(serverState: any): boolean {
+    try {
+      this.wasmState.import_state(serverState, false);
+      return true;
+    } catch (e) {
+      console.error(`[Prediction] rejected server state: ${e}`);
+      return false;
+    }
+  }
+
   private recreateFromState(serverState: any, serverTick: number): void {
     console.warn(`[Prediction] import_state failed (WASM tick=${this.wasmState.tick()}, expected=${serverTick}), recreating`);
     try { this.wasmState.free(); } catch { /* already freed */ }
     this.wasmState = new WasmState(this.seed, this.mapJson);
-    this.wasmState.import_state(serverState);
+    this.importServerState(serverState);
     if (this.wasmState.tick() !== serverTick) {
       console.error(`[Prediction] import_state failed even after recreate (tick=${this.wasmState.tick()}, expected=${serverTick})`);
     }
@@ -164,6 +164,8 @@ export class GameScene extends Phaser.Scene {
   readonly inputManager = new InputManager();
   private playing = false;
   private localPlayerId = 0;
+  /** The seed's side assignment (`WasmState.side_swap`): registered player 1 plays sim index 0. */
+  private sideSwap = false;
   private prediction: PredictionManager | null = null;
   private predictionAccum = 0;
   onLocalInput?: (input: PlayerInput, tick: number) => void;
@@ -670,11 +672,12 @@ export class GameScene extends Phaser.Scene {
     this.warmupState = this.warmupWasm!.export_state();
     // Banish player 2 off-screen and import back so WASM sim knows they're gone
     this.banishWarmupPlayer2(this.warmupState);
-    this.warmupWasm!.import_state(this.warmupState);
+    this.warmupWasm!.import_state_unchecked(this.warmupState);
     this.currState = this.warmupState;
     this.prevState = this.warmupState;
     this.config = this.warmupConfig;
     this.localPlayerId = 0;
+    this.sideSwap = false; // the local player is sim index 0, whatever the seed
     this.playing = false; // not a real match
     this.prediction = null;
     hideAnnounce();
@@ -896,6 +899,15 @@ export class GameScene extends Phaser.Scene {
     showAnnounce(`Round ${round + 1} - ${winnerName} wins!\n${roundWins[0]} - ${roundWins[1]}`);
   }
 
+  /**
+   * Sim player index of a registered player, or the registered player of a sim index: the
+   * mapping is its own inverse. Inputs go to the sim in registered order; its states list
+   * players by sim index.
+   */
+  private sideIndex(index: number): number {
+    return this.sideSwap && (index === 0 || index === 1) ? 1 - index : index;
+  }
+
   private initRound(seed: number, mapIndex: number) {
     const map = MAP_POOL[mapIndex] ?? MAP_POOL[0] ?? ARENA;
     const mapJson = JSON.stringify(map);
@@ -912,6 +924,7 @@ export class GameScene extends Phaser.Scene {
     // Get initial state from a temp WASM instance for display
     const tempWasm = new WasmState(seed, mapJson);
     const initial = tempWasm.export_state();
+    this.sideSwap = tempWasm.side_swap();
     tempWasm.free();
     this.prevState = initial;
     this.currState = initial;
@@ -1097,6 +1110,7 @@ export class GameScene extends Phaser.Scene {
       try { this.replayWasm.free(); } catch { /* already freed */ }
     }
     this.replayWasm = new WasmState(seed, mapJson);
+    this.sideSwap = this.replayWasm.side_swap();
     const initial = this.replayWasm.export_state();
     this.prevState = initial;
     this.currState = initial;
@@ -1165,6 +1179,7 @@ export class GameScene extends Phaser.Scene {
       try { this.replayWasm.free(); } catch { /* already freed */ }
     }
     this.replayWasm = new WasmState(round.seed, mapJson);
+    this.sideSwap = this.replayWasm.side_swap();
     const initial = this.replayWasm.export_state();
     this.prevState = initial;
     this.currState = initial;
@@ -1373,11 +1388,16 @@ export class GameScene extends Phaser.Scene {
           p0.y + PLAYER_HEIGHT / 2,
         ) : NULL_INPUT;
         const prevWarmup = this.warmupState;
-        this.warmupWasm.step(input.buttons, input.aimX, input.aimY, 0, 0, 0);
+        // The sim routes transcript columns by its side swap; put the input where index 0 reads it
+        if (this.warmupWasm.side_swap()) {
+          this.warmupWasm.step(0, 0, 0, input.buttons, input.aimX, input.aimY);
+        } else {
+          this.warmupWasm.step(input.buttons, input.aimX, input.aimY, 0, 0, 0);
+        }
         this.warmupState = this.warmupWasm.export_state();
         this.banishWarmupPlayer2(this.warmupState);
         // Import banished state back so WASM sim has P2 off-screen (prevents bullet absorption)
-        this.warmupWasm.import_state(this.warmupState);
+        this.warmupWasm.import_state_unchecked(this.warmupState);
         this.detectAudioEvents(prevWarmup, this.warmupState);
       }
       if (this.warmupAccum > TICK_DT_MS * 2) this.warmupAccum = 0;
@@ -1405,7 +1425,7 @@ export class GameScene extends Phaser.Scene {
           this.currState = this.replayWasm.export_state();
           this.replayTick++;
         } else if (this.currState!.matchOver) {
-          const w = this.currState!.winner;
+          const w = this.sideIndex(this.currState!.winner);
           if (w === 0) this.replayRoundWins[0]++;
           else if (w === 1) this.replayRoundWins[1]++;
           const wName = (this.playerUsernames[w] || `Player ${w + 1}`).toUpperCase();
@@ -1460,7 +1480,7 @@ export class GameScene extends Phaser.Scene {
         this.predictionAccum -= TICK_DT_MS;
         ticksRun++;
 
-        const player = this.prediction.predictedState.players[this.localPlayerId];
+        const player = this.prediction.predictedState.players[this.sideIndex(this.localPlayerId)];
         if (player) {
           const input = this.inputManager.getPlayer1Input(
             player.x + PLAYER_WIDTH / 2,
@@ -1483,7 +1503,7 @@ export class GameScene extends Phaser.Scene {
         const targetTick = this.lastServerTick + PRED_LEAD;
         let extraTicks = 0;
         while (this.prediction.currentTick < targetTick && extraTicks < 4) {
-          const player = this.prediction.predictedState.players[this.localPlayerId];
+          const player = this.prediction.predictedState.players[this.sideIndex(this.localPlayerId)];
           if (!player) break;
           const input = this.inputManager.getPlayer1Input(
             player.x + PLAYER_WIDTH / 2,
@@ -1526,7 +1546,7 @@ export class GameScene extends Phaser.Scene {
     const displayState = predicted ?? curr;
 
     // Detect rocket explosions — local player rockets from predicted, remote from server
-    const localId = this.localPlayerId;
+    const localId = this.sideIndex(this.localPlayerId);
     const currentRocketIds = new Set<number>();
     const rocketSource = (predicted && !this.replayMode) ? predicted : curr;
     const serverRockets = curr.projectiles;
@@ -1666,7 +1686,7 @@ export class GameScene extends Phaser.Scene {
         playerStates.push(null);
         continue;
       }
-      const isLocal = i === this.localPlayerId && !this.replayMode;
+      const isLocal = this.sideIndex(i) === this.localPlayerId && !this.replayMode;
       const raw = curr.players[i]!;
       let cp: any;
       let drawX: number, drawY: number;
@@ -1790,15 +1810,15 @@ export class GameScene extends Phaser.Scene {
 
       // Update character sprite
       if (sprite) {
-        const slug = CHARACTER_SLUGS[this.characterSlots[i] ?? 0];
+        const slug = CHARACTER_SLUGS[this.characterSlots[this.sideIndex(i)] ?? 0];
         let animKey: string;
         const hasGun = cp.weapon != null && cp.weapon >= 0;
         // Determine crouch button state for edge detection
         // Local player: read inputManager directly (no round-trip delay)
-        const isLocal = (i === this.localPlayerId && !this.replayMode) || (this.warmupMode && i === 0);
+        const isLocal = (this.sideIndex(i) === this.localPlayerId && !this.replayMode) || (this.warmupMode && i === 0);
         const playerBtns = isLocal
           ? this.inputManager.getPlayer1Input(cp.x, cp.y).buttons
-          : this.lastReceivedButtons[i];
+          : this.lastReceivedButtons[this.sideIndex(i)];
         const tauntNow = !!((playerBtns ?? 0) & Button.Taunt);
         const tauntPrev = !!((this.prevFrameButtons[i] ?? 0) & Button.Taunt);
         const tauntEdge = tauntNow && !tauntPrev && cp.grounded;
@@ -1925,10 +1945,10 @@ export class GameScene extends Phaser.Scene {
     for (let i = 0; i < 2; i++) {
       const cp = playerStates[i];
       if (!cp) continue;
-      const isLocal = (i === this.localPlayerId && !this.replayMode) || (this.warmupMode && i === 0);
+      const isLocal = (this.sideIndex(i) === this.localPlayerId && !this.replayMode) || (this.warmupMode && i === 0);
       const btns = isLocal
         ? this.inputManager.getPlayer1Input(cp.x, cp.y).buttons
-        : this.lastReceivedButtons[i];
+        : this.lastReceivedButtons[this.sideIndex(i)];
       this.prevFrameButtons[i] = btns ?? 0;
     }
   }
@@ -1983,7 +2003,7 @@ export class GameScene extends Phaser.Scene {
     // Username above player
     const nameText = this.nameTexts[index];
     if (!nameText) return;
-    const uname = this.playerUsernames[index];
+    const uname = this.playerUsernames[this.sideIndex(index)];
     if (uname) {
       nameText.setText(uname);
       nameText.setPosition(drawX + PLAYER_WIDTH / 2, drawY - 6);
@@ -2001,7 +2021,7 @@ export class GameScene extends Phaser.Scene {
   ) {
     // Local player's bullets from predicted state (instant feedback).
     // Remote player's bullets from server state (matches their rendered position).
-    const localId = this.localPlayerId;
+    const localId = this.sideIndex(this.localPlayerId);
     const projectiles: { proj: any; ownerState: any }[] = [];
 
     if (predicted && !this.replayMode) {
@@ -2147,8 +2167,8 @@ export class GameScene extends Phaser.Scene {
     }
 
     // Local player from predicted state, remote from server state (curr)
-    const localP = (predicted ?? curr).players[this.localPlayerId];
-    const remoteP = curr.players[1 - this.localPlayerId];
+    const localP = (predicted ?? curr).players[this.sideIndex(this.localPlayerId)];
+    const remoteP = curr.players[this.sideIndex(1 - this.localPlayerId)];
 
     // Warmup or single-player
     if (!localP || !remoteP || this.warmupMode) {
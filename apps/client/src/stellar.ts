@@ -187,12 +187,16 @@ export async function startMatch(
   player1: string,
   player2: string,
   seedCommit: Uint8Array,
+  configCommit: Uint8Array,
+  mapHash: Uint8Array,
 ): Promise<void> {
   await callContract("start_match", [
     StellarSdk.nativeToScVal(sessionId, { type: "u32" }),
     StellarSdk.nativeToScVal(player1, { type: "address" }),
     StellarSdk.nativeToScVal(player2, { type: "address" }),
     StellarSdk.nativeToScVal(seedCommit, { type: "bytes" }),
+    StellarSdk.nativeToScVal(configCommit, { type: "bytes" }),
+    StellarSdk.nativeToScVal(mapHash, { type: "bytes" }),
   ]);
 }
 
@@ -201,8 +205,11 @@ export async function settleMatch(
   seal: Uint8Array,
   journal: Uint8Array,
 ): Promise<void> {
+  if (!connectedAddress) throw new Error("Wallet not connected");
+  // The contract only takes a settlement from one of the match's players (or the admin)
   await callContract("settle_match", [
     StellarSdk.nativeToScVal(sessionId, { type: "u32" }),
+    StellarSdk.nativeToScVal(connectedAddress, { type: "address" }),
     StellarSdk.nativeToScVal(seal, { type: "bytes" }),
     StellarSdk.nativeToScVal(journal, { type: "bytes" }),
   ]);
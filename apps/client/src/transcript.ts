@@ -10,10 +10,13 @@ export type TickInputs = [PlayerInput, PlayerInput];
 export class TranscriptRecorder {
   private inputs: TickInputs[] = [];
   private _seed: number = 0;
+  private _local = false;
 
-  start(seed: number) {
+  /** `local`: both players are on this device, so both input streams are authoritative. */
+  start(seed: number, local = false) {
     this.inputs = [];
     this._seed = seed;
+    this._local = local;
   }
 
   record(p0: PlayerInput, p1: PlayerInput) {
@@ -23,6 +26,24 @@ export class TranscriptRecorder {
     ]);
   }
 
+  /**
+   * Append ticks in the raw 6-byte layout (WasmState.local_transcript / merge_input_streams):
+   * p0 buttons, aim_x, aim_y, then p1's, aims as signed bytes.
+   */
+  recordTicks(bytes: Uint8Array) {
+    const aim = (b: number) => (b << 24) >> 24;
+    for (let off = 0; off + 6 <= bytes.length; off += 6) {
+      this.record(
+        { buttons: bytes[off], aimX: aim(bytes[off + 1]), aimY: aim(bytes[off + 2]) },
+        { buttons: bytes[off + 3], aimX: aim(bytes[off + 4]), aimY: aim(bytes[off + 5]) },
+      );
+    }
+  }
+
+  get local(): boolean {
+    return this._local;
+  }
+
   get seed(): number {
     return this._seed;
   }
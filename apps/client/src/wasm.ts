@@ -1,5 +1,5 @@
 // Client WASM loader — initializes the chickenz-wasm module asynchronously at app startup.
-import init, { WasmState } from "../../../services/prover/wasm/pkg/chickenz_wasm.js";
+import init, { WasmState, extract_ghost } from "../../../services/prover/wasm/pkg/chickenz_wasm.js";
 
 let initialized = false;
 
@@ -9,4 +9,4 @@ export async function initChickenzWasm() {
   initialized = true;
 }
 
-export { WasmState };
+export { WasmState, extract_ghost };
@@ -2,10 +2,12 @@
 
 use soroban_sdk::{
     contract, contractimpl, contracttype, contracterror, contractclient,
-    Address, Bytes, BytesN, Env,
+    symbol_short, Address, Bytes, BytesN, Env, Symbol, TryFromVal, Val, Vec,
     crypto::Hash,
 };
 
+use chickenz_core_mini::{self as mini, layout};
+
 // ── Cross-contract clients ───────────────────────────────────────────────────
 
 #[contractclient(name = "VerifierClient")]
@@ -32,6 +34,31 @@ pub trait GameHubInterface {
 // ~30 days of ledgers (5s per ledger)
 const MATCH_TTL_LEDGERS: u32 = 518_400;
 
+// ~1 day: warn_if_expiring() emits an "expiring" event once a match has less than this left
+const TTL_WARNING_LEDGERS: u32 = 17_280;
+
+// ~120 days: tombstones outlive the match entry so expiry can be told apart from "never existed"
+const TOMBSTONE_TTL_LEDGERS: u32 = 4 * MATCH_TTL_LEDGERS;
+
+// ~1 hour: how long both players of a commit-reveal match have to reveal their seed entropy
+const REVEAL_TIMEOUT_LEDGERS: u32 = 720;
+
+// ~1 day: default for how long after start_match a player has to wait before cancelling a match
+// that never settled (the admin can cancel any time)
+const SETTLEMENT_DEADLINE_LEDGERS: u32 = 17_280;
+
+// Storage layout version. Bump when a stored type changes shape and add a step to migrate().
+//   1: original MatchData (player1, player2, seed_commit, settled)
+//   2: MatchData gains journal_digest + settled_ledger
+//   3: MatchData gains created_ledger + expires_ledger
+//   4: MatchData gains result (scores + env kills decoded from the settled journal)
+//   5: MatchData gains final_stats (final tick, health and lives from a v5 journal)
+//   6: MatchData gains transcript_commit (the transcript both players agreed on)
+//   7: MatchData gains cancelled + settlement_deadline_ledgers
+//   8: MatchData gains team_result (winning team and team scores from a v7 journal)
+//   9: MatchData gains config_commit + map_hash (the rules and map the match was started with)
+const SCHEMA_VERSION: u32 = 9;
+
 #[contracttype]
 #[derive(Clone)]
 pub enum DataKey {
@@ -40,6 +67,19 @@ pub enum DataKey {
     Verifier,
     ImageId,
     Match(u32),
+    SchemaVersion,
+    /// Persistent marker that a session was started; survives the temporary Match entry.
+    Tombstone(u32),
+    /// Journal schema versions settle_match accepts (admin-managed; unset = every known one).
+    JournalVersions,
+    /// Seed commitments and reveals of a match started with start_match_committed.
+    SeedReveal(u32),
+    /// Image ID of the guest before the pickup stats commitment was committed (admin-managed).
+    /// While set, journals without it (version < 9) are verified against it instead of ImageId.
+    LegacyImageId,
+    /// Ledgers after start a player may cancel an unsettled match (admin-managed; unset =
+    /// SETTLEMENT_DEADLINE_LEDGERS). Snapshotted into each match when it starts.
+    SettlementDeadline,
 }
 
 #[contracttype]
@@ -49,6 +89,113 @@ pub struct MatchData {
     pub player2: Address,
     pub seed_commit: BytesN<32>,
     pub settled: bool,
+    /// SHA-256 of the journal accepted in settle_match (None until settled).
+    pub journal_digest: Option<BytesN<32>>,
+    /// Ledger sequence at settlement (0 until settled).
+    pub settled_ledger: u32,
+    /// Ledger sequence at start_match (0 for matches migrated from schema < 3).
+    pub created_ledger: u32,
+    /// Last ledger the temporary entry is live, as of its most recent TTL bump.
+    pub expires_ledger: u32,
+    /// Outcome committed by the settled journal (None until settled, and for matches settled
+    /// before schema 4).
+    pub result: Option<MatchResult>,
+    /// Final tick, health and lives committed by the settled journal (None until settled, and
+    /// for journals before version 5 or matches settled before schema 5).
+    pub final_stats: Option<FinalStats>,
+    /// Transcript hash both players agreed on with commit_transcript; once set, only a journal
+    /// committing this transcript_hash settles the match.
+    pub transcript_commit: Option<BytesN<32>>,
+    /// Set by cancel_match; a cancelled match can no longer settle.
+    pub cancelled: bool,
+    /// Ledgers after created_ledger from which either player may cancel the match.
+    pub settlement_deadline_ledgers: u32,
+    /// Team outcome committed by the settled journal (None until settled, and for journals
+    /// before version 7 or matches settled before schema 8).
+    pub team_result: Option<TeamResult>,
+    /// `FpMatchConfig::commitment` of the rules the match was started with; only a journal
+    /// committing it settles the match (None for matches started before schema 9, unchecked).
+    pub config_commit: Option<BytesN<32>>,
+    /// `fp::map_hash` of the map the match was started on; journals before version 3 count as
+    /// played on the arena (None for matches started before schema 9, unchecked).
+    pub map_hash: Option<BytesN<32>>,
+}
+
+/// Match outcome decoded from the settlement journal, in registered player order.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MatchResult {
+    pub player1_won: bool,
+    /// Kills credited to each player.
+    pub player1_score: u32,
+    pub player2_score: u32,
+    /// Deaths of each player that nobody was credited for (the sudden-death zone).
+    pub player1_env_kills: u32,
+    pub player2_env_kills: u32,
+}
+
+/// How the match ended, from the settlement journal (version 5 on), in registered player order.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FinalStats {
+    pub final_tick: u32,
+    pub player1_health: i32,
+    pub player2_health: i32,
+    pub player1_lives: i32,
+    pub player2_lives: i32,
+}
+
+/// Team outcome from the settlement journal (version 7 on), teams numbered as in the journal.
+/// Until the sim plays team matches, player1 is team 0 and player2 team 1, so this mirrors
+/// MatchResult.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TeamResult {
+    /// Winning team (0 or 1), or -1 when no team won.
+    pub winner_team: i32,
+    pub team0_score: u32,
+    pub team1_score: u32,
+    pub player1_team: u32,
+    pub player2_team: u32,
+}
+
+/// Commit-reveal seeding of a match: each player's commitment to 32 bytes of entropy, and the
+/// entropy once revealed. The seed is `derive_seed` of both reveals; until both are in, the
+/// match's seed_commit is all zeros and it can't settle.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SeedReveal {
+    pub commit1: BytesN<32>,
+    pub commit2: BytesN<32>,
+    pub reveal1: Option<BytesN<32>>,
+    pub reveal2: Option<BytesN<32>>,
+    /// Last ledger a reveal is accepted; after it, claim_reveal_timeout forfeits the match to
+    /// the player who revealed.
+    pub deadline_ledger: u32,
+}
+
+/// Proof receipt details for a settled match.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SettlementInfo {
+    /// SHA-256 of the settling journal; None for a forfeit, which had no journal.
+    pub journal_digest: Option<BytesN<32>>,
+    pub settled_ledger: u32,
+    /// Settled by claim_reveal_timeout: `result` has the forfeit's winner and no scores.
+    pub forfeit: bool,
+    pub result: Option<MatchResult>,
+    pub final_stats: Option<FinalStats>,
+    pub team_result: Option<TeamResult>,
+}
+
+/// Lifetime of a match entry.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TtlInfo {
+    pub created_ledger: u32,
+    /// TTL applied on every bump (MATCH_TTL_LEDGERS).
+    pub ttl_ledgers: u32,
+    pub expires_ledger: u32,
 }
 
 #[contracterror]
@@ -64,32 +211,637 @@ pub enum Error {
     InvalidJournal = 7,
     SeedMismatch = 8,
     InvalidWinner = 9,
+    MatchNotSettled = 10,
+    AlreadyMigrated = 11,
+    MigrationRequired = 12,
+    SessionMismatch = 13,
+    MatchExpired = 14,
+    /// Journal schema version unknown to this contract, or not in the accepted set.
+    JournalVersionRejected = 15,
+    /// The match was started without seed commitments (start_match, not start_match_committed).
+    NotCommitReveal = 16,
+    /// A commit-reveal match is still waiting for a reveal.
+    SeedNotRevealed = 17,
+    AlreadyRevealed = 18,
+    /// The preimage does not hash to the player's commitment.
+    RevealMismatch = 19,
+    /// The reveal deadline has passed.
+    RevealClosed = 20,
+    /// claim_reveal_timeout before the reveal deadline.
+    RevealStillOpen = 21,
+    /// The journal's transcript_hash is not the one the players committed.
+    TranscriptMismatch = 22,
+    /// The match was cancelled with cancel_match.
+    MatchCancelled = 23,
+    /// A player called cancel_match before the match's settlement deadline.
+    SettlementStillOpen = 24,
+    /// The journal's config_commit is not the ruleset the match was started with.
+    ConfigMismatch = 25,
+    /// The journal's map hash is not the map the match was started on.
+    MapMismatch = 26,
+}
+
+/// Schema v1 MatchData, kept only so migrate() can read pre-v2 entries.
+#[contracttype(export = false)]
+#[derive(Clone, Debug)]
+pub struct MatchDataV1 {
+    pub player1: Address,
+    pub player2: Address,
+    pub seed_commit: BytesN<32>,
+    pub settled: bool,
+}
+
+/// Schema v2 MatchData, kept only so migrate() can read pre-v3 entries.
+#[contracttype(export = false)]
+#[derive(Clone, Debug)]
+pub struct MatchDataV2 {
+    pub player1: Address,
+    pub player2: Address,
+    pub seed_commit: BytesN<32>,
+    pub settled: bool,
+    pub journal_digest: Option<BytesN<32>>,
+    pub settled_ledger: u32,
+}
+
+/// Schema v3 MatchData, kept only so migrate() can read pre-v4 entries.
+#[contracttype(export = false)]
+#[derive(Clone, Debug)]
+pub struct MatchDataV3 {
+    pub player1: Address,
+    pub player2: Address,
+    pub seed_commit: BytesN<32>,
+    pub settled: bool,
+    pub journal_digest: Option<BytesN<32>>,
+    pub settled_ledger: u32,
+    pub created_ledger: u32,
+    pub expires_ledger: u32,
+}
+
+/// Schema v4 MatchData, kept only so migrate() can read pre-v5 entries.
+#[contracttype(export = false)]
+#[derive(Clone, Debug)]
+pub struct MatchDataV4 {
+    pub player1: Address,
+    pub player2: Address,
+    pub seed_commit: BytesN<32>,
+    pub settled: bool,
+    pub journal_digest: Option<BytesN<32>>,
+    pub settled_ledger: u32,
+    pub created_ledger: u32,
+    pub expires_ledger: u32,
+    pub result: Option<MatchResult>,
+}
+
+/// Schema v5 MatchData, kept only so migrate() can read pre-v6 entries.
+#[contracttype(export = false)]
+#[derive(Clone, Debug)]
+pub struct MatchDataV5 {
+    pub player1: Address,
+    pub player2: Address,
+    pub seed_commit: BytesN<32>,
+    pub settled: bool,
+    pub journal_digest: Option<BytesN<32>>,
+    pub settled_ledger: u32,
+    pub created_ledger: u32,
+    pub expires_ledger: u32,
+    pub result: Option<MatchResult>,
+    pub final_stats: Option<FinalStats>,
+}
+
+/// Schema v6 MatchData, kept only so migrate() can read pre-v7 entries.
+#[contracttype(export = false)]
+#[derive(Clone, Debug)]
+pub struct MatchDataV6 {
+    pub player1: Address,
+    pub player2: Address,
+    pub seed_commit: BytesN<32>,
+    pub settled: bool,
+    pub journal_digest: Option<BytesN<32>>,
+    pub settled_ledger: u32,
+    pub created_ledger: u32,
+    pub expires_ledger: u32,
+    pub result: Option<MatchResult>,
+    pub final_stats: Option<FinalStats>,
+    pub transcript_commit: Option<BytesN<32>>,
+}
+
+/// Schema v7 MatchData, kept only so migrate() can read pre-v8 entries.
+#[contracttype(export = false)]
+#[derive(Clone, Debug)]
+pub struct MatchDataV7 {
+    pub player1: Address,
+    pub player2: Address,
+    pub seed_commit: BytesN<32>,
+    pub settled: bool,
+    pub journal_digest: Option<BytesN<32>>,
+    pub settled_ledger: u32,
+    pub created_ledger: u32,
+    pub expires_ledger: u32,
+    pub result: Option<MatchResult>,
+    pub final_stats: Option<FinalStats>,
+    pub transcript_commit: Option<BytesN<32>>,
+    pub cancelled: bool,
+    pub settlement_deadline_ledgers: u32,
+}
+
+/// Schema v8 MatchData, kept only so migrate() can read pre-v9 entries.
+#[contracttype(export = false)]
+#[derive(Clone, Debug)]
+pub struct MatchDataV8 {
+    pub player1: Address,
+    pub player2: Address,
+    pub seed_commit: BytesN<32>,
+    pub settled: bool,
+    pub journal_digest: Option<BytesN<32>>,
+    pub settled_ledger: u32,
+    pub created_ledger: u32,
+    pub expires_ledger: u32,
+    pub result: Option<MatchResult>,
+    pub final_stats: Option<FinalStats>,
+    pub transcript_commit: Option<BytesN<32>>,
+    pub cancelled: bool,
+    pub settlement_deadline_ledgers: u32,
+    pub team_result: Option<TeamResult>,
 }
 
 // ── Journal layout ───────────────────────────────────────────────────────────
-// 76 bytes = 19 u32 words (LE):
-//   [0..4)   winner (i32 as u32)
-//   [4..8)   score_p0 (u32)
-//   [8..12)  score_p1 (u32)
-//   [12..44) transcript_hash (32 bytes)
-//   [44..76) seed_commit (32 bytes)
+// A 120-byte body = 30 u32 words (LE), defined in chickenz_journal_layout and decoded by
+// chickenz_core_mini (both shared with the prover), behind a version word from schema version 1
+// on (124 bytes), followed by the 32-byte map hash from version 3 (156 bytes) and the final
+// tick, health and lives from version 5 (176 bytes), then the team result from version 7 (204
+// bytes), and optionally by the 32-byte event-chain head (bound by the proof, not read here).
+// Unversioned journals are version 0. session_id binds the proof to one match; config_commit
+// is the ruleset/orientation the match was replayed under; env kills count the zone deaths the
+// scores leave out.
+
+/// Layout of a journal version this contract can settle: a known one with two-player
+/// scores. The wide layouts carry matches of more than two players, which have no escrow here.
+fn settleable_layout(version: u32) -> Option<layout::Layout> {
+    layout::layout(version).filter(|l| !l.is_wide())
+}
+
+/// Journal versions settle_match accepts: the admin's set, or every settleable version.
+fn accepted_journal_versions(env: &Env) -> Vec<u32> {
+    env.storage().instance().get(&DataKey::JournalVersions).unwrap_or_else(|| {
+        let mut versions = Vec::new(env);
+        for v in layout::KNOWN_VERSIONS {
+            if settleable_layout(v).is_some() {
+                versions.push_back(v);
+            }
+        }
+        versions
+    })
+}
+
+/// Decode a journal with the prover's decode: the version must be settleable and accepted, and
+/// the length one that version allows (with or without the event-chain trailer).
+fn decode_journal(env: &Env, journal: &Bytes) -> Result<mini::Journal, Error> {
+    let len = journal.len() as usize;
+    if len < 4 {
+        return Err(Error::InvalidJournal);
+    }
+    // A journal longer than any layout still gets its version checked first
+    let mut buf = [0u8; mini::MAX_JOURNAL_SIZE];
+    let b = &mut buf[..len.min(mini::MAX_JOURNAL_SIZE)];
+    journal.slice(0..b.len() as u32).copy_into_slice(b);
+
+    let version = mini::journal_version(b).ok_or(Error::InvalidJournal)?;
+    let l = settleable_layout(version).ok_or(Error::JournalVersionRejected)?;
+    if !accepted_journal_versions(env).contains(version) {
+        return Err(Error::JournalVersionRejected);
+    }
+    if !l.is_valid_size(len) {
+        return Err(Error::InvalidJournal);
+    }
+    mini::Journal::decode(b).ok_or(Error::InvalidJournal)
+}
+
+/// Kills credited to each player by a journal, in registered player order.
+fn decode_scores(j: &mini::Journal) -> (u32, u32) {
+    (j.scores[0], j.scores[1])
+}
+
+/// The stored result of a journal whose winner has already been checked settleable.
+fn decode_result(j: &mini::Journal) -> MatchResult {
+    let (player1_score, player2_score) = decode_scores(j);
+    MatchResult {
+        player1_won: j.winner == 0,
+        player1_score,
+        player2_score,
+        player1_env_kills: j.env_kills[0],
+        player2_env_kills: j.env_kills[1],
+    }
+}
+
+/// The final stats of a journal, if its version carries them.
+fn decode_final_stats(j: &mini::Journal) -> Option<FinalStats> {
+    j.stats.map(|s| FinalStats {
+        final_tick: s.tick,
+        player1_health: s.health[0],
+        player2_health: s.health[1],
+        player1_lives: s.lives[0],
+        player2_lives: s.lives[1],
+    })
+}
+
+/// The team result of a journal, if its version carries it. Only the registered players'
+/// team slots are kept: the contract escrows two-player matches alone.
+fn decode_team_result(j: &mini::Journal) -> Option<TeamResult> {
+    j.teams.map(|t| TeamResult {
+        winner_team: t.winner_team,
+        team0_score: t.team_scores[0],
+        team1_score: t.team_scores[1],
+        player1_team: t.player_teams[0],
+        player2_team: t.player_teams[1],
+    })
+}
+
+/// Image ID a journal's proof must verify against: the legacy one for a journal without the
+/// pickup stats commitment while the transition window is open, ImageId otherwise.
+fn image_id_for(env: &Env, j: &mini::Journal) -> Result<BytesN<32>, Error> {
+    let legacy = match j.pickup_stats_commit {
+        None => env.storage().instance().get(&DataKey::LegacyImageId),
+        Some(_) => None,
+    };
+    match legacy {
+        Some(image_id) => Ok(image_id),
+        None => env.storage().instance().get(&DataKey::ImageId).ok_or(Error::NotInitialized),
+    }
+}
+
+// ── Admin / schema helpers ───────────────────────────────────────────────────
+
+fn require_admin(env: &Env) -> Result<Address, Error> {
+    let admin: Address = env
+        .storage()
+        .instance()
+        .get(&DataKey::Admin)
+        .ok_or(Error::NotInitialized)?;
+    admin.require_auth();
+    Ok(admin)
+}
+
+/// Stored schema version. Deployments that predate versioning have no key and are v1.
+fn stored_schema_version(env: &Env) -> u32 {
+    env.storage()
+        .instance()
+        .get(&DataKey::SchemaVersion)
+        .unwrap_or(1)
+}
 
-const JOURNAL_SIZE: usize = 76;
+/// Entrypoints that touch MatchData refuse to run until migrate() has caught storage up
+/// (migrate_batch() alone doesn't).
+fn require_current_schema(env: &Env) -> Result<(), Error> {
+    if stored_schema_version(env) != SCHEMA_VERSION {
+        return Err(Error::MigrationRequired);
+    }
+    Ok(())
+}
+
+// ── Migration helpers ────────────────────────────────────────────────────────
+// Each reads a match entry stored under schema `from` and brings it up to one version's
+// layout; migrate_matches() finishes the last step to MatchData.
+
+fn read_match_v2(env: &Env, key: &DataKey, from: u32) -> Option<MatchDataV2> {
+    if from >= 2 {
+        return env.storage().temporary().get(key);
+    }
+    // v1 → v2: MatchData gains journal_digest + settled_ledger.
+    // The digest of matches settled under v1 was never stored.
+    env.storage().temporary().get::<_, MatchDataV1>(key).map(|old| MatchDataV2 {
+        player1: old.player1,
+        player2: old.player2,
+        seed_commit: old.seed_commit,
+        settled: old.settled,
+        journal_digest: None,
+        settled_ledger: 0,
+    })
+}
+
+fn read_match_v3(env: &Env, key: &DataKey, from: u32) -> Option<MatchDataV3> {
+    if from >= 3 {
+        return env.storage().temporary().get(key);
+    }
+    // v2 → v3: MatchData gains created_ledger + expires_ledger. The creation ledger of older
+    // matches is unknown; their TTL restarts here, and they get a tombstone.
+    read_match_v2(env, key, from).map(|old| MatchDataV3 {
+        player1: old.player1,
+        player2: old.player2,
+        seed_commit: old.seed_commit,
+        settled: old.settled,
+        journal_digest: old.journal_digest,
+        settled_ledger: old.settled_ledger,
+        created_ledger: 0,
+        expires_ledger: 0,
+    })
+}
+
+fn read_match_v4(env: &Env, key: &DataKey, from: u32) -> Option<MatchDataV4> {
+    if from >= 4 {
+        return env.storage().temporary().get(key);
+    }
+    // v3 → v4: MatchData gains result. Journals settled before v4 weren't kept, so their
+    // result stays unknown; the digest still identifies the proof.
+    read_match_v3(env, key, from).map(|old| MatchDataV4 {
+        player1: old.player1,
+        player2: old.player2,
+        seed_commit: old.seed_commit,
+        settled: old.settled,
+        journal_digest: old.journal_digest,
+        settled_ledger: old.settled_ledger,
+        created_ledger: old.created_ledger,
+        expires_ledger: old.expires_ledger,
+        result: None,
+    })
+}
+
+fn read_match_v5(env: &Env, key: &DataKey, from: u32) -> Option<MatchDataV5> {
+    if from >= 5 {
+        return env.storage().temporary().get(key);
+    }
+    // v4 → v5: MatchData gains final_stats, which no journal settled before v5 carried.
+    read_match_v4(env, key, from).map(|old| MatchDataV5 {
+        player1: old.player1,
+        player2: old.player2,
+        seed_commit: old.seed_commit,
+        settled: old.settled,
+        journal_digest: old.journal_digest,
+        settled_ledger: old.settled_ledger,
+        created_ledger: old.created_ledger,
+        expires_ledger: old.expires_ledger,
+        result: old.result,
+        final_stats: None,
+    })
+}
+
+fn read_match_v6(env: &Env, key: &DataKey, from: u32) -> Option<MatchDataV6> {
+    if from >= 6 {
+        return env.storage().temporary().get(key);
+    }
+    // v5 → v6: MatchData gains transcript_commit. Nothing was committed before v6.
+    read_match_v5(env, key, from).map(|old| MatchDataV6 {
+        player1: old.player1,
+        player2: old.player2,
+        seed_commit: old.seed_commit,
+        settled: old.settled,
+        journal_digest: old.journal_digest,
+        settled_ledger: old.settled_ledger,
+        created_ledger: old.created_ledger,
+        expires_ledger: old.expires_ledger,
+        result: old.result,
+        final_stats: old.final_stats,
+        transcript_commit: None,
+    })
+}
 
-fn decode_winner(journal: &Bytes) -> i32 {
-    let b0 = journal.get(0).unwrap() as u32;
-    let b1 = journal.get(1).unwrap() as u32;
-    let b2 = journal.get(2).unwrap() as u32;
-    let b3 = journal.get(3).unwrap() as u32;
-    (b0 | (b1 << 8) | (b2 << 16) | (b3 << 24)) as i32
+fn read_match_v7(env: &Env, key: &DataKey, from: u32) -> Option<MatchDataV7> {
+    if from >= 7 {
+        return env.storage().temporary().get(key);
+    }
+    // v6 → v7: MatchData gains cancelled + settlement_deadline_ledgers. In-flight matches get
+    // the current deadline, counted from their creation ledger.
+    read_match_v6(env, key, from).map(|old| MatchDataV7 {
+        player1: old.player1,
+        player2: old.player2,
+        seed_commit: old.seed_commit,
+        settled: old.settled,
+        journal_digest: old.journal_digest,
+        settled_ledger: old.settled_ledger,
+        created_ledger: old.created_ledger,
+        expires_ledger: old.expires_ledger,
+        result: old.result,
+        final_stats: old.final_stats,
+        transcript_commit: old.transcript_commit,
+        cancelled: false,
+        settlement_deadline_ledgers: settlement_deadline(env),
+    })
+}
+
+fn read_match_v8(env: &Env, key: &DataKey, from: u32) -> Option<MatchDataV8> {
+    if from >= 8 {
+        return env.storage().temporary().get(key);
+    }
+    // v7 → v8: MatchData gains team_result, which no journal settled before v8 carried.
+    read_match_v7(env, key, from).map(|old| MatchDataV8 {
+        player1: old.player1,
+        player2: old.player2,
+        seed_commit: old.seed_commit,
+        settled: old.settled,
+        journal_digest: old.journal_digest,
+        settled_ledger: old.settled_ledger,
+        created_ledger: old.created_ledger,
+        expires_ledger: old.expires_ledger,
+        result: old.result,
+        final_stats: old.final_stats,
+        transcript_commit: old.transcript_commit,
+        cancelled: old.cancelled,
+        settlement_deadline_ledgers: old.settlement_deadline_ledgers,
+        team_result: None,
+    })
+}
+
+/// Whether a match entry already has the MatchData layout: an earlier migrate_batch()
+/// rewrote it while the stored schema version still says `from`.
+fn is_migrated(env: &Env, key: &DataKey) -> bool {
+    env.storage()
+        .temporary()
+        .get::<_, Val>(key)
+        .is_some_and(|v| MatchData::try_from_val(env, &v).is_ok())
+}
+
+/// Rewrite the listed match entries from schema `from` to MatchData, skipping unknown or
+/// expired ids and ones already rewritten. Returns how many were.
+fn migrate_matches(env: &Env, from: u32, session_ids: &Vec<u32>) -> u32 {
+    let mut migrated = 0u32;
+    for session_id in session_ids.iter() {
+        let key = DataKey::Match(session_id);
+        if is_migrated(env, &key) {
+            continue;
+        }
+        let Some(old) = read_match_v8(env, &key, from) else {
+            continue;
+        };
+        // v8 → v9: MatchData gains config_commit + map_hash. Matches started before v9
+        // recorded neither, so their journals stay unchecked.
+        let mut match_data = MatchData {
+            player1: old.player1,
+            player2: old.player2,
+            seed_commit: old.seed_commit,
+            settled: old.settled,
+            journal_digest: old.journal_digest,
+            settled_ledger: old.settled_ledger,
+            created_ledger: old.created_ledger,
+            expires_ledger: old.expires_ledger,
+            result: old.result,
+            final_stats: old.final_stats,
+            transcript_commit: old.transcript_commit,
+            cancelled: old.cancelled,
+            settlement_deadline_ledgers: old.settlement_deadline_ledgers,
+            team_result: old.team_result,
+            config_commit: None,
+            map_hash: None,
+        };
+        store_match(env, session_id, &mut match_data);
+        store_tombstone(env, session_id);
+        migrated += 1;
+    }
+    migrated
 }
 
-fn extract_seed_commit(env: &Env, journal: &Bytes) -> BytesN<32> {
-    let mut buf = [0u8; 32];
-    for i in 0..32 {
-        buf[i] = journal.get(44 + i as u32).unwrap();
+// ── Match storage helpers ────────────────────────────────────────────────────
+
+/// `caller` is one of the match's players or the admin (auth is the caller's to require).
+fn require_player_or_admin(env: &Env, m: &MatchData, caller: &Address) -> Result<(), Error> {
+    let admin: Address = env
+        .storage()
+        .instance()
+        .get(&DataKey::Admin)
+        .ok_or(Error::NotInitialized)?;
+    if *caller != m.player1 && *caller != m.player2 && *caller != admin {
+        return Err(Error::Unauthorized);
     }
-    BytesN::from_array(env, &buf)
+    Ok(())
+}
+
+/// Settlement deadline new matches get: the admin's setting, or SETTLEMENT_DEADLINE_LEDGERS.
+fn settlement_deadline(env: &Env) -> u32 {
+    env.storage()
+        .instance()
+        .get(&DataKey::SettlementDeadline)
+        .unwrap_or(SETTLEMENT_DEADLINE_LEDGERS)
+}
+
+/// A match can still change outcome: neither settled nor cancelled.
+fn require_open(m: &MatchData) -> Result<(), Error> {
+    if m.settled {
+        return Err(Error::MatchAlreadySettled);
+    }
+    if m.cancelled {
+        return Err(Error::MatchCancelled);
+    }
+    Ok(())
+}
+
+/// Load a match, telling an expired entry (tombstone left behind) apart from an unknown id.
+fn load_match(env: &Env, session_id: u32) -> Result<MatchData, Error> {
+    env.storage()
+        .temporary()
+        .get(&DataKey::Match(session_id))
+        .ok_or_else(|| {
+            if env.storage().persistent().has(&DataKey::Tombstone(session_id)) {
+                Error::MatchExpired
+            } else {
+                Error::MatchNotFound
+            }
+        })
+}
+
+/// Write a match and restart its TTL, recording the new expiry ledger in the entry itself.
+fn store_match(env: &Env, session_id: u32, match_data: &mut MatchData) {
+    let key = DataKey::Match(session_id);
+    match_data.expires_ledger = env.ledger().sequence() + MATCH_TTL_LEDGERS;
+    env.storage().temporary().set(&key, match_data);
+    env.storage()
+        .temporary()
+        .extend_ttl(&key, MATCH_TTL_LEDGERS, MATCH_TTL_LEDGERS);
+}
+
+fn ttl_info(match_data: &MatchData) -> TtlInfo {
+    TtlInfo {
+        created_ledger: match_data.created_ledger,
+        ttl_ledgers: MATCH_TTL_LEDGERS,
+        expires_ledger: match_data.expires_ledger,
+    }
+}
+
+fn load_seed_reveal(env: &Env, session_id: u32) -> Option<SeedReveal> {
+    env.storage().temporary().get(&DataKey::SeedReveal(session_id))
+}
+
+/// Write a match's seed reveal entry, with the same TTL as the match entry.
+fn store_seed_reveal(env: &Env, session_id: u32, reveal: &SeedReveal) {
+    let key = DataKey::SeedReveal(session_id);
+    env.storage().temporary().set(&key, reveal);
+    env.storage()
+        .temporary()
+        .extend_ttl(&key, MATCH_TTL_LEDGERS, MATCH_TTL_LEDGERS);
+}
+
+fn sha256_array(env: &Env, bytes: &[u8]) -> [u8; 32] {
+    env.crypto().sha256(&Bytes::from_slice(env, bytes)).to_array()
+}
+
+fn store_tombstone(env: &Env, session_id: u32) {
+    let key = DataKey::Tombstone(session_id);
+    env.storage().persistent().set(&key, &true);
+    env.storage()
+        .persistent()
+        .extend_ttl(&key, TOMBSTONE_TTL_LEDGERS, TOMBSTONE_TTL_LEDGERS);
+}
+
+/// Register a match and call Game Hub start_game(); shared by both ways of starting one.
+/// Emits `("match_started", session_id)` with `(player1, player2, seed_commit)`.
+fn begin_match(
+    env: &Env,
+    session_id: u32,
+    player1: Address,
+    player2: Address,
+    seed_commit: BytesN<32>,
+    config_commit: BytesN<32>,
+    map_hash: BytesN<32>,
+) -> Result<(), Error> {
+    let key = DataKey::Match(session_id);
+    if env.storage().temporary().has(&key) {
+        return Err(Error::MatchAlreadyExists);
+    }
+
+    // Call Game Hub start_game first
+    let game_hub_addr: Address = env
+        .storage()
+        .instance()
+        .get(&DataKey::GameHub)
+        .ok_or(Error::NotInitialized)?;
+    let game_hub = GameHubClient::new(env, &game_hub_addr);
+    game_hub.start_game(
+        &env.current_contract_address(),
+        &session_id,
+        &player1,
+        &player2,
+        &3i128, // initial lives
+        &3i128,
+    );
+
+    env.events().publish(
+        (Symbol::new(env, "match_started"), session_id),
+        (player1.clone(), player2.clone(), seed_commit.clone()),
+    );
+
+    // Store match data after Game Hub succeeds
+    let mut match_data = MatchData {
+        player1,
+        player2,
+        seed_commit,
+        settled: false,
+        journal_digest: None,
+        settled_ledger: 0,
+        created_ledger: env.ledger().sequence(),
+        expires_ledger: 0,
+        result: None,
+        final_stats: None,
+        transcript_commit: None,
+        cancelled: false,
+        settlement_deadline_ledgers: settlement_deadline(env),
+        team_result: None,
+        config_commit: Some(config_commit),
+        map_hash: Some(map_hash),
+    };
+    store_match(env, session_id, &mut match_data);
+    store_tombstone(env, session_id);
+    env.storage()
+        .instance()
+        .extend_ttl(MATCH_TTL_LEDGERS, MATCH_TTL_LEDGERS);
+
+    Ok(())
 }
 
 // ── Contract ─────────────────────────────────────────────────────────────────
@@ -114,101 +866,300 @@ impl ChickenzContract {
         env.storage().instance().set(&DataKey::GameHub, &game_hub);
         env.storage().instance().set(&DataKey::Verifier, &verifier);
         env.storage().instance().set(&DataKey::ImageId, &image_id);
+        env.storage().instance().set(&DataKey::SchemaVersion, &SCHEMA_VERSION);
+        Ok(())
+    }
+
+    /// Admin-only: replace the contract code. Instance config and match entries survive;
+    /// call migrate() (after any migrate_batch() calls) if the new code bumped SCHEMA_VERSION.
+    pub fn upgrade(env: Env, new_wasm_hash: BytesN<32>) -> Result<(), Error> {
+        require_admin(&env)?;
+        env.deployer().update_current_contract_wasm(new_wasm_hash);
         Ok(())
     }
 
+    /// Admin-only: bring stored data up to SCHEMA_VERSION. Runs once per version.
+    ///
+    /// Temporary storage can't be enumerated, so the caller lists the in-flight
+    /// `session_ids` to rewrite; unknown or expired ids are skipped. Returns the number of
+    /// match entries migrated. Too many for one transaction go through migrate_batch() first,
+    /// with the last batch here.
+    pub fn migrate(env: Env, session_ids: Vec<u32>) -> Result<u32, Error> {
+        require_admin(&env)?;
+        let from = stored_schema_version(&env);
+        if from >= SCHEMA_VERSION {
+            return Err(Error::AlreadyMigrated);
+        }
+
+        let migrated = migrate_matches(&env, from, &session_ids);
+        env.storage().instance().set(&DataKey::SchemaVersion, &SCHEMA_VERSION);
+        env.storage()
+            .instance()
+            .extend_ttl(MATCH_TTL_LEDGERS, MATCH_TTL_LEDGERS);
+        Ok(migrated)
+    }
+
+    /// Admin-only: rewrite some match entries like migrate() without bumping the schema
+    /// version, so a migration too big for one transaction can be split. Entries an earlier
+    /// batch rewrote are skipped; entrypoints stay blocked with MigrationRequired until
+    /// migrate() takes the last batch. Returns the number of match entries migrated.
+    pub fn migrate_batch(env: Env, session_ids: Vec<u32>) -> Result<u32, Error> {
+        require_admin(&env)?;
+        let from = stored_schema_version(&env);
+        if from >= SCHEMA_VERSION {
+            return Err(Error::AlreadyMigrated);
+        }
+        Ok(migrate_matches(&env, from, &session_ids))
+    }
+
+    /// Current storage schema version.
+    pub fn schema_version(env: Env) -> u32 {
+        stored_schema_version(&env)
+    }
+
     /// Admin can update the expected image ID (e.g. after guest code change).
     pub fn set_image_id(env: Env, image_id: BytesN<32>) -> Result<(), Error> {
-        let admin: Address = env
-            .storage()
-            .instance()
-            .get(&DataKey::Admin)
-            .ok_or(Error::NotInitialized)?;
-        admin.require_auth();
+        require_admin(&env)?;
         env.storage().instance().set(&DataKey::ImageId, &image_id);
         Ok(())
     }
 
-    /// Start a match. Registers players and calls Game Hub start_game().
+    /// Admin-only: open (Some) or close (None) the transition window for journals without the
+    /// pickup stats commitment. While open, settle_match verifies those against `image_id` (the guest
+    /// that still commits them) and newer journals against the current image ID.
+    pub fn set_legacy_image_id(env: Env, image_id: Option<BytesN<32>>) -> Result<(), Error> {
+        require_admin(&env)?;
+        match image_id {
+            Some(image_id) => env.storage().instance().set(&DataKey::LegacyImageId, &image_id),
+            None => env.storage().instance().remove(&DataKey::LegacyImageId),
+        }
+        Ok(())
+    }
+
+    /// Image ID pre-v9 journals verify against, while the transition window is open.
+    pub fn legacy_image_id(env: Env) -> Option<BytesN<32>> {
+        env.storage().instance().get(&DataKey::LegacyImageId)
+    }
+
+    /// Admin-only: the journal schema versions settle_match accepts. Every version must be
+    /// one this contract can settle (JournalVersionRejected otherwise); drop 0 once no
+    /// unversioned proofs are in flight.
+    pub fn set_journal_versions(env: Env, versions: Vec<u32>) -> Result<(), Error> {
+        require_admin(&env)?;
+        for v in versions.iter() {
+            if settleable_layout(v).is_none() {
+                return Err(Error::JournalVersionRejected);
+            }
+        }
+        env.storage().instance().set(&DataKey::JournalVersions, &versions);
+        Ok(())
+    }
+
+    /// Journal schema versions settle_match currently accepts.
+    pub fn journal_versions(env: Env) -> Vec<u32> {
+        accepted_journal_versions(&env)
+    }
+
+    /// Admin-only: how many ledgers after start a player has to wait before cancel_match.
+    /// Applies to matches started from now on; each match keeps the deadline it started with.
+    pub fn set_settlement_deadline_ledgers(env: Env, ledgers: u32) -> Result<(), Error> {
+        require_admin(&env)?;
+        env.storage().instance().set(&DataKey::SettlementDeadline, &ledgers);
+        Ok(())
+    }
+
+    /// Settlement deadline new matches start with.
+    pub fn settlement_deadline_ledgers(env: Env) -> u32 {
+        settlement_deadline(&env)
+    }
+
+    /// Start a match. Registers players, calls Game Hub start_game() and emits
+    /// `("match_started", session_id)` with both players and the seed commitment.
+    /// `config_commit` (`FpMatchConfig::commitment`) and `map_hash` (`fp::map_hash` of the map
+    /// as played, mirrored or not) are what settle_match holds the journal to.
     pub fn start_match(
         env: Env,
         session_id: u32,
         player1: Address,
         player2: Address,
         seed_commit: BytesN<32>,
+        config_commit: BytesN<32>,
+        map_hash: BytesN<32>,
     ) -> Result<(), Error> {
-        let admin: Address = env
-            .storage()
-            .instance()
-            .get(&DataKey::Admin)
-            .ok_or(Error::NotInitialized)?;
-        admin.require_auth();
+        require_admin(&env)?;
+        require_current_schema(&env)?;
+        begin_match(&env, session_id, player1, player2, seed_commit, config_commit, map_hash)
+    }
 
-        let key = DataKey::Match(session_id);
-        if env.storage().temporary().has(&key) {
-            return Err(Error::MatchAlreadyExists);
+    /// Start a match seeded by both players (commit-reveal) instead of the operator. Each
+    /// commitment is the SHA-256 of the player's 32 random bytes (`commitment` in
+    /// chickenz-core). Both then call reveal() within REVEAL_TIMEOUT_LEDGERS; the second reveal
+    /// sets the match's seed_commit to `hash_seed(derive_seed(reveal1, reveal2))`. Its
+    /// `match_started` event carries the all-zero seed_commit; `seeded` follows the reveals.
+    /// `config_commit` and `map_hash` are as for start_match.
+    pub fn start_match_committed(
+        env: Env,
+        session_id: u32,
+        player1: Address,
+        player2: Address,
+        commit1: BytesN<32>,
+        commit2: BytesN<32>,
+        config_commit: BytesN<32>,
+        map_hash: BytesN<32>,
+    ) -> Result<(), Error> {
+        require_admin(&env)?;
+        require_current_schema(&env)?;
+        let seed_commit = BytesN::from_array(&env, &[0; 32]);
+        begin_match(&env, session_id, player1, player2, seed_commit, config_commit, map_hash)?;
+        let reveal = SeedReveal {
+            commit1,
+            commit2,
+            reveal1: None,
+            reveal2: None,
+            deadline_ledger: env.ledger().sequence() + REVEAL_TIMEOUT_LEDGERS,
+        };
+        store_seed_reveal(&env, session_id, &reveal);
+        Ok(())
+    }
+
+    /// Reveal a player's seed entropy for a commit-reveal match. `preimage` must hash to the
+    /// player's commitment. Once both players have revealed, the seed is derived, the match's
+    /// seed_commit is set, and a `("seeded", session_id)` event carries the seed.
+    pub fn reveal(env: Env, session_id: u32, player: Address, preimage: BytesN<32>) -> Result<(), Error> {
+        require_current_schema(&env)?;
+        player.require_auth();
+        let mut match_data = load_match(&env, session_id)?;
+        require_open(&match_data)?;
+        let mut seed_reveal = load_seed_reveal(&env, session_id).ok_or(Error::NotCommitReveal)?;
+        let (commit, slot) = if player == match_data.player1 {
+            (&seed_reveal.commit1, &mut seed_reveal.reveal1)
+        } else if player == match_data.player2 {
+            (&seed_reveal.commit2, &mut seed_reveal.reveal2)
+        } else {
+            return Err(Error::Unauthorized);
+        };
+        if slot.is_some() {
+            return Err(Error::AlreadyRevealed);
         }
+        if env.ledger().sequence() > seed_reveal.deadline_ledger {
+            return Err(Error::RevealClosed);
+        }
+        let expected = mini::reveal_commitment_with(&preimage.to_array(), |b| sha256_array(&env, b));
+        if expected != commit.to_array() {
+            return Err(Error::RevealMismatch);
+        }
+        *slot = Some(preimage);
+
+        if let (Some(r1), Some(r2)) = (&seed_reveal.reveal1, &seed_reveal.reveal2) {
+            let seed = mini::derive_seed_with(&r1.to_array(), &r2.to_array(), |b| sha256_array(&env, b));
+            let seed_commit = mini::hash_seed_with(seed, |b| sha256_array(&env, b));
+            match_data.seed_commit = BytesN::from_array(&env, &seed_commit);
+            store_match(&env, session_id, &mut match_data);
+            env.events().publish((symbol_short!("seeded"), session_id), seed);
+        }
+        store_seed_reveal(&env, session_id, &seed_reveal);
+        Ok(())
+    }
+
+    /// After the reveal deadline of a commit-reveal match, forfeit it to the only player who
+    /// revealed: Game Hub end_game() is called for them and the match is marked settled with
+    /// no journal, its result the forfeit (no scores), which get_settlement reports as one. Fails with SeedNotRevealed if neither player revealed (the match is left to
+    /// expire) and AlreadyRevealed if both did.
+    pub fn claim_reveal_timeout(env: Env, session_id: u32) -> Result<(), Error> {
+        require_current_schema(&env)?;
+        let mut match_data = load_match(&env, session_id)?;
+        require_open(&match_data)?;
+        let seed_reveal = load_seed_reveal(&env, session_id).ok_or(Error::NotCommitReveal)?;
+        if env.ledger().sequence() <= seed_reveal.deadline_ledger {
+            return Err(Error::RevealStillOpen);
+        }
+        let player1_won = match (&seed_reveal.reveal1, &seed_reveal.reveal2) {
+            (Some(_), None) => true,
+            (None, Some(_)) => false,
+            (None, None) => return Err(Error::SeedNotRevealed),
+            (Some(_), Some(_)) => return Err(Error::AlreadyRevealed),
+        };
 
-        // Call Game Hub start_game first
         let game_hub_addr: Address = env
             .storage()
             .instance()
             .get(&DataKey::GameHub)
             .ok_or(Error::NotInitialized)?;
-        let game_hub = GameHubClient::new(&env, &game_hub_addr);
-        game_hub.start_game(
-            &env.current_contract_address(),
-            &session_id,
-            &player1,
-            &player2,
-            &3i128, // initial lives
-            &3i128,
-        );
+        GameHubClient::new(&env, &game_hub_addr).end_game(&session_id, &player1_won);
 
-        // Store match data after Game Hub succeeds
-        let match_data = MatchData {
-            player1,
-            player2,
-            seed_commit,
-            settled: false,
-        };
-        env.storage().temporary().set(&key, &match_data);
-        env.storage()
-            .temporary()
-            .extend_ttl(&key, MATCH_TTL_LEDGERS, MATCH_TTL_LEDGERS);
-        env.storage()
-            .instance()
-            .extend_ttl(MATCH_TTL_LEDGERS, MATCH_TTL_LEDGERS);
+        match_data.settled = true;
+        match_data.settled_ledger = env.ledger().sequence();
+        match_data.result = Some(MatchResult {
+            player1_won,
+            player1_score: 0,
+            player2_score: 0,
+            player1_env_kills: 0,
+            player2_env_kills: 0,
+        });
+        store_match(&env, session_id, &mut match_data);
+        Ok(())
+    }
+
+    /// Commitments, reveals and deadline of a commit-reveal match.
+    pub fn get_seed_reveal(env: Env, session_id: u32) -> Result<SeedReveal, Error> {
+        require_current_schema(&env)?;
+        load_match(&env, session_id)?;
+        load_seed_reveal(&env, session_id).ok_or(Error::NotCommitReveal)
+    }
 
+    /// Both players agree on the transcript their match is settled with: `commit` is its
+    /// transcript_hash, the SHA-256 of every tick's raw input bytes (`fp::hash_transcript`),
+    /// which monolithic and chunked proofs both commit. From then on settle_match only accepts
+    /// a journal committing it (TranscriptMismatch otherwise). Needs both players' auth; they
+    /// can replace it the same way until the match settles.
+    pub fn commit_transcript(env: Env, session_id: u32, commit: BytesN<32>) -> Result<(), Error> {
+        require_current_schema(&env)?;
+        let mut match_data = load_match(&env, session_id)?;
+        require_open(&match_data)?;
+        match_data.player1.require_auth();
+        match_data.player2.require_auth();
+
+        match_data.transcript_commit = Some(commit);
+        store_match(&env, session_id, &mut match_data);
         Ok(())
     }
 
-    /// Settle a match with a ZK proof. Verifies the proof and calls Game Hub end_game().
+    /// Settle a match with a ZK proof. Verifies the proof, calls Game Hub end_game() and emits
+    /// `("match_settled", session_id)` with the winner's address, both players' scores and the
+    /// journal digest.
     ///
+    /// `caller`: either player or the admin, with auth
     /// `seal`: 260-byte Groth16 seal from RISC Zero
-    /// `journal`: 76-byte raw journal (ProverOutput in fixed word layout)
+    /// `journal`: raw journal (ProverOutput in fixed word layout) of an accepted schema version:
+    /// 236 bytes for version 9, 204 for version 7, 176 for version 5, 156 for version 3, 124
+    /// for version 1, 120 for unversioned version 0, plus an optional event-chain head.
+    /// Versions before 9 verify against the legacy image ID while one is set.
     pub fn settle_match(
         env: Env,
         session_id: u32,
+        caller: Address,
         seal: Bytes,
         journal: Bytes,
     ) -> Result<(), Error> {
-        let key = DataKey::Match(session_id);
+        require_current_schema(&env)?;
+        caller.require_auth();
 
         // 1. Load and validate match
-        let mut match_data: MatchData = env
-            .storage()
-            .temporary()
-            .get(&key)
-            .ok_or(Error::MatchNotFound)?;
+        let mut match_data = load_match(&env, session_id)?;
+        require_player_or_admin(&env, &match_data, &caller)?;
+        require_open(&match_data)?;
 
-        if match_data.settled {
-            return Err(Error::MatchAlreadySettled);
+        // A commit-reveal match has no seed_commit until both players have revealed
+        if let Some(r) = load_seed_reveal(&env, session_id) {
+            if r.reveal1.is_none() || r.reveal2.is_none() {
+                return Err(Error::SeedNotRevealed);
+            }
         }
 
-        // 2. Validate journal size
-        if journal.len() != JOURNAL_SIZE as u32 {
-            return Err(Error::InvalidJournal);
+        // 2. Validate journal version, size and winner (before paying for proof verification)
+        let j = decode_journal(&env, &journal)?;
+        if !layout::is_settleable_winner(j.winner) {
+            return Err(Error::InvalidWinner);
         }
 
         // Extend instance TTL to prevent expiry
@@ -219,12 +1170,8 @@ impl ChickenzContract {
         // 3. Compute journal digest = SHA-256(journal)
         let journal_digest: Hash<32> = env.crypto().sha256(&journal);
 
-        // 4. Load image_id and verifier
-        let image_id: BytesN<32> = env
-            .storage()
-            .instance()
-            .get(&DataKey::ImageId)
-            .ok_or(Error::NotInitialized)?;
+        // 4. Load image_id (the legacy one for a pre-v9 journal, while set) and verifier
+        let image_id = image_id_for(&env, &j)?;
         let verifier_addr: Address = env
             .storage()
             .instance()
@@ -233,26 +1180,40 @@ impl ChickenzContract {
 
         // 5. Verify ZK proof — panics on failure, reverting the entire tx
         let verifier = VerifierClient::new(&env, &verifier_addr);
-        verifier.verify(
-            &seal,
-            &image_id,
-            &BytesN::from_array(&env, &journal_digest.to_array()),
-        );
+        let journal_digest = BytesN::from_array(&env, &journal_digest.to_array());
+        verifier.verify(&seal, &image_id, &journal_digest);
 
-        // 6. Decode journal: extract winner and seed_commit
-        let winner = decode_winner(&journal);
-        if winner != 0 && winner != 1 {
-            return Err(Error::InvalidWinner);
-        }
-        let proof_seed_commit = extract_seed_commit(&env, &journal);
+        // 6. Decode journal: extract seed_commit
+        let proof_seed_commit = BytesN::from_array(&env, &j.seed_commit);
 
-        // 7. Verify seed_commit matches what was registered at match start
+        // 7. Verify seed_commit matches what was registered at match start,
+        //    and that the proof was generated for this session (no cross-session replay)
         if proof_seed_commit != match_data.seed_commit {
             return Err(Error::SeedMismatch);
         }
+        if j.session_id != session_id {
+            return Err(Error::SessionMismatch);
+        }
+        // ...and that it replayed the transcript the players committed to, if they did
+        if let Some(commit) = &match_data.transcript_commit {
+            if j.transcript_hash != commit.to_array() {
+                return Err(Error::TranscriptMismatch);
+            }
+        }
+        // ...and under the rules and on the map the match was started with
+        if let Some(commit) = &match_data.config_commit {
+            if j.config_commit != commit.to_array() {
+                return Err(Error::ConfigMismatch);
+            }
+        }
+        if let Some(hash) = &match_data.map_hash {
+            if j.map_hash.unwrap_or(mini::ARENA_MAP_HASH) != hash.to_array() {
+                return Err(Error::MapMismatch);
+            }
+        }
 
         // 8. Determine player1_won (draws are impossible — sim always picks a winner)
-        let player1_won = winner == 0;
+        let player1_won = j.winner == 0;
 
         // 9. Call Game Hub end_game FIRST (before updating state)
         let game_hub_addr: Address = env
@@ -264,21 +1225,115 @@ impl ChickenzContract {
         game_hub.end_game(&session_id, &player1_won);
 
         // 10. Mark settled after Game Hub succeeds
+        let winner = if player1_won { match_data.player1.clone() } else { match_data.player2.clone() };
+        env.events().publish(
+            (Symbol::new(&env, "match_settled"), session_id),
+            (winner, decode_scores(&j), journal_digest.clone()),
+        );
         match_data.settled = true;
-        env.storage().temporary().set(&key, &match_data);
-        env.storage()
-            .temporary()
-            .extend_ttl(&key, MATCH_TTL_LEDGERS, MATCH_TTL_LEDGERS);
+        match_data.journal_digest = Some(journal_digest);
+        match_data.settled_ledger = env.ledger().sequence();
+        match_data.result = Some(decode_result(&j));
+        match_data.final_stats = decode_final_stats(&j);
+        match_data.team_result = decode_team_result(&j);
+        store_match(&env, session_id, &mut match_data);
 
         Ok(())
     }
 
+    /// Call off a match that never settled. The admin can cancel any time; either player once
+    /// the match's settlement_deadline_ledgers have passed since it started
+    /// (SettlementStillOpen before). Game Hub end_game() has no draw or no-contest outcome, so
+    /// it isn't called: the hub session stays open rather than crediting either player. A
+    /// `("cancelled", session_id)` event carries the caller. The match keeps its entry, marked
+    /// cancelled, and can't settle.
+    pub fn cancel_match(env: Env, session_id: u32, caller: Address) -> Result<(), Error> {
+        require_current_schema(&env)?;
+        caller.require_auth();
+        let mut match_data = load_match(&env, session_id)?;
+        require_player_or_admin(&env, &match_data, &caller)?;
+        require_open(&match_data)?;
+
+        let admin: Address = env
+            .storage()
+            .instance()
+            .get(&DataKey::Admin)
+            .ok_or(Error::NotInitialized)?;
+        let deadline = match_data
+            .created_ledger
+            .saturating_add(match_data.settlement_deadline_ledgers);
+        if caller != admin && env.ledger().sequence() <= deadline {
+            return Err(Error::SettlementStillOpen);
+        }
+
+        match_data.cancelled = true;
+        store_match(&env, session_id, &mut match_data);
+        env.events().publish((symbol_short!("cancelled"), session_id), caller);
+        Ok(())
+    }
+
     /// Read match data.
     pub fn get_match(env: Env, session_id: u32) -> Result<MatchData, Error> {
+        require_current_schema(&env)?;
+        load_match(&env, session_id)
+    }
+
+    /// Creation ledger, configured TTL and current expiry of a match.
+    pub fn get_ttl_info(env: Env, session_id: u32) -> Result<TtlInfo, Error> {
+        let match_data = Self::get_match(env, session_id)?;
+        Ok(ttl_info(&match_data))
+    }
+
+    /// Emit an `("expiring", session_id)` event carrying the expiry ledger if fewer than
+    /// TTL_WARNING_LEDGERS remain, and return whether it did. Anyone may submit it (a keeper
+    /// watching open matches): events only land from a transaction, never from a simulated
+    /// getter call. The TTL is left alone; extend_match_ttl restarts it.
+    pub fn warn_if_expiring(env: Env, session_id: u32) -> Result<bool, Error> {
+        let match_data = Self::get_match(env.clone(), session_id)?;
+        let remaining = match_data.expires_ledger.saturating_sub(env.ledger().sequence());
+        if remaining >= TTL_WARNING_LEDGERS {
+            return Ok(false);
+        }
+        env.events().publish(
+            (symbol_short!("expiring"), session_id),
+            match_data.expires_ledger,
+        );
+        Ok(true)
+    }
+
+    /// Restart a match's TTL from the current ledger. `caller` must be one of the two
+    /// players or the admin.
+    pub fn extend_match_ttl(env: Env, session_id: u32, caller: Address) -> Result<TtlInfo, Error> {
+        require_current_schema(&env)?;
+        caller.require_auth();
+        let mut match_data = load_match(&env, session_id)?;
+        require_player_or_admin(&env, &match_data, &caller)?;
+
+        store_match(&env, session_id, &mut match_data);
+        store_tombstone(&env, session_id);
         env.storage()
-            .temporary()
-            .get(&DataKey::Match(session_id))
-            .ok_or(Error::MatchNotFound)
+            .instance()
+            .extend_ttl(MATCH_TTL_LEDGERS, MATCH_TTL_LEDGERS);
+        Ok(ttl_info(&match_data))
+    }
+
+    /// Read the journal digest, settlement ledger and decoded result of a settled match, or
+    /// the forfeit claim_reveal_timeout recorded (a result without a journal). Matches settled
+    /// before schema 2 kept neither and count as not settled here.
+    pub fn get_settlement(env: Env, session_id: u32) -> Result<SettlementInfo, Error> {
+        let match_data = Self::get_match(env, session_id)?;
+        let forfeit = match_data.journal_digest.is_none() && match_data.result.is_some();
+        if !match_data.settled || (match_data.journal_digest.is_none() && !forfeit) {
+            return Err(Error::MatchNotSettled);
+        }
+        Ok(SettlementInfo {
+            journal_digest: match_data.journal_digest,
+            settled_ledger: match_data.settled_ledger,
+            forfeit,
+            result: match_data.result,
+            final_stats: match_data.final_stats,
+            team_result: match_data.team_result,
+        })
     }
 }
 
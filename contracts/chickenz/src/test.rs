@@ -1,8 +1,146 @@
 #![cfg(test)]
 
 use super::*;
-use soroban_sdk::testutils::Address as _;
-use soroban_sdk::{Env, Address, BytesN, Bytes};
+use soroban_sdk::testutils::{Address as _, Events, Ledger};
+use soroban_sdk::{contract, contractimpl, symbol_short, Env, Address, BytesN, Bytes, IntoVal, Symbol};
+
+// ── Mock cross-contract dependencies ─────────────────────────────────────────
+
+#[contract]
+pub struct MockVerifier;
+
+#[contractimpl]
+impl MockVerifier {
+    pub fn verify(_env: Env, _seal: Bytes, _image_id: BytesN<32>, _journal: BytesN<32>) {}
+}
+
+/// Accepts a seal only if it is the image ID it is checked against, so tests can tell which
+/// image ID settle_match picked.
+#[contract]
+pub struct MockImageVerifier;
+
+#[contractimpl]
+impl MockImageVerifier {
+    pub fn verify(env: Env, seal: Bytes, image_id: BytesN<32>, _journal: BytesN<32>) {
+        assert_eq!(seal, Bytes::from_slice(&env, &image_id.to_array()), "seal is for another image");
+    }
+}
+
+#[contract]
+pub struct MockGameHub;
+
+#[contractimpl]
+impl MockGameHub {
+    pub fn start_game(
+        _env: Env,
+        _game_id: Address,
+        _session_id: u32,
+        _player1: Address,
+        _player2: Address,
+        _player1_points: i128,
+        _player2_points: i128,
+    ) {
+    }
+    /// Records the outcome so tests can read it back with `game_hub_winner`.
+    pub fn end_game(env: Env, session_id: u32, player1_won: bool) {
+        env.storage().instance().set(&session_id, &player1_won);
+    }
+}
+
+/// The contract's admin, who may settle any match.
+fn admin(env: &Env, client: &ChickenzContractClient) -> Address {
+    env.as_contract(&client.address, || env.storage().instance().get(&DataKey::Admin).unwrap())
+}
+
+/// Outcome Game Hub end_game() was called with for a session, if it was.
+fn game_hub_winner(env: &Env, client: &ChickenzContractClient, session_id: u32) -> Option<bool> {
+    let game_hub: Address =
+        env.as_contract(&client.address, || env.storage().instance().get(&DataKey::GameHub).unwrap());
+    env.as_contract(&game_hub, || env.storage().instance().get(&session_id))
+}
+
+/// Initialized contract wired to mock verifier + game hub, all auths mocked.
+fn setup_with_mocks(env: &Env) -> ChickenzContractClient<'_> {
+    env.mock_all_auths();
+    let contract_id = env.register(ChickenzContract, ());
+    let game_hub = env.register(MockGameHub, ());
+    let verifier = env.register(MockVerifier, ());
+    let client = ChickenzContractClient::new(env, &contract_id);
+    client.initialize(
+        &Address::generate(env),
+        &game_hub,
+        &verifier,
+        &BytesN::from_array(env, &[0xAA; 32]),
+    );
+    client
+}
+
+/// Config commitment make_journal commits.
+const CONFIG_COMMIT: [u8; 32] = [0xDD; 32];
+
+/// Config commitment and map hash (the arena) make_journal commits, as start_match takes them.
+fn match_rules(env: &Env) -> (BytesN<32>, BytesN<32>) {
+    (BytesN::from_array(env, &CONFIG_COMMIT), BytesN::from_array(env, &mini::ARENA_MAP_HASH))
+}
+
+/// Current-version journal: winner, scores 3-1, transcript hash 0xBB.., given seed commit + session,
+/// config commit CONFIG_COMMIT on the arena, env kills 0-2, ended at tick 1730 with player 2 on 45 health and 2 lives,
+/// each player its own team, pickup stats commitment 0x5E..
+fn make_journal(env: &Env, winner: i32, seed_commit: [u8; 32], session_id: u32) -> Bytes {
+    let teams = chickenz_core::TeamResult::solo(2, winner, &[3, 1, 0, 0]);
+    let output = chickenz_core::ProverOutput {
+        winner,
+        scores: [3, 1, 0, 0],
+        transcript_hash: [0xBB; 32],
+        seed_commit,
+        session_id,
+        config_commit: CONFIG_COMMIT,
+        env_kills: [0, 2, 0, 0],
+        player_count: 2,
+        map_hash: mini::ARENA_MAP_HASH,
+        final_tick: 1730,
+        final_health: [0, 45, 0, 0],
+        final_lives: [0, 2, 0, 0],
+        winner_team: teams.winner_team,
+        team_scores: teams.team_scores,
+        player_teams: teams.player_teams,
+        pickup_stats_commit: [0x5E; 32],
+    };
+    Bytes::from_slice(env, &output.to_journal_bytes())
+}
+
+/// The same journal in the version-7 layout (no pickup stats commitment), as the guest before
+/// it commits.
+fn make_v7_journal(env: &Env, winner: i32, seed_commit: [u8; 32], session_id: u32) -> Bytes {
+    let mut journal = Bytes::from_slice(env, &layout::encode_version(7).to_le_bytes());
+    journal.append(&make_journal(env, winner, seed_commit, session_id).slice(4..layout::V7.size() as u32));
+    journal
+}
+
+/// The same journal in the version-5 layout (no team result), as the guest before it commits.
+fn make_v5_journal(env: &Env, winner: i32, seed_commit: [u8; 32], session_id: u32) -> Bytes {
+    let mut journal = Bytes::from_slice(env, &layout::encode_version(5).to_le_bytes());
+    journal.append(&make_journal(env, winner, seed_commit, session_id).slice(4..layout::V5.size() as u32));
+    journal
+}
+
+/// The same journal in the version-3 layout (no final stats), as the guest before them commits.
+fn make_v3_journal(env: &Env, winner: i32, seed_commit: [u8; 32], session_id: u32) -> Bytes {
+    let mut journal = Bytes::from_slice(env, &layout::encode_version(3).to_le_bytes());
+    journal.append(&make_journal(env, winner, seed_commit, session_id).slice(4..layout::V3.size() as u32));
+    journal
+}
+
+/// The same journal in the unversioned version-0 layout (the body without the version word or
+/// the map hash).
+fn make_v0_journal(env: &Env, winner: i32, seed_commit: [u8; 32], session_id: u32) -> Bytes {
+    make_journal(env, winner, seed_commit, session_id).slice(4..layout::V1.size() as u32)
+}
+
+/// `decode_journal` on a fresh contract with the default accepted versions.
+fn decode(env: &Env, journal: &Bytes) -> Result<chickenz_core_mini::Journal, Error> {
+    env.as_contract(&env.register(ChickenzContract, ()), || decode_journal(env, journal))
+}
 
 fn setup_contract(env: &Env) -> (Address, Address, Address, Address, BytesN<32>) {
     let contract_id = env.register(ChickenzContract, ());
@@ -38,8 +176,8 @@ fn test_double_initialize() {
 
 #[test]
 fn test_journal_decode() {
-    // Build a 76-byte journal manually
-    let mut journal_bytes = [0u8; 76];
+    // Build a 120-byte unversioned (version 0) journal manually
+    let mut journal_bytes = [0u8; 120];
     // winner = 0 (player 0 wins) at offset 0
     journal_bytes[0] = 0;
     journal_bytes[1] = 0;
@@ -57,20 +195,24 @@ fn test_journal_decode() {
     for i in 44..76 {
         journal_bytes[i] = 0xCC;
     }
+    // session_id = 0x0102 at offset 76
+    journal_bytes[76] = 0x02;
+    journal_bytes[77] = 0x01;
 
     let env = Env::default();
     let journal = Bytes::from_slice(&env, &journal_bytes);
 
-    let winner = decode_winner(&journal);
-    assert_eq!(winner, 0);
-
-    let seed = extract_seed_commit(&env, &journal);
-    assert_eq!(seed, BytesN::from_array(&env, &[0xCC; 32]));
+    let j = decode(&env, &journal).unwrap();
+    assert_eq!(j.layout, layout::V0);
+    assert_eq!(j.winner, 0);
+    assert_eq!(j.scores[..2], [3, 1]);
+    assert_eq!(j.seed_commit, [0xCC; 32]);
+    assert_eq!(j.session_id, 0x0102);
 }
 
 #[test]
 fn test_journal_decode_draw() {
-    let mut journal_bytes = [0u8; 76];
+    let mut journal_bytes = [0u8; 120];
     // winner = -1 (0xFFFFFFFF LE) for draw
     journal_bytes[0] = 0xFF;
     journal_bytes[1] = 0xFF;
@@ -80,6 +222,1310 @@ fn test_journal_decode_draw() {
     let env = Env::default();
     let journal = Bytes::from_slice(&env, &journal_bytes);
 
-    let winner = decode_winner(&journal);
-    assert_eq!(winner, -1);
+    assert_eq!(decode(&env, &journal).unwrap().winner, -1);
+}
+
+#[test]
+fn test_get_settlement_records_digest_and_ledger() {
+    let env = Env::default();
+    let client = setup_with_mocks(&env);
+    let seed_commit = [0xCC; 32];
+    let (config, map) = match_rules(&env);
+    client.start_match(
+        &7,
+        &Address::generate(&env),
+        &Address::generate(&env),
+        &BytesN::from_array(&env, &seed_commit),
+        &config,
+        &map,
+    );
+
+    env.ledger().set_sequence_number(1234);
+    let journal = make_journal(&env, 0, seed_commit, 7);
+    client.settle_match(&7, &admin(&env, &client), &Bytes::new(&env), &journal);
+
+    let info = client.get_settlement(&7);
+    let expected = BytesN::from_array(&env, &env.crypto().sha256(&journal).to_array());
+    assert_eq!(info.journal_digest, Some(expected));
+    assert_eq!(info.settled_ledger, 1234);
+    assert!(!info.forfeit);
+    let result = MatchResult {
+        player1_won: true,
+        player1_score: 3,
+        player2_score: 1,
+        player1_env_kills: 0,
+        player2_env_kills: 2,
+    };
+    assert_eq!(info.result, Some(result.clone()));
+    let stats = FinalStats {
+        final_tick: 1730,
+        player1_health: 0,
+        player2_health: 45,
+        player1_lives: 0,
+        player2_lives: 2,
+    };
+    assert_eq!(info.final_stats, Some(stats.clone()));
+    // A 1v1 is two teams of one: the team result mirrors the player result
+    let teams = TeamResult {
+        winner_team: 0,
+        team0_score: 3,
+        team1_score: 1,
+        player1_team: 0,
+        player2_team: 1,
+    };
+    assert_eq!(info.team_result, Some(teams.clone()));
+
+    let match_data = client.get_match(&7);
+    assert!(match_data.settled);
+    assert_eq!(match_data.journal_digest, Some(expected));
+    assert_eq!(match_data.result, Some(result));
+    assert_eq!(match_data.final_stats, Some(stats));
+    assert_eq!(match_data.team_result, Some(teams));
+}
+
+#[test]
+fn test_team_result_follows_the_winner() {
+    let env = Env::default();
+    let client = setup_with_mocks(&env);
+    start_session(&env, &client, 7, [0xCC; 32]);
+    start_session(&env, &client, 8, [0xCC; 32]);
+    client.settle_match(&7, &admin(&env, &client), &Bytes::new(&env), &make_journal(&env, 1, [0xCC; 32], 7));
+    client.settle_match(&8, &admin(&env, &client), &Bytes::new(&env), &make_v5_journal(&env, 1, [0xCC; 32], 8));
+
+    let info = client.get_settlement(&7);
+    let (result, teams) = (info.result.unwrap(), info.team_result.unwrap());
+    assert_eq!(teams.winner_team == 0, result.player1_won);
+    assert_eq!((teams.team0_score, teams.team1_score), (result.player1_score, result.player2_score));
+    // A journal from before the team result settles without one
+    assert_eq!(client.get_settlement(&8).team_result, None);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #10)")]
+fn test_get_settlement_unsettled() {
+    let env = Env::default();
+    let client = setup_with_mocks(&env);
+    let (config, map) = match_rules(&env);
+    client.start_match(
+        &7,
+        &Address::generate(&env),
+        &Address::generate(&env),
+        &BytesN::from_array(&env, &[0xCC; 32]),
+        &config,
+        &map,
+    );
+    client.get_settlement(&7);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #4)")]
+fn test_get_settlement_unknown_match() {
+    let env = Env::default();
+    let client = setup_with_mocks(&env);
+    client.get_settlement(&99);
+}
+
+/// Rewind the deployment to schema v1 and plant a v1-layout match entry.
+fn plant_v1_match(env: &Env, client: &ChickenzContractClient, session_id: u32, settled: bool) {
+    env.as_contract(&client.address, || {
+        env.storage().instance().set(&DataKey::SchemaVersion, &1u32);
+        env.storage().temporary().set(
+            &DataKey::Match(session_id),
+            &MatchDataV1 {
+                player1: Address::generate(env),
+                player2: Address::generate(env),
+                seed_commit: BytesN::from_array(env, &[0xCC; 32]),
+                settled,
+            },
+        );
+    });
+}
+
+#[test]
+fn test_initialize_sets_schema_version() {
+    let env = Env::default();
+    let client = setup_with_mocks(&env);
+    assert_eq!(client.schema_version(), SCHEMA_VERSION);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #11)")]
+fn test_migrate_rejected_when_current() {
+    let env = Env::default();
+    let client = setup_with_mocks(&env);
+    client.migrate(&soroban_sdk::vec![&env]);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #12)")]
+fn test_old_schema_blocks_match_access() {
+    let env = Env::default();
+    let client = setup_with_mocks(&env);
+    plant_v1_match(&env, &client, 5, false);
+    client.get_match(&5);
+}
+
+#[test]
+fn test_migrate_v1_match_data() {
+    let env = Env::default();
+    let client = setup_with_mocks(&env);
+    plant_v1_match(&env, &client, 5, false);
+    plant_v1_match(&env, &client, 6, true);
+    assert_eq!(client.schema_version(), 1);
+
+    // 7 was never started — skipped, not an error
+    let migrated = client.migrate(&soroban_sdk::vec![&env, 5, 6, 7]);
+    assert_eq!(migrated, 2);
+    assert_eq!(client.schema_version(), SCHEMA_VERSION);
+
+    let m5 = client.get_match(&5);
+    assert!(!m5.settled);
+    assert_eq!(m5.seed_commit, BytesN::from_array(&env, &[0xCC; 32]));
+    assert_eq!(m5.journal_digest, None);
+    assert_eq!(m5.settled_ledger, 0);
+    assert_eq!(m5.created_ledger, 0);
+    assert_eq!(m5.expires_ledger, MATCH_TTL_LEDGERS);
+    assert!(client.get_match(&6).settled);
+
+    // Runs once per version
+    assert_eq!(
+        client.try_migrate(&soroban_sdk::vec![&env, 5]),
+        Err(Ok(Error::AlreadyMigrated))
+    );
+}
+
+#[test]
+fn test_migrate_in_batches() {
+    let env = Env::default();
+    let client = setup_with_mocks(&env);
+    for session_id in 5..8 {
+        plant_v1_match(&env, &client, session_id, session_id == 6);
+    }
+
+    // Batches leave the version (and the entrypoints blocked) until the last one
+    assert_eq!(client.migrate_batch(&soroban_sdk::vec![&env, 5, 6]), 2);
+    assert_eq!(client.schema_version(), 1);
+    assert_eq!(client.try_get_match(&5), Err(Ok(Error::MigrationRequired)));
+
+    // An entry listed again is already current and skipped, not misread as v1
+    assert_eq!(client.migrate_batch(&soroban_sdk::vec![&env, 6, 7]), 1);
+    assert_eq!(client.migrate(&soroban_sdk::vec![&env, 7]), 0);
+    assert_eq!(client.schema_version(), SCHEMA_VERSION);
+    assert!(!client.get_match(&5).settled);
+    assert!(client.get_match(&6).settled);
+    assert_eq!(client.get_match(&7).seed_commit, BytesN::from_array(&env, &[0xCC; 32]));
+
+    assert_eq!(
+        client.try_migrate_batch(&soroban_sdk::vec![&env, 5]),
+        Err(Ok(Error::AlreadyMigrated))
+    );
+}
+
+#[test]
+fn test_migrated_match_settles() {
+    let env = Env::default();
+    let client = setup_with_mocks(&env);
+    plant_v1_match(&env, &client, 5, false);
+    client.migrate(&soroban_sdk::vec![&env, 5]);
+
+    let journal = make_journal(&env, 1, [0xCC; 32], 5);
+    client.settle_match(&5, &admin(&env, &client), &Bytes::new(&env), &journal);
+    assert!(client.get_match(&5).settled);
+    assert_eq!(
+        client.get_settlement(&5).journal_digest,
+        Some(BytesN::from_array(&env, &env.crypto().sha256(&journal).to_array()))
+    );
+}
+
+#[test]
+#[should_panic]
+fn test_upgrade_requires_admin_auth() {
+    let env = Env::default();
+    let (contract_id, admin, game_hub, verifier, image_id) = setup_contract(&env);
+    let client = ChickenzContractClient::new(&env, &contract_id);
+    client.initialize(&admin, &game_hub, &verifier, &image_id);
+    // No auths mocked: admin.require_auth() must reject before the wasm swap is attempted
+    client.upgrade(&BytesN::from_array(&env, &[0x11; 32]));
+}
+
+fn start_session(env: &Env, client: &ChickenzContractClient, session_id: u32, seed_commit: [u8; 32]) {
+    start_session_with(env, client, session_id, seed_commit, CONFIG_COMMIT, mini::ARENA_MAP_HASH);
+}
+
+/// A match started under the given config commitment and map hash.
+fn start_session_with(
+    env: &Env,
+    client: &ChickenzContractClient,
+    session_id: u32,
+    seed_commit: [u8; 32],
+    config_commit: [u8; 32],
+    map_hash: [u8; 32],
+) {
+    client.start_match(
+        &session_id,
+        &Address::generate(env),
+        &Address::generate(env),
+        &BytesN::from_array(env, &seed_commit),
+        &BytesN::from_array(env, &config_commit),
+        &BytesN::from_array(env, &map_hash),
+    );
+}
+
+#[test]
+fn test_journal_cannot_settle_other_session() {
+    let env = Env::default();
+    let client = setup_with_mocks(&env);
+    // Operator error: the same seed registered for two sessions
+    start_session(&env, &client, 7, [0xCC; 32]);
+    start_session(&env, &client, 8, [0xCC; 32]);
+
+    let journal_for_7 = make_journal(&env, 0, [0xCC; 32], 7);
+    assert_eq!(
+        client.try_settle_match(&8, &admin(&env, &client), &Bytes::new(&env), &journal_for_7),
+        Err(Ok(Error::SessionMismatch))
+    );
+    assert!(!client.get_match(&8).settled);
+
+    // The proof still settles the session it was generated for
+    client.settle_match(&7, &admin(&env, &client), &Bytes::new(&env), &journal_for_7);
+    assert!(client.get_match(&7).settled);
+}
+
+#[test]
+fn test_journal_must_match_started_config_and_map() {
+    let env = Env::default();
+    let client = setup_with_mocks(&env);
+    let admin = admin(&env, &client);
+    // Started under other rules than the journal's CONFIG_COMMIT
+    start_session_with(&env, &client, 7, [0xCC; 32], [0xDE; 32], mini::ARENA_MAP_HASH);
+    assert_eq!(
+        client.try_settle_match(&7, &admin, &Bytes::new(&env), &make_journal(&env, 0, [0xCC; 32], 7)),
+        Err(Ok(Error::ConfigMismatch))
+    );
+    assert!(!client.get_match(&7).settled);
+
+    // Started on another map than the arena the journal was played on
+    start_session_with(&env, &client, 8, [0xCC; 32], CONFIG_COMMIT, [0x3A; 32]);
+    assert_eq!(
+        client.try_settle_match(&8, &admin, &Bytes::new(&env), &make_journal(&env, 0, [0xCC; 32], 8)),
+        Err(Ok(Error::MapMismatch))
+    );
+    // ...which an unversioned journal, from before the map hash, can't have been either
+    assert_eq!(
+        client.try_settle_match(&8, &admin, &Bytes::new(&env), &make_v0_journal(&env, 0, [0xCC; 32], 8)),
+        Err(Ok(Error::MapMismatch))
+    );
+
+    // A match started on the arena settles with one
+    start_session(&env, &client, 9, [0xCC; 32]);
+    client.settle_match(&9, &admin, &Bytes::new(&env), &make_v0_journal(&env, 0, [0xCC; 32], 9));
+    assert!(client.get_match(&9).settled);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #7)")]
+fn test_legacy_76_byte_journal_rejected() {
+    let env = Env::default();
+    let client = setup_with_mocks(&env);
+    start_session(&env, &client, 7, [0xCC; 32]);
+    let journal = make_journal(&env, 0, [0xCC; 32], 7).slice(0..76);
+    client.settle_match(&7, &admin(&env, &client), &Bytes::new(&env), &journal);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #7)")]
+fn test_journal_without_config_commit_rejected() {
+    let env = Env::default();
+    let client = setup_with_mocks(&env);
+    start_session(&env, &client, 7, [0xCC; 32]);
+    let journal = make_journal(&env, 0, [0xCC; 32], 7).slice(0..80);
+    client.settle_match(&7, &admin(&env, &client), &Bytes::new(&env), &journal);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #7)")]
+fn test_journal_without_env_kills_rejected() {
+    let env = Env::default();
+    let client = setup_with_mocks(&env);
+    start_session(&env, &client, 7, [0xCC; 32]);
+    let journal = make_journal(&env, 0, [0xCC; 32], 7).slice(0..112);
+    client.settle_match(&7, &admin(&env, &client), &Bytes::new(&env), &journal);
+}
+
+#[test]
+fn test_migrate_v3_keeps_ledgers() {
+    let env = Env::default();
+    let client = setup_with_mocks(&env);
+    let digest = BytesN::from_array(&env, &[0xEE; 32]);
+    env.as_contract(&client.address, || {
+        env.storage().instance().set(&DataKey::SchemaVersion, &3u32);
+        env.storage().temporary().set(
+            &DataKey::Match(5),
+            &MatchDataV3 {
+                player1: Address::generate(&env),
+                player2: Address::generate(&env),
+                seed_commit: BytesN::from_array(&env, &[0xCC; 32]),
+                settled: true,
+                journal_digest: Some(digest.clone()),
+                settled_ledger: 42,
+                created_ledger: 7,
+                expires_ledger: 7 + MATCH_TTL_LEDGERS,
+            },
+        );
+        // Each step carries the ledgers through; only the final rewrite restarts the TTL
+        let v8 = read_match_v8(&env, &DataKey::Match(5), 3).unwrap();
+        assert_eq!((v8.created_ledger, v8.expires_ledger), (7, 7 + MATCH_TTL_LEDGERS));
+    });
+
+    env.ledger().set_sequence_number(100);
+    assert_eq!(client.migrate(&soroban_sdk::vec![&env, 5]), 1);
+    let m = client.get_match(&5);
+    assert_eq!(m.created_ledger, 7);
+    assert_eq!(m.expires_ledger, 100 + MATCH_TTL_LEDGERS);
+    // The journal was never stored, so the result of an already-settled match is unknown
+    assert_eq!((m.result, m.final_stats), (None, None));
+    let info = client.get_settlement(&5);
+    assert_eq!((info.journal_digest, info.settled_ledger, info.result), (Some(digest), 42, None));
+}
+
+#[test]
+fn test_migrate_v4_keeps_result() {
+    let env = Env::default();
+    let client = setup_with_mocks(&env);
+    let result = MatchResult {
+        player1_won: false,
+        player1_score: 1,
+        player2_score: 3,
+        player1_env_kills: 0,
+        player2_env_kills: 0,
+    };
+    env.as_contract(&client.address, || {
+        env.storage().instance().set(&DataKey::SchemaVersion, &4u32);
+        env.storage().temporary().set(
+            &DataKey::Match(5),
+            &MatchDataV4 {
+                player1: Address::generate(&env),
+                player2: Address::generate(&env),
+                seed_commit: BytesN::from_array(&env, &[0xCC; 32]),
+                settled: true,
+                journal_digest: Some(BytesN::from_array(&env, &[0xEE; 32])),
+                settled_ledger: 42,
+                created_ledger: 7,
+                expires_ledger: 7 + MATCH_TTL_LEDGERS,
+                result: Some(result.clone()),
+            },
+        );
+    });
+
+    assert_eq!(client.migrate(&soroban_sdk::vec![&env, 5]), 1);
+    let info = client.get_settlement(&5);
+    assert_eq!((info.settled_ledger, info.result, info.final_stats), (42, Some(result), None));
+    let m = client.get_match(&5);
+    assert_eq!((m.created_ledger, m.transcript_commit), (7, None));
+}
+
+#[test]
+fn test_migrate_v2_keeps_settlement() {
+    let env = Env::default();
+    let client = setup_with_mocks(&env);
+    let digest = BytesN::from_array(&env, &[0xEE; 32]);
+    env.as_contract(&client.address, || {
+        env.storage().instance().set(&DataKey::SchemaVersion, &2u32);
+        env.storage().temporary().set(
+            &DataKey::Match(5),
+            &MatchDataV2 {
+                player1: Address::generate(&env),
+                player2: Address::generate(&env),
+                seed_commit: BytesN::from_array(&env, &[0xCC; 32]),
+                settled: true,
+                journal_digest: Some(digest.clone()),
+                settled_ledger: 42,
+            },
+        );
+    });
+
+    env.ledger().set_sequence_number(100);
+    assert_eq!(client.migrate(&soroban_sdk::vec![&env, 5]), 1);
+    let info = client.get_settlement(&5);
+    assert_eq!(info.journal_digest, Some(digest));
+    assert_eq!(info.settled_ledger, 42);
+    assert_eq!(client.get_match(&5).expires_ledger, 100 + MATCH_TTL_LEDGERS);
+}
+
+/// Keep the contract instance live across a long ledger jump, as other traffic would.
+fn bump_instance(env: &Env, client: &ChickenzContractClient) {
+    env.as_contract(&client.address, || {
+        env.storage()
+            .instance()
+            .extend_ttl(MATCH_TTL_LEDGERS, MATCH_TTL_LEDGERS);
+    });
+}
+
+#[test]
+fn test_ttl_info_tracks_creation() {
+    let env = Env::default();
+    let client = setup_with_mocks(&env);
+    env.ledger().set_sequence_number(100);
+    start_session(&env, &client, 7, [0xCC; 32]);
+
+    let info = client.get_ttl_info(&7);
+    assert_eq!(info.created_ledger, 100);
+    assert_eq!(info.ttl_ledgers, MATCH_TTL_LEDGERS);
+    assert_eq!(info.expires_ledger, 100 + MATCH_TTL_LEDGERS);
+    assert_eq!(env.events().all().len(), 0);
+}
+
+#[test]
+fn test_ttl_info_warns_near_expiry() {
+    let env = Env::default();
+    let client = setup_with_mocks(&env);
+    start_session(&env, &client, 7, [0xCC; 32]);
+
+    // Not yet within a day of expiry: nothing to announce
+    env.ledger().set_sequence_number(MATCH_TTL_LEDGERS - TTL_WARNING_LEDGERS);
+    assert!(!client.warn_if_expiring(&7));
+    assert_eq!(env.events().all().len(), 0);
+
+    // The getter only reads; the warning is a transaction's
+    env.ledger().set_sequence_number(MATCH_TTL_LEDGERS - 10);
+    let info = client.get_ttl_info(&7);
+    assert_eq!(env.events().all().len(), 0);
+    assert!(client.warn_if_expiring(&7));
+    assert_eq!(
+        env.events().all(),
+        soroban_sdk::vec![
+            &env,
+            (
+                client.address.clone(),
+                (symbol_short!("expiring"), 7u32).into_val(&env),
+                info.expires_ledger.into_val(&env),
+            )
+        ]
+    );
+}
+
+#[test]
+fn test_expired_match_returns_match_expired() {
+    let env = Env::default();
+    let client = setup_with_mocks(&env);
+    start_session(&env, &client, 7, [0xCC; 32]);
+
+    env.ledger().set_sequence_number(MATCH_TTL_LEDGERS - 10);
+    bump_instance(&env, &client);
+    env.ledger().set_sequence_number(MATCH_TTL_LEDGERS + 1);
+
+    assert_eq!(client.try_get_match(&7), Err(Ok(Error::MatchExpired)));
+    assert_eq!(client.try_get_ttl_info(&7), Err(Ok(Error::MatchExpired)));
+    let journal = make_journal(&env, 0, [0xCC; 32], 7);
+    assert_eq!(
+        client.try_settle_match(&7, &admin(&env, &client), &Bytes::new(&env), &journal),
+        Err(Ok(Error::MatchExpired))
+    );
+    // Never-started sessions are still just unknown
+    assert_eq!(client.try_get_match(&8), Err(Ok(Error::MatchNotFound)));
+}
+
+#[test]
+fn test_player_extends_match_ttl() {
+    let env = Env::default();
+    let client = setup_with_mocks(&env);
+    let player1 = Address::generate(&env);
+    let (config, map) = match_rules(&env);
+    client.start_match(
+        &7,
+        &player1,
+        &Address::generate(&env),
+        &BytesN::from_array(&env, &[0xCC; 32]),
+        &config,
+        &map,
+    );
+
+    let extended_at = MATCH_TTL_LEDGERS - 10;
+    env.ledger().set_sequence_number(extended_at);
+    let info = client.extend_match_ttl(&7, &player1);
+    assert_eq!(info.created_ledger, 0);
+    assert_eq!(info.expires_ledger, extended_at + MATCH_TTL_LEDGERS);
+
+    // Past the original expiry the match is still live
+    env.ledger().set_sequence_number(MATCH_TTL_LEDGERS + 1);
+    assert!(!client.get_match(&7).settled);
+    assert_eq!(client.get_ttl_info(&7), info);
+}
+
+#[test]
+fn test_extend_match_ttl_rejects_outsider() {
+    let env = Env::default();
+    let client = setup_with_mocks(&env);
+    start_session(&env, &client, 7, [0xCC; 32]);
+    assert_eq!(
+        client.try_extend_match_ttl(&7, &Address::generate(&env)),
+        Err(Ok(Error::Unauthorized))
+    );
+}
+
+#[test]
+fn test_journal_layout_matches_prover() {
+    let env = Env::default();
+    for winner in [-1, 0, 1] {
+        let teams = chickenz_core::TeamResult::solo(2, winner, &[7, 0x0102_0304, 0, 0]);
+        let output = chickenz_core::ProverOutput {
+            winner,
+            scores: [7, 0x0102_0304, 0, 0],
+            transcript_hash: core::array::from_fn(|i| i as u8),
+            seed_commit: core::array::from_fn(|i| 0x40 + i as u8),
+            session_id: 0xA1B2_C3D4,
+            config_commit: core::array::from_fn(|i| 0x80 + i as u8),
+            env_kills: [2, 0x0506_0708, 0, 0],
+            player_count: 2,
+            map_hash: core::array::from_fn(|i| 0xC0 + i as u8),
+            final_tick: 0x0A0B_0C0D,
+            final_health: [-5, 100, 0, 0],
+            final_lives: [0, 0x0102_0304, 0, 0],
+            winner_team: teams.winner_team,
+            team_scores: teams.team_scores,
+            player_teams: teams.player_teams,
+            pickup_stats_commit: core::array::from_fn(|i| 0xE0 + i as u8),
+        };
+        let current = Bytes::from_slice(&env, &output.to_journal_bytes());
+        assert_eq!(current.len(), layout::SIZE as u32);
+        // Version 7 is the same journal up to the pickup stats commitment
+        let mut v7 = Bytes::from_slice(&env, &layout::encode_version(7).to_le_bytes());
+        v7.append(&current.slice(4..layout::V7.size() as u32));
+        let no_pickup_stats = chickenz_core::ProverOutput { pickup_stats_commit: [0; 32], ..output.clone() };
+        // Version 5 is the same journal up to the team result, which decodes to the same teams
+        let mut v5 = Bytes::from_slice(&env, &layout::encode_version(5).to_le_bytes());
+        v5.append(&current.slice(4..layout::V5.size() as u32));
+        // Version 3 is the same journal up to the final stats
+        let mut v3 = Bytes::from_slice(&env, &layout::encode_version(3).to_le_bytes());
+        v3.append(&current.slice(4..layout::V3.size() as u32));
+        let no_stats = chickenz_core::ProverOutput {
+            final_tick: 0,
+            final_health: [0; 4],
+            final_lives: [0; 4],
+            ..no_pickup_stats.clone()
+        };
+        // Version 0 is the same body without the version word, the map hash or the stats
+        let v0 = current.slice(4..layout::V1.size() as u32);
+        let arena = chickenz_core::ProverOutput { map_hash: [0; 32], ..no_stats.clone() };
+
+        let stats = FinalStats {
+            final_tick: 0x0A0B_0C0D,
+            player1_health: -5,
+            player2_health: 100,
+            player1_lives: 0,
+            player2_lives: 0x0102_0304,
+        };
+        assert_eq!(decode_final_stats(&decode(&env, &current).unwrap()), Some(stats));
+        assert_eq!(decode_final_stats(&decode(&env, &v3).unwrap()), None);
+        let team_result = TeamResult {
+            winner_team: winner,
+            team0_score: 7,
+            team1_score: 0x0102_0304,
+            player1_team: 0,
+            player2_team: 1,
+        };
+        assert_eq!(decode_team_result(&decode(&env, &current).unwrap()), Some(team_result));
+        assert_eq!(decode_team_result(&decode(&env, &v5).unwrap()), None);
+
+        let cases = [
+            (current, layout::CURRENT, &output),
+            (v7, layout::V7, &no_pickup_stats),
+            (v5, layout::V5, &no_pickup_stats),
+            (v3, layout::V3, &no_stats),
+            (v0, layout::V0, &arena),
+        ];
+        for (journal, l, expected) in cases {
+            let j = decode(&env, &journal).unwrap();
+            assert_eq!(j.layout, l);
+            assert_eq!(j.event_chain_head, None);
+            assert_eq!(layout::is_settleable_winner(j.winner), winner != -1);
+            assert_eq!(&chickenz_core::ProverOutput::from(j), expected);
+            assert_eq!(decode_result(&j).player2_env_kills, 0x0506_0708);
+        }
+    }
+}
+
+#[test]
+fn test_seed_commit_matches_prover() {
+    let env = Env::default();
+    for seed in [0, 42, u32::MAX] {
+        let commit = chickenz_core_mini::hash_seed_with(seed, |pre| {
+            env.crypto().sha256(&Bytes::from_slice(&env, pre)).to_array()
+        });
+        assert_eq!(commit, chickenz_core::hash_seed(seed));
+    }
+}
+
+/// Journals the guest committed for canonical scenarios, with the fields they decode to,
+/// written by `chickenz-host prove --emit-test-vector`. Regenerate them when the journal or the
+/// sim changes (the host's `committed_vectors_match_this_build` fails until then).
+const JOURNAL_VECTORS: &str = include_str!("../test_data/journal-vectors.json");
+
+fn hex_bytes(env: &Env, hex: &str) -> Bytes {
+    assert_eq!(hex.len() % 2, 0, "odd hex length");
+    let mut bytes = Bytes::new(env);
+    for i in (0..hex.len()).step_by(2) {
+        bytes.push_back(u8::from_str_radix(&hex[i..i + 2], 16).expect("not hex"));
+    }
+    bytes
+}
+
+fn hex32(env: &Env, hex: &str) -> [u8; 32] {
+    let mut out = [0u8; 32];
+    hex_bytes(env, hex).copy_into_slice(&mut out);
+    out
+}
+
+#[test]
+fn test_prover_journal_vectors_settle() {
+    let file: serde_json::Value = serde_json::from_str(JOURNAL_VECTORS).unwrap();
+    assert_eq!(file["journal_version"].as_u64(), Some(layout::VERSION as u64), "vectors predate this journal");
+    let vectors = file["vectors"].as_array().unwrap();
+    let mut winners = [false; 2];
+
+    for v in vectors {
+        let name = v["name"].as_str().unwrap();
+        let field = |key: &str| v[key].as_i64().unwrap_or_else(|| panic!("{name}: no {key}"));
+        let pair = |key: &str| [v[key][0].as_i64().unwrap(), v[key][1].as_i64().unwrap()];
+        let env = Env::default();
+        let client = setup_with_mocks(&env);
+        let (seed, session_id) = (field("seed") as u32, field("session_id") as u32);
+        let journal = hex_bytes(&env, v["journal"].as_str().unwrap());
+        let seed_commit = hex32(&env, v["seed_commit"].as_str().unwrap());
+
+        // The seed the match was started on is the one the journal commits to
+        let commit = chickenz_core_mini::hash_seed_with(seed, |pre| {
+            env.crypto().sha256(&Bytes::from_slice(&env, pre)).to_array()
+        });
+        assert_eq!(commit, seed_commit, "{name}: seed commit");
+
+        let j = decode(&env, &journal).unwrap_or_else(|e| panic!("{name}: {e:?}"));
+        assert_eq!(j.layout, layout::CURRENT, "{name}");
+        assert_eq!(j.winner as i64, field("winner"), "{name}: winner");
+        assert_eq!(j.session_id, session_id, "{name}: session");
+        assert_eq!(j.seed_commit, seed_commit, "{name}: seed commit");
+        assert_eq!([j.scores[0] as i64, j.scores[1] as i64], pair("scores"), "{name}: scores");
+        assert_eq!([j.env_kills[0] as i64, j.env_kills[1] as i64], pair("env_kills"), "{name}: env kills");
+        assert_eq!(j.transcript_hash, hex32(&env, v["transcript_hash"].as_str().unwrap()), "{name}");
+        assert_eq!(j.config_commit, hex32(&env, v["config_commit"].as_str().unwrap()), "{name}");
+        assert_eq!(j.map_hash, Some(hex32(&env, v["map_hash"].as_str().unwrap())), "{name}");
+        let pickup_stats = hex32(&env, v["pickup_stats_commit"].as_str().unwrap());
+        assert_eq!(j.pickup_stats_commit, Some(pickup_stats), "{name}");
+
+        let (config_commit, map_hash) = (j.config_commit, j.map_hash.unwrap());
+        start_session_with(&env, &client, session_id, seed_commit, config_commit, map_hash);
+        // The players commit the transcript's raw-input hash, which the host checks the vector carries
+        let transcript = BytesN::from_array(&env, &hex32(&env, v["transcript_hash"].as_str().unwrap()));
+        client.commit_transcript(&session_id, &transcript);
+        client.settle_match(&session_id, &admin(&env, &client), &Bytes::new(&env), &journal);
+        let (health, lives) = (pair("final_health"), pair("final_lives"));
+        let (scores, env_kills) = (pair("scores"), pair("env_kills"));
+        let m = client.get_match(&session_id);
+        assert!(m.settled, "{name}");
+        assert_eq!(game_hub_winner(&env, &client, session_id), Some(field("winner") == 0), "{name}");
+        let result = MatchResult {
+            player1_won: field("winner") == 0,
+            player1_score: scores[0] as u32,
+            player2_score: scores[1] as u32,
+            player1_env_kills: env_kills[0] as u32,
+            player2_env_kills: env_kills[1] as u32,
+        };
+        assert_eq!(m.result, Some(result), "{name}");
+        let stats = FinalStats {
+            final_tick: field("final_tick") as u32,
+            player1_health: health[0] as i32,
+            player2_health: health[1] as i32,
+            player1_lives: lives[0] as i32,
+            player2_lives: lives[1] as i32,
+        };
+        assert_eq!(m.final_stats, Some(stats), "{name}");
+        winners[field("winner") as usize] = true;
+    }
+    // Both registered players win somewhere, so a flipped winner encoding can't pass
+    assert_eq!(winners, [true, true]);
+}
+
+#[test]
+fn test_event_chain_journal_settles() {
+    let env = Env::default();
+    let client = setup_with_mocks(&env);
+    start_session(&env, &client, 7, [0xCC; 32]);
+
+    let mut journal = make_journal(&env, 1, [0xCC; 32], 7);
+    journal.append(&Bytes::from_slice(&env, &[0xEE; 32]));
+    assert_eq!(journal.len(), layout::SIZE_WITH_EVENT_CHAIN as u32);
+    // Any other trailer length is still malformed
+    let short = journal.slice(0..(layout::SIZE_WITH_EVENT_CHAIN - 4) as u32);
+    assert_eq!(
+        client.try_settle_match(&7, &admin(&env, &client), &Bytes::new(&env), &short),
+        Err(Ok(Error::InvalidJournal))
+    );
+
+    client.settle_match(&7, &admin(&env, &client), &Bytes::new(&env), &journal);
+    assert!(client.get_match(&7).settled);
+}
+
+#[test]
+fn test_draw_journal_rejected() {
+    let env = Env::default();
+    let client = setup_with_mocks(&env);
+    start_session(&env, &client, 9, [0xCC; 32]);
+
+    let draw = make_journal(&env, layout::WINNER_DRAW, [0xCC; 32], 9);
+    assert_eq!(
+        client.try_settle_match(&9, &admin(&env, &client), &Bytes::new(&env), &draw),
+        Err(Ok(Error::InvalidWinner))
+    );
+    assert!(!client.get_match(&9).settled);
+}
+
+#[test]
+fn test_v0_journal_settles_during_transition() {
+    let env = Env::default();
+    let client = setup_with_mocks(&env);
+    assert_eq!(client.journal_versions(), Vec::from_array(&env, [0, 1, 3, 5, 7]));
+    start_session(&env, &client, 7, [0xCC; 32]);
+    start_session(&env, &client, 8, [0xCC; 32]);
+
+    let mut v0 = make_v0_journal(&env, 1, [0xCC; 32], 7);
+    client.settle_match(&7, &admin(&env, &client), &Bytes::new(&env), &v0);
+    // With the event-chain trailer too
+    v0 = make_v0_journal(&env, 1, [0xCC; 32], 8);
+    v0.append(&Bytes::from_slice(&env, &[0xEE; 32]));
+    client.settle_match(&8, &admin(&env, &client), &Bytes::new(&env), &v0);
+
+    // Both versions store the same result
+    let current_env = Env::default();
+    let current_client = setup_with_mocks(&current_env);
+    start_session(&current_env, &current_client, 7, [0xCC; 32]);
+    let journal = make_journal(&current_env, 1, [0xCC; 32], 7);
+    current_client.settle_match(&7, &admin(&current_env, &current_client), &Bytes::new(&current_env), &journal);
+    assert_eq!(
+        client.get_settlement(&7).result,
+        current_client.get_settlement(&7).result
+    );
+}
+
+#[test]
+fn test_retired_journal_version_rejected() {
+    let env = Env::default();
+    let client = setup_with_mocks(&env);
+    start_session(&env, &client, 7, [0xCC; 32]);
+    client.set_journal_versions(&Vec::from_array(&env, [layout::VERSION]));
+    assert_eq!(client.journal_versions(), Vec::from_array(&env, [7]));
+
+    assert_eq!(
+        client.try_settle_match(&7, &admin(&env, &client), &Bytes::new(&env), &make_v0_journal(&env, 0, [0xCC; 32], 7)),
+        Err(Ok(Error::JournalVersionRejected))
+    );
+    client.settle_match(&7, &admin(&env, &client), &Bytes::new(&env), &make_journal(&env, 0, [0xCC; 32], 7));
+    assert!(client.get_match(&7).settled);
+}
+
+#[test]
+fn test_unknown_journal_version_rejected() {
+    let env = Env::default();
+    let client = setup_with_mocks(&env);
+    start_session(&env, &client, 7, [0xCC; 32]);
+
+    let mut journal = make_journal(&env, 0, [0xCC; 32], 7);
+    let mut bytes = [0u8; layout::SIZE];
+    journal.copy_into_slice(&mut bytes);
+    bytes[..4].copy_from_slice(&layout::encode_version(layout::WIDE.version + 1).to_le_bytes());
+    assert_eq!(
+        client.try_settle_match(&7, &admin(&env, &client), &Bytes::new(&env), &Bytes::from_slice(&env, &bytes)),
+        Err(Ok(Error::JournalVersionRejected))
+    );
+    journal = journal.slice(0..2);
+    // Too short to carry a version word at all
+    assert_eq!(
+        client.try_settle_match(&7, &admin(&env, &client), &Bytes::new(&env), &journal),
+        Err(Ok(Error::InvalidJournal))
+    );
+}
+
+#[test]
+fn test_set_journal_versions_rejects_unknown_version() {
+    let env = Env::default();
+    let client = setup_with_mocks(&env);
+    assert_eq!(
+        client.try_set_journal_versions(&Vec::from_array(&env, [1, 9])),
+        Err(Ok(Error::JournalVersionRejected))
+    );
+    // The wide layout is known but has no two-player escrow to settle
+    assert_eq!(
+        client.try_set_journal_versions(&Vec::from_array(&env, [1, layout::WIDE.version])),
+        Err(Ok(Error::JournalVersionRejected))
+    );
+    assert_eq!(client.journal_versions(), Vec::from_array(&env, [0, 1, 3, 5, 7]));
+}
+
+#[test]
+#[should_panic]
+fn test_set_journal_versions_requires_admin_auth() {
+    let env = Env::default();
+    let (contract_id, admin, game_hub, verifier, image_id) = setup_contract(&env);
+    let client = ChickenzContractClient::new(&env, &contract_id);
+    client.initialize(&admin, &game_hub, &verifier, &image_id);
+    client.set_journal_versions(&Vec::from_array(&env, [1]));
+}
+
+/// Commit-reveal match 7 between the returned players, committed to reveals [0x11; 32] and
+/// [0x22; 32].
+fn start_committed_session(env: &Env, client: &ChickenzContractClient) -> (Address, Address) {
+    let (player1, player2) = (Address::generate(env), Address::generate(env));
+    let (config, map) = match_rules(env);
+    client.start_match_committed(
+        &7,
+        &player1,
+        &player2,
+        &BytesN::from_array(env, &chickenz_core::commitment([0x11; 32])),
+        &BytesN::from_array(env, &chickenz_core::commitment([0x22; 32])),
+        &config,
+        &map,
+    );
+    (player1, player2)
+}
+
+#[test]
+fn test_commit_reveal_seeds_and_settles() {
+    let env = Env::default();
+    let client = setup_with_mocks(&env);
+    let (player1, player2) = start_committed_session(&env, &client);
+    assert_eq!(client.get_match(&7).seed_commit, BytesN::from_array(&env, &[0; 32]));
+
+    // Nothing settles, and nobody else reveals, until both players have
+    let seed = chickenz_core::derive_seed([0x11; 32], [0x22; 32]);
+    let journal = make_journal(&env, 0, chickenz_core::hash_seed(seed), 7);
+    let admin = admin(&env, &client);
+    assert_eq!(
+        client.try_settle_match(&7, &admin, &Bytes::new(&env), &journal),
+        Err(Ok(Error::SeedNotRevealed))
+    );
+    assert_eq!(
+        client.try_reveal(&7, &Address::generate(&env), &BytesN::from_array(&env, &[0x11; 32])),
+        Err(Ok(Error::Unauthorized))
+    );
+    assert_eq!(
+        client.try_reveal(&7, &player2, &BytesN::from_array(&env, &[0x11; 32])),
+        Err(Ok(Error::RevealMismatch))
+    );
+
+    client.reveal(&7, &player2, &BytesN::from_array(&env, &[0x22; 32]));
+    assert_eq!(
+        client.try_reveal(&7, &player2, &BytesN::from_array(&env, &[0x22; 32])),
+        Err(Ok(Error::AlreadyRevealed))
+    );
+    assert_eq!(client.get_match(&7).seed_commit, BytesN::from_array(&env, &[0; 32]));
+    client.reveal(&7, &player1, &BytesN::from_array(&env, &[0x11; 32]));
+
+    // The on-chain seed is the prover's
+    assert_eq!(client.get_match(&7).seed_commit, BytesN::from_array(&env, &chickenz_core::hash_seed(seed)));
+    let events = env.events().all();
+    assert_eq!(
+        events.last().unwrap(),
+        (client.address.clone(), (symbol_short!("seeded"), 7u32).into_val(&env), seed.into_val(&env))
+    );
+    let reveal = client.get_seed_reveal(&7);
+    assert_eq!(reveal.reveal1, Some(BytesN::from_array(&env, &[0x11; 32])));
+    assert_eq!(reveal.reveal2, Some(BytesN::from_array(&env, &[0x22; 32])));
+
+    // A proof replayed under another seed is still rejected
+    let other = make_journal(&env, 0, chickenz_core::hash_seed(seed ^ 1), 7);
+    assert_eq!(client.try_settle_match(&7, &admin, &Bytes::new(&env), &other), Err(Ok(Error::SeedMismatch)));
+    client.settle_match(&7, &admin, &Bytes::new(&env), &journal);
+    assert!(client.get_match(&7).settled);
+    assert_eq!(game_hub_winner(&env, &client, 7), Some(true));
+    assert_eq!(client.try_claim_reveal_timeout(&7), Err(Ok(Error::MatchAlreadySettled)));
+}
+
+#[test]
+fn test_reveal_timeout_forfeits_the_silent_player() {
+    let env = Env::default();
+    let client = setup_with_mocks(&env);
+    env.ledger().set_sequence_number(100);
+    let (_, player2) = start_committed_session(&env, &client);
+    let deadline = client.get_seed_reveal(&7).deadline_ledger;
+    assert_eq!(deadline, 100 + REVEAL_TIMEOUT_LEDGERS);
+
+    client.reveal(&7, &player2, &BytesN::from_array(&env, &[0x22; 32]));
+    env.ledger().set_sequence_number(deadline);
+    assert_eq!(client.try_claim_reveal_timeout(&7), Err(Ok(Error::RevealStillOpen)));
+
+    // Player 1 never reveals; too late now
+    env.ledger().set_sequence_number(deadline + 1);
+    let player1 = client.get_match(&7).player1;
+    assert_eq!(
+        client.try_reveal(&7, &player1, &BytesN::from_array(&env, &[0x11; 32])),
+        Err(Ok(Error::RevealClosed))
+    );
+    client.claim_reveal_timeout(&7);
+    let match_data = client.get_match(&7);
+    assert!(match_data.settled);
+    assert_eq!(match_data.journal_digest, None);
+    assert_eq!(game_hub_winner(&env, &client, 7), Some(false));
+    assert_eq!(client.try_claim_reveal_timeout(&7), Err(Ok(Error::MatchAlreadySettled)));
+
+    // get_settlement reports the forfeit, without a journal or scores
+    let info = client.get_settlement(&7);
+    assert!(info.forfeit);
+    assert_eq!((info.journal_digest, info.settled_ledger), (None, deadline + 1));
+    let result = MatchResult {
+        player1_won: false,
+        player1_score: 0,
+        player2_score: 0,
+        player1_env_kills: 0,
+        player2_env_kills: 0,
+    };
+    assert_eq!(info.result, Some(result));
+}
+
+#[test]
+fn test_reveal_refused_once_match_cancelled() {
+    let env = Env::default();
+    let client = setup_with_mocks(&env);
+    let (player1, _) = start_committed_session(&env, &client);
+    client.cancel_match(&7, &admin(&env, &client));
+    assert_eq!(
+        client.try_reveal(&7, &player1, &BytesN::from_array(&env, &[0x11; 32])),
+        Err(Ok(Error::MatchCancelled))
+    );
+    assert_eq!(client.get_seed_reveal(&7).reveal1, None);
+}
+
+#[test]
+fn test_reveal_timeout_needs_exactly_one_reveal() {
+    let env = Env::default();
+    let client = setup_with_mocks(&env);
+    start_committed_session(&env, &client);
+    env.ledger().set_sequence_number(REVEAL_TIMEOUT_LEDGERS + 1);
+    assert_eq!(client.try_claim_reveal_timeout(&7), Err(Ok(Error::SeedNotRevealed)));
+    assert!(!client.get_match(&7).settled);
+
+    // Operator-seeded matches have no reveal phase
+    start_session(&env, &client, 8, [0xCC; 32]);
+    assert_eq!(client.try_get_seed_reveal(&8), Err(Ok(Error::NotCommitReveal)));
+    assert_eq!(client.try_claim_reveal_timeout(&8), Err(Ok(Error::NotCommitReveal)));
+    assert_eq!(
+        client.try_reveal(&8, &client.get_match(&8).player1, &BytesN::from_array(&env, &[0; 32])),
+        Err(Ok(Error::NotCommitReveal))
+    );
+}
+
+#[test]
+fn test_reveal_commitment_matches_prover() {
+    let env = Env::default();
+    let sha256 = |pre: &[u8]| env.crypto().sha256(&Bytes::from_slice(&env, pre)).to_array();
+    let (a, b) = ([0x5A; 32], [0xA5; 32]);
+    assert_eq!(chickenz_core_mini::reveal_commitment_with(&a, sha256), chickenz_core::commitment(a));
+    assert_eq!(chickenz_core_mini::derive_seed_with(&a, &b, sha256), chickenz_core::derive_seed(a, b));
+}
+
+/// Contract wired to MockImageVerifier, on image 0xAA, with match 7 started on seed commit 0xCC.
+fn setup_with_image_verifier(env: &Env) -> ChickenzContractClient<'_> {
+    env.mock_all_auths();
+    let contract_id = env.register(ChickenzContract, ());
+    let client = ChickenzContractClient::new(env, &contract_id);
+    client.initialize(
+        &Address::generate(env),
+        &env.register(MockGameHub, ()),
+        &env.register(MockImageVerifier, ()),
+        &BytesN::from_array(env, &[0xAA; 32]),
+    );
+    start_session(env, &client, 7, [0xCC; 32]);
+    client
+}
+
+#[test]
+fn test_legacy_image_id_settles_pre_pickup_stats_journals() {
+    let env = Env::default();
+    let client = setup_with_image_verifier(&env);
+    start_session(&env, &client, 8, [0xCC; 32]);
+    // The guest that commits the pickup stats replaces 0xAA; its proofs in flight stay good
+    client.set_image_id(&BytesN::from_array(&env, &[0xBB; 32]));
+    client.set_legacy_image_id(&Some(BytesN::from_array(&env, &[0xAA; 32])));
+    assert_eq!(client.legacy_image_id(), Some(BytesN::from_array(&env, &[0xAA; 32])));
+
+    let old_seal = Bytes::from_slice(&env, &[0xAA; 32]);
+    let new_seal = Bytes::from_slice(&env, &[0xBB; 32]);
+    start_session(&env, &client, 9, [0xCC; 32]);
+    client.settle_match(&7, &admin(&env, &client), &old_seal, &make_v3_journal(&env, 0, [0xCC; 32], 7));
+    client.settle_match(&8, &admin(&env, &client), &new_seal, &make_journal(&env, 0, [0xCC; 32], 8));
+    client.settle_match(&9, &admin(&env, &client), &old_seal, &make_v5_journal(&env, 0, [0xCC; 32], 9));
+    start_session(&env, &client, 10, [0xCC; 32]);
+    client.settle_match(&10, &admin(&env, &client), &old_seal, &make_v7_journal(&env, 0, [0xCC; 32], 10));
+
+    // All settle with the same result; only the newer journals carry the stats and teams
+    let (old, new) = (client.get_settlement(&7), client.get_settlement(&8));
+    assert_eq!(old.result, new.result);
+    assert_eq!(old.final_stats, None);
+    assert_eq!(new.final_stats.map(|s| s.final_tick), Some(1730));
+    let v5 = client.get_settlement(&9);
+    assert_eq!((v5.final_stats, v5.team_result), (new.final_stats, None));
+    assert_eq!(new.team_result.map(|t| t.winner_team), Some(0));
+    assert_eq!(client.get_settlement(&10).team_result, new.team_result);
+
+    client.set_legacy_image_id(&None);
+    assert_eq!(client.legacy_image_id(), None);
+}
+
+#[test]
+#[should_panic]
+fn test_team_journal_ignores_legacy_image_id() {
+    let env = Env::default();
+    let client = setup_with_image_verifier(&env);
+    client.set_image_id(&BytesN::from_array(&env, &[0xBB; 32]));
+    client.set_legacy_image_id(&Some(BytesN::from_array(&env, &[0xAA; 32])));
+    // A proof from the legacy guest can't pass off a journal with the pickup stats commitment
+    let seal = Bytes::from_slice(&env, &[0xAA; 32]);
+    client.settle_match(&7, &admin(&env, &client), &seal, &make_journal(&env, 0, [0xCC; 32], 7));
+}
+
+#[test]
+#[should_panic]
+fn test_closed_window_verifies_pre_stats_journals_against_image_id() {
+    let env = Env::default();
+    let client = setup_with_image_verifier(&env);
+    client.set_image_id(&BytesN::from_array(&env, &[0xBB; 32]));
+    let seal = Bytes::from_slice(&env, &[0xAA; 32]);
+    client.settle_match(&7, &admin(&env, &client), &seal, &make_v3_journal(&env, 0, [0xCC; 32], 7));
+}
+
+#[test]
+fn test_players_commit_transcript_and_settle() {
+    let env = Env::default();
+    let client = setup_with_mocks(&env);
+    let (player1, player2) = (Address::generate(&env), Address::generate(&env));
+    let (config, map) = match_rules(&env);
+    client.start_match(&7, &player1, &player2, &BytesN::from_array(&env, &[0xCC; 32]), &config, &map);
+    start_session(&env, &client, 8, [0xCC; 32]);
+
+    // Both players sign off on the transcript make_journal commits
+    client.commit_transcript(&7, &BytesN::from_array(&env, &[0xBB; 32]));
+    let auths = env.auths();
+    assert_eq!(auths.len(), 2);
+    assert_eq!((auths[0].0.clone(), auths[1].0.clone()), (player1.clone(), player2.clone()));
+    assert_eq!(client.get_match(&7).transcript_commit, Some(BytesN::from_array(&env, &[0xBB; 32])));
+
+    // Only a player (or the admin) settles, and only with that transcript
+    let journal = make_journal(&env, 1, [0xCC; 32], 7);
+    assert_eq!(
+        client.try_settle_match(&7, &Address::generate(&env), &Bytes::new(&env), &journal),
+        Err(Ok(Error::Unauthorized))
+    );
+    let mut other = [0u8; layout::SIZE];
+    journal.copy_into_slice(&mut other);
+    other[layout::CURRENT.at(layout::TRANSCRIPT_HASH)] ^= 1;
+    assert_eq!(
+        client.try_settle_match(&7, &player2, &Bytes::new(&env), &Bytes::from_slice(&env, &other)),
+        Err(Ok(Error::TranscriptMismatch))
+    );
+    client.settle_match(&7, &player2, &Bytes::new(&env), &journal);
+    assert_eq!(game_hub_winner(&env, &client, 7), Some(false));
+    assert_eq!(
+        client.try_commit_transcript(&7, &BytesN::from_array(&env, &[0xBB; 32])),
+        Err(Ok(Error::MatchAlreadySettled))
+    );
+
+    // Without a commitment any transcript settles, as before
+    let mut uncommitted = [0u8; layout::SIZE];
+    make_journal(&env, 0, [0xCC; 32], 8).copy_into_slice(&mut uncommitted);
+    uncommitted[layout::CURRENT.at(layout::TRANSCRIPT_HASH)] ^= 1;
+    client.settle_match(&8, &admin(&env, &client), &Bytes::new(&env), &Bytes::from_slice(&env, &uncommitted));
+    assert!(client.get_match(&8).settled);
+}
+
+#[test]
+fn test_players_cancel_after_settlement_deadline() {
+    let env = Env::default();
+    let client = setup_with_mocks(&env);
+    env.ledger().set_sequence_number(100);
+    client.set_settlement_deadline_ledgers(&500);
+    assert_eq!(client.settlement_deadline_ledgers(), 500);
+    start_session(&env, &client, 7, [0xCC; 32]);
+    let match_data = client.get_match(&7);
+    assert_eq!((match_data.settlement_deadline_ledgers, match_data.cancelled), (500, false));
+
+    // Until the deadline has passed a player can't call it off, and outsiders never can
+    env.ledger().set_sequence_number(600);
+    assert_eq!(client.try_cancel_match(&7, &match_data.player1), Err(Ok(Error::SettlementStillOpen)));
+    env.ledger().set_sequence_number(601);
+    assert_eq!(client.try_cancel_match(&7, &Address::generate(&env)), Err(Ok(Error::Unauthorized)));
+    client.cancel_match(&7, &match_data.player2);
+    assert_eq!(
+        env.events().all().last().unwrap(),
+        (
+            client.address.clone(),
+            (symbol_short!("cancelled"), 7u32).into_val(&env),
+            match_data.player2.into_val(&env)
+        )
+    );
+    assert!(client.get_match(&7).cancelled);
+    // Neither player is reported the winner of a match that was never played out
+    assert_eq!(game_hub_winner(&env, &client, 7), None);
+
+    // A proof that turns up late no longer settles it
+    let journal = make_journal(&env, 0, [0xCC; 32], 7);
+    assert_eq!(
+        client.try_settle_match(&7, &admin(&env, &client), &Bytes::new(&env), &journal),
+        Err(Ok(Error::MatchCancelled))
+    );
+    assert_eq!(
+        client.try_commit_transcript(&7, &BytesN::from_array(&env, &[0xBB; 32])),
+        Err(Ok(Error::MatchCancelled))
+    );
+    assert_eq!(client.try_cancel_match(&7, &match_data.player1), Err(Ok(Error::MatchCancelled)));
+    assert_eq!(client.try_get_settlement(&7), Err(Ok(Error::MatchNotSettled)));
+}
+
+#[test]
+fn test_admin_cancels_before_deadline() {
+    let env = Env::default();
+    let client = setup_with_mocks(&env);
+    start_session(&env, &client, 7, [0xCC; 32]);
+    start_session(&env, &client, 8, [0xCC; 32]);
+    assert_eq!(client.get_match(&7).settlement_deadline_ledgers, SETTLEMENT_DEADLINE_LEDGERS);
+
+    client.cancel_match(&7, &admin(&env, &client));
+    assert!(client.get_match(&7).cancelled);
+    assert_eq!(game_hub_winner(&env, &client, 7), None);
+
+    // A settled match stays settled
+    client.settle_match(&8, &admin(&env, &client), &Bytes::new(&env), &make_journal(&env, 0, [0xCC; 32], 8));
+    assert_eq!(
+        client.try_cancel_match(&8, &admin(&env, &client)),
+        Err(Ok(Error::MatchAlreadySettled))
+    );
+    assert_eq!(game_hub_winner(&env, &client, 8), Some(true));
+}
+
+#[test]
+fn test_migrate_v6_keeps_transcript_commit() {
+    let env = Env::default();
+    let client = setup_with_mocks(&env);
+    let commit = BytesN::from_array(&env, &[0xBB; 32]);
+    env.as_contract(&client.address, || {
+        env.storage().instance().set(&DataKey::SchemaVersion, &6u32);
+        env.storage().temporary().set(
+            &DataKey::Match(5),
+            &MatchDataV6 {
+                player1: Address::generate(&env),
+                player2: Address::generate(&env),
+                seed_commit: BytesN::from_array(&env, &[0xCC; 32]),
+                settled: false,
+                journal_digest: None,
+                settled_ledger: 0,
+                created_ledger: 7,
+                expires_ledger: 7 + MATCH_TTL_LEDGERS,
+                result: None,
+                final_stats: None,
+                transcript_commit: Some(commit.clone()),
+            },
+        );
+    });
+
+    assert_eq!(client.migrate(&soroban_sdk::vec![&env, 5]), 1);
+    let m = client.get_match(&5);
+    assert_eq!((m.created_ledger, m.transcript_commit), (7, Some(commit)));
+    assert!(!m.cancelled);
+    assert_eq!(m.settlement_deadline_ledgers, SETTLEMENT_DEADLINE_LEDGERS);
+}
+
+#[test]
+fn test_migrate_v7_keeps_cancellation() {
+    let env = Env::default();
+    let client = setup_with_mocks(&env);
+    env.as_contract(&client.address, || {
+        env.storage().instance().set(&DataKey::SchemaVersion, &7u32);
+        env.storage().temporary().set(
+            &DataKey::Match(5),
+            &MatchDataV7 {
+                player1: Address::generate(&env),
+                player2: Address::generate(&env),
+                seed_commit: BytesN::from_array(&env, &[0xCC; 32]),
+                settled: false,
+                journal_digest: None,
+                settled_ledger: 0,
+                created_ledger: 7,
+                expires_ledger: 7 + MATCH_TTL_LEDGERS,
+                result: None,
+                final_stats: None,
+                transcript_commit: None,
+                cancelled: true,
+                settlement_deadline_ledgers: 100,
+            },
+        );
+    });
+
+    assert_eq!(client.migrate(&soroban_sdk::vec![&env, 5]), 1);
+    let m = client.get_match(&5);
+    assert!(m.cancelled);
+    assert_eq!((m.settlement_deadline_ledgers, m.team_result), (100, None));
+    // Started before the config and map were recorded, so its journal isn't checked against them
+    assert_eq!((m.config_commit, m.map_hash), (None, None));
+}
+
+#[test]
+fn test_start_match_emits_event() {
+    let env = Env::default();
+    let client = setup_with_mocks(&env);
+    let (player1, player2) = (Address::generate(&env), Address::generate(&env));
+    let seed_commit = BytesN::from_array(&env, &[0xCC; 32]);
+    let (config, map) = match_rules(&env);
+    client.start_match(&7, &player1, &player2, &seed_commit, &config, &map);
+    assert_eq!(
+        env.events().all(),
+        soroban_sdk::vec![
+            &env,
+            (
+                client.address.clone(),
+                (Symbol::new(&env, "match_started"), 7u32).into_val(&env),
+                (player1.clone(), player2.clone(), seed_commit.clone()).into_val(&env),
+            )
+        ]
+    );
+
+    // A rejected start announces nothing
+    assert_eq!(
+        client.try_start_match(&7, &player1, &player2, &seed_commit, &config, &map),
+        Err(Ok(Error::MatchAlreadyExists))
+    );
+    assert_eq!(env.events().all().len(), 0);
+}
+
+#[test]
+fn test_settle_match_emits_event() {
+    let env = Env::default();
+    let client = setup_with_mocks(&env);
+    start_session(&env, &client, 7, [0xCC; 32]);
+    let admin = admin(&env, &client);
+
+    // Failed settlements announce nothing
+    let wrong_seed = make_journal(&env, 1, [0xCD; 32], 7);
+    assert_eq!(
+        client.try_settle_match(&7, &admin, &Bytes::new(&env), &wrong_seed),
+        Err(Ok(Error::SeedMismatch))
+    );
+    assert_eq!(env.events().all().len(), 0);
+    assert_eq!(
+        client.try_settle_match(&7, &admin, &Bytes::new(&env), &make_journal(&env, layout::WINNER_DRAW, [0xCC; 32], 7)),
+        Err(Ok(Error::InvalidWinner))
+    );
+    assert_eq!(env.events().all().len(), 0);
+
+    let journal = make_journal(&env, 1, [0xCC; 32], 7);
+    client.settle_match(&7, &admin, &Bytes::new(&env), &journal);
+    let digest = BytesN::from_array(&env, &env.crypto().sha256(&journal).to_array());
+    assert_eq!(
+        env.events().all(),
+        soroban_sdk::vec![
+            &env,
+            (
+                client.address.clone(),
+                (Symbol::new(&env, "match_settled"), 7u32).into_val(&env),
+                (client.get_match(&7).player2, (3u32, 1u32), digest.clone()).into_val(&env),
+            )
+        ]
+    );
+    assert_eq!(client.get_settlement(&7).journal_digest, Some(digest));
 }
@@ -493,6 +493,28 @@ describe("lives system", () => {
     expect(s.matchOver).toBe(true);
     expect(s.tick).toBe(lingerStart + remaining);
   });
+
+  test("time-up tiebreak: lives, then score, then health, then player 0", () => {
+    // No sudden death, whose full close would end the match on lives alone
+    const timeUpConfig: MatchConfig = { ...config, suddenDeathStartTick: MATCH_DURATION_TICKS + 1 };
+    const winner = (lives: number[], score: number[], health: number[]) => {
+      const state = createInitialState(timeUpConfig);
+      const players = state.players.map((p, i) => ({ ...p, lives: lives[i]!, health: health[i]! }));
+      const s = step(
+        { ...state, players, score: new Map([[0, score[0]!], [1, score[1]!]]), tick: MATCH_DURATION_TICKS - 1 },
+        NO_INPUTS,
+        NO_INPUTS,
+        timeUpConfig,
+      );
+      expect(s.matchOver).toBe(true);
+      return s.winner;
+    };
+    expect(winner([1, 2], [5, 0], [100, 10])).toBe(1);
+    expect(winner([2, 2], [3, 1], [10, 100])).toBe(0);
+    expect(winner([2, 2], [1, 3], [100, 10])).toBe(1);
+    expect(winner([2, 2], [2, 2], [40, 90])).toBe(1);
+    expect(winner([2, 2], [2, 2], [90, 90])).toBe(0);
+  });
 });
 
 describe("sudden death", () => {
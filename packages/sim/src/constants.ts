@@ -48,6 +48,8 @@ export const TICK_DT_MS = 1000 / TICK_RATE; // ~16.667ms
 
 // ── Weapon Stats ──────────────────────────────────────────
 
+// Follows the prover's fixed-point table (`fp::WEAPON_STATS` in services/prover/core), the
+// source of truth: change a number there first.
 export const WEAPON_STATS: Record<WeaponType, WeaponStats> = {
   [WeaponType.Pistol]: {
     damage: 20,
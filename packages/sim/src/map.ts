@@ -9,13 +9,13 @@ export const ARENA: GameMap = {
     // Ground (2 tiles tall)
     { x: 0, y: 512, width: 960, height: 32 },
     // Lower platforms
-    { x: 128, y: 416, width: 176, height: 16 },
-    { x: 672, y: 416, width: 176, height: 16 },
+    { x: 128, y: 416, width: 176, height: 16, oneWay: true },
+    { x: 672, y: 416, width: 176, height: 16, oneWay: true },
     // Mid platform
-    { x: 352, y: 304, width: 256, height: 16 },
+    { x: 352, y: 304, width: 256, height: 16, oneWay: true },
     // Upper platforms
-    { x: 64, y: 208, width: 144, height: 16 },
-    { x: 752, y: 208, width: 144, height: 16 },
+    { x: 64, y: 208, width: 144, height: 16, oneWay: true },
+    { x: 752, y: 208, width: 144, height: 16, oneWay: true },
   ],
   spawnPoints: [
     { x: 144, y: 480 },
@@ -593,10 +593,16 @@ export function step(
     matchOver = true;
     const p0 = players[0]!;
     const p1 = players[1]!;
+    const s0 = prev.score.get(p0.id) ?? 0;
+    const s1 = prev.score.get(p1.id) ?? 0;
     if (p0.lives > p1.lives) {
       winner = p0.id;
     } else if (p1.lives > p0.lives) {
       winner = p1.id;
+    } else if (s0 > s1) {
+      winner = p0.id;
+    } else if (s1 > s0) {
+      winner = p1.id;
     } else if (p0.health > p1.health) {
       winner = p0.id;
     } else if (p1.health > p0.health) {
@@ -17,7 +17,12 @@ export const Button = {
   Right: 2,
   Jump: 4,
   Shoot: 8,
-  Taunt: 16,
+  /** Edge-triggered burst in the facing direction, off cooldown (fixed-point sim). */
+  Dash: 16,
+  /** With Jump on a one-way platform, drop through it (fixed-point sim). */
+  Down: 32,
+  /** Emote wheel slot 1 (`fp::button::EMOTE_MASK`): cosmetic, masked off before the sim reads it. */
+  Taunt: 64,
 } as const;
 
 export interface PlayerInput {
@@ -111,6 +116,7 @@ export interface Projectile {
   readonly vy: number;
   readonly lifetime: number;
   readonly weapon: WeaponType;
+  readonly projRadius?: number;             // hitbox radius (set by the WASM sim, for rendering)
 }
 
 // ── Map ─────────────────────────────────────────────────────
@@ -120,6 +126,10 @@ export interface Platform {
   readonly y: number;
   readonly width: number;
   readonly height: number;
+  /** Only collides from above in the fixed-point sim (DOWN + Jump drops through); ignored here. */
+  readonly oneWay?: boolean;
+  /** Contact damage every hazard interval in the fixed-point sim (spikes, lava); ignored here. */
+  readonly hazardDamage?: number;
 }
 
 export interface GameMap {
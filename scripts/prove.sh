@@ -65,7 +65,7 @@ echo "Transcript: $TRANSCRIPT"
 echo "Mode: ${RISC0_DEV_MODE:+dev }${HAS_LOCAL:+STARK}${HAS_LOCAL:-Groth16} $@"
 echo ""
 
-"$HOST_BIN" "$TRANSCRIPT" "$@"
+"$HOST_BIN" prove "$TRANSCRIPT" "$@"
 
 echo ""
 if [ -f "$PROVER_DIR/proof_artifacts.json" ]; then
@@ -2,18 +2,19 @@
 set -euo pipefail
 
 # Chickenz Start Match On-Chain
-# Usage: ./scripts/start-match.sh <session_id> <seed>
+# Usage: ./scripts/start-match.sh <session_id> <seed> <config_commit> <map_hash>
 #
-# Registers a match on the Game Hub with the given seed commitment.
+# Registers a match on the Game Hub with the given seed commitment, and the config commitment
+# and map hash its proof must commit (hex, as the host prints them) for settle_match to take it.
 
 CHICKENZ_CONTRACT="CDYU5GFNDBIFYWLW54QV3LPDNQTER6ID3SK4QCCBVUY7NU76ESBP7LZP"
 NETWORK="testnet"
 SOURCE="${STELLAR_SOURCE:-default}"
 
-if [ $# -lt 2 ]; then
-    echo "Usage: $0 <session_id> <seed>"
+if [ $# -lt 4 ]; then
+    echo "Usage: $0 <session_id> <seed> <config_commit> <map_hash>"
     echo ""
-    echo "Example: $0 42 42"
+    echo "Example: $0 42 42 <Config commit> <Map hash>  (from the host's output)"
     echo ""
     echo "Environment:"
     echo "  STELLAR_SOURCE    Stellar key name (default: 'default')"
@@ -22,6 +23,8 @@ fi
 
 SESSION_ID="$1"
 SEED="$2"
+CONFIG_COMMIT="$3"
+MAP_HASH="$4"
 
 # Compute seed_commit = SHA-256(seed as LE u32)
 SEED_COMMIT=$(python3 -c "
@@ -36,6 +39,8 @@ echo "=== Chickenz Start Match ==="
 echo "Session ID:  $SESSION_ID"
 echo "Seed:        $SEED"
 echo "Seed commit: $SEED_COMMIT"
+echo "Config:      $CONFIG_COMMIT"
+echo "Map hash:    $MAP_HASH"
 echo "Player:      $PLAYER"
 echo "Contract:    $CHICKENZ_CONTRACT"
 echo ""
@@ -49,7 +54,9 @@ stellar contract invoke \
     --session_id "$SESSION_ID" \
     --player1 "$PLAYER" \
     --player2 "$PLAYER" \
-    --seed_commit "$SEED_COMMIT"
+    --seed_commit "$SEED_COMMIT" \
+    --config_commit "$CONFIG_COMMIT" \
+    --map_hash "$MAP_HASH"
 
 echo ""
 echo "Match registered on Game Hub!"
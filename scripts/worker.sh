@@ -69,7 +69,7 @@ while true; do
   echo "  Proving..."
   PROOF_START=$(date +%s)
 
-  if (cd "$(dirname "$ARTIFACTS_FILE")" && "$PROVER_BINARY" "$TMPFILE" 2>&1 | tail -5); then
+  if (cd "$(dirname "$ARTIFACTS_FILE")" && "$PROVER_BINARY" prove "$TMPFILE" 2>&1 | tail -5); then
     PROOF_END=$(date +%s)
     echo "  Proof generated in $((PROOF_END - PROOF_START))s"
 
@@ -2,78 +2,104 @@
 
 risc0_zkvm::guest::entry!(main);
 
-use chickenz_core::fp::{self, ChunkProof, FpInput, MATCH_DURATION_TICKS};
+use chickenz_core::diag::{HashPrefix, LineBuf};
+use chickenz_core::fp::{self, ChunkProof, INPUT_BYTES, MATCH_DURATION_TICKS, MAX_CHUNK_INPUT_WORDS};
 use sha2::{Digest, Sha256};
 
-/// Max state bytes (conservative upper bound for encode_state output).
-const MAX_STATE_WORDS: usize = 256;
-/// Max chunk input: 360 ticks × 6 bytes = 2160 bytes = 540 u32 words.
-const MAX_CHUNK_INPUT_WORDS: usize = 540;
+/// Max state words: `fp::MAX_ENCODED_STATE_LEN` (v3 layout, full projectile array, lag
+/// history and the map) rounded up to whole words.
+const MAX_STATE_WORDS: usize = fp::MAX_ENCODED_STATE_LEN.div_ceil(4);
+
+/// Log a phase breadcrumb, formatted on the stack (no heap on the success path).
+fn breadcrumb(args: core::fmt::Arguments) {
+    risc0_zkvm::guest::env::log(LineBuf::<160>::format(args).as_str());
+}
 
 /// Chunk guest: replays N ticks from a given state, commits state hash chain.
 ///
 /// Input (all via read_slice):
 ///   [state_byte_len: u32, tick_count: u32]
 ///   [state_bytes padded to u32 words]
-///   [input_bytes (tick_count × 6) padded to u32 words]
+///   [input_bytes (tick_count × 3 × the state's player_count) padded to u32 words]
+///
+/// The state carries its map (any map, not just the arena), so the state bytes are the map
+/// input too; its hash is committed so the match guest can check every chunk played on the
+/// same one.
 ///
-/// Output (via commit_slice): ChunkProof as 30 u32 words (120 bytes)
+/// Output (via commit_slice): ChunkProof as `CHUNK_PROOF_WORDS` u32 words, stamped with this
+/// build's `fp::SIM_VERSION`, or `WIDE_CHUNK_PROOF_WORDS` for a match of more than two players
 fn main() {
     // 1. Read header
     let mut header = [0u32; 2];
     risc0_zkvm::guest::env::read_slice(&mut header);
     let state_byte_len = header[0] as usize;
     let tick_count = header[1] as usize;
+    breadcrumb(format_args!("chunk: header state_bytes={state_byte_len} ticks={tick_count}"));
 
     // 2. Read state bytes (fixed buffer, no heap)
     let state_word_count = (state_byte_len + 3) / 4;
+    assert!(
+        state_word_count <= MAX_STATE_WORDS,
+        "chunk: state is {state_byte_len} bytes, buffer holds {}",
+        MAX_STATE_WORDS * 4
+    );
     let mut state_words = [0u32; MAX_STATE_WORDS];
     risc0_zkvm::guest::env::read_slice(&mut state_words[..state_word_count]);
     let state_bytes: &[u8] = bytemuck::cast_slice(&state_words[..state_word_count]);
     let state_bytes = &state_bytes[..state_byte_len];
 
-    // 3. Read input bytes (fixed buffer, no heap)
-    let input_byte_len = tick_count * 6;
+    // 3. Decode state (it carries its map), hash it (streaming, no Vec)
+    let mut state = fp::decode_state_checked(state_bytes).unwrap_or_else(|issue| {
+        panic!("chunk: undecodable state ({state_byte_len} bytes): {issue}")
+    });
+    // Derived from the tick, but hashed: a boundary carrying other bounds is no state the sim reached
+    let bounds = fp::consistent_arena_bounds(&state);
+    assert!(
+        state.arena_bounds() == bounds,
+        "chunk: arena bounds {} at tick {}, expected {bounds}",
+        state.arena_bounds(),
+        state.tick
+    );
+    let state_hash_in = fp::hash_state(&state);
+    let map_hash = fp::map_hash(&state.map);
+    let tick_start = state.tick as u32;
+    let player_count = state.player_count as usize;
+    breadcrumb(format_args!(
+        "chunk: decoded {player_count}-player state at tick {tick_start}, hash_in {}, map {}",
+        HashPrefix(&state_hash_in),
+        HashPrefix(&map_hash)
+    ));
+
+    // 4. Read input bytes, `INPUT_BYTES` per player per tick (fixed buffer, no heap)
+    let tick_len = INPUT_BYTES * player_count;
+    let input_byte_len = tick_count * tick_len;
     let input_word_count = (input_byte_len + 3) / 4;
+    assert!(
+        input_word_count <= MAX_CHUNK_INPUT_WORDS,
+        "chunk: {tick_count} ticks need {input_byte_len} input bytes, buffer holds {}",
+        MAX_CHUNK_INPUT_WORDS * 4
+    );
     let mut input_words = [0u32; MAX_CHUNK_INPUT_WORDS];
     risc0_zkvm::guest::env::read_slice(&mut input_words[..input_word_count]);
     let input_bytes: &[u8] = bytemuck::cast_slice(&input_words[..input_word_count]);
     let input_bytes = &input_bytes[..input_byte_len];
 
-    // 4. Decode state, hash it (streaming, no Vec)
-    let mut state = fp::decode_state(state_bytes);
-    let state_hash_in = fp::hash_state(&state);
-    let tick_start = state.tick as u32;
-
-    // 5. Replay ticks + stream input hash in one pass
-    let map = fp::arena_map();
+    // 5. Replay ticks + stream input hash in one pass (the map rides in the state hash, which
+    //    the match guest chains back to a state built on the committed config's map). Only the
+    //    ticks stepped are hashed: the match guest checks the hash against the transcript's
+    //    ticks tick_start..tick_end
     let mut input_hasher = Sha256::new();
 
     for t in 0..tick_count {
-        let off = t * 6;
-        let tick_bytes = &input_bytes[off..off + 6];
+        let off = t * tick_len;
+        let tick_bytes = &input_bytes[off..off + tick_len];
 
         // Feed raw bytes to hasher
         input_hasher.update(tick_bytes);
 
-        let inputs = [
-            FpInput {
-                buttons: tick_bytes[0],
-                aim_x: tick_bytes[1] as i8,
-                aim_y: tick_bytes[2] as i8,
-            },
-            FpInput {
-                buttons: tick_bytes[3],
-                aim_x: tick_bytes[4] as i8,
-                aim_y: tick_bytes[5] as i8,
-            },
-        ];
-        fp::step_mut(&mut state, &inputs, &map);
+        let inputs = fp::tick_inputs(tick_bytes, player_count);
+        fp::step_mut(&mut state, &inputs[..player_count]);
         if state.match_over {
-            // Hash remaining tick bytes for integrity
-            if off + 6 < input_byte_len {
-                input_hasher.update(&input_bytes[off + 6..]);
-            }
             break;
         }
     }
@@ -92,7 +118,24 @@ fn main() {
         scores: state.score,
         match_over: state.match_over,
         winner: state.winner,
+        env_kills: state.env_kills,
+        map_hash,
+        health: state.health(),
+        lives: state.lives(),
+        sim_version: fp::SIM_VERSION,
+        pickup_stats_commit: state.pickup_stats_commit(),
     };
 
-    risc0_zkvm::guest::env::commit_slice(&proof.to_words());
+    breadcrumb(format_args!(
+        "chunk: replayed ticks {tick_start}..{}, match_over={}, input_hash {}, hash_out {}",
+        proof.tick_end,
+        proof.match_over,
+        HashPrefix(&input_hash),
+        HashPrefix(&state_hash_out)
+    ));
+    if player_count > 2 {
+        risc0_zkvm::guest::env::commit_slice(&proof.to_wide_words());
+    } else {
+        risc0_zkvm::guest::env::commit_slice(&proof.to_words());
+    }
 }
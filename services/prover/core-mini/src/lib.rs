@@ -0,0 +1,343 @@
+//! The slice of `chickenz-core` that settlement needs, without `std`, `alloc`, serde or sha2,
+//! so the Soroban contract (wasm32) can depend on it: the journal layout, the decode of a
+//! committed `ProverOutput`, the seed commitment and the constants both sides share.
+//! `chickenz-core` builds on the same functions, so the prover and the contract can't decode a
+//! journal differently.
+//!
+//! Hashing is left to the caller: the contract has `env.crypto().sha256`, the prover sha2.
+
+#![no_std]
+
+pub use chickenz_journal_layout as layout;
+
+/// Max players in one match (`fp::State::player_count`); the journal has a score and an
+/// env-kill slot for each. Every encoding of a two-player match is the one it had before the
+/// sim grew past two players.
+pub const MAX_PLAYERS: usize = 4;
+
+/// Longest journal of any known schema version: the wide body, map hash, stats, teams and
+/// pickup stats commitment plus the event-chain trailer.
+/// A buffer this size holds any journal `Journal::decode` accepts.
+pub const MAX_JOURNAL_SIZE: usize = layout::WIDE.size() + layout::HASH_LEN;
+
+/// `fp::map_hash` of the arena, the map every journal before schema version 3 was played on.
+pub const ARENA_MAP_HASH: [u8; 32] = [
+    0x7d, 0x6c, 0xfb, 0x1a, 0xa4, 0xaf, 0x5c, 0xa5, 0xfe, 0x68, 0x28, 0x10, 0xfb, 0xe4, 0x88, 0xd4,
+    0x13, 0x62, 0xa0, 0x8d, 0x8b, 0x1e, 0x59, 0x6c, 0x05, 0xfc, 0x3a, 0x80, 0x02, 0x79, 0x1c, 0xb0,
+];
+
+/// What the seed commitment hashes: the seed's little-endian bytes.
+pub const fn seed_preimage(seed: u32) -> [u8; 4] {
+    seed.to_le_bytes()
+}
+
+/// The seed commitment (`ProverOutput::seed_commit`): `seed_preimage` under the caller's SHA-256.
+pub fn hash_seed_with(seed: u32, sha256: impl FnOnce(&[u8]) -> [u8; 32]) -> [u8; 32] {
+    sha256(&seed_preimage(seed))
+}
+
+/// Length of one player's seed entropy in commit-reveal seeding.
+pub const REVEAL_LEN: usize = 32;
+
+/// Commitment to one player's seed entropy: SHA-256 (the caller's) of the 32-byte reveal.
+pub fn reveal_commitment_with(reveal: &[u8; REVEAL_LEN], sha256: impl FnOnce(&[u8]) -> [u8; 32]) -> [u8; 32] {
+    sha256(reveal)
+}
+
+/// What a commit-reveal seed hashes: player 1's reveal, then player 2's.
+pub fn reveals_preimage(reveal_a: &[u8; REVEAL_LEN], reveal_b: &[u8; REVEAL_LEN]) -> [u8; 2 * REVEAL_LEN] {
+    let mut pre = [0u8; 2 * REVEAL_LEN];
+    pre[..REVEAL_LEN].copy_from_slice(reveal_a);
+    pre[REVEAL_LEN..].copy_from_slice(reveal_b);
+    pre
+}
+
+/// Seed of a commit-reveal match: the first four bytes, little-endian, of the SHA-256 of
+/// `reveals_preimage`. Each player commits before seeing the other's reveal, so neither can
+/// steer it. The match then commits `hash_seed_with` of this seed, like any other.
+pub fn derive_seed_with(
+    reveal_a: &[u8; REVEAL_LEN],
+    reveal_b: &[u8; REVEAL_LEN],
+    sha256: impl FnOnce(&[u8]) -> [u8; 32],
+) -> u32 {
+    let h = sha256(&reveals_preimage(reveal_a, reveal_b));
+    u32::from_le_bytes([h[0], h[1], h[2], h[3]])
+}
+
+fn u32_at(b: &[u8], offset: usize) -> Option<u32> {
+    let c = b.get(offset..offset + 4)?;
+    Some(u32::from_le_bytes([c[0], c[1], c[2], c[3]]))
+}
+
+fn hash_at(b: &[u8], offset: usize) -> Option<[u8; 32]> {
+    b.get(offset..offset + layout::HASH_LEN)?.try_into().ok()
+}
+
+/// Schema version of a journal from its first word (which may be one this build doesn't
+/// know), or `None` when it's shorter than a word.
+pub fn journal_version(b: &[u8]) -> Option<u32> {
+    Some(layout::decode_version(u32_at(b, 0)?))
+}
+
+/// Layout of a journal of a known schema version that is long enough to decode.
+pub fn journal_layout(b: &[u8]) -> Option<layout::Layout> {
+    let l = layout::layout(journal_version(b)?)?;
+    (b.len() >= l.size()).then_some(l)
+}
+
+/// How a match ended, from schema version 5: the tick it ended on and each player's health and
+/// lives then. Slots past `player_count` are 0.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FinalStats {
+    pub tick: u32,
+    pub health: [i32; MAX_PLAYERS],
+    pub lives: [i32; MAX_PLAYERS],
+}
+
+/// Teams in a team result. The sim plays free-for-all today; a two-player match is read as
+/// one player per team.
+pub const TEAMS: usize = 2;
+
+/// The team result, from schema version 7: the winning team, each team's score and each
+/// player's team. Slots past `player_count` are 0.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TeamResult {
+    /// `layout::NO_WINNER_TEAM` when no team won.
+    pub winner_team: i32,
+    pub team_scores: [u32; TEAMS],
+    pub player_teams: [u32; MAX_PLAYERS],
+}
+
+impl TeamResult {
+    /// The team result of a free-for-all match: with two players, player 0 is team 0 and
+    /// player 1 team 1, so each team's score is its player's and the winning team is the
+    /// winner. With more players there are no teams to report, so no team wins.
+    pub fn solo(player_count: u8, winner: i32, scores: &[u32; MAX_PLAYERS]) -> Self {
+        if player_count != 2 {
+            return TeamResult { winner_team: layout::NO_WINNER_TEAM, ..TeamResult::default() };
+        }
+        TeamResult { winner_team: winner, team_scores: [scores[0], scores[1]], player_teams: [0, 1, 0, 0] }
+    }
+}
+
+/// A decoded journal: the fields of `ProverOutput`, the layout they were read with and the
+/// event-chain trailer, if any.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Journal {
+    pub layout: layout::Layout,
+    pub winner: i32,
+    /// Slots past `player_count` are 0.
+    pub scores: [u32; MAX_PLAYERS],
+    pub transcript_hash: [u8; 32],
+    pub seed_commit: [u8; 32],
+    pub session_id: u32,
+    pub config_commit: [u8; 32],
+    pub env_kills: [u32; MAX_PLAYERS],
+    /// 2 for the two-player layouts; the committed count for the wide ones.
+    pub player_count: u8,
+    /// `fp::map_hash` of the map played on, from schema version 3; older journals were all
+    /// played on the arena (`ARENA_MAP_HASH`).
+    pub map_hash: Option<[u8; 32]>,
+    /// Final tick, health and lives, from schema version 5.
+    pub stats: Option<FinalStats>,
+    /// Winning team, team scores and player teams, from schema version 7.
+    pub teams: Option<TeamResult>,
+    /// `fp::PickupStats::commitment` of the registered-order pickup stats, from schema
+    /// version 9.
+    pub pickup_stats_commit: Option<[u8; 32]>,
+    /// The final event-chain head, when the journal carries the trailer.
+    pub event_chain_head: Option<[u8; 32]>,
+}
+
+fn decode_stats(b: &[u8], l: layout::Layout) -> Option<FinalStats> {
+    let stat = |f: usize| u32_at(b, l.stats_at(f)?);
+    let wide = |f: usize| if l.is_wide() { stat(f).map(|v| v as i32) } else { Some(0) };
+    Some(FinalStats {
+        tick: stat(layout::FINAL_TICK)?,
+        health: [
+            stat(layout::FINAL_HEALTH_P0)? as i32, stat(layout::FINAL_HEALTH_P1)? as i32,
+            wide(layout::FINAL_HEALTH_P2)?, wide(layout::FINAL_HEALTH_P3)?,
+        ],
+        lives: [
+            stat(layout::FINAL_LIVES_P0)? as i32, stat(layout::FINAL_LIVES_P1)? as i32,
+            wide(layout::FINAL_LIVES_P2)?, wide(layout::FINAL_LIVES_P3)?,
+        ],
+    })
+}
+
+fn decode_teams(b: &[u8], l: layout::Layout) -> Option<TeamResult> {
+    let team = |f: usize| u32_at(b, l.teams_at(f)?);
+    Some(TeamResult {
+        winner_team: layout::decode_winner(team(layout::WINNER_TEAM)?),
+        team_scores: [team(layout::TEAM_SCORE_0)?, team(layout::TEAM_SCORE_1)?],
+        player_teams: [
+            team(layout::PLAYER_TEAM_P0)?, team(layout::PLAYER_TEAM_P1)?,
+            team(layout::PLAYER_TEAM_P2)?, team(layout::PLAYER_TEAM_P3)?,
+        ],
+    })
+}
+
+impl Journal {
+    /// Decode a journal of any known schema version, or `None` for an unknown version or a
+    /// journal too short for its layout. Doesn't check the exact length: settlement also wants
+    /// `Layout::is_valid_size`.
+    pub fn decode(b: &[u8]) -> Option<Self> {
+        let l = journal_layout(b)?;
+        let field = |f: usize| u32_at(b, l.at(f));
+        let wide = |f: usize| if l.is_wide() { field(f) } else { Some(0) };
+        Some(Journal {
+            layout: l,
+            winner: layout::decode_winner(field(layout::WINNER)?),
+            scores: [
+                field(layout::SCORE_P0)?, field(layout::SCORE_P1)?,
+                wide(layout::SCORE_P2)?, wide(layout::SCORE_P3)?,
+            ],
+            transcript_hash: hash_at(b, l.at(layout::TRANSCRIPT_HASH))?,
+            seed_commit: hash_at(b, l.at(layout::SEED_COMMIT))?,
+            session_id: field(layout::SESSION_ID)?,
+            config_commit: hash_at(b, l.at(layout::CONFIG_COMMIT))?,
+            env_kills: [
+                field(layout::ENV_KILLS_P0)?, field(layout::ENV_KILLS_P1)?,
+                wide(layout::ENV_KILLS_P2)?, wide(layout::ENV_KILLS_P3)?,
+            ],
+            player_count: if l.is_wide() { wide(layout::PLAYER_COUNT)? as u8 } else { 2 },
+            map_hash: match l.map_hash_at() {
+                Some(at) => Some(hash_at(b, at)?),
+                None => None,
+            },
+            stats: match l.stats_at(layout::FINAL_TICK) {
+                Some(_) => Some(decode_stats(b, l)?),
+                None => None,
+            },
+            teams: match l.teams_at(layout::WINNER_TEAM) {
+                Some(_) => Some(decode_teams(b, l)?),
+                None => None,
+            },
+            pickup_stats_commit: match l.pickup_stats_at(layout::PICKUP_STATS_COMMIT) {
+                Some(at) => Some(hash_at(b, at)?),
+                None => None,
+            },
+            event_chain_head: hash_at(b, l.event_chain_head()),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn put(b: &mut [u8], offset: usize, word: u32) {
+        b[offset..offset + 4].copy_from_slice(&word.to_le_bytes());
+    }
+
+    fn journal(l: layout::Layout) -> [u8; MAX_JOURNAL_SIZE] {
+        let mut b = [0u8; MAX_JOURNAL_SIZE];
+        if l.header > 0 {
+            put(&mut b, 0, layout::encode_version(l.version));
+        }
+        put(&mut b, l.at(layout::WINNER), layout::encode_winner(1));
+        put(&mut b, l.at(layout::SCORE_P0), 3);
+        put(&mut b, l.at(layout::SCORE_P1), 4);
+        b[l.at(layout::SEED_COMMIT)..][..32].fill(0xBB);
+        put(&mut b, l.at(layout::SESSION_ID), 77);
+        put(&mut b, l.at(layout::ENV_KILLS_P1), 2);
+        if let Some(at) = l.map_hash_at() {
+            b[at..][..32].fill(0x3A);
+        }
+        if l.is_wide() {
+            put(&mut b, l.at(layout::PLAYER_COUNT), 3);
+            put(&mut b, l.at(layout::SCORE_P2), 5);
+            put(&mut b, l.at(layout::ENV_KILLS_P2), 1);
+        }
+        if let Some(at) = l.stats_at(0) {
+            put(&mut b, at + layout::FINAL_TICK, 1730);
+            put(&mut b, at + layout::FINAL_HEALTH_P1, 60);
+            put(&mut b, at + layout::FINAL_LIVES_P0, (-1i32) as u32);
+            put(&mut b, at + layout::FINAL_LIVES_P1, 2);
+            if l.is_wide() {
+                put(&mut b, at + layout::FINAL_LIVES_P2, 1);
+            }
+        }
+        if let Some(at) = l.teams_at(0) {
+            put(&mut b, at + layout::WINNER_TEAM, layout::encode_winner(1));
+            put(&mut b, at + layout::TEAM_SCORE_0, 3);
+            put(&mut b, at + layout::TEAM_SCORE_1, 4);
+            put(&mut b, at + layout::PLAYER_TEAM_P1, 1);
+        }
+        if let Some(at) = l.pickup_stats_at(layout::PICKUP_STATS_COMMIT) {
+            b[at..][..32].fill(0x5E);
+        }
+        b[l.event_chain_head()..][..32].fill(0xEE);
+        b
+    }
+
+    #[test]
+    fn decodes_every_known_version() {
+        for v in layout::KNOWN_VERSIONS {
+            let l = layout::layout(v).unwrap();
+            let b = journal(l);
+            let j = Journal::decode(&b[..l.size()]).unwrap();
+            assert_eq!((j.layout, j.winner, j.session_id, j.seed_commit), (l, 1, 77, [0xBB; 32]));
+            assert_eq!(j.event_chain_head, None);
+            assert_eq!(j.map_hash, l.map_hash.then_some([0x3A; 32]));
+            let lives = if l.is_wide() { [-1, 2, 1, 0] } else { [-1, 2, 0, 0] };
+            let stats = FinalStats { tick: 1730, health: [0, 60, 0, 0], lives };
+            assert_eq!(j.stats, l.stats.then_some(stats));
+            let teams = TeamResult { winner_team: 1, team_scores: [3, 4], player_teams: [0, 1, 0, 0] };
+            assert_eq!(j.teams, l.teams.then_some(teams));
+            assert_eq!(j.pickup_stats_commit, l.pickup_stats.then_some([0x5E; 32]));
+            if l.is_wide() {
+                assert_eq!((j.scores, j.env_kills, j.player_count), ([3, 4, 5, 0], [0, 2, 1, 0], 3));
+            } else {
+                assert_eq!((j.scores, j.env_kills, j.player_count), ([3, 4, 0, 0], [0, 2, 0, 0], 2));
+            }
+            let with_chain = Journal::decode(&b[..l.size() + layout::HASH_LEN]).unwrap();
+            assert_eq!(with_chain.event_chain_head, Some([0xEE; 32]));
+            assert_eq!(Journal { event_chain_head: None, ..with_chain }, j);
+            assert_eq!(Journal::decode(&b[..l.size() - 4]), None);
+        }
+    }
+
+    #[test]
+    fn solo_teams_mirror_a_two_player_result() {
+        let t = TeamResult::solo(2, 1, &[3, 4, 0, 0]);
+        assert_eq!((t.winner_team, t.team_scores, t.player_teams), (1, [3, 4], [0, 1, 0, 0]));
+        // The committed team block of a 1v1 reads back as the same result
+        let b = journal(layout::CURRENT);
+        assert_eq!(Journal::decode(&b[..layout::SIZE]).unwrap().teams, Some(t));
+        let ffa = TeamResult::solo(3, 2, &[1, 2, 5, 0]);
+        assert_eq!(ffa, TeamResult { winner_team: layout::NO_WINNER_TEAM, ..TeamResult::default() });
+    }
+
+    #[test]
+    fn rejects_unknown_versions_and_short_journals() {
+        let mut b = journal(layout::V1);
+        put(&mut b, 0, layout::encode_version(11));
+        assert_eq!(journal_version(&b), Some(11));
+        assert_eq!(Journal::decode(&b), None);
+        assert_eq!(journal_version(&[1, 0]), None);
+        assert_eq!(Journal::decode(&[]), None);
+    }
+
+    #[test]
+    fn seed_commitment_hashes_the_le_seed() {
+        let seen = hash_seed_with(0x0102_0304, |pre| {
+            assert_eq!(pre, [4, 3, 2, 1]);
+            [7; 32]
+        });
+        assert_eq!(seen, [7; 32]);
+    }
+
+    #[test]
+    fn derived_seed_reads_the_hash_of_both_reveals() {
+        let seed = derive_seed_with(&[1; 32], &[2; 32], |pre| {
+            assert_eq!(pre[..32], [1; 32]);
+            assert_eq!(pre[32..], [2; 32]);
+            let mut h = [0xEE; 32];
+            h[..4].copy_from_slice(&[4, 3, 2, 1]);
+            h
+        });
+        assert_eq!(seed, 0x0102_0304);
+        assert_eq!(reveal_commitment_with(&[5; 32], |pre| [pre[0]; 32]), [5; 32]);
+    }
+}
@@ -0,0 +1,86 @@
+//! Headless batch simulation: plays many scripted matches back to back on one reused State,
+//! then micro-benchmarks `reset_state` against building a fresh State per match.
+//!
+//! Pass a lag (ticks) to measure the per-tick cost of lag-compensated hit checks against the
+//! default run (native proxy for the extra zkVM cycles).
+//!
+//! Usage:
+//!   cargo run --release -p chickenz-core --example batch-sim -- [matches] [lag_ticks]
+
+use std::hint::black_box;
+use std::time::Instant;
+
+use chickenz_core::fp::{self, button, FpInput, FpMatchConfig};
+
+/// Cheap deterministic per-tick inputs so matches actually play out.
+fn scripted_inputs(seed: u32, tick: i32) -> [FpInput; 2] {
+    let h = seed.wrapping_mul(0x9E37_79B9) ^ (tick as u32).wrapping_mul(0x85EB_CA6B);
+    let pick = |bits: u32, toward: u8| {
+        let mut buttons = toward | button::SHOOT;
+        if bits & 0x10 == 0 { buttons |= button::JUMP; }
+        FpInput { buttons, aim_x: if toward == button::RIGHT { 1 } else { -1 }, aim_y: 0 }
+    };
+    let p0 = if (h >> 3) & 3 == 0 { button::LEFT } else { button::RIGHT };
+    let p1 = if (h >> 7) & 3 == 0 { button::RIGHT } else { button::LEFT };
+    [pick(h, p0), pick(h >> 11, p1)]
+}
+
+fn main() {
+    let matches: u32 = std::env::args()
+        .nth(1)
+        .map(|s| s.parse().expect("matches must be a number"))
+        .unwrap_or(2000);
+    let lag: u8 = std::env::args()
+        .nth(2)
+        .map(|s| s.parse().expect("lag_ticks must be a number"))
+        .unwrap_or(0);
+
+    let map = fp::arena_map();
+    let cfg = FpMatchConfig { lag_ticks: [lag, lag], ..FpMatchConfig::default() };
+
+    // Match loop: one State for the whole batch
+    let start = Instant::now();
+    let mut state = fp::create_initial_state(0, &map);
+    let mut wins = [0u32; 3]; // p0, p1, draw
+    let mut total_ticks = 0u64;
+    for seed in 0..matches {
+        fp::reset_state(&mut state, seed, &map, &cfg);
+        while !state.match_over {
+            let inputs = scripted_inputs(seed, state.tick);
+            fp::step_mut(&mut state, &inputs);
+        }
+        total_ticks += state.tick as u64;
+        wins[match state.winner { 0 => 0, 1 => 1, _ => 2 }] += 1;
+    }
+    let elapsed = start.elapsed();
+    eprintln!("=== Batch sim: {matches} matches, lag {lag} ===");
+    eprintln!("P0 wins: {}, P1 wins: {}, draws: {}", wins[0], wins[1], wins[2]);
+    eprintln!(
+        "{} ticks in {:.1} ms ({:.0} ticks/s)",
+        total_ticks,
+        elapsed.as_secs_f64() * 1000.0,
+        total_ticks as f64 / elapsed.as_secs_f64()
+    );
+    eprintln!("{:.1} ns/tick", elapsed.as_nanos() as f64 / total_ticks as f64);
+
+    // Micro-benchmark: match setup only
+    const ITERS: u32 = 1_000_000;
+    let start = Instant::now();
+    for seed in 0..ITERS {
+        let s = fp::create_initial_state_cfg(
+            seed, black_box(&map), cfg.initial_lives, cfg.match_duration, cfg.sudden_death,
+        );
+        black_box(&s);
+    }
+    let fresh = start.elapsed();
+
+    let start = Instant::now();
+    for seed in 0..ITERS {
+        fp::reset_state(black_box(&mut state), seed, black_box(&map), &cfg);
+    }
+    let reset = start.elapsed();
+
+    eprintln!("=== Setup cost ({ITERS} iterations) ===");
+    eprintln!("create_initial_state_cfg: {:.1} ns/match", fresh.as_nanos() as f64 / ITERS as f64);
+    eprintln!("reset_state:              {:.1} ns/match", reset.as_nanos() as f64 / ITERS as f64);
+}
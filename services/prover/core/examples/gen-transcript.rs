@@ -1,8 +1,12 @@
 //! Generates test transcript JSON files for the prover host.
 //!
 //! Usage:
-//!   cargo run -p chickenz-core --example gen-transcript -- [idle|combat] > transcript.json
+//!   cargo run -p chickenz-core --example gen-transcript -- [idle|combat|short|bot-vs-bot|bot-vs-idle] > transcript.json
+//!
+//! The bot modes play `chickenz_core::bot` on the fixed-point sim, for transcripts with the
+//! shots, stomps and pickups of a real match.
 
+use chickenz_core::bot::{self, Pilot};
 use chickenz_core::*;
 
 fn main() {
@@ -42,8 +46,18 @@ fn main() {
             // Short 100-tick idle match for quick proof testing
             vec![[NULL_INPUT; 2]; 100]
         }
+        "bot-vs-bot" | "bot-vs-idle" => {
+            let pilots = if mode == "bot-vs-bot" { [Pilot::Bot; 2] } else { [Pilot::Bot, Pilot::Idle] };
+            let cfg = fp::FpMatchConfig::default();
+            // Past time-up, so a match still lingering over its last death gets to finish
+            let ticks = config.match_duration_ticks + fp::DEATH_LINGER_TICKS as u32;
+            bot::bot_match(config.seed, &cfg, pilots, ticks)
+                .iter()
+                .map(|tick| tick.map(|i| PlayerInput { buttons: i.buttons, aim_x: i.aim_x as f64, aim_y: i.aim_y as f64 }))
+                .collect()
+        }
         _ => {
-            eprintln!("Unknown mode: {}. Use 'idle', 'combat', or 'short'", mode);
+            eprintln!("Unknown mode: {}. Use 'idle', 'combat', 'short', 'bot-vs-bot' or 'bot-vs-idle'", mode);
             std::process::exit(1);
         }
     };
@@ -69,7 +83,24 @@ fn main() {
         state.players[0].lives, state.players[1].lives
     );
 
+    // What the prover replays: the fixed-point sim (the bot modes were played on it)
+    let fp_input = fp::FpProverInput {
+        session_id: 0,
+        seed: config.seed,
+        mirrored: false,
+        transcript: transcript.iter().map(|tick| tick.map(|i| quantize_input(&i))).collect(),
+        map: None,
+        gen_seed: None,
+        rules: None,
+    };
+    let fp_state = fp::run_streaming(&fp::encode_raw_input(&fp_input)).state;
+    eprintln!(
+        "Fixed-point: tick {}, match over {}, winner {}, scores P0={} P1={}",
+        fp_state.tick, fp_state.match_over, fp_state.registered_winner(), fp_state.score[0], fp_state.score[1]
+    );
+
     let input = ProverInput {
+        session_id: 0,
         config,
         transcript,
     };
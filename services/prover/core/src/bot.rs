@@ -0,0 +1,195 @@
+//! A deterministic bot for the fixed-point sim, for test and benchmark transcripts that look
+//! like real matches: shots, stomps, pickups and the zone instead of idling.
+//!
+//! [`bot_input`] decides one player's input from the state alone, in integer math, drawing
+//! its hesitations from the fixed-point PRNG through the caller's `rng`, so a bot match
+//! replays identically anywhere. [`bot_match`] plays one and records the transcript.
+
+use crate::fp::{self, button, flag, Fp, FpInput, FpMatchConfig, State, NULL_INPUT, ONE, PLAYER_HEIGHT, PLAYER_WIDTH};
+
+/// Horizontal slack around a goal before the bot stops walking to it.
+const ARRIVE: Fp = 8 * ONE;
+/// Distance the bot keeps from its target while it has a weapon to shoot with.
+const STANDOFF: Fp = 160 * ONE;
+/// How close to the zone edge the bot gets before retreating to the middle.
+const ZONE_MARGIN: Fp = 48 * ONE;
+/// Height difference that counts as another floor: jump up to it, or drop down.
+const FLOOR_GAP: Fp = 48 * ONE;
+
+/// Who plays a seat in [`bot_match`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Pilot {
+    Bot,
+    Idle,
+}
+
+/// The input `player_idx` plays this tick: shake off a rider, retreat from the zone, fetch a
+/// pickup while unarmed, otherwise close in on the nearest opponent and shoot along the
+/// nearest of the eight aim directions, jumping when walled in or the target is a floor up.
+/// Advances `rng` once.
+pub fn bot_input(state: &State, player_idx: usize, rng: &mut u32) -> FpInput {
+    let (roll, next) = fp::prng_int_range(*rng, 0, 99);
+    *rng = next;
+    let me = &state.players[player_idx];
+    if me.state_flags & flag::ALIVE == 0 || state.match_over {
+        return NULL_INPUT;
+    }
+    // Jumps and shake-off presses only count on their edges, so they alternate with releases
+    let even = state.tick % 2 == 0;
+    let (cx, cy) = (me.x + PLAYER_WIDTH / 2, me.y + PLAYER_HEIGHT / 2);
+
+    if me.stomped_by >= 0 {
+        let shake = if even { button::LEFT } else { button::RIGHT };
+        let jump = if even { button::JUMP } else { 0 };
+        return FpInput { buttons: shake | jump, aim_x: 0, aim_y: 0 };
+    }
+
+    let target = nearest_opponent(state, player_idx);
+    let armed = me.weapon != fp::WEAPON_NONE;
+    let pickup = (!armed).then(|| nearest_pickup(state, cx, cy)).flatten();
+
+    // Where to stand: out of the zone first, then a pickup, then in range of the target
+    let zone = state.arena_bounds();
+    let near_top = zone.top > 0 && cy < zone.top + ZONE_MARGIN;
+    let near_bottom = zone.bottom < state.map.height && cy > zone.bottom - ZONE_MARGIN;
+    let goal = if cx < zone.left + ZONE_MARGIN || cx > zone.right - ZONE_MARGIN {
+        Some(((zone.left + zone.right) / 2, cy))
+    } else if near_top || near_bottom {
+        Some((cx, (zone.top + zone.bottom) / 2))
+    } else if let Some(p) = pickup {
+        Some(p)
+    } else {
+        target.map(|t| {
+            let (tx, ty) = center(state, t);
+            let side = if cx < tx { -1 } else { 1 };
+            // A fifth of the time the bot charges in, which is how stomps happen
+            let gap = if roll < 20 || !armed { 0 } else { STANDOFF };
+            (tx + side * gap, ty)
+        })
+    };
+
+    let mut buttons = 0;
+    if let Some((gx, gy)) = goal {
+        let dx = gx - cx;
+        // One tick in ten the bot hesitates and lets its momentum carry it
+        if dx.abs() > ARRIVE && roll >= 10 {
+            buttons |= if dx > 0 { button::RIGHT } else { button::LEFT };
+        }
+        let walled = buttons != 0 && me.vx == 0 && me.grounded;
+        if even && (walled || (gy < cy - FLOOR_GAP && me.grounded) || (me.wall_sliding && gy < cy)) {
+            buttons |= button::JUMP;
+        } else if even && gy > cy + FLOOR_GAP && me.grounded && dx.abs() < STANDOFF {
+            buttons |= button::DOWN | button::JUMP;
+        }
+    }
+
+    let (mut aim_x, mut aim_y) = (0, 0);
+    if let Some(t) = target {
+        let (tx, ty) = center(state, t);
+        let (dx, dy) = (tx - cx, ty - cy);
+        aim_x = dx.signum() as i8;
+        aim_y = if dy.abs() > dx.abs() / 2 { dy.signum() as i8 } else { 0 };
+        if aim_y != 0 && dx.abs() < dy.abs() / 2 {
+            aim_x = 0;
+        }
+        // Holds fire a quarter of the time, so ammo and cooldowns vary
+        if roll >= 25 && me.shoot_cooldown == 0 {
+            buttons |= button::SHOOT;
+        }
+    }
+    FpInput { buttons, aim_x, aim_y }
+}
+
+fn center(state: &State, idx: usize) -> (Fp, Fp) {
+    let p = &state.players[idx];
+    (p.x + PLAYER_WIDTH / 2, p.y + PLAYER_HEIGHT / 2)
+}
+
+/// Index of the closest live opponent (taxicab distance, lowest index on a tie).
+fn nearest_opponent(state: &State, me: usize) -> Option<usize> {
+    let (cx, cy) = center(state, me);
+    (0..state.player_count as usize)
+        .filter(|&i| i != me && state.players[i].state_flags & flag::ALIVE != 0)
+        .min_by_key(|&i| {
+            let (x, y) = center(state, i);
+            (x - cx).abs() + (y - cy).abs()
+        })
+}
+
+/// Position of the closest pickup that can be collected now.
+fn nearest_pickup(state: &State, cx: Fp, cy: Fp) -> Option<(Fp, Fp)> {
+    state.weapon_pickups[..state.pickup_count as usize]
+        .iter()
+        .filter(|p| p.respawn_timer <= 0)
+        .map(|p| (p.x, p.y))
+        .min_by_key(|&(x, y)| (x - cx).abs() + (y - cy).abs())
+}
+
+/// Play `cfg` on the arena from `seed` with each player's `pilots` entry until the match ends
+/// or `max_ticks` pass, and return the transcript. The bots' draws come from their own PRNG
+/// streams, seeded from `seed`, so the same arguments give the same transcript.
+pub fn bot_match(seed: u32, cfg: &FpMatchConfig, pilots: [Pilot; 2], max_ticks: u32) -> Vec<[FpInput; 2]> {
+    let map = cfg.oriented_map(&fp::arena_map());
+    let mut state = fp::create_initial_state_with(seed, &map, cfg);
+    let mut rngs = [seed ^ 0xB07_0000, seed ^ 0xB07_0001];
+    let mut transcript = Vec::new();
+    while !state.match_over && transcript.len() < max_ticks as usize {
+        let mut inputs = [NULL_INPUT; 2];
+        for (i, input) in inputs.iter_mut().enumerate() {
+            if pilots[i] == Pilot::Bot {
+                *input = bot_input(&state, i, &mut rngs[i]);
+            }
+        }
+        fp::step_mut(&mut state, &inputs);
+        transcript.push(inputs);
+    }
+    transcript
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fp::{encode_raw_input, run_streaming, FpProverInput, MATCH_DURATION_TICKS};
+
+    fn replay(seed: u32, transcript: &[[FpInput; 2]]) -> State {
+        let input = FpProverInput {
+            session_id: 0,
+            seed,
+            mirrored: false,
+            transcript: transcript.to_vec(),
+            map: None,
+            gen_seed: None,
+            rules: None,
+        };
+        run_streaming(&encode_raw_input(&input)).state
+    }
+
+    fn bytes(transcript: &[[FpInput; 2]]) -> Vec<u8> {
+        transcript.iter().flatten().flat_map(|i| [i.buttons, i.aim_x as u8, i.aim_y as u8]).collect()
+    }
+
+    #[test]
+    fn same_seed_same_transcript() {
+        let cfg = FpMatchConfig::default();
+        let a = bot_match(42, &cfg, [Pilot::Bot; 2], MATCH_DURATION_TICKS as u32);
+        let b = bot_match(42, &cfg, [Pilot::Bot; 2], MATCH_DURATION_TICKS as u32);
+        assert_eq!(bytes(&a), bytes(&b));
+        assert_ne!(bytes(&a), bytes(&bot_match(43, &cfg, [Pilot::Bot; 2], MATCH_DURATION_TICKS as u32)));
+
+        // The bots actually play: they move, jump and shoot, and the replay ends the match
+        let pressed = |b: u8| a.iter().flatten().any(|i| i.buttons & b != 0);
+        assert!(pressed(button::LEFT) && pressed(button::RIGHT) && pressed(button::JUMP) && pressed(button::SHOOT));
+        assert!(replay(42, &a).match_over);
+    }
+
+    #[test]
+    fn bot_beats_an_idle_player_before_time_up() {
+        for seed in [1, 42, 777] {
+            let transcript = bot_match(seed, &FpMatchConfig::default(), [Pilot::Bot, Pilot::Idle], MATCH_DURATION_TICKS as u32);
+            let state = replay(seed, &transcript);
+            assert!(state.match_over, "seed {seed}");
+            assert!(state.tick < MATCH_DURATION_TICKS, "seed {seed}: time-up at {}", state.tick);
+            assert_eq!(state.winner, 0, "seed {seed}");
+        }
+    }
+}
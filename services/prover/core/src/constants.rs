@@ -7,9 +7,36 @@ pub const GRAVITY: f64 = 0.5;
 pub const PLAYER_SPEED: f64 = 4.0;
 pub const ACCELERATION: f64 = 0.8;
 pub const DECELERATION: f64 = 0.6;
-pub const JUMP_VELOCITY: f64 = -12.0;
+pub const JUMP_VELOCITY: f64 = -10.5;
 pub const MAX_FALL_SPEED: f64 = 12.0;
 
+// Double jump
+pub const MAX_JUMPS: i32 = 2;
+
+// Wall slide & wall jump (same as `fp::WALL_*`)
+pub const WALL_SLIDE_SPEED: f64 = 2.0;
+pub const WALL_JUMP_VX: f64 = 7.0;
+pub const WALL_JUMP_VY: f64 = -10.0;
+/// How far past a solid platform's side a player still counts as against it.
+pub const WALL_SLIDE_BAND: f64 = 2.0;
+
+// Stomp (same as `fp::STOMP_*`)
+/// A ridden player loses STOMP_DAMAGE_PER_HIT on ticks that are a multiple of this.
+pub const STOMP_DAMAGE_INTERVAL: u32 = 2;
+pub const STOMP_DAMAGE_PER_HIT: i32 = 1;
+/// Each alternating LEFT/RIGHT press adds this to the shake-off meter, which decays by
+/// STOMP_SHAKE_DECAY a tick and breaks free at STOMP_SHAKE_THRESHOLD.
+pub const STOMP_SHAKE_PER_PRESS: i32 = 17;
+pub const STOMP_SHAKE_THRESHOLD: i32 = 100;
+pub const STOMP_SHAKE_DECAY: i32 = 1;
+/// Ticks a ridden player auto-runs one way before turning around.
+pub const STOMP_AUTO_RUN_MIN: i32 = 20;
+pub const STOMP_AUTO_RUN_MAX: i32 = 60;
+/// Ticks a player that got rid of its rider can't be ridden again.
+pub const STOMP_COOLDOWN_TICKS: i32 = 90;
+/// Ticks the ridden pair may fall before the rider lets go.
+pub const STOMP_AIRBORNE_DETACH_TICKS: u8 = 12;
+
 // Player hitbox
 pub const PLAYER_WIDTH: f64 = 24.0;
 pub const PLAYER_HEIGHT: f64 = 32.0;
@@ -17,11 +44,14 @@ pub const PLAYER_HEIGHT: f64 = 32.0;
 // Legacy projectile defaults
 pub const PROJECTILE_SPEED: f64 = 8.0;
 pub const PROJECTILE_LIFETIME: i32 = 90;
+#[deprecated(note = "cooldown is per weapon: use `weapon_stats(weapon).cooldown`")]
 pub const SHOOT_COOLDOWN: i32 = 15;
+#[deprecated(note = "radius is per weapon: use `weapon_stats(weapon).proj_radius`")]
 pub const PROJECTILE_RADIUS: f64 = 4.0;
 
 // Health / combat
 pub const MAX_HEALTH: i32 = 100;
+#[deprecated(note = "damage is per weapon (pistol does 20): use `weapon_stats(weapon).damage`")]
 pub const PROJECTILE_DAMAGE: i32 = 25;
 
 // Respawn
@@ -51,6 +81,21 @@ pub const WEAPON_ROTATION: [WeaponType; 5] = [
     WeaponType::SMG,
 ];
 
+/// Respawn odds per `WEAPON_ROTATION` entry, taken from `fp::WEAPON_RESPAWN_WEIGHTS` (indexed by
+/// fixed-point weapon id), so the fixed-point-only grenade is left out.
+pub const WEAPON_RESPAWN_WEIGHTS: [i32; WEAPON_ROTATION.len()] = {
+    let mut weights = [0; WEAPON_ROTATION.len()];
+    let mut i = 0;
+    while i < weights.len() {
+        weights[i] = crate::fp::WEAPON_RESPAWN_WEIGHTS[WEAPON_ROTATION[i] as usize];
+        i += 1;
+    }
+    weights
+};
+
+/// Weapon numbers of the f64 sim. `fp::WEAPON_STATS` is the source of truth: a change goes there
+/// first, then here (in px rather than 1/256 px), which `tests::weapon_stats_match_the_fp_table`
+/// enforces.
 pub fn weapon_stats(weapon: WeaponType) -> WeaponStats {
     match weapon {
         WeaponType::Pistol => WeaponStats {
@@ -63,6 +108,7 @@ pub fn weapon_stats(weapon: WeaponType) -> WeaponStats {
             spread_deg: 0.0,
             splash_radius: 0.0,
             splash_damage: 0,
+            proj_radius: 2.0,
         },
         WeaponType::Shotgun => WeaponStats {
             damage: 12,
@@ -74,6 +120,7 @@ pub fn weapon_stats(weapon: WeaponType) -> WeaponStats {
             spread_deg: 15.0,
             splash_radius: 0.0,
             splash_damage: 0,
+            proj_radius: 2.0,
         },
         WeaponType::Sniper => WeaponStats {
             damage: 80,
@@ -85,10 +132,11 @@ pub fn weapon_stats(weapon: WeaponType) -> WeaponStats {
             spread_deg: 0.0,
             splash_radius: 0.0,
             splash_damage: 0,
+            proj_radius: 0.0,
         },
         WeaponType::Rocket => WeaponStats {
             damage: 50,
-            speed: 5.0,
+            speed: 7.0,
             cooldown: 45,
             lifetime: 120,
             ammo: 4,
@@ -96,6 +144,7 @@ pub fn weapon_stats(weapon: WeaponType) -> WeaponStats {
             spread_deg: 0.0,
             splash_radius: 40.0,
             splash_damage: 25,
+            proj_radius: 6.0,
         },
         WeaponType::SMG => WeaponStats {
             damage: 10,
@@ -107,6 +156,64 @@ pub fn weapon_stats(weapon: WeaponType) -> WeaponStats {
             spread_deg: 0.0,
             splash_radius: 0.0,
             splash_damage: 0,
+            proj_radius: 2.0,
         },
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fp::{self, Fp};
+
+    /// The fixed-point weapon `w` is. No wildcard arm, so a new `WeaponType` doesn't compile
+    /// until it is paired with its `fp::WEAPON_STATS` entry, and so checked below.
+    fn fp_weapon(w: WeaponType) -> i8 {
+        match w {
+            WeaponType::Pistol => fp::WEAPON_PISTOL,
+            WeaponType::Shotgun => fp::WEAPON_SHOTGUN,
+            WeaponType::Sniper => fp::WEAPON_SNIPER,
+            WeaponType::Rocket => fp::WEAPON_ROCKET,
+            WeaponType::SMG => fp::WEAPON_SMG,
+        }
+    }
+
+    /// `v` px in fixed point; a value the fixed-point table can't hold exactly is a drift too.
+    fn to_fp(v: f64) -> Fp {
+        let scaled = v * fp::ONE as f64;
+        assert_eq!(scaled.fract(), 0.0, "{v} px is no whole number of 1/256 px");
+        scaled as Fp
+    }
+
+    #[test]
+    fn weapon_stats_match_the_fp_table() {
+        let types: Vec<WeaponType> = (0..).map_while(WeaponType::from_i32).collect();
+        assert_eq!(types.len(), WEAPON_ROTATION.len());
+        for w in types {
+            assert_eq!(fp_weapon(w) as i32, w as i32, "{w:?} id");
+            let (f64_stats, fp_stats) = (weapon_stats(w), fp::fp_weapon_stats(fp_weapon(w)));
+            let fields = [
+                ("damage", f64_stats.damage, fp_stats.damage),
+                ("speed", to_fp(f64_stats.speed), fp_stats.speed),
+                ("cooldown", f64_stats.cooldown, fp_stats.cooldown),
+                ("lifetime", f64_stats.lifetime, fp_stats.lifetime),
+                ("ammo", f64_stats.ammo, fp_stats.ammo),
+                ("pellets", f64_stats.pellets, fp_stats.pellets),
+                ("splash_radius", to_fp(f64_stats.splash_radius), fp_stats.splash_radius),
+                ("splash_damage", f64_stats.splash_damage, fp_stats.splash_damage),
+                ("proj_radius", to_fp(f64_stats.proj_radius), fp_stats.proj_radius),
+            ];
+            for (field, f64_value, fp_value) in fields {
+                assert_eq!(f64_value, fp_value, "{w:?} {field}: weapon_stats vs fp::WEAPON_STATS");
+            }
+        }
+    }
+
+    #[test]
+    fn respawn_weights_match_the_fp_table() {
+        for (i, &w) in WEAPON_ROTATION.iter().enumerate() {
+            let fp_slot = fp::WEAPON_ROTATION.iter().position(|&fw| fw == fp_weapon(w)).unwrap();
+            assert_eq!(WEAPON_RESPAWN_WEIGHTS[i], fp::WEAPON_RESPAWN_WEIGHTS[fp_slot], "{w:?}");
+        }
+    }
+}
@@ -0,0 +1,75 @@
+//! Allocation-free formatting for zkVM guest diagnostics.
+//!
+//! Guests report progress through `env::log` breadcrumbs and fail through panics whose
+//! messages carry the values behind the broken invariant. Both are formatted with these
+//! helpers so a breadcrumb costs a stack buffer, not a heap allocation, on the success path.
+
+use core::fmt;
+
+/// The first 4 bytes of a hash as 8 hex characters: enough to tell two hashes apart in a log.
+#[derive(Clone, Copy)]
+pub struct HashPrefix<'a>(pub &'a [u8; 32]);
+
+impl fmt::Display for HashPrefix<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for b in &self.0[..4] {
+            write!(f, "{b:02x}")?;
+        }
+        Ok(())
+    }
+}
+
+/// One line formatted into an `N`-byte stack buffer. Output that doesn't fit is cut at the
+/// last whole character.
+pub struct LineBuf<const N: usize> {
+    buf: [u8; N],
+    len: usize,
+}
+
+impl<const N: usize> LineBuf<N> {
+    pub fn format(args: fmt::Arguments<'_>) -> Self {
+        let mut line = LineBuf { buf: [0; N], len: 0 };
+        // write_str never fails; truncation is silent
+        let _ = fmt::write(&mut line, args);
+        line
+    }
+
+    pub fn as_str(&self) -> &str {
+        // Only whole UTF-8 characters are ever copied in
+        core::str::from_utf8(&self.buf[..self.len]).unwrap_or("")
+    }
+}
+
+impl<const N: usize> fmt::Write for LineBuf<N> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let mut take = s.len().min(N - self.len);
+        while !s.is_char_boundary(take) {
+            take -= 1;
+        }
+        self.buf[self.len..self.len + take].copy_from_slice(&s.as_bytes()[..take]);
+        self.len += take;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hash_prefix_is_eight_hex_chars() {
+        let mut h = [0u8; 32];
+        h[..5].copy_from_slice(&[0xde, 0xad, 0x0b, 0x07, 0xff]);
+        let line = LineBuf::<32>::format(format_args!("{}", HashPrefix(&h)));
+        assert_eq!(line.as_str(), "dead0b07");
+    }
+
+    #[test]
+    fn line_buf_truncates_at_a_char_boundary() {
+        let line = LineBuf::<8>::format(format_args!("tick {}..{}", 120, 480));
+        assert_eq!(line.as_str(), "tick 120");
+        // 'é' is two bytes: the half that would fit is dropped
+        let line = LineBuf::<6>::format(format_args!("chunké"));
+        assert_eq!(line.as_str(), "chunk");
+    }
+}
@@ -0,0 +1,155 @@
+//! Chunk proof journal.
+
+use super::*;
+
+/// Chunk proof journal — what each chunk guest commits.
+/// Fixed-size: 212 bytes = 53 u32 words, or `WIDE_CHUNK_PROOF_WORDS` for a chunk of a match
+/// of more than two players.
+#[derive(Clone, Debug)]
+pub struct ChunkProof {
+    pub state_hash_in: [u8; 32],
+    pub state_hash_out: [u8; 32],
+    /// SHA-256 of the raw input bytes of the ticks replayed, `tick_start..tick_end`.
+    pub input_hash: [u8; 32],
+    pub tick_start: u32,
+    pub tick_end: u32,
+    pub scores: [u32; MAX_PLAYERS],
+    pub match_over: bool,
+    pub winner: i32,
+    /// `State::env_kills` at the end of the chunk (internal player order, like `scores`).
+    pub env_kills: [u32; MAX_PLAYERS],
+    /// `map_hash` of the map the chunk replayed on (the state's), so the match guest can check
+    /// every chunk of a match used the same one.
+    pub map_hash: [u8; 32],
+    /// Each player's health at the end of the chunk (internal player order, like `scores`).
+    pub health: [i32; MAX_PLAYERS],
+    /// Each player's lives at the end of the chunk.
+    pub lives: [i32; MAX_PLAYERS],
+    /// `SIM_VERSION` the chunk guest was built with, so the match guest can refuse a chunk
+    /// replayed under other rules. 0 in a 44-word journal from before it was committed.
+    pub sim_version: u32,
+    /// `State::pickup_stats_commit` at the end of the chunk. All zero in a journal from before
+    /// it was committed; those carry an older `sim_version`, which the match guest refuses.
+    pub pickup_stats_commit: [u8; 32],
+}
+
+pub const CHUNK_PROOF_WORDS: usize = 53;
+/// The wide chunk journal: the 53 words, then the scores, env kills, health and lives of
+/// players 2..
+pub const WIDE_CHUNK_PROOF_WORDS: usize = CHUNK_PROOF_WORDS + 4 * (MAX_PLAYERS - 2);
+/// The chunk guest's input buffer: 360 ticks × 12 bytes (four players) = 4320 bytes = 1080
+/// u32 words, or 720 ticks of a two-player match.
+pub const MAX_CHUNK_INPUT_WORDS: usize = 1080;
+
+impl ChunkProof {
+    /// Encode as 53 u32 words for commit_slice.
+    pub fn to_words(&self) -> [u32; CHUNK_PROOF_WORDS] {
+        let mut w = [0u32; CHUNK_PROOF_WORDS];
+        for i in 0..8 {
+            let off = i * 4;
+            w[i] = u32::from_le_bytes([
+                self.state_hash_in[off], self.state_hash_in[off+1],
+                self.state_hash_in[off+2], self.state_hash_in[off+3],
+            ]);
+        }
+        for i in 0..8 {
+            let off = i * 4;
+            w[8+i] = u32::from_le_bytes([
+                self.state_hash_out[off], self.state_hash_out[off+1],
+                self.state_hash_out[off+2], self.state_hash_out[off+3],
+            ]);
+        }
+        for i in 0..8 {
+            let off = i * 4;
+            w[16+i] = u32::from_le_bytes([
+                self.input_hash[off], self.input_hash[off+1],
+                self.input_hash[off+2], self.input_hash[off+3],
+            ]);
+        }
+        w[24] = self.tick_start;
+        w[25] = self.tick_end;
+        w[26] = self.scores[0];
+        w[27] = self.scores[1];
+        w[28] = self.match_over as u32;
+        w[29] = self.winner as u32;
+        w[30] = self.env_kills[0];
+        w[31] = self.env_kills[1];
+        for i in 0..8 {
+            let off = i * 4;
+            w[32+i] = u32::from_le_bytes([
+                self.map_hash[off], self.map_hash[off+1],
+                self.map_hash[off+2], self.map_hash[off+3],
+            ]);
+        }
+        w[40] = self.health[0] as u32;
+        w[41] = self.health[1] as u32;
+        w[42] = self.lives[0] as u32;
+        w[43] = self.lives[1] as u32;
+        w[44] = self.sim_version;
+        for i in 0..8 {
+            let off = i * 4;
+            w[45+i] = u32::from_le_bytes([
+                self.pickup_stats_commit[off], self.pickup_stats_commit[off+1],
+                self.pickup_stats_commit[off+2], self.pickup_stats_commit[off+3],
+            ]);
+        }
+        w
+    }
+
+    /// Encode as `WIDE_CHUNK_PROOF_WORDS` words: `to_words`, then `scores[2..]`,
+    /// `env_kills[2..]`, `health[2..]` and `lives[2..]`.
+    pub fn to_wide_words(&self) -> [u32; WIDE_CHUNK_PROOF_WORDS] {
+        let mut w = [0u32; WIDE_CHUNK_PROOF_WORDS];
+        w[..CHUNK_PROOF_WORDS].copy_from_slice(&self.to_words());
+        let extra = MAX_PLAYERS - 2;
+        let at = |i: usize| CHUNK_PROOF_WORDS + i * extra;
+        w[at(0)..at(1)].copy_from_slice(&self.scores[2..]);
+        w[at(1)..at(2)].copy_from_slice(&self.env_kills[2..]);
+        for i in 0..extra {
+            w[at(2) + i] = self.health[2 + i] as u32;
+            w[at(3) + i] = self.lives[2 + i] as u32;
+        }
+        w
+    }
+
+    /// Decode from journal bytes (212 bytes = 53 u32 words as LE, or the wide journal). A
+    /// 176-byte journal from before the sim version was committed decodes with version 0, and
+    /// a 180-byte one from before the pickup stats were committed with their commitment zeroed.
+    pub fn from_journal_bytes(b: &[u8]) -> Self {
+        let hash_at = |off: usize| -> [u8; 32] {
+            let mut h = [0u8; 32];
+            h.copy_from_slice(&b[off..off+32]);
+            h
+        };
+        let u32_at = |off: usize| -> u32 {
+            u32::from_le_bytes([b[off], b[off+1], b[off+2], b[off+3]])
+        };
+        let mut proof = ChunkProof {
+            state_hash_in: hash_at(0),
+            state_hash_out: hash_at(32),
+            input_hash: hash_at(64),
+            tick_start: u32_at(96),
+            tick_end: u32_at(100),
+            scores: widen([u32_at(104), u32_at(108)], 0),
+            match_over: u32_at(112) != 0,
+            winner: u32_at(116) as i32,
+            env_kills: widen([u32_at(120), u32_at(124)], 0),
+            map_hash: hash_at(128),
+            health: widen([u32_at(160) as i32, u32_at(164) as i32], 0),
+            lives: widen([u32_at(168) as i32, u32_at(172) as i32], 0),
+            sim_version: if b.len() >= 180 { u32_at(176) } else { 0 },
+            pickup_stats_commit: if b.len() >= CHUNK_PROOF_WORDS * 4 { hash_at(180) } else { [0; 32] },
+        };
+        if b.len() >= WIDE_CHUNK_PROOF_WORDS * 4 {
+            let extra = MAX_PLAYERS - 2;
+            let at = |field: usize, i: usize| (CHUNK_PROOF_WORDS + field * extra + i) * 4;
+            for i in 0..extra {
+                proof.scores[2 + i] = u32_at(at(0, i));
+                proof.env_kills[2 + i] = u32_at(at(1, i));
+                proof.health[2 + i] = u32_at(at(2, i)) as i32;
+                proof.lives[2 + i] = u32_at(at(3, i)) as i32;
+            }
+        }
+        proof
+    }
+}
@@ -0,0 +1,231 @@
+//! Fixed-point arithmetic, versions and tuning constants, button/flag bits.
+
+/// Max projectiles alive at once. With weapons (shotgun 5 pellets), increase cap.
+pub const MAX_PROJECTILES: usize = 24;
+/// Max weapon pickups on the map: one per `NUM_WEAPON_SPAWNS` slot, then room for a weapon
+/// dropped by each of `MAX_PLAYERS`.
+pub const MAX_WEAPON_PICKUPS: usize = 8;
+/// Max health pickups on the map: one per `Map::health_spawns` slot.
+pub const MAX_HEALTH_PICKUPS: usize = 2;
+/// Max players in one match (`State::player_count`); shared with the journal decode.
+pub use chickenz_core_mini::MAX_PLAYERS;
+
+/// Gameplay ruleset version. Bump whenever a change alters simulation output for the same
+/// seed + transcript, so replays and proofs can be matched to the rules they were played under.
+///
+/// - 0: baseline
+/// - 1: per-weapon projectile hitbox radius (`FpWeaponStats::proj_radius`)
+/// - 2: opt-in lag-compensated hits (`FpMatchConfig::lag_ticks`); state encoding appends
+///   the lag config (and position history when enabled)
+/// - 3: mirrored-map matches (`FpMatchConfig::mirrored`); state encoding appends the flag
+/// - 4: `Player::invincibility_source`; state encoding appends one byte per player
+/// - 5: stomp rider detaches on a JUMP edge or once the victim has been airborne for
+///   `STOMP_AIRBORNE_DETACH_TICKS`; state encoding appends `stomp_airborne_ticks` per player
+/// - 6: opt-in wall stamina (`FpMatchConfig::stamina`); state encoding appends the flag and
+///   `Player::stamina` per player
+/// - 7: per-weapon owner-immunity window (`FpWeaponStats::owner_immunity_ticks`); own-shot
+///   deaths score nobody; state encoding appends `owner_immune_until` per live projectile
+/// - 8: seed-derived side assignment (`State::side_swap`); the journal winner and scores are
+///   in registered player order; state encoding appends the flag
+/// - 9: `encode_state`/`hash_state` default to `StateFormat::V2` (packed players); same
+///   gameplay, new state hashes (`hash_state_as(.., StateFormat::V1)` recovers the old ones)
+/// - 10: after the result is decided (linger, `match_over`) only the winner moves; the
+///   loser's inputs no longer reach the state
+/// - 11: weighted pickup respawns (`WEAPON_RESPAWN_WEIGHTS`) instead of a uniform draw
+/// - 12: `State` owns its map and `StateFormat::V3` carries it, so state hashes cover the map
+/// - 13: zone deaths are counted in `State::env_kills` (committed to the journal); v3 state
+///   encoding carries them ahead of the map
+/// - 14: opt-in pickup despawn (`FpMatchConfig::active_despawn`); v3 state encoding carries the
+///   flag and `WeaponPickup::active_timer` per slot
+/// - 15: `State::final_blow` marks a linger started by the match-deciding elimination; v3 state
+///   encoding carries it
+/// - 16: grenade (`WEAPON_GRENADE`): projectile gravity and bounces (`FpWeaponStats::gravity`,
+///   `bounces`), splash driven by the weapon table; v3 state encoding carries
+///   `Projectile::bounces_left` per slot
+/// - 17: time-up ties on lives go to the higher score before the higher health
+/// - 18: one-way platforms (`Platform::one_way`, the arena's five floating ones) that DOWN +
+///   JUMP drops through; the v3 map encoding carries the flags
+/// - 19: knockback (`FpWeaponStats::knockback`): hits push the victim along the shot, splash
+///   away from the blast; a knocked stomp mount throws its rider
+/// - 20: hazard platforms (`Platform::hazard_damage`) hurt standing or side contact every
+///   `HAZARD_DAMAGE_INTERVAL` ticks, an environment kill; the v3 map encoding carries the damage
+/// - 21: new shots are resolved against players where they spawn, before their first move, so
+///   a point-blank shot can't spawn past a touching (or overlapping) opponent
+/// - 22: stomp links stay two-sided: a mount killed by the zone or a hazard drops its rider the
+///   next tick instead of taking stomp damage (and lives) while dead, and in a stack of three or
+///   more a mount that loses its rider keeps its own mount
+/// - 23: emote bits (`button::EMOTE_MASK`) are masked off every input before it is played;
+///   `prev_buttons` no longer records them
+/// - 24: the stomp cooldown only keeps off the rider the victim last got rid of
+///   (`Player::stomp_cooldown_from`); v3 state encoding carries it per player slot
+/// - 25: health pickups at the map's `Map::health_spawns` (none on the arena or generated
+///   maps); `encode_state`/`hash_state` default to `StateFormat::V4`, which carries them and
+///   the spawns
+/// - 26: dash (`button::DASH`, `DASH_*`): an edge-triggered horizontal burst with reduced
+///   gravity and a per-player cooldown (`Player::dash_cooldown`, carried by v4 state encoding)
+/// - 27: `State::pickup_stats` tallies weapon pickups, time holding each weapon and the ammo
+///   given up for a pickup; v4 state encoding carries them, so state hashes change
+/// - 28: a player who dies holding a weapon with rounds left drops it as a pickup holding them
+///   (`WeaponPickup::dropped_ammo`), gone after `DROPPED_WEAPON_DESPAWN_TICKS`;
+///   `MAX_WEAPON_PICKUPS` doubles to make room, and v4 state encoding carries the new slots
+/// - 29: `PickupStats::damage_pacing` (damage per 10 s of play) joins the committed stats
+/// - 30: the zone closes per `FpMatchConfig::zone_mode` (`zone_mode::PINCH` as before);
+///   `State::arena_top`/`arena_bottom` bound it vertically, and v4 state encoding carries them
+///   and the mode
+/// - 31: a zone death scores for the last opponent to hurt the player within
+///   `ZONE_KILL_CREDIT_TICKS` (`Player::last_damaged_by`), and is an env kill only without one;
+///   v4 state encoding carries the credit per player
+/// - 32: transcripts are in registered order; the sim routes each input to its internal index
+///   (`side_index`), so a side swap no longer needs the recorder to reorder them
+pub const SIM_VERSION: u32 = 32;
+
+/// Ticks of per-player position history kept for lag-compensated hit checks.
+/// Usable lag is 0..LAG_RING-1 (slot 0 is the current tick).
+pub const LAG_RING: usize = 8;
+
+// -- Fixed-point arithmetic --------------------------------------------------
+
+pub type Fp = i32;
+pub const FRAC: u32 = 8;
+pub const ONE: Fp = 1 << FRAC; // 256
+
+/// Fixed-point multiply: (a * b) >> FRAC. The product is taken in i64; a result outside i32
+/// would be truncated, which debug builds catch.
+#[inline(always)]
+pub fn mul(a: Fp, b: Fp) -> Fp {
+    let r = (a as i64 * b as i64) >> FRAC;
+    debug_assert!(r == r as Fp as i64, "mul({a}, {b}) overflows Fp");
+    r as Fp
+}
+
+/// Fixed-point divide: (a << FRAC) / b, in i64 like `mul`.
+#[inline(always)]
+pub fn div(a: Fp, b: Fp) -> Fp {
+    let r = ((a as i64) << FRAC) / b as i64;
+    debug_assert!(r == r as Fp as i64, "div({a}, {b}) overflows Fp");
+    r as Fp
+}
+
+/// Convert integer to fixed-point
+#[inline(always)]
+pub const fn fp(v: i32) -> Fp {
+    v * ONE
+}
+
+// -- Constants ---------------------------------------------------------------
+
+pub const GRAVITY: Fp = 128; // 0.5
+pub const PLAYER_SPEED: Fp = 1024; // 4.0
+pub const ACCELERATION: Fp = 205; // 0.8 (204.8 rounded)
+pub const DECELERATION: Fp = 154; // 0.6 (153.6 rounded)
+pub const JUMP_VELOCITY: Fp = -2688; // -10.5
+pub const MAX_FALL_SPEED: Fp = 3072; // 12.0
+
+pub const PLAYER_WIDTH: Fp = 6144; // 24
+pub const PLAYER_HEIGHT: Fp = 8192; // 32
+
+pub const PROJECTILE_SPEED: Fp = 2048; // 8.0
+pub const PROJECTILE_LIFETIME: i32 = 90;
+#[deprecated(note = "cooldown is per weapon: use `fp_weapon_stats(weapon).cooldown`")]
+pub const SHOOT_COOLDOWN: i32 = 15;
+
+pub const MAX_HEALTH: i32 = 100;
+#[deprecated(note = "damage is per weapon (pistol does 20): use `fp_weapon_stats(weapon).damage`")]
+pub const PROJECTILE_DAMAGE: i32 = 25;
+
+pub const RESPAWN_TICKS: i32 = 60;
+pub const INVINCIBLE_TICKS: i32 = 60;
+pub const DEATH_LINGER_TICKS: i32 = 30;
+pub const INITIAL_LIVES: i32 = 1;
+pub const MATCH_DURATION_TICKS: i32 = 1800; // 30s
+pub const SUDDEN_DEATH_START_TICK: i32 = 1200; // 20 seconds
+pub const SUDDEN_DEATH_DURATION: i32 = 300; // 5 seconds to close
+pub const ZONE_MAX_DPS: i32 = 20; // damage per second at full close
+/// A zone death scores for the opponent who last hurt the player at most this many ticks before.
+pub const ZONE_KILL_CREDIT_TICKS: i32 = 180; // 3 seconds
+/// Hazard platforms hurt the players touching them on ticks that are a multiple of this.
+pub const HAZARD_DAMAGE_INTERVAL: i32 = 20;
+
+// Double jump
+pub const MAX_JUMPS: i32 = 2;
+
+// Wall slide & wall jump
+pub const WALL_SLIDE_SPEED: Fp = 512; // 2.0
+pub const WALL_JUMP_VX: Fp = 1792; // 7.0
+pub const WALL_JUMP_VY: Fp = -2560; // -10.0
+
+// Dash
+pub const DASH_VELOCITY: Fp = 3072; // 12.0, horizontal, in the facing direction
+pub const DASH_TICKS: i32 = 8; // burst length: vx holds, gravity is DASH_GRAVITY
+pub const DASH_GRAVITY: Fp = 32; // 0.125
+pub const DASH_COOLDOWN_TICKS: i32 = 60; // from the press, burst included
+
+// Wall stamina (opt-in via `FpMatchConfig::stamina`)
+pub const STAMINA_MAX: i32 = 600;
+pub const WALL_SLIDE_STAMINA_DRAIN: i32 = 5; // per sliding tick → 120 ticks (2s) from full
+pub const WALL_JUMP_STAMINA_COST: i32 = 150;
+pub const STAMINA_REGEN: i32 = 10; // per grounded tick → full in 60 ticks
+
+// Stomp
+pub const STOMP_DAMAGE_INTERVAL: i32 = 2;
+pub const STOMP_DAMAGE_PER_HIT: i32 = 1;
+pub const STOMP_SHAKE_PER_PRESS: i32 = 17;
+pub const STOMP_SHAKE_THRESHOLD: i32 = 100;
+pub const STOMP_SHAKE_DECAY: i32 = 1;
+pub const STOMP_AUTO_RUN_MIN: i32 = 20;
+pub const STOMP_AUTO_RUN_MAX: i32 = 60;
+pub const STOMP_COOLDOWN_TICKS: i32 = 90;
+/// Rider drops off once the victim has been airborne this many ticks in a row (ledge walk-off).
+pub const STOMP_AIRBORNE_DETACH_TICKS: u8 = 12;
+
+pub mod button {
+    pub const LEFT: u8 = 1;
+    pub const RIGHT: u8 = 2;
+    pub const JUMP: u8 = 4;
+    pub const SHOOT: u8 = 8;
+    /// On its edge, off cooldown: a dash (`DASH_VELOCITY` in the facing direction). The bit was
+    /// the client's taunt, which the sim used to ignore; the taunt is now emote slot 1.
+    pub const DASH: u8 = 16;
+    /// With JUMP while standing on a one-way platform: drop through it instead of jumping.
+    pub const DOWN: u8 = 32;
+    /// Emote wheel selector (two bits, see `emote`). Hashed with the transcript and reported as
+    /// an `EmoteEvent`, but masked off before the sim reads the input, `prev_buttons` included.
+    /// The client sends its taunt as slot 1.
+    pub const EMOTE_MASK: u8 = 64 | 128;
+    pub const EMOTE_SHIFT: u32 = 6;
+
+    /// Wheel slot selected in `buttons`: 0 for none, else 1..=3.
+    #[inline(always)]
+    pub const fn emote(buttons: u8) -> u8 {
+        (buttons & EMOTE_MASK) >> EMOTE_SHIFT
+    }
+}
+
+pub mod flag {
+    pub const ALIVE: u32 = 1;
+    pub const INVINCIBLE: u32 = 2;
+}
+
+/// Why `flag::INVINCIBLE` is set. Damage gating only looks at the bit; this is for
+/// rendering and stats. Set and cleared together with the bit.
+pub mod invincibility {
+    pub const NONE: u8 = 0;
+    pub const RESPAWN: u8 = 1;
+    pub const START_GRACE: u8 = 2;
+    pub const SCRIPTED: u8 = 3;
+}
+
+/// How the sudden-death zone closes in (`FpMatchConfig::zone_mode`); see `zone_bounds`.
+pub mod zone_mode {
+    /// The left and right edges move in to meet in the middle.
+    pub const PINCH: u8 = 0;
+    /// The ceiling comes down to the floor.
+    pub const DESCENT: u8 = 1;
+    /// All four edges move in to meet in the middle.
+    pub const BOX: u8 = 2;
+    /// Modes there are: a config or state naming another is invalid.
+    pub const COUNT: u8 = 3;
+}
+
+pub const FACING_RIGHT: i32 = 1;
+pub const FACING_LEFT: i32 = -1;
@@ -0,0 +1,1422 @@
+//! State and map encoding (for chunked proving) and decoding, and state validation.
+
+use std::sync::Arc;
+
+use sha2::{Digest, Sha256};
+
+use super::*;
+
+// -- State serialization (for chunked proving) --------------------------------
+
+/// Byte sink for `write_state`: a growing Vec, a caller's buffer, or just a length count.
+pub(super) trait ByteSink {
+    fn extend_from_slice(&mut self, bytes: &[u8]);
+    fn push(&mut self, byte: u8) {
+        self.extend_from_slice(&[byte]);
+    }
+}
+
+impl ByteSink for Vec<u8> {
+    fn extend_from_slice(&mut self, bytes: &[u8]) {
+        Vec::extend_from_slice(self, bytes);
+    }
+    fn push(&mut self, byte: u8) {
+        Vec::push(self, byte);
+    }
+}
+
+/// Writes into a slice already checked to be large enough.
+struct SliceSink<'a> {
+    buf: &'a mut [u8],
+    len: usize,
+}
+
+impl ByteSink for SliceSink<'_> {
+    fn extend_from_slice(&mut self, bytes: &[u8]) {
+        self.buf[self.len..self.len + bytes.len()].copy_from_slice(bytes);
+        self.len += bytes.len();
+    }
+}
+
+struct CountSink(usize);
+
+impl ByteSink for CountSink {
+    fn extend_from_slice(&mut self, bytes: &[u8]) {
+        self.0 += bytes.len();
+    }
+}
+
+impl ByteSink for Sha256 {
+    fn extend_from_slice(&mut self, bytes: &[u8]) {
+        self.update(bytes);
+    }
+}
+
+/// State byte layouts. `decode_state` tells them apart by the leading word (a v1 state starts
+/// with its tick, never negative).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StateFormat {
+    /// Every player field a full word; fields added after the first release trail the state.
+    /// Kept so hashes committed by older chunk proofs can be recomputed.
+    V1,
+    /// `V2_TAG`, then players packed to 44 bytes (direction bitfield, i16 counters, per-player
+    /// extras inline) and projectiles with their immunity tick inline. About 40% fewer bytes
+    /// for the chunk guest to hash.
+    V2,
+    /// `V3_TAG`, the v2 layout, `env_kills`, the pickup despawn flag, every slot's
+    /// `active_timer`, `final_blow`, every projectile slot's `bounces_left` (0 past
+    /// `proj_count`) and every player slot's `stomp_cooldown_from`, then the state's map
+    /// (`MAP_ENCODED_LEN` bytes). The only format that round-trips `State::map`, `env_kills`,
+    /// the despawn rule, `final_blow`, grenade bounces and the stomp cooldown's rider; v1/v2
+    /// bytes decode onto a supplied map with none of them.
+    ///
+    /// A state of more than two players leads with `V3_WIDE_TAG` instead and carries the
+    /// player count and the players past the first two right after the v2 layout. It is the
+    /// only format that holds them: v1/v2 encode players 0 and 1 alone.
+    ///
+    /// Carries the map without its health spawns (`MAP_BASE_LEN` bytes) and no health pickups,
+    /// so it decodes with none.
+    V3,
+    /// `V4_TAG` (`V4_WIDE_TAG` past two players), the v3 layout up to its map, then the health
+    /// pickups, dash cooldowns and pickup stats (`V4_EXTRA_BYTES`) and the whole map with its
+    /// health spawns (`MAP_ENCODED_LEN` bytes). Older formats decode with every dash cooldown
+    /// at 0, empty pickup stats, no zone kill credit (`Player::last_damaged_by` at -1) and no
+    /// dropped weapons (pickups past `NUM_WEAPON_SPAWNS` come back as spawned ones, with no
+    /// `active_timer` in v3).
+    V4,
+}
+
+/// Format produced by `encode_state` and `hash_state`.
+pub const STATE_FORMAT: StateFormat = StateFormat::V4;
+
+/// Leading word of a v2 state.
+const V2_TAG: i32 = -2;
+/// Leading word of a v3 state.
+const V3_TAG: i32 = -3;
+/// Leading word of a v3 state of more than two players.
+pub(super) const V3_WIDE_TAG: i32 = -4;
+/// Leading word of a v4 state.
+const V4_TAG: i32 = -5;
+/// Leading word of a v4 state of more than two players.
+pub(super) const V4_WIDE_TAG: i32 = -6;
+
+/// Map encoding up to the health spawns: width, height, then every platform, spawn and weapon
+/// spawn as words, then a byte with bit `i` set when platform `i` is one-way, then every
+/// platform's `hazard_damage`. What a v3 state carries.
+pub const MAP_BASE_LEN: usize =
+    4 * (2 + 4 * NUM_PLATFORMS + 2 * NUM_SPAWNS + 2 * NUM_WEAPON_SPAWNS) + 1 + 4 * NUM_PLATFORMS;
+/// Encoded map: the base encoding, then `health_spawn_count` and every health spawn as words.
+pub const MAP_ENCODED_LEN: usize = MAP_BASE_LEN + 1 + 2 * 4 * MAX_HEALTH_PICKUPS;
+const _: () = assert!(NUM_PLATFORMS <= 8, "one-way flags fit one byte");
+
+// v2 record sizes
+pub(super) const V2_PLAYER_BYTES: usize = 44;
+pub(super) const V2_PROJ_BYTES: usize = 8 * 4 + 1;
+const V2_PICKUP_BYTES: usize = 4 * 4 + 1;
+/// Globals after the pickups, up to the lag config.
+const V2_TAIL_BYTES: usize = 6 * 4 + 1 + 2 * 4 + 2 + 3 * 4;
+const HISTORY_BYTES: usize = 2 * LAG_RING * 8;
+/// Weapon pickup slots whose `active_timer` the v3 extras carry: the spawn slots, as many as
+/// there were slots when v3 was laid out. The v4 extras carry the rest.
+const V3_PICKUP_SLOTS: usize = NUM_WEAPON_SPAWNS;
+/// v3 `env_kills`, the despawn flag, `active_timer` of the first `V3_PICKUP_SLOTS` slots,
+/// `final_blow`, per-slot `bounces_left` and per-player `stomp_cooldown_from`, between the v2
+/// layout and the map.
+pub(super) const V3_EXTRA_BYTES: usize =
+    2 * 4 + 1 + V3_PICKUP_SLOTS * 4 + 1 + MAX_PROJECTILES + MAX_PLAYERS;
+/// v4 `health_pickup_count`, every health pickup slot's position and respawn timer (zeros
+/// past the count), every player slot's `dash_cooldown`, `State::pickup_stats`
+/// (`PickupStats::to_bytes`), then the `active_timer` of the weapon pickup slots past
+/// `V3_PICKUP_SLOTS`, every slot's `dropped_ammo`, `cfg_zone_mode`, `arena_top` and
+/// `arena_bottom`, and every player slot's `last_damaged_by` and `last_damage_tick`, between
+/// the v3 extras and the map.
+pub(super) const V4_EXTRA_BYTES: usize = 1 + MAX_HEALTH_PICKUPS * 3 * 4 + MAX_PLAYERS + PICKUP_STATS_BYTES
+    + (MAX_WEAPON_PICKUPS - V3_PICKUP_SLOTS) * 4 + MAX_WEAPON_PICKUPS * 4 + 1 + 2 * 4 + MAX_PLAYERS * (1 + 4);
+/// Each player past the first two in a wide v3 or v4 state: the v2 record, score, `env_kills` and
+/// `prev_buttons` (then its `HISTORY_BYTES / 2` of history when lag is on).
+const WIDE_PLAYER_BYTES: usize = V2_PLAYER_BYTES + 2 * 4 + 1;
+
+/// Largest v4 state: every player, projectile and pickup slot live, lag history present.
+/// Sizes the chunk guest's state buffer.
+pub const MAX_ENCODED_STATE_LEN: usize = 8 + 2 * V2_PLAYER_BYTES + 1 + MAX_PROJECTILES * V2_PROJ_BYTES
+    + 1 + MAX_WEAPON_PICKUPS * V2_PICKUP_BYTES + V2_TAIL_BYTES + 2 + HISTORY_BYTES + 1
+    + 1 + (MAX_PLAYERS - 2) * (WIDE_PLAYER_BYTES + HISTORY_BYTES / 2)
+    + V3_EXTRA_BYTES + V4_EXTRA_BYTES + MAP_ENCODED_LEN;
+
+pub(super) fn write_state<B: ByteSink>(s: &State, format: StateFormat, b: &mut B) {
+    match format {
+        StateFormat::V1 => write_state_v1(s, b),
+        StateFormat::V2 => write_state_v2(s, V2_TAG, b),
+        StateFormat::V3 => {
+            write_state_v2(s, if s.player_count > 2 { V3_WIDE_TAG } else { V3_TAG }, b);
+            write_v3_extras(s, b);
+            write_map_base(&s.map, b);
+        }
+        StateFormat::V4 => {
+            write_state_v2(s, if s.player_count > 2 { V4_WIDE_TAG } else { V4_TAG }, b);
+            write_v3_extras(s, b);
+            b.push(s.health_pickup_count);
+            for (i, hp) in s.health_pickups.iter().enumerate() {
+                let hp = if i < s.health_pickup_count as usize { *hp } else { EMPTY_HEALTH_PICKUP };
+                for v in [hp.x, hp.y, hp.respawn_timer] {
+                    b.extend_from_slice(&v.to_le_bytes());
+                }
+            }
+            for p in &s.players {
+                b.push(p.dash_cooldown as u8);
+            }
+            b.extend_from_slice(&s.pickup_stats.to_bytes());
+            for wp in &s.weapon_pickups[V3_PICKUP_SLOTS..] {
+                b.extend_from_slice(&wp.active_timer.to_le_bytes());
+            }
+            for wp in &s.weapon_pickups {
+                b.extend_from_slice(&wp.dropped_ammo.to_le_bytes());
+            }
+            b.push(s.cfg_zone_mode);
+            b.extend_from_slice(&s.arena_top.to_le_bytes());
+            b.extend_from_slice(&s.arena_bottom.to_le_bytes());
+            for p in &s.players {
+                b.push(p.last_damaged_by as u8);
+                b.extend_from_slice(&p.last_damage_tick.to_le_bytes());
+            }
+            write_map(&s.map, b);
+        }
+    }
+}
+
+/// The fields v3 added after the v2 layout (`V3_EXTRA_BYTES`), also in v4.
+fn write_v3_extras<B: ByteSink>(s: &State, b: &mut B) {
+    b.extend_from_slice(&s.env_kills[0].to_le_bytes());
+    b.extend_from_slice(&s.env_kills[1].to_le_bytes());
+    b.push(s.cfg_active_despawn as u8);
+    for wp in &s.weapon_pickups[..V3_PICKUP_SLOTS] {
+        b.extend_from_slice(&wp.active_timer.to_le_bytes());
+    }
+    b.push(s.final_blow as u8);
+    for (i, pj) in s.projectiles.iter().enumerate() {
+        b.push(if i < s.proj_count as usize { pj.bounces_left } else { 0 });
+    }
+    for p in &s.players {
+        b.push(p.stomp_cooldown_from as u8);
+    }
+}
+
+fn write_map<B: ByteSink>(m: &Map, b: &mut B) {
+    write_map_base(m, b);
+    write_map_health(m, b);
+}
+
+fn write_map_health<B: ByteSink>(m: &Map, b: &mut B) {
+    b.push(m.health_spawn_count);
+    for sp in &m.health_spawns {
+        b.extend_from_slice(&sp.x.to_le_bytes());
+        b.extend_from_slice(&sp.y.to_le_bytes());
+    }
+}
+
+fn write_map_base<B: ByteSink>(m: &Map, b: &mut B) {
+    b.extend_from_slice(&m.width.to_le_bytes());
+    b.extend_from_slice(&m.height.to_le_bytes());
+    for p in &m.platforms {
+        for v in [p.x, p.y, p.width, p.height] {
+            b.extend_from_slice(&v.to_le_bytes());
+        }
+    }
+    for sp in m.spawns.iter().chain(&m.weapon_spawns) {
+        b.extend_from_slice(&sp.x.to_le_bytes());
+        b.extend_from_slice(&sp.y.to_le_bytes());
+    }
+    b.push(m.platforms.iter().enumerate().fold(0u8, |bits, (i, p)| bits | (p.one_way as u8) << i));
+    for p in &m.platforms {
+        b.extend_from_slice(&p.hazard_damage.to_le_bytes());
+    }
+}
+
+/// The map's encoding: the bytes a v4 state carries, and what the match guest reads.
+pub fn encode_map(map: &Map) -> [u8; MAP_ENCODED_LEN] {
+    let mut b = [0u8; MAP_ENCODED_LEN];
+    let mut sink = SliceSink { buf: &mut b, len: 0 };
+    write_map(map, &mut sink);
+    b
+}
+
+/// Inverse of `encode_map`, or `None` when `b` isn't exactly `MAP_ENCODED_LEN` bytes.
+pub fn decode_map(b: &[u8]) -> Option<Map> {
+    (b.len() == MAP_ENCODED_LEN).then(|| read_map(&mut Reader { b, off: 0 }))
+}
+
+/// SHA-256 of the map's encoding, leaving out the health section when the map declares no
+/// health spawns, so maps from before health pickups keep their hashes.
+pub fn map_hash(map: &Map) -> [u8; 32] {
+    let mut h = Sha256::new();
+    write_map_base(map, &mut h);
+    if map.health_spawn_count != 0 {
+        write_map_health(map, &mut h);
+    }
+    h.finalize().into()
+}
+
+/// Deterministic binary encoding of State (for hashing + chunk transfer), in `STATE_FORMAT`.
+pub fn encode_state(s: &State) -> Vec<u8> {
+    encode_state_as(s, STATE_FORMAT)
+}
+
+/// `encode_state` in a specific format.
+pub fn encode_state_as(s: &State, format: StateFormat) -> Vec<u8> {
+    let mut b = Vec::with_capacity(512);
+    write_state(s, format, &mut b);
+    b
+}
+
+/// Length of `encode_state(s)`, without allocating.
+pub fn encoded_state_len(s: &State) -> usize {
+    let mut count = CountSink(0);
+    write_state(s, STATE_FORMAT, &mut count);
+    count.0
+}
+
+/// Upper bound on `encoded_state_len` for any state of `s`'s match: every projectile and
+/// pickup slot live (history and the player count are fixed by the match's config).
+pub fn max_encoded_state_len(s: &State) -> usize {
+    let mut full = s.clone();
+    full.proj_count = MAX_PROJECTILES as u8;
+    full.pickup_count = MAX_WEAPON_PICKUPS as u8;
+    encoded_state_len(&full)
+}
+
+/// `encode_state` into a caller-provided buffer (zero allocation). Returns the bytes written;
+/// a too-small buffer is left untouched.
+pub fn encode_state_into(s: &State, buf: &mut [u8]) -> Result<usize, BufferTooSmall> {
+    let needed = encoded_state_len(s);
+    if buf.len() < needed {
+        return Err(BufferTooSmall { needed, got: buf.len() });
+    }
+    let mut sink = SliceSink { buf, len: 0 };
+    write_state(s, STATE_FORMAT, &mut sink);
+    Ok(sink.len)
+}
+
+/// `encode_state_into` target shorter than the encoding.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BufferTooSmall {
+    pub needed: usize,
+    pub got: usize,
+}
+
+impl core::fmt::Display for BufferTooSmall {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "state encoding needs {} bytes, buffer has {}", self.needed, self.got)
+    }
+}
+
+fn write_state_v1<B: ByteSink>(s: &State, b: &mut B) {
+    b.extend_from_slice(&s.tick.to_le_bytes());
+    for p in &s.players[..2] {
+        b.extend_from_slice(&p.id.to_le_bytes());
+        b.extend_from_slice(&p.x.to_le_bytes());
+        b.extend_from_slice(&p.y.to_le_bytes());
+        b.extend_from_slice(&p.vx.to_le_bytes());
+        b.extend_from_slice(&p.vy.to_le_bytes());
+        b.extend_from_slice(&p.facing.to_le_bytes());
+        b.extend_from_slice(&p.health.to_le_bytes());
+        b.extend_from_slice(&p.lives.to_le_bytes());
+        b.extend_from_slice(&p.shoot_cooldown.to_le_bytes());
+        b.push(p.grounded as u8);
+        b.extend_from_slice(&p.state_flags.to_le_bytes());
+        b.extend_from_slice(&p.respawn_timer.to_le_bytes());
+        b.push(p.weapon as u8);
+        b.extend_from_slice(&p.ammo.to_le_bytes());
+        b.extend_from_slice(&p.jumps_left.to_le_bytes());
+        b.push(p.wall_sliding as u8);
+        b.extend_from_slice(&p.wall_dir.to_le_bytes());
+        b.extend_from_slice(&p.stomped_by.to_le_bytes());
+        b.extend_from_slice(&p.stomping_on.to_le_bytes());
+        b.extend_from_slice(&p.stomp_shake_progress.to_le_bytes());
+        b.extend_from_slice(&p.stomp_last_shake_dir.to_le_bytes());
+        b.extend_from_slice(&p.stomp_auto_run_dir.to_le_bytes());
+        b.extend_from_slice(&p.stomp_auto_run_timer.to_le_bytes());
+        b.extend_from_slice(&p.stomp_cooldown.to_le_bytes());
+    }
+    b.push(s.proj_count);
+    for i in 0..s.proj_count as usize {
+        let pj = &s.projectiles[i];
+        b.extend_from_slice(&pj.id.to_le_bytes());
+        b.extend_from_slice(&pj.owner_id.to_le_bytes());
+        b.extend_from_slice(&pj.x.to_le_bytes());
+        b.extend_from_slice(&pj.y.to_le_bytes());
+        b.extend_from_slice(&pj.vx.to_le_bytes());
+        b.extend_from_slice(&pj.vy.to_le_bytes());
+        b.extend_from_slice(&pj.lifetime.to_le_bytes());
+        b.push(pj.weapon as u8);
+    }
+    b.push(s.pickup_count);
+    for i in 0..s.pickup_count as usize {
+        let wp = &s.weapon_pickups[i];
+        b.extend_from_slice(&wp.id.to_le_bytes());
+        b.extend_from_slice(&wp.x.to_le_bytes());
+        b.extend_from_slice(&wp.y.to_le_bytes());
+        b.push(wp.weapon as u8);
+        b.extend_from_slice(&wp.respawn_timer.to_le_bytes());
+    }
+    b.extend_from_slice(&s.rng_state.to_le_bytes());
+    b.extend_from_slice(&s.score[0].to_le_bytes());
+    b.extend_from_slice(&s.score[1].to_le_bytes());
+    b.extend_from_slice(&s.next_proj_id.to_le_bytes());
+    b.extend_from_slice(&s.arena_left.to_le_bytes());
+    b.extend_from_slice(&s.arena_right.to_le_bytes());
+    b.push(s.match_over as u8);
+    b.extend_from_slice(&s.winner.to_le_bytes());
+    b.extend_from_slice(&s.death_linger_timer.to_le_bytes());
+    b.push(s.prev_buttons[0]);
+    b.push(s.prev_buttons[1]);
+    b.extend_from_slice(&s.cfg_initial_lives.to_le_bytes());
+    b.extend_from_slice(&s.cfg_match_duration.to_le_bytes());
+    b.extend_from_slice(&s.cfg_sudden_death.to_le_bytes());
+    b.push(s.cfg_lag_ticks[0]);
+    b.push(s.cfg_lag_ticks[1]);
+    // Position history only affects the sim when lag compensation is on
+    if s.cfg_lag_ticks != [0, 0] {
+        for i in 0..2 {
+            for k in 0..LAG_RING {
+                b.extend_from_slice(&s.pos_history_x[i][k].to_le_bytes());
+                b.extend_from_slice(&s.pos_history_y[i][k].to_le_bytes());
+            }
+        }
+    }
+    b.push(s.cfg_mirrored as u8);
+    b.push(s.players[0].invincibility_source);
+    b.push(s.players[1].invincibility_source);
+    b.push(s.players[0].stomp_airborne_ticks);
+    b.push(s.players[1].stomp_airborne_ticks);
+    b.push(s.cfg_stamina as u8);
+    b.extend_from_slice(&s.players[0].stamina.to_le_bytes());
+    b.extend_from_slice(&s.players[1].stamina.to_le_bytes());
+    for pj in &s.projectiles[..s.proj_count as usize] {
+        b.extend_from_slice(&pj.owner_immune_until.to_le_bytes());
+    }
+    b.push(s.side_swap as u8);
+}
+
+
+/// Direction-like field (-1, 0, 1) as a 2-bit code.
+fn dir_code(d: i32) -> u16 {
+    (d + 1) as u16 & 3
+}
+
+fn dir_from_code(bits: u16, shift: u32) -> i32 {
+    ((bits >> shift) & 3) as i32 - 1
+}
+
+/// Whether `p` survives v2 packing unchanged: the ranges the sim keeps players in.
+fn packs_losslessly(p: &Player) -> bool {
+    let small = |v: i32| (i16::MIN as i32..=i16::MAX as i32).contains(&v);
+    let dir = |v: i32| (-1..=1).contains(&v);
+    let id = |v: i32| (i8::MIN as i32..=i8::MAX as i32).contains(&v);
+    id(p.id) && id(p.stomped_by) && id(p.stomping_on) && id(p.jumps_left)
+        && dir(p.facing) && dir(p.wall_dir) && dir(p.stomp_last_shake_dir) && dir(p.stomp_auto_run_dir)
+        && p.state_flags <= u8::MAX as u32
+        && [p.health, p.lives, p.shoot_cooldown, p.respawn_timer, p.ammo, p.stomp_shake_progress,
+            p.stomp_auto_run_timer, p.stomp_cooldown, p.stamina].iter().all(|&v| small(v))
+}
+
+/// `V2_PLAYER_BYTES`: id, position/velocity words, direction + bool bitfield, flags, i16 counters.
+fn write_player_v2<B: ByteSink>(p: &Player, b: &mut B) {
+    debug_assert!(packs_losslessly(p), "player out of v2 range: {p:?}");
+    b.push(p.id as u8);
+    for v in [p.x, p.y, p.vx, p.vy] {
+        b.extend_from_slice(&v.to_le_bytes());
+    }
+    let bits = dir_code(p.facing)
+        | dir_code(p.wall_dir) << 2
+        | dir_code(p.stomp_last_shake_dir) << 4
+        | dir_code(p.stomp_auto_run_dir) << 6
+        | (p.grounded as u16) << 8
+        | (p.wall_sliding as u16) << 9;
+    b.extend_from_slice(&bits.to_le_bytes());
+    b.push(p.state_flags as u8);
+    for v in [p.health, p.lives, p.shoot_cooldown, p.respawn_timer] {
+        b.extend_from_slice(&(v as i16).to_le_bytes());
+    }
+    b.push(p.weapon as u8);
+    b.extend_from_slice(&(p.ammo as i16).to_le_bytes());
+    b.push(p.jumps_left as u8);
+    b.push(p.stomped_by as u8);
+    b.push(p.stomping_on as u8);
+    for v in [p.stomp_shake_progress, p.stomp_auto_run_timer, p.stomp_cooldown] {
+        b.extend_from_slice(&(v as i16).to_le_bytes());
+    }
+    b.push(p.invincibility_source);
+    b.push(p.stomp_airborne_ticks);
+    b.extend_from_slice(&(p.stamina as i16).to_le_bytes());
+}
+
+/// Same field order as v1 with nothing optional: every group is always present. Also the
+/// body of v3 and v4 (`tag` tells them apart), followed by the extra players under a wide tag.
+fn write_state_v2<B: ByteSink>(s: &State, tag: i32, b: &mut B) {
+    b.extend_from_slice(&tag.to_le_bytes());
+    b.extend_from_slice(&s.tick.to_le_bytes());
+    for p in &s.players[..2] {
+        write_player_v2(p, b);
+    }
+    b.push(s.proj_count);
+    for pj in &s.projectiles[..s.proj_count as usize] {
+        for v in [pj.id, pj.owner_id, pj.x, pj.y, pj.vx, pj.vy, pj.lifetime] {
+            b.extend_from_slice(&v.to_le_bytes());
+        }
+        b.push(pj.weapon as u8);
+        b.extend_from_slice(&pj.owner_immune_until.to_le_bytes());
+    }
+    b.push(s.pickup_count);
+    for wp in &s.weapon_pickups[..s.pickup_count as usize] {
+        for v in [wp.id, wp.x, wp.y] {
+            b.extend_from_slice(&v.to_le_bytes());
+        }
+        b.push(wp.weapon as u8);
+        b.extend_from_slice(&wp.respawn_timer.to_le_bytes());
+    }
+    b.extend_from_slice(&s.rng_state.to_le_bytes());
+    b.extend_from_slice(&s.score[0].to_le_bytes());
+    b.extend_from_slice(&s.score[1].to_le_bytes());
+    b.extend_from_slice(&s.next_proj_id.to_le_bytes());
+    b.extend_from_slice(&s.arena_left.to_le_bytes());
+    b.extend_from_slice(&s.arena_right.to_le_bytes());
+    b.push(s.match_over as u8);
+    b.extend_from_slice(&s.winner.to_le_bytes());
+    b.extend_from_slice(&s.death_linger_timer.to_le_bytes());
+    b.push(s.prev_buttons[0]);
+    b.push(s.prev_buttons[1]);
+    b.extend_from_slice(&s.cfg_initial_lives.to_le_bytes());
+    b.extend_from_slice(&s.cfg_match_duration.to_le_bytes());
+    b.extend_from_slice(&s.cfg_sudden_death.to_le_bytes());
+    b.push(s.cfg_lag_ticks[0]);
+    b.push(s.cfg_lag_ticks[1]);
+    if s.cfg_lag_ticks != [0, 0] {
+        for i in 0..2 {
+            for k in 0..LAG_RING {
+                b.extend_from_slice(&s.pos_history_x[i][k].to_le_bytes());
+                b.extend_from_slice(&s.pos_history_y[i][k].to_le_bytes());
+            }
+        }
+    }
+    b.push(s.cfg_mirrored as u8 | (s.cfg_stamina as u8) << 1 | (s.side_swap as u8) << 2);
+    if tag == V3_WIDE_TAG || tag == V4_WIDE_TAG {
+        b.push(s.player_count);
+        for i in 2..s.player_count as usize {
+            write_player_v2(&s.players[i], b);
+            b.extend_from_slice(&s.score[i].to_le_bytes());
+            b.extend_from_slice(&s.env_kills[i].to_le_bytes());
+            b.push(s.prev_buttons[i]);
+            if s.cfg_lag_ticks != [0, 0] {
+                for k in 0..LAG_RING {
+                    b.extend_from_slice(&s.pos_history_x[i][k].to_le_bytes());
+                    b.extend_from_slice(&s.pos_history_y[i][k].to_le_bytes());
+                }
+            }
+        }
+    }
+}
+
+/// Decode State from bytes produced by `encode_state` (any format). v1/v2 states predate
+/// `State::map` and were only ever played on the arena map, so they get `arena_map()` in the
+/// state's orientation; use `decode_state_on` to supply a different one.
+pub fn decode_state(b: &[u8]) -> State {
+    decode_state_with(b, None)
+}
+
+/// `decode_state` for untrusted bytes: `check_encoded_len` first, so truncated input or a
+/// projectile or pickup count past its maximum is an error instead of a panic. The format is
+/// the one the leading word names (`StateFormat`), which `hash_state` covers, so encodings of
+/// different formats never hash alike. Doesn't validate field ranges: see `try_decode_state`.
+pub fn decode_state_checked(b: &[u8]) -> Result<State, StateIssue> {
+    check_encoded_len(b)?;
+    Ok(decode_state(b))
+}
+
+/// Decode length-checked bytes onto `map`: a v3 or v4 state must carry exactly `map`
+/// (`StateIssue::MapMismatch` otherwise), a v1/v2 state adopts it. The migration path for
+/// boundary states encoded before the map moved into the state.
+pub fn decode_state_on(b: &[u8], map: &Map) -> Result<State, StateIssue> {
+    check_encoded_len(b)?;
+    let s = decode_state_with(b, Some(map));
+    if *s.map != *map {
+        return Err(StateIssue::MapMismatch { expected: map_hash(map), actual: map_hash(&s.map) });
+    }
+    Ok(s)
+}
+
+fn decode_state_with(b: &[u8], legacy_map: Option<&Map>) -> State {
+    let mut s = if is_v2(b) || is_v3(b) || is_v4(b) { decode_state_v2(b) } else { decode_state_v1(b) };
+    if is_v3(b) || is_v4(b) {
+        let tail = if is_v4(b) { V4_EXTRA_BYTES + MAP_ENCODED_LEN } else { MAP_BASE_LEN };
+        let mut r = Reader { b, off: b.len() - tail - V3_EXTRA_BYTES };
+        s.env_kills[0] = r.u32();
+        s.env_kills[1] = r.u32();
+        s.cfg_active_despawn = r.u8() != 0;
+        for wp in &mut s.weapon_pickups[..V3_PICKUP_SLOTS] {
+            wp.active_timer = r.i32();
+        }
+        s.final_blow = r.u8() != 0;
+        for i in 0..MAX_PROJECTILES {
+            let bounces = r.u8();
+            if i < s.proj_count as usize {
+                s.projectiles[i].bounces_left = bounces;
+            }
+        }
+        for p in &mut s.players {
+            p.stomp_cooldown_from = r.i8();
+        }
+        if is_v4(b) {
+            s.health_pickup_count = r.u8();
+            for hp in &mut s.health_pickups {
+                *hp = HealthPickup { x: r.i32(), y: r.i32(), respawn_timer: r.i32() };
+            }
+            for p in &mut s.players {
+                p.dash_cooldown = r.u8() as i32;
+            }
+            let mut stats = [0u8; PICKUP_STATS_BYTES];
+            for v in &mut stats {
+                *v = r.u8();
+            }
+            s.pickup_stats = PickupStats::from_bytes(&stats);
+            for wp in &mut s.weapon_pickups[V3_PICKUP_SLOTS..] {
+                wp.active_timer = r.i32();
+            }
+            for wp in &mut s.weapon_pickups {
+                wp.dropped_ammo = r.i32();
+            }
+            s.cfg_zone_mode = r.u8();
+            s.arena_top = r.i32();
+            s.arena_bottom = r.i32();
+            for p in &mut s.players {
+                p.last_damaged_by = r.i8();
+                p.last_damage_tick = r.i32();
+            }
+            s.map = Arc::new(read_map(&mut r));
+        } else {
+            s.map = Arc::new(read_map_base(&mut r));
+            // Before v4 every zone was a pinch, open top to bottom
+            s.arena_bottom = s.map.height;
+        }
+    } else {
+        // v1/v2 predate the cooldown's rider; their states have two players, so it's the other
+        for p in &mut s.players[..2] {
+            if p.stomp_cooldown > 0 {
+                p.stomp_cooldown_from = 1 - p.id;
+            }
+        }
+        if let Some(map) = legacy_map {
+            s.map = Arc::new(map.clone());
+        }
+        s.arena_bottom = s.map.height;
+    }
+    s
+}
+
+fn is_v2(b: &[u8]) -> bool {
+    b.len() >= 4 && b[..4] == V2_TAG.to_le_bytes()
+}
+
+/// A v3 state, of two players or more.
+fn is_v3(b: &[u8]) -> bool {
+    b.len() >= 4 && (b[..4] == V3_TAG.to_le_bytes() || b[..4] == V3_WIDE_TAG.to_le_bytes())
+}
+
+/// A v4 state, of two players or more.
+fn is_v4(b: &[u8]) -> bool {
+    b.len() >= 4 && (b[..4] == V4_TAG.to_le_bytes() || b[..4] == V4_WIDE_TAG.to_le_bytes())
+}
+
+/// A v3 or v4 state of more than two players.
+fn is_wide(b: &[u8]) -> bool {
+    b.len() >= 4 && (b[..4] == V3_WIDE_TAG.to_le_bytes() || b[..4] == V4_WIDE_TAG.to_le_bytes())
+}
+
+/// The map a v1/v2 state was played on.
+fn legacy_map(mirrored: bool) -> Map {
+    let arena = arena_map();
+    if mirrored { mirror_map(&arena) } else { arena }
+}
+
+/// Player slot before decoding (also the defaults for v1 fields that trail the state).
+const DECODE_PLAYER: Player = Player {
+    id: 0, x: 0, y: 0, vx: 0, vy: 0, facing: 0, health: 0,
+    lives: 0, shoot_cooldown: 0, grounded: false, state_flags: 0, respawn_timer: 0,
+    invincibility_source: invincibility::NONE, weapon: WEAPON_NONE, ammo: 0,
+    jumps_left: MAX_JUMPS, wall_sliding: false, wall_dir: 0,
+    stomped_by: -1, stomping_on: -1, stomp_shake_progress: 0,
+    stomp_last_shake_dir: 0, stomp_auto_run_dir: 0, stomp_auto_run_timer: 0,
+    stomp_cooldown: 0, stomp_cooldown_from: -1, stomp_airborne_ticks: 0, stamina: STAMINA_MAX,
+    dash_cooldown: 0, last_damaged_by: -1, last_damage_tick: 0,
+};
+
+/// Little-endian reader over bytes already length-checked by `check_encoded_len`.
+struct Reader<'a> {
+    b: &'a [u8],
+    off: usize,
+}
+
+impl Reader<'_> {
+    fn u8(&mut self) -> u8 {
+        let v = self.b[self.off];
+        self.off += 1;
+        v
+    }
+    fn i8(&mut self) -> i32 {
+        self.u8() as i8 as i32
+    }
+    fn i16(&mut self) -> i32 {
+        let v = i16::from_le_bytes([self.b[self.off], self.b[self.off + 1]]);
+        self.off += 2;
+        v as i32
+    }
+    fn u16(&mut self) -> u16 {
+        self.i16() as u16
+    }
+    fn i32(&mut self) -> i32 {
+        let o = self.off;
+        self.off += 4;
+        i32::from_le_bytes([self.b[o], self.b[o + 1], self.b[o + 2], self.b[o + 3]])
+    }
+    fn u32(&mut self) -> u32 {
+        self.i32() as u32
+    }
+    fn point(&mut self) -> SpawnPoint {
+        SpawnPoint { x: self.i32(), y: self.i32() }
+    }
+}
+
+fn read_map(r: &mut Reader) -> Map {
+    let mut m = read_map_base(r);
+    m.health_spawn_count = r.u8();
+    m.health_spawns = core::array::from_fn(|_| r.point());
+    m
+}
+
+/// The map of a v3 state, which has no health spawns.
+fn read_map_base(r: &mut Reader) -> Map {
+    let (width, height) = (r.i32(), r.i32());
+    let mut platforms: [Platform; NUM_PLATFORMS] = core::array::from_fn(|_| Platform {
+        x: r.i32(), y: r.i32(), width: r.i32(), height: r.i32(), one_way: false, hazard_damage: 0,
+    });
+    let spawns = core::array::from_fn(|_| r.point());
+    let weapon_spawns = core::array::from_fn(|_| r.point());
+    let one_way = r.u8();
+    for (i, p) in platforms.iter_mut().enumerate() {
+        p.one_way = one_way & 1 << i != 0;
+    }
+    for p in &mut platforms {
+        p.hazard_damage = r.i32();
+    }
+    Map {
+        width, height, platforms, spawns, weapon_spawns,
+        health_spawns: [SpawnPoint::default(); MAX_HEALTH_PICKUPS],
+        health_spawn_count: 0,
+        index: PlatformIndex::default(),
+    }
+}
+
+fn read_player_v2(r: &mut Reader) -> Player {
+    let id = r.i8();
+    let (x, y, vx, vy) = (r.i32(), r.i32(), r.i32(), r.i32());
+    let bits = r.u16();
+    let state_flags = r.u8() as u32;
+    let (health, lives, shoot_cooldown, respawn_timer) = (r.i16(), r.i16(), r.i16(), r.i16());
+    let weapon = r.u8() as i8;
+    let ammo = r.i16();
+    let (jumps_left, stomped_by, stomping_on) = (r.i8(), r.i8(), r.i8());
+    let (stomp_shake_progress, stomp_auto_run_timer, stomp_cooldown) = (r.i16(), r.i16(), r.i16());
+    let (invincibility_source, stomp_airborne_ticks) = (r.u8(), r.u8());
+    let stamina = r.i16();
+    Player {
+        id, x, y, vx, vy,
+        facing: dir_from_code(bits, 0),
+        health, lives, shoot_cooldown,
+        grounded: bits & 1 << 8 != 0,
+        state_flags, respawn_timer, invincibility_source, weapon, ammo, jumps_left,
+        wall_sliding: bits & 1 << 9 != 0,
+        wall_dir: dir_from_code(bits, 2),
+        stomped_by, stomping_on, stomp_shake_progress,
+        stomp_last_shake_dir: dir_from_code(bits, 4),
+        stomp_auto_run_dir: dir_from_code(bits, 6),
+        stomp_auto_run_timer, stomp_cooldown, stomp_cooldown_from: -1, stomp_airborne_ticks, stamina,
+        dash_cooldown: 0, last_damaged_by: -1, last_damage_tick: 0,
+    }
+}
+
+fn decode_state_v2(b: &[u8]) -> State {
+    let mut r = Reader { b, off: 4 };
+    let tick = r.i32();
+    let players = widen([read_player_v2(&mut r), read_player_v2(&mut r)], EMPTY_PLAYER);
+    let proj_count = r.u8();
+    let mut projectiles = [EMPTY_PROJECTILE; MAX_PROJECTILES];
+    for pj in &mut projectiles[..proj_count as usize] {
+        *pj = Projectile {
+            id: r.i32(),
+            owner_id: r.i32(),
+            x: r.i32(),
+            y: r.i32(),
+            vx: r.i32(),
+            vy: r.i32(),
+            lifetime: r.i32(),
+            weapon: r.u8() as i8,
+            owner_immune_until: r.i32(),
+            bounces_left: 0,
+        };
+    }
+    let pickup_count = r.u8();
+    let mut weapon_pickups = [EMPTY_PICKUP; MAX_WEAPON_PICKUPS];
+    for wp in &mut weapon_pickups[..pickup_count as usize] {
+        *wp = WeaponPickup {
+            id: r.i32(),
+            x: r.i32(),
+            y: r.i32(),
+            weapon: r.u8() as i8,
+            respawn_timer: r.i32(),
+            active_timer: 0,
+            dropped_ammo: 0,
+        };
+    }
+    let rng_state = r.u32();
+    let score = widen([r.u32(), r.u32()], 0);
+    let next_proj_id = r.i32();
+    let arena_left = r.i32();
+    let arena_right = r.i32();
+    let match_over = r.u8() != 0;
+    let winner = r.i32();
+    let death_linger_timer = r.i32();
+    let prev_buttons = widen([r.u8(), r.u8()], 0);
+    let cfg_initial_lives = r.i32();
+    let cfg_match_duration = r.i32();
+    let cfg_sudden_death = r.i32();
+    let cfg_lag_ticks = [r.u8(), r.u8()];
+    let mut pos_history_x = [[0; LAG_RING]; MAX_PLAYERS];
+    let mut pos_history_y = [[0; LAG_RING]; MAX_PLAYERS];
+    if cfg_lag_ticks != [0, 0] {
+        for i in 0..2 {
+            for k in 0..LAG_RING {
+                pos_history_x[i][k] = r.i32();
+                pos_history_y[i][k] = r.i32();
+            }
+        }
+    }
+    let cfg_bits = r.u8();
+
+    let mut s = State {
+        tick, players, player_count: 2, projectiles, proj_count, weapon_pickups, pickup_count,
+        health_pickups: [EMPTY_HEALTH_PICKUP; MAX_HEALTH_PICKUPS], health_pickup_count: 0,
+        rng_state, score, env_kills: [0; MAX_PLAYERS], pickup_stats: PickupStats::default(),
+        next_proj_id, arena_left, arena_right,
+        // The map's height, once `decode_state_with` knows the map
+        arena_top: 0, arena_bottom: 0,
+        match_over, winner, death_linger_timer, final_blow: false, prev_buttons,
+        cfg_initial_lives, cfg_match_duration, cfg_sudden_death, cfg_zone_mode: zone_mode::PINCH,
+        cfg_lag_ticks, pos_history_x, pos_history_y,
+        cfg_mirrored: cfg_bits & 1 != 0,
+        cfg_stamina: cfg_bits & 2 != 0,
+        cfg_active_despawn: false,
+        side_swap: cfg_bits & 4 != 0,
+        map: Arc::new(legacy_map(cfg_bits & 1 != 0)),
+    };
+    if is_wide(b) {
+        read_extra_players(&mut r, &mut s);
+    }
+    s
+}
+
+/// The player count and players 2.. of a wide v3 or v4 state (see `write_state_v2`).
+fn read_extra_players(r: &mut Reader, s: &mut State) {
+    s.player_count = r.u8();
+    for i in 2..s.player_count as usize {
+        s.players[i] = read_player_v2(r);
+        s.score[i] = r.u32();
+        s.env_kills[i] = r.u32();
+        s.prev_buttons[i] = r.u8();
+        if s.cfg_lag_ticks != [0, 0] {
+            for k in 0..LAG_RING {
+                s.pos_history_x[i][k] = r.i32();
+                s.pos_history_y[i][k] = r.i32();
+            }
+        }
+    }
+}
+
+fn decode_state_v1(b: &[u8]) -> State {
+    let mut off = 0usize;
+    let r32 = |b: &[u8], o: &mut usize| -> i32 {
+        let v = i32::from_le_bytes([b[*o], b[*o+1], b[*o+2], b[*o+3]]);
+        *o += 4; v
+    };
+    let ru32 = |b: &[u8], o: &mut usize| -> u32 {
+        let v = u32::from_le_bytes([b[*o], b[*o+1], b[*o+2], b[*o+3]]);
+        *o += 4; v
+    };
+
+    let tick = r32(b, &mut off);
+    let mut players = widen([DECODE_PLAYER; 2], EMPTY_PLAYER);
+    for p in &mut players[..2] {
+        p.id = r32(b, &mut off);
+        p.x = r32(b, &mut off);
+        p.y = r32(b, &mut off);
+        p.vx = r32(b, &mut off);
+        p.vy = r32(b, &mut off);
+        p.facing = r32(b, &mut off);
+        p.health = r32(b, &mut off);
+        p.lives = r32(b, &mut off);
+        p.shoot_cooldown = r32(b, &mut off);
+        p.grounded = b[off] != 0; off += 1;
+        p.state_flags = ru32(b, &mut off);
+        p.respawn_timer = r32(b, &mut off);
+        p.weapon = b[off] as i8; off += 1;
+        p.ammo = r32(b, &mut off);
+        p.jumps_left = r32(b, &mut off);
+        p.wall_sliding = b[off] != 0; off += 1;
+        p.wall_dir = r32(b, &mut off);
+        p.stomped_by = r32(b, &mut off);
+        p.stomping_on = r32(b, &mut off);
+        p.stomp_shake_progress = r32(b, &mut off);
+        p.stomp_last_shake_dir = r32(b, &mut off);
+        p.stomp_auto_run_dir = r32(b, &mut off);
+        p.stomp_auto_run_timer = r32(b, &mut off);
+        p.stomp_cooldown = r32(b, &mut off);
+    }
+    let proj_count = b[off]; off += 1;
+    let mut projectiles = [EMPTY_PROJECTILE; MAX_PROJECTILES];
+    for i in 0..proj_count as usize {
+        projectiles[i] = Projectile {
+            id: r32(b, &mut off),
+            owner_id: r32(b, &mut off),
+            x: r32(b, &mut off),
+            y: r32(b, &mut off),
+            vx: r32(b, &mut off),
+            vy: r32(b, &mut off),
+            lifetime: r32(b, &mut off),
+            weapon: { let w = b[off] as i8; off += 1; w },
+            owner_immune_until: OWNER_IMMUNE_FOREVER,
+            bounces_left: 0,
+        };
+    }
+    let pickup_count = b[off]; off += 1;
+    let mut weapon_pickups = [EMPTY_PICKUP; MAX_WEAPON_PICKUPS];
+    for i in 0..pickup_count as usize {
+        weapon_pickups[i] = WeaponPickup {
+            id: r32(b, &mut off),
+            x: r32(b, &mut off),
+            y: r32(b, &mut off),
+            weapon: { let w = b[off] as i8; off += 1; w },
+            respawn_timer: r32(b, &mut off),
+            active_timer: 0,
+            dropped_ammo: 0,
+        };
+    }
+    let rng_state = ru32(b, &mut off);
+    let s0 = ru32(b, &mut off);
+    let s1 = ru32(b, &mut off);
+    let next_proj_id = r32(b, &mut off);
+    let arena_left = r32(b, &mut off);
+    let arena_right = r32(b, &mut off);
+    let match_over = b[off] != 0; off += 1;
+    let winner = r32(b, &mut off);
+    let death_linger_timer = r32(b, &mut off);
+    let prev_b0 = b[off]; off += 1;
+    let prev_b1 = b[off]; off += 1;
+    // Config fields (appended in newer format; default to constants if missing). A partial
+    // group is dropped with everything after it, where `check_encoded_len` expects the lag
+    // config: reading on would take config bytes for the lag and overrun the history.
+    let (cfg_initial_lives, cfg_match_duration, cfg_sudden_death) = if off + 12 <= b.len() {
+        (r32(b, &mut off), r32(b, &mut off), r32(b, &mut off))
+    } else {
+        off = b.len();
+        (INITIAL_LIVES, MATCH_DURATION_TICKS, SUDDEN_DEATH_START_TICK)
+    };
+    let mut cfg_lag_ticks = [0u8; 2];
+    if off + 2 <= b.len() {
+        cfg_lag_ticks = [b[off], b[off + 1]];
+        off += 2;
+    }
+    let mut pos_history_x = [[0; LAG_RING]; MAX_PLAYERS];
+    let mut pos_history_y = [[0; LAG_RING]; MAX_PLAYERS];
+    if cfg_lag_ticks != [0, 0] {
+        for i in 0..2 {
+            for k in 0..LAG_RING {
+                pos_history_x[i][k] = r32(b, &mut off);
+                pos_history_y[i][k] = r32(b, &mut off);
+            }
+        }
+    }
+    let mut cfg_mirrored = false;
+    if off < b.len() {
+        cfg_mirrored = b[off] != 0;
+        off += 1;
+    }
+    if off + 2 <= b.len() {
+        players[0].invincibility_source = b[off];
+        players[1].invincibility_source = b[off + 1];
+        off += 2;
+    }
+    if off + 2 <= b.len() {
+        players[0].stomp_airborne_ticks = b[off];
+        players[1].stomp_airborne_ticks = b[off + 1];
+        off += 2;
+    }
+    let mut cfg_stamina = false;
+    if off + 9 <= b.len() {
+        cfg_stamina = b[off] != 0;
+        off += 1;
+        players[0].stamina = r32(b, &mut off);
+        players[1].stamina = r32(b, &mut off);
+    }
+    if off + 4 * proj_count as usize <= b.len() {
+        for pj in &mut projectiles[..proj_count as usize] {
+            pj.owner_immune_until = r32(b, &mut off);
+        }
+    }
+    let mut side_swap = false;
+    if off < b.len() {
+        side_swap = b[off] != 0;
+    }
+
+    State {
+        tick, players, player_count: 2, projectiles, proj_count, weapon_pickups, pickup_count,
+        health_pickups: [EMPTY_HEALTH_PICKUP; MAX_HEALTH_PICKUPS], health_pickup_count: 0,
+        rng_state, score: widen([s0, s1], 0), env_kills: [0; MAX_PLAYERS],
+        pickup_stats: PickupStats::default(), next_proj_id,
+        arena_left, arena_right, arena_top: 0, arena_bottom: 0, // as in `decode_state_v2`
+        match_over, winner, death_linger_timer, final_blow: false,
+        prev_buttons: widen([prev_b0, prev_b1], 0),
+        cfg_initial_lives, cfg_match_duration, cfg_sudden_death, cfg_zone_mode: zone_mode::PINCH,
+        cfg_lag_ticks, pos_history_x, pos_history_y, cfg_mirrored, cfg_stamina,
+        cfg_active_despawn: false, side_swap,
+        map: Arc::new(legacy_map(cfg_mirrored)),
+    }
+}
+
+// -- State validation ----------------------------------------------------------
+
+/// How far outside the map an entity may sit before its position is considered impossible.
+/// Same margin the sim uses to cull stray projectiles.
+pub const OOB_MARGIN: Fp = 50 << 8; // 50px
+
+/// A field in an imported/decoded State that no legal sequence of ticks can produce.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum StateIssue {
+    /// Encoded state ends before the fields it declares.
+    Truncated,
+    TooManyProjectiles(u8),
+    TooManyPickups(u8),
+    TooManyHealthPickups(u8),
+    /// `State::player_count` outside 2..=MAX_PLAYERS.
+    PlayerCount(u8),
+    PlayerId { player: usize, id: i32 },
+    Health { player: usize, health: i32 },
+    Lives { player: usize, lives: i32 },
+    PlayerWeapon { player: usize, weapon: i8 },
+    PlayerPosition { player: usize, x: Fp, y: Fp },
+    /// Unknown source, or a source without `flag::INVINCIBLE` (or the reverse).
+    InvincibilitySource { player: usize, source: u8, flags: u32 },
+    Stamina { player: usize, stamina: i32 },
+    DashCooldown { player: usize, cooldown: i32 },
+    /// Zone kill credit to nobody's id, the player's own, or for damage after the state's tick
+    /// (or a tick with nobody to credit).
+    LastDamage { player: usize, by: i32, tick: i32 },
+    ProjectileOwner { id: i32, owner_id: i32 },
+    ProjectileWeapon { id: i32, weapon: i8 },
+    /// More bounces left than the weapon ever grants.
+    ProjectileBounces { id: i32, bounces_left: u8 },
+    ProjectilePosition { id: i32, x: Fp, y: Fp },
+    DuplicateProjectileId(i32),
+    /// `next_proj_id` not past every live projectile's id, so the next shot would reuse one.
+    NextProjectileId { next: i32, max_live: i32 },
+    PickupWeapon { id: i32, weapon: i8 },
+    PickupPosition { id: i32, x: Fp, y: Fp },
+    /// A dropped weapon holding more rounds than the weapon's full ammo (or fewer than none).
+    PickupAmmo { id: i32, ammo: i32 },
+    DuplicatePickupId(i32),
+    HealthPickupPosition { slot: usize, x: Fp, y: Fp },
+    /// `State::cfg_zone_mode` not a `zone_mode`.
+    ZoneMode(u8),
+    /// Arena bounds other than the tick and match phase give (`consistent_arena_bounds`).
+    ArenaBounds { actual: ZoneBounds, expected: ZoneBounds },
+    /// A v3 or v4 state carrying a different map (`map_hash` of each) than the one supplied.
+    MapMismatch { expected: [u8; 32], actual: [u8; 32] },
+}
+
+impl core::fmt::Display for StateIssue {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match *self {
+            StateIssue::Truncated => write!(f, "encoded state is truncated"),
+            StateIssue::TooManyProjectiles(n) => write!(f, "proj_count {n} > {MAX_PROJECTILES}"),
+            StateIssue::TooManyPickups(n) => write!(f, "pickup_count {n} > {MAX_WEAPON_PICKUPS}"),
+            StateIssue::TooManyHealthPickups(n) => {
+                write!(f, "health_pickup_count {n} > {MAX_HEALTH_PICKUPS}")
+            }
+            StateIssue::PlayerCount(n) => write!(f, "player_count {n} outside 2..={MAX_PLAYERS}"),
+            StateIssue::PlayerId { player, id } => write!(f, "player {player} has id {id}"),
+            StateIssue::Health { player, health } => write!(f, "player {player} health {health}"),
+            StateIssue::Lives { player, lives } => write!(f, "player {player} lives {lives}"),
+            StateIssue::PlayerWeapon { player, weapon } => write!(f, "player {player} weapon {weapon}"),
+            StateIssue::PlayerPosition { player, x, y } => {
+                write!(f, "player {player} outside map at ({x}, {y})")
+            }
+            StateIssue::InvincibilitySource { player, source, flags } => {
+                write!(f, "player {player} invincibility source {source} with flags {flags:#x}")
+            }
+            StateIssue::Stamina { player, stamina } => write!(f, "player {player} stamina {stamina}"),
+            StateIssue::DashCooldown { player, cooldown } => write!(f, "player {player} dash cooldown {cooldown}"),
+            StateIssue::LastDamage { player, by, tick } => {
+                write!(f, "player {player} last damaged by {by} at tick {tick}")
+            }
+            StateIssue::ProjectileOwner { id, owner_id } => {
+                write!(f, "projectile {id} owned by player {owner_id}")
+            }
+            StateIssue::ProjectileWeapon { id, weapon } => write!(f, "projectile {id} weapon {weapon}"),
+            StateIssue::ProjectileBounces { id, bounces_left } => {
+                write!(f, "projectile {id} has {bounces_left} bounces left")
+            }
+            StateIssue::ProjectilePosition { id, x, y } => {
+                write!(f, "projectile {id} outside map at ({x}, {y})")
+            }
+            StateIssue::DuplicateProjectileId(id) => write!(f, "duplicate projectile id {id}"),
+            StateIssue::NextProjectileId { next, max_live } => {
+                write!(f, "next_proj_id {next} not past live projectile id {max_live}")
+            }
+            StateIssue::PickupWeapon { id, weapon } => write!(f, "pickup {id} weapon {weapon}"),
+            StateIssue::PickupPosition { id, x, y } => write!(f, "pickup {id} outside map at ({x}, {y})"),
+            StateIssue::PickupAmmo { id, ammo } => write!(f, "pickup {id} holds {ammo} rounds"),
+            StateIssue::DuplicatePickupId(id) => write!(f, "duplicate pickup id {id}"),
+            StateIssue::HealthPickupPosition { slot, x, y } => {
+                write!(f, "health pickup {slot} outside map at ({x}, {y})")
+            }
+            StateIssue::ZoneMode(mode) => write!(f, "zone mode {mode}"),
+            StateIssue::ArenaBounds { actual, expected } => {
+                write!(f, "arena bounds {actual}, the tick gives {expected}")
+            }
+            StateIssue::MapMismatch { expected, actual } => {
+                let short = |h: [u8; 32]| u32::from_be_bytes([h[0], h[1], h[2], h[3]]);
+                write!(f, "state map {:08x}.. is not the expected map {:08x}..", short(actual), short(expected))
+            }
+        }
+    }
+}
+
+#[inline(always)]
+fn weapon_in_range(w: i8) -> bool {
+    (0..WEAPON_COUNT as i8).contains(&w)
+}
+
+#[inline(always)]
+fn invincibility_consistent(p: &Player) -> bool {
+    let invincible = p.state_flags & flag::INVINCIBLE != 0;
+    p.invincibility_source <= invincibility::SCRIPTED
+        && invincible == (p.invincibility_source != invincibility::NONE)
+}
+
+#[inline(always)]
+fn pos_in_map(x: Fp, y: Fp, map: &Map) -> bool {
+    (-OOB_MARGIN..=map.width + OOB_MARGIN).contains(&x)
+        && (-OOB_MARGIN..=map.height + OOB_MARGIN).contains(&y)
+}
+
+/// Check every field range the sim relies on. Returns all issues found, not just the first.
+pub fn validate_state(s: &State) -> Result<(), Vec<StateIssue>> {
+    let map = &*s.map;
+    let mut issues = Vec::new();
+    if s.proj_count as usize > MAX_PROJECTILES {
+        issues.push(StateIssue::TooManyProjectiles(s.proj_count));
+    }
+    if s.pickup_count as usize > MAX_WEAPON_PICKUPS {
+        issues.push(StateIssue::TooManyPickups(s.pickup_count));
+    }
+    if s.health_pickup_count as usize > MAX_HEALTH_PICKUPS {
+        issues.push(StateIssue::TooManyHealthPickups(s.health_pickup_count));
+    }
+    if !(2..=MAX_PLAYERS as u8).contains(&s.player_count) {
+        issues.push(StateIssue::PlayerCount(s.player_count));
+    }
+    let player_count = (s.player_count as usize).clamp(2, MAX_PLAYERS);
+    for (i, p) in s.players[..player_count].iter().enumerate() {
+        if p.id != i as i32 {
+            issues.push(StateIssue::PlayerId { player: i, id: p.id });
+        }
+        if !(0..=MAX_HEALTH).contains(&p.health) {
+            issues.push(StateIssue::Health { player: i, health: p.health });
+        }
+        if p.lives < 0 {
+            issues.push(StateIssue::Lives { player: i, lives: p.lives });
+        }
+        if p.weapon != WEAPON_NONE && !weapon_in_range(p.weapon) {
+            issues.push(StateIssue::PlayerWeapon { player: i, weapon: p.weapon });
+        }
+        if !pos_in_map(p.x, p.y, map) {
+            issues.push(StateIssue::PlayerPosition { player: i, x: p.x, y: p.y });
+        }
+        if !invincibility_consistent(p) {
+            issues.push(StateIssue::InvincibilitySource {
+                player: i, source: p.invincibility_source, flags: p.state_flags,
+            });
+        }
+        if !(0..=STAMINA_MAX).contains(&p.stamina) {
+            issues.push(StateIssue::Stamina { player: i, stamina: p.stamina });
+        }
+        if !(0..=DASH_COOLDOWN_TICKS).contains(&p.dash_cooldown) {
+            issues.push(StateIssue::DashCooldown { player: i, cooldown: p.dash_cooldown });
+        }
+        if !last_damage_consistent(p, i, player_count, s.tick) {
+            issues.push(StateIssue::LastDamage { player: i, by: p.last_damaged_by, tick: p.last_damage_tick });
+        }
+    }
+    let proj_count = (s.proj_count as usize).min(MAX_PROJECTILES);
+    for i in 0..proj_count {
+        let pj = &s.projectiles[i];
+        if !(0..player_count as i32).contains(&pj.owner_id) {
+            issues.push(StateIssue::ProjectileOwner { id: pj.id, owner_id: pj.owner_id });
+        }
+        if !weapon_in_range(pj.weapon) {
+            issues.push(StateIssue::ProjectileWeapon { id: pj.id, weapon: pj.weapon });
+        } else if pj.bounces_left > fp_weapon_stats(pj.weapon).bounces {
+            issues.push(StateIssue::ProjectileBounces { id: pj.id, bounces_left: pj.bounces_left });
+        }
+        if !pos_in_map(pj.x, pj.y, map) {
+            issues.push(StateIssue::ProjectilePosition { id: pj.id, x: pj.x, y: pj.y });
+        }
+        if s.projectiles[..i].iter().any(|o| o.id == pj.id) {
+            issues.push(StateIssue::DuplicateProjectileId(pj.id));
+        }
+    }
+    if let Some(max_live) = max_projectile_id(s) {
+        if s.next_proj_id <= max_live {
+            issues.push(StateIssue::NextProjectileId { next: s.next_proj_id, max_live });
+        }
+    }
+    let pickup_count = (s.pickup_count as usize).min(MAX_WEAPON_PICKUPS);
+    for i in 0..pickup_count {
+        let wp = &s.weapon_pickups[i];
+        if !weapon_in_range(wp.weapon) {
+            issues.push(StateIssue::PickupWeapon { id: wp.id, weapon: wp.weapon });
+        } else if !(0..=fp_weapon_stats(wp.weapon).ammo).contains(&wp.dropped_ammo) {
+            issues.push(StateIssue::PickupAmmo { id: wp.id, ammo: wp.dropped_ammo });
+        }
+        if !pos_in_map(wp.x, wp.y, map) {
+            issues.push(StateIssue::PickupPosition { id: wp.id, x: wp.x, y: wp.y });
+        }
+        if s.weapon_pickups[..i].iter().any(|o| o.id == wp.id) {
+            issues.push(StateIssue::DuplicatePickupId(wp.id));
+        }
+    }
+    let health_pickup_count = (s.health_pickup_count as usize).min(MAX_HEALTH_PICKUPS);
+    for (slot, hp) in s.health_pickups[..health_pickup_count].iter().enumerate() {
+        if !pos_in_map(hp.x, hp.y, map) {
+            issues.push(StateIssue::HealthPickupPosition { slot, x: hp.x, y: hp.y });
+        }
+    }
+    if s.cfg_zone_mode >= zone_mode::COUNT {
+        issues.push(StateIssue::ZoneMode(s.cfg_zone_mode));
+    }
+    let expected = consistent_arena_bounds(s);
+    if s.arena_bounds() != expected {
+        issues.push(StateIssue::ArenaBounds { actual: s.arena_bounds(), expected });
+    }
+    if issues.is_empty() { Ok(()) } else { Err(issues) }
+}
+
+/// Player `i` was last hurt by another of the `player_count` players no later than `tick`, or
+/// by nobody yet.
+fn last_damage_consistent(p: &Player, i: usize, player_count: usize, tick: i32) -> bool {
+    match p.last_damaged_by {
+        -1 => p.last_damage_tick == 0,
+        by => (0..player_count as i32).contains(&by) && by != i as i32 && (0..=tick).contains(&p.last_damage_tick),
+    }
+}
+
+/// Highest id among the live projectiles, if any.
+fn max_projectile_id(s: &State) -> Option<i32> {
+    let proj_count = (s.proj_count as usize).min(MAX_PROJECTILES);
+    s.projectiles[..proj_count].iter().map(|pj| pj.id).max()
+}
+
+/// Lenient alternative to rejecting: clamp ranges, drop projectiles/pickups that can't be
+/// repaired, bump `next_proj_id` past the live projectile ids and recompute arena bounds. Returns the issues that were fixed; the result always
+/// passes `validate_state`.
+pub fn sanitize_state(s: &mut State) -> Vec<StateIssue> {
+    let issues = match validate_state(s) {
+        Ok(()) => return Vec::new(),
+        Err(issues) => issues,
+    };
+    let map = Arc::clone(&s.map);
+    let clamp_x = |x: Fp| x.clamp(-OOB_MARGIN, map.width + OOB_MARGIN);
+    let clamp_y = |y: Fp| y.clamp(-OOB_MARGIN, map.height + OOB_MARGIN);
+
+    s.player_count = s.player_count.clamp(2, MAX_PLAYERS as u8);
+    let player_count = s.player_count as usize;
+    for (i, p) in s.players[..player_count].iter_mut().enumerate() {
+        p.id = i as i32;
+        p.health = p.health.clamp(0, MAX_HEALTH);
+        p.lives = p.lives.max(0);
+        if p.weapon != WEAPON_NONE && !weapon_in_range(p.weapon) {
+            p.weapon = WEAPON_NONE;
+            p.ammo = 0;
+        }
+        p.x = clamp_x(p.x);
+        p.y = clamp_y(p.y);
+        if !invincibility_consistent(p) {
+            // Keep the bit (it gates damage); an unexplained one is treated as scripted
+            p.invincibility_source = if p.state_flags & flag::INVINCIBLE != 0 {
+                invincibility::SCRIPTED
+            } else {
+                invincibility::NONE
+            };
+        }
+        p.stamina = p.stamina.clamp(0, STAMINA_MAX);
+        p.dash_cooldown = p.dash_cooldown.clamp(0, DASH_COOLDOWN_TICKS);
+        if !last_damage_consistent(p, i, player_count, s.tick) {
+            (p.last_damaged_by, p.last_damage_tick) = (-1, 0);
+        }
+    }
+
+    // Compact in place, keeping only projectiles the sim could have spawned
+    let proj_count = (s.proj_count as usize).min(MAX_PROJECTILES);
+    let mut write = 0usize;
+    for read in 0..proj_count {
+        let mut pj = s.projectiles[read];
+        let keep = (0..player_count as i32).contains(&pj.owner_id)
+            && weapon_in_range(pj.weapon)
+            && pos_in_map(pj.x, pj.y, &map)
+            && !s.projectiles[..write].iter().any(|o| o.id == pj.id);
+        if keep {
+            pj.bounces_left = pj.bounces_left.min(fp_weapon_stats(pj.weapon).bounces);
+            s.projectiles[write] = pj;
+            write += 1;
+        }
+    }
+    for i in write..MAX_PROJECTILES {
+        s.projectiles[i] = EMPTY_PROJECTILE;
+    }
+    s.proj_count = write as u8;
+    if let Some(max_live) = max_projectile_id(s) {
+        s.next_proj_id = s.next_proj_id.max(max_live + 1);
+    }
+
+    let pickup_count = (s.pickup_count as usize).min(MAX_WEAPON_PICKUPS);
+    let mut write = 0usize;
+    for read in 0..pickup_count {
+        let mut wp = s.weapon_pickups[read];
+        if !weapon_in_range(wp.weapon) || s.weapon_pickups[..write].iter().any(|o| o.id == wp.id) {
+            continue;
+        }
+        wp.x = clamp_x(wp.x);
+        wp.y = clamp_y(wp.y);
+        wp.dropped_ammo = wp.dropped_ammo.clamp(0, fp_weapon_stats(wp.weapon).ammo);
+        s.weapon_pickups[write] = wp;
+        write += 1;
+    }
+    for i in write..MAX_WEAPON_PICKUPS {
+        s.weapon_pickups[i] = EMPTY_PICKUP;
+    }
+    s.pickup_count = write as u8;
+
+    s.health_pickup_count = s.health_pickup_count.min(MAX_HEALTH_PICKUPS as u8);
+    for hp in &mut s.health_pickups[..s.health_pickup_count as usize] {
+        hp.x = clamp_x(hp.x);
+        hp.y = clamp_y(hp.y);
+    }
+
+    if s.cfg_zone_mode >= zone_mode::COUNT {
+        s.cfg_zone_mode = zone_mode::PINCH;
+    }
+    let bounds = consistent_arena_bounds(s);
+    s.set_arena_bounds(bounds);
+    issues
+}
+
+/// Check that `b` holds every field `decode_state` will read (counts within maxima, no
+/// short read), so decoding untrusted bytes can't index out of bounds. Cheap: it reads the
+/// counts, not the fields, so guests run it ahead of `decode_state` to fail with the reason.
+pub fn check_encoded_len(b: &[u8]) -> Result<(), StateIssue> {
+    if is_v2(b) || is_v3(b) || is_v4(b) { check_encoded_len_v2(b) } else { check_encoded_len_v1(b) }
+}
+
+/// v2 has no optional groups: the whole layout is fixed by the two counts and the lag config
+/// (v3 adds the fixed-size env kills, pickup timers, projectile bounces and map, v4 the health
+/// pickups, and a wide state its extra players).
+fn check_encoded_len_v2(b: &[u8]) -> Result<(), StateIssue> {
+    let mut off = 8 + 2 * V2_PLAYER_BYTES;
+    let proj_count = *b.get(off).ok_or(StateIssue::Truncated)?;
+    if proj_count as usize > MAX_PROJECTILES {
+        return Err(StateIssue::TooManyProjectiles(proj_count));
+    }
+    off += 1 + proj_count as usize * V2_PROJ_BYTES;
+    let pickup_count = *b.get(off).ok_or(StateIssue::Truncated)?;
+    if pickup_count as usize > MAX_WEAPON_PICKUPS {
+        return Err(StateIssue::TooManyPickups(pickup_count));
+    }
+    off += 1 + pickup_count as usize * V2_PICKUP_BYTES + V2_TAIL_BYTES;
+    let lag = b.get(off..off + 2).ok_or(StateIssue::Truncated)?;
+    let lagged = lag != [0, 0];
+    off += 2 + if lagged { HISTORY_BYTES } else { 0 } + 1;
+    if is_wide(b) {
+        let player_count = *b.get(off).ok_or(StateIssue::Truncated)?;
+        if !(3..=MAX_PLAYERS as u8).contains(&player_count) {
+            return Err(StateIssue::PlayerCount(player_count));
+        }
+        let history = if lagged { HISTORY_BYTES / 2 } else { 0 };
+        off += 1 + (player_count as usize - 2) * (WIDE_PLAYER_BYTES + history);
+    }
+    if is_v3(b) {
+        off += V3_EXTRA_BYTES + MAP_BASE_LEN;
+    } else if is_v4(b) {
+        off += V3_EXTRA_BYTES + V4_EXTRA_BYTES + MAP_ENCODED_LEN;
+    }
+    if b.len() < off {
+        return Err(StateIssue::Truncated);
+    }
+    if is_v4(b) {
+        // Decoding reads the tail from the end, so that's where the count is
+        let health_pickup_count = b[b.len() - MAP_ENCODED_LEN - V4_EXTRA_BYTES];
+        if health_pickup_count as usize > MAX_HEALTH_PICKUPS {
+            return Err(StateIssue::TooManyHealthPickups(health_pickup_count));
+        }
+    }
+    Ok(())
+}
+
+fn check_encoded_len_v1(b: &[u8]) -> Result<(), StateIssue> {
+    const PLAYER_BYTES: usize = 21 * 4 + 3;
+    const PROJ_BYTES: usize = 7 * 4 + 1;
+    const PICKUP_BYTES: usize = 4 * 4 + 1;
+    const TAIL_BYTES: usize = 6 * 4 + 1 + 2 * 4 + 2;
+    const CFG_BYTES: usize = 3 * 4;
+
+    let mut off = 4 + 2 * PLAYER_BYTES;
+    let proj_count = *b.get(off).ok_or(StateIssue::Truncated)?;
+    if proj_count as usize > MAX_PROJECTILES {
+        return Err(StateIssue::TooManyProjectiles(proj_count));
+    }
+    off += 1 + proj_count as usize * PROJ_BYTES;
+    let pickup_count = *b.get(off).ok_or(StateIssue::Truncated)?;
+    if pickup_count as usize > MAX_WEAPON_PICKUPS {
+        return Err(StateIssue::TooManyPickups(pickup_count));
+    }
+    off += 1 + pickup_count as usize * PICKUP_BYTES + TAIL_BYTES;
+    if b.len() < off {
+        return Err(StateIssue::Truncated);
+    }
+    // Optional trailing config; history follows only when lag is on
+    off += CFG_BYTES;
+    if b.len() >= off + 2 && b[off..off + 2] != [0, 0] && b.len() < off + 2 + HISTORY_BYTES {
+        return Err(StateIssue::Truncated);
+    }
+    Ok(())
+}
+
+/// Decode untrusted bytes (e.g. a chunk boundary or client-supplied snapshot) and validate the
+/// result. With `sanitize`, out-of-range fields are clamped instead of rejected; truncated or
+/// over-count input is always rejected, as is a v3 or v4 state carrying a map other than `map`
+/// (see `decode_state_on`).
+pub fn try_decode_state(b: &[u8], map: &Map, sanitize: bool) -> Result<State, Vec<StateIssue>> {
+    let mut s = decode_state_on(b, map).map_err(|e| vec![e])?;
+    if sanitize {
+        sanitize_state(&mut s);
+    } else {
+        validate_state(&s)?;
+    }
+    Ok(s)
+}
@@ -0,0 +1,123 @@
+//! The platform lookup grid (`Map::build_index`): which platform slots touch each square of a
+//! map, so collision queries only look at the platforms near them. A pure accelerator: every
+//! query returns a superset of the platforms it could hit, visited in slot order, so the sim
+//! steps identically with or without one.
+
+use super::*;
+
+/// Side of a `PlatformGrid` cell.
+pub const PLATFORM_GRID_CELL: Fp = fp(64);
+/// Most cells a `PlatformGrid` has across or down; a larger map shares its edge cells.
+pub const PLATFORM_GRID_MAX_CELLS: i32 = 256;
+
+const _: () = assert!(NUM_PLATFORMS <= 32, "a cell's platforms are a u32 mask");
+
+/// Every platform slot, as a mask.
+pub(super) const ALL_PLATFORMS: u32 = if NUM_PLATFORMS == 32 { u32::MAX } else { (1 << NUM_PLATFORMS) - 1 };
+
+/// The platform slots of each `PLATFORM_GRID_CELL` square of a map, as masks. A platform is in
+/// every cell its closed box (edges included) touches, whatever its size, so the grid takes no
+/// view on which platforms a query skips.
+#[derive(Clone, Debug)]
+pub struct PlatformGrid {
+    cols: i32,
+    rows: i32,
+    cells: Vec<u32>,
+}
+
+impl PlatformGrid {
+    pub fn new(map: &Map) -> Self {
+        let count = |size: Fp| {
+            let cells = (size as i64 + PLATFORM_GRID_CELL as i64 - 1).div_euclid(PLATFORM_GRID_CELL as i64);
+            cells.clamp(1, PLATFORM_GRID_MAX_CELLS as i64) as i32
+        };
+        let (cols, rows) = (count(map.width), count(map.height));
+        let mut cells = vec![0u32; (cols * rows) as usize];
+        for (i, plat) in map.platforms.iter().enumerate() {
+            let (x0, x1) = (plat.x.min(plat.x + plat.width), plat.x.max(plat.x + plat.width));
+            let (y0, y1) = (plat.y.min(plat.y + plat.height), plat.y.max(plat.y + plat.height));
+            let ((c0, c1), (r0, r1)) = (cell_span(x0, x1, cols), cell_span(y0, y1, rows));
+            for r in r0..=r1 {
+                for c in c0..=c1 {
+                    cells[(r * cols + c) as usize] |= 1 << i;
+                }
+            }
+        }
+        PlatformGrid { cols, rows, cells }
+    }
+
+    /// Platform slots in the cells the closed box (x0..=x1, y0..=y1) touches: every platform
+    /// whose closed box meets it, and maybe some more.
+    #[inline(always)]
+    pub fn query(&self, x0: Fp, y0: Fp, x1: Fp, y1: Fp) -> u32 {
+        let ((c0, c1), (r0, r1)) = (cell_span(x0, x1, self.cols), cell_span(y0, y1, self.rows));
+        let mut mask = 0;
+        for r in r0..=r1 {
+            for c in c0..=c1 {
+                mask |= self.cells[(r * self.cols + c) as usize];
+            }
+        }
+        mask
+    }
+}
+
+/// First and last cell (of `n`) that the closed span lo..=hi touches. Clamping keeps a span off
+/// the grid on its edge cells, and two spans that meet still share a cell.
+#[inline(always)]
+fn cell_span(lo: Fp, hi: Fp, n: i32) -> (i32, i32) {
+    let cell = |v: Fp| (v as i64).div_euclid(PLATFORM_GRID_CELL as i64).clamp(0, n as i64 - 1) as i32;
+    (cell(lo), cell(hi))
+}
+
+/// A map's `PlatformGrid`, once built. Not part of the map: serde skips it, and maps compare
+/// equal with or without one.
+#[derive(Clone, Debug, Default)]
+pub struct PlatformIndex(Option<PlatformGrid>);
+
+impl PartialEq for PlatformIndex {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+impl Eq for PlatformIndex {}
+
+impl Map {
+    /// Build the platform grid the collision queries use from now on. Worth it where a map
+    /// steps many ticks (the browser); the guests scan every platform rather than pay its
+    /// memory. Changing the platforms afterwards means building it again.
+    pub fn build_index(&mut self) {
+        self.index = PlatformIndex(Some(PlatformGrid::new(self)));
+    }
+
+    pub fn is_indexed(&self) -> bool {
+        self.index.0.is_some()
+    }
+
+    /// Platform slots that may meet the closed box (x0..=x1, y0..=y1), as a mask: all of them
+    /// without an index.
+    #[inline(always)]
+    pub(super) fn platforms_near(&self, x0: Fp, y0: Fp, x1: Fp, y1: Fp) -> u32 {
+        match &self.index.0 {
+            Some(grid) => grid.query(x0, y0, x1, y1),
+            None => ALL_PLATFORMS,
+        }
+    }
+}
+
+/// Slots `first` and up, as a mask.
+#[inline(always)]
+pub(super) fn slots_from(first: usize) -> u32 {
+    u32::MAX.checked_shl(first as u32).unwrap_or(0)
+}
+
+/// The slots of `mask`, lowest first.
+#[inline(always)]
+pub(super) fn slots(mut mask: u32) -> impl Iterator<Item = usize> {
+    std::iter::from_fn(move || {
+        if mask == 0 { return None; }
+        let i = mask.trailing_zeros() as usize;
+        mask &= mask - 1;
+        Some(i)
+    })
+}
@@ -0,0 +1,181 @@
+//! Transcript, seed and state hashes, and the ring of recent state hashes.
+
+use sha2::{Digest, Sha256};
+
+use super::*;
+
+// -- Hashing -----------------------------------------------------------------
+
+pub fn hash_transcript(transcript: &[[FpInput; 2]]) -> [u8; 32] {
+    let mut buf = vec![0u8; transcript.len() * 6];
+    for (i, tick) in transcript.iter().enumerate() {
+        let off = i * 6;
+        buf[off] = tick[0].buttons;
+        buf[off + 1] = tick[0].aim_x as u8;
+        buf[off + 2] = tick[0].aim_y as u8;
+        buf[off + 3] = tick[1].buttons;
+        buf[off + 4] = tick[1].aim_x as u8;
+        buf[off + 5] = tick[1].aim_y as u8;
+    }
+    let mut h = Sha256::new();
+    h.update(&buf);
+    h.finalize().into()
+}
+
+/// `hash_transcript` of a match of `player_count` players: SHA-256 over the raw tick bytes
+/// (`INPUT_BYTES` per player), the same bytes `run_streaming` hashes.
+pub fn hash_transcript_wide(transcript: &[TickInputs], player_count: usize) -> [u8; 32] {
+    let mut h = Sha256::new();
+    for tick in transcript {
+        for inp in &tick[..player_count] {
+            h.update([inp.buttons, inp.aim_x as u8, inp.aim_y as u8]);
+        }
+    }
+    h.finalize().into()
+}
+
+pub fn hash_seed(seed: u32) -> [u8; 32] {
+    let mut h = Sha256::new();
+    h.update(chickenz_core_mini::seed_preimage(seed));
+    h.finalize().into()
+}
+
+/// Hash the full game state (for chunk boundary commitments): SHA-256 of `encode_state`,
+/// fed field by field (no intermediate Vec).
+pub fn hash_state(s: &State) -> [u8; 32] {
+    hash_state_as(s, STATE_FORMAT)
+}
+
+/// `hash_state` over a specific format (`V1` for states committed by older proofs).
+pub fn hash_state_as(s: &State, format: StateFormat) -> [u8; 32] {
+    let mut h = Sha256::new();
+    write_state(s, format, &mut h);
+    h.finalize().into()
+}
+
+/// FNV-1a (64-bit) over the same bytes, in the same field order, as `hash_state`: cheap
+/// enough to take every tick, for telling two timelines apart (`RecentHashes`, a client's
+/// desync check). Not consensus-critical: nothing proved or settled depends on it, and a
+/// collision only delays noticing a desync. Proofs and published states use `hash_state`.
+pub fn checksum_state(s: &State) -> u64 {
+    let mut h = Fnv64(0xcbf2_9ce4_8422_2325);
+    write_state(s, STATE_FORMAT, &mut h);
+    h.0
+}
+
+/// FNV-1a, 64-bit.
+struct Fnv64(u64);
+
+impl ByteSink for Fnv64 {
+    fn extend_from_slice(&mut self, bytes: &[u8]) {
+        for &b in bytes {
+            self.0 = (self.0 ^ b as u64).wrapping_mul(0x0000_0100_0000_01b3);
+        }
+    }
+}
+
+// -- Recent state hashes (client-side rollback validation) ---------------------
+
+/// Ticks a `RecentHashes` ring remembers.
+pub const RECENT_HASH_COUNT: usize = 16;
+
+/// The last `RECENT_HASH_COUNT` (tick, `checksum_state`) pairs of the states a client stepped
+/// through, oldest first. After a rollback and resimulation, `check` against the history an
+/// authoritative snapshot carries tells whether the client rejoined its timeline, without
+/// waiting for the next snapshot. Kept by the stepping wrappers (`step_mut_recorded`,
+/// `WasmState`), not in `State`, so it changes neither `hash_state` nor the guests' cycles.
+#[derive(Clone, Debug, Default)]
+pub struct RecentHashes {
+    ring: [(i32, u64); RECENT_HASH_COUNT],
+    /// Slot of the oldest entry.
+    start: usize,
+    len: usize,
+}
+
+/// Outcome of `RecentHashes::check`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct HistoryCheck {
+    /// Ticks both histories hold.
+    pub overlap: u32,
+    /// Earliest of them whose hashes differ.
+    pub first_mismatch: Option<i32>,
+}
+
+impl HistoryCheck {
+    /// The histories share at least one tick and agree on every shared one.
+    pub fn agrees(&self) -> bool {
+        self.overlap > 0 && self.first_mismatch.is_none()
+    }
+}
+
+impl RecentHashes {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Remember `checksum_state(state)` for its tick.
+    pub fn record(&mut self, state: &State) {
+        self.push(state.tick, checksum_state(state));
+    }
+
+    /// Remember `hash` for `tick`, first forgetting every entry at or after it (the ticks a
+    /// rollback is about to step again), then the oldest one if the ring is full.
+    pub fn push(&mut self, tick: i32, hash: u64) {
+        while self.len > 0 && self.entry(self.len - 1).0 >= tick {
+            self.len -= 1;
+        }
+        if self.len == RECENT_HASH_COUNT {
+            self.start = (self.start + 1) % RECENT_HASH_COUNT;
+            self.len -= 1;
+        }
+        self.ring[(self.start + self.len) % RECENT_HASH_COUNT] = (tick, hash);
+        self.len += 1;
+    }
+
+    fn entry(&self, i: usize) -> (i32, u64) {
+        self.ring[(self.start + i) % RECENT_HASH_COUNT]
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+
+    /// (tick, hash) pairs, oldest first.
+    pub fn iter(&self) -> impl Iterator<Item = (i32, u64)> + '_ {
+        (0..self.len).map(|i| self.entry(i))
+    }
+
+    /// Hash recorded for `tick`, if it is still in the ring.
+    pub fn get(&self, tick: i32) -> Option<u64> {
+        self.iter().find(|&(t, _)| t == tick).map(|(_, h)| h)
+    }
+
+    /// Compare against another timeline's (tick, hash) pairs on the ticks both hold.
+    pub fn check(&self, other: impl IntoIterator<Item = (i32, u64)>) -> HistoryCheck {
+        let mut check = HistoryCheck::default();
+        for (tick, hash) in other {
+            let Some(mine) = self.get(tick) else { continue };
+            check.overlap += 1;
+            if mine != hash {
+                check.first_mismatch = Some(check.first_mismatch.map_or(tick, |t| t.min(tick)));
+            }
+        }
+        check
+    }
+}
+
+/// `step_mut`, then record the new state in `recent`. Returns its `checksum_state`.
+pub fn step_mut_recorded(state: &mut State, inputs: &[FpInput], recent: &mut RecentHashes) -> u64 {
+    step_mut(state, inputs);
+    let hash = checksum_state(state);
+    recent.push(state.tick, hash);
+    hash
+}
@@ -0,0 +1,300 @@
+//! Per-tick inputs and the raw input encodings the guests and the server exchange.
+
+use serde::{Deserialize, Serialize};
+
+use super::*;
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct FpInput {
+    pub buttons: u8,
+    pub aim_x: i8,
+    pub aim_y: i8,
+}
+
+pub const NULL_INPUT: FpInput = FpInput {
+    buttons: 0,
+    aim_x: 0,
+    aim_y: 0,
+};
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct FpProverInput {
+    /// Session the proof is bound to (journal only — not part of the sim input).
+    #[serde(default)]
+    pub session_id: u32,
+    pub seed: u32,
+    /// Played on the mirrored arena (see `FpMatchConfig::mirrored`).
+    #[serde(default)]
+    pub mirrored: bool,
+    /// Inputs in registered player order: `[t][0]` is registered player 0's input whichever
+    /// side `State::side_swap` gives them; the sim routes it. Two-player matches only; wider
+    /// ones use `encode_raw_input_wide`.
+    pub transcript: Vec<[FpInput; 2]>,
+    /// Map played on, before `mirrored` flips it; `None` is the arena. Only the chunked
+    /// pipeline proves other maps: the monolithic guest replays on the arena.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub map: Option<Map>,
+    /// Played on `generate_map(gen_seed, MapGenParams::default())` instead, when `map` is
+    /// `None`. The match guest generates it itself, so the journal's map hash commits to it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gen_seed: Option<u32>,
+    /// Rule overrides the match was played under (a warmup, a custom mode); `None` is the
+    /// standard match. Not part of the raw input: the guests read them from their header.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rules: Option<MatchRules>,
+}
+
+/// The lives and clock of a match, as `FpMatchConfig` has them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MatchRules {
+    pub initial_lives: i32,
+    pub match_duration: i32,
+    pub sudden_death: i32,
+}
+
+/// Standard match rules.
+impl Default for MatchRules {
+    fn default() -> Self {
+        MatchRules::of(&FpMatchConfig::default())
+    }
+}
+
+impl MatchRules {
+    /// The rules of `cfg`.
+    pub fn of(cfg: &FpMatchConfig) -> Self {
+        MatchRules { initial_lives: cfg.initial_lives, match_duration: cfg.match_duration, sudden_death: cfg.sudden_death }
+    }
+
+    /// As guest header words: [initial_lives, match_duration, sudden_death], each an i32's bits.
+    pub fn to_words(&self) -> [u32; 3] {
+        [self.initial_lives as u32, self.match_duration as u32, self.sudden_death as u32]
+    }
+
+    pub fn from_words(words: [u32; 3]) -> Self {
+        MatchRules { initial_lives: words[0] as i32, match_duration: words[1] as i32, sudden_death: words[2] as i32 }
+    }
+
+    /// `cfg` with these rules.
+    pub fn apply(&self, cfg: FpMatchConfig) -> FpMatchConfig {
+        FpMatchConfig {
+            initial_lives: self.initial_lives,
+            match_duration: self.match_duration,
+            sudden_death: self.sudden_death,
+            ..cfg
+        }
+    }
+}
+
+impl FpProverInput {
+    /// Match config the guests replay under (and commit to the journal).
+    pub fn match_config(&self) -> FpMatchConfig {
+        let cfg = FpMatchConfig { mirrored: self.mirrored, ..FpMatchConfig::default() };
+        match self.rules {
+            Some(rules) => rules.apply(cfg),
+            None => cfg,
+        }
+    }
+
+    /// `map`, else the generated map of `gen_seed`, else the arena.
+    pub fn base_map(&self) -> Map {
+        match (&self.map, self.gen_seed) {
+            (Some(map), _) => map.clone(),
+            (None, Some(gen_seed)) => generate_map(gen_seed, MapGenParams::default()),
+            (None, None) => arena_map(),
+        }
+    }
+
+    /// The map as played: `base_map` oriented by `match_config`.
+    pub fn oriented_map(&self) -> Map {
+        self.match_config().oriented_map(&self.base_map())
+    }
+}
+
+/// One tick's inputs in registered player order; slots past the match's player count are
+/// `NULL_INPUT`.
+pub type TickInputs = [FpInput; MAX_PLAYERS];
+
+/// High half of the word after the seed in the raw input of a match of other than two
+/// players: `WIDE_INPUT_TAG | player_count`, with the tick count in the next word. A tick count
+/// never has these bits set, so two-player inputs keep their 8-byte header.
+pub const WIDE_INPUT_TAG: u32 = 0xC41D_0000;
+pub(super) const WIDE_INPUT_TAG_MASK: u32 = 0xFFFF_0000;
+
+/// What a raw input header declares (see `encode_raw_input_wide`).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RawInputHeader {
+    pub seed: u32,
+    pub player_count: usize,
+    pub tick_count: usize,
+    /// Byte offset of the first tick: 8, or 12 behind a wide header.
+    pub ticks_at: usize,
+}
+
+impl RawInputHeader {
+    pub fn parse(data: &[u8]) -> Self {
+        let word = |off: usize| u32::from_le_bytes([data[off], data[off + 1], data[off + 2], data[off + 3]]);
+        let seed = word(0);
+        let second = word(4);
+        if second & WIDE_INPUT_TAG_MASK == WIDE_INPUT_TAG {
+            let player_count = (second & !WIDE_INPUT_TAG_MASK) as usize;
+            RawInputHeader { seed, player_count, tick_count: word(8) as usize, ticks_at: 12 }
+        } else {
+            RawInputHeader { seed, player_count: 2, tick_count: second as usize, ticks_at: 8 }
+        }
+    }
+
+    /// Bytes per tick: `INPUT_BYTES` per player.
+    pub fn tick_bytes(&self) -> usize {
+        INPUT_BYTES * self.player_count
+    }
+}
+
+/// The first `player_count` inputs of one raw tick (`INPUT_BYTES` each, in player order).
+#[inline(always)]
+pub fn tick_inputs(bytes: &[u8], player_count: usize) -> TickInputs {
+    let mut inputs = [NULL_INPUT; MAX_PLAYERS];
+    for (i, inp) in inputs[..player_count].iter_mut().enumerate() {
+        let b = &bytes[i * INPUT_BYTES..];
+        *inp = FpInput { buttons: b[0], aim_x: b[1] as i8, aim_y: b[2] as i8 };
+    }
+    inputs
+}
+
+/// Decode a raw input of any player count into seed, player count and per-tick inputs.
+pub fn decode_raw_input_wide(data: &[u8]) -> (u32, usize, Vec<TickInputs>) {
+    let header = RawInputHeader::parse(data);
+    let n = header.player_count;
+    assert!((2..=MAX_PLAYERS).contains(&n), "raw input declares {n} players");
+    let transcript = (0..header.tick_count)
+        .map(|t| tick_inputs(&data[header.ticks_at + t * header.tick_bytes()..], n))
+        .collect();
+    (header.seed, n, transcript)
+}
+
+/// Encode a transcript of `player_count` players as raw bytes: the two-player layout of
+/// `encode_raw_input` for two, else [seed] [`WIDE_INPUT_TAG` | player_count] [tick_count]
+/// [tick × `INPUT_BYTES` × player_count].
+pub fn encode_raw_input_wide(seed: u32, player_count: usize, transcript: &[TickInputs]) -> Vec<u8> {
+    let header = if player_count == 2 { 8 } else { 12 };
+    let mut buf = Vec::with_capacity(header + transcript.len() * INPUT_BYTES * player_count);
+    buf.extend_from_slice(&seed.to_le_bytes());
+    if player_count != 2 {
+        buf.extend_from_slice(&(WIDE_INPUT_TAG | player_count as u32).to_le_bytes());
+    }
+    buf.extend_from_slice(&(transcript.len() as u32).to_le_bytes());
+    for tick in transcript {
+        for inp in &tick[..player_count] {
+            buf.extend_from_slice(&[inp.buttons, inp.aim_x as u8, inp.aim_y as u8]);
+        }
+    }
+    buf
+}
+
+/// Decode raw bytes into seed + transcript (no serde overhead in zkVM).
+/// Format: [seed: 4 bytes LE] [tick_count: 4 bytes LE] [tick × 6 bytes: p0.buttons p0.aim_x p0.aim_y p1.buttons p1.aim_x p1.aim_y]
+/// Two-player inputs only (`decode_raw_input_wide` reads any).
+pub fn decode_raw_input(data: &[u8]) -> (u32, Vec<[FpInput; 2]>) {
+    let seed = u32::from_le_bytes([data[0], data[1], data[2], data[3]]);
+    let tick_count = u32::from_le_bytes([data[4], data[5], data[6], data[7]]) as usize;
+    assert!(tick_count as u32 & WIDE_INPUT_TAG_MASK != WIDE_INPUT_TAG, "raw input of a wider match");
+    let mut transcript = Vec::with_capacity(tick_count);
+    let mut offset = 8;
+    for _ in 0..tick_count {
+        let p0 = FpInput {
+            buttons: data[offset],
+            aim_x: data[offset + 1] as i8,
+            aim_y: data[offset + 2] as i8,
+        };
+        let p1 = FpInput {
+            buttons: data[offset + 3],
+            aim_x: data[offset + 4] as i8,
+            aim_y: data[offset + 5] as i8,
+        };
+        transcript.push([p0, p1]);
+        offset += 6;
+    }
+    (seed, transcript)
+}
+
+/// Encode FpProverInput as raw bytes for the guest.
+pub fn encode_raw_input(input: &FpProverInput) -> Vec<u8> {
+    let mut buf = Vec::with_capacity(8 + input.transcript.len() * 6);
+    buf.extend_from_slice(&input.seed.to_le_bytes());
+    buf.extend_from_slice(&(input.transcript.len() as u32).to_le_bytes());
+    for tick in &input.transcript {
+        buf.push(tick[0].buttons);
+        buf.push(tick[0].aim_x as u8);
+        buf.push(tick[0].aim_y as u8);
+        buf.push(tick[1].buttons);
+        buf.push(tick[1].aim_x as u8);
+        buf.push(tick[1].aim_y as u8);
+    }
+    buf
+}
+
+/// Bytes per player input in a single-player stream: buttons, aim_x, aim_y.
+pub const INPUT_BYTES: usize = 3;
+
+/// Interleave two independently captured input streams (`INPUT_BYTES` per tick each) into the
+/// 6-byte ticks of the raw transcript (`encode_raw_input` after its 8-byte header). Padding rule:
+/// the result is as long as the longer stream, the shorter one's missing ticks being
+/// `NULL_INPUT` (its player idles); a trailing partial input is not a tick and is dropped.
+pub fn merge_input_streams(p0: &[u8], p1: &[u8]) -> Vec<u8> {
+    let ticks = (p0.len() / INPUT_BYTES).max(p1.len() / INPUT_BYTES);
+    let input_at = |stream: &[u8], t: usize| -> [u8; INPUT_BYTES] {
+        match stream.get(t * INPUT_BYTES..(t + 1) * INPUT_BYTES) {
+            Some(b) => [b[0], b[1], b[2]],
+            None => [NULL_INPUT.buttons, NULL_INPUT.aim_x as u8, NULL_INPUT.aim_y as u8],
+        }
+    };
+    let mut buf = Vec::with_capacity(ticks * 2 * INPUT_BYTES);
+    for t in 0..ticks {
+        buf.extend_from_slice(&input_at(p0, t));
+        buf.extend_from_slice(&input_at(p1, t));
+    }
+    buf
+}
+
+/// Bytes per run of `encode_input_runs`: tick count (u16 LE), then one 6-byte raw tick.
+pub const INPUT_RUN_BYTES: usize = 2 + 2 * INPUT_BYTES;
+
+/// Run-length encode a two-player transcript, for shipping scripted matches (a scripted player
+/// holds each input for many ticks): [seed: 4 bytes LE] then runs of [tick count: u16 LE]
+/// [the tick's 6 raw bytes], each run at most `u16::MAX` ticks.
+pub fn encode_input_runs(seed: u32, transcript: &[[FpInput; 2]]) -> Vec<u8> {
+    let raw = |[p0, p1]: &[FpInput; 2]| {
+        [p0.buttons, p0.aim_x as u8, p0.aim_y as u8, p1.buttons, p1.aim_x as u8, p1.aim_y as u8]
+    };
+    let mut buf = seed.to_le_bytes().to_vec();
+    let mut ticks = transcript.iter().map(raw).peekable();
+    while let Some(tick) = ticks.next() {
+        let mut count = 1u16;
+        while count < u16::MAX && ticks.peek() == Some(&tick) {
+            ticks.next();
+            count += 1;
+        }
+        buf.extend_from_slice(&count.to_le_bytes());
+        buf.extend_from_slice(&tick);
+    }
+    buf
+}
+
+/// Seed and transcript of `encode_input_runs` bytes; `None` if they are truncated or hold an
+/// empty run.
+pub fn decode_input_runs(data: &[u8]) -> Option<(u32, Vec<[FpInput; 2]>)> {
+    let seed = u32::from_le_bytes(data.get(..4)?.try_into().unwrap());
+    let runs = &data[4..];
+    if runs.len() % INPUT_RUN_BYTES != 0 {
+        return None;
+    }
+    let mut transcript = Vec::new();
+    for run in runs.chunks_exact(INPUT_RUN_BYTES) {
+        let count = u16::from_le_bytes([run[0], run[1]]) as usize;
+        if count == 0 {
+            return None;
+        }
+        let inputs = tick_inputs(&run[2..], 2);
+        transcript.extend(core::iter::repeat([inputs[0], inputs[1]]).take(count));
+    }
+    Some((seed, transcript))
+}
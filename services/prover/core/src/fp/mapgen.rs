@@ -0,0 +1,359 @@
+//! Procedural arenas: `generate_map` derives a whole `Map` from a generation seed with the
+//! integer PRNG, so both clients and the guests build the identical map (and `map_hash`);
+//! `validate_map` is the check every generated map passes.
+
+use super::*;
+
+/// Height a single jump lifts the feet from a standstill: v² / 2g (110.25px).
+pub const JUMP_RISE: Fp = JUMP_VELOCITY * JUMP_VELOCITY / (2 * GRAVITY);
+/// Ticks a single jump spends airborne back down to its take-off height.
+pub const JUMP_AIR_TICKS: i32 = 2 * -JUMP_VELOCITY / GRAVITY;
+/// Horizontal distance covered at full run over `JUMP_AIR_TICKS` (168px).
+pub const JUMP_REACH: Fp = JUMP_AIR_TICKS * PLAYER_SPEED;
+
+/// Largest map `validate_map` accepts in either dimension (4096px), far inside the `Fp` range.
+pub const MAX_MAP_SIZE: Fp = fp(4096);
+
+/// Generation attempts before `generate_map` settles for the bare ground.
+pub const MAP_GEN_ATTEMPTS: u32 = 16;
+/// Draws per platform before an attempt is abandoned.
+const PLACE_TRIES: u32 = 8;
+
+// Layout, in whole pixels
+const GROUND_DEPTH: i32 = 28; // visible ground; the slab runs 4px past the bottom like the arena's
+const PLATFORM_THICKNESS: i32 = 16;
+const WIDTH_STEP: i32 = 16;
+const EDGE_MARGIN: i32 = 32;
+const CENTER_GAP: i32 = 24;
+const TOP_MARGIN: i32 = 96;
+/// Vertical distance between platforms sharing a column: room for a player underneath.
+const MIN_RISE: i32 = 64;
+/// Columns closer than this count as shared for `MIN_RISE`.
+const COLUMN_MARGIN: i32 = 24;
+/// Pickups float this far above the surface they spawn on (as on the arena).
+const PICKUP_HOVER: i32 = 32;
+
+/// Knobs for `generate_map`. Sizes in whole pixels.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MapGenParams {
+    pub width: i32,
+    pub height: i32,
+    /// Floating platforms over the ground, at most `NUM_PLATFORMS - 1`. They come in mirrored
+    /// pairs, so both sides play the same; an odd count adds one centered platform.
+    pub floating: u8,
+    /// Platform widths are drawn from this range in `WIDTH_STEP` steps.
+    pub min_platform_width: i32,
+    pub max_platform_width: i32,
+}
+
+impl Default for MapGenParams {
+    fn default() -> Self {
+        MapGenParams { width: 960, height: 540, floating: 5, min_platform_width: 96, max_platform_width: 224 }
+    }
+}
+
+/// A platform rectangle in whole pixels while the layout is drawn.
+#[derive(Clone, Copy)]
+struct Rect {
+    x: i32,
+    y: i32,
+    w: i32,
+}
+
+impl Rect {
+    fn center(&self) -> i32 {
+        self.x + self.w / 2
+    }
+
+    /// Horizontal gap to `other`'s span, 0 when they overlap.
+    fn gap(&self, other: &Rect) -> i32 {
+        (other.x - (self.x + self.w)).max(self.x - (other.x + other.w)).max(0)
+    }
+
+    fn mirrored(&self, width: i32) -> Rect {
+        Rect { x: width - self.x - self.w, ..*self }
+    }
+}
+
+struct Draw {
+    state: u32,
+}
+
+impl Draw {
+    fn range(&mut self, min: i32, max: i32) -> i32 {
+        let (v, next) = prng_int_range(self.state, min, max);
+        self.state = next;
+        v
+    }
+}
+
+/// Deterministic arena for `gen_seed`: a solid ground, then `params.floating` one-way platforms
+/// placed in mirrored pairs (an odd one centered), each a single jump from one already placed
+/// (at most 3/4 of `JUMP_RISE` up and half of `JUMP_REACH` across) with head room between
+/// platforms sharing a column. Player spawns 0 and 1 stand on the outermost pair (or the
+/// ground), 2 and 3 on the ground; weapon spawns sit over the interior platforms, then the
+/// ground. A layout failing a draw or `validate_map` is redrawn from where the PRNG left off,
+/// up to `MAP_GEN_ATTEMPTS` times, then only the ground is kept.
+pub fn generate_map(gen_seed: u32, params: MapGenParams) -> Map {
+    let mut draw = Draw { state: gen_seed };
+    let floating = (params.floating as usize).min(NUM_PLATFORMS - 1);
+    for _ in 0..MAP_GEN_ATTEMPTS {
+        if let Some(rects) = draw_layout(&mut draw, &params, floating) {
+            let map = build_map(&params, &rects);
+            if validate_map(&map).is_ok() {
+                return map;
+            }
+        }
+    }
+    build_map(&params, &[])
+}
+
+fn ground(params: &MapGenParams) -> Rect {
+    Rect { x: 0, y: params.height - GROUND_DEPTH, w: params.width }
+}
+
+/// One attempt's floating platforms, centered one first, then each pair left before right.
+fn draw_layout(draw: &mut Draw, params: &MapGenParams, floating: usize) -> Option<Vec<Rect>> {
+    let max_rise = (JUMP_RISE * 3 / 4) >> FRAC;
+    let reach = (JUMP_REACH / 2) >> FRAC;
+    let w_lo = params.min_platform_width.max(WIDTH_STEP) / WIDTH_STEP;
+    let w_hi = params.max_platform_width / WIDTH_STEP;
+    if w_hi < w_lo {
+        return None;
+    }
+    let mut placed = vec![ground(params)];
+    let fits = |placed: &[Rect], r: &Rect| {
+        r.y >= TOP_MARGIN
+            && placed.iter().all(|p| p.gap(r) >= COLUMN_MARGIN || (p.y - r.y).abs() >= MIN_RISE)
+    };
+
+    if floating % 2 == 1 {
+        let w = draw.range(w_lo, w_hi) * WIDTH_STEP;
+        let rise = draw.range(MIN_RISE, max_rise);
+        let center = Rect { x: (params.width - w) / 2, y: placed[0].y - rise, w };
+        if !fits(&placed, &center) {
+            return None;
+        }
+        placed.push(center);
+    }
+    for _ in 0..floating / 2 {
+        let left = (0..PLACE_TRIES).find_map(|_| {
+            let support = placed[draw.range(0, placed.len() as i32 - 1) as usize];
+            let w = draw.range(w_lo, w_hi) * WIDTH_STEP;
+            let max_x = params.width / 2 - CENTER_GAP - w;
+            if max_x < EDGE_MARGIN {
+                return None;
+            }
+            let x = draw.range(EDGE_MARGIN, max_x);
+            let rise = draw.range(MIN_RISE, max_rise);
+            let r = Rect { x, y: support.y - rise, w };
+            (r.gap(&support) <= reach && fits(&placed, &r)).then_some(r)
+        })?;
+        let right = left.mirrored(params.width);
+        if !fits(&placed, &right) {
+            return None;
+        }
+        placed.push(left);
+        placed.push(right);
+    }
+    Some(placed[1..].to_vec())
+}
+
+/// Player spawn (top-left corner of the player box) standing on `r` at `cx`.
+fn stand_on(r: &Rect, cx: i32) -> SpawnPoint {
+    SpawnPoint { x: fp(cx) - PLAYER_WIDTH / 2, y: fp(r.y) - PLAYER_HEIGHT }
+}
+
+fn hover_over(r: &Rect, cx: i32) -> SpawnPoint {
+    SpawnPoint { x: fp(cx), y: fp(r.y - PICKUP_HOVER) }
+}
+
+fn build_map(params: &MapGenParams, rects: &[Rect]) -> Map {
+    let ground = ground(params);
+    let width = params.width;
+    let mut platforms = [EMPTY_PLATFORM; NUM_PLATFORMS];
+    platforms[0] = Platform {
+        x: 0,
+        y: fp(ground.y),
+        width: fp(width),
+        height: fp(GROUND_DEPTH + 4),
+        one_way: false,
+        hazard_damage: 0,
+    };
+    for (slot, r) in platforms[1..].iter_mut().zip(rects) {
+        *slot = Platform {
+            x: fp(r.x),
+            y: fp(r.y),
+            width: fp(r.w),
+            height: fp(PLATFORM_THICKNESS),
+            one_way: true,
+            hazard_damage: 0,
+        };
+    }
+
+    // The outermost pair, by its left member: pairs follow the centered platform, if any
+    let pairs_at = rects.len() % 2;
+    let outer = (pairs_at..rects.len()).step_by(2).min_by_key(|&i| rects[i].center());
+    let (outer_left, outer_right) = match outer {
+        Some(i) => (rects[i], rects[i + 1]),
+        None => (ground, ground),
+    };
+    let left_cx = if outer.is_some() { outer_left.center() } else { width / 8 };
+    let spawns = [
+        stand_on(&outer_left, left_cx),
+        stand_on(&outer_right, width - left_cx),
+        stand_on(&ground, width / 4),
+        stand_on(&ground, width - width / 4),
+    ];
+
+    let mut weapon_spawns = rects
+        .iter()
+        .enumerate()
+        .filter(|&(i, _)| !matches!(outer, Some(o) if i == o || i == o + 1))
+        .map(|(_, r)| hover_over(r, r.center()))
+        .chain([width / 2, width / 3, width - width / 3, width / 6].map(|cx| hover_over(&ground, cx)));
+    let weapon_spawns = core::array::from_fn(|_| weapon_spawns.next().unwrap());
+
+    // No health spawns, so generated maps play and hash as before health pickups
+    Map {
+        width: fp(width),
+        height: fp(params.height),
+        platforms,
+        spawns,
+        weapon_spawns,
+        health_spawns: Default::default(),
+        health_spawn_count: 0,
+        index: PlatformIndex::default(),
+    }
+}
+
+/// Why `validate_map` rejects a map.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MapIssue {
+    /// A dimension outside `1..=MAX_MAP_SIZE`.
+    Size { width: Fp, height: Fp },
+    /// Every platform slot empty: nothing to stand on but the map's bottom edge.
+    NoPlatforms,
+    /// A platform slot that is neither empty (all zero size) nor a positive-size rectangle
+    /// within the map's width with its top inside the map.
+    PlatformBounds { index: usize },
+    PlatformOverlap { a: usize, b: usize },
+    /// Not reachable from the lowest surface: no chain of platforms each at most `MAX_JUMPS`
+    /// jumps up and `JUMP_REACH` across from the last.
+    Unreachable { index: usize },
+    /// Player box outside the map or inside a solid platform.
+    SpawnPosition { index: usize, x: Fp, y: Fp },
+    WeaponSpawnPosition { index: usize, x: Fp, y: Fp },
+    /// Out of reach of a player standing on the platform (or map bottom) under it: none spans
+    /// its x with a top at most `PLAYER_HEIGHT + PICKUP_RADIUS` below it.
+    WeaponSpawnUnreachable { index: usize, x: Fp, y: Fp },
+}
+
+impl core::fmt::Display for MapIssue {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match *self {
+            MapIssue::Size { width, height } => write!(f, "map size {width}x{height}"),
+            MapIssue::NoPlatforms => write!(f, "map has no platforms"),
+            MapIssue::PlatformBounds { index } => write!(f, "platform {index} out of bounds"),
+            MapIssue::PlatformOverlap { a, b } => write!(f, "platforms {a} and {b} overlap"),
+            MapIssue::Unreachable { index } => write!(f, "platform {index} is unreachable"),
+            MapIssue::SpawnPosition { index, x, y } => write!(f, "spawn {index} blocked at ({x}, {y})"),
+            MapIssue::WeaponSpawnPosition { index, x, y } => {
+                write!(f, "weapon spawn {index} outside map at ({x}, {y})")
+            }
+            MapIssue::WeaponSpawnUnreachable { index, x, y } => {
+                write!(f, "weapon spawn {index} at ({x}, {y}) floats over nothing to stand on")
+            }
+        }
+    }
+}
+
+fn is_empty_slot(p: &Platform) -> bool {
+    p.width == 0 && p.height == 0
+}
+
+fn overlaps(ax: Fp, ay: Fp, aw: Fp, ah: Fp, b: &Platform) -> bool {
+    ax < b.x + b.width && b.x < ax + aw && ay < b.y + b.height && b.y < ay + ah
+}
+
+/// Structural checks for a map: sane size, at least one platform, sane platform rectangles, no
+/// overlapping platforms, every platform reachable by jumping from the lowest one, spawns
+/// inside the map (players clear of solid platforms) and weapon spawns within reach of a
+/// surface under them. Holds for the arena and every `generate_map` result.
+pub fn validate_map(map: &Map) -> Result<(), Vec<MapIssue>> {
+    let mut issues = Vec::new();
+    if !(1..=MAX_MAP_SIZE).contains(&map.width) || !(1..=MAX_MAP_SIZE).contains(&map.height) {
+        return Err(vec![MapIssue::Size { width: map.width, height: map.height }]);
+    }
+    let used: Vec<usize> = (0..NUM_PLATFORMS).filter(|&i| !is_empty_slot(&map.platforms[i])).collect();
+    if used.is_empty() {
+        return Err(vec![MapIssue::NoPlatforms]);
+    }
+    for &i in &used {
+        let p = &map.platforms[i];
+        if p.width <= 0 || p.height <= 0 || p.x < 0 || p.x + p.width > map.width || p.y < 0 || p.y >= map.height {
+            issues.push(MapIssue::PlatformBounds { index: i });
+        }
+    }
+    for (n, &a) in used.iter().enumerate() {
+        for &b in &used[n + 1..] {
+            let pa = &map.platforms[a];
+            if overlaps(pa.x, pa.y, pa.width, pa.height, &map.platforms[b]) {
+                issues.push(MapIssue::PlatformOverlap { a, b });
+            }
+        }
+    }
+
+    // Flood upward from the lowest surface; falling back down is always possible
+    let floor = used.iter().map(|&i| map.platforms[i].y).max();
+    let mut reached = [false; NUM_PLATFORMS];
+    for &i in &used {
+        reached[i] = Some(map.platforms[i].y) == floor;
+    }
+    loop {
+        let mut grew = false;
+        for &to in &used {
+            if reached[to] {
+                continue;
+            }
+            let t = &map.platforms[to];
+            reached[to] = used.iter().any(|&from| {
+                let s = &map.platforms[from];
+                let gap = (t.x - (s.x + s.width)).max(s.x - (t.x + t.width)).max(0);
+                reached[from] && s.y - t.y <= JUMP_RISE * MAX_JUMPS && gap <= JUMP_REACH
+            });
+            grew |= reached[to];
+        }
+        if !grew {
+            break;
+        }
+    }
+    issues.extend(used.iter().filter(|&&i| !reached[i]).map(|&index| MapIssue::Unreachable { index }));
+
+    for (index, s) in map.spawns.iter().enumerate() {
+        let inside = s.x >= 0 && s.x + PLAYER_WIDTH <= map.width && s.y >= 0 && s.y + PLAYER_HEIGHT <= map.height;
+        let blocked = used.iter().any(|&i| {
+            let p = &map.platforms[i];
+            !p.one_way && overlaps(s.x, s.y, PLAYER_WIDTH, PLAYER_HEIGHT, p)
+        });
+        if !inside || blocked {
+            issues.push(MapIssue::SpawnPosition { index, x: s.x, y: s.y });
+        }
+    }
+    for (index, s) in map.weapon_spawns.iter().enumerate() {
+        if s.x < 0 || s.x > map.width || s.y < 0 || s.y > map.height {
+            issues.push(MapIssue::WeaponSpawnPosition { index, x: s.x, y: s.y });
+            continue;
+        }
+        let within_reach = |top: Fp| (0..PLAYER_HEIGHT + PICKUP_RADIUS).contains(&(top - s.y));
+        let stood_under = within_reach(map.height)
+            || used.iter().any(|&i| {
+                let p = &map.platforms[i];
+                p.x <= s.x && s.x <= p.x + p.width && within_reach(p.y)
+            });
+        if !stood_under {
+            issues.push(MapIssue::WeaponSpawnUnreachable { index, x: s.x, y: s.y });
+        }
+    }
+    if issues.is_empty() { Ok(()) } else { Err(issues) }
+}
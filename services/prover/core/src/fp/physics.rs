@@ -0,0 +1,267 @@
+//! Player input, gravity, movement and collision.
+
+use super::*;
+
+#[inline(always)]
+pub(super) fn apply_input_mut(p: &mut Player, buttons: u8, prev_buttons: u8, aim_x: i8, map: &Map, stamina: bool) {
+    if p.state_flags & flag::ALIVE == 0 { return; }
+
+    // Dash cooldown ticks here, so it also runs down for the winner during the linger
+    let was_dashing = is_dashing(p);
+    p.dash_cooldown = (p.dash_cooldown - 1).max(0);
+    if was_dashing && !is_dashing(p) {
+        // Burst over: back to running speed
+        p.vx = p.vx.clamp(-PLAYER_SPEED, PLAYER_SPEED);
+    }
+
+    // If being stomped, skip movement (victim is auto-run controlled)
+    if p.stomped_by >= 0 { return; }
+    // If stomping on someone, skip movement (rider is locked to victim)
+    if p.stomping_on >= 0 { return; }
+
+    let mut target_vx: Fp = 0;
+    if buttons & button::LEFT != 0 {
+        target_vx -= PLAYER_SPEED;
+    }
+    if buttons & button::RIGHT != 0 {
+        target_vx += PLAYER_SPEED;
+    }
+
+    // A dash holds its burst speed (unless a wall it hit has zeroed it)
+    if is_dashing(p) {
+        target_vx = p.vx;
+    }
+
+    if target_vx != 0 {
+        if p.vx < target_vx {
+            p.vx = (p.vx + ACCELERATION).min(target_vx);
+        } else if p.vx > target_vx {
+            p.vx = (p.vx - ACCELERATION).max(target_vx);
+        }
+    } else if p.vx > 0 {
+        p.vx = (p.vx - DECELERATION).max(0);
+    } else if p.vx < 0 {
+        p.vx = (p.vx + DECELERATION).min(0);
+    }
+
+    // Jump edge detection: pressed now, not pressed last tick
+    let jump_edge = (buttons & button::JUMP != 0) && (prev_buttons & button::JUMP == 0);
+
+    if jump_edge {
+        if buttons & button::DOWN != 0 && p.grounded && on_one_way_platform(p, map) {
+            // Drop through: sink below the top, so the platform doesn't catch the fall
+            p.y += 1;
+            p.grounded = false;
+        } else if p.wall_sliding && p.jumps_left > 0 {
+            // Wall jump: push away from wall
+            p.vx = WALL_JUMP_VX * (-p.wall_dir);
+            p.vy = WALL_JUMP_VY;
+            p.jumps_left -= 1;
+            p.wall_sliding = false;
+            p.wall_dir = 0;
+            if stamina {
+                p.stamina = (p.stamina - WALL_JUMP_STAMINA_COST).max(0);
+            }
+        } else if p.jumps_left > 0 {
+            // Normal/double jump
+            p.vy = JUMP_VELOCITY;
+            p.jumps_left -= 1;
+        }
+    }
+
+    if aim_x > 0 {
+        p.facing = FACING_RIGHT;
+    } else if aim_x < 0 {
+        p.facing = FACING_LEFT;
+    }
+
+    // Dash edge detection, off cooldown: a horizontal burst the facing way, or off the wall
+    let dash_edge = (buttons & button::DASH != 0) && (prev_buttons & button::DASH == 0);
+    if dash_edge && p.dash_cooldown == 0 {
+        let dir = if p.wall_sliding { -p.wall_dir } else { p.facing };
+        p.dash_cooldown = DASH_COOLDOWN_TICKS;
+        p.vx = DASH_VELOCITY * dir;
+        p.vy = 0;
+        p.wall_sliding = false;
+        p.wall_dir = 0;
+    }
+}
+
+/// Within the first `DASH_TICKS` of a dash's cooldown, and not ridden.
+#[inline(always)]
+pub(super) fn is_dashing(p: &Player) -> bool {
+    p.dash_cooldown > DASH_COOLDOWN_TICKS - DASH_TICKS && p.stomped_by < 0
+}
+
+/// Standing on the top of a one-way platform.
+#[inline(always)]
+fn on_one_way_platform(p: &Player, map: &Map) -> bool {
+    let bottom = p.y + PLAYER_HEIGHT;
+    slots(map.platforms_near(p.x, bottom, p.x + PLAYER_WIDTH, bottom)).any(|i| {
+        let plat = &map.platforms[i];
+        plat.one_way
+            && p.y + PLAYER_HEIGHT == plat.y
+            && p.x + PLAYER_WIDTH > plat.x
+            && p.x < plat.x + plat.width
+    })
+}
+
+#[inline(always)]
+pub(super) fn apply_gravity_mut(p: &mut Player) {
+    if p.state_flags & flag::ALIVE == 0 { return; }
+    // Skip gravity for stomp rider (rider is locked to victim in stomp processing)
+    if p.stomping_on >= 0 { return; }
+    let max_fall = if p.wall_sliding { WALL_SLIDE_SPEED } else { MAX_FALL_SPEED };
+    let gravity = if is_dashing(p) { DASH_GRAVITY } else { GRAVITY };
+    p.vy = (p.vy + gravity).min(max_fall);
+}
+
+/// Returns the highest `hazard_damage` of the platforms the player was resolved onto or out of
+/// the side of (0 when none): bumping a head on one doesn't count.
+#[inline(always)]
+pub(super) fn move_and_collide_mut(p: &mut Player, buttons: u8, map: &Map, stamina: bool) -> i32 {
+    if p.state_flags & flag::ALIVE == 0 { return 0; }
+    // Skip movement for stomp rider (rider is locked to victim in stomp processing)
+    if p.stomping_on >= 0 { return 0; }
+
+    let prev_bottom = p.y + PLAYER_HEIGHT;
+    p.x += p.vx;
+    p.y += p.vy;
+    p.grounded = false;
+    let mut hazard = 0;
+
+    // Platform collision — solid platforms are full AABBs, one-way ones only have a top.
+    // Slot order; each resolution moves the player, so the next slot is looked up from there
+    let mut next = 0;
+    loop {
+        let near = map.platforms_near(p.x, p.y, p.x + PLAYER_WIDTH, p.y + PLAYER_HEIGHT) & slots_from(next);
+        let Some(i) = slots(near).next() else { break };
+        next = i + 1;
+        let plat = &map.platforms[i];
+        // Skip empty/padding platforms
+        if plat.width == 0 || plat.height == 0 { continue; }
+        // Check overlap
+        if p.x + PLAYER_WIDTH > plat.x
+            && p.x < plat.x + plat.width
+            && p.y + PLAYER_HEIGHT > plat.y
+            && p.y < plat.y + plat.height
+        {
+            if plat.one_way {
+                // Land only when falling onto it from above its top
+                if p.vy >= 0 && prev_bottom <= plat.y {
+                    p.y = plat.y - PLAYER_HEIGHT;
+                    p.vy = 0;
+                    p.grounded = true;
+                    hazard = hazard.max(plat.hazard_damage);
+                }
+                continue;
+            }
+            let overlap_left = (p.x + PLAYER_WIDTH) - plat.x;
+            let overlap_right = (plat.x + plat.width) - p.x;
+            let overlap_top = (p.y + PLAYER_HEIGHT) - plat.y;
+            let overlap_bottom = (plat.y + plat.height) - p.y;
+            let min_overlap = overlap_left.min(overlap_right).min(overlap_top).min(overlap_bottom);
+
+            if min_overlap == overlap_top {
+                p.y = plat.y - PLAYER_HEIGHT;
+                p.vy = 0;
+                p.grounded = true;
+                hazard = hazard.max(plat.hazard_damage);
+            } else if min_overlap == overlap_bottom {
+                p.y = plat.y + plat.height;
+                p.vy = 0;
+            } else if min_overlap == overlap_left {
+                p.x = plat.x - PLAYER_WIDTH;
+                p.vx = 0;
+                hazard = hazard.max(plat.hazard_damage);
+            } else {
+                p.x = plat.x + plat.width;
+                p.vx = 0;
+                hazard = hazard.max(plat.hazard_damage);
+            }
+        }
+    }
+
+    // Arena boundary clamping (use map bounds — zone is damage-only, not physical)
+    if p.x < 0 {
+        p.x = 0;
+    }
+    if p.x + PLAYER_WIDTH > map.width {
+        p.x = map.width - PLAYER_WIDTH;
+    }
+    if p.y < 0 {
+        p.y = 0;
+        p.vy = 0;
+    }
+    if p.y + PLAYER_HEIGHT > map.height {
+        p.y = map.height - PLAYER_HEIGHT;
+        p.vy = 0;
+        p.grounded = true;
+    }
+
+    // Wall slide detection (2E)
+    let pressing_left = buttons & button::LEFT != 0;
+    let pressing_right = buttons & button::RIGHT != 0;
+    p.wall_sliding = false;
+    p.wall_dir = 0;
+
+    // Out of stamina: no wall to hold onto
+    let can_slide = !stamina || p.stamina > 0;
+
+    if !p.grounded && p.vy > 0 && can_slide && !is_dashing(p) {
+        // Map boundary walls (not zone — zone is damage-only)
+        if p.x <= 0 && pressing_left {
+            p.wall_sliding = true;
+            p.wall_dir = -1;
+        } else if p.x + PLAYER_WIDTH >= map.width && pressing_right {
+            p.wall_sliding = true;
+            p.wall_dir = 1;
+        }
+
+        // Platform side walls (2-pixel tolerance band); one-way platforms have none
+        if !p.wall_sliding {
+            let near = map.platforms_near(p.x - 512, p.y, p.x + PLAYER_WIDTH + 512, p.y + PLAYER_HEIGHT);
+            for i in slots(near) {
+                let plat = &map.platforms[i];
+                if plat.one_way { continue; }
+                // Vertical overlap check
+                if p.y + PLAYER_HEIGHT > plat.y && p.y < plat.y + plat.height {
+                    // Right side into left edge of platform
+                    if pressing_right && p.x + PLAYER_WIDTH >= plat.x && p.x + PLAYER_WIDTH <= plat.x + 512 {
+                        p.wall_sliding = true;
+                        p.wall_dir = 1;
+                        break;
+                    }
+                    // Left side into right edge of platform
+                    if pressing_left && p.x <= plat.x + plat.width && p.x >= plat.x + plat.width - 512 {
+                        p.wall_sliding = true;
+                        p.wall_dir = -1;
+                        break;
+                    }
+                }
+            }
+        }
+    }
+
+    if stamina {
+        if p.wall_sliding {
+            p.stamina = (p.stamina - WALL_SLIDE_STAMINA_DRAIN).max(0);
+        } else if p.grounded {
+            p.stamina = (p.stamina + STAMINA_REGEN).min(STAMINA_MAX);
+        }
+    }
+
+    // Wall sliding: character and gun face away from the wall_dir side
+    if p.wall_sliding {
+        p.facing = p.wall_dir;
+        p.vx = 0;
+    }
+
+    // Jump refund: grounded resets to MAX_JUMPS, wall slide grants 1 if exhausted
+    if p.grounded {
+        p.jumps_left = MAX_JUMPS;
+    } else if p.wall_sliding && p.jumps_left == 0 {
+        p.jumps_left = 1;
+    }
+    hazard
+}
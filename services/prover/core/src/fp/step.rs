@@ -0,0 +1,866 @@
+//! The tick itself, plus the runners built on it: event chains, streaming runs and ghosts.
+
+use std::sync::Arc;
+
+use sha2::{Digest, Sha256};
+
+use super::*;
+
+// -- Step --------------------------------------------------------------------
+
+/// The match-deciding elimination: decide the winner and start the full linger. Both the
+/// hit and the zone paths go through here, so the flag, timer and winner always agree.
+#[inline(always)]
+pub(super) fn start_final_linger(state: &mut State, winner: i32) {
+    state.winner = winner;
+    state.death_linger_timer = DEATH_LINGER_TICKS;
+    state.final_blow = true;
+}
+
+/// Whether hits, splash and hazards can hurt the player: alive and not invincible. (Stomps and
+/// the sudden-death zone ignore invincibility.)
+#[inline(always)]
+pub(super) fn is_damageable(p: &Player) -> bool {
+    p.state_flags & flag::ALIVE != 0 && p.state_flags & flag::INVINCIBLE == 0
+}
+
+/// Record that `attacker` hurt `p` at `tick`, for crediting a zone death (`zone_kill_credit`).
+/// Hurting yourself leaves the last opponent's credit standing.
+#[inline(always)]
+pub(super) fn mark_damaged_by(p: &mut Player, attacker: i32, tick: i32) {
+    if attacker >= 0 && attacker != p.id {
+        p.last_damaged_by = attacker;
+        p.last_damage_tick = tick;
+    }
+}
+
+/// Make a player invincible for `ticks` (counted down on respawn_timer), recording why.
+/// Use this rather than setting `flag::INVINCIBLE` directly so the source tracks the bit.
+#[inline(always)]
+pub fn grant_invincibility(p: &mut Player, source: u8, ticks: i32) {
+    p.state_flags |= flag::INVINCIBLE;
+    p.invincibility_source = source;
+    p.respawn_timer = ticks;
+}
+
+/// Move projectiles without damage or hit checks (cosmetic only, for match_over / death linger).
+fn advance_projectiles_cosmetic(state: &mut State, map: &Map) {
+    let mut write = 0usize;
+    for read in 0..state.proj_count as usize {
+        let mut proj = state.projectiles[read];
+        if advance_projectile(&mut proj, map) {
+            state.projectiles[write] = proj;
+            write += 1;
+        }
+    }
+    state.proj_count = write as u8;
+}
+
+/// Movement once the result is decided (death linger and after `match_over`): only the
+/// winner moves (input + gravity + collision). The loser is frozen, even if still alive at
+/// time-up, and their buttons aren't recorded, so post-match inputs from the loser can't change
+/// the final state or its hash.
+fn move_winner_only(state: &mut State, inputs: &[FpInput], map: &Map) {
+    let Ok(i) = usize::try_from(state.winner) else { return };
+    if i >= state.player_count as usize { return; }
+    let stamina = state.cfg_stamina;
+    let p = &mut state.players[i];
+    if p.state_flags & flag::ALIVE != 0 {
+        apply_input_mut(p, inputs[i].buttons, state.prev_buttons[i], inputs[i].aim_x, map, stamina);
+        apply_gravity_mut(p);
+        move_and_collide_mut(p, inputs[i].buttons, map, stamina);
+    }
+    state.prev_buttons[i] = inputs[i].buttons;
+}
+
+/// Advance one tick in place, on the state's own map. `inputs` holds one input per player
+/// (`State::player_count`) in registered order, as transcripts record them; the sim routes
+/// each to its internal index (`side_index`). Any past `player_count` are ignored.
+pub fn step_mut(state: &mut State, inputs: &[FpInput]) {
+    let map = Arc::clone(&state.map);
+    step_mut_on(state, inputs, &map, &mut ());
+}
+
+/// Emotes are reported and masked off, then a decided match (`match_over`, the death linger)
+/// only moves its winner; otherwise the tick runs every `Phase` in order.
+fn step_mut_on<D: EventSink>(state: &mut State, inputs: &[FpInput], map: &Map, events: &mut D) {
+    let n = state.player_count as usize;
+
+    // 1. Inputs to internal order; emotes reported, then masked off so nothing below
+    // (prev_buttons included) sees them
+    let mut played = [NULL_INPUT; MAX_PLAYERS];
+    for (registered, inp) in inputs[..n].iter().enumerate() {
+        let i = side_index(state.side_swap, registered as i32) as usize;
+        let emote = button::emote(inp.buttons);
+        if emote != 0 {
+            events.emote(EmoteEvent { player: i as i32, emote });
+        }
+        played[i] = FpInput { buttons: inp.buttons & !button::EMOTE_MASK, ..*inp };
+    }
+    let inputs = &played[..n];
+
+    if state.match_over {
+        // Winner can still move after match ends (taunt/flex/dance)
+        state.tick += 1;
+        move_winner_only(state, inputs, map);
+        return;
+    }
+
+    // Death linger countdown — winner can still move, but no combat
+    if state.death_linger_timer > 0 {
+        state.tick += 1;
+        state.death_linger_timer -= 1;
+        if state.death_linger_timer <= 0 {
+            state.match_over = true;
+            state.death_linger_timer = 0;
+            // Clear all projectiles, pickups, and player weapons on match end
+            state.proj_count = 0;
+            state.pickup_count = 0;
+            state.health_pickup_count = 0;
+            for p in &mut state.players {
+                p.weapon = WEAPON_NONE;
+                p.ammo = 0;
+            }
+        }
+        // Let the winner keep moving during linger (input + gravity + collision)
+        move_winner_only(state, inputs, map);
+        return;
+    }
+
+    state.tick += 1;
+    let mut tick = TickScratch::new(state.prev_buttons);
+    for phase in Phase::ALL {
+        run_phase(phase, state, inputs, map, &mut tick, events);
+    }
+    debug_assert_eq!(tick.next_phase, Phase::ALL.len(), "a gameplay phase was skipped");
+}
+
+// -- Phases ------------------------------------------------------------------
+
+/// The phases of a gameplay tick, in the order `step_mut` runs them (`Phase::ALL`). Each is a
+/// function of its own below; the order decides outcomes (a pickup grabbed before the shot
+/// that needs it, a zone death after the hit deaths), so a new phase goes in here, not inline.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Phase {
+    /// Shoot cooldowns, invincibility and stomp cooldowns run down (`tick_cooldowns`).
+    Cooldowns,
+    /// Input, gravity, then movement and platform collision (`move_players`).
+    Movement,
+    /// Damage from the hazard platforms touched while moving (`apply_hazard_damage`).
+    Hazards,
+    /// New stomps, after movement (`start_stomps`).
+    StartStomps,
+    /// Active stomps: damage, auto-run, shake-off and detach (`tick_stomps`).
+    TickStomps,
+    /// Post-movement positions into the lag-compensation ring (`record_positions`).
+    RecordPositions,
+    /// Weapon, then health pickups (`resolve_weapon_pickups`, `resolve_health_pickups`).
+    Pickups,
+    /// New shots from armed players (`shoot`).
+    Shooting,
+    /// New shots resolved where they spawn, before their first move (`resolve_hits_mut`).
+    PointBlankHits,
+    /// Projectiles move; those that hit something solid splash (`move_projectiles`).
+    Projectiles,
+    /// Every projectile against the players (`resolve_hits_mut`).
+    ProjectileHits,
+    /// Killed players lose a life; one survivor starts the final linger (`apply_deaths`).
+    Deaths,
+    /// Sudden-death zone damage (`apply_zone`).
+    Zone,
+    /// The dead drop their weapons as pickups (`drop_weapons`).
+    WeaponDrops,
+    /// Time-up decides the winner (`check_time_up`).
+    TimeUp,
+    /// Projectile kills score (`score_kills`).
+    Score,
+    /// Pickup respawn and despawn timers (`tick_pickup_timers`).
+    PickupTimers,
+    /// A tick of `PickupStats::time_holding_weapon` for every armed player (`tally_weapon_time`).
+    WeaponTime,
+    /// This tick's damage into its `PickupStats::damage_pacing` bucket (`tally_damage_pacing`).
+    Pacing,
+    /// This tick's buttons, for the next tick's edges (`record_prev_buttons`).
+    PrevButtons,
+}
+
+impl Phase {
+    /// Every phase, in declaration (and run) order.
+    pub const ALL: [Phase; 20] = [
+        Phase::Cooldowns,
+        Phase::Movement,
+        Phase::Hazards,
+        Phase::StartStomps,
+        Phase::TickStomps,
+        Phase::RecordPositions,
+        Phase::Pickups,
+        Phase::Shooting,
+        Phase::PointBlankHits,
+        Phase::Projectiles,
+        Phase::ProjectileHits,
+        Phase::Deaths,
+        Phase::Zone,
+        Phase::WeaponDrops,
+        Phase::TimeUp,
+        Phase::Score,
+        Phase::PickupTimers,
+        Phase::WeaponTime,
+        Phase::Pacing,
+        Phase::PrevButtons,
+    ];
+}
+
+/// What phases of one tick hand on to later ones.
+pub(super) struct TickScratch {
+    /// `State::prev_buttons` as the tick started (edges are against these).
+    pub prev_buttons: [u8; MAX_PLAYERS],
+    /// Highest hazard damage each player touched while moving.
+    pub hazard: [i32; MAX_PLAYERS],
+    /// First projectile slot spawned by this tick's shots.
+    pub first_new_proj: usize,
+    pub hit_kills: KillList,
+    pub solid_kills: KillList,
+    /// Damage recorded by the phases run so far, every source together.
+    pub damage: u32,
+    /// Index in `Phase::ALL` of the phase due next.
+    pub next_phase: usize,
+}
+
+impl TickScratch {
+    pub(super) fn new(prev_buttons: [u8; MAX_PLAYERS]) -> Self {
+        TickScratch {
+            prev_buttons,
+            hazard: [0; MAX_PLAYERS],
+            first_new_proj: 0,
+            hit_kills: KillList::new(),
+            solid_kills: KillList::new(),
+            damage: 0,
+            next_phase: 0,
+        }
+    }
+}
+
+/// An `EventSink` passing everything on to `inner` while adding up the damage recorded, so
+/// the pacing tally doesn't depend on which sink the caller stepped with.
+struct DamageTally<'a, D> {
+    inner: &'a mut D,
+    damage: u32,
+}
+
+impl<D: EventSink> EventSink for DamageTally<'_, D> {
+    #[inline(always)]
+    fn record(&mut self, event: DamageEvent) {
+        self.damage = self.damage.saturating_add(event.amount.max(0) as u32);
+        self.inner.record(event);
+    }
+    #[inline(always)]
+    fn shot(&mut self, event: ShotEvent) {
+        self.inner.shot(event);
+    }
+    #[inline(always)]
+    fn pickup(&mut self, event: PickupEvent) {
+        self.inner.pickup(event);
+    }
+    #[inline(always)]
+    fn stomp(&mut self, event: StompEvent) {
+        self.inner.stomp(event);
+    }
+    #[inline(always)]
+    fn emote(&mut self, event: EmoteEvent) {
+        self.inner.emote(event);
+    }
+}
+
+/// Run `phase`, which must be the one due next in `tick`.
+#[inline(always)]
+pub(super) fn run_phase<D: EventSink>(
+    phase: Phase,
+    state: &mut State,
+    inputs: &[FpInput],
+    map: &Map,
+    tick: &mut TickScratch,
+    events: &mut D,
+) {
+    debug_assert_eq!(
+        Phase::ALL.get(tick.next_phase),
+        Some(&phase),
+        "phase {phase:?} run out of order (phase {} due)",
+        tick.next_phase
+    );
+    tick.next_phase += 1;
+    let mut sink = DamageTally { inner: events, damage: 0 };
+    let events = &mut sink;
+    match phase {
+        Phase::Cooldowns => tick_cooldowns(state),
+        Phase::Movement => tick.hazard = move_players(state, inputs, &tick.prev_buttons, map),
+        Phase::Hazards => apply_hazard_damage(state, &tick.hazard, events),
+        Phase::StartStomps => start_stomps(state, events),
+        Phase::TickStomps => tick_stomps(state, inputs, &tick.prev_buttons, events),
+        Phase::RecordPositions => record_positions(state),
+        Phase::Pickups => {
+            resolve_weapon_pickups(state, events);
+            resolve_health_pickups(state);
+        }
+        Phase::Shooting => tick.first_new_proj = shoot(state, inputs, events),
+        Phase::PointBlankHits => resolve_hits_mut(state, tick.first_new_proj, &mut tick.hit_kills, events),
+        Phase::Projectiles => move_projectiles(state, map, &mut tick.solid_kills, events),
+        Phase::ProjectileHits => resolve_hits_mut(state, 0, &mut tick.hit_kills, events),
+        Phase::Deaths => apply_deaths(state, &tick.hit_kills, &tick.solid_kills),
+        Phase::Zone => apply_zone(state, map, events),
+        Phase::WeaponDrops => drop_weapons(state),
+        Phase::TimeUp => check_time_up(state),
+        Phase::Score => score_kills(state, &tick.hit_kills, &tick.solid_kills),
+        Phase::PickupTimers => tick_pickup_timers(state),
+        Phase::WeaponTime => tally_weapon_time(state),
+        Phase::Pacing => tally_damage_pacing(state, tick.damage),
+        Phase::PrevButtons => record_prev_buttons(state, inputs),
+    }
+    tick.damage = tick.damage.saturating_add(sink.damage);
+}
+
+/// `Phase::Cooldowns`: for live players, the shoot cooldown, invincibility (on
+/// `respawn_timer`) and the stomp cooldown (paused while ridden).
+#[inline(always)]
+pub(super) fn tick_cooldowns(state: &mut State) {
+    let n = state.player_count as usize;
+    for p in &mut state.players[..n] {
+        if p.state_flags & flag::ALIVE == 0 { continue; }
+        p.shoot_cooldown = (p.shoot_cooldown - 1).max(0);
+        if p.state_flags & flag::INVINCIBLE != 0 {
+            p.respawn_timer -= 1;
+            if p.respawn_timer <= 0 {
+                p.state_flags &= !flag::INVINCIBLE;
+                p.invincibility_source = invincibility::NONE;
+                p.respawn_timer = 0;
+            }
+        }
+        if p.stomp_cooldown > 0 && p.stomped_by < 0 {
+            p.stomp_cooldown -= 1;
+            if p.stomp_cooldown == 0 {
+                p.stomp_cooldown_from = -1;
+            }
+        }
+    }
+}
+
+/// `Phase::Movement`: input, gravity, then move and collide, player by player (all in place,
+/// no copies). Returns the hazard damage each player touched.
+#[inline(always)]
+pub(super) fn move_players(
+    state: &mut State,
+    inputs: &[FpInput],
+    prev_buttons: &[u8; MAX_PLAYERS],
+    map: &Map,
+) -> [i32; MAX_PLAYERS] {
+    let stamina = state.cfg_stamina;
+    let mut hazard = [0i32; MAX_PLAYERS];
+    for i in 0..state.player_count as usize {
+        apply_input_mut(&mut state.players[i], inputs[i].buttons, prev_buttons[i], inputs[i].aim_x, map, stamina);
+        apply_gravity_mut(&mut state.players[i]);
+        hazard[i] = move_and_collide_mut(&mut state.players[i], inputs[i].buttons, map, stamina);
+    }
+    hazard
+}
+
+/// `Phase::RecordPositions`: post-movement positions into this tick's lag-compensation slot.
+#[inline(always)]
+pub(super) fn record_positions(state: &mut State) {
+    let slot = (state.tick as usize) % LAG_RING;
+    for i in 0..state.player_count as usize {
+        state.pos_history_x[i][slot] = state.players[i].x;
+        state.pos_history_y[i][slot] = state.players[i].y;
+    }
+}
+
+/// `Phase::Shooting`: every live, armed player off cooldown and holding SHOOT fires, spending
+/// a round (dropping the weapon on the last). Returns the first slot of the new projectiles.
+#[inline(always)]
+pub(super) fn shoot<D: EventSink>(state: &mut State, inputs: &[FpInput], events: &mut D) -> usize {
+    let first_new_proj = state.proj_count as usize;
+    for i in 0..state.player_count as usize {
+        if state.players[i].state_flags & flag::ALIVE != 0
+            && inputs[i].buttons & button::SHOOT != 0
+            && state.players[i].shoot_cooldown <= 0
+            && state.players[i].weapon != WEAPON_NONE
+            && state.players[i].ammo > 0
+        {
+            let weapon = state.players[i].weapon;
+            let stats = fp_weapon_stats(weapon);
+            state.players[i].shoot_cooldown = stats.cooldown;
+            // Wall sliding aim override is applied in shot_direction (gun always points outward)
+            let pellets = spawn_weapon_projectiles(state, i, inputs[i].aim_x, inputs[i].aim_y);
+            if pellets > 0 {
+                let first = &state.projectiles[(state.proj_count - pellets) as usize];
+                events.shot(ShotEvent {
+                    shooter: state.players[i].id, weapon, pellets, x: first.x, y: first.y,
+                });
+            }
+            state.players[i].ammo -= 1;
+            if state.players[i].ammo <= 0 {
+                state.players[i].weapon = WEAPON_NONE;
+            }
+        }
+    }
+    first_new_proj
+}
+
+/// `Phase::Projectiles`: move projectiles in place and compact the dead ones. Platform and
+/// wall contacts destroy a shot (grenades bounce first); a splash weapon's destruction damages
+/// around it, its kills going to `solid_kills`.
+#[inline(always)]
+pub(super) fn move_projectiles<D: EventSink>(
+    state: &mut State,
+    map: &Map,
+    solid_kills: &mut KillList,
+    events: &mut D,
+) {
+    let n = state.player_count as usize;
+    let mut write = 0usize;
+    for read in 0..state.proj_count as usize {
+        let mut proj = state.projectiles[read];
+        if advance_projectile(&mut proj, map) {
+            state.projectiles[write] = proj;
+            write += 1;
+        } else if fp_weapon_stats(proj.weapon).splash_radius > 0 {
+            // Splash damage on any destruction
+            let owner_immune = state.tick < proj.owner_immune_until;
+            apply_fp_splash_damage(&proj, owner_immune, None, state.tick, &mut state.players[..n], solid_kills, events);
+        }
+    }
+    state.proj_count = write as u8;
+}
+
+/// `Phase::Deaths`: each player killed by a hit or a splash loses a life and stops, and its
+/// stomp links break. Then one player left with lives starts the final linger as the winner;
+/// none left starts it with player 0. (No respawn: one life per round, a death ends it.)
+#[inline(always)]
+pub(super) fn apply_deaths(state: &mut State, hit_kills: &KillList, solid_kills: &KillList) {
+    let n = state.player_count as usize;
+    for p_idx in 0..n {
+        if hit_kills.contains_victim(state.players[p_idx].id)
+            || solid_kills.contains_victim(state.players[p_idx].id) {
+            state.players[p_idx].lives -= 1;
+            state.players[p_idx].respawn_timer = 0;
+            state.players[p_idx].vx = 0;
+            state.players[p_idx].vy = 0;
+            // Break stomp links
+            let my_id = state.players[p_idx].id;
+            for other in (0..n).filter(|&o| o != p_idx) {
+                if state.players[other].stomping_on == my_id {
+                    state.players[other].stomping_on = -1;
+                    state.players[other].grounded = false;
+                }
+                if state.players[other].stomped_by == my_id {
+                    clear_mount_fields(&mut state.players[other]);
+                }
+            }
+            clear_stomp_fields(&mut state.players[p_idx]);
+        }
+    }
+
+    // Check elimination — start linger instead of immediate match_over
+    let mut alive_count = 0i32;
+    let mut alive_id = -1i32;
+    for i in 0..n {
+        if state.players[i].lives > 0 { alive_count += 1; alive_id = state.players[i].id; }
+    }
+    if alive_count == 1 {
+        start_final_linger(state, alive_id);
+    } else if alive_count == 0 {
+        start_final_linger(state, 0);
+    }
+}
+
+/// `Phase::TimeUp`: at `cfg_match_duration`, unless already decided, the match ends with the
+/// best `time_up_rank` winning (the lowest index on a full tie).
+#[inline(always)]
+pub(super) fn check_time_up(state: &mut State) {
+    if !state.match_over && state.death_linger_timer == 0 && state.tick >= state.cfg_match_duration {
+        state.match_over = true;
+        let mut best = 0;
+        for i in 1..state.player_count as usize {
+            if time_up_rank(state, i) > time_up_rank(state, best) { best = i; }
+        }
+        state.winner = state.players[best].id;
+    }
+}
+
+/// `Phase::Score`: a point per projectile kill (stomp kills are scored by the stomp phases).
+/// Own-shot deaths (past the owner-immunity window) cost a life but score nobody.
+#[inline(always)]
+pub(super) fn score_kills(state: &mut State, hit_kills: &KillList, solid_kills: &KillList) {
+    for &(killer, victim) in hit_kills.iter().chain(solid_kills.iter()) {
+        if killer >= 0 && (killer as usize) < state.score.len() && killer != victim {
+            state.score[killer as usize] += 1;
+        }
+    }
+}
+
+/// `Phase::WeaponTime`: each live player ending the tick armed holds that weapon a tick longer.
+#[inline(always)]
+pub(super) fn tally_weapon_time(state: &mut State) {
+    for i in 0..state.player_count as usize {
+        let p = &state.players[i];
+        if p.state_flags & flag::ALIVE == 0 { continue; }
+        if let Some(t) = state.pickup_stats.time_holding_weapon[i].get_mut(p.weapon as usize) {
+            *t += 1;
+        }
+    }
+}
+
+/// `Phase::Pacing`: `damage` into the bucket of the current tick, the last used bucket once
+/// the match runs past them.
+#[inline(always)]
+pub(super) fn tally_damage_pacing(state: &mut State, damage: u32) {
+    if damage == 0 { return; }
+    let last = pacing_buckets(state.cfg_match_duration) - 1;
+    let bucket = (((state.tick - 1).max(0) / PACING_BUCKET_TICKS) as usize).min(last);
+    let slot = &mut state.pickup_stats.damage_pacing[bucket];
+    *slot = slot.saturating_add(damage.min(u16::MAX as u32) as u16);
+}
+
+/// `Phase::PrevButtons`: this tick's buttons, for the next tick's edge detection.
+#[inline(always)]
+pub(super) fn record_prev_buttons(state: &mut State, inputs: &[FpInput]) {
+    for i in 0..state.player_count as usize {
+        state.prev_buttons[i] = inputs[i].buttons;
+    }
+}
+
+/// Standing of player `i` when time runs out, compared lexicographically: most lives, then
+/// most kills, then most health.
+#[inline(always)]
+fn time_up_rank(state: &State, i: usize) -> (i32, u32, i32) {
+    let p = &state.players[i];
+    (p.lives, state.score[i], p.health)
+}
+
+/// Index of the highest score; the lowest index wins ties.
+#[inline(always)]
+pub(super) fn top_score_index(score: &[u32]) -> usize {
+    let mut best = 0;
+    for (i, &s) in score.iter().enumerate().skip(1) {
+        if s > score[best] { best = i; }
+    }
+    best
+}
+
+/// Convenience wrapper that returns a new State (for tests / non-zkVM use).
+pub fn step(prev: &State, inputs: &[FpInput]) -> State {
+    let mut s = prev.clone();
+    step_mut(&mut s, inputs);
+    s
+}
+
+/// Players whose `prev_buttons` in `state` disagree with `recorded`, the inputs of the tick
+/// that produced it in registered order, as a bitmask (bit `i` for `recorded[i]`). A snapshot
+/// with a mismatch makes the next jump edge differ from the recorded run. Once the result is
+/// decided (death linger, match over) a tick only records the winner's buttons, so only the
+/// winner is compared. Emote bits are never recorded, so they are ignored.
+pub fn prev_buttons_mismatch(state: &State, recorded: &[FpInput]) -> u8 {
+    let decided = state.match_over || state.death_linger_timer > 0;
+    let mut mask = 0;
+    for (registered, inp) in recorded.iter().enumerate().take(state.player_count as usize) {
+        let i = side_index(state.side_swap, registered as i32) as usize;
+        if decided && state.winner != i as i32 {
+            continue;
+        }
+        if state.prev_buttons[i] != inp.buttons & !button::EMOTE_MASK {
+            mask |= 1 << registered;
+        }
+    }
+    mask
+}
+
+// -- Event chain (lightweight spectator verification) -------------------------
+
+/// What a spectator can check about one tick: who died, and who was credited. Live stepping
+/// (`step_mut_events`) also lists what the renderer plays effects for: each instance of
+/// damage, shots, pickups and stomps, and the emotes selected. Only the kills are part of the
+/// event chain (which hashes the inputs, emote bits and all).
+#[derive(Clone, Copy, Debug, Default)]
+pub struct TickEvents {
+    /// (killer, victim) per death; killer is -1 when nobody scored (hazard, uncredited zone
+    /// death, own shot).
+    pub kills: KillList,
+    /// Damage in the order it was dealt (two records for a rocket's direct hit and the
+    /// splash on the other player), zone damage included.
+    pub damage: DamageList,
+    pub shots: ShotList,
+    pub pickups: PickupList,
+    pub stomps: StompList,
+    pub emotes: EmoteList,
+}
+
+impl TickEvents {
+    /// Events of the step that took `before` to `after`, read off the lives and score deltas.
+    /// Damage, shots, pickups, stomps and emotes can't be recovered from two states, so those
+    /// lists are empty.
+    pub fn between(before: &State, after: &State) -> Self {
+        Self::since(before.players.map(|p| p.lives), before.score, after)
+    }
+
+    /// The killer of each victim is the first other player whose score went up.
+    fn since(lives: [i32; MAX_PLAYERS], score: [u32; MAX_PLAYERS], after: &State) -> Self {
+        let mut kills = KillList::new();
+        let n = after.player_count as usize;
+        for victim in 0..n {
+            if after.players[victim].lives < lives[victim] {
+                let killer = (0..n)
+                    .find(|&other| other != victim && after.score[other] > score[other])
+                    .map_or(-1, |other| other as i32);
+                kills.push(killer, victim as i32);
+            }
+        }
+        TickEvents { kills, ..TickEvents::default() }
+    }
+}
+
+/// Rolling SHA-256 over (tick, inputs, kill events), starting from 32 zero bytes:
+/// `head' = SHA-256(head ‖ tick LE ‖ input bytes ‖ kill count ‖ (killer, victim) LE…)`, with
+/// `INPUT_BYTES` per player (6 in a two-player match).
+/// A server publishes the head as it goes; anyone with the transcript recomputes it, and the
+/// `event-chain` guest feature commits the final head next to the journal.
+#[derive(Clone, Debug, Default)]
+pub struct EventChain {
+    head: [u8; 32],
+}
+
+impl EventChain {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn head(&self) -> [u8; 32] {
+        self.head
+    }
+
+    /// Fold one tick into the chain and return the new head.
+    pub fn update(&mut self, tick: i32, inputs: &[FpInput], events: &TickEvents) -> [u8; 32] {
+        let mut h = Sha256::new();
+        h.update(self.head);
+        h.update(tick.to_le_bytes());
+        for inp in inputs {
+            h.update([inp.buttons, inp.aim_x as u8, inp.aim_y as u8]);
+        }
+        h.update([events.kills.len]);
+        for &(killer, victim) in events.kills.iter() {
+            h.update(killer.to_le_bytes());
+            h.update(victim.to_le_bytes());
+        }
+        self.head = h.finalize().into();
+        self.head
+    }
+}
+
+/// `step_mut`, returning the tick's events with each one recorded where it happened. The
+/// guests' `step_mut` records nothing, so its path is unchanged.
+pub fn step_mut_events(state: &mut State, inputs: &[FpInput]) -> TickEvents {
+    let lives = state.players.map(|p| p.lives);
+    let score = state.score;
+    let mut events = TickEvents::default();
+    let map = Arc::clone(&state.map);
+    step_mut_on(state, inputs, &map, &mut events);
+    events.kills = TickEvents::since(lives, score, state).kills;
+    events
+}
+
+/// `step_mut`, then fold the tick (numbered after the step) into `chain`.
+pub fn step_mut_chained(state: &mut State, inputs: &[FpInput], chain: &mut EventChain) -> [u8; 32] {
+    let lives = state.players.map(|p| p.lives);
+    let score = state.score;
+    step_mut(state, inputs);
+    let inputs = &inputs[..state.player_count as usize];
+    chain.update(state.tick, inputs, &TickEvents::since(lives, score, state))
+}
+
+// -- Streaming sim + hash (single pass, zero heap allocation) ----------------
+
+/// Result of running the game simulation with streaming hash.
+pub struct StreamingResult {
+    pub state: State,
+    pub transcript_hash: [u8; 32],
+    pub seed_commit: [u8; 32],
+}
+
+/// Run the full simulation in a single pass over raw input bytes.
+/// Parses each tick's inputs, feeds them to a streaming SHA-256 hasher,
+/// and steps the sim — all without allocating any Vec.
+///
+/// `data` layout: [seed: 4 LE] [tick_count: 4 LE] [tick × 6 bytes], or the wide layout of
+/// `encode_raw_input_wide`, whose player count must be the config's.
+pub fn run_streaming(data: &[u8]) -> StreamingResult {
+    run_streaming_with(data, &FpMatchConfig::default())
+}
+
+/// `run_streaming` under a match config, on `cfg.oriented_map(&arena_map())`.
+pub fn run_streaming_with(data: &[u8], cfg: &FpMatchConfig) -> StreamingResult {
+    run_streaming_inner(data, cfg, &arena_map(), None)
+}
+
+/// `run_streaming_with` on `cfg.oriented_map(base)` instead of the arena.
+pub fn run_streaming_on(data: &[u8], cfg: &FpMatchConfig, base: &Map) -> StreamingResult {
+    run_streaming_inner(data, cfg, base, None)
+}
+
+/// `run_streaming_with`, also folding every stepped tick into `chain` (ticks after the
+/// match ends are hashed into the transcript but not stepped, so not chained).
+pub fn run_streaming_with_chain(data: &[u8], cfg: &FpMatchConfig, chain: &mut EventChain) -> StreamingResult {
+    run_streaming_inner(data, cfg, &arena_map(), Some(chain))
+}
+
+fn run_streaming_inner(
+    data: &[u8],
+    cfg: &FpMatchConfig,
+    base: &Map,
+    mut chain: Option<&mut EventChain>,
+) -> StreamingResult {
+    let header = RawInputHeader::parse(data);
+    let (seed, tick_count, n) = (header.seed, header.tick_count, header.player_count);
+    assert!(
+        n == cfg.player_count as usize,
+        "raw input declares {n} players, config {}", cfg.player_count
+    );
+    let tick_len = header.tick_bytes();
+    let end = header.ticks_at + tick_count * tick_len;
+
+    let mut state = create_initial_state_with(seed, &cfg.oriented_map(base), cfg);
+    let mut hasher = Sha256::new();
+
+    let mut offset = header.ticks_at;
+    for _ in 0..tick_count {
+        // Parse inputs directly from raw bytes (no intermediate Vec)
+        let tick_bytes = &data[offset..offset + tick_len];
+        let inputs = tick_inputs(tick_bytes, n);
+
+        // Feed raw tick bytes to hasher (same serialization as hash_transcript)
+        hasher.update(tick_bytes);
+
+        // Step the simulation
+        match chain.as_deref_mut() {
+            Some(chain) => {
+                step_mut_chained(&mut state, &inputs, chain);
+            }
+            None => step_mut(&mut state, &inputs),
+        }
+        offset += tick_len;
+        if state.match_over {
+            // Hash remaining ticks for transcript integrity
+            hasher.update(&data[offset..end]);
+            break;
+        }
+    }
+
+    let transcript_hash: [u8; 32] = hasher.finalize().into();
+    let seed_commit = hash_seed(seed);
+
+    StreamingResult {
+        state,
+        transcript_hash,
+        seed_commit,
+    }
+}
+
+// -- Ghost replay (practice mode) ----------------------------------------------
+
+/// Where one player was on a sampled tick: what practice mode draws for the ghost of an
+/// earlier run, without keeping whole states.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GhostFrame {
+    pub tick: i32,
+    pub x: Fp,
+    pub y: Fp,
+    /// -1 left, 1 right.
+    pub facing: i8,
+    /// WEAPON_NONE (-1) or a weapon id.
+    pub weapon: i8,
+    pub alive: bool,
+}
+
+/// Bytes per frame of `encode_ghost`: tick u32, x i32, y i32 (all LE, x and y fixed-point),
+/// weapon i8, then a flag byte (`GHOST_ALIVE`, `GHOST_FACING_LEFT`).
+pub const GHOST_FRAME_BYTES: usize = 14;
+pub const GHOST_ALIVE: u8 = 1;
+pub const GHOST_FACING_LEFT: u8 = 2;
+
+impl GhostFrame {
+    pub(super) fn of(state: &State, player_idx: usize) -> GhostFrame {
+        let p = &state.players[player_idx];
+        GhostFrame {
+            tick: state.tick,
+            x: p.x,
+            y: p.y,
+            facing: if p.facing < 0 { -1 } else { 1 },
+            weapon: p.weapon,
+            alive: p.state_flags & flag::ALIVE != 0,
+        }
+    }
+}
+
+/// `extract_ghost_every` with every tick kept.
+pub fn extract_ghost(seed: u32, map: &Map, transcript: &[[FpInput; 2]], player_idx: usize) -> Vec<GhostFrame> {
+    extract_ghost_every(seed, map, transcript, player_idx, 1)
+}
+
+/// Replay a two-player transcript once and keep `player_idx`'s frame (a transcript column, so a
+/// registered player) on tick 0 and on every `every`-th tick after it, stopping at the tick the
+/// match ends (always kept, so the ghost finishes where the run did).
+pub fn extract_ghost_every(
+    seed: u32,
+    map: &Map,
+    transcript: &[[FpInput; 2]],
+    player_idx: usize,
+    every: usize,
+) -> Vec<GhostFrame> {
+    assert!(player_idx < 2, "ghost of player {player_idx} in a two-player transcript");
+    assert!(every > 0, "ghost sampled every 0 ticks");
+    let mut state = create_initial_state(seed, map);
+    let player_idx = side_index(state.side_swap, player_idx as i32) as usize;
+    let mut frames = Vec::with_capacity(transcript.len() / every + 2);
+    frames.push(GhostFrame::of(&state, player_idx));
+    for inputs in transcript {
+        step_mut(&mut state, inputs);
+        if state.match_over || state.tick as usize % every == 0 {
+            frames.push(GhostFrame::of(&state, player_idx));
+        }
+        if state.match_over {
+            break;
+        }
+    }
+    frames
+}
+
+/// Pack frames for the renderer, `GHOST_FRAME_BYTES` each.
+pub fn encode_ghost(frames: &[GhostFrame]) -> Vec<u8> {
+    let mut buf = Vec::with_capacity(frames.len() * GHOST_FRAME_BYTES);
+    for f in frames {
+        buf.extend_from_slice(&(f.tick as u32).to_le_bytes());
+        buf.extend_from_slice(&f.x.to_le_bytes());
+        buf.extend_from_slice(&f.y.to_le_bytes());
+        buf.push(f.weapon as u8);
+        let mut flags = 0;
+        if f.alive {
+            flags |= GHOST_ALIVE;
+        }
+        if f.facing < 0 {
+            flags |= GHOST_FACING_LEFT;
+        }
+        buf.push(flags);
+    }
+    buf
+}
+
+/// Frames of an `encode_ghost` buffer, in order; a trailing partial frame is dropped.
+pub fn decode_ghost(bytes: &[u8]) -> impl Iterator<Item = GhostFrame> + '_ {
+    bytes.chunks_exact(GHOST_FRAME_BYTES).map(|b| {
+        let word = |at: usize| [b[at], b[at + 1], b[at + 2], b[at + 3]];
+        GhostFrame {
+            tick: u32::from_le_bytes(word(0)) as i32,
+            x: i32::from_le_bytes(word(4)),
+            y: i32::from_le_bytes(word(8)),
+            weapon: b[12] as i8,
+            facing: if b[13] & GHOST_FACING_LEFT != 0 { -1 } else { 1 },
+            alive: b[13] & GHOST_ALIVE != 0,
+        }
+    })
+}
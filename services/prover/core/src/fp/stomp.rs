@@ -0,0 +1,220 @@
+//! Stomps: landing on a head, riding, shaking off and detaching.
+
+use super::*;
+
+/// Helper: clear all stomp fields on a player.
+#[inline(always)]
+pub(super) fn clear_stomp_fields(p: &mut Player) {
+    p.stomping_on = -1;
+    clear_mount_fields(p);
+}
+
+/// Helper: clear the fields of a player being ridden (its rider, shake-off and auto-run) but
+/// not `stomping_on`: in a stack of three or more, a mount that loses its rider keeps riding
+/// the player under it.
+#[inline(always)]
+pub(super) fn clear_mount_fields(p: &mut Player) {
+    p.stomped_by = -1;
+    p.stomp_shake_progress = 0;
+    p.stomp_last_shake_dir = 0;
+    p.stomp_auto_run_dir = 0;
+    p.stomp_auto_run_timer = 0;
+    p.stomp_airborne_ticks = 0;
+}
+
+/// Helper: rider steps off without the shake-off bounce. The victim gets the usual cooldown so
+/// the rider can't land straight back on its head (anyone else still can).
+#[inline(always)]
+pub(super) fn detach_stomp_rider(players: &mut [Player], rider_idx: usize, victim_idx: usize) {
+    players[rider_idx].stomping_on = -1;
+    players[rider_idx].vy = 0;
+    players[rider_idx].grounded = false;
+    players[victim_idx].stomp_cooldown = STOMP_COOLDOWN_TICKS;
+    players[victim_idx].stomp_cooldown_from = players[rider_idx].id;
+    clear_mount_fields(&mut players[victim_idx]);
+}
+
+/// Stomp detection, after movement. Each A tries every B in index order; once A is riding
+/// someone it starts no other stomp this tick. A B in its stomp cooldown is only off limits to
+/// the rider it last got rid of.
+#[inline(always)]
+pub(super) fn start_stomps<D: EventSink>(state: &mut State, events: &mut D) {
+    let n = state.player_count as usize;
+    for a_idx in 0..n {
+        for b_idx in 0..n {
+            if b_idx == a_idx { continue; }
+            // Skip if already stomping or being stomped, or target in cooldown against A
+            if state.players[a_idx].stomping_on >= 0 { break; }
+            if state.players[a_idx].stomped_by >= 0 { break; }
+            if state.players[a_idx].state_flags & flag::ALIVE == 0 { break; }
+            if state.players[b_idx].stomped_by >= 0 { continue; }
+            if state.players[b_idx].stomp_cooldown > 0
+                && state.players[b_idx].stomp_cooldown_from == state.players[a_idx].id
+            {
+                continue;
+            }
+            if state.players[b_idx].state_flags & flag::ALIVE == 0 { continue; }
+
+            // A falling onto B's head
+            if state.players[a_idx].vy > 0 {
+                let a_feet = state.players[a_idx].y + PLAYER_HEIGHT;
+                let b_head = state.players[b_idx].y;
+                if a_feet >= b_head && a_feet <= b_head + fp(8)
+                    && state.players[a_idx].x + PLAYER_WIDTH > state.players[b_idx].x
+                    && state.players[a_idx].x < state.players[b_idx].x + PLAYER_WIDTH
+                {
+                    // Initiate stomp
+                    let b_id = state.players[b_idx].id;
+                    let a_id = state.players[a_idx].id;
+                    state.players[a_idx].stomping_on = b_id;
+                    state.players[a_idx].grounded = true;
+                    state.players[a_idx].vy = 0;
+                    state.players[a_idx].y = state.players[b_idx].y - PLAYER_HEIGHT;
+
+                    state.players[b_idx].stomped_by = a_id;
+                    state.players[b_idx].stomp_shake_progress = 0;
+                    state.players[b_idx].stomp_last_shake_dir = 0;
+
+                    // Random auto-run direction and timer
+                    let (dir_val, new_rng) = prng_int_range(state.rng_state, 0, 1);
+                    state.rng_state = new_rng;
+                    state.players[b_idx].stomp_auto_run_dir = if dir_val == 0 { -1 } else { 1 };
+                    let (timer_val, new_rng2) = prng_int_range(state.rng_state, STOMP_AUTO_RUN_MIN, STOMP_AUTO_RUN_MAX);
+                    state.rng_state = new_rng2;
+                    state.players[b_idx].stomp_auto_run_timer = timer_val;
+                    events.stomp(StompEvent { kind: stomp_event::STARTED, rider: a_id, victim: b_id });
+                }
+            }
+        }
+    }
+}
+
+/// Active stomps: drop broken links, detach on a rider JUMP edge or a long fall, tick the
+/// victim's damage, auto-run and shake-off, and keep each rider locked on its mount.
+#[inline(always)]
+pub(super) fn tick_stomps<D: EventSink>(
+    state: &mut State,
+    inputs: &[FpInput],
+    prev_buttons: &[u8; MAX_PLAYERS],
+    events: &mut D,
+) {
+    let n = state.player_count as usize;
+    let current_tick = state.tick;
+    for victim_idx in 0..n {
+        if state.players[victim_idx].stomped_by < 0 { continue; }
+        let rider_id = state.players[victim_idx].stomped_by;
+        let Some(rider_idx) = state.players[..n].iter().position(|p| p.id == rider_id) else {
+            clear_mount_fields(&mut state.players[victim_idx]);
+            continue;
+        };
+
+        // Check rider validity
+        if state.players[rider_idx].state_flags & flag::ALIVE == 0
+            || state.players[rider_idx].stomping_on != state.players[victim_idx].id
+        {
+            clear_mount_fields(&mut state.players[victim_idx]);
+            continue;
+        }
+        // A mount the zone or a hazard killed (those deaths don't break links) throws its rider
+        if state.players[victim_idx].state_flags & flag::ALIVE == 0 {
+            state.players[rider_idx].stomping_on = -1;
+            state.players[rider_idx].grounded = false;
+            clear_stomp_fields(&mut state.players[victim_idx]);
+            continue;
+        }
+
+        // Detach: rider taps JUMP, or the pair has been falling too long ("death taxi")
+        let victim = &mut state.players[victim_idx];
+        victim.stomp_airborne_ticks =
+            if victim.grounded { 0 } else { victim.stomp_airborne_ticks.saturating_add(1) };
+        let rider_jump_edge = inputs[rider_idx].buttons & button::JUMP != 0
+            && prev_buttons[rider_idx] & button::JUMP == 0;
+        if rider_jump_edge || victim.stomp_airborne_ticks > STOMP_AIRBORNE_DETACH_TICKS {
+            events.stomp(StompEvent {
+                kind: stomp_event::DETACHED, rider: rider_id, victim: state.players[victim_idx].id,
+            });
+            detach_stomp_rider(&mut state.players, rider_idx, victim_idx);
+            continue;
+        }
+
+        // Damage tick
+        if current_tick % STOMP_DAMAGE_INTERVAL == 0 {
+            let victim = &state.players[victim_idx];
+            events.record(DamageEvent {
+                victim: victim.id, attacker: state.players[rider_idx].id,
+                amount: STOMP_DAMAGE_PER_HIT.min(victim.health.max(0)),
+                source: damage_source::STOMP, weapon: WEAPON_NONE,
+                x: victim.x + PLAYER_WIDTH / 2, y: victim.y + PLAYER_HEIGHT / 2,
+            });
+            state.players[victim_idx].health -= STOMP_DAMAGE_PER_HIT;
+            mark_damaged_by(&mut state.players[victim_idx], rider_id, current_tick);
+            if state.players[victim_idx].health <= 0 {
+                // Kill victim, launch rider
+                state.players[victim_idx].health = 0;
+                state.players[victim_idx].state_flags = 0;
+                state.players[victim_idx].invincibility_source = invincibility::NONE;
+                state.players[rider_idx].stomping_on = -1;
+                state.players[rider_idx].vy = JUMP_VELOCITY / 2;
+                state.players[rider_idx].grounded = false;
+                clear_stomp_fields(&mut state.players[victim_idx]);
+                // Track kill in score
+                let killer = state.players[rider_idx].id;
+                if killer >= 0 && (killer as usize) < state.score.len() {
+                    state.score[killer as usize] += 1;
+                }
+                state.players[victim_idx].lives -= 1;
+                continue;
+            }
+        }
+
+        // Auto-run: move victim
+        state.players[victim_idx].stomp_auto_run_timer -= 1;
+        if state.players[victim_idx].stomp_auto_run_timer <= 0 {
+            state.players[victim_idx].stomp_auto_run_dir *= -1;
+            let (timer_val, new_rng) = prng_int_range(state.rng_state, STOMP_AUTO_RUN_MIN, STOMP_AUTO_RUN_MAX);
+            state.rng_state = new_rng;
+            state.players[victim_idx].stomp_auto_run_timer = timer_val;
+        }
+        let run_dir = state.players[victim_idx].stomp_auto_run_dir;
+        debug_assert!((-1..=1).contains(&run_dir));
+        let run_vx = PLAYER_SPEED * run_dir;
+        state.players[victim_idx].vx = run_vx;
+
+        // Shake-off detection (alternating L/R)
+        let v_buttons = inputs[victim_idx].buttons;
+        let v_prev = prev_buttons[victim_idx];
+        let left_edge = (v_buttons & button::LEFT != 0) && (v_prev & button::LEFT == 0);
+        let right_edge = (v_buttons & button::RIGHT != 0) && (v_prev & button::RIGHT == 0);
+        if left_edge && state.players[victim_idx].stomp_last_shake_dir != -1 {
+            state.players[victim_idx].stomp_shake_progress += STOMP_SHAKE_PER_PRESS;
+            state.players[victim_idx].stomp_last_shake_dir = -1;
+        }
+        if right_edge && state.players[victim_idx].stomp_last_shake_dir != 1 {
+            state.players[victim_idx].stomp_shake_progress += STOMP_SHAKE_PER_PRESS;
+            state.players[victim_idx].stomp_last_shake_dir = 1;
+        }
+        state.players[victim_idx].stomp_shake_progress =
+            (state.players[victim_idx].stomp_shake_progress - STOMP_SHAKE_DECAY).max(0);
+
+        // Break free
+        if state.players[victim_idx].stomp_shake_progress >= STOMP_SHAKE_THRESHOLD {
+            state.players[rider_idx].stomping_on = -1;
+            state.players[rider_idx].vy = JUMP_VELOCITY;
+            state.players[rider_idx].grounded = false;
+            state.players[victim_idx].stomp_cooldown = STOMP_COOLDOWN_TICKS;
+            state.players[victim_idx].stomp_cooldown_from = rider_id;
+            clear_mount_fields(&mut state.players[victim_idx]);
+            events.stomp(StompEvent {
+                kind: stomp_event::BROKEN, rider: rider_id, victim: state.players[victim_idx].id,
+            });
+            continue;
+        }
+
+        // Lock rider to victim position
+        state.players[rider_idx].x = state.players[victim_idx].x;
+        state.players[rider_idx].y = state.players[victim_idx].y - PLAYER_HEIGHT;
+        state.players[rider_idx].vx = 0;
+        state.players[rider_idx].vy = 0;
+        state.players[rider_idx].grounded = true;
+    }
+}
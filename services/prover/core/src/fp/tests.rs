@@ -0,0 +1,3969 @@
+use super::*;
+
+use std::sync::Arc;
+
+use sha2::{Digest, Sha256};
+
+#[test]
+fn fp_arithmetic() {
+    assert_eq!(fp(10), 2560);
+    assert_eq!(mul(fp(3), fp(4)), fp(12));
+    assert_eq!(div(fp(10), fp(2)), fp(5));
+    assert_eq!(mul(GRAVITY, ONE), GRAVITY);
+}
+
+#[test]
+fn idle_match_ends() {
+    let map = arena_map();
+    let mut state = create_initial_state(42, &map);
+    let inputs = [NULL_INPUT; 2];
+    for _ in 0..MATCH_DURATION_TICKS {
+        if state.match_over { break; }
+        state = step(&state, &inputs);
+    }
+    assert!(state.match_over);
+    assert!(state.tick <= MATCH_DURATION_TICKS);
+}
+
+#[test]
+fn player_moves_right() {
+    let map = arena_map();
+    let mut state = create_initial_state(42, &map);
+    let x0 = state.players[0].x;
+    let inputs = [
+        FpInput { buttons: button::RIGHT, aim_x: 1, aim_y: 0 },
+        NULL_INPUT,
+    ];
+    for _ in 0..10 {
+        state = step(&state, &inputs);
+    }
+    assert!(state.players[0].x > x0);
+}
+
+#[test]
+fn unarmed_cannot_shoot() {
+    let map = arena_map();
+    let mut state = create_initial_state(42, &map);
+    // Clear pickups so player stays unarmed
+    state.pickup_count = 0;
+    let inputs = [
+        FpInput { buttons: button::SHOOT, aim_x: 1, aim_y: 0 },
+        NULL_INPUT,
+    ];
+    state = step(&state, &inputs);
+    assert_eq!(state.proj_count, 0);
+}
+
+#[test]
+fn armed_creates_projectile() {
+    let map = arena_map();
+    let mut state = create_initial_state(42, &map);
+    state.players[0].weapon = WEAPON_PISTOL;
+    state.players[0].ammo = 15;
+    state.pickup_count = 0;
+    let inputs = [
+        FpInput { buttons: button::SHOOT, aim_x: 1, aim_y: 0 },
+        NULL_INPUT,
+    ];
+    state = step(&state, &inputs);
+    assert_eq!(state.proj_count, 1);
+    assert_eq!(state.projectiles[0].owner_id, 0);
+    assert_eq!(state.projectiles[0].weapon, WEAPON_PISTOL);
+    assert!(state.projectiles[0].vx > 0);
+}
+
+#[test]
+fn shotgun_creates_five_pellets() {
+    let map = arena_map();
+    let mut state = create_initial_state(42, &map);
+    state.players[0].weapon = WEAPON_SHOTGUN;
+    state.players[0].ammo = 6;
+    state.pickup_count = 0;
+    let inputs = [
+        FpInput { buttons: button::SHOOT, aim_x: 1, aim_y: 0 },
+        NULL_INPUT,
+    ];
+    state = step(&state, &inputs);
+    assert_eq!(state.proj_count, 5);
+    for i in 0..5 {
+        assert_eq!(state.projectiles[i].weapon, WEAPON_SHOTGUN);
+    }
+}
+
+#[test]
+fn weapon_pickup_works() {
+    let map = arena_map();
+    let mut state = create_initial_state(42, &map);
+    // Place player 0 on top of weapon pickup 0
+    state.players[0].x = state.weapon_pickups[0].x - PLAYER_WIDTH / 2;
+    state.players[0].y = state.weapon_pickups[0].y - PLAYER_HEIGHT / 2;
+    assert_eq!(state.players[0].weapon, WEAPON_NONE);
+    state = step(&state, &[NULL_INPUT; 2]);
+    assert_ne!(state.players[0].weapon, WEAPON_NONE);
+    assert!(state.players[0].ammo > 0);
+}
+
+#[test]
+fn ammo_depletes_drops_weapon() {
+    let map = arena_map();
+    let mut state = create_initial_state(42, &map);
+    state.players[0].weapon = WEAPON_PISTOL;
+    state.players[0].ammo = 1;
+    state.pickup_count = 0;
+    let inputs = [
+        FpInput { buttons: button::SHOOT, aim_x: 1, aim_y: 0 },
+        NULL_INPUT,
+    ];
+    state = step(&state, &inputs);
+    assert_eq!(state.players[0].weapon, WEAPON_NONE);
+    assert_eq!(state.players[0].ammo, 0);
+}
+
+#[test]
+fn deterministic_replay() {
+    let map = arena_map();
+    let run = || {
+        let mut s = create_initial_state(42, &map);
+        for tick in 0..200i32 {
+            let p0 = FpInput {
+                buttons: if tick % 30 < 15 { button::RIGHT | button::SHOOT } else { button::LEFT },
+                aim_x: 1,
+                aim_y: 0,
+            };
+            let p1 = FpInput {
+                buttons: if tick % 20 < 10 { button::LEFT | button::SHOOT } else { button::RIGHT | button::JUMP },
+                aim_x: -1,
+                aim_y: 0,
+            };
+            s = step(&s, &[p0, p1]);
+            if s.match_over { break; }
+        }
+        s
+    };
+    let r1 = run();
+    let r2 = run();
+    assert_eq!(r1.tick, r2.tick);
+    assert_eq!(r1.winner, r2.winner);
+    assert_eq!(r1.score, r2.score);
+    assert_eq!(r1.players[0].x, r2.players[0].x);
+    assert_eq!(r1.players[1].x, r2.players[1].x);
+    assert_eq!(r1.players[0].weapon, r2.players[0].weapon);
+    assert_eq!(r1.players[0].ammo, r2.players[0].ammo);
+}
+
+#[test]
+fn encode_decode_roundtrip() {
+    let map = arena_map();
+    let mut state = create_initial_state(42, &map);
+    state.players[0].weapon = WEAPON_SNIPER;
+    state.players[0].ammo = 3;
+    let encoded = encode_state(&state);
+    let decoded = decode_state(&encoded);
+    assert_eq!(state.tick, decoded.tick);
+    assert_eq!(state.players[0].x, decoded.players[0].x);
+    assert_eq!(state.players[0].weapon, decoded.players[0].weapon);
+    assert_eq!(state.players[0].ammo, decoded.players[0].ammo);
+    assert_eq!(state.pickup_count, decoded.pickup_count);
+    assert_eq!(state.weapon_pickups[0].weapon, decoded.weapon_pickups[0].weapon);
+    assert_eq!(state.rng_state, decoded.rng_state);
+    assert_eq!(state.winner, decoded.winner);
+}
+
+#[test]
+fn streaming_matches_original() {
+    // Build a transcript with some combat inputs
+    let seed = 42u32;
+    let tick_count = 300u32;
+    let mut transcript = Vec::with_capacity(tick_count as usize);
+    let mut raw = Vec::with_capacity(8 + tick_count as usize * 6);
+    raw.extend_from_slice(&seed.to_le_bytes());
+    raw.extend_from_slice(&tick_count.to_le_bytes());
+
+    for t in 0..tick_count {
+        let p0 = FpInput {
+            buttons: if t % 3 == 0 { button::RIGHT | button::SHOOT } else { button::RIGHT },
+            aim_x: 1,
+            aim_y: 0,
+        };
+        let p1 = FpInput {
+            buttons: if t % 5 == 0 { button::LEFT | button::SHOOT } else { button::LEFT },
+            aim_x: -1,
+            aim_y: 0,
+        };
+        raw.push(p0.buttons);
+        raw.push(p0.aim_x as u8);
+        raw.push(p0.aim_y as u8);
+        raw.push(p1.buttons);
+        raw.push(p1.aim_x as u8);
+        raw.push(p1.aim_y as u8);
+        transcript.push([p0, p1]);
+    }
+
+    // Original three-step approach
+    let (orig_seed, orig_transcript) = decode_raw_input(&raw);
+    let map = arena_map();
+    let mut orig_state = create_initial_state(orig_seed, &map);
+    for tick_inputs in &orig_transcript {
+        step_mut(&mut orig_state, tick_inputs);
+        if orig_state.match_over { break; }
+    }
+    let orig_hash = hash_transcript(&orig_transcript);
+    let orig_seed_commit = hash_seed(orig_seed);
+
+    // Streaming single-pass approach
+    let streaming = run_streaming(&raw);
+
+    // Must produce identical results
+    assert_eq!(streaming.state.tick, orig_state.tick);
+    assert_eq!(streaming.state.winner, orig_state.winner);
+    assert_eq!(streaming.state.match_over, orig_state.match_over);
+    assert_eq!(streaming.state.score, orig_state.score);
+    assert_eq!(streaming.state.players[0].x, orig_state.players[0].x);
+    assert_eq!(streaming.state.players[1].x, orig_state.players[1].x);
+    assert_eq!(streaming.state.players[0].lives, orig_state.players[0].lives);
+    assert_eq!(streaming.state.players[1].lives, orig_state.players[1].lives);
+    assert_eq!(streaming.transcript_hash, orig_hash);
+    assert_eq!(streaming.seed_commit, orig_seed_commit);
+}
+
+#[test]
+fn merge_input_streams_pads_shorter_stream_with_null_input() {
+    let p0 = [button::RIGHT, 1, 0, button::JUMP, 2, 0xFF];
+    let p1 = [button::LEFT, 0xFF, 0, 9];
+    let merged = merge_input_streams(&p0, &p1);
+    // p1's second tick is missing (its trailing byte is a partial input)
+    assert_eq!(merged, [button::RIGHT, 1, 0, button::LEFT, 0xFF, 0, button::JUMP, 2, 0xFF, 0, 0, 0]);
+    assert_eq!(merge_input_streams(&p1, &p0)[6..], [0, 0, 0, button::JUMP, 2, 0xFF]);
+    assert!(merge_input_streams(&[], &[1, 2]).is_empty());
+
+    // The merged ticks are the raw transcript's
+    let mut raw = 7u32.to_le_bytes().to_vec();
+    raw.extend_from_slice(&2u32.to_le_bytes());
+    raw.extend_from_slice(&merged);
+    let (_, transcript) = decode_raw_input(&raw);
+    let idle = transcript[1][1];
+    assert_eq!((idle.buttons, idle.aim_x, idle.aim_y), (0, 0, 0));
+    assert_eq!(transcript[1][0].aim_y, -1);
+}
+
+#[test]
+fn streaming_early_exit_hashes_all_ticks() {
+    // Create inputs where match ends early (one player dies quickly)
+    // The streaming function must hash ALL tick bytes, not just ticks played
+    let seed = 42u32;
+    let tick_count = 1800u32;
+    let mut raw = Vec::with_capacity(8 + tick_count as usize * 6);
+    raw.extend_from_slice(&seed.to_le_bytes());
+    raw.extend_from_slice(&tick_count.to_le_bytes());
+
+    // All idle inputs
+    for _ in 0..tick_count {
+        raw.extend_from_slice(&[0u8; 6]);
+    }
+
+    let streaming = run_streaming(&raw);
+
+    // Original approach for comparison
+    let (_, orig_transcript) = decode_raw_input(&raw);
+    let orig_hash = hash_transcript(&orig_transcript);
+    assert_eq!(streaming.transcript_hash, orig_hash);
+}
+
+/// Both players charge and shoot (same inputs as the host vector "duel" scenario).
+fn duel_transcript(ticks: u32) -> Vec<[FpInput; 2]> {
+    (0..ticks)
+        .map(|t| {
+            let jump = if (t / 30) % 2 == 1 { button::JUMP } else { 0 };
+            [
+                FpInput { buttons: button::RIGHT | button::SHOOT | jump, aim_x: 1, aim_y: 0 },
+                FpInput { buttons: button::LEFT | button::SHOOT, aim_x: -1, aim_y: 0 },
+            ]
+        })
+        .collect()
+}
+
+/// Live chained stepping vs. recomputing from the transcript with `TickEvents::between`.
+fn event_heads(transcript: &[[FpInput; 2]]) -> (Vec<[u8; 32]>, usize) {
+    let map = arena_map();
+    let mut live = create_initial_state(42, &map);
+    let mut live_chain = EventChain::new();
+    let mut replay = live.clone();
+    let mut replay_chain = EventChain::new();
+    let mut heads = Vec::new();
+    let mut deaths = 0;
+    for inputs in transcript {
+        let head = step_mut_chained(&mut live, inputs, &mut live_chain);
+        let next = step(&replay, inputs);
+        let events = TickEvents::between(&replay, &next);
+        deaths += events.kills.len as usize;
+        assert_eq!(replay_chain.update(next.tick, inputs, &events), head);
+        replay = next;
+        heads.push(head);
+        if live.match_over { break; }
+    }
+    (heads, deaths)
+}
+
+#[test]
+fn event_chain_recomputes_from_transcript() {
+    let transcript = duel_transcript(1800);
+    let (heads, deaths) = event_heads(&transcript);
+    assert!(deaths > 0, "duel should kill someone");
+
+    let input = FpProverInput { session_id: 1, seed: 42, mirrored: false, transcript: transcript.clone(), map: None, gen_seed: None, rules: None };
+    let mut chain = EventChain::new();
+    let streamed = run_streaming_with_chain(&encode_raw_input(&input), &FpMatchConfig::default(), &mut chain);
+    assert_eq!(chain.head(), *heads.last().unwrap());
+    assert_eq!(streamed.transcript_hash, run_streaming(&encode_raw_input(&input)).transcript_hash);
+
+    // One changed input changes that head and every head after it
+    let mut tampered = transcript;
+    tampered[100][1].buttons ^= button::JUMP;
+    let (tampered_heads, _) = event_heads(&tampered);
+    assert_eq!(tampered_heads[..100], heads[..100]);
+    for (a, b) in tampered_heads.iter().zip(&heads).skip(100) {
+        assert_ne!(a, b);
+    }
+}
+
+#[test]
+fn emotes_are_hashed_and_reported_but_never_played() {
+    let plain = duel_transcript(1800);
+    let mut emoting = plain.clone();
+    for (t, inputs) in emoting.iter_mut().enumerate() {
+        // Held and tapped, over the JUMP edges too
+        inputs[t % 2].buttons |= ((t / 7 % 4) as u8) << button::EMOTE_SHIFT;
+    }
+    let run = |transcript: &Vec<[FpInput; 2]>| {
+        let input = FpProverInput { session_id: 1, seed: 42, mirrored: false, transcript: transcript.clone(), map: None, gen_seed: None, rules: None };
+        run_streaming(&encode_raw_input(&input))
+    };
+    let (a, b) = (run(&plain), run(&emoting));
+    assert_ne!(a.transcript_hash, b.transcript_hash);
+    assert_ne!(hash_transcript(&plain), hash_transcript(&emoting));
+    assert!(a.state.match_over);
+    assert_eq!(hash_state(&a.state), hash_state(&b.state));
+    assert_eq!(encode_state(&a.state), encode_state(&b.state));
+
+    // The event chain takes the raw inputs, so it tells the two apart
+    let (plain_heads, _) = event_heads(&plain);
+    let (emoting_heads, _) = event_heads(&emoting);
+    assert_ne!(plain_heads.last(), emoting_heads.last());
+
+    let map = arena_map();
+    let mut state = create_initial_state(42, &map);
+    let wave = FpInput { buttons: button::RIGHT | (3 << button::EMOTE_SHIFT), aim_x: 1, aim_y: 0 };
+    let events = step_mut_events(&mut state, &[NULL_INPUT, wave]);
+    let emotes: Vec<EmoteEvent> = events.emotes.iter().copied().collect();
+    assert_eq!(emotes, [EmoteEvent { player: 1, emote: 3 }]);
+    assert_eq!(button::emote(wave.buttons), 3);
+    assert_eq!(state.prev_buttons[1], button::RIGHT);
+    assert_eq!(prev_buttons_mismatch(&state, &[NULL_INPUT, wave]), 0);
+}
+
+#[test]
+fn ghost_matches_full_replay_at_sampled_ticks() {
+    let map = arena_map();
+    let transcript = duel_transcript(1800);
+    for (player_idx, every) in [(0, 1), (1, 1), (0, 10), (1, 7)] {
+        let ghost = extract_ghost_every(42, &map, &transcript, player_idx, every);
+        let mut state = create_initial_state(42, &map);
+        let mut frames = ghost.iter();
+        assert_eq!(frames.next(), Some(&GhostFrame::of(&state, player_idx)));
+        for inputs in &transcript {
+            step_mut(&mut state, inputs);
+            if state.tick as usize % every == 0 || state.match_over {
+                let f = frames.next().unwrap();
+                let p = &state.players[player_idx];
+                assert_eq!((f.tick, f.x, f.y, f.weapon), (state.tick, p.x, p.y, p.weapon));
+                assert_eq!((f.facing as i32, f.alive), (p.facing, p.state_flags & flag::ALIVE != 0));
+            }
+            if state.match_over { break; }
+        }
+        assert!(frames.next().is_none());
+        assert!(state.match_over, "duel should end");
+        assert_eq!(ghost.last().unwrap().tick, state.tick);
+    }
+    assert_eq!(extract_ghost(42, &map, &transcript, 0), extract_ghost_every(42, &map, &transcript, 0, 1));
+}
+
+#[test]
+fn ghost_packs_round_trip() {
+    // P0 picks up weapons, P1 faces left and dies: every field off its default
+    let transcript = duel_transcript(1800);
+    let mut ghost = extract_ghost_every(42, &arena_map(), &transcript, 0, 4);
+    ghost.extend(extract_ghost_every(42, &arena_map(), &transcript, 1, 4));
+    assert!(ghost.iter().any(|f| f.facing < 0) && ghost.iter().any(|f| f.weapon != WEAPON_NONE));
+    assert!(ghost.iter().any(|f| !f.alive));
+
+    let bytes = encode_ghost(&ghost);
+    assert_eq!(bytes.len(), ghost.len() * GHOST_FRAME_BYTES);
+    assert!(decode_ghost(&bytes).eq(ghost.iter().copied()));
+    assert_eq!(decode_ghost(&bytes[..bytes.len() - 1]).count(), ghost.len() - 1);
+}
+
+/// 10,000 draws with the pistol at 96% land within a few standard deviations (~20 for the
+/// pistol, ~10 for the rest) of the expected counts; the same seed gives the same stream.
+#[test]
+fn weighted_respawn_histogram() {
+    let weights = [96, 1, 1, 1, 1];
+    let draw_all = |seed: u32| {
+        let mut rng = seed;
+        let mut counts = [0u32; 5];
+        for _ in 0..10_000 {
+            let (i, next) = prng_weighted_index(rng, &weights);
+            rng = next;
+            counts[i] += 1;
+        }
+        (counts, rng)
+    };
+    let (counts, end) = draw_all(12345);
+    assert!((9500..=9700).contains(&counts[0]), "{counts:?}");
+    for &c in &counts[1..] {
+        assert!((50..=150).contains(&c), "{counts:?}");
+    }
+    assert_eq!(draw_all(12345), (counts, end));
+
+    let map = arena_map();
+    let mut state = create_initial_state(3, &map);
+    state.weapon_pickups[0].respawn_timer = 1;
+    let rng = state.rng_state;
+    tick_pickup_timers(&mut state);
+    let (idx, next) = prng_weighted_index(rng, &WEAPON_RESPAWN_WEIGHTS);
+    assert_eq!((state.weapon_pickups[0].weapon, state.rng_state), (WEAPON_ROTATION[idx], next));
+}
+
+fn despawn_state(active_despawn: bool) -> State {
+    let map = arena_map();
+    create_initial_state_with(3, &map, &FpMatchConfig { active_despawn, ..FpMatchConfig::default() })
+}
+
+#[test]
+fn untouched_pickup_despawns_and_redraws_on_schedule() {
+    let mut state = despawn_state(true);
+    assert!(state.weapon_pickups[..NUM_WEAPON_SPAWNS].iter().all(|wp| wp.active_timer == ACTIVE_DESPAWN_TICKS));
+    for _ in 0..ACTIVE_DESPAWN_TICKS - 1 {
+        tick_pickup_timers(&mut state);
+    }
+    assert!(state.weapon_pickups[..NUM_WEAPON_SPAWNS].iter().all(|wp| wp.respawn_timer == 0 && wp.active_timer == 1));
+    tick_pickup_timers(&mut state);
+    assert!(state.weapon_pickups[..NUM_WEAPON_SPAWNS].iter().all(|wp| wp.respawn_timer == WEAPON_PICKUP_RESPAWN_TICKS));
+
+    // The normal respawn cycle: hidden, then one weighted draw per pickup in slot order
+    let mut rng = state.rng_state;
+    for _ in 0..WEAPON_PICKUP_RESPAWN_TICKS {
+        tick_pickup_timers(&mut state);
+    }
+    for wp in &state.weapon_pickups[..NUM_WEAPON_SPAWNS] {
+        let (idx, next) = prng_weighted_index(rng, &WEAPON_RESPAWN_WEIGHTS);
+        rng = next;
+        assert_eq!((wp.weapon, wp.respawn_timer, wp.active_timer), (WEAPON_ROTATION[idx], 0, ACTIVE_DESPAWN_TICKS));
+    }
+    assert_eq!(state.rng_state, rng);
+
+    // Without the ruleset nothing ever leaves
+    let mut off = despawn_state(false);
+    for _ in 0..2 * ACTIVE_DESPAWN_TICKS {
+        tick_pickup_timers(&mut off);
+    }
+    assert!(off.weapon_pickups.iter().all(|wp| wp.respawn_timer == 0 && wp.active_timer == 0));
+    assert_eq!(off.rng_state, 3);
+}
+
+#[test]
+fn collected_pickup_respawns_as_without_despawn() {
+    let run = |active_despawn: bool| {
+        let mut state = despawn_state(active_despawn);
+        for _ in 0..100 {
+            tick_pickup_timers(&mut state);
+        }
+        state.players[0].x = state.weapon_pickups[0].x - PLAYER_WIDTH / 2;
+        state.players[0].y = state.weapon_pickups[0].y - PLAYER_HEIGHT / 2;
+        resolve_weapon_pickups(&mut state, &mut ());
+        assert_eq!(state.weapon_pickups[0].respawn_timer, WEAPON_PICKUP_RESPAWN_TICKS);
+        assert_eq!(state.weapon_pickups[0].active_timer, 0);
+        for _ in 0..WEAPON_PICKUP_RESPAWN_TICKS {
+            tick_pickup_timers(&mut state);
+        }
+        state
+    };
+    let (on, off) = (run(true), run(false));
+    assert_eq!(on.players[0].weapon, off.players[0].weapon);
+    assert_eq!((on.weapon_pickups[0].weapon, on.rng_state), (off.weapon_pickups[0].weapon, off.rng_state));
+    assert_eq!(on.weapon_pickups[0].respawn_timer, 0);
+    // Back with a fresh countdown; the untouched pickups kept counting down
+    assert_eq!(on.weapon_pickups[0].active_timer, ACTIVE_DESPAWN_TICKS);
+    assert_eq!(on.weapon_pickups[1].active_timer, ACTIVE_DESPAWN_TICKS - 100 - WEAPON_PICKUP_RESPAWN_TICKS);
+}
+
+/// A rocket hitting player 1 with player 0 (its unprotected owner) in the blast: one direct
+/// record and one splash record, each with the health it actually took.
+#[test]
+fn rocket_direct_hit_and_splash_are_separate_damage_events() {
+    let map = arena_map();
+    let mut state = create_initial_state(42, &map);
+    state.pickup_count = 0;
+    for _ in 0..5 {
+        step_mut(&mut state, &[NULL_INPUT; 2]);
+    }
+    state.players[0].x = fp(400);
+    state.players[1].x = fp(440);
+    let target = state.players[1];
+    state.projectiles[0] = Projectile {
+        id: 0, owner_id: 0,
+        x: target.x - fp(4), y: target.y + PLAYER_HEIGHT / 2,
+        vx: fp(2), vy: 0, lifetime: 30, weapon: WEAPON_ROCKET,
+        owner_immune_until: 0,
+        bounces_left: 0,
+    };
+    state.proj_count = 1;
+    let mut plain = state.clone();
+    let health = [state.players[0].health, state.players[1].health];
+
+    let events = step_mut_events(&mut state, &[NULL_INPUT; 2]);
+    step_mut(&mut plain, &[NULL_INPUT; 2]);
+    assert_eq!(hash_state(&state), hash_state(&plain));
+
+    let damage: Vec<DamageEvent> = events.damage.iter().copied().collect();
+    assert_eq!(damage.len(), 2, "{damage:?}");
+    let (direct, splash) = (damage[0], damage[1]);
+    let rocket = fp_weapon_stats(WEAPON_ROCKET);
+    assert_eq!((direct.victim, direct.attacker, direct.source), (1, 0, damage_source::PROJECTILE));
+    assert_eq!((direct.weapon, direct.amount), (WEAPON_ROCKET, rocket.damage));
+    assert_eq!((splash.victim, splash.attacker, splash.source), (0, 0, damage_source::SPLASH));
+    let p0 = state.players[0];
+    assert_eq!((splash.x, splash.y), (p0.x + PLAYER_WIDTH / 2, p0.y + PLAYER_HEIGHT / 2));
+    let dist = (splash.x - direct.x).abs() + (splash.y - direct.y).abs();
+    let expected = rocket.splash_damage - rocket.splash_damage * dist / rocket.splash_radius;
+    assert!(expected > 0);
+    assert_eq!(splash.amount, expected);
+    assert_eq!(health[0] - p0.health, splash.amount);
+    assert_eq!(health[1] - state.players[1].health, direct.amount);
+    assert_eq!(events.kills.len, 0);
+}
+
+#[test]
+fn rocket_splash_kill_records_hit_and_kill() {
+    let map = arena_map();
+    let mut state = create_initial_state(42, &map);
+    state.pickup_count = 0;
+    for _ in 0..5 {
+        step_mut(&mut state, &[NULL_INPUT; 2]);
+    }
+    state.players[1].health = 1;
+    let target = state.players[1];
+    // Expires on its next move, at player 1's center: splash only, no direct hit
+    state.projectiles[0] = Projectile {
+        id: 0, owner_id: 0,
+        x: target.x + PLAYER_WIDTH / 2, y: target.y + PLAYER_HEIGHT / 2,
+        vx: 0, vy: 0, lifetime: 1, weapon: WEAPON_ROCKET,
+        owner_immune_until: 0,
+        bounces_left: 0,
+    };
+    state.proj_count = 1;
+    let mut plain = state.clone();
+
+    let events = step_mut_events(&mut state, &[NULL_INPUT; 2]);
+    step_mut(&mut plain, &[NULL_INPUT; 2]);
+    assert_eq!(hash_state(&state), hash_state(&plain));
+
+    let damage: Vec<DamageEvent> = events.damage.iter().copied().collect();
+    assert_eq!(damage.len(), 1, "{damage:?}");
+    assert_eq!((damage[0].victim, damage[0].attacker, damage[0].source), (1, 0, damage_source::SPLASH));
+    assert_eq!((damage[0].weapon, damage[0].amount), (WEAPON_ROCKET, 1));
+    assert_eq!(events.kills.iter().copied().collect::<Vec<_>>(), [(0, 1)]);
+    assert_eq!((events.shots.len, events.pickups.len, events.stomps.len), (0, 0, 0));
+}
+
+/// Both players standing still on the floor, no pickups, `weapon` fired by player 0 and
+/// about to reach player 1's center moving `(vx, vy)`.
+fn shot_at_player_1(weapon: i8, vx: Fp, vy: Fp) -> State {
+    let mut state = create_initial_state(42, &arena_map());
+    state.pickup_count = 0;
+    for _ in 0..5 {
+        step_mut(&mut state, &[NULL_INPUT; 2]);
+    }
+    state.players[0].x = fp(200);
+    state.players[1].x = fp(440);
+    let target = state.players[1];
+    state.projectiles[0] = Projectile {
+        id: 0, owner_id: 0,
+        x: target.x + PLAYER_WIDTH / 2 - vx, y: target.y + PLAYER_HEIGHT / 2 - vy,
+        vx, vy, lifetime: 30, weapon,
+        owner_immune_until: OWNER_IMMUNE_FOREVER,
+        bounces_left: 0,
+    };
+    state.proj_count = 1;
+    state
+}
+
+/// A sniper hit sets the idle victim moving at the weapon's knockback along the shot, then
+/// the usual deceleration bleeds it off: the exact per-tick x deltas the other sims match.
+#[test]
+fn hit_knockback_moves_victim_along_the_shot() {
+    let knockback = fp_weapon_stats(WEAPON_SNIPER).knockback;
+    assert_eq!(knockback, 1024);
+    let mut state = shot_at_player_1(WEAPON_SNIPER, fp(16), 0);
+    step_mut(&mut state, &[NULL_INPUT; 2]);
+    assert_eq!(state.proj_count, 0);
+    assert_eq!((state.players[1].vx, state.players[1].vy), (knockback, 0));
+
+    let mut deltas = Vec::new();
+    for _ in 0..8 {
+        let x = state.players[1].x;
+        step_mut(&mut state, &[NULL_INPUT; 2]);
+        deltas.push(state.players[1].x - x);
+    }
+    assert_eq!(deltas, [870, 716, 562, 408, 254, 100, 0, 0]);
+
+    // Shot leftwards: the mirror image
+    let mut left = shot_at_player_1(WEAPON_SNIPER, -fp(16), 0);
+    step_mut(&mut left, &[NULL_INPUT; 2]);
+    assert_eq!(left.players[1].vx, -knockback);
+
+    // A diagonal shot splits the push by Manhattan share, truncating toward zero
+    let mut diagonal = shot_at_player_1(WEAPON_PISTOL, fp(6), -fp(2));
+    step_mut(&mut diagonal, &[NULL_INPUT; 2]);
+    assert_eq!((diagonal.players[1].vx, diagonal.players[1].vy), (192, -64));
+}
+
+/// Rocket splash pushes bystanders away from the blast, scaled by the damage falloff;
+/// invincible players are neither damaged nor moved.
+#[test]
+fn splash_knockback_is_radial_with_falloff() {
+    let rocket = fp_weapon_stats(WEAPON_ROCKET);
+    let mut state = shot_at_player_1(WEAPON_ROCKET, fp(2), 0);
+    state.projectiles[0].owner_immune_until = 0;
+    state.players[0].x = fp(410);
+    let blast_x = state.projectiles[0].x + fp(2);
+    let dist = (blast_x - (state.players[0].x + PLAYER_WIDTH / 2)).abs();
+    let mut shielded = state.clone();
+    grant_invincibility(&mut shielded.players[0], invincibility::RESPAWN, 60);
+
+    step_mut(&mut state, &[NULL_INPUT; 2]);
+    let push = rocket.knockback - rocket.knockback * dist / rocket.splash_radius;
+    assert!(push > 0 && push < rocket.knockback);
+    assert_eq!((state.players[0].vx, state.players[0].vy), (-push, 0));
+    assert_eq!(state.players[1].vx, rocket.knockback);
+
+    step_mut(&mut shielded, &[NULL_INPUT; 2]);
+    assert_eq!((shielded.players[0].vx, shielded.players[0].health), (0, MAX_HEALTH));
+}
+
+/// Knockback on top of existing speed stops at KNOCKBACK_MAX_SPEED on each axis.
+#[test]
+fn knockback_is_clamped() {
+    let mut state = shot_at_player_1(WEAPON_ROCKET, fp(2), -fp(2));
+    state.projectiles[0].owner_immune_until = OWNER_IMMUNE_FOREVER;
+    state.players[1].vx = fp(11);
+    state.players[1].vy = -fp(11);
+    let before = state.players[1];
+    step_mut(&mut state, &[NULL_INPUT; 2]);
+    let p = state.players[1];
+    assert!(p.health < before.health);
+    assert_eq!((p.vx, p.vy), (KNOCKBACK_MAX_SPEED, -KNOCKBACK_MAX_SPEED));
+
+    let x = p.x;
+    step_mut(&mut state, &[NULL_INPUT; 2]);
+    assert!(state.players[1].x - x <= KNOCKBACK_MAX_SPEED);
+}
+
+/// A hit that knocks a stomp mount back throws the rider off, with the usual cooldown.
+#[test]
+fn knockback_throws_the_stomp_rider() {
+    let map = arena_map();
+    let mut state = riding_state(&map, fp(400), fp(480));
+    state.pickup_count = 0;
+    let mount = state.players[1];
+    state.projectiles[0] = Projectile {
+        id: 0, owner_id: 0,
+        x: mount.x + PLAYER_WIDTH / 2 - fp(8), y: mount.y + PLAYER_HEIGHT / 2,
+        vx: fp(8), vy: 0, lifetime: 30, weapon: WEAPON_PISTOL,
+        owner_immune_until: OWNER_IMMUNE_FOREVER,
+        bounces_left: 0,
+    };
+    state.proj_count = 1;
+
+    let events = step_mut_events(&mut state, &[NULL_INPUT; 2]);
+    assert_eq!(state.proj_count, 0);
+    assert_eq!(state.players[1].vx, PLAYER_SPEED + fp_weapon_stats(WEAPON_PISTOL).knockback);
+    assert_eq!((state.players[0].stomping_on, state.players[1].stomped_by), (-1, -1));
+    assert_eq!(state.players[1].stomp_cooldown, STOMP_COOLDOWN_TICKS);
+    let stomps: Vec<StompEvent> = events.stomps.iter().copied().collect();
+    assert_eq!(stomps, [StompEvent { kind: stomp_event::KNOCKED_OFF, rider: 0, victim: 1 }]);
+}
+
+/// A grenade lobbed up and to the right gains `gravity` of vy every tick at constant vx,
+/// and the same inputs give the same arc.
+#[test]
+fn grenade_shot_arcs_under_gravity() {
+    let map = arena_map();
+    let mut state = create_initial_state(42, &map);
+    state.pickup_count = 0;
+    for _ in 0..5 {
+        step_mut(&mut state, &[NULL_INPUT; 2]);
+    }
+    // On the middle platform: open sky up and to the right
+    state.players[0].x = map.spawns[2].x;
+    state.players[0].y = map.spawns[2].y;
+    state.players[0].weapon = WEAPON_GRENADE;
+    state.players[0].ammo = 1;
+    let throw = FpInput { buttons: button::SHOOT, aim_x: 127, aim_y: -127 };
+    let flight = |mut s: State| {
+        step_mut(&mut s, &[throw, NULL_INPUT]);
+        let mut path = Vec::new();
+        while s.proj_count == 1 && s.projectiles[0].bounces_left == 1 {
+            let p = s.projectiles[0];
+            path.push((p.x, p.y, p.vx, p.vy));
+            step_mut(&mut s, &[NULL_INPUT; 2]);
+        }
+        (path, hash_state(&s))
+    };
+
+    let (path, hash) = flight(state.clone());
+    assert_eq!(flight(state), (path.clone(), hash));
+    assert!(path.len() > 10, "{}", path.len());
+    let gravity = fp_weapon_stats(WEAPON_GRENADE).gravity;
+    for w in path.windows(2) {
+        let ((x0, y0, vx0, vy0), (x1, y1, vx1, vy1)) = (w[0], w[1]);
+        assert_eq!((vx1, vy1), (vx0, vy0 + gravity));
+        assert_eq!((x1, y1), (x0 + vx1, y0 + vy1));
+    }
+    // Rises, then comes back down
+    assert!(path[0].3 < 0 && path[path.len() - 1].3 > 0);
+}
+
+/// Dropped straight down: the first solid contact bounces it back up from where it was,
+/// at half speed; the second one destroys it.
+#[test]
+fn grenade_bounces_once_then_dies_on_contact() {
+    let map = arena_map();
+    let gravity = fp_weapon_stats(WEAPON_GRENADE).gravity;
+    let mut proj = Projectile {
+        id: 0, owner_id: 0, x: map.width / 2, y: fp(40), vx: 0, vy: 0,
+        lifetime: 10_000, weapon: WEAPON_GRENADE,
+        owner_immune_until: OWNER_IMMUNE_FOREVER,
+        bounces_left: fp_weapon_stats(WEAPON_GRENADE).bounces,
+    };
+    let mut bounces = 0;
+    loop {
+        let before = proj;
+        if !advance_projectile(&mut proj, &map) { break; }
+        if proj.bounces_left == before.bounces_left { continue; }
+        bounces += 1;
+        assert_eq!((proj.x, proj.y), (before.x, before.y));
+        assert_eq!((proj.vx, proj.vy), (0, -(before.vy + gravity) / 2));
+        assert!(proj.vy < 0);
+    }
+    assert_eq!((bounces, proj.bounces_left), (1, 0));
+    assert!(proj.lifetime > 0 && hits_solid(&proj, &map));
+}
+
+/// A grenade expiring at player 1's center: splash only, the kill credited to the
+/// thrower, and the damage tagged with the grenade.
+#[test]
+fn grenade_expiry_splash_kill_credits_thrower() {
+    let map = arena_map();
+    let mut state = create_initial_state(42, &map);
+    state.pickup_count = 0;
+    for _ in 0..5 {
+        step_mut(&mut state, &[NULL_INPUT; 2]);
+    }
+    state.players[1].health = 1;
+    let target = state.players[1];
+    state.projectiles[0] = Projectile {
+        id: 0, owner_id: 0,
+        x: target.x + PLAYER_WIDTH / 2, y: target.y + PLAYER_HEIGHT / 2,
+        vx: 0, vy: 0, lifetime: 1, weapon: WEAPON_GRENADE,
+        owner_immune_until: OWNER_IMMUNE_FOREVER,
+        bounces_left: 1,
+    };
+    state.proj_count = 1;
+    let lives = target.lives;
+
+    let events = step_mut_events(&mut state, &[NULL_INPUT; 2]);
+    let damage: Vec<DamageEvent> = events.damage.iter().copied().collect();
+    assert_eq!(damage.len(), 1, "{damage:?}");
+    assert_eq!((damage[0].victim, damage[0].attacker, damage[0].source), (1, 0, damage_source::SPLASH));
+    assert_eq!((damage[0].weapon, damage[0].amount), (WEAPON_GRENADE, 1));
+    assert_eq!(events.kills.iter().copied().collect::<Vec<_>>(), [(0, 1)]);
+    assert_eq!((state.proj_count, state.players[1].lives), (0, lives - 1));
+}
+
+#[test]
+fn shots_and_pickups_are_recorded() {
+    let map = arena_map();
+    let mut state = create_initial_state(42, &map);
+    state.pickup_count = 0;
+    for _ in 0..5 {
+        step_mut(&mut state, &[NULL_INPUT; 2]);
+    }
+    state.players[0].weapon = WEAPON_SHOTGUN;
+    state.players[0].ammo = 3;
+    // Player 1 grabs a sniper and fires it in the same tick
+    let p1 = state.players[1];
+    state.weapon_pickups[0] = WeaponPickup {
+        id: 7, x: p1.x + PLAYER_WIDTH / 2, y: p1.y + PLAYER_HEIGHT / 2,
+        weapon: WEAPON_SNIPER, respawn_timer: 0, active_timer: 0, dropped_ammo: 0,
+    };
+    state.pickup_count = 1;
+    let shoot = FpInput { buttons: button::SHOOT, aim_x: 0, aim_y: 0 };
+    let mut plain = state.clone();
+
+    let events = step_mut_events(&mut state, &[shoot, shoot]);
+    step_mut(&mut plain, &[shoot, shoot]);
+    assert_eq!(hash_state(&state), hash_state(&plain));
+
+    let pickups: Vec<PickupEvent> = events.pickups.iter().copied().collect();
+    assert_eq!(pickups, [PickupEvent {
+        player: 1, pickup_id: 7, weapon: WEAPON_SNIPER,
+        x: p1.x + PLAYER_WIDTH / 2, y: p1.y + PLAYER_HEIGHT / 2,
+    }]);
+    let shots: Vec<ShotEvent> = events.shots.iter().copied().collect();
+    assert_eq!(shots.len(), 2, "{shots:?}");
+    assert_eq!((shots[0].shooter, shots[0].weapon), (0, WEAPON_SHOTGUN));
+    assert_eq!(shots[0].pellets as i32, fp_weapon_stats(WEAPON_SHOTGUN).pellets);
+    assert_eq!((shots[1].shooter, shots[1].weapon, shots[1].pellets), (1, WEAPON_SNIPER, 1));
+    let sniper = state.projectiles[state.proj_count as usize - 1];
+    assert_eq!((shots[1].x, shots[1].y), (sniper.x - sniper.vx, sniper.y - sniper.vy));
+}
+
+/// Player 1 at `health` in front of a pistol shot from player 0, under `lives`.
+fn pistol_shot_state(lives: i32, health: i32) -> State {
+    let map = arena_map();
+    let mut state = create_initial_state_cfg(42, &map, lives, MATCH_DURATION_TICKS, SUDDEN_DEATH_START_TICK);
+    state.pickup_count = 0;
+    for _ in 0..5 {
+        step_mut(&mut state, &[NULL_INPUT; 2]);
+    }
+    state.players[1].health = health;
+    let target = state.players[1];
+    state.projectiles[0] = Projectile {
+        id: 0, owner_id: 0,
+        x: target.x - fp(4), y: target.y + PLAYER_HEIGHT / 2,
+        vx: fp(2), vy: 0, lifetime: 30, weapon: WEAPON_PISTOL,
+        owner_immune_until: OWNER_IMMUNE_FOREVER,
+        bounces_left: 0,
+    };
+    state.proj_count = 1;
+    state
+}
+
+#[test]
+fn final_blow_only_for_match_deciding_kills() {
+    // Last life: the kill starts the full linger and is flagged final through match_over
+    let mut state = pistol_shot_state(1, 10);
+    step_mut(&mut state, &[NULL_INPUT; 2]);
+    assert_eq!(state.players[1].lives, 0);
+    assert_eq!((state.final_blow, state.death_linger_timer, state.winner), (true, DEATH_LINGER_TICKS, 0));
+    for left in (0..DEATH_LINGER_TICKS).rev() {
+        step_mut(&mut state, &[NULL_INPUT; 2]);
+        assert_eq!(state.death_linger_timer, left);
+    }
+    assert!(state.match_over && state.final_blow);
+
+    // A life to spare: same kill, no linger, not final
+    let mut state = pistol_shot_state(2, 10);
+    step_mut(&mut state, &[NULL_INPUT; 2]);
+    assert_eq!(state.players[1].lives, 1);
+    assert_eq!((state.final_blow, state.death_linger_timer), (false, 0));
+
+    // Non-lethal hit, then time-up: match over without a final blow
+    let map = arena_map();
+    let mut state = pistol_shot_state(1, 50);
+    step_mut(&mut state, &[NULL_INPUT; 2]);
+    assert!(state.players[1].health > 0);
+    state.cfg_match_duration = state.tick + 1;
+    step_mut(&mut state, &[NULL_INPUT; 2]);
+    assert!(state.match_over);
+    assert!(!state.final_blow);
+    reset_state(&mut state, 42, &map, &FpMatchConfig::default());
+    assert!(!state.final_blow);
+}
+
+/// Winner at time-up of a fresh match with these (lives, score, health) per player.
+fn time_up_winner(standing: [(i32, u32, i32); 2]) -> i32 {
+    let mut state = create_initial_state(42, &arena_map());
+    for (i, (lives, score, health)) in standing.into_iter().enumerate() {
+        state.players[i].lives = lives;
+        state.score[i] = score;
+        state.players[i].health = health;
+    }
+    state.cfg_match_duration = state.tick + 1;
+    step_mut(&mut state, &[NULL_INPUT; 2]);
+    assert!(state.match_over);
+    state.winner
+}
+
+#[test]
+fn time_up_tiebreak_order() {
+    // Lives first, whatever the score and health
+    assert_eq!(time_up_winner([(1, 5, 100), (2, 0, 10)]), 1);
+    // Equal lives: the higher score beats the higher health
+    assert_eq!(time_up_winner([(2, 3, 10), (2, 1, 100)]), 0);
+    assert_eq!(time_up_winner([(2, 1, 100), (2, 3, 10)]), 1);
+    // Equal lives and score: health
+    assert_eq!(time_up_winner([(2, 2, 40), (2, 2, 90)]), 1);
+    // Full tie: player 0
+    assert_eq!(time_up_winner([(2, 2, 90), (2, 2, 90)]), 0);
+}
+
+/// Once the result is decided only the winner moves: the loser's inputs during linger and
+/// after `match_over` (here a loser still alive at time-up) never reach the state.
+#[test]
+fn post_match_ignores_loser_inputs() {
+    let map = arena_map();
+    let mut lingering = create_initial_state(42, &map);
+    lingering.players[1].lives = 0;
+    lingering.players[1].state_flags = 0;
+    lingering.death_linger_timer = DEATH_LINGER_TICKS;
+    lingering.winner = 0;
+    let mut over = create_initial_state(42, &map);
+    over.match_over = true;
+    over.winner = 1;
+
+    let mash = FpInput { buttons: button::LEFT | button::JUMP | button::SHOOT, aim_x: -1, aim_y: 1 };
+    for (start, loser) in [(lingering, 1), (over, 0)] {
+        let run = |loser_input: FpInput, winner_input: FpInput| {
+            let mut s = start.clone();
+            let mut inputs = [NULL_INPUT; 2];
+            inputs[loser] = loser_input;
+            inputs[1 - loser] = winner_input;
+            for _ in 0..30 {
+                step_mut(&mut s, &inputs);
+            }
+            hash_state(&s)
+        };
+        let baseline = run(NULL_INPUT, NULL_INPUT);
+        assert_eq!(run(mash, NULL_INPUT), baseline);
+        assert_ne!(run(NULL_INPUT, mash), baseline);
+    }
+}
+
+#[test]
+fn side_swap_from_seed_maps_journal_to_registered_order() {
+    let seed_for = |swap: bool| (0u32..).find(|&s| side_swap_for_seed(s) == swap).unwrap();
+    for swap in [false, true] {
+        let seed = seed_for(swap);
+        let map = arena_map();
+        let initial = create_initial_state(seed, &map);
+        assert_eq!(initial.side_swap, swap);
+        let mut reset = create_initial_state(seed ^ 1, &map);
+        reset_state(&mut reset, seed, &map, &FpMatchConfig::default());
+        assert_eq!(reset.side_swap, swap);
+
+        // Carried through encoding and bound by the state hash
+        let decoded = decode_state(&encode_state(&initial));
+        assert_eq!(decoded.side_swap, swap);
+        let mut flipped = initial.clone();
+        flipped.side_swap = !swap;
+        assert_ne!(hash_state(&flipped), hash_state(&initial));
+
+        // Internal index 0 still gets the left spawn; only the journal mapping changes
+        assert!(initial.players[0].x < initial.players[1].x);
+        let input = FpProverInput { session_id: 3, seed, mirrored: false, transcript: duel_transcript(1800), map: None, gen_seed: None, rules: None };
+        let state = run_streaming(&encode_raw_input(&input)).state;
+        assert!(state.winner == 0 || state.winner == 1);
+        let expected_winner = if swap { 1 - state.winner } else { state.winner };
+        assert_eq!(state.registered_winner(), expected_winner);
+        let mut expected_scores = state.score;
+        if swap { expected_scores.swap(0, 1); }
+        assert_eq!(state.registered_scores(), expected_scores);
+
+        let output = crate::ProverOutput {
+            winner: state.registered_winner(),
+            scores: state.registered_scores(),
+            transcript_hash: [0; 32],
+            seed_commit: hash_seed(seed),
+            session_id: 3,
+            config_commit: FpMatchConfig::default().commitment(),
+            env_kills: state.registered_env_kills(),
+            player_count: 2,
+            map_hash: map_hash(&state.map),
+            final_tick: state.tick as u32,
+            final_health: state.registered_health(),
+            final_lives: state.registered_lives(),
+            winner_team: state.registered_teams().winner_team,
+            team_scores: state.registered_teams().team_scores,
+            player_teams: state.registered_teams().player_teams,
+            pickup_stats_commit: state.pickup_stats_commit(),
+        };
+        let journal = crate::ProverOutput::from_journal_bytes(&output.to_journal_bytes());
+        assert_eq!((journal.winner, journal.scores), (expected_winner, expected_scores));
+        // Teams follow the registered players, whichever side the seed put them on
+        assert_eq!((journal.winner_team, journal.team_scores), (expected_winner, [expected_scores[0], expected_scores[1]]));
+        assert_eq!(journal.player_teams, [0, 1, 0, 0]);
+    }
+    assert_eq!(side_index(true, -1), -1);
+    assert_eq!(side_index(true, side_index(true, 0)), 0);
+}
+
+/// Each zone mode halfway closed: a player whose center it has passed takes the burst, one in the
+/// safe box doesn't, even where another mode would have closed in.
+/// A full match recorded in registered order, as the client records it: registered player 0
+/// holds the middle of the arena and registered player 1 runs for its wall, where the zone
+/// closes first. Whichever side the seed hands registered player 0, it wins, from the replayed
+/// raw transcript through to the journal.
+#[test]
+fn registered_player_0_wins_under_either_side_swap() {
+    let map = arena_map();
+    let middle = map.width / 2;
+    for swap in [false, true] {
+        let seed = (0u32..).find(|&s| side_swap_for_seed(s) == swap).unwrap();
+        let mut state = create_initial_state(seed, &map);
+        let mut transcript = Vec::new();
+        while !state.match_over {
+            let tick = [0, 1].map(|registered| {
+                let p = &state.players[side_index(swap, registered) as usize];
+                let x = p.x + PLAYER_WIDTH / 2;
+                let buttons = match registered {
+                    0 if x < middle - PLAYER_WIDTH => button::RIGHT,
+                    0 if x > middle + PLAYER_WIDTH => button::LEFT,
+                    0 => 0,
+                    _ if x < middle => button::LEFT,
+                    _ => button::RIGHT,
+                };
+                FpInput { buttons, aim_x: 0, aim_y: 0 }
+            });
+            step_mut(&mut state, &tick);
+            transcript.push(tick);
+        }
+
+        let input = FpProverInput { session_id: 3, seed, mirrored: false, transcript, map: None, gen_seed: None, rules: None };
+        let replayed = run_streaming(&encode_raw_input(&input)).state;
+        assert_eq!(hash_state(&replayed), hash_state(&state));
+        assert_eq!(replayed.side_swap, swap);
+        let teams = replayed.registered_teams();
+        let output = crate::ProverOutput {
+            winner: replayed.registered_winner(),
+            scores: replayed.registered_scores(),
+            transcript_hash: hash_transcript(&input.transcript),
+            seed_commit: hash_seed(seed),
+            session_id: 3,
+            config_commit: FpMatchConfig::default().commitment(),
+            env_kills: replayed.registered_env_kills(),
+            player_count: 2,
+            map_hash: map_hash(&replayed.map),
+            final_tick: replayed.tick as u32,
+            final_health: replayed.registered_health(),
+            final_lives: replayed.registered_lives(),
+            winner_team: teams.winner_team,
+            team_scores: teams.team_scores,
+            player_teams: teams.player_teams,
+            pickup_stats_commit: replayed.pickup_stats_commit(),
+        };
+        let journal = crate::ProverOutput::from_journal_bytes(&output.to_journal_bytes());
+        assert_eq!(journal.winner, 0, "swap {swap}");
+        assert_eq!(journal.final_lives[1], 0, "swap {swap}");
+        assert!(journal.final_lives[0] > 0, "swap {swap}");
+    }
+}
+
+#[test]
+fn zone_modes_damage_only_the_closed_region() {
+    let map = arena_map();
+    let (w, h) = (map.width, map.height);
+    // Mode, then centers in its zone paired with centers out of it
+    let cases = [
+        (zone_mode::PINCH, [(w / 8, h / 2), (w - w / 8, h / 2)], [(w / 2, h / 2), (w / 2, h / 8)]),
+        (zone_mode::DESCENT, [(w / 2, h / 8), (w / 16, h / 3)], [(w / 2, h - h / 8), (w / 16, h - h / 8)]),
+        (zone_mode::BOX, [(w / 2, h / 8), (w / 8, h / 2)], [(w / 2, h / 2), (w / 3, h - h / 3)]),
+    ];
+    for (mode, closed, safe) in cases {
+        let cfg = FpMatchConfig { sudden_death: 0, zone_mode: mode, ..FpMatchConfig::default() };
+        for (inside, outside) in closed.into_iter().zip(safe) {
+            let mut state = create_initial_state_with(1, &map, &cfg);
+            state.tick = SUDDEN_DEATH_DURATION / 2;
+            for (p, (cx, cy)) in state.players.iter_mut().zip([inside, outside]) {
+                (p.x, p.y) = (cx - PLAYER_WIDTH / 2, cy - PLAYER_HEIGHT / 2);
+            }
+            apply_zone(&mut state, &map, &mut ());
+            assert_eq!(state.arena_bounds(), zone_bounds(state.tick, 0, mode, w, h));
+            // The burst at half close, as in every mode
+            assert_eq!(state.players[0].health, MAX_HEALTH - 1, "mode {mode}: {inside:?} spared");
+            assert_eq!(state.players[1].health, MAX_HEALTH, "mode {mode}: {outside:?} hurt");
+            let decoded = decode_state(&encode_state(&state));
+            assert_eq!((decoded.cfg_zone_mode, decoded.arena_bounds()), (mode, state.arena_bounds()));
+        }
+    }
+}
+
+/// The default zone is the pinch every match played before the modes: the side span it always
+/// had (the storm goldens' v1-v3 digests, which don't carry the vertical bounds, are unchanged
+/// too), open top to bottom, and a config commitment that doesn't mention it.
+#[test]
+fn default_zone_mode_is_the_original_pinch() {
+    let map = arena_map();
+    let (w, h) = (map.width, map.height);
+    let cfg = FpMatchConfig::default();
+    assert_eq!(cfg.zone_mode, zone_mode::PINCH);
+    let at = |tick| zone_bounds(tick, SUDDEN_DEATH_START_TICK, cfg.zone_mode, w, h);
+    assert_eq!(at(SUDDEN_DEATH_START_TICK - 1), ZoneBounds::open(w, h));
+    let half = SUDDEN_DEATH_START_TICK + SUDDEN_DEATH_DURATION / 2;
+    assert_eq!(at(half), ZoneBounds { left: w / 4, right: w - w / 4, top: 0, bottom: h });
+    assert_eq!(at(i32::MAX), ZoneBounds { left: w / 2, right: w / 2, top: 0, bottom: h });
+    for tick in 0..2 * MATCH_DURATION_TICKS {
+        let b = at(tick);
+        assert_eq!((b.left + b.right, b.top, b.bottom), (w, 0, h), "tick {tick}");
+    }
+
+    let mut old = Sha256::new();
+    old.update(cfg.initial_lives.to_le_bytes());
+    old.update(cfg.match_duration.to_le_bytes());
+    old.update(cfg.sudden_death.to_le_bytes());
+    old.update(cfg.lag_ticks);
+    old.update([cfg.mirrored as u8, cfg.stamina as u8, cfg.active_despawn as u8]);
+    assert_eq!(cfg.commitment(), <[u8; 32]>::from(old.finalize()));
+    assert_ne!(FpMatchConfig { zone_mode: zone_mode::DESCENT, ..cfg }.commitment(), cfg.commitment());
+}
+
+/// Nobody moves, so the closing zone decides the match: player 1's spawn is reached first, and
+/// that death is an env kill rather than nobody's.
+#[test]
+fn idle_match_into_full_zone_closure_commits_env_kills() {
+    let input = FpProverInput {
+        session_id: 3,
+        seed: 42,
+        mirrored: false,
+        transcript: vec![[NULL_INPUT; 2]; MATCH_DURATION_TICKS as usize],
+        map: None,
+        gen_seed: None,
+        rules: None,
+    };
+    let state = run_streaming(&encode_raw_input(&input)).state;
+    assert!(state.tick > SUDDEN_DEATH_START_TICK + SUDDEN_DEATH_DURATION);
+    assert_eq!(state.score, [0, 0, 0, 0]);
+    assert_eq!(state.env_kills, [0, 1, 0, 0]);
+    assert!(state.final_blow);
+    assert_eq!(state.winner, 0);
+
+    let output = crate::ProverOutput {
+        winner: state.registered_winner(),
+        scores: state.registered_scores(),
+        transcript_hash: hash_transcript(&input.transcript),
+        seed_commit: hash_seed(42),
+        session_id: 3,
+        config_commit: FpMatchConfig::default().commitment(),
+        env_kills: state.registered_env_kills(),
+        player_count: 2,
+        map_hash: map_hash(&state.map),
+        final_tick: state.tick as u32,
+        final_health: state.registered_health(),
+        final_lives: state.registered_lives(),
+        winner_team: state.registered_teams().winner_team,
+        team_scores: state.registered_teams().team_scores,
+        player_teams: state.registered_teams().player_teams,
+        pickup_stats_commit: state.pickup_stats_commit(),
+    };
+    let journal = crate::ProverOutput::from_journal_bytes(&output.to_journal_bytes());
+    assert_eq!(journal.scores, [0, 0, 0, 0]);
+    // The zone took player 1's last life; the winner ends with whatever it had left
+    assert_eq!(journal.final_tick, state.tick as u32);
+    assert_eq!(journal.final_lives[side_index(state.side_swap, 1) as usize], 0);
+    assert!(journal.final_lives[side_index(state.side_swap, 0) as usize] > 0);
+    assert_eq!(journal.env_kills, registered_scores(state.side_swap, [0, 1, 0, 0]));
+    assert_eq!(journal, output);
+}
+
+/// Player 1 at the left wall of a fully closed pinch, a zone burst from death, and player 0 safe
+/// in the middle; the zone is due to hit on the state's tick.
+fn one_burst_from_the_zone() -> (State, Map) {
+    let map = arena_map();
+    let cfg = FpMatchConfig { sudden_death: 0, ..FpMatchConfig::default() };
+    let mut state = create_initial_state_with(1, &map, &cfg);
+    state.tick = SUDDEN_DEATH_DURATION;
+    state.players[0].x = map.width / 2 - PLAYER_WIDTH / 2;
+    state.players[1].x = 0;
+    state.players[1].health = 1;
+    (state, map)
+}
+
+#[test]
+fn hits_mark_the_attacker_but_own_splash_does_not() {
+    let state = point_blank_sniper(fp(4), 1);
+    assert!(state.players[1].health > 0);
+    assert_eq!((state.players[1].last_damaged_by, state.players[1].last_damage_tick), (0, state.tick));
+    assert_eq!(state.players[0].last_damaged_by, -1);
+
+    // Player 1's own rocket splashes it: still player 0's to credit, from the sniper's tick
+    let mut state = state;
+    let hit_tick = state.tick;
+    let target = state.players[1];
+    state.projectiles[0] = Projectile {
+        id: 9, owner_id: 1,
+        x: target.x + PLAYER_WIDTH / 2, y: target.y + PLAYER_HEIGHT / 2,
+        vx: 0, vy: 0, lifetime: 1, weapon: WEAPON_ROCKET,
+        owner_immune_until: 0,
+        bounces_left: 0,
+    };
+    state.proj_count = 1;
+    state.next_proj_id = 10;
+    step_mut(&mut state, &[NULL_INPUT; 2]);
+    assert!(state.players[1].health < target.health);
+    assert_eq!((state.players[1].last_damaged_by, state.players[1].last_damage_tick), (0, hit_tick));
+}
+
+/// A zone death scores for the opponent who hurt the player up to `ZONE_KILL_CREDIT_TICKS`
+/// before, and is an env kill with nobody to credit or a tick later.
+#[test]
+fn zone_death_scores_for_a_recent_attacker() {
+    let credit_from = SUDDEN_DEATH_DURATION - ZONE_KILL_CREDIT_TICKS;
+    let cases = [
+        (None, false),
+        (Some(SUDDEN_DEATH_DURATION), true),
+        (Some(credit_from), true),
+        (Some(credit_from - 1), false),
+    ];
+    for (damaged_at, credited) in cases {
+        let (mut state, map) = one_burst_from_the_zone();
+        if let Some(tick) = damaged_at {
+            mark_damaged_by(&mut state.players[1], 0, tick);
+        }
+        let before = state.clone();
+        let decoded = decode_state(&encode_state(&state));
+        assert_eq!(hash_state(&decoded), hash_state(&state));
+        assert_eq!(decoded.players[1].last_damaged_by, state.players[1].last_damaged_by);
+
+        apply_zone(&mut state, &map, &mut ());
+        assert_eq!(state.players[1].lives, INITIAL_LIVES - 1, "{damaged_at:?}");
+        assert_eq!(state.winner, 0);
+        let (score, env_kills) = if credited { ([1, 0, 0, 0], [0; 4]) } else { ([0; 4], [0, 1, 0, 0]) };
+        assert_eq!((state.score, state.env_kills), (score, env_kills), "{damaged_at:?}");
+        let killer = if credited { 0 } else { -1 };
+        assert_eq!(TickEvents::between(&before, &state).kills.iter().copied().collect::<Vec<_>>(), [(killer, 1)]);
+    }
+}
+
+/// Both players die in the same burst: the tiebreak goes to the one with the credited kill,
+/// not to player 0 for its index.
+#[test]
+fn double_zone_death_goes_to_the_credited_kill() {
+    let (mut state, map) = one_burst_from_the_zone();
+    state.players[0].x = map.width - PLAYER_WIDTH;
+    state.players[0].health = 1;
+    mark_damaged_by(&mut state.players[0], 1, state.tick - 30);
+    apply_zone(&mut state, &map, &mut ());
+    assert_eq!(state.players.map(|p| p.state_flags & flag::ALIVE)[..2], [0, 0]);
+    assert_eq!((state.score, state.env_kills), ([0, 1, 0, 0], [0, 1, 0, 0]));
+    assert_eq!(state.winner, 1);
+}
+
+#[test]
+fn validate_rejects_impossible_zone_kill_credit() {
+    let map = arena_map();
+    let mut state = create_initial_state(42, &map);
+    state.tick = 50;
+    state.players[0].last_damaged_by = 0;
+    state.players[0].last_damage_tick = 10;
+    mark_damaged_by(&mut state.players[1], 0, 51);
+    let issues = validate_state(&state).unwrap_err();
+    assert_eq!(issues, [
+        StateIssue::LastDamage { player: 0, by: 0, tick: 10 },
+        StateIssue::LastDamage { player: 1, by: 0, tick: 51 },
+    ]);
+    assert_eq!(sanitize_state(&mut state), issues);
+    assert_eq!(state.players.map(|p| (p.last_damaged_by, p.last_damage_tick))[..2], [(-1, 0), (-1, 0)]);
+}
+
+/// Place a projectile 4px left of player 1's hitbox, vertically centered.
+fn near_miss_hit(weapon: i8) -> bool {
+    let map = arena_map();
+    let mut state = create_initial_state(42, &map);
+    state.players[1].state_flags = flag::ALIVE;
+    let target = state.players[1];
+    state.projectiles[0] = Projectile {
+        id: 0,
+        owner_id: 0,
+        x: target.x - fp(4),
+        y: target.y + PLAYER_HEIGHT / 2,
+        vx: 0,
+        vy: 0,
+        lifetime: 10,
+        weapon,
+        owner_immune_until: OWNER_IMMUNE_FOREVER,
+        bounces_left: 0,
+    };
+    state.proj_count = 1;
+    let hp = state.players[1].health;
+    resolve_hits_mut(&mut state, 0, &mut KillList::new(), &mut ());
+    state.players[1].health < hp
+}
+
+/// Player 0 fires the sniper (16 px/tick, a point shot) into player 1 standing `dx` ahead
+/// of it, overlapping: the shot spawns inside player 1, 4 px short of its far edge.
+fn point_blank_sniper(dx: Fp, aim_x: i8) -> State {
+    let mut state = create_initial_state(42, &arena_map());
+    state.pickup_count = 0;
+    for _ in 0..5 {
+        step_mut(&mut state, &[NULL_INPUT; 2]);
+    }
+    state.players[0].x = fp(300);
+    state.players[1].x = fp(300) + dx;
+    state.players[1].state_flags = flag::ALIVE;
+    state.players[0].weapon = WEAPON_SNIPER;
+    state.players[0].ammo = 3;
+    let shoot = FpInput { buttons: button::SHOOT, aim_x, aim_y: 0 };
+    step_mut(&mut state, &[shoot, NULL_INPUT]);
+    state
+}
+
+/// Resolved where it spawned (as the TS sim, which doesn't move a shot on its spawn tick),
+/// not after its first move, which would carry it 12 px past player 1.
+#[test]
+fn point_blank_shot_hits_at_spawn_not_after_first_move_facing_right() {
+    let state = point_blank_sniper(fp(4), 1);
+    assert_eq!(state.players[1].health, MAX_HEALTH - fp_weapon_stats(WEAPON_SNIPER).damage);
+    assert_eq!(state.proj_count, 0);
+}
+
+#[test]
+fn point_blank_shot_hits_at_spawn_not_after_first_move_facing_left() {
+    let state = point_blank_sniper(-fp(4), -1);
+    assert_eq!(state.players[1].health, MAX_HEALTH - fp_weapon_stats(WEAPON_SNIPER).damage);
+    assert_eq!(state.proj_count, 0);
+}
+
+#[test]
+fn shot_spawning_clear_of_players_moves_on_its_spawn_tick() {
+    let state = point_blank_sniper(fp(100), 1);
+    assert_eq!(state.players[1].health, MAX_HEALTH);
+    assert_eq!(state.proj_count, 1);
+    // Spawned at player 0's right edge, then one 16 px move
+    assert_eq!(state.projectiles[0].x, fp(300) + PLAYER_WIDTH + fp(16));
+}
+
+#[test]
+fn rocket_radius_connects_sniper_misses() {
+    assert!(near_miss_hit(WEAPON_ROCKET));
+    assert!(!near_miss_hit(WEAPON_SNIPER));
+}
+
+#[test]
+fn projectile_radius_inflates_solid_hits() {
+    let map = arena_map();
+    let plat = map.platforms[1];
+    let mut proj = EMPTY_PROJECTILE;
+    proj.x = plat.x - fp(4);
+    proj.y = plat.y + plat.height / 2;
+    proj.weapon = WEAPON_ROCKET;
+    assert!(hits_solid(&proj, &map));
+    proj.weapon = WEAPON_SNIPER;
+    assert!(!hits_solid(&proj, &map));
+}
+
+/// Player 0 standing on the arena's lower-left platform (one-way).
+fn on_platform_state() -> (State, Platform) {
+    let mut state = create_initial_state(42, &arena_map());
+    state.pickup_count = 0;
+    let plat = state.map.platforms[1];
+    assert!(plat.one_way);
+    let p = &mut state.players[0];
+    (p.x, p.y, p.vx, p.vy, p.grounded) = (plat.x + fp(40), plat.y - PLAYER_HEIGHT, 0, 0, true);
+    (state, plat)
+}
+
+#[test]
+fn down_jump_drops_through_one_way_platform() {
+    let press = |buttons: u8| [FpInput { buttons, aim_x: 0, aim_y: 0 }, NULL_INPUT];
+
+    // DOWN alone keeps standing; JUMP alone jumps
+    let (mut state, plat) = on_platform_state();
+    step_mut(&mut state, &press(button::DOWN));
+    assert!(state.players[0].grounded);
+    assert_eq!(state.players[0].y, plat.y - PLAYER_HEIGHT);
+    step_mut(&mut state, &press(button::JUMP));
+    assert!(state.players[0].vy < 0);
+
+    // DOWN + JUMP falls through to the ground without spending a jump
+    let (mut state, plat) = on_platform_state();
+    step_mut(&mut state, &press(button::DOWN | button::JUMP));
+    let p = state.players[0];
+    assert!(!p.grounded && p.vy > 0 && p.y > plat.y - PLAYER_HEIGHT);
+    assert_eq!(p.jumps_left, MAX_JUMPS);
+    for _ in 0..60 {
+        step_mut(&mut state, &press(button::DOWN));
+    }
+    let ground = state.map.platforms[0];
+    assert!(!ground.one_way);
+    assert!(state.players[0].grounded);
+    assert_eq!(state.players[0].y, ground.y - PLAYER_HEIGHT);
+
+    // The solid ground never lets go
+    step_mut(&mut state, &press(button::DOWN | button::JUMP));
+    assert!(state.players[0].vy < 0, "a normal jump off solid ground");
+}
+
+#[test]
+fn one_way_platform_passes_jumps_from_below() {
+    let (mut state, plat) = on_platform_state();
+    let p = &mut state.players[0];
+    (p.y, p.vy, p.grounded) = (plat.y + plat.height + fp(2), JUMP_VELOCITY, false);
+    step_mut(&mut state, &[NULL_INPUT; 2]);
+    assert!(state.players[0].vy < 0, "no ceiling bump under a one-way platform");
+    for _ in 0..60 {
+        step_mut(&mut state, &[NULL_INPUT; 2]);
+    }
+    assert!(state.players[0].grounded);
+    assert_eq!(state.players[0].y, plat.y - PLAYER_HEIGHT);
+}
+
+#[test]
+fn one_way_platform_sides_are_not_walls() {
+    let slides = |one_way: bool| {
+        let (mut state, plat) = on_platform_state();
+        let mut map = (*state.map).clone();
+        map.platforms[1].one_way = one_way;
+        state.map = Arc::new(map);
+        let p = &mut state.players[0];
+        (p.x, p.y, p.vy, p.grounded) = (plat.x + plat.width, plat.y - fp(4), ONE, false);
+        step_mut(&mut state, &[FpInput { buttons: button::LEFT, aim_x: 0, aim_y: 0 }, NULL_INPUT]);
+        state.players[0].wall_sliding
+    };
+    assert!(slides(false));
+    assert!(!slides(true));
+}
+
+#[test]
+fn one_way_drops_are_deterministic_and_encoded() {
+    let run = || {
+        let mut state = create_initial_state(7, &arena_map());
+        for t in 0..600u32 {
+            let h = t.wrapping_mul(0x9E37_79B9);
+            let buttons = [button::LEFT, button::RIGHT, 0][(h % 3) as usize]
+                | if h & 0x300 == 0 { button::JUMP } else { 0 }
+                | if h & 0x400 != 0 { button::DOWN } else { 0 };
+            step_mut(&mut state, &[FpInput { buttons, aim_x: 1, aim_y: 0 }, NULL_INPUT]);
+        }
+        state
+    };
+    let (a, b) = (run(), run());
+    assert_eq!(hash_state(&a), hash_state(&b));
+    let decoded = decode_state(&encode_state(&a));
+    assert!(map_eq(&decoded.map, &a.map));
+    assert_eq!(decoded.map.platforms.iter().filter(|p| p.one_way).count(), 5);
+    assert_eq!(hash_state(&decoded), hash_state(&a));
+    // The flags are part of the map hash
+    let mut solid = (*a.map).clone();
+    solid.platforms.iter_mut().for_each(|p| p.one_way = false);
+    assert_ne!(map_hash(&solid), map_hash(&a.map));
+}
+
+/// Both players standing on the arena floor with `hazard_damage` set on it, past the start
+/// grace.
+fn on_hazard_floor(hazard_damage: i32) -> State {
+    let mut map = arena_map();
+    map.platforms[0].hazard_damage = hazard_damage;
+    let mut state = create_initial_state(42, &map);
+    state.pickup_count = 0;
+    for p in &mut state.players[..2] {
+        (p.state_flags, p.invincibility_source, p.respawn_timer) = (flag::ALIVE, invincibility::NONE, 0);
+    }
+    state
+}
+
+#[test]
+fn hazard_platform_hurts_standing_players_every_interval() {
+    let mut state = on_hazard_floor(3);
+    let mut hits = 0;
+    for _ in 0..100 {
+        let events = step_mut_events(&mut state, &[NULL_INPUT; 2]);
+        if state.tick % HAZARD_DAMAGE_INTERVAL == 0 {
+            hits += 1;
+            let sources: Vec<(i32, u8, i32)> =
+                events.damage.iter().map(|d| (d.victim, d.source, d.attacker)).collect();
+            assert_eq!(sources, [(0, damage_source::HAZARD, -1), (1, damage_source::HAZARD, -1)]);
+        } else {
+            assert_eq!(events.damage.iter().count(), 0);
+        }
+    }
+    assert_eq!(hits, 100 / HAZARD_DAMAGE_INTERVAL);
+    assert!(state.players[..2].iter().all(|p| p.health == MAX_HEALTH - 3 * hits));
+
+    // The same floor without the hazard
+    let mut normal = on_hazard_floor(0);
+    for _ in 0..100 {
+        step_mut(&mut normal, &[NULL_INPUT; 2]);
+    }
+    assert!(normal.players[..2].iter().all(|p| p.health == MAX_HEALTH));
+
+    // Invincible players and the airborne are spared
+    let mut spared = on_hazard_floor(3);
+    grant_invincibility(&mut spared.players[1], invincibility::SCRIPTED, 200);
+    for _ in 0..100 {
+        let jump = if spared.players[0].grounded { button::JUMP } else { 0 };
+        step_mut(&mut spared, &[FpInput { buttons: jump, aim_x: 0, aim_y: 0 }, NULL_INPUT]);
+    }
+    assert_eq!(spared.players[1].health, MAX_HEALTH);
+    assert!(spared.players[0].health > state.players[0].health);
+}
+
+#[test]
+fn hazard_platform_death_is_an_env_kill() {
+    let mut state = on_hazard_floor(MAX_HEALTH);
+    grant_invincibility(&mut state.players[1], invincibility::SCRIPTED, 200);
+    while state.tick < HAZARD_DAMAGE_INTERVAL {
+        step_mut(&mut state, &[NULL_INPUT; 2]);
+    }
+    assert_eq!(state.players[0].state_flags & flag::ALIVE, 0);
+    assert_eq!((state.env_kills[0], state.score[1]), (1, 0));
+    assert_eq!(state.players[0].lives, INITIAL_LIVES - 1);
+    assert!(state.death_linger_timer > 0);
+    assert_eq!(state.winner, 1);
+}
+
+#[test]
+fn hazard_damage_is_encoded_with_the_map() {
+    let state = on_hazard_floor(7);
+    let decoded = decode_state(&encode_state(&state));
+    assert_eq!(decoded.map.platforms[0].hazard_damage, 7);
+    assert!(map_eq(&decoded.map, &state.map));
+    assert_ne!(map_hash(&state.map), map_hash(&arena_map()));
+}
+
+#[test]
+fn arena_map_hash_matches_the_one_settlement_assumes() {
+    assert_eq!(map_hash(&arena_map()), chickenz_core_mini::ARENA_MAP_HASH);
+}
+
+#[test]
+fn encoded_map_round_trips_and_hashes_like_the_state_carries_it() {
+    let mut map = arena_map();
+    map.platforms[2].one_way = true;
+    map.platforms[5].hazard_damage = 9;
+    map.spawns[3].x = -fp(12);
+    let b = encode_map(&map);
+    assert!(map_eq(&decode_map(&b).unwrap(), &map));
+    // Without health spawns the hash leaves their section out, as it did before they existed
+    assert_eq!(Sha256::digest(&b[..MAP_BASE_LEN]).as_slice(), map_hash(&map));
+    assert!(decode_map(&b[1..]).is_none());
+    assert!(decode_map(&[b.as_slice(), &[0]].concat()).is_none());
+
+    map.health_spawns[1] = SpawnPoint { x: fp(300), y: fp(200) };
+    map.health_spawn_count = 2;
+    let b = encode_map(&map);
+    assert_eq!(decode_map(&b).unwrap().used_health_spawns(), map.used_health_spawns());
+    assert_eq!(Sha256::digest(b).as_slice(), map_hash(&map));
+}
+
+/// Player 0 high on the left map wall, falling, under the given stamina rule.
+fn left_wall_state(stamina: bool) -> State {
+    let cfg = FpMatchConfig { stamina, ..FpMatchConfig::default() };
+    let mut state = create_initial_state_with(42, &arena_map(), &cfg);
+    state.pickup_count = 0;
+    let p = &mut state.players[0];
+    p.x = 0;
+    p.y = fp(10);
+    p.vy = ONE;
+    p.grounded = false;
+    state
+}
+
+#[test]
+fn stamina_exhausts_on_wall_then_normal_fall() {
+    let hold_wall = [FpInput { buttons: button::LEFT, aim_x: 0, aim_y: 0 }, NULL_INPUT];
+    let slide_ticks = STAMINA_MAX / WALL_SLIDE_STAMINA_DRAIN;
+
+    let mut state = left_wall_state(true);
+    for t in 0..slide_ticks {
+        step_mut(&mut state, &hold_wall);
+        assert!(state.players[0].wall_sliding, "tick {t}");
+        assert!(state.players[0].vy <= WALL_SLIDE_SPEED);
+    }
+    assert_eq!(state.players[0].stamina, 0);
+
+    // Out of stamina: no slide, and gravity runs up past the slide cap
+    step_mut(&mut state, &hold_wall);
+    assert!(!state.players[0].wall_sliding);
+    for _ in 0..8 {
+        let vy = state.players[0].vy;
+        step_mut(&mut state, &hold_wall);
+        assert!(!state.players[0].wall_sliding && !state.players[0].grounded);
+        assert_eq!(state.players[0].vy, (vy + GRAVITY).min(MAX_FALL_SPEED));
+    }
+    assert!(state.players[0].vy > WALL_SLIDE_SPEED);
+
+    // Without the ruleset the same hold slides indefinitely and stamina is untouched
+    let mut state = left_wall_state(false);
+    for _ in 0..=slide_ticks {
+        step_mut(&mut state, &hold_wall);
+    }
+    assert!(state.players[0].wall_sliding);
+    assert_eq!(state.players[0].stamina, STAMINA_MAX);
+}
+
+#[test]
+fn stamina_wall_jump_cost_and_ground_regen() {
+    let hold_wall = [FpInput { buttons: button::LEFT, aim_x: 0, aim_y: 0 }, NULL_INPUT];
+    let mut state = left_wall_state(true);
+    step_mut(&mut state, &hold_wall);
+    assert!(state.players[0].wall_sliding);
+    let before = state.players[0].stamina;
+    step_mut(&mut state, &[FpInput { buttons: button::JUMP, aim_x: 0, aim_y: 0 }, NULL_INPUT]);
+    assert_eq!(state.players[0].vx, WALL_JUMP_VX);
+    assert_eq!(state.players[0].stamina, before - WALL_JUMP_STAMINA_COST);
+
+    // Land, then regenerate back to full
+    while !state.players[0].grounded {
+        step_mut(&mut state, &[NULL_INPUT; 2]);
+    }
+    for _ in 0..STAMINA_MAX / STAMINA_REGEN {
+        step_mut(&mut state, &[NULL_INPUT; 2]);
+    }
+    assert_eq!(state.players[0].stamina, STAMINA_MAX);
+    let decoded = decode_state(&encode_state(&state));
+    assert!(decoded.cfg_stamina);
+    assert_eq!(decoded.players[0].stamina, STAMINA_MAX);
+}
+
+/// Player 0 standing on the arena floor facing right, a solid block on the floor `gap` to its
+/// right; the pickups removed and player 1 far off at its spawn.
+fn dash_floor_state(gap: Fp) -> State {
+    let mut map = arena_map();
+    let floor = map.platforms[0];
+    let block = Platform { x: fp(400), y: floor.y - fp(64), width: fp(32), height: fp(64), ..floor };
+    map.platforms[1] = Platform { one_way: false, hazard_damage: 0, ..block };
+    let mut state = create_initial_state(42, &map);
+    state.pickup_count = 0;
+    let p = &mut state.players[0];
+    (p.x, p.y, p.vx, p.vy, p.grounded, p.facing) =
+        (block.x - PLAYER_WIDTH - gap, floor.y - PLAYER_HEIGHT, 0, 0, true, FACING_RIGHT);
+    state
+}
+
+#[test]
+fn dash_bursts_once_per_cooldown() {
+    let press = |buttons: u8| [FpInput { buttons, aim_x: 0, aim_y: 0 }, NULL_INPUT];
+    let mut state = dash_floor_state(fp(300));
+    state.players[0].x = fp(20);
+    let x0 = state.players[0].x;
+    step_mut(&mut state, &press(button::DASH));
+    let p = state.players[0];
+    assert_eq!((p.vx, p.x, p.dash_cooldown), (DASH_VELOCITY, x0 + DASH_VELOCITY, DASH_COOLDOWN_TICKS));
+
+    // Holding the button is no new press: the burst runs its course, then drops to running speed
+    for _ in 1..DASH_TICKS {
+        step_mut(&mut state, &press(button::DASH));
+        assert_eq!(state.players[0].vx, DASH_VELOCITY);
+    }
+    step_mut(&mut state, &press(button::DASH));
+    assert!(state.players[0].vx <= PLAYER_SPEED);
+    assert_eq!(state.players[0].dash_cooldown, DASH_COOLDOWN_TICKS - DASH_TICKS);
+
+    // A fresh press while cooling down does nothing
+    step_mut(&mut state, &press(0));
+    step_mut(&mut state, &press(button::DASH | button::LEFT));
+    assert!(state.players[0].vx > -PLAYER_SPEED);
+    assert_eq!(state.players[0].dash_cooldown, DASH_COOLDOWN_TICKS - DASH_TICKS - 2);
+
+    // Once it runs out the next press dashes again, the way the player now faces
+    while state.players[0].dash_cooldown > 0 {
+        step_mut(&mut state, &press(0));
+    }
+    step_mut(&mut state, &[FpInput { buttons: button::DASH, aim_x: -1, aim_y: 0 }, NULL_INPUT]);
+    assert_eq!(state.players[0].vx, -DASH_VELOCITY);
+    assert_eq!(state.players[0].dash_cooldown, DASH_COOLDOWN_TICKS);
+}
+
+#[test]
+fn dash_cancels_wall_slide_with_reduced_gravity() {
+    let hold_wall = [FpInput { buttons: button::LEFT, aim_x: 0, aim_y: 0 }, NULL_INPUT];
+    let mut state = left_wall_state(false);
+    step_mut(&mut state, &hold_wall);
+    assert!(state.players[0].wall_sliding);
+
+    // Off the wall, away from it, and no slide restarts while the burst lasts
+    step_mut(&mut state, &[FpInput { buttons: button::DASH | button::LEFT, ..hold_wall[0] }, NULL_INPUT]);
+    let p = state.players[0];
+    assert!(!p.wall_sliding);
+    assert_eq!((p.vx, p.vy), (DASH_VELOCITY, DASH_GRAVITY));
+    for t in 2..=DASH_TICKS {
+        step_mut(&mut state, &hold_wall);
+        assert!(!state.players[0].wall_sliding);
+        assert_eq!(state.players[0].vy, t * DASH_GRAVITY);
+    }
+    // Full gravity once it's over
+    let vy = state.players[0].vy;
+    step_mut(&mut state, &hold_wall);
+    assert_eq!(state.players[0].vy, vy + GRAVITY);
+}
+
+#[test]
+fn dash_is_locked_out_while_stomping_or_stomped() {
+    let map = arena_map();
+    let dash = FpInput { buttons: button::DASH, aim_x: 0, aim_y: 0 };
+    for inputs in [[dash, NULL_INPUT], [NULL_INPUT, dash]] {
+        let mut state = riding_state(&map, fp(400), fp(480));
+        step_mut(&mut state, &inputs);
+        for p in &state.players[..2] {
+            assert_eq!(p.dash_cooldown, 0);
+            assert_ne!(p.vx.abs(), DASH_VELOCITY);
+        }
+    }
+}
+
+#[test]
+fn dashing_into_a_wall_stops_flush() {
+    let mut state = dash_floor_state(fp(30));
+    let block = state.map.platforms[1];
+    step_mut(&mut state, &[FpInput { buttons: button::DASH, aim_x: 0, aim_y: 0 }, NULL_INPUT]);
+    assert_eq!(state.players[0].vx, DASH_VELOCITY);
+    for _ in 0..DASH_COOLDOWN_TICKS {
+        step_mut(&mut state, &[FpInput { buttons: button::RIGHT, aim_x: 0, aim_y: 0 }, NULL_INPUT]);
+        let p = state.players[0];
+        assert!(p.x + PLAYER_WIDTH <= block.x, "inside the block at x {}", p.x);
+        assert!(p.grounded && !p.wall_sliding);
+    }
+    let p = state.players[0];
+    assert_eq!((p.x + PLAYER_WIDTH, p.vx), (block.x, 0));
+}
+
+#[test]
+fn dashes_are_deterministic_and_round_trip_v4() {
+    let run = || {
+        let mut state = create_initial_state(7, &arena_map());
+        let mut mid_dash = None;
+        for t in 0..600u32 {
+            let h = t.wrapping_mul(0x9E37_79B9);
+            let buttons = [button::LEFT, button::RIGHT, 0][(h % 3) as usize]
+                | if h & 0x300 == 0 { button::JUMP } else { 0 }
+                | if h & 0xC00 == 0 { button::DASH } else { 0 };
+            step_mut(&mut state, &[FpInput { buttons, aim_x: 0, aim_y: 0 }, FpInput { buttons, aim_x: 0, aim_y: 0 }]);
+            if mid_dash.is_none() && state.players[0].dash_cooldown > DASH_COOLDOWN_TICKS - DASH_TICKS {
+                mid_dash = Some(state.clone());
+            }
+        }
+        (state, mid_dash.expect("no dash in 600 ticks"))
+    };
+    let ((a, dashing), (b, _)) = (run(), run());
+    assert_eq!(hash_state(&a), hash_state(&b));
+
+    let decoded = decode_state(&encode_state(&dashing));
+    assert_eq!(decoded.players[0].dash_cooldown, dashing.players[0].dash_cooldown);
+    assert_eq!(hash_state(&decoded), hash_state(&dashing));
+    // The cooldown is hashed, and older formats decode without one
+    let mut cooled = dashing.clone();
+    cooled.players[0].dash_cooldown = 0;
+    assert_ne!(hash_state(&cooled), hash_state(&dashing));
+    let v3 = decode_state(&encode_state_as(&dashing, StateFormat::V3));
+    assert_eq!(v3.players[0].dash_cooldown, 0);
+
+    let mut bad = dashing.clone();
+    bad.players[1].dash_cooldown = DASH_COOLDOWN_TICKS + 1;
+    assert_eq!(
+        validate_state(&bad),
+        Err(vec![StateIssue::DashCooldown { player: 1, cooldown: DASH_COOLDOWN_TICKS + 1 }])
+    );
+    assert_eq!(sanitize_state(&mut bad).len(), 1);
+    assert_eq!(bad.players[1].dash_cooldown, DASH_COOLDOWN_TICKS);
+}
+
+/// Put player 0 wall sliding against the left (-1) or right (1) map wall, armed, and shoot.
+fn wall_slide_shot(wall_dir: i32, aim_x: i8, aim_y: i8) -> State {
+    let map = arena_map();
+    let mut state = create_initial_state(42, &map);
+    state.pickup_count = 0;
+    let p = &mut state.players[0];
+    p.x = if wall_dir < 0 { 0 } else { map.width - PLAYER_WIDTH };
+    p.y = fp(100);
+    p.vy = ONE;
+    p.grounded = false;
+    p.weapon = WEAPON_PISTOL;
+    p.ammo = 15;
+    let toward_wall = if wall_dir < 0 { button::LEFT } else { button::RIGHT };
+    let inputs = [
+        FpInput { buttons: toward_wall | button::SHOOT, aim_x, aim_y },
+        NULL_INPUT,
+    ];
+    step_mut(&mut state, &inputs);
+    assert!(state.players[0].wall_sliding);
+    assert_eq!(state.players[0].wall_dir, wall_dir);
+    state
+}
+
+#[test]
+fn wall_slide_shots_go_away_from_wall_keeping_vertical_aim() {
+    for wall_dir in [-1, 1] {
+        for (aim_x, aim_y) in [(-1, -1), (1, -1), (-1, 1), (1, 1), (0, -1), (0, 1), (0, 0)] {
+            let state = wall_slide_shot(wall_dir, aim_x, aim_y);
+            // Projectile survived its first move (not spawned into the wall)
+            assert_eq!(state.proj_count, 1, "wall {wall_dir} aim ({aim_x},{aim_y})");
+            let proj = state.projectiles[0];
+            assert_eq!(proj.vx.signum(), -wall_dir, "wall {wall_dir} aim ({aim_x},{aim_y})");
+            assert_eq!(proj.vy.signum(), aim_y as i32, "wall {wall_dir} aim ({aim_x},{aim_y})");
+            if aim_y != 0 {
+                // Normalized diagonal, not a full-speed horizontal + vertical
+                assert_eq!(proj.vx.abs(), proj.vy.abs());
+                assert!(proj.vx.abs() < fp_weapon_stats(WEAPON_PISTOL).speed);
+            }
+        }
+    }
+}
+
+#[test]
+fn reset_state_matches_fresh_state() {
+    let map = arena_map();
+    let cfg = FpMatchConfig {
+        initial_lives: 2, match_duration: 900, sudden_death: 600, lag_ticks: [3, 0], mirrored: false, stamina: true,
+        active_despawn: true, player_count: 2, zone_mode: zone_mode::BOX,
+    };
+    // Dirty a state with a real match first
+    let mut state = create_initial_state(7, &map);
+    let inputs = [
+        FpInput { buttons: button::RIGHT | button::SHOOT, aim_x: 1, aim_y: 0 },
+        FpInput { buttons: button::LEFT | button::JUMP, aim_x: -1, aim_y: 0 },
+    ];
+    for _ in 0..400 {
+        step_mut(&mut state, &inputs);
+    }
+    reset_state(&mut state, 99, &map, &cfg);
+    let fresh = create_initial_state_with(99, &map, &cfg);
+    assert_eq!(hash_state(&state), hash_state(&fresh));
+    assert_eq!(encode_state(&state), encode_state(&fresh));
+}
+
+/// A pistol round owned by player 0 coming back at them from the right (as a bounced shot
+/// would), immune to its owner until `immune_until`.
+fn returning_shot(immune_until: i32) -> State {
+    let mut state = create_initial_state(42, &arena_map());
+    state.pickup_count = 0;
+    let p = state.players[0];
+    state.projectiles[0] = Projectile {
+        id: 0, owner_id: 0,
+        x: p.x + PLAYER_WIDTH + fp(2), y: p.y + PLAYER_HEIGHT / 2,
+        vx: -fp(4), vy: 0, lifetime: 30, weapon: WEAPON_PISTOL,
+        owner_immune_until: immune_until,
+        bounces_left: 0,
+    };
+    state.proj_count = 1;
+    state
+}
+
+#[test]
+fn owner_immunity_window_then_own_shot_hits() {
+    let damage = fp_weapon_stats(WEAPON_PISTOL).damage;
+
+    // Inside the window (or with permanent immunity) the shot passes through its owner
+    for until in [5, OWNER_IMMUNE_FOREVER] {
+        let mut state = returning_shot(until);
+        for _ in 0..3 {
+            step_mut(&mut state, &[NULL_INPUT; 2]);
+        }
+        assert_eq!(state.players[0].health, MAX_HEALTH, "until {until}");
+        assert_eq!(state.proj_count, 1, "until {until}");
+    }
+
+    // Window over: the returning shot hits the shooter
+    let mut state = returning_shot(1);
+    step_mut(&mut state, &[NULL_INPUT; 2]);
+    assert_eq!(state.players[0].health, MAX_HEALTH - damage);
+    assert_eq!(state.proj_count, 0);
+
+    // A lethal own shot costs a life but scores nobody
+    let mut state = returning_shot(1);
+    state.players[0].health = damage;
+    step_mut(&mut state, &[NULL_INPUT; 2]);
+    assert_eq!(state.players[0].state_flags & flag::ALIVE, 0);
+    assert_eq!(state.score, [0, 0, 0, 0]);
+
+    // Every current weapon keeps the old never-hit-the-owner rule
+    assert!(WEAPON_STATS.iter().all(|w| w.owner_immunity_ticks == OWNER_IMMUNE_ALWAYS));
+    let decoded = decode_state(&encode_state(&returning_shot(9)));
+    assert_eq!(decoded.projectiles[0].owner_immune_until, 9);
+}
+
+/// Run player 1 left for 12 ticks, then drop a still sniper round owned by player 0 just
+/// inside where player 1's hitbox was 4 ticks ago. Returns (hit, state).
+fn lagged_hit(lag: u8) -> (bool, State) {
+    let map = arena_map();
+    let cfg = FpMatchConfig { lag_ticks: [lag, 0], ..FpMatchConfig::default() };
+    let mut state = create_initial_state_with(42, &map, &cfg);
+    state.pickup_count = 0;
+    let inputs = [NULL_INPUT, FpInput { buttons: button::LEFT, aim_x: -1, aim_y: 0 }];
+    for _ in 0..12 {
+        step_mut(&mut state, &inputs);
+    }
+    let old = ((state.tick - 4) as usize) % LAG_RING;
+    let old_x = state.pos_history_x[1][old];
+    let old_y = state.pos_history_y[1][old];
+    assert!(old_x + PLAYER_WIDTH - ONE > state.players[1].x + PLAYER_WIDTH, "victim must have moved");
+    state.projectiles[0] = Projectile {
+        id: 0, owner_id: 0,
+        x: old_x + PLAYER_WIDTH - ONE, y: old_y + PLAYER_HEIGHT / 2,
+        vx: 0, vy: 0, lifetime: 10, weapon: WEAPON_SNIPER,
+        owner_immune_until: OWNER_IMMUNE_FOREVER,
+        bounces_left: 0,
+    };
+    state.proj_count = 1;
+    let hp = state.players[1].health;
+    resolve_hits_mut(&mut state, 0, &mut KillList::new(), &mut ());
+    (state.players[1].health < hp, state)
+}
+
+#[test]
+fn lag_compensation_hits_past_position() {
+    assert!(!lagged_hit(0).0);
+    assert!(lagged_hit(4).0);
+}
+
+#[test]
+fn lag_history_roundtrips_and_hashes() {
+    let (_, state) = lagged_hit(4);
+    let decoded = decode_state(&encode_state(&state));
+    assert_eq!(decoded.cfg_lag_ticks, [4, 0]);
+    assert_eq!(decoded.pos_history_x, state.pos_history_x);
+    assert_eq!(decoded.pos_history_y, state.pos_history_y);
+    assert_eq!(hash_state(&decoded), hash_state(&state));
+    let mut h = Sha256::new();
+    h.update(encode_state(&state));
+    let encoded_hash: [u8; 32] = h.finalize().into();
+    assert_eq!(encoded_hash, hash_state(&state));
+}
+
+#[test]
+fn streaming_hash_state_matches_encode() {
+    // Run a short sim and verify streaming hash_state == encode_state → SHA-256
+    let map = arena_map();
+    let mut state = create_initial_state(42, &map);
+    let inputs = [
+        FpInput { buttons: button::RIGHT | button::SHOOT, aim_x: 1, aim_y: 0 },
+        FpInput { buttons: button::LEFT, aim_x: -1, aim_y: 0 },
+    ];
+    // Run several ticks to get non-trivial state
+    for _ in 0..100 {
+        step_mut(&mut state, &inputs);
+    }
+
+    // Old approach: encode_state → Vec → SHA-256
+    let encoded = encode_state(&state);
+    let mut h = Sha256::new();
+    h.update(&encoded);
+    let old_hash: [u8; 32] = h.finalize().into();
+
+    // New approach: streaming hash_state
+    let new_hash = hash_state(&state);
+
+    assert_eq!(old_hash, new_hash);
+}
+
+#[test]
+fn state_formats_roundtrip_and_v2_is_smaller() {
+    let map = arena_map();
+    let mut states = vec![busy_state(&map)];
+    let cfg = FpMatchConfig { lag_ticks: [2, 1], mirrored: true, stamina: true, ..FpMatchConfig::default() };
+    let mut odd = create_initial_state_with(7, &cfg.oriented_map(&map), &cfg);
+    odd.side_swap = true;
+    odd.players[0].wall_dir = -1;
+    odd.players[0].wall_sliding = true;
+    odd.players[1].stomp_last_shake_dir = 1;
+    odd.players[1].stomp_auto_run_dir = -1;
+    odd.players[1].stomped_by = 0;
+    odd.players[1].stamina = 123;
+    odd.players[0].health = -5;
+    odd.env_kills = [2, 1, 0, 0];
+    odd.cfg_active_despawn = true;
+    odd.weapon_pickups[2].active_timer = 17;
+    odd.final_blow = true;
+    grant_invincibility(&mut odd.players[1], invincibility::SCRIPTED, 40);
+    states.push(odd);
+
+    for state in &states {
+        let v1 = encode_state_as(state, StateFormat::V1);
+        let v2 = encode_state_as(state, StateFormat::V2);
+        let v3 = encode_state_as(state, StateFormat::V3);
+        let v4 = encode_state_as(state, StateFormat::V4);
+        let formats = [(StateFormat::V1, &v1), (StateFormat::V2, &v2), (StateFormat::V3, &v3), (StateFormat::V4, &v4)];
+        for (format, bytes) in formats {
+            let decoded = decode_state(bytes);
+            assert_eq!(&encode_state_as(&decoded, format), bytes, "{format:?}");
+            // Decoding either format yields the same state
+            assert_eq!(encode_state_as(&decoded, StateFormat::V1), v1, "{format:?}");
+            let mut h = Sha256::new();
+            h.update(bytes);
+            assert_eq!(hash_state_as(state, format), <[u8; 32]>::from(h.finalize()));
+        }
+        assert_ne!(hash_state_as(state, StateFormat::V1), hash_state_as(state, StateFormat::V2));
+        assert_eq!(hash_state(state), hash_state_as(state, STATE_FORMAT));
+
+        // Players: 87 bytes + 6 trailing (v1) vs 44 (v2); v2 adds its 4-byte tag and
+        // folds the three trailing flag bytes into one; projectiles move bytes, not size
+        let players_saved = 2 * (87 + 6 - 44);
+        assert_eq!(v1.len() - v2.len(), players_saved - 4 + 2);
+        // v3: same body under its own tag, then env kills, pickup timers and the map
+        assert_eq!(v3[4..v2.len()], v2[4..]);
+        assert_eq!(v3.len(), v2.len() + V3_EXTRA_BYTES + MAP_BASE_LEN);
+        // v4: the v3 extras, then the health pickups and the whole map
+        assert_eq!(v4[4..v2.len() + V3_EXTRA_BYTES], v3[4..v2.len() + V3_EXTRA_BYTES]);
+        assert_eq!(v4.len(), v2.len() + V3_EXTRA_BYTES + V4_EXTRA_BYTES + MAP_ENCODED_LEN);
+        let decoded = decode_state(&v3);
+        assert_eq!(decoded.env_kills, state.env_kills);
+        assert_eq!(decoded.cfg_active_despawn, state.cfg_active_despawn);
+        assert_eq!(decoded.weapon_pickups[2].active_timer, state.weapon_pickups[2].active_timer);
+        assert_eq!(decoded.final_blow, state.final_blow);
+    }
+    let mut lagged = states.pop().unwrap();
+    lagged.proj_count = MAX_PROJECTILES as u8;
+    lagged.pickup_count = MAX_WEAPON_PICKUPS as u8;
+    let map = Arc::clone(&lagged.map);
+    seat_extra_players(&mut lagged, &map, MAX_PLAYERS as u8, 1);
+    assert_eq!(encoded_state_len(&lagged), MAX_ENCODED_STATE_LEN);
+}
+
+/// A state steps on, encodes and hashes its own map; v2 bytes migrate onto a supplied one.
+#[test]
+fn state_carries_its_map() {
+    let arena = arena_map();
+    let mut sunk = arena.clone();
+    sunk.platforms[0].y = fp(520);
+    let mut a = create_initial_state(42, &arena);
+    let mut b = create_initial_state(42, &sunk);
+    for _ in 0..60 {
+        step_mut(&mut a, &[NULL_INPUT; 2]);
+        step_mut(&mut b, &[NULL_INPUT; 2]);
+    }
+    assert_eq!(b.players[0].y - a.players[0].y, fp(8));
+    b.players = a.players;
+    assert_eq!(encode_state_as(&a, StateFormat::V2), encode_state_as(&b, StateFormat::V2));
+    assert_ne!(hash_state(&a), hash_state(&b));
+
+    let bytes = encode_state(&b);
+    assert_eq!(*decode_state(&bytes).map, sunk);
+    assert_eq!(try_decode_state(&bytes, &sunk, false).map(|s| encode_state(&s)), Ok(bytes.clone()));
+    assert_eq!(
+        decode_state_on(&bytes, &arena).map(|s| hash_state(&s)),
+        Err(StateIssue::MapMismatch { expected: map_hash(&arena), actual: map_hash(&sunk) })
+    );
+
+    // Legacy bytes: the arena map by default, or whatever map the caller supplies
+    let v2 = encode_state_as(&b, StateFormat::V2);
+    assert_eq!(*decode_state(&v2).map, arena);
+    let migrated = decode_state_on(&v2, &sunk).unwrap();
+    assert_eq!(encode_state(&migrated), bytes);
+    let cfg = FpMatchConfig { mirrored: true, ..FpMatchConfig::default() };
+    let mirrored = create_initial_state_with(3, &cfg.oriented_map(&arena), &cfg);
+    assert_eq!(*decode_state(&encode_state_as(&mirrored, StateFormat::V1)).map, mirror_map(&arena));
+
+    // reset_state adopts a new map and keeps the handle for an equal one
+    let handle = Arc::clone(&a.map);
+    reset_state(&mut a, 42, &arena, &FpMatchConfig::default());
+    assert!(Arc::ptr_eq(&a.map, &handle));
+    reset_state(&mut a, 42, &sunk, &FpMatchConfig::default());
+    assert_eq!(hash_state(&a), hash_state(&create_initial_state(42, &sunk)));
+}
+
+#[test]
+fn state_can_be_shared_across_threads() {
+    // The host's chunk pool hands boundary states to its workers by reference
+    fn shareable<T: Send + Sync>() {}
+    shareable::<State>();
+}
+
+/// Live mid-match state with projectiles in flight, for validation tests.
+fn busy_state(map: &Map) -> State {
+    let mut state = create_initial_state(42, map);
+    state.players[0].weapon = WEAPON_SMG;
+    state.players[0].ammo = 30;
+    let inputs = [
+        FpInput { buttons: button::SHOOT, aim_x: 1, aim_y: 0 },
+        NULL_INPUT,
+    ];
+    for _ in 0..20 {
+        step_mut(&mut state, &inputs);
+    }
+    assert!(state.proj_count >= 2);
+    // Only v4 carries the pickup stats; the older formats round-trip everything else
+    state.pickup_stats = PickupStats::default();
+    state
+}
+
+#[test]
+fn validate_accepts_simulated_states() {
+    let map = arena_map();
+    let mut state = create_initial_state(7, &map);
+    assert_eq!(validate_state(&state), Ok(()));
+    let inputs = [
+        FpInput { buttons: button::RIGHT | button::SHOOT | button::JUMP, aim_x: 1, aim_y: 0 },
+        FpInput { buttons: button::LEFT | button::SHOOT, aim_x: -1, aim_y: 0 },
+    ];
+    while !state.match_over {
+        step_mut(&mut state, &inputs);
+        assert_eq!(validate_state(&state), Ok(()), "tick {}", state.tick);
+    }
+}
+
+#[test]
+fn validate_rejects_player_ranges() {
+    let map = arena_map();
+    let mut state = create_initial_state(42, &map);
+    state.players[0].health = 10_000;
+    state.players[1].lives = -5;
+    state.players[1].weapon = 9;
+    state.players[0].id = 1;
+    let issues = validate_state(&state).unwrap_err();
+    assert!(issues.contains(&StateIssue::Health { player: 0, health: 10_000 }));
+    assert!(issues.contains(&StateIssue::Lives { player: 1, lives: -5 }));
+    assert!(issues.contains(&StateIssue::PlayerWeapon { player: 1, weapon: 9 }));
+    assert!(issues.contains(&StateIssue::PlayerId { player: 0, id: 1 }));
+    assert_eq!(issues.len(), 4);
+}
+
+#[test]
+fn validate_rejects_positions_outside_map() {
+    let map = arena_map();
+    let mut state = busy_state(&map);
+    state.players[1].x = fp(-9999);
+    state.projectiles[0].y = map.height + OOB_MARGIN + 1;
+    state.weapon_pickups[0].x = map.width * 2;
+    let issues = validate_state(&state).unwrap_err();
+    assert!(matches!(issues[0], StateIssue::PlayerPosition { player: 1, .. }));
+    assert!(issues.iter().any(|i| matches!(i, StateIssue::ProjectilePosition { .. })));
+    assert!(issues.iter().any(|i| matches!(i, StateIssue::PickupPosition { .. })));
+    assert_eq!(issues.len(), 3);
+}
+
+#[test]
+fn validate_rejects_projectile_owner_and_duplicate_ids() {
+    let map = arena_map();
+    let mut state = busy_state(&map);
+    state.projectiles[0].owner_id = 9;
+    state.projectiles[1].id = state.projectiles[0].id;
+    state.projectiles[1].weapon = 7;
+    let id = state.projectiles[0].id;
+    let issues = validate_state(&state).unwrap_err();
+    assert!(issues.contains(&StateIssue::ProjectileOwner { id, owner_id: 9 }));
+    assert!(issues.contains(&StateIssue::DuplicateProjectileId(id)));
+    assert!(issues.contains(&StateIssue::ProjectileWeapon { id, weapon: 7 }));
+
+    let mut state = create_initial_state(42, &map);
+    state.weapon_pickups[2].id = state.weapon_pickups[1].id;
+    state.weapon_pickups[3].weapon = WEAPON_NONE;
+    let issues = validate_state(&state).unwrap_err();
+    assert!(issues.contains(&StateIssue::DuplicatePickupId(1)));
+    assert!(issues.contains(&StateIssue::PickupWeapon { id: 3, weapon: WEAPON_NONE }));
+}
+
+/// A snapshot whose `next_proj_id` trails a live projectile's id would hand the next shot an
+/// id already in flight: rejected, or bumped past the live ids when sanitizing.
+#[test]
+fn next_projectile_id_must_pass_every_live_id() {
+    let map = arena_map();
+    let mut state = busy_state(&map);
+    let live_ids = |s: &State| s.projectiles[..s.proj_count as usize].iter().map(|pj| pj.id).collect::<Vec<_>>();
+    let max_live = *live_ids(&state).iter().max().unwrap();
+    state.next_proj_id = max_live - 1;
+    let issues = validate_state(&state).unwrap_err();
+    assert_eq!(issues, vec![StateIssue::NextProjectileId { next: max_live - 1, max_live }]);
+
+    let bytes = encode_state(&state);
+    assert!(try_decode_state(&bytes, &map, false).is_err());
+    let mut repaired = try_decode_state(&bytes, &map, true).unwrap();
+    assert_eq!(repaired.next_proj_id, max_live + 1);
+
+    // The next shot gets a fresh id
+    repaired.players[0].shoot_cooldown = 0;
+    step_mut(&mut repaired, &[FpInput { buttons: button::SHOOT, aim_x: 1, aim_y: 0 }, NULL_INPUT]);
+    let mut ids = live_ids(&repaired);
+    assert!(ids.contains(&(max_live + 1)));
+    ids.sort();
+    ids.dedup();
+    assert_eq!(ids.len(), repaired.proj_count as usize);
+}
+
+#[test]
+fn validate_rejects_counts_and_arena_bounds() {
+    let map = arena_map();
+    let mut state = create_initial_state(42, &map);
+    state.proj_count = MAX_PROJECTILES as u8 + 1;
+    state.pickup_count = MAX_WEAPON_PICKUPS as u8 + 1;
+    state.arena_left = fp(500);
+    state.arena_right = fp(100);
+    let issues = validate_state(&state).unwrap_err();
+    assert!(issues.contains(&StateIssue::TooManyProjectiles(MAX_PROJECTILES as u8 + 1)));
+    assert!(issues.contains(&StateIssue::TooManyPickups(MAX_WEAPON_PICKUPS as u8 + 1)));
+    let expected = ZoneBounds::open(map.width, map.height);
+    let actual = ZoneBounds { left: fp(500), right: fp(100), ..expected };
+    assert!(issues.contains(&StateIssue::ArenaBounds { actual, expected }));
+}
+
+/// Arena bounds are derived from the tick: a snapshot carrying stale ones would hash unlike
+/// the authoritative state even though the next step recomputes them, so imports reject them
+/// (or, sanitizing, recompute them).
+#[test]
+fn stale_arena_bounds_are_rejected_on_import() {
+    let map = arena_map();
+    let cfg = FpMatchConfig { sudden_death: 60, ..FpMatchConfig::default() };
+    let mut state = create_initial_state_with(5, &map, &cfg);
+    for _ in 0..200 {
+        step_mut(&mut state, &[NULL_INPUT, NULL_INPUT]);
+    }
+    assert!(!state.match_over && state.death_linger_timer == 0);
+    let live = state.arena_bounds();
+    assert_eq!(live, zone_bounds(200, 60, zone_mode::PINCH, map.width, map.height));
+    assert!(live.left > 0);
+
+    // A snapshot taken before the zone started, re-stamped with the current tick
+    let mut stale = state.clone();
+    (stale.arena_left, stale.arena_right) = (0, map.width);
+    assert_ne!(hash_state(&stale), hash_state(&state));
+    let issues = validate_state(&stale).unwrap_err();
+    let actual = ZoneBounds::open(map.width, map.height);
+    assert_eq!(issues, vec![StateIssue::ArenaBounds { actual, expected: live }]);
+    let bytes = encode_state(&stale);
+    assert!(try_decode_state(&bytes, &map, false).is_err());
+    assert_eq!(hash_state(&try_decode_state(&bytes, &map, true).unwrap()), hash_state(&state));
+
+    // Once the match stops the bounds freeze: any symmetric span the zone had passed is fine,
+    // one further in than the tick allows is not
+    while !state.match_over {
+        step_mut(&mut state, &[NULL_INPUT, NULL_INPUT]);
+    }
+    assert_eq!(validate_state(&state), Ok(()));
+    let mut frozen = state.clone();
+    (frozen.arena_left, frozen.arena_right) = (fp(10), map.width - fp(10));
+    assert_eq!(validate_state(&frozen), Ok(()));
+    frozen.arena_right -= 1;
+    assert!(validate_state(&frozen).is_err());
+    (frozen.arena_left, frozen.arena_right) = (map.width / 2 + 1, map.width / 2 - 1);
+    let max_left = zone_bounds(frozen.tick, 60, zone_mode::PINCH, map.width, map.height).left;
+    assert!(matches!(validate_state(&frozen).unwrap_err()[..], [StateIssue::ArenaBounds { .. }]));
+    sanitize_state(&mut frozen);
+    assert_eq!((frozen.arena_left, frozen.arena_right), (max_left, map.width - max_left));
+}
+
+#[test]
+fn sanitize_repairs_every_issue_class() {
+    let map = arena_map();
+    let mut state = busy_state(&map);
+    let live = state.proj_count;
+    state.players[0].health = 10_000;
+    state.players[1].lives = -5;
+    state.players[1].weapon = 9;
+    state.players[1].x = fp(-9999);
+    state.projectiles[0].owner_id = 9;
+    state.projectiles[1].id = state.projectiles[2].id;
+    state.weapon_pickups[3].weapon = 42;
+    state.arena_left = fp(500);
+    state.arena_right = fp(100);
+
+    let fixed = sanitize_state(&mut state);
+    assert!(fixed.len() >= 7);
+    assert_eq!(validate_state(&state), Ok(()));
+    assert_eq!(state.players[0].health, MAX_HEALTH);
+    assert_eq!(state.players[1].lives, 0);
+    assert_eq!(state.players[1].weapon, WEAPON_NONE);
+    assert_eq!(state.players[1].x, -OOB_MARGIN);
+    assert_eq!(state.proj_count, live - 2);
+    assert_eq!(state.pickup_count, 3);
+    assert_eq!(state.arena_bounds(), consistent_arena_bounds(&state));
+
+    // Already-valid states are left alone
+    let before = hash_state(&state);
+    assert!(sanitize_state(&mut state).is_empty());
+    assert_eq!(hash_state(&state), before);
+}
+
+#[test]
+fn encode_state_into_caller_buffer() {
+    let map = arena_map();
+    let state = busy_state(&map);
+    let bytes = encode_state(&state);
+    let len = encoded_state_len(&state);
+    assert_eq!(len, bytes.len());
+
+    // Exact fit
+    let mut exact = vec![0u8; len];
+    assert_eq!(encode_state_into(&state, &mut exact), Ok(len));
+    assert_eq!(exact, bytes);
+
+    // Oversized: prefix written, tail untouched
+    let mut big = vec![0xAAu8; max_encoded_state_len(&state)];
+    assert!(big.len() > len);
+    assert_eq!(encode_state_into(&state, &mut big), Ok(len));
+    assert_eq!(&big[..len], &bytes[..]);
+    assert!(big[len..].iter().all(|&b| b == 0xAA));
+
+    // Undersized: rejected, nothing written
+    let mut small = vec![0xAAu8; len - 1];
+    assert_eq!(
+        encode_state_into(&state, &mut small),
+        Err(BufferTooSmall { needed: len, got: len - 1 })
+    );
+    assert!(small.iter().all(|&b| b == 0xAA));
+
+    // One max-sized buffer fits every state of the match
+    let mut state = state;
+    let max = max_encoded_state_len(&state);
+    for _ in 0..200 {
+        step_mut(&mut state, &[FpInput { buttons: button::SHOOT, aim_x: 1, aim_y: 0 }; 2]);
+        assert!(encoded_state_len(&state) <= max);
+    }
+}
+
+#[test]
+fn try_decode_rejects_truncated_and_overcount() {
+    let map = arena_map();
+    let state = busy_state(&map);
+    let bytes = encode_state_as(&state, StateFormat::V1);
+    let decoded = try_decode_state(&bytes, &map, false).unwrap();
+    assert_eq!(hash_state(&decoded), hash_state(&state));
+
+    // One byte into the required tail, past the optional trailing bytes
+    // (28 + 4 per projectile + side_swap)
+    let optional = 29 + 4 * state.proj_count as usize;
+    for len in [0, 10, 4 + 2 * 87, bytes.len() - optional - 1] {
+        assert_eq!(
+            try_decode_state(&bytes[..len], &map, true).unwrap_err(),
+            vec![StateIssue::Truncated],
+            "len {len}"
+        );
+    }
+    let mut over = bytes.clone();
+    over[4 + 2 * 87] = 200;
+    assert_eq!(
+        try_decode_state(&over, &map, true).unwrap_err(),
+        vec![StateIssue::TooManyProjectiles(200)]
+    );
+
+    // Lag on but history missing
+    let cfg = FpMatchConfig { lag_ticks: [3, 0], ..FpMatchConfig::default() };
+    let mut lagged = create_initial_state_with(1, &map, &cfg);
+    let full = encode_state_as(&lagged, StateFormat::V1);
+    assert!(try_decode_state(&full, &map, false).is_ok());
+    assert_eq!(
+        // Cut 3 bytes into the history, under the 14 bytes that follow it
+        try_decode_state(&full[..full.len() - 17], &map, false).unwrap_err(),
+        vec![StateIssue::Truncated]
+    );
+
+    // v2 has no optional groups: every proper prefix is truncated
+    for state in [&state, &lagged] {
+        let bytes = encode_state_as(state, StateFormat::V2);
+        assert!(try_decode_state(&bytes, &map, false).is_ok());
+        for len in 0..bytes.len() {
+            assert_eq!(
+                try_decode_state(&bytes[..len], &map, true).unwrap_err(),
+                vec![StateIssue::Truncated],
+                "v2 len {len}"
+            );
+        }
+    }
+    let mut over = encode_state_as(&state, StateFormat::V2);
+    over[8 + 2 * 44] = 200;
+    assert_eq!(
+        try_decode_state(&over, &map, true).unwrap_err(),
+        vec![StateIssue::TooManyProjectiles(200)]
+    );
+
+    // Range issues: reject, or clamp with sanitize
+    lagged.players[0].health = 500;
+    let bad = encode_state(&lagged);
+    assert_eq!(
+        try_decode_state(&bad, &map, false).unwrap_err(),
+        vec![StateIssue::Health { player: 0, health: 500 }]
+    );
+    assert_eq!(try_decode_state(&bad, &map, true).unwrap().players[0].health, MAX_HEALTH);
+}
+
+/// Every prefix of every format, lagged or not, two players or four, decodes or fails with
+/// `Truncated`: none panics.
+#[test]
+fn decode_state_checked_never_panics_on_prefixes() {
+    let map = arena_map();
+    let lagged = create_initial_state_with(1, &map, &FpMatchConfig { lag_ticks: [3, 0], ..FpMatchConfig::default() });
+    let wide = create_initial_state_with(5, &map, &FpMatchConfig { lag_ticks: [2, 1], ..four_player_cfg() });
+    for state in [&busy_state(&map), &lagged] {
+        for format in [StateFormat::V1, StateFormat::V2, StateFormat::V3, StateFormat::V4] {
+            let bytes = encode_state_as(state, format);
+            assert_eq!(hash_state(&decode_state_checked(&bytes).unwrap()), hash_state(state));
+            for len in 0..bytes.len() {
+                if let Err(issue) = decode_state_checked(&bytes[..len]) {
+                    assert_eq!(issue, StateIssue::Truncated, "{format:?} len {len}");
+                } else {
+                    assert_eq!(format, StateFormat::V1, "only v1 has optional trailing groups");
+                }
+            }
+        }
+    }
+    let bytes = encode_state(&wide);
+    assert_eq!(decode_state_checked(&bytes).unwrap().player_count, 4);
+    for len in 0..bytes.len() {
+        assert_eq!(decode_state_checked(&bytes[..len]).unwrap_err(), StateIssue::Truncated, "len {len}");
+    }
+}
+
+#[test]
+fn decode_state_checked_rejects_counts_past_the_caps() {
+    let state = busy_state(&arena_map());
+    assert!(state.proj_count > 0);
+    let bytes = encode_state(&state);
+    let proj_at = 8 + 2 * V2_PLAYER_BYTES;
+    let pickup_at = proj_at + 1 + state.proj_count as usize * V2_PROJ_BYTES;
+    assert_eq!(bytes[pickup_at], state.pickup_count);
+
+    let mut over = bytes.clone();
+    over[proj_at] = MAX_PROJECTILES as u8 + 1;
+    assert_eq!(
+        decode_state_checked(&over).unwrap_err(),
+        StateIssue::TooManyProjectiles(MAX_PROJECTILES as u8 + 1)
+    );
+    let mut over = bytes.clone();
+    over[pickup_at] = MAX_WEAPON_PICKUPS as u8 + 1;
+    assert_eq!(
+        decode_state_checked(&over).unwrap_err(),
+        StateIssue::TooManyPickups(MAX_WEAPON_PICKUPS as u8 + 1)
+    );
+    let mut over = bytes.clone();
+    let health_at = bytes.len() - MAP_ENCODED_LEN - V4_EXTRA_BYTES;
+    over[health_at] = MAX_HEALTH_PICKUPS as u8 + 1;
+    assert_eq!(
+        decode_state_checked(&over).unwrap_err(),
+        StateIssue::TooManyHealthPickups(MAX_HEALTH_PICKUPS as u8 + 1)
+    );
+    let mut wide = bytes.clone();
+    wide[..4].copy_from_slice(&V4_WIDE_TAG.to_le_bytes());
+    assert!(decode_state_checked(&wide).is_err());
+}
+
+/// The arena with health packs on the ground, one under each player's spawn.
+fn health_map() -> Map {
+    let mut map = arena_map();
+    for (hs, sp) in map.health_spawns.iter_mut().zip(&map.spawns) {
+        *hs = SpawnPoint { x: sp.x + PLAYER_WIDTH / 2, y: sp.y + PLAYER_HEIGHT / 2 };
+    }
+    map.health_spawn_count = 2;
+    map
+}
+
+#[test]
+fn health_pickups_heal_to_the_cap_and_respawn() {
+    let map = health_map();
+    let mut state = create_initial_state(3, &map);
+    assert_eq!(state.health_pickup_count, 2);
+    assert!(state.health_pickups[..2].iter().all(|hp| hp.respawn_timer == 0));
+
+    // At full health the pack stays put
+    step_mut(&mut state, &[NULL_INPUT; 2]);
+    assert_eq!(state.players[0].health, MAX_HEALTH);
+    assert_eq!(state.health_pickups[0].respawn_timer, 0);
+
+    // Hurt players heal, never past the cap
+    state.players[0].health = MAX_HEALTH - HEALTH_PICKUP_HEAL - 10;
+    state.players[1].health = MAX_HEALTH - 10;
+    step_mut(&mut state, &[NULL_INPUT; 2]);
+    assert_eq!(state.players[0].health, MAX_HEALTH - 10);
+    assert_eq!(state.players[1].health, MAX_HEALTH);
+    assert!(state.health_pickups[..2].iter().all(|hp| hp.respawn_timer == HEALTH_PICKUP_RESPAWN_TICKS - 1));
+
+    // Taken packs don't heal until they respawn
+    state.players[0].health = 10;
+    for _ in 0..HEALTH_PICKUP_RESPAWN_TICKS - 1 {
+        step_mut(&mut state, &[NULL_INPUT; 2]);
+    }
+    assert_eq!(state.players[0].health, 10);
+    assert_eq!(state.health_pickups[0].respawn_timer, 0);
+    step_mut(&mut state, &[NULL_INPUT; 2]);
+    assert_eq!(state.players[0].health, 10 + HEALTH_PICKUP_HEAL);
+    assert_eq!(validate_state(&state), Ok(()));
+}
+
+#[test]
+fn health_pickups_are_deterministic_and_round_trip_v4() {
+    let map = health_map();
+    let run = || {
+        let mut state = create_initial_state(9, &map);
+        state.players[0].health = 20;
+        state.players[1].health = 40;
+        for t in 0..400u32 {
+            let dir = if t % 120 < 60 { button::RIGHT } else { button::LEFT };
+            step_mut(&mut state, &[FpInput { buttons: dir, aim_x: 0, aim_y: 0 }, NULL_INPUT]);
+        }
+        state
+    };
+    let state = run();
+    assert_eq!(hash_state(&state), hash_state(&run()));
+    assert!(state.health_pickups[..2].iter().any(|hp| hp.respawn_timer > 0));
+
+    let bytes = encode_state(&state);
+    assert_eq!(bytes[..4], (-5i32).to_le_bytes());
+    let decoded = decode_state_on(&bytes, &map).unwrap();
+    assert_eq!(decoded.health_pickup_count, 2);
+    assert_eq!(decoded.health_pickups, state.health_pickups);
+    assert_eq!(decoded.map.used_health_spawns(), map.used_health_spawns());
+    assert_eq!(encode_state(&decoded), bytes);
+
+    // A mirrored match reflects the packs with the map
+    let mirrored = create_initial_state(9, &mirror_map(&map));
+    assert_eq!(mirrored.health_pickups[0].x, map.width - map.health_spawns[0].x);
+}
+
+#[test]
+fn v3_states_decode_with_no_health_pickups() {
+    let state = create_initial_state(4, &health_map());
+    let decoded = decode_state(&encode_state_as(&state, StateFormat::V3));
+    assert_eq!(decoded.health_pickup_count, 0);
+    assert!(decoded.map.used_health_spawns().is_empty());
+    assert_eq!(validate_state(&decoded), Ok(()));
+
+    // Without health spawns, v3 and v4 carry the same map and nothing is lost
+    let arena = create_initial_state(4, &arena_map());
+    let decoded = decode_state(&encode_state_as(&arena, StateFormat::V3));
+    assert_eq!(encode_state(&decoded), encode_state(&arena));
+}
+
+#[test]
+fn invincibility_source_tracks_bit() {
+    let map = arena_map();
+    let mut state = create_initial_state(3, &map);
+    grant_invincibility(&mut state.players[0], invincibility::RESPAWN, 5);
+    grant_invincibility(&mut state.players[1], invincibility::START_GRACE, 50);
+    assert_eq!(validate_state(&state), Ok(()));
+
+    // Encoded, hashed and decoded with the state
+    let bytes = encode_state(&state);
+    assert_eq!(decode_state(&bytes).players[1].invincibility_source, invincibility::START_GRACE);
+    let mut other = state.clone();
+    other.players[1].invincibility_source = invincibility::SCRIPTED;
+    assert_ne!(hash_state(&other), hash_state(&state));
+
+    // Cleared together with the bit when the timer runs out
+    for _ in 0..5 {
+        assert_eq!(state.players[0].invincibility_source, invincibility::RESPAWN);
+        step_mut(&mut state, &[NULL_INPUT, NULL_INPUT]);
+    }
+    assert_eq!(state.players[0].state_flags & flag::INVINCIBLE, 0);
+    assert_eq!(state.players[0].invincibility_source, invincibility::NONE);
+    assert_eq!(state.players[1].invincibility_source, invincibility::START_GRACE);
+
+    // ...and on death (zone damage ignores invincibility)
+    state.players[1].x = 0;
+    state.cfg_sudden_death = state.tick;
+    while state.players[1].state_flags & flag::ALIVE != 0 {
+        step_mut(&mut state, &[NULL_INPUT, NULL_INPUT]);
+    }
+    assert_eq!(state.players[1].invincibility_source, invincibility::NONE);
+}
+
+#[test]
+fn validate_rejects_inconsistent_invincibility() {
+    let map = arena_map();
+    let mut state = create_initial_state(3, &map);
+    state.players[0].state_flags |= flag::INVINCIBLE;
+    state.players[1].invincibility_source = invincibility::RESPAWN;
+    assert_eq!(
+        validate_state(&state),
+        Err(vec![
+            StateIssue::InvincibilitySource {
+                player: 0, source: invincibility::NONE, flags: flag::ALIVE | flag::INVINCIBLE,
+            },
+            StateIssue::InvincibilitySource {
+                player: 1, source: invincibility::RESPAWN, flags: flag::ALIVE,
+            },
+        ])
+    );
+    sanitize_state(&mut state);
+    assert_eq!(state.players[0].invincibility_source, invincibility::SCRIPTED);
+    assert_eq!(state.players[1].invincibility_source, invincibility::NONE);
+    assert_eq!(state.players[0].state_flags & flag::INVINCIBLE, flag::INVINCIBLE);
+}
+
+/// A two-player tick scripted by internal index, in the registered order `step_mut` reads.
+fn registered_tick(state: &State, mut inputs: [FpInput; 2]) -> [FpInput; 2] {
+    if state.side_swap {
+        inputs.swap(0, 1);
+    }
+    inputs
+}
+
+/// P0 riding P1, with P1 standing at (x, y) and auto-running right for a long stretch.
+fn riding_state(map: &Map, x: Fp, y: Fp) -> State {
+    let mut state = create_initial_state(5, map);
+    state.players[1].x = x;
+    state.players[1].y = y;
+    state.players[1].grounded = true;
+    state.players[1].stomped_by = 0;
+    state.players[1].stomp_auto_run_dir = 1;
+    state.players[1].stomp_auto_run_timer = STOMP_AUTO_RUN_MAX;
+    state.players[0].x = x;
+    state.players[0].y = y - PLAYER_HEIGHT;
+    state.players[0].stomping_on = 1;
+    state.players[0].grounded = true;
+    state
+}
+
+#[test]
+fn prev_buttons_mismatch_flags_the_diverging_player() {
+    let map = arena_map();
+    let inputs = [FpInput { buttons: button::JUMP | button::RIGHT, aim_x: 1, aim_y: 0 }, NULL_INPUT];
+    let mut state = create_initial_state(42, &map);
+    step_mut(&mut state, &inputs);
+    assert_eq!(prev_buttons_mismatch(&state, &inputs), 0);
+
+    state.prev_buttons[0] = button::RIGHT;
+    assert_eq!(prev_buttons_mismatch(&state, &inputs), 0b01);
+    state.prev_buttons[1] = button::LEFT;
+    assert_eq!(prev_buttons_mismatch(&state, &inputs), 0b11);
+
+    // Once decided, the loser's buttons aren't recorded
+    state.match_over = true;
+    state.winner = 0;
+    assert_eq!(prev_buttons_mismatch(&state, &inputs), 0b01);
+    state.prev_buttons[0] = inputs[0].buttons;
+    assert_eq!(prev_buttons_mismatch(&state, &inputs), 0);
+}
+
+#[test]
+fn stomp_rider_detaches_on_jump_edge() {
+    let map = arena_map();
+    let jump = [FpInput { buttons: button::JUMP, aim_x: 0, aim_y: 0 }, NULL_INPUT];
+
+    // Held JUMP (no edge) keeps riding
+    let mut state = riding_state(&map, fp(400), fp(480));
+    state.prev_buttons[0] = button::JUMP;
+    let tick = registered_tick(&state, jump);
+    step_mut(&mut state, &tick);
+    assert_eq!(state.players[0].stomping_on, 1);
+
+    // Fresh press steps off without the shake-off bounce
+    let mut state = riding_state(&map, fp(400), fp(480));
+    let tick = registered_tick(&state, jump);
+    step_mut(&mut state, &tick);
+    assert_eq!(state.players[0].stomping_on, -1);
+    assert_eq!(state.players[0].vy, 0);
+    assert_eq!(state.players[1].stomped_by, -1);
+    assert_eq!(state.players[1].stomp_cooldown, STOMP_COOLDOWN_TICKS);
+    assert_eq!(state.players[1].stomp_cooldown_from, 0);
+
+    // Cooldown stops the rider landing straight back on
+    for _ in 0..20 {
+        step_mut(&mut state, &[NULL_INPUT, NULL_INPUT]);
+        assert_eq!(state.players[0].stomping_on, -1);
+    }
+    // and forgets the rider once it runs out
+    while state.players[1].stomp_cooldown > 0 {
+        assert_eq!(state.players[1].stomp_cooldown_from, 0);
+        step_mut(&mut state, &[NULL_INPUT, NULL_INPUT]);
+    }
+    assert_eq!(state.players[1].stomp_cooldown_from, -1);
+}
+
+/// Three players: P0 steps off P1's head, then both P0 and P2 drop onto it during P1's
+/// cooldown. Only P0, the rider P1 got rid of, is kept off.
+#[test]
+fn stomp_cooldown_only_keeps_off_the_last_rider() {
+    let map = arena_map();
+    let cfg = FpMatchConfig { player_count: 3, ..FpMatchConfig::default() };
+    let drop_onto_p1 = |attacker: usize| {
+        let mut state = create_initial_state_with(5, &map, &cfg);
+        for _ in 0..5 {
+            step_mut(&mut state, &[NULL_INPUT; 3]);
+        }
+        let victim = state.players[1];
+        assert!(victim.grounded);
+        state.players[1].stomp_cooldown = STOMP_COOLDOWN_TICKS;
+        state.players[1].stomp_cooldown_from = 0;
+        state.players[attacker].x = victim.x;
+        state.players[attacker].y = victim.y - PLAYER_HEIGHT - fp(2);
+        state.players[attacker].vy = fp(3);
+        state.players[attacker].grounded = false;
+        step_mut(&mut state, &[NULL_INPUT; 3]);
+        state
+    };
+
+    let blocked = drop_onto_p1(0);
+    assert_eq!((blocked.players[0].stomping_on, blocked.players[1].stomped_by), (-1, -1));
+    let ridden = drop_onto_p1(2);
+    assert_eq!((ridden.players[2].stomping_on, ridden.players[1].stomped_by), (1, 2));
+    crate::invariants::invariants(&ridden).unwrap();
+}
+
+#[test]
+fn stomp_cooldown_rider_survives_encoding() {
+    let map = arena_map();
+    let mut state = riding_state(&map, fp(400), fp(480));
+    let jump = FpInput { buttons: button::JUMP, aim_x: 0, aim_y: 0 };
+    let tick = registered_tick(&state, [jump, NULL_INPUT]);
+    step_mut(&mut state, &tick);
+    assert_eq!((state.players[1].stomp_cooldown_from, state.players[0].stomp_cooldown_from), (0, -1));
+    let decoded = decode_state(&encode_state(&state));
+    assert_eq!(decoded.players[1].stomp_cooldown_from, 0);
+    assert_eq!(hash_state(&decoded), hash_state(&state));
+
+    // v1/v2 states don't carry it: of two players, the one to keep off is the other
+    for format in [StateFormat::V1, StateFormat::V2] {
+        let legacy = decode_state(&encode_state_as(&state, format));
+        assert_eq!(legacy.players[1].stomp_cooldown_from, 0);
+        assert_eq!(legacy.players[0].stomp_cooldown_from, -1);
+    }
+}
+
+#[test]
+fn stomp_rider_detaches_after_victim_falls() {
+    let map = arena_map();
+    // Victim near the right edge of the middle platform (352..608, top 304)
+    let mut state = riding_state(&map, fp(580), fp(304) - PLAYER_HEIGHT);
+    let idle = [NULL_INPUT, NULL_INPUT];
+
+    while state.players[1].grounded {
+        step_mut(&mut state, &idle);
+        assert_eq!(state.players[0].stomping_on, 1, "still on the platform");
+    }
+    // First airborne tick counted; the pair falls together for the allowance...
+    assert_eq!(state.players[1].stomp_airborne_ticks, 1);
+    for _ in 1..STOMP_AIRBORNE_DETACH_TICKS {
+        step_mut(&mut state, &idle);
+        assert_eq!(state.players[0].stomping_on, 1);
+    }
+    assert_eq!(state.players[1].stomp_airborne_ticks, STOMP_AIRBORNE_DETACH_TICKS);
+    // ...then the rider drops off
+    step_mut(&mut state, &idle);
+    assert_eq!(state.players[0].stomping_on, -1);
+    assert_eq!(state.players[1].stomped_by, -1);
+    assert_eq!(state.players[1].stomp_airborne_ticks, 0);
+    assert_eq!(state.players[1].stomp_cooldown, STOMP_COOLDOWN_TICKS);
+
+    let decoded = decode_state(&encode_state(&state));
+    assert_eq!(hash_state(&decoded), hash_state(&state));
+}
+
+#[test]
+fn stomp_mount_killed_by_the_zone_drops_its_rider() {
+    let map = arena_map();
+    let mut state = riding_state(&map, fp(400), fp(480));
+    // The zone's kill leaves the link; the next tick is a stomp-damage tick
+    state.players[1].health = 0;
+    state.players[1].lives = 0;
+    state.players[1].state_flags = 0;
+    state.tick = STOMP_DAMAGE_INTERVAL - 1;
+    step_mut(&mut state, &[NULL_INPUT, NULL_INPUT]);
+    assert_eq!(state.players[0].stomping_on, -1);
+    assert_eq!(state.players[1].stomped_by, -1);
+    assert_eq!((state.players[1].lives, state.score[0]), (0, 0));
+}
+
+#[test]
+fn stomp_mount_that_loses_its_rider_keeps_its_own_mount() {
+    let map = arena_map();
+    let cfg = FpMatchConfig { player_count: 3, ..FpMatchConfig::default() };
+    let mut state = create_initial_state_with(5, &map, &cfg);
+    // P2 on P1 on P0, P0 standing on the ground
+    let (x, y) = (fp(400), fp(480));
+    for (i, p) in state.players[..3].iter_mut().enumerate() {
+        p.x = x;
+        p.y = y - i as Fp * PLAYER_HEIGHT;
+        p.grounded = true;
+        p.stomping_on = i as i32 - 1;
+        p.stomped_by = if i < 2 { i as i32 + 1 } else { -1 };
+        p.stomp_auto_run_dir = 1;
+        p.stomp_auto_run_timer = STOMP_AUTO_RUN_MAX;
+    }
+    let jump = FpInput { buttons: button::JUMP, aim_x: 0, aim_y: 0 };
+    step_mut(&mut state, &[NULL_INPUT, NULL_INPUT, jump]);
+    assert_eq!((state.players[2].stomping_on, state.players[1].stomped_by), (-1, -1));
+    assert_eq!((state.players[1].stomping_on, state.players[0].stomped_by), (0, 1));
+    crate::invariants::invariants(&state).unwrap();
+}
+
+#[test]
+fn stomp_start_break_and_detach_are_recorded() {
+    let map = arena_map();
+    let stomps = |events: &TickEvents| events.stomps.iter().copied().collect::<Vec<_>>();
+    let event = |kind| StompEvent { kind, rider: 0, victim: 1 };
+
+    // Player 0 drops onto player 1's head
+    let mut state = create_initial_state(5, &map);
+    for _ in 0..5 {
+        step_mut(&mut state, &[NULL_INPUT; 2]);
+    }
+    let victim = state.players[1];
+    assert!(victim.grounded);
+    state.players[0].x = victim.x;
+    state.players[0].y = victim.y - PLAYER_HEIGHT - fp(2);
+    state.players[0].vy = fp(3);
+    state.players[0].grounded = false;
+    let events = step_mut_events(&mut state, &[NULL_INPUT; 2]);
+    assert_eq!(stomps(&events), [event(stomp_event::STARTED)]);
+    assert_eq!(state.players[0].stomping_on, 1);
+
+    // Player 1 shakes it off
+    let mut broken = Vec::new();
+    for t in 0..120 {
+        let dir = if t % 2 == 0 { button::LEFT } else { button::RIGHT };
+        let shake = registered_tick(&state, [NULL_INPUT, FpInput { buttons: dir, aim_x: 0, aim_y: 0 }]);
+        let events = step_mut_events(&mut state, &shake);
+        broken.extend(stomps(&events));
+        if state.players[1].stomped_by < 0 { break; }
+    }
+    assert_eq!(broken, [event(stomp_event::BROKEN)]);
+
+    // The rider jumps off
+    let mut state = riding_state(&map, fp(400), fp(480));
+    let jump = registered_tick(&state, [FpInput { buttons: button::JUMP, aim_x: 0, aim_y: 0 }, NULL_INPUT]);
+    let events = step_mut_events(&mut state, &jump);
+    assert_eq!(stomps(&events), [event(stomp_event::DETACHED)]);
+}
+
+fn map_eq(a: &Map, b: &Map) -> bool {
+    let pt = |p: &SpawnPoint| (p.x, p.y);
+    a.width == b.width
+        && a.height == b.height
+        && a.platforms.iter().zip(&b.platforms)
+            .all(|(p, q)| {
+                (p.x, p.y, p.width, p.height, p.one_way, p.hazard_damage)
+                    == (q.x, q.y, q.width, q.height, q.one_way, q.hazard_damage)
+            })
+        && a.spawns.iter().map(pt).eq(b.spawns.iter().map(pt))
+        && a.weapon_spawns.iter().map(pt).eq(b.weapon_spawns.iter().map(pt))
+}
+
+#[test]
+fn mirror_map_twice_is_identity() {
+    let map = arena_map();
+    let m = mirror_map(&map);
+    assert!(!map_eq(&map, &m));
+    assert!(map_eq(&map, &mirror_map(&m)));
+    // P0's spawn lands on the right half
+    assert!(m.spawns[0].x > map.width / 2);
+    assert_eq!(m.spawns[0].x, map.width - map.spawns[0].x - PLAYER_WIDTH);
+    assert_eq!(m.platforms[1].x + m.platforms[1].width, map.width - map.platforms[1].x);
+}
+
+#[test]
+fn generated_map_is_a_function_of_its_seed() {
+    let params = MapGenParams::default();
+    let map = generate_map(1234, params);
+    assert_eq!(encode_map(&map), encode_map(&generate_map(1234, params)));
+    assert_eq!(map_hash(&map), map_hash(&generate_map(1234, params)));
+    let distinct: std::collections::HashSet<_> = (0..20).map(|seed| map_hash(&generate_map(seed, params))).collect();
+    assert!(distinct.len() > 15, "only {} layouts from 20 seeds", distinct.len());
+    // Pairs mirror each other, and so do the two players' spawns
+    assert_eq!(map.spawns[1].x, map.width - map.spawns[0].x - PLAYER_WIDTH);
+    assert_eq!(map.spawns[0].y, map.spawns[1].y);
+}
+
+#[test]
+fn generated_maps_pass_validation() {
+    let params = MapGenParams::default();
+    let mut bare = 0;
+    for seed in 0..1000 {
+        let map = generate_map(seed, params);
+        assert_eq!(validate_map(&map), Ok(()), "seed {seed}");
+        bare += map.platforms[1..].iter().all(|p| *p == EMPTY_PLATFORM) as u32;
+    }
+    assert!(bare < 10, "{bare} seeds fell back to the bare ground");
+    // Odd, even and over-full platform counts all fit
+    for floating in [0, 1, 4, 7, 20] {
+        let params = MapGenParams { floating, ..params };
+        for seed in 0..50 {
+            assert_eq!(validate_map(&generate_map(seed, params)), Ok(()), "seed {seed}, {floating} floating");
+        }
+    }
+    // A generated map plays
+    let mut state = create_initial_state(5, &generate_map(77, params));
+    for _ in 0..600 {
+        let next = step(&state, &[FpInput { buttons: button::RIGHT | button::JUMP, aim_x: 1, aim_y: 0 }, NULL_INPUT]);
+        crate::invariants::transition_invariants(&state, &next).unwrap();
+        state = next;
+    }
+}
+
+#[test]
+fn validate_map_rejects_broken_layouts() {
+    assert_eq!(validate_map(&arena_map()), Ok(()));
+
+    let mut overlap = arena_map();
+    overlap.platforms[6] = Platform { x: fp(150), y: fp(420), width: fp(40), height: fp(16), one_way: true, hazard_damage: 0 };
+    assert_eq!(validate_map(&overlap), Err(vec![MapIssue::PlatformOverlap { a: 1, b: 6 }]));
+
+    let mut stranded = arena_map();
+    stranded.platforms[6] = Platform { x: fp(400), y: fp(40), width: fp(64), height: fp(16), one_way: true, hazard_damage: 0 };
+    assert_eq!(validate_map(&stranded), Err(vec![MapIssue::Unreachable { index: 6 }]));
+
+    let mut outside = arena_map();
+    outside.platforms[6] = Platform { x: fp(900), y: fp(100), width: fp(100), height: fp(16), one_way: true, hazard_damage: 0 };
+    outside.spawns[2] = SpawnPoint { x: fp(100), y: fp(500) };
+    outside.weapon_spawns[3].x = fp(-1);
+    assert_eq!(
+        validate_map(&outside),
+        Err(vec![
+            MapIssue::PlatformBounds { index: 6 },
+            MapIssue::SpawnPosition { index: 2, x: fp(100), y: fp(500) },
+            MapIssue::WeaponSpawnPosition { index: 3, x: fp(-1), y: fp(480) },
+        ])
+    );
+}
+
+#[test]
+fn validate_map_rejects_sizes_empty_maps_and_floating_weapon_spawns() {
+    for (width, height) in [(0, fp(540)), (fp(960), -fp(1)), (MAX_MAP_SIZE + 1, fp(540))] {
+        let map = Map { width, height, ..arena_map() };
+        assert_eq!(validate_map(&map), Err(vec![MapIssue::Size { width, height }]));
+    }
+    let tall = Map { height: MAX_MAP_SIZE, ..arena_map() };
+    assert_eq!(validate_map(&tall), Ok(()));
+
+    let bare = Map { platforms: [EMPTY_PLATFORM; NUM_PLATFORMS], ..arena_map() };
+    assert_eq!(validate_map(&bare), Err(vec![MapIssue::NoPlatforms]));
+
+    // Pickups hover 32px over their platform: 48px is still in reach, the sky isn't
+    let mut floating = arena_map();
+    floating.weapon_spawns[0].y = fp(416) - PLAYER_HEIGHT - PICKUP_RADIUS + 1;
+    assert_eq!(validate_map(&floating), Ok(()));
+    floating.weapon_spawns[0].y -= 1;
+    floating.weapon_spawns[2] = SpawnPoint { x: fp(20), y: fp(300) };
+    assert_eq!(
+        validate_map(&floating),
+        Err(vec![
+            MapIssue::WeaponSpawnUnreachable { index: 0, x: fp(192), y: fp(416) - PLAYER_HEIGHT - PICKUP_RADIUS },
+            MapIssue::WeaponSpawnUnreachable { index: 2, x: fp(20), y: fp(300) },
+        ])
+    );
+    // A weapon spawn inside a platform is below its top, so just as unreachable
+    floating = arena_map();
+    floating.weapon_spawns[1].y = fp(420);
+    assert_eq!(validate_map(&floating), Err(vec![MapIssue::WeaponSpawnUnreachable { index: 1, x: fp(736), y: fp(420) }]));
+}
+
+/// Swap LEFT/RIGHT and negate horizontal aim.
+fn mirror_input(i: FpInput) -> FpInput {
+    let mut buttons = i.buttons & !(button::LEFT | button::RIGHT);
+    if i.buttons & button::LEFT != 0 { buttons |= button::RIGHT; }
+    if i.buttons & button::RIGHT != 0 { buttons |= button::LEFT; }
+    FpInput { buttons, aim_x: i.aim_x.saturating_neg(), aim_y: i.aim_y }
+}
+
+#[test]
+fn mirrored_match_is_exact_reflection() {
+    let base = arena_map();
+    let cfg = FpMatchConfig { mirrored: true, ..FpMatchConfig::default() };
+    let mmap = cfg.oriented_map(&base);
+    let mut orig = create_initial_state(11, &base);
+    let mut refl = create_initial_state_with(11, &mmap, &cfg);
+    assert_eq!(refl.players[0].facing, FACING_LEFT);
+    for s in [&mut orig, &mut refl] {
+        s.players[0].weapon = WEAPON_SHOTGUN;
+        s.players[0].ammo = 99;
+        s.players[1].weapon = WEAPON_ROCKET;
+        s.players[1].ammo = 99;
+    }
+
+    let w = base.width;
+    for t in 0..MATCH_DURATION_TICKS {
+        let h = (t as u32).wrapping_mul(0x9E37_79B9);
+        let inputs = [
+            FpInput {
+                buttons: [button::RIGHT, button::LEFT, 0][(h % 3) as usize]
+                    | if h & 0x100 != 0 { button::JUMP } else { 0 }
+                    | button::SHOOT,
+                aim_x: 1,
+                aim_y: ((h >> 12) % 3) as i8 - 1,
+            },
+            FpInput {
+                buttons: [button::LEFT, 0, button::RIGHT][((h >> 4) % 3) as usize]
+                    | if h & 0x2000 != 0 { button::JUMP } else { 0 }
+                    | button::SHOOT,
+                aim_x: -1,
+                aim_y: 0,
+            },
+        ];
+        let inputs = registered_tick(&orig, inputs);
+        step_mut(&mut orig, &inputs);
+        step_mut(&mut refl, &inputs.map(mirror_input));
+        for i in 0..2 {
+            let (a, b) = (&orig.players[i], &refl.players[i]);
+            assert_eq!(b.x, w - a.x - PLAYER_WIDTH, "tick {t} player {i} x");
+            assert_eq!((b.y, b.vx, b.vy), (a.y, -a.vx, a.vy), "tick {t} player {i}");
+            assert_eq!((b.health, b.weapon, b.facing), (a.health, a.weapon, -a.facing), "tick {t} player {i}");
+        }
+        assert_eq!(refl.proj_count, orig.proj_count, "tick {t}");
+        for k in 0..orig.proj_count as usize {
+            let (a, b) = (&orig.projectiles[k], &refl.projectiles[k]);
+            assert_eq!((b.x, b.y, b.vx), (w - a.x, a.y, -a.vx), "tick {t} proj {k}");
+        }
+        if orig.match_over { break; }
+    }
+    assert!(refl.match_over);
+    assert_eq!((refl.winner, refl.score), (orig.winner, orig.score));
+    assert!(orig.score != [0; MAX_PLAYERS], "script should produce kills");
+
+    // Orientation is part of both the state hash and the config commitment
+    let cfg0 = FpMatchConfig::default();
+    assert_ne!(cfg.commitment(), cfg0.commitment());
+    let s0 = create_initial_state_with(1, &base, &cfg0);
+    let mut s1 = s0.clone();
+    s1.cfg_mirrored = true;
+    assert_ne!(hash_state(&s0), hash_state(&s1));
+    assert!(decode_state(&encode_state(&s1)).cfg_mirrored);
+}
+
+/// Saturated-projectile scenario: both players kept armed (cycling through every weapon)
+/// and firing every tick they can. Returns a digest over every tick's state hash.
+fn projectile_storm_digest(seed: u32, mirrored: bool, format: StateFormat) -> ([u8; 32], u8) {
+    let cfg = FpMatchConfig {
+        initial_lives: 99, match_duration: 1800, sudden_death: 1200, lag_ticks: [2, 0], mirrored, stamina: false,
+        active_despawn: false, player_count: 2, zone_mode: zone_mode::PINCH,
+    };
+    let map = cfg.oriented_map(&arena_map());
+    let mut state = create_initial_state_with(seed, &map, &cfg);
+    let mut digest = Sha256::new();
+    let mut max_live = 0u8;
+    for t in 0..1800i32 {
+        for i in 0..2 {
+            state.players[i].weapon = ((t / 150 + i as i32 * 2 * (t / 450 % 2)) % WEAPON_COUNT as i32) as i8;
+            state.players[i].ammo = 99;
+        }
+        let h = (t as u32).wrapping_mul(0x9E37_79B9);
+        // Odd phases: same weapon, level aim — fills the projectile array
+        let level = t / 450 % 2 == 1;
+        let aim_y = |bits: u32| if level { 0 } else { (bits % 3) as i8 - 1 };
+        let inputs = [
+            FpInput {
+                buttons: button::SHOOT
+                    | [button::RIGHT, button::LEFT, 0][(h % 3) as usize]
+                    | if h & 0x300 == 0 { button::JUMP } else { 0 },
+                aim_x: ((h >> 5) % 3) as i8 - 1,
+                aim_y: aim_y(h >> 9),
+            },
+            FpInput {
+                buttons: button::SHOOT
+                    | [button::LEFT, 0, button::RIGHT][((h >> 13) % 3) as usize]
+                    | if h & 0xC000 == 0 { button::JUMP } else { 0 },
+                aim_x: ((h >> 17) % 3) as i8 - 1,
+                aim_y: aim_y(h >> 21),
+            },
+        ];
+        step_mut(&mut state, &inputs);
+        max_live = max_live.max(state.proj_count);
+        digest.update(hash_state_as(&state, format));
+        if state.match_over { break; }
+    }
+    (digest.finalize().into(), max_live)
+}
+
+/// Golden digests recorded before the projectile-loop restructure (re-recorded whenever the
+/// state encoding grows a field or a `SIM_VERSION` bump changes them); any change to
+/// projectile movement, hits or compaction order shows up here.
+#[test]
+fn projectile_storm_golden_hashes() {
+    let hex = |d: [u8; 32]| d.iter().map(|b| format!("{b:02x}")).collect::<String>();
+    let (d, live_1) = projectile_storm_digest(1, false, StateFormat::V1);
+    assert_eq!(hex(d), "7634250587f4c1a1e74cefdceb4fdcbd0c05005638531823952deb49c8fa6f16");
+    let (d, live_2) = projectile_storm_digest(2, true, StateFormat::V1);
+    assert_eq!(hex(d), "e87003addc5d318dba6bdf72eb8cb60a6df1653ad993ac6b918f120bbbe41689");
+    // Knockback scatters the players, so one of the two runs fills the array
+    assert!(live_1.max(live_2) >= 16);
+    // Packed players: same states, different bytes
+    let (d, _) = projectile_storm_digest(1, false, StateFormat::V2);
+    assert_eq!(hex(d), "cfb063d527c5d806bf281997f1fb34781778e2aa8f7902250f6ab3e495738141");
+    let (d, _) = projectile_storm_digest(2, true, StateFormat::V2);
+    assert_eq!(hex(d), "59b10bcc05a613535116bf283abd167c861a706c1a31b07b833601544c4cb697");
+    // v2 plus the (mirrored) map
+    let (d, _) = projectile_storm_digest(1, false, StateFormat::V3);
+    assert_eq!(hex(d), "a2a5ef17085447dd9a62eb256fb1d94a47457bd5650d28067fff6c11bbf9352a");
+    let (d, _) = projectile_storm_digest(2, true, StateFormat::V3);
+    assert_eq!(hex(d), "14f4ebeeaaf3e3305f021054219f4c1abf5d36c5dd47241b79b6b047c3a48ddc");
+}
+
+/// Four players running at each other from their spawns, jumping and firing on staggered
+/// schedules.
+fn four_player_transcript(ticks: usize) -> Vec<TickInputs> {
+    (0..ticks)
+        .map(|t| {
+            core::array::from_fn(|i| {
+                let right = (t / 40 + i) % 4 < 2;
+                let jump = if (t + 7 * i) % 50 < 3 { button::JUMP } else { 0 };
+                let (dir, aim_x) = if right { (button::RIGHT, 1) } else { (button::LEFT, -1) };
+                FpInput { buttons: dir | jump | button::SHOOT, aim_x, aim_y: 0 }
+            })
+        })
+        .collect()
+}
+
+fn four_player_cfg() -> FpMatchConfig {
+    FpMatchConfig { player_count: 4, ..FpMatchConfig::default() }
+}
+
+#[test]
+fn four_player_match_is_deterministic() {
+    let cfg = four_player_cfg();
+    let map = cfg.oriented_map(&arena_map());
+    let transcript = four_player_transcript((MATCH_DURATION_TICKS + DEATH_LINGER_TICKS + 1) as usize);
+    let data = encode_raw_input_wide(11, 4, &transcript);
+    let a = run_streaming_with(&data, &cfg);
+    let b = run_streaming_with(&data, &cfg);
+    assert_eq!(hash_state(&a.state), hash_state(&b.state));
+    assert_eq!(a.transcript_hash, hash_transcript_wide(&transcript, 4));
+    assert!(a.state.match_over);
+    assert!((0..4).contains(&a.state.winner));
+
+    // Stepping the decoded transcript by hand lands on the same state, and every player
+    // (not just the first two) takes part
+    let (seed, n, decoded) = decode_raw_input_wide(&data);
+    assert_eq!((seed, n, decoded.len()), (11, 4, transcript.len()));
+    let mut state = create_initial_state_with(seed, &map, &cfg);
+    assert_eq!(state.active_players().len(), 4);
+    for (t, inputs) in decoded.iter().enumerate() {
+        step_mut(&mut state, inputs);
+        if t == 30 {
+            for (i, p) in state.active_players().iter().enumerate() {
+                assert_eq!(p.id, i as i32);
+                assert_ne!(p.x, map.spawns[i].x, "player {i} never moved");
+            }
+        }
+        if state.match_over { break; }
+    }
+    assert_eq!(hash_state(&state), hash_state(&a.state));
+}
+
+#[test]
+fn wide_raw_input_keeps_two_player_bytes() {
+    let transcript = duel_transcript(300);
+    let input = FpProverInput { session_id: 0, seed: 9, mirrored: false, transcript: transcript.clone(), map: None, gen_seed: None, rules: None };
+    let widened: Vec<TickInputs> = transcript.iter().map(|t| widen(*t, NULL_INPUT)).collect();
+    let raw = encode_raw_input(&input);
+    assert_eq!(encode_raw_input_wide(9, 2, &widened), raw);
+    let (seed, n, decoded) = decode_raw_input_wide(&raw);
+    assert_eq!((seed, n), (9, 2));
+    assert_eq!(hash_transcript_wide(&decoded, 2), hash_transcript(&transcript));
+    assert_eq!(run_streaming(&raw).transcript_hash, hash_transcript(&transcript));
+
+    let raw3 = encode_raw_input_wide(9, 3, &widened);
+    assert_eq!(raw3.len(), 12 + 300 * 3 * INPUT_BYTES);
+    assert_eq!(
+        RawInputHeader::parse(&raw3),
+        RawInputHeader { seed: 9, player_count: 3, tick_count: 300, ticks_at: 12 }
+    );
+    let (_, n, decoded) = decode_raw_input_wide(&raw3);
+    assert_eq!(n, 3);
+    assert_eq!(hash_transcript_wide(&decoded, 3), hash_transcript_wide(&widened, 3));
+}
+
+#[test]
+fn input_runs_roundtrip_and_compress_held_inputs() {
+    let transcript = duel_transcript(300);
+    let runs = encode_input_runs(9, &transcript);
+    let (seed, decoded) = decode_input_runs(&runs).unwrap();
+    assert_eq!(seed, 9);
+    assert_eq!(hash_transcript(&decoded), hash_transcript(&transcript));
+    // One run per half-second of jumping or not
+    assert_eq!(runs.len(), 4 + 10 * INPUT_RUN_BYTES);
+
+    // Runs cap at u16::MAX ticks
+    let idle = vec![[NULL_INPUT; 2]; u16::MAX as usize + 5];
+    let runs = encode_input_runs(1, &idle);
+    assert_eq!(runs.len(), 4 + 2 * INPUT_RUN_BYTES);
+    assert_eq!(decode_input_runs(&runs).unwrap().1.len(), idle.len());
+
+    assert!(decode_input_runs(&runs[..runs.len() - 1]).is_none());
+    assert!(decode_input_runs(&[1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]).is_none());
+    assert!(decode_input_runs(&[1, 0]).is_none());
+}
+
+#[test]
+fn four_player_state_roundtrips_and_hashes() {
+    for lag_ticks in [[0, 0], [2, 1]] {
+        let cfg = FpMatchConfig { lag_ticks, ..four_player_cfg() };
+        let mut state = create_initial_state_with(5, &arena_map(), &cfg);
+        for inputs in &four_player_transcript(200) {
+            step_mut(&mut state, inputs);
+        }
+        state.score = [1, 2, 3, 4];
+        state.env_kills = [0, 1, 0, 2];
+        state.players[3].weapon = WEAPON_SMG;
+        state.players[3].ammo = 7;
+        state.prev_buttons[2] = button::JUMP;
+
+        let bytes = encode_state(&state);
+        assert_eq!(bytes[..4], V4_WIDE_TAG.to_le_bytes());
+        assert_eq!(bytes.len(), encoded_state_len(&state));
+        assert_eq!(check_encoded_len(&bytes), Ok(()));
+        assert_eq!(check_encoded_len(&bytes[..bytes.len() - 1]), Err(StateIssue::Truncated));
+
+        let decoded = decode_state(&bytes);
+        assert_eq!(decoded.player_count, 4);
+        assert_eq!(encode_state(&decoded), bytes);
+        assert_eq!(hash_state(&decoded), hash_state(&state));
+        assert_eq!((decoded.score, decoded.env_kills), (state.score, state.env_kills));
+        assert_eq!(decoded.prev_buttons, state.prev_buttons);
+        if lag_ticks != [0, 0] {
+            // The history ring is only encoded while lag compensation is on
+            assert_eq!(decoded.pos_history_x, state.pos_history_x);
+        }
+        assert_eq!(decoded.players[3].ammo, 7);
+        assert!(validate_state(&decoded).is_ok());
+
+        // The same state as a two-player match hashes differently
+        let mut two = state.clone();
+        two.player_count = 2;
+        assert_ne!(hash_state(&two), hash_state(&state));
+    }
+
+    let proof = ChunkProof {
+        state_hash_in: [1; 32], state_hash_out: [2; 32], input_hash: [3; 32],
+        tick_start: 0, tick_end: 360, scores: [1, 2, 3, 4], match_over: true, winner: 3,
+        env_kills: [0, 1, 0, 2], map_hash: [5; 32], health: [0, 100, -1, 35], lives: [0, 3, 1, 2],
+        sim_version: SIM_VERSION, pickup_stats_commit: [6; 32],
+    };
+    let bytes: Vec<u8> = proof.to_wide_words().iter().flat_map(|w| w.to_le_bytes()).collect();
+    let back = ChunkProof::from_journal_bytes(&bytes);
+    assert_eq!((back.scores, back.env_kills, back.winner), (proof.scores, proof.env_kills, 3));
+    assert_eq!((back.health, back.lives), (proof.health, proof.lives));
+    assert_eq!(back.map_hash, [5; 32]);
+    let narrow: Vec<u8> = proof.to_words().iter().flat_map(|w| w.to_le_bytes()).collect();
+    let narrow = ChunkProof::from_journal_bytes(&narrow);
+    assert_eq!((narrow.scores, narrow.health, narrow.lives), ([1, 2, 0, 0], [0, 100, 0, 0], [0, 3, 0, 0]));
+    assert_eq!((back.sim_version, narrow.sim_version), (SIM_VERSION, SIM_VERSION));
+    assert_eq!((back.pickup_stats_commit, narrow.pickup_stats_commit), ([6; 32], [6; 32]));
+
+    // One from before the pickup stats reads with their commitment zeroed
+    let unstated: Vec<u8> = proof.to_words()[..45].iter().flat_map(|w| w.to_le_bytes()).collect();
+    let unstated = ChunkProof::from_journal_bytes(&unstated);
+    assert_eq!((unstated.sim_version, unstated.pickup_stats_commit), (SIM_VERSION, [0; 32]));
+
+    // A journal from before the version word reads as version 0
+    let legacy: Vec<u8> = proof.to_words()[..44].iter().flat_map(|w| w.to_le_bytes()).collect();
+    let legacy = ChunkProof::from_journal_bytes(&legacy);
+    assert_eq!((legacy.sim_version, legacy.lives), (0, narrow.lives));
+}
+
+#[test]
+fn recent_hashes_wrap_and_forget_rewound_ticks() {
+    let mut state = create_initial_state(42, &arena_map());
+    let mut recent = RecentHashes::new();
+    recent.record(&state);
+    let mut at_15 = None;
+    for _ in 0..20 {
+        step_mut_recorded(&mut state, &[NULL_INPUT; 2], &mut recent);
+        if state.tick == 15 {
+            at_15 = Some(state.clone());
+        }
+    }
+    // Ticks 0..=20 went in; the ring kept the last 16
+    assert_eq!(recent.len(), RECENT_HASH_COUNT);
+    let ticks: Vec<i32> = recent.iter().map(|(t, _)| t).collect();
+    assert_eq!(ticks, (5..=20).collect::<Vec<_>>());
+    assert_eq!(recent.get(4), None);
+    assert_eq!(recent.get(20), Some(checksum_state(&state)));
+
+    // Rolling back to tick 15 and stepping again replaces 16.. instead of duplicating them
+    let mut replay = at_15.unwrap();
+    let jump = FpInput { buttons: button::JUMP, aim_x: 0, aim_y: 0 };
+    step_mut_recorded(&mut replay, &[jump, NULL_INPUT], &mut recent);
+    assert_eq!(recent.len(), 12);
+    assert_eq!(recent.iter().last(), Some((16, checksum_state(&replay))));
+    recent.clear();
+    assert!(recent.is_empty());
+}
+
+#[test]
+fn checksum_agrees_across_replays_and_flags_any_change() {
+    let transcript = duel_transcript(300);
+    let map = arena_map();
+    let (mut a, mut b) = (create_initial_state(7, &map), create_initial_state(7, &map));
+    for tick in &transcript {
+        step_mut(&mut a, tick);
+        step_mut(&mut b, tick);
+        assert_eq!(checksum_state(&a), checksum_state(&b));
+    }
+
+    // One flipped field, anywhere the state hash covers, is a different checksum
+    let base = checksum_state(&a);
+    let mut flipped = a.clone();
+    flipped.players[1].x += 1;
+    assert_ne!(checksum_state(&flipped), base);
+    let mut flipped = a.clone();
+    flipped.rng_state ^= 1;
+    assert_ne!(checksum_state(&flipped), base);
+    let mut flipped = a.clone();
+    flipped.pickup_stats.damage_pacing[0] ^= 1;
+    assert_ne!(checksum_state(&flipped), base);
+}
+
+#[test]
+fn recent_hashes_check_overlap_with_another_timeline() {
+    let start = create_initial_state(42, &arena_map());
+    let run = |diverge_at: i32| {
+        let (mut state, mut recent) = (start.clone(), RecentHashes::new());
+        let right = FpInput { buttons: button::RIGHT, aim_x: 1, aim_y: 0 };
+        for tick in 0..30 {
+            let p0 = if tick >= diverge_at { right } else { NULL_INPUT };
+            step_mut_recorded(&mut state, &[p0, NULL_INPUT], &mut recent);
+        }
+        recent
+    };
+    let server = run(i32::MAX);
+
+    // The same timeline agrees on every tick both hold
+    let check = run(i32::MAX).check(server.iter());
+    assert_eq!(check, HistoryCheck { overlap: RECENT_HASH_COUNT as u32, first_mismatch: None });
+    assert!(check.agrees());
+
+    // A client that pressed right from tick 20 on left the timeline at tick 21
+    let check = run(20).check(server.iter());
+    assert_eq!(check.first_mismatch, Some(21));
+    assert!(!check.agrees());
+
+    // No shared ticks proves nothing either way
+    let mut old = RecentHashes::new();
+    old.push(3, 0xDEAD);
+    assert_eq!(old.check(server.iter()), HistoryCheck::default());
+    assert!(!old.check(server.iter()).agrees());
+}
+
+/// Every tick's state hash and event-chain head, folded into one digest, over a full match
+/// of hashed inputs for every player: stomps, pickups, shots, dashes, hazards and the zone all
+/// fire.
+fn full_match_digest(seed: u32, cfg: &FpMatchConfig, hazard_damage: i32) -> [u8; 32] {
+    let mut map = arena_map();
+    map.platforms[0].hazard_damage = hazard_damage;
+    let mut state = create_initial_state_with(seed, &cfg.oriented_map(&map), cfg);
+    let mut chain = EventChain::new();
+    let mut digest = Sha256::new();
+    for t in 0..(MATCH_DURATION_TICKS + DEATH_LINGER_TICKS + 1) as u32 {
+        let inputs: TickInputs = core::array::from_fn(|i| {
+            let h = t.wrapping_mul(0x9E37_79B9) ^ (i as u32 + 1).wrapping_mul(0x85EB_CA6B);
+            let h = h ^ (h >> 16);
+            FpInput {
+                buttons: [button::LEFT, button::RIGHT, 0][(h % 3) as usize]
+                    | if h & 0x300 == 0 { button::JUMP } else { 0 }
+                    | if h & 0xC00 == 0 { button::DOWN } else { 0 }
+                    | if h & 0x1000 != 0 { button::SHOOT } else { 0 }
+                    | if h & 0x3_0000 == 0 { button::DASH } else { 0 },
+                aim_x: ((h >> 13) % 3) as i8 - 1,
+                aim_y: ((h >> 15) % 3) as i8 - 1,
+            }
+        });
+        digest.update(step_mut_chained(&mut state, &inputs, &mut chain));
+        digest.update(hash_state(&state));
+    }
+    assert!(state.match_over);
+    digest.finalize().into()
+}
+
+/// Golden digests of whole matches under each ruleset, recorded before `fp` was split into
+/// submodules. A pure refactor must leave them alone; a `SIM_VERSION` bump re-records them.
+#[test]
+fn full_match_golden_hashes() {
+    let hex = |d: [u8; 32]| d.iter().map(|b| format!("{b:02x}")).collect::<String>();
+    let every_rule = FpMatchConfig {
+        lag_ticks: [2, 1],
+        mirrored: true,
+        stamina: true,
+        active_despawn: true,
+        ..FpMatchConfig::default()
+    };
+    let cases = [
+        (3, FpMatchConfig::default(), 0),
+        (9, every_rule, 0),
+        (5, FpMatchConfig::default(), 4),
+        (11, FpMatchConfig { player_count: 4, ..FpMatchConfig::default() }, 2),
+    ];
+    let digests: Vec<String> =
+        cases.iter().map(|(seed, cfg, hazard)| hex(full_match_digest(*seed, cfg, *hazard))).collect();
+    assert_eq!(digests, [
+        "cef70d832d3a7f866c9cd7e57420ea1713a9e757430d3b589ea15457932766ad",
+        "9bb71b3090eb43d222aa80d9fed1d364bd9a0fa8cd8cd37180663da83be06960",
+        "68e076e9ecc8b7e779e07eb6a11d202a78f4c2bc5a364d6e5f93ce19a40ff7a5",
+        "90421d96a7c496c94c07cec16728c969f36a3f598adb831850ecd4839ac96445",
+    ]);
+}
+
+/// Configs at the edges of their fields' ranges: the warmup room's, everything at `i32::MAX`, and
+/// a zone that opened long before tick 0 on a match that ends as soon as it starts. Random
+/// inputs (shots, stomps, pickups) for a full match each, with every state checked so a wrapped
+/// value can't slip through, and the debug build's overflow checks armed.
+#[test]
+fn extreme_configs_play_full_matches_without_overflow() {
+    let warmup = FpMatchConfig { initial_lives: 99, match_duration: 999_999, sudden_death: 999_999, ..FpMatchConfig::default() };
+    let configs = [
+        warmup,
+        FpMatchConfig { player_count: 4, ..warmup },
+        FpMatchConfig { initial_lives: i32::MAX, match_duration: i32::MAX, sudden_death: i32::MAX, ..FpMatchConfig::default() },
+        FpMatchConfig { match_duration: i32::MAX, sudden_death: i32::MIN, zone_mode: zone_mode::DESCENT, ..FpMatchConfig::default() },
+        FpMatchConfig { initial_lives: i32::MAX, match_duration: i32::MIN, sudden_death: -1, player_count: 4, ..FpMatchConfig::default() },
+        FpMatchConfig { match_duration: 0, sudden_death: 0, stamina: true, active_despawn: true, zone_mode: zone_mode::BOX, ..FpMatchConfig::default() },
+    ];
+    for (c, cfg) in configs.iter().enumerate() {
+        let map = cfg.oriented_map(&arena_map());
+        let mut state = create_initial_state_with(c as u32, &map, cfg);
+        let mut rng = 0x9E37_79B9u32 ^ c as u32;
+        for _ in 0..2 * MATCH_DURATION_TICKS {
+            let mut inputs = [NULL_INPUT; MAX_PLAYERS];
+            for (i, input) in inputs[..cfg.player_count as usize].iter_mut().enumerate() {
+                rng ^= rng << 13;
+                rng ^= rng >> 17;
+                rng ^= rng << 5;
+                // Always firing, mostly across the map, so the matches see kills
+                let aim_x = if rng & 0x300 == 0 { (rng >> 8) as i8 } else if i % 2 == 0 { 1 } else { -1 };
+                *input = FpInput { buttons: rng as u8 | button::SHOOT, aim_x, aim_y: (rng >> 16) as i8 };
+            }
+            let prev = state.clone();
+            step_mut(&mut state, &inputs);
+            if let Err(violation) = crate::invariants::transition_invariants(&prev, &state) {
+                panic!("config {c} tick {}: {violation}", state.tick);
+            }
+            assert_eq!(state.arena_bounds(), consistent_arena_bounds(&state), "config {c}");
+        }
+    }
+}
+
+/// A warmup room's prover input: its rules ride along outside the raw input, and the replay
+/// under `match_config` plays them (99 lives, the clock running out at 900) and commits them.
+#[test]
+fn prover_input_rules_replay_a_warmup() {
+    let rules = MatchRules { initial_lives: 99, match_duration: 900, sudden_death: 900 };
+    let standard = FpProverInput { session_id: 5, seed: 42, mirrored: false, transcript: duel_transcript(1800), map: None, gen_seed: None, rules: None };
+    let warmup = FpProverInput { rules: Some(rules), ..standard.clone() };
+    assert_eq!(encode_raw_input(&warmup), encode_raw_input(&standard));
+
+    let cfg = warmup.match_config();
+    assert_eq!(MatchRules::of(&cfg), rules);
+    assert_eq!(MatchRules::from_words(rules.to_words()), rules);
+    assert_eq!(MatchRules::of(&standard.match_config()), MatchRules::default());
+    assert_ne!(cfg.commitment(), standard.match_config().commitment());
+
+    let state = run_streaming_on(&encode_raw_input(&warmup), &cfg, &warmup.base_map()).state;
+    assert!(state.match_over);
+    assert_eq!(state.tick, 900);
+    assert!(state.registered_lives()[..2].iter().all(|&l| l > INITIAL_LIVES && l <= 99));
+    assert_eq!(state.cfg_initial_lives, 99);
+}
+
+/// Transcript files round-trip a prover input, header and every tick, and read a tick at a time
+/// from any reader.
+#[test]
+fn transcript_file_round_trips() {
+    let standard = FpProverInput { session_id: 9, seed: 42, mirrored: false, transcript: duel_transcript(600), map: None, gen_seed: None, rules: None };
+    let warmup = FpProverInput {
+        mirrored: true,
+        gen_seed: Some(77),
+        rules: Some(MatchRules { initial_lives: 99, match_duration: 900, sudden_death: 900 }),
+        ..standard.clone()
+    };
+    for input in [&standard, &warmup] {
+        let mut file = Vec::new();
+        write_transcript(&mut file, input).unwrap();
+        assert_eq!(file.len(), TRANSCRIPT_HEADER_LEN + 6 * input.transcript.len());
+        assert!(is_transcript_file(&file));
+        // The ticks are the raw input's, byte for byte
+        assert_eq!(file[TRANSCRIPT_HEADER_LEN - 8..], encode_raw_input(input)[..]);
+
+        let back = read_transcript(&mut std::io::Cursor::new(&file)).unwrap();
+        assert_eq!(encode_raw_input(&back), encode_raw_input(input));
+        assert_eq!(
+            (back.session_id, back.mirrored, back.gen_seed, back.rules),
+            (input.session_id, input.mirrored, input.gen_seed, input.rules)
+        );
+        assert_eq!(back.match_config(), input.match_config());
+    }
+
+    // A custom map has no place in the file
+    let custom = FpProverInput { map: Some(arena_map()), ..standard };
+    let err = write_transcript(&mut Vec::new(), &custom).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+}
+
+#[test]
+fn corrupted_transcript_files_are_rejected() {
+    let input = FpProverInput { session_id: 9, seed: 42, mirrored: false, transcript: duel_transcript(10), map: None, gen_seed: None, rules: None };
+    let mut file = Vec::new();
+    write_transcript(&mut file, &input).unwrap();
+    let read = |bytes: &[u8]| read_transcript(&mut std::io::Cursor::new(bytes.to_vec())).unwrap_err();
+    let patched = |off: usize, word: u32| {
+        let mut bytes = file.clone();
+        bytes[off..off + 4].copy_from_slice(&word.to_le_bytes());
+        bytes
+    };
+
+    assert!(matches!(read(&patched(0, u32::from_le_bytes(*b"{\"se"))), TranscriptIssue::Magic(m) if &m == b"{\"se"));
+    assert!(!is_transcript_file(b"{\"session_id\": 1}"));
+    assert!(matches!(read(&patched(4, 2)), TranscriptIssue::Version(2)));
+    assert!(matches!(read(&patched(12, 4)), TranscriptIssue::Flags(4)));
+    assert!(matches!(read(&file[..TRANSCRIPT_HEADER_LEN - 1]), TranscriptIssue::TruncatedHeader));
+    assert!(matches!(read(&patched(36, WIDE_INPUT_TAG | 3)), TranscriptIssue::WideInput(_)));
+    assert!(matches!(read(&file[..file.len() - 1]), TranscriptIssue::Truncated { ticks: 9, tick_count: 10 }));
+    assert!(matches!(read(&patched(36, 11)), TranscriptIssue::Truncated { ticks: 10, tick_count: 11 }));
+    assert!(matches!(read(&patched(36, 9)), TranscriptIssue::TrailingBytes));
+    assert_eq!(read(&patched(4, 2)).to_string(), "transcript version 2, this build reads 1");
+}
+
+#[test]
+fn phases_are_listed_in_run_order() {
+    for (i, phase) in Phase::ALL.into_iter().enumerate() {
+        assert_eq!(phase as usize, i);
+    }
+
+    // The dispatcher, run by hand, is the gameplay tick
+    let mut stepped = pistol_shot_state(1, 10);
+    let mut by_hand = stepped.clone();
+    step_mut(&mut stepped, &[NULL_INPUT; 2]);
+    let map = Arc::clone(&by_hand.map);
+    by_hand.tick += 1;
+    let mut tick = TickScratch::new(by_hand.prev_buttons);
+    for phase in Phase::ALL {
+        run_phase(phase, &mut by_hand, &[NULL_INPUT; 2], &map, &mut tick, &mut ());
+    }
+    assert_eq!(tick.next_phase, Phase::ALL.len());
+    assert_eq!(encode_state(&by_hand), encode_state(&stepped));
+}
+
+#[cfg(debug_assertions)]
+#[test]
+#[should_panic(expected = "out of order")]
+fn phases_run_out_of_order_panic() {
+    let mut state = pistol_shot_state(1, 10);
+    let map = Arc::clone(&state.map);
+    let mut tick = TickScratch::new(state.prev_buttons);
+    run_phase(Phase::Cooldowns, &mut state, &[NULL_INPUT; 2], &map, &mut tick, &mut ());
+    run_phase(Phase::Hazards, &mut state, &[NULL_INPUT; 2], &map, &mut tick, &mut ());
+}
+
+#[test]
+fn cooldowns_run_down_for_live_players() {
+    let mut state = pistol_shot_state(1, 10);
+    for p in &mut state.players[..2] {
+        (p.shoot_cooldown, p.respawn_timer, p.stomp_cooldown, p.stomp_cooldown_from) = (3, 1, 1, 1);
+        p.state_flags |= flag::INVINCIBLE;
+        p.invincibility_source = invincibility::RESPAWN;
+    }
+    state.players[1].state_flags &= !flag::ALIVE;
+    tick_cooldowns(&mut state);
+
+    let p = state.players[0];
+    assert_eq!((p.shoot_cooldown, p.respawn_timer, p.stomp_cooldown, p.stomp_cooldown_from), (2, 0, 0, -1));
+    assert_eq!((p.state_flags & flag::INVINCIBLE, p.invincibility_source), (0, invincibility::NONE));
+    let dead = state.players[1];
+    assert_eq!((dead.shoot_cooldown, dead.respawn_timer, dead.stomp_cooldown), (3, 1, 1));
+}
+
+#[test]
+fn movement_moves_each_player_and_reports_hazards() {
+    let mut state = dash_floor_state(fp(300));
+    state.players[0].x = fp(20);
+    let map = Arc::clone(&state.map);
+    let x0 = state.players[0].x;
+    let inputs = [FpInput { buttons: button::RIGHT, aim_x: 1, aim_y: 0 }, NULL_INPUT];
+    let hazard = move_players(&mut state, &inputs, &[0; MAX_PLAYERS], &map);
+    assert_eq!(hazard, [0; MAX_PLAYERS]);
+    assert_eq!((state.players[0].vx, state.players[0].x), (ACCELERATION, x0 + ACCELERATION));
+    assert!(state.players[0].grounded);
+
+    // Standing on a hazard platform reports its damage for that player only
+    let mut map = (*map).clone();
+    map.platforms[0].hazard_damage = 7;
+    let hazard = move_players(&mut state, &[NULL_INPUT; 2], &[0; MAX_PLAYERS], &map);
+    assert_eq!(hazard[0], 7);
+}
+
+#[test]
+fn positions_go_to_the_tick_slot() {
+    let mut state = pistol_shot_state(1, 10);
+    state.tick = LAG_RING as i32 + 3;
+    record_positions(&mut state);
+    for i in 0..2 {
+        assert_eq!(state.pos_history_x[i][3], state.players[i].x);
+        assert_eq!(state.pos_history_y[i][3], state.players[i].y);
+    }
+}
+
+#[test]
+fn shooting_spends_ammo_and_returns_the_first_new_slot() {
+    let mut state = pistol_shot_state(1, 10);
+    state.players[0].weapon = WEAPON_PISTOL;
+    state.players[0].ammo = 1;
+    state.players[1].weapon = WEAPON_PISTOL;
+    state.players[1].ammo = 5;
+    state.players[1].shoot_cooldown = 1;
+    let fire = FpInput { buttons: button::SHOOT, aim_x: 1, aim_y: 0 };
+    let mut events = TickEvents::default();
+    let first = shoot(&mut state, &[fire, fire], &mut events);
+
+    // Player 1 is on cooldown; player 0 fires its last round and drops the weapon
+    assert_eq!((first, state.proj_count), (1, 2));
+    assert_eq!(state.projectiles[1].owner_id, 0);
+    assert_eq!((state.players[0].weapon, state.players[0].ammo), (WEAPON_NONE, 0));
+    assert_eq!(state.players[0].shoot_cooldown, fp_weapon_stats(WEAPON_PISTOL).cooldown);
+    assert_eq!((state.players[1].ammo, events.shots.len), (5, 1));
+}
+
+#[test]
+fn projectiles_compact_after_moving() {
+    let mut state = pistol_shot_state(1, 10);
+    let map = Arc::clone(&state.map);
+    let live = state.projectiles[0];
+    state.projectiles[1] = Projectile { lifetime: 1, ..live };
+    state.projectiles[2] = Projectile { id: 2, ..live };
+    state.proj_count = 3;
+    let mut solid_kills = KillList::new();
+    move_projectiles(&mut state, &map, &mut solid_kills, &mut ());
+    assert_eq!(state.proj_count, 2);
+    assert_eq!((state.projectiles[0].x, state.projectiles[1].id), (live.x + live.vx, 2));
+    assert_eq!(solid_kills.iter().count(), 0);
+}
+
+#[test]
+fn deaths_cost_a_life_and_decide_the_round() {
+    let mut state = pistol_shot_state(2, 10);
+    state.players[1].vx = fp(1);
+    let mut hits = KillList::new();
+    hits.push(0, 1);
+    apply_deaths(&mut state, &hits, &KillList::new());
+    assert_eq!((state.players[1].lives, state.players[1].vx), (1, 0));
+    assert_eq!(state.death_linger_timer, 0);
+
+    apply_deaths(&mut state, &KillList::new(), &hits);
+    assert_eq!(state.players[1].lives, 0);
+    assert_eq!((state.winner, state.death_linger_timer), (0, DEATH_LINGER_TICKS));
+}
+
+#[test]
+fn time_up_picks_the_best_ranked_player() {
+    let mut state = pistol_shot_state(1, 10);
+    state.players[0].health = 5;
+    state.tick = state.cfg_match_duration - 1;
+    check_time_up(&mut state);
+    assert!(!state.match_over);
+    state.tick += 1;
+    check_time_up(&mut state);
+    assert_eq!((state.match_over, state.winner), (true, 1));
+}
+
+#[test]
+fn score_counts_projectile_kills_but_not_own_shots() {
+    let mut state = pistol_shot_state(1, 10);
+    let (mut hits, mut splash) = (KillList::new(), KillList::new());
+    hits.push(0, 1);
+    splash.push(1, 1);
+    splash.push(-1, 0);
+    score_kills(&mut state, &hits, &splash);
+    assert_eq!(state.score[..2], [1, 0]);
+}
+
+#[test]
+fn prev_buttons_keep_this_ticks_input() {
+    let mut state = pistol_shot_state(1, 10);
+    let inputs = [FpInput { buttons: button::JUMP, aim_x: 0, aim_y: 0 }, NULL_INPUT];
+    record_prev_buttons(&mut state, &inputs);
+    assert_eq!(state.prev_buttons[..2], [button::JUMP, 0]);
+}
+
+/// Player 0 walks onto pickup 0, holds it, then swaps it half spent for pickup 1 and shoots
+/// that dry.
+#[test]
+fn pickup_stats_tally_a_scripted_match() {
+    let mut state = create_initial_state(42, &arena_map());
+    let on_pickup = |state: &mut State, pi: usize| {
+        state.players[0].x = state.weapon_pickups[pi].x - PLAYER_WIDTH / 2;
+        state.players[0].y = state.weapon_pickups[pi].y - PLAYER_HEIGHT / 2;
+    };
+    let (first, second) = (state.weapon_pickups[0].weapon, state.weapon_pickups[1].weapon);
+    on_pickup(&mut state, 0);
+    for _ in 0..10 {
+        step_mut(&mut state, &[NULL_INPUT; 2]);
+    }
+    let stats = state.pickup_stats;
+    assert_eq!(stats.pickups_collected[0][first as usize], 1);
+    assert_eq!(stats.time_holding_weapon[0][first as usize], 10);
+    assert_eq!(stats.shots_remaining_when_dropped[0], 0);
+
+    state.players[0].ammo = 3;
+    on_pickup(&mut state, 1);
+    step_mut(&mut state, &[NULL_INPUT; 2]);
+    let stats = state.pickup_stats;
+    assert_eq!(stats.shots_remaining_when_dropped[0], 3);
+    assert_eq!(stats.pickups_collected[0][second as usize], 1);
+    assert_eq!(stats.pickups_collected[0].iter().sum::<u32>(), 2);
+    assert_eq!(stats.time_holding_weapon[0][second as usize], 1);
+
+    // Running dry drops the weapon with nothing left in it
+    state.players[0].ammo = 1;
+    state.players[0].shoot_cooldown = 0;
+    state.pickup_count = 0;
+    step_mut(&mut state, &[FpInput { buttons: button::SHOOT, aim_x: 1, aim_y: 0 }, NULL_INPUT]);
+    assert_eq!(state.players[0].weapon, WEAPON_NONE);
+    assert_eq!(state.pickup_stats.shots_remaining_when_dropped[0], 3);
+    // and an unarmed tick holds nothing
+    let held = state.pickup_stats.time_holding_weapon;
+    step_mut(&mut state, &[NULL_INPUT; 2]);
+    assert_eq!(state.pickup_stats.time_holding_weapon, held);
+    assert_eq!(state.pickup_stats.pickups_collected[1], [0; WEAPON_COUNT]);
+}
+
+#[test]
+fn pickup_stats_round_trip_v4_and_commit_in_registered_order() {
+    let mut state = busy_state(&arena_map());
+    state.pickup_stats.pickups_collected[0][WEAPON_SNIPER as usize] = 2;
+    state.pickup_stats.time_holding_weapon[1][WEAPON_SMG as usize] = 450;
+    state.pickup_stats.shots_remaining_when_dropped[1] = 7;
+    state.pickup_stats.damage_pacing[2] = 300;
+    let bytes = encode_state(&state);
+    let decoded = decode_state(&bytes);
+    assert_eq!(decoded.pickup_stats, state.pickup_stats);
+    assert_eq!(encode_state(&decoded), bytes);
+    assert_eq!(PickupStats::from_bytes(&state.pickup_stats.to_bytes()), state.pickup_stats);
+
+    // The stats are part of the state hash, so chunks chain them
+    let mut other = state.clone();
+    other.pickup_stats.shots_remaining_when_dropped[1] += 1;
+    assert_ne!(hash_state(&other), hash_state(&state));
+
+    // A swapped match commits the stats of its registered players
+    state.side_swap = true;
+    let registered = state.registered_pickup_stats();
+    assert_eq!(registered.pickups_collected[1][WEAPON_SNIPER as usize], 2);
+    assert_eq!(registered.shots_remaining_when_dropped[..2], [7, 0]);
+    assert_eq!(registered.damage_pacing, state.pickup_stats.damage_pacing);
+    assert_eq!(state.pickup_stats_commit(), registered.commitment());
+    assert_ne!(state.pickup_stats_commit(), state.pickup_stats.commitment());
+}
+
+#[test]
+fn damage_pacing_buckets_a_burst_then_idle() {
+    // A pistol hit in the opening ticks, then nobody does anything
+    let mut state = pistol_shot_state(3, MAX_HEALTH);
+    let mut recorded = 0;
+    while state.tick < 1100 {
+        let events = step_mut_events(&mut state, &[NULL_INPUT; 2]);
+        recorded += events.damage.iter().map(|d| d.amount as u32).sum::<u32>();
+    }
+    assert!(recorded > 0);
+    let mut expected = [0u16; PACING_BUCKETS];
+    expected[0] = recorded as u16;
+    assert_eq!(state.pickup_stats.damage_pacing, expected);
+
+    // Played out (sudden death and all), every damage event lands in one of the match's buckets
+    while !state.match_over && state.tick < 5000 {
+        let events = step_mut_events(&mut state, &[NULL_INPUT; 2]);
+        recorded += events.damage.iter().map(|d| d.amount as u32).sum::<u32>();
+    }
+    let pacing = state.pickup_stats.damage_pacing;
+    assert_eq!(pacing.iter().map(|&d| d as u32).sum::<u32>(), recorded);
+    assert_eq!(pacing[1], 0);
+    assert!(pacing[pacing_buckets(MATCH_DURATION_TICKS)..].iter().all(|&d| d == 0));
+
+    // The histogram is committed with the rest of the stats
+    let mut other = state.clone();
+    other.pickup_stats.damage_pacing[1] = 1;
+    assert_ne!(other.pickup_stats_commit(), state.pickup_stats_commit());
+}
+
+#[test]
+fn damage_pacing_stays_bounded_in_endless_matches() {
+    assert_eq!(
+        [0, 600, 601, MATCH_DURATION_TICKS, 999_999].map(pacing_buckets),
+        [1, 1, 2, 3, PACING_BUCKETS]
+    );
+
+    // Warmup runs for hours: its late damage piles into the last bucket
+    let mut map = arena_map();
+    map.platforms[0].hazard_damage = 3;
+    let mut state = create_initial_state_cfg(42, &map, 99, 999_999, 999_999);
+    state.pickup_count = 0;
+    for p in &mut state.players[..2] {
+        (p.state_flags, p.invincibility_source, p.respawn_timer) = (flag::ALIVE, invincibility::NONE, 0);
+    }
+    state.tick = 100 * PACING_BUCKET_TICKS - 1;
+    step_mut(&mut state, &[NULL_INPUT; 2]);
+    let mut expected = [0u16; PACING_BUCKETS];
+    expected[PACING_BUCKETS - 1] = 6;
+    assert_eq!(state.pickup_stats.damage_pacing, expected);
+
+    // and a bucket saturates rather than wrapping
+    state.pickup_stats.damage_pacing[PACING_BUCKETS - 1] = u16::MAX - 1;
+    state.tick += HAZARD_DAMAGE_INTERVAL - 1;
+    step_mut(&mut state, &[NULL_INPUT; 2]);
+    assert_eq!(state.pickup_stats.damage_pacing[PACING_BUCKETS - 1], u16::MAX);
+}
+
+/// Three players, P2 dead holding a sniper with rounds left: the tick drops it where P2 fell.
+fn dropped_sniper_state() -> State {
+    let cfg = FpMatchConfig { player_count: 3, ..FpMatchConfig::default() };
+    let mut state = create_initial_state_with(5, &arena_map(), &cfg);
+    state.players[2].weapon = WEAPON_SNIPER;
+    state.players[2].ammo = 2;
+    state.players[2].state_flags = 0;
+    step_mut(&mut state, &[NULL_INPUT; 3]);
+    state
+}
+
+#[test]
+fn a_dead_players_weapon_drops_with_its_rounds() {
+    let state = dropped_sniper_state();
+    let p2 = state.players[2];
+    assert_eq!((p2.weapon, p2.ammo), (WEAPON_NONE, 0));
+    assert_eq!(state.pickup_count as usize, NUM_WEAPON_SPAWNS + 1);
+    let wp = state.weapon_pickups[NUM_WEAPON_SPAWNS];
+    assert_eq!((wp.x, wp.y), (p2.x + PLAYER_WIDTH / 2, p2.y + PLAYER_HEIGHT / 2));
+    assert_eq!((wp.weapon, wp.dropped_ammo, wp.respawn_timer), (WEAPON_SNIPER, 2, 0));
+    assert_eq!(wp.id, NUM_WEAPON_SPAWNS as i32);
+    // The pickup timers ran after the drop in the same tick
+    assert_eq!(wp.active_timer, DROPPED_WEAPON_DESPAWN_TICKS - 1);
+    assert_eq!(validate_state(&state), Ok(()));
+
+    // A weapon shot dry is lost with nothing to drop
+    let mut dry = create_initial_state(5, &arena_map());
+    dry.players[1].weapon = WEAPON_SNIPER;
+    dry.players[1].ammo = 0;
+    dry.players[1].state_flags = 0;
+    drop_weapons(&mut dry);
+    assert_eq!(dry.players[1].weapon, WEAPON_NONE);
+    assert_eq!(dry.pickup_count as usize, NUM_WEAPON_SPAWNS);
+}
+
+/// P0 and P1 both touch P2's dropped sniper: P0 (lower index) gets its two rounds, and the
+/// slot is freed rather than respawned.
+#[test]
+fn a_contested_dropped_weapon_goes_to_the_lower_index_and_is_gone() {
+    let mut state = dropped_sniper_state();
+    let wp = state.weapon_pickups[NUM_WEAPON_SPAWNS];
+    // P2 fell by a spawn: keep the spawned pickups out of it
+    for spawned in &mut state.weapon_pickups[..NUM_WEAPON_SPAWNS] {
+        spawned.respawn_timer = WEAPON_PICKUP_RESPAWN_TICKS;
+    }
+    for i in 0..2 {
+        state.players[i].x = wp.x - PLAYER_WIDTH / 2;
+        state.players[i].y = wp.y - PLAYER_HEIGHT / 2;
+    }
+    let mut events = TickEvents::default();
+    resolve_weapon_pickups(&mut state, &mut events);
+    assert_eq!((state.players[0].weapon, state.players[0].ammo), (WEAPON_SNIPER, 2));
+    assert_eq!(state.players[1].weapon, WEAPON_NONE);
+    assert_eq!(state.pickup_count as usize, NUM_WEAPON_SPAWNS);
+    assert_eq!(state.weapon_pickups[NUM_WEAPON_SPAWNS].weapon, EMPTY_PICKUP.weapon);
+    let taken: Vec<(i32, i32)> = events.pickups.iter().map(|e| (e.player, e.pickup_id)).collect();
+    assert_eq!(taken, [(0, wp.id)]);
+}
+
+#[test]
+fn an_uncollected_dropped_weapon_despawns() {
+    let mut state = dropped_sniper_state();
+    // Despawns even with the active-despawn ruleset off, and never redraws a weapon
+    assert!(!state.cfg_active_despawn);
+    let rng = state.rng_state;
+    for _ in 1..DROPPED_WEAPON_DESPAWN_TICKS - 1 {
+        tick_pickup_timers(&mut state);
+    }
+    assert_eq!(state.weapon_pickups[NUM_WEAPON_SPAWNS].active_timer, 1);
+    tick_pickup_timers(&mut state);
+    assert_eq!(state.pickup_count as usize, NUM_WEAPON_SPAWNS);
+    assert_eq!(state.weapon_pickups[NUM_WEAPON_SPAWNS].dropped_ammo, 0);
+    assert_eq!(state.rng_state, rng);
+}
+
+#[test]
+fn dropped_weapons_round_trip_v4_only() {
+    let mut state = dropped_sniper_state();
+    let bytes = encode_state(&state);
+    let decoded = decode_state(&bytes);
+    assert_eq!(encode_state(&decoded), bytes);
+    let wp = decoded.weapon_pickups[NUM_WEAPON_SPAWNS];
+    assert_eq!((wp.dropped_ammo, wp.active_timer), (2, DROPPED_WEAPON_DESPAWN_TICKS - 1));
+
+    // v3 has room for the spawn slots' timers alone: the drop comes back as a spawned pickup
+    let v3 = decode_state(&encode_state_as(&state, StateFormat::V3));
+    let wp = v3.weapon_pickups[NUM_WEAPON_SPAWNS];
+    assert_eq!((wp.weapon, wp.dropped_ammo, wp.active_timer), (WEAPON_SNIPER, 0, 0));
+    assert!(v3.weapon_pickups[..NUM_WEAPON_SPAWNS]
+        .iter()
+        .zip(&state.weapon_pickups)
+        .all(|(a, b)| (a.weapon, a.active_timer) == (b.weapon, b.active_timer)));
+
+    // More rounds than the weapon holds is an issue, and sanitizing clamps them
+    state.weapon_pickups[NUM_WEAPON_SPAWNS].dropped_ammo = fp_weapon_stats(WEAPON_SNIPER).ammo + 1;
+    let id = state.weapon_pickups[NUM_WEAPON_SPAWNS].id;
+    let issues = validate_state(&state).unwrap_err();
+    assert!(issues.contains(&StateIssue::PickupAmmo { id, ammo: fp_weapon_stats(WEAPON_SNIPER).ammo + 1 }));
+    sanitize_state(&mut state);
+    assert_eq!(state.weapon_pickups[NUM_WEAPON_SPAWNS].dropped_ammo, fp_weapon_stats(WEAPON_SNIPER).ammo);
+}
+
+/// Maps for the platform grid to agree with brute force on: the arena, generated maps, and
+/// clutter (overlapping, zero-size, negative-size and off-map platforms).
+fn grid_test_maps() -> Vec<Map> {
+    let mut maps = vec![arena_map()];
+    maps.extend((0..4).map(|seed| generate_map(seed, MapGenParams::default())));
+    let mut rng = 99;
+    for _ in 0..4 {
+        let mut map = arena_map();
+        for plat in &mut map.platforms[1..] {
+            let mut draw = |min: i32, max: i32| {
+                let (v, next) = prng_int_range(rng, min, max);
+                rng = next;
+                fp(v)
+            };
+            *plat = Platform {
+                x: draw(-100, 1000), y: draw(-100, 600),
+                width: draw(-40, 300), height: draw(-20, 80),
+                one_way: draw(0, 1) == fp(1), hazard_damage: 0,
+            };
+        }
+        map.platforms[7] = EMPTY_PLATFORM;
+        maps.push(map);
+    }
+    maps
+}
+
+#[test]
+fn platform_grid_is_a_pure_accelerator() {
+    let mut rng = 7;
+    let mut draw = |min: i32, max: i32| {
+        let (v, next) = prng_int_range(rng, min, max);
+        rng = next;
+        v
+    };
+    for map in grid_test_maps() {
+        let mut indexed = map.clone();
+        indexed.build_index();
+        assert!(indexed.is_indexed() && !map.is_indexed());
+        assert_eq!(indexed, map);
+        assert!(mirror_map(&indexed).is_indexed());
+
+        for _ in 0..2000 {
+            // Every platform a box meets is a candidate
+            let (x0, y0) = (draw(-200 * 256, 1100 * 256), draw(-200 * 256, 700 * 256));
+            let (x1, y1) = (x0 + draw(0, 80 * 256), y0 + draw(0, 80 * 256));
+            let near = indexed.platforms_near(x0, y0, x1, y1);
+            for (i, plat) in map.platforms.iter().enumerate() {
+                let (px0, px1) = (plat.x.min(plat.x + plat.width), plat.x.max(plat.x + plat.width));
+                let (py0, py1) = (plat.y.min(plat.y + plat.height), plat.y.max(plat.y + plat.height));
+                if px0 <= x1 && x0 <= px1 && py0 <= y1 && y0 <= py1 {
+                    assert_ne!(near & (1 << i), 0, "platform {i} missed by ({x0}, {y0})..({x1}, {y1})");
+                }
+            }
+
+            // Projectiles hit the same solids
+            let weapon = draw(0, WEAPON_COUNT as i32 - 1) as i8;
+            let proj = Projectile { x: x0, y: y0, weapon, ..EMPTY_PROJECTILE };
+            assert_eq!(hits_solid(&proj, &indexed), hits_solid(&proj, &map));
+
+            // Players move, land, slide and collide the same
+            let mut p = create_initial_state(1, &map).players[0];
+            (p.x, p.y, p.vx, p.vy) = (x0, y0, draw(-2048, 2048), draw(-2048, 2048));
+            p.state_flags = flag::ALIVE;
+            let buttons = draw(0, 255) as u8;
+            let (mut a, mut b) = (p, p);
+            assert_eq!(
+                move_and_collide_mut(&mut a, buttons, &indexed, true),
+                move_and_collide_mut(&mut b, buttons, &map, true)
+            );
+            apply_input_mut(&mut a, button::DOWN | button::JUMP, 0, 0, &indexed, true);
+            apply_input_mut(&mut b, button::DOWN | button::JUMP, 0, 0, &map, true);
+            assert_eq!(format!("{a:?}"), format!("{b:?}"));
+        }
+    }
+
+    // Open air near the arena's ceiling has nothing to look at
+    let mut arena = arena_map();
+    arena.build_index();
+    assert_eq!(arena.platforms_near(fp(400), fp(20), fp(420), fp(40)), 0);
+
+    // A whole match steps identically on the indexed map
+    let map = generate_map(3, MapGenParams::default());
+    let mut indexed = map.clone();
+    indexed.build_index();
+    let (mut plain, mut fast) = (create_initial_state(5, &map), create_initial_state(5, &indexed));
+    assert!(fast.map.is_indexed());
+    for tick in &duel_transcript(600) {
+        step_mut(&mut plain, tick);
+        step_mut(&mut fast, tick);
+    }
+    assert_eq!(hash_state(&fast), hash_state(&plain));
+}
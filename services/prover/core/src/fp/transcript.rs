@@ -0,0 +1,153 @@
+//! Transcript files: a prover input as a small binary header around the guest's raw input, so
+//! the game server can stream a match to disk instead of building `ProverInput` JSON.
+//!
+//! Layout: [`TRANSCRIPT_MAGIC`] [version] [session_id] [flags] [gen_seed] [initial_lives]
+//! [match_duration] [sudden_death], little-endian words (`gen_seed` 0 without
+//! `transcript_flag::GEN_SEED`), then the guest's raw input as `encode_raw_input` writes it:
+//! [seed] [tick_count] [tick × 6 bytes].
+//!
+//! Custom maps have no place in it; they still go as JSON. Inputs aren't range-checked: like the
+//! JSON validator, every `buttons` byte and every `i8` aim is a valid input.
+
+use std::io::{self, Read, Write};
+
+use super::*;
+
+/// First bytes of every transcript file.
+pub const TRANSCRIPT_MAGIC: [u8; 4] = *b"CKZT";
+pub const TRANSCRIPT_VERSION: u32 = 1;
+/// Bytes before the first tick.
+pub const TRANSCRIPT_HEADER_LEN: usize = 40;
+
+pub mod transcript_flag {
+    pub const MIRRORED: u32 = 1;
+    /// The match was played on `generate_map` of the header's map seed.
+    pub const GEN_SEED: u32 = 2;
+    pub const ALL: u32 = MIRRORED | GEN_SEED;
+}
+
+/// Why a transcript file can't be read.
+#[derive(Debug)]
+pub enum TranscriptIssue {
+    Io(io::Error),
+    /// Doesn't start with `TRANSCRIPT_MAGIC`.
+    Magic([u8; 4]),
+    Version(u32),
+    /// Flag bits this version doesn't define.
+    Flags(u32),
+    /// The file ends inside its header.
+    TruncatedHeader,
+    /// The raw input header is a wider match's (`WIDE_INPUT_TAG`); files hold two players.
+    WideInput(u32),
+    /// The file ends after `ticks` of its `tick_count` ticks.
+    Truncated { ticks: usize, tick_count: usize },
+    /// Bytes follow the last tick.
+    TrailingBytes,
+}
+
+impl core::fmt::Display for TranscriptIssue {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            TranscriptIssue::Io(e) => write!(f, "reading transcript: {e}"),
+            TranscriptIssue::Magic(m) => write!(f, "not a transcript file (starts {m:02x?})"),
+            TranscriptIssue::Version(v) => {
+                write!(f, "transcript version {v}, this build reads {TRANSCRIPT_VERSION}")
+            }
+            TranscriptIssue::Flags(flags) => write!(f, "unknown transcript flags {flags:#x}"),
+            TranscriptIssue::TruncatedHeader => write!(f, "transcript header is truncated"),
+            TranscriptIssue::WideInput(word) => write!(f, "wide raw input header {word:#x}"),
+            TranscriptIssue::Truncated { ticks, tick_count } => {
+                write!(f, "transcript ends after {ticks} of {tick_count} ticks")
+            }
+            TranscriptIssue::TrailingBytes => write!(f, "bytes after the last tick"),
+        }
+    }
+}
+
+impl std::error::Error for TranscriptIssue {}
+
+impl From<io::Error> for TranscriptIssue {
+    fn from(e: io::Error) -> Self {
+        TranscriptIssue::Io(e)
+    }
+}
+
+/// Write `input` as a transcript file. Fails with `InvalidInput` for a custom map.
+pub fn write_transcript<W: Write>(w: &mut W, input: &FpProverInput) -> io::Result<()> {
+    if input.map.is_some() {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "a custom map doesn't fit a transcript file"));
+    }
+    let mut flags = 0;
+    if input.mirrored {
+        flags |= transcript_flag::MIRRORED;
+    }
+    if input.gen_seed.is_some() {
+        flags |= transcript_flag::GEN_SEED;
+    }
+    let rules = input.rules.unwrap_or_default();
+    w.write_all(&TRANSCRIPT_MAGIC)?;
+    for word in [TRANSCRIPT_VERSION, input.session_id, flags, input.gen_seed.unwrap_or(0)] {
+        w.write_all(&word.to_le_bytes())?;
+    }
+    for word in rules.to_words() {
+        w.write_all(&word.to_le_bytes())?;
+    }
+    w.write_all(&encode_raw_input(input))
+}
+
+/// Read a transcript file, a tick at a time, to its end: bytes past the last tick are an error.
+pub fn read_transcript<R: Read>(r: &mut R) -> Result<FpProverInput, TranscriptIssue> {
+    let mut header = [0u8; TRANSCRIPT_HEADER_LEN];
+    r.read_exact(&mut header).map_err(|e| match e.kind() {
+        io::ErrorKind::UnexpectedEof => TranscriptIssue::TruncatedHeader,
+        _ => TranscriptIssue::Io(e),
+    })?;
+    let word = |off: usize| u32::from_le_bytes([header[off], header[off + 1], header[off + 2], header[off + 3]]);
+    let magic = [header[0], header[1], header[2], header[3]];
+    if magic != TRANSCRIPT_MAGIC {
+        return Err(TranscriptIssue::Magic(magic));
+    }
+    if word(4) != TRANSCRIPT_VERSION {
+        return Err(TranscriptIssue::Version(word(4)));
+    }
+    let flags = word(12);
+    if flags & !transcript_flag::ALL != 0 {
+        return Err(TranscriptIssue::Flags(flags));
+    }
+    let rules = MatchRules::from_words([word(20), word(24), word(28)]);
+    let tick_count = word(36);
+    if tick_count & WIDE_INPUT_TAG_MASK == WIDE_INPUT_TAG {
+        return Err(TranscriptIssue::WideInput(tick_count));
+    }
+    let tick_count = tick_count as usize;
+
+    // The count is untrusted until the ticks are there, so don't reserve for all of it up front
+    let mut transcript = Vec::with_capacity(tick_count.min(MATCH_DURATION_TICKS as usize * 2));
+    let mut tick = [0u8; 2 * INPUT_BYTES];
+    for ticks in 0..tick_count {
+        r.read_exact(&mut tick).map_err(|e| match e.kind() {
+            io::ErrorKind::UnexpectedEof => TranscriptIssue::Truncated { ticks, tick_count },
+            _ => TranscriptIssue::Io(e),
+        })?;
+        let inputs = tick_inputs(&tick, 2);
+        transcript.push([inputs[0], inputs[1]]);
+    }
+    if r.read(&mut [0u8; 1])? != 0 {
+        return Err(TranscriptIssue::TrailingBytes);
+    }
+
+    Ok(FpProverInput {
+        session_id: word(8),
+        seed: word(32),
+        mirrored: flags & transcript_flag::MIRRORED != 0,
+        transcript,
+        map: None,
+        gen_seed: (flags & transcript_flag::GEN_SEED != 0).then(|| word(16)),
+        rules: Some(rules).filter(|r| *r != MatchRules::default()),
+    })
+}
+
+/// Whether `bytes` start like a transcript file (the rest may still be bad).
+pub fn is_transcript_file(bytes: &[u8]) -> bool {
+    bytes.starts_with(&TRANSCRIPT_MAGIC)
+}
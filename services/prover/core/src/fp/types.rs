@@ -0,0 +1,1020 @@
+//! Players, projectiles, pickups, maps and `State`; event lists; the PRNG; the initial state.
+
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use super::*;
+
+// -- Types -------------------------------------------------------------------
+
+#[derive(Clone, Copy, Debug)]
+pub struct Player {
+    pub id: i32,
+    pub x: Fp,
+    pub y: Fp,
+    pub vx: Fp,
+    pub vy: Fp,
+    pub facing: i32,
+    pub health: i32,
+    pub lives: i32,
+    pub shoot_cooldown: i32,
+    pub grounded: bool,
+    pub state_flags: u32,
+    pub respawn_timer: i32,
+    pub invincibility_source: u8, // invincibility::*, NONE unless flag::INVINCIBLE
+    pub weapon: i8,  // WEAPON_NONE (-1) or 0..4
+    pub ammo: i32,
+    // Double jump
+    pub jumps_left: i32,
+    // Wall slide/jump
+    pub wall_sliding: bool,
+    pub wall_dir: i32, // -1 = wall on left, 1 = wall on right, 0 = none
+    // Stomp
+    pub stomped_by: i32,   // -1 = none, otherwise player id
+    pub stomping_on: i32,  // -1 = none, otherwise player id
+    pub stomp_shake_progress: i32,
+    pub stomp_last_shake_dir: i32,
+    pub stomp_auto_run_dir: i32,
+    pub stomp_auto_run_timer: i32,
+    pub stomp_cooldown: i32,
+    pub stomp_cooldown_from: i32, // -1 = none, otherwise the rider id `stomp_cooldown` keeps off
+    pub stomp_airborne_ticks: u8, // victim only: consecutive ungrounded ticks while ridden
+    // Wall stamina (only drains/regenerates when `State::cfg_stamina`)
+    pub stamina: i32, // 0..=STAMINA_MAX
+    // Dash
+    pub dash_cooldown: i32, // 0..=DASH_COOLDOWN_TICKS, dashing while above the cooldown's last DASH_TICKS
+    // Zone kill credit
+    pub last_damaged_by: i32,  // -1 = none, otherwise the id of the last opponent to hurt this player
+    pub last_damage_tick: i32, // tick of that damage (0 with none)
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Projectile {
+    pub id: i32,
+    pub owner_id: i32,
+    pub x: Fp,
+    pub y: Fp,
+    pub vx: Fp,
+    pub vy: Fp,
+    pub lifetime: i32,
+    pub weapon: i8,
+    /// Passes through its owner while `State::tick` is below this (OWNER_IMMUNE_FOREVER = always).
+    pub owner_immune_until: i32,
+    /// Solid contacts left to bounce off (`FpWeaponStats::bounces` at spawn).
+    pub bounces_left: u8,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct WeaponPickup {
+    pub id: i32,
+    pub x: Fp,
+    pub y: Fp,
+    pub weapon: i8,
+    pub respawn_timer: i32,
+    /// Ticks left before an uncollected active pickup despawns (`State::cfg_active_despawn`),
+    /// or before a dropped weapon goes away for good.
+    pub active_timer: i32,
+    /// Rounds in a weapon a dying player dropped; 0 for a spawned pickup, which grants the
+    /// weapon's full `FpWeaponStats::ammo`.
+    pub dropped_ammo: i32,
+}
+
+/// A health pack at a `Map::health_spawns` point; available while `respawn_timer` is 0.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct HealthPickup {
+    pub x: Fp,
+    pub y: Fp,
+    /// Ticks until it is back after being collected.
+    pub respawn_timer: i32,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Platform {
+    pub x: Fp,
+    pub y: Fp,
+    pub width: Fp,
+    pub height: Fp,
+    /// Players only collide with its top, landing from above; DOWN + JUMP drops through.
+    /// Projectiles hit it like any other platform.
+    pub one_way: bool,
+    /// Health lost by a player standing on it or pushed out of its side (spikes, lava), every
+    /// `HAZARD_DAMAGE_INTERVAL` ticks; 0 for a normal platform. A death to it is an env kill.
+    pub hazard_damage: i32,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SpawnPoint {
+    pub x: Fp,
+    pub y: Fp,
+}
+
+pub const NUM_PLATFORMS: usize = 8;
+/// Unused platform slot (zero-size, skipped by collision).
+pub const EMPTY_PLATFORM: Platform = Platform { x: 0, y: 0, width: 0, height: 0, one_way: false, hazard_damage: 0 };
+pub const NUM_SPAWNS: usize = 4;
+pub const NUM_WEAPON_SPAWNS: usize = 4;
+const _: () = assert!(MAX_WEAPON_PICKUPS >= NUM_WEAPON_SPAWNS + MAX_PLAYERS, "a drop slot per player");
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Map {
+    pub width: Fp,
+    pub height: Fp,
+    pub platforms: [Platform; NUM_PLATFORMS],
+    pub spawns: [SpawnPoint; NUM_SPAWNS],
+    pub weapon_spawns: [SpawnPoint; NUM_WEAPON_SPAWNS],
+    /// Health pickup points; the first `health_spawn_count` are used. Absent from maps
+    /// authored before health pickups, which have none.
+    #[serde(default)]
+    pub health_spawns: [SpawnPoint; MAX_HEALTH_PICKUPS],
+    #[serde(default)]
+    pub health_spawn_count: u8,
+    /// Platform lookup grid, once `build_index` has run; none until then.
+    #[serde(skip)]
+    pub index: PlatformIndex,
+}
+
+impl Map {
+    /// The health spawns in use.
+    pub fn used_health_spawns(&self) -> &[SpawnPoint] {
+        &self.health_spawns[..(self.health_spawn_count as usize).min(MAX_HEALTH_PICKUPS)]
+    }
+}
+
+/// Ticks per `PickupStats::damage_pacing` bucket (10 seconds).
+pub const PACING_BUCKET_TICKS: i32 = 600;
+/// Most `damage_pacing` buckets a match uses: 3 minutes' worth. Longer matches (warmup) pile
+/// the rest into the last.
+pub const PACING_BUCKETS: usize = 18;
+
+/// `damage_pacing` buckets a match of `match_duration` ticks uses: enough to cover it, at
+/// least one and at most `PACING_BUCKETS`.
+pub fn pacing_buckets(match_duration: i32) -> usize {
+    let buckets = (match_duration.max(1) - 1) / PACING_BUCKET_TICKS + 1;
+    (buckets as usize).min(PACING_BUCKETS)
+}
+
+/// Weapon pickup and ammo tallies of a match, per player slot (internal order, like `score`
+/// in `State::pickup_stats`), and its damage pacing. Never read by the sim; the journal
+/// commits them through `commitment`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PickupStats {
+    /// Weapon pickups collected, by weapon.
+    pub pickups_collected: [[u32; WEAPON_COUNT]; MAX_PLAYERS],
+    /// Ticks ended alive holding each weapon.
+    pub time_holding_weapon: [[u32; WEAPON_COUNT]; MAX_PLAYERS],
+    /// Rounds left in the weapons a player gave up for a pickup. One that ran dry drops with
+    /// none, so it adds nothing.
+    pub shots_remaining_when_dropped: [u32; MAX_PLAYERS],
+    /// Damage dealt to anyone, from any source, per `PACING_BUCKET_TICKS` of play (saturating).
+    /// Only the first `pacing_buckets(cfg_match_duration)` fill; ticks past them count in the
+    /// last one. Coarse enough to publish, fine enough to show a long lull before a kill.
+    pub damage_pacing: [u16; PACING_BUCKETS],
+}
+
+/// Bytes of `PickupStats::to_bytes`.
+pub const PICKUP_STATS_BYTES: usize = MAX_PLAYERS * (2 * WEAPON_COUNT + 1) * 4 + PACING_BUCKETS * 2;
+
+impl PickupStats {
+    /// Little-endian words, player slot by slot: `pickups_collected`, `time_holding_weapon`,
+    /// then `shots_remaining_when_dropped`; then `damage_pacing` as little-endian halfwords.
+    pub fn to_bytes(&self) -> [u8; PICKUP_STATS_BYTES] {
+        let mut b = [0u8; PICKUP_STATS_BYTES];
+        let (players, pacing) = b.split_at_mut(PICKUP_STATS_BYTES - PACING_BUCKETS * 2);
+        let words = (0..MAX_PLAYERS).flat_map(|i| {
+            self.pickups_collected[i].into_iter()
+                .chain(self.time_holding_weapon[i])
+                .chain([self.shots_remaining_when_dropped[i]])
+        });
+        for (chunk, w) in players.chunks_exact_mut(4).zip(words) {
+            chunk.copy_from_slice(&w.to_le_bytes());
+        }
+        for (chunk, d) in pacing.chunks_exact_mut(2).zip(self.damage_pacing) {
+            chunk.copy_from_slice(&d.to_le_bytes());
+        }
+        b
+    }
+
+    /// Inverse of `to_bytes`.
+    pub fn from_bytes(b: &[u8; PICKUP_STATS_BYTES]) -> Self {
+        let mut words = b.chunks_exact(4).map(|c| u32::from_le_bytes([c[0], c[1], c[2], c[3]]));
+        let mut stats = PickupStats::default();
+        for i in 0..MAX_PLAYERS {
+            for w in stats.pickups_collected[i].iter_mut().chain(&mut stats.time_holding_weapon[i]) {
+                *w = words.next().unwrap_or(0);
+            }
+            stats.shots_remaining_when_dropped[i] = words.next().unwrap_or(0);
+        }
+        let pacing = &b[PICKUP_STATS_BYTES - PACING_BUCKETS * 2..];
+        for (d, c) in stats.damage_pacing.iter_mut().zip(pacing.chunks_exact(2)) {
+            *d = u16::from_le_bytes([c[0], c[1]]);
+        }
+        stats
+    }
+
+    /// The stats with players 0 and 1 swapped when `side_swap` is set: internal order to
+    /// registered order, or back. The pacing is the whole match's, so it stays.
+    pub fn registered(&self, side_swap: bool) -> Self {
+        PickupStats {
+            pickups_collected: registered_scores(side_swap, self.pickups_collected),
+            time_holding_weapon: registered_scores(side_swap, self.time_holding_weapon),
+            shots_remaining_when_dropped: registered_scores(side_swap, self.shots_remaining_when_dropped),
+            damage_pacing: self.damage_pacing,
+        }
+    }
+
+    /// SHA-256 of `to_bytes`: what the journal commits for the registered-order stats.
+    pub fn commitment(&self) -> [u8; 32] {
+        Sha256::digest(self.to_bytes()).into()
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct State {
+    pub tick: i32,
+    /// The first `player_count` slots are in play; the rest stay `EMPTY_PLAYER`.
+    pub players: [Player; MAX_PLAYERS],
+    /// Players in the match, 2..=MAX_PLAYERS (`FpMatchConfig::player_count`).
+    pub player_count: u8,
+    pub projectiles: [Projectile; MAX_PROJECTILES],
+    pub proj_count: u8,
+    pub weapon_pickups: [WeaponPickup; MAX_WEAPON_PICKUPS],
+    pub pickup_count: u8,
+    /// One per used `Map::health_spawns` point, in order; cleared with the weapon pickups
+    /// when the match ends.
+    pub health_pickups: [HealthPickup; MAX_HEALTH_PICKUPS],
+    pub health_pickup_count: u8,
+    pub rng_state: u32,
+    pub score: [u32; MAX_PLAYERS],
+    /// Deaths per player that nobody scored for because the environment did the killing: a
+    /// hazard, or the zone with no opponent to credit (`zone_kill_credit`).
+    pub env_kills: [u32; MAX_PLAYERS],
+    /// Weapon pickup and ammo tallies so far.
+    pub pickup_stats: PickupStats,
+    pub next_proj_id: i32,
+    /// The sudden-death zone's safe box (`ZoneBounds`, `arena_bounds`).
+    pub arena_left: Fp,
+    pub arena_right: Fp,
+    pub arena_top: Fp,
+    pub arena_bottom: Fp,
+    pub match_over: bool,
+    pub winner: i32,
+    pub death_linger_timer: i32,
+    /// The linger (and the `match_over` after it) was started by the elimination that decided
+    /// the match, not by time-up: lets the client slow down the last `DEATH_LINGER_TICKS`.
+    pub final_blow: bool,
+    pub prev_buttons: [u8; MAX_PLAYERS],
+    // Per-match config (allows warmup/custom modes)
+    pub cfg_initial_lives: i32,
+    pub cfg_match_duration: i32,
+    pub cfg_sudden_death: i32,
+    /// How the zone closes, a `zone_mode` (`FpMatchConfig::zone_mode`).
+    pub cfg_zone_mode: u8,
+    /// Favor-the-shooter lag per player (ticks, 0 = off): projectiles owned by player i also
+    /// hit where the victim was `cfg_lag_ticks[i]` ticks ago. Players past the first two
+    /// shoot without lag compensation.
+    pub cfg_lag_ticks: [u8; 2],
+    /// Ring of recent post-movement positions, slot = tick % LAG_RING.
+    pub pos_history_x: [[Fp; LAG_RING]; MAX_PLAYERS],
+    pub pos_history_y: [[Fp; LAG_RING]; MAX_PLAYERS],
+    /// Played on the mirrored map (`FpMatchConfig::mirrored`).
+    pub cfg_mirrored: bool,
+    /// Wall sliding drains `Player::stamina` and wall jumps cost it (`FpMatchConfig::stamina`).
+    pub cfg_stamina: bool,
+    /// Uncollected pickups despawn after `ACTIVE_DESPAWN_TICKS` (`FpMatchConfig::active_despawn`).
+    pub cfg_active_despawn: bool,
+    /// Registered player 1 plays internal index 0 (left spawn, tiebreaks) and registered
+    /// player 0 plays index 1. Fixed by the seed (`side_swap_for_seed`), not by the operator.
+    pub side_swap: bool,
+    /// The map this state is played on, already oriented. Shared, so cloning a State (or the
+    /// handle, once per tick in `step_mut`) doesn't copy it, and `Arc` so the host's chunk
+    /// workers can read boundary states from other threads. The v3 encoding carries it, so
+    /// `hash_state` commits to it.
+    pub map: Arc<Map>,
+}
+
+impl State {
+    /// The zone's safe box as stored.
+    pub fn arena_bounds(&self) -> ZoneBounds {
+        ZoneBounds { left: self.arena_left, right: self.arena_right, top: self.arena_top, bottom: self.arena_bottom }
+    }
+
+    pub fn set_arena_bounds(&mut self, b: ZoneBounds) {
+        (self.arena_left, self.arena_right, self.arena_top, self.arena_bottom) = (b.left, b.right, b.top, b.bottom);
+    }
+
+    /// The `player_count` players in the match, alive or not.
+    pub fn active_players(&self) -> &[Player] {
+        &self.players[..self.player_count as usize]
+    }
+
+    /// `winner` as a registered player index (what the journal commits); -1 stays -1.
+    pub fn registered_winner(&self) -> i32 {
+        side_index(self.side_swap, self.winner)
+    }
+
+    /// `score` in registered player order.
+    pub fn registered_scores(&self) -> [u32; MAX_PLAYERS] {
+        registered_scores(self.side_swap, self.score)
+    }
+
+    /// `env_kills` in registered player order.
+    pub fn registered_env_kills(&self) -> [u32; MAX_PLAYERS] {
+        registered_scores(self.side_swap, self.env_kills)
+    }
+
+    /// Each active player's health (internal order, like `score`); slots past `player_count`
+    /// are 0.
+    pub fn health(&self) -> [i32; MAX_PLAYERS] {
+        let mut health = [0; MAX_PLAYERS];
+        for (h, p) in health.iter_mut().zip(self.active_players()) {
+            *h = p.health;
+        }
+        health
+    }
+
+    /// Each active player's lives (internal order); slots past `player_count` are 0.
+    pub fn lives(&self) -> [i32; MAX_PLAYERS] {
+        let mut lives = [0; MAX_PLAYERS];
+        for (l, p) in lives.iter_mut().zip(self.active_players()) {
+            *l = p.lives;
+        }
+        lives
+    }
+
+    /// `health` in registered player order.
+    pub fn registered_health(&self) -> [i32; MAX_PLAYERS] {
+        registered_scores(self.side_swap, self.health())
+    }
+
+    /// `lives` in registered player order.
+    pub fn registered_lives(&self) -> [i32; MAX_PLAYERS] {
+        registered_scores(self.side_swap, self.lives())
+    }
+
+    /// `pickup_stats` in registered player order.
+    pub fn registered_pickup_stats(&self) -> PickupStats {
+        self.pickup_stats.registered(self.side_swap)
+    }
+
+    /// The pickup stats commitment the journal commits.
+    pub fn pickup_stats_commit(&self) -> [u8; 32] {
+        self.registered_pickup_stats().commitment()
+    }
+
+    /// The team result the journal commits: `TeamResult::solo` of the registered winner and
+    /// scores, until the sim plays team matches.
+    pub fn registered_teams(&self) -> crate::TeamResult {
+        crate::TeamResult::solo(self.player_count, self.registered_winner(), &self.registered_scores())
+    }
+}
+
+/// Side assignment for a seed: the low bit of `hash_seed(seed)`. The seed is committed at
+/// session start, so whichever player order the operator registers, a coin neither chose decides
+/// who gets index 0's spawn and tiebreak advantage.
+pub fn side_swap_for_seed(seed: u32) -> bool {
+    hash_seed(seed)[0] & 1 == 1
+}
+
+/// Map an internal player index to its registered index, or back (the mapping is its own
+/// inverse). Negative indices (no winner) and players past the first two pass through.
+pub fn side_index(side_swap: bool, index: i32) -> i32 {
+    if side_swap && (index == 0 || index == 1) { 1 - index } else { index }
+}
+
+/// Internal per-player values in registered player order.
+pub fn registered_scores<T>(side_swap: bool, mut score: [T; MAX_PLAYERS]) -> [T; MAX_PLAYERS] {
+    if side_swap { score.swap(0, 1); }
+    score
+}
+
+/// Sentinel projectile (unused slot)
+pub const EMPTY_PROJECTILE: Projectile = Projectile {
+    id: -1, owner_id: -1, x: 0, y: 0, vx: 0, vy: 0, lifetime: 0, weapon: WEAPON_NONE,
+    owner_immune_until: OWNER_IMMUNE_FOREVER, bounces_left: 0,
+};
+
+/// Sentinel player (unused slot past `State::player_count`)
+pub const EMPTY_PLAYER: Player = Player {
+    id: -1, x: 0, y: 0, vx: 0, vy: 0, facing: FACING_RIGHT, health: 0, lives: 0, shoot_cooldown: 0,
+    grounded: false, state_flags: 0, respawn_timer: 0, invincibility_source: invincibility::NONE,
+    weapon: WEAPON_NONE, ammo: 0, jumps_left: 0, wall_sliding: false, wall_dir: 0,
+    stomped_by: -1, stomping_on: -1, stomp_shake_progress: 0, stomp_last_shake_dir: 0,
+    stomp_auto_run_dir: 0, stomp_auto_run_timer: 0, stomp_cooldown: 0, stomp_cooldown_from: -1,
+    stomp_airborne_ticks: 0, stamina: 0, dash_cooldown: 0, last_damaged_by: -1, last_damage_tick: 0,
+};
+
+/// Sentinel weapon pickup (unused slot)
+pub const EMPTY_PICKUP: WeaponPickup = WeaponPickup {
+    id: -1, x: 0, y: 0, weapon: WEAPON_NONE, respawn_timer: 0, active_timer: 0, dropped_ammo: 0,
+};
+
+/// Sentinel health pickup (unused slot)
+pub const EMPTY_HEALTH_PICKUP: HealthPickup = HealthPickup { x: 0, y: 0, respawn_timer: 0 };
+
+/// The health pickups a match on `map` starts with: one per used health spawn, available.
+pub(super) fn initial_health_pickups(map: &Map) -> ([HealthPickup; MAX_HEALTH_PICKUPS], u8) {
+    let mut pickups = [EMPTY_HEALTH_PICKUP; MAX_HEALTH_PICKUPS];
+    let spawns = map.used_health_spawns();
+    for (hp, sp) in pickups.iter_mut().zip(spawns) {
+        *hp = HealthPickup { x: sp.x, y: sp.y, respawn_timer: 0 };
+    }
+    (pickups, spawns.len() as u8)
+}
+
+/// Small fixed-size list for kill events (max 4 per tick)
+#[derive(Clone, Copy, Debug)]
+pub struct KillList {
+    pub data: [(i32, i32); 4],
+    pub len: u8,
+}
+
+impl Default for KillList {
+    fn default() -> Self { Self::new() }
+}
+
+impl KillList {
+    pub const fn new() -> Self {
+        KillList { data: [(-1, -1); 4], len: 0 }
+    }
+    pub fn push(&mut self, killer: i32, victim: i32) {
+        if (self.len as usize) < self.data.len() {
+            self.data[self.len as usize] = (killer, victim);
+            self.len += 1;
+        }
+    }
+    pub fn contains_victim(&self, id: i32) -> bool {
+        for i in 0..self.len as usize {
+            if self.data[i].1 == id { return true; }
+        }
+        false
+    }
+    pub fn iter(&self) -> impl Iterator<Item = &(i32, i32)> {
+        self.data[..self.len as usize].iter()
+    }
+}
+
+/// What dealt a `DamageEvent`.
+pub mod damage_source {
+    /// Direct projectile hit (`DamageEvent::weapon` is the shot's weapon).
+    pub const PROJECTILE: u8 = 0;
+    /// Splash (rocket, grenade), on impact or on any other destruction.
+    pub const SPLASH: u8 = 1;
+    pub const STOMP: u8 = 2;
+    /// Sudden-death zone; nobody is the attacker.
+    pub const ZONE: u8 = 3;
+    /// Hazard platform contact; nobody is the attacker.
+    pub const HAZARD: u8 = 4;
+}
+
+/// One instance of damage, for floating damage numbers. `amount` is the health actually
+/// removed; (x, y) is the impact point: the projectile for direct hits, the victim's center
+/// otherwise.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DamageEvent {
+    pub victim: i32,
+    /// -1 for the zone.
+    pub attacker: i32,
+    pub amount: i32,
+    /// A `damage_source` value.
+    pub source: u8,
+    /// Weapon behind a projectile or splash hit, else `WEAPON_NONE`.
+    pub weapon: i8,
+    pub x: Fp,
+    pub y: Fp,
+}
+
+/// A shot fired: one per trigger pull, however many pellets it spawned. (x, y) is where the
+/// first projectile spawned (the muzzle).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ShotEvent {
+    pub shooter: i32,
+    pub weapon: i8,
+    pub pellets: u8,
+    pub x: Fp,
+    pub y: Fp,
+}
+
+/// A weapon pickup collected; (x, y) is the pickup's position.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PickupEvent {
+    pub player: i32,
+    pub pickup_id: i32,
+    pub weapon: i8,
+    pub x: Fp,
+    pub y: Fp,
+}
+
+/// What a `StompEvent` reports.
+pub mod stomp_event {
+    /// The rider landed on the victim's head.
+    pub const STARTED: u8 = 0;
+    /// The victim shook the rider off.
+    pub const BROKEN: u8 = 1;
+    /// The rider let go: jumped off, or the pair fell too long.
+    pub const DETACHED: u8 = 2;
+    /// A hit's knockback on the victim threw the rider off.
+    pub const KNOCKED_OFF: u8 = 3;
+}
+
+/// A stomp starting or ending alive. A stomp kill is a kill instead.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StompEvent {
+    /// A `stomp_event` value.
+    pub kind: u8,
+    pub rider: i32,
+    pub victim: i32,
+}
+
+/// A player selecting an emote wheel slot (`button::emote`) on a tick. Never affects play.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct EmoteEvent {
+    pub player: i32,
+    /// 1..=3.
+    pub emote: u8,
+}
+
+/// Damage records kept per tick; any beyond this are dropped (they only drive effects).
+pub const MAX_DAMAGE_EVENTS: usize = 16;
+/// Shots per tick: one per player.
+pub const MAX_SHOT_EVENTS: usize = MAX_PLAYERS;
+/// Pickups collected per tick.
+pub const MAX_PICKUP_EVENTS: usize = MAX_WEAPON_PICKUPS;
+/// Stomp starts and ends per tick.
+pub const MAX_STOMP_EVENTS: usize = 4;
+/// Emotes per tick: one per player.
+pub const MAX_EMOTE_EVENTS: usize = MAX_PLAYERS;
+
+/// Filler for the unused slots of an `EventList`.
+pub trait EmptyEvent: Copy {
+    const EMPTY: Self;
+}
+
+impl EmptyEvent for DamageEvent {
+    const EMPTY: Self = DamageEvent {
+        victim: -1, attacker: -1, amount: 0, source: damage_source::PROJECTILE, weapon: WEAPON_NONE, x: 0, y: 0,
+    };
+}
+
+impl EmptyEvent for ShotEvent {
+    const EMPTY: Self = ShotEvent { shooter: -1, weapon: WEAPON_NONE, pellets: 0, x: 0, y: 0 };
+}
+
+impl EmptyEvent for PickupEvent {
+    const EMPTY: Self = PickupEvent { player: -1, pickup_id: -1, weapon: WEAPON_NONE, x: 0, y: 0 };
+}
+
+impl EmptyEvent for StompEvent {
+    const EMPTY: Self = StompEvent { kind: stomp_event::STARTED, rider: -1, victim: -1 };
+}
+
+impl EmptyEvent for EmoteEvent {
+    const EMPTY: Self = EmoteEvent { player: -1, emote: 0 };
+}
+
+/// Small fixed-size list for one tick's events of one kind; pushes past `N` are dropped.
+#[derive(Clone, Copy, Debug)]
+pub struct EventList<T, const N: usize> {
+    pub data: [T; N],
+    pub len: u8,
+}
+
+impl<T: EmptyEvent, const N: usize> Default for EventList<T, N> {
+    fn default() -> Self { Self::new() }
+}
+
+impl<T: EmptyEvent, const N: usize> EventList<T, N> {
+    pub const fn new() -> Self {
+        EventList { data: [T::EMPTY; N], len: 0 }
+    }
+    pub fn push(&mut self, event: T) {
+        if (self.len as usize) < N {
+            self.data[self.len as usize] = event;
+            self.len += 1;
+        }
+    }
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.data[..self.len as usize].iter()
+    }
+}
+
+pub type DamageList = EventList<DamageEvent, MAX_DAMAGE_EVENTS>;
+pub type ShotList = EventList<ShotEvent, MAX_SHOT_EVENTS>;
+pub type PickupList = EventList<PickupEvent, MAX_PICKUP_EVENTS>;
+pub type StompList = EventList<StompEvent, MAX_STOMP_EVENTS>;
+pub type EmoteList = EventList<EmoteEvent, MAX_EMOTE_EVENTS>;
+
+/// Where event sites report to: `()` drops everything (plain `step_mut`, the guests),
+/// `TickEvents` keeps it (`step_mut_events`).
+pub(super) trait EventSink {
+    fn record(&mut self, event: DamageEvent);
+    fn shot(&mut self, event: ShotEvent);
+    fn pickup(&mut self, event: PickupEvent);
+    fn stomp(&mut self, event: StompEvent);
+    fn emote(&mut self, event: EmoteEvent);
+}
+
+impl EventSink for () {
+    #[inline(always)]
+    fn record(&mut self, _event: DamageEvent) {}
+    #[inline(always)]
+    fn shot(&mut self, _event: ShotEvent) {}
+    #[inline(always)]
+    fn pickup(&mut self, _event: PickupEvent) {}
+    #[inline(always)]
+    fn stomp(&mut self, _event: StompEvent) {}
+    #[inline(always)]
+    fn emote(&mut self, _event: EmoteEvent) {}
+}
+
+impl EventSink for TickEvents {
+    fn record(&mut self, event: DamageEvent) {
+        self.damage.push(event);
+    }
+    fn shot(&mut self, event: ShotEvent) {
+        self.shots.push(event);
+    }
+    fn pickup(&mut self, event: PickupEvent) {
+        self.pickups.push(event);
+    }
+    fn stomp(&mut self, event: StompEvent) {
+        self.stomps.push(event);
+    }
+    fn emote(&mut self, event: EmoteEvent) {
+        self.emotes.push(event);
+    }
+}
+
+// -- PRNG (pure integer) -----------------------------------------------------
+
+pub fn prng_int_range(state: u32, min: i32, max: i32) -> (i32, u32) {
+    let s = state.wrapping_add(0x6D2B79F5);
+    let t = (s as u64).wrapping_mul((s ^ (s >> 15)) as u64);
+    let t = t.wrapping_add(t.wrapping_mul(t | 1));
+    let result = ((t ^ (t >> 14)) >> 16) as u32;
+    let range = (max - min + 1) as u32;
+    let val = ((result as u64 * range as u64) >> 32) as i32;
+    (min + val, s)
+}
+
+/// Weighted draw: index `i` with probability `weights[i] / sum(weights)`, by walking the
+/// cumulative weights against one `prng_int_range` draw over the total. Weights must be >= 0
+/// with a positive sum.
+pub fn prng_weighted_index(state: u32, weights: &[i32]) -> (usize, u32) {
+    let total: i32 = weights.iter().sum();
+    let (draw, next) = prng_int_range(state, 0, total - 1);
+    let mut cumulative = 0;
+    for (i, &w) in weights.iter().enumerate() {
+        cumulative += w;
+        if draw < cumulative { return (i, next); }
+    }
+    (weights.len() - 1, next)
+}
+
+// -- Map + Init --------------------------------------------------------------
+
+pub fn arena_map() -> Map {
+    Map {
+        width: fp(960),
+        height: fp(540),
+        platforms: [
+            Platform { x: fp(0), y: fp(512), width: fp(960), height: fp(32), one_way: false, hazard_damage: 0 },
+            Platform { x: fp(128), y: fp(416), width: fp(176), height: fp(16), one_way: true, hazard_damage: 0 },
+            Platform { x: fp(672), y: fp(416), width: fp(176), height: fp(16), one_way: true, hazard_damage: 0 },
+            Platform { x: fp(352), y: fp(304), width: fp(256), height: fp(16), one_way: true, hazard_damage: 0 },
+            Platform { x: fp(64), y: fp(208), width: fp(144), height: fp(16), one_way: true, hazard_damage: 0 },
+            Platform { x: fp(752), y: fp(208), width: fp(144), height: fp(16), one_way: true, hazard_damage: 0 },
+            EMPTY_PLATFORM, // unused
+            EMPTY_PLATFORM, // unused
+        ],
+        spawns: [
+            SpawnPoint { x: fp(144), y: fp(480) },
+            SpawnPoint { x: fp(832), y: fp(480) },
+            SpawnPoint { x: fp(432), y: fp(272) },
+            SpawnPoint { x: fp(480), y: fp(176) },
+        ],
+        weapon_spawns: [
+            SpawnPoint { x: fp(192), y: fp(384) },
+            SpawnPoint { x: fp(736), y: fp(384) },
+            SpawnPoint { x: fp(464), y: fp(272) },
+            SpawnPoint { x: fp(464), y: fp(480) },
+        ],
+        health_spawns: [SpawnPoint { x: 0, y: 0 }; MAX_HEALTH_PICKUPS],
+        health_spawn_count: 0,
+        index: PlatformIndex::default(),
+    }
+}
+
+/// Reflect a map across its vertical center line (exact integer math). Player spawns are
+/// top-left corners, so they're reflected as a PLAYER_WIDTH box; weapon and health spawns are
+/// points.
+/// Unused (zero-size) platform slots stay zeroed. Applying it twice returns the original map.
+/// An indexed map's mirror is indexed too.
+pub fn mirror_map(map: &Map) -> Map {
+    let mut m = map.clone();
+    for p in &mut m.platforms {
+        if p.width == 0 && p.height == 0 { continue; }
+        p.x = map.width - p.x - p.width;
+    }
+    for s in &mut m.spawns {
+        s.x = map.width - s.x - PLAYER_WIDTH;
+    }
+    for s in m.weapon_spawns.iter_mut().chain(&mut m.health_spawns) {
+        s.x = map.width - s.x;
+    }
+    if map.is_indexed() {
+        m.build_index();
+    }
+    m
+}
+
+/// Per-match rule overrides (warmup, custom modes). Default = standard match.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FpMatchConfig {
+    pub initial_lives: i32,
+    pub match_duration: i32,
+    pub sudden_death: i32,
+    /// Per-player favor-the-shooter lag in ticks (clamped to LAG_RING - 1). [0, 0] = off.
+    /// Only players 0 and 1 have a setting; the rest shoot without lag compensation. Both
+    /// bytes are in every `commitment` and encoded state, so giving each of `MAX_PLAYERS` one
+    /// would change the config commitment and state format of every match, two-player ones
+    /// included.
+    #[serde(default)]
+    pub lag_ticks: [u8; 2],
+    /// Side-swapped rematch: play on `mirror_map` of the base map, so P0 starts on the
+    /// mirrored left spawn (now on the right) and the starting facings swap.
+    #[serde(default)]
+    pub mirrored: bool,
+    /// Wall stamina ruleset: sliding drains stamina, wall jumps cost it, the ground
+    /// regenerates it, and at zero the player can't wall slide.
+    #[serde(default)]
+    pub stamina: bool,
+    /// Pickup despawn ruleset: an active pickup left uncollected for `ACTIVE_DESPAWN_TICKS`
+    /// enters the respawn cycle and comes back as a new draw, so far-side spawns keep rotating.
+    #[serde(default)]
+    pub active_despawn: bool,
+    /// Players in the match, 2..=MAX_PLAYERS. Players past the first two spawn at
+    /// `Map::spawns[i]` facing the center.
+    #[serde(default = "default_player_count")]
+    pub player_count: u8,
+    /// How the sudden-death zone closes in, a `zone_mode`: the default pinch from the sides,
+    /// the ceiling coming down, or a shrinking box.
+    #[serde(default)]
+    pub zone_mode: u8,
+}
+
+fn default_player_count() -> u8 {
+    2
+}
+
+impl Default for FpMatchConfig {
+    fn default() -> Self {
+        FpMatchConfig {
+            initial_lives: INITIAL_LIVES,
+            match_duration: MATCH_DURATION_TICKS,
+            sudden_death: SUDDEN_DEATH_START_TICK,
+            lag_ticks: [0, 0],
+            mirrored: false,
+            stamina: false,
+            active_despawn: false,
+            player_count: 2,
+            zone_mode: zone_mode::PINCH,
+        }
+    }
+}
+
+impl FpMatchConfig {
+    /// The map this config is played on: `base` as authored, or its mirror.
+    pub fn oriented_map(&self, base: &Map) -> Map {
+        if self.mirrored { mirror_map(base) } else { base.clone() }
+    }
+
+    /// SHA-256 over every rule field, committed to the journal so a proof attests which
+    /// ruleset (and orientation) was played.
+    pub fn commitment(&self) -> [u8; 32] {
+        let mut h = Sha256::new();
+        h.update(self.initial_lives.to_le_bytes());
+        h.update(self.match_duration.to_le_bytes());
+        h.update(self.sudden_death.to_le_bytes());
+        h.update(self.lag_ticks);
+        h.update([self.mirrored as u8]);
+        h.update([self.stamina as u8]);
+        h.update([self.active_despawn as u8]);
+        // Only a wider match hashes its player count, so two-player commitments are unchanged
+        if self.player_count != 2 {
+            h.update([self.player_count]);
+        }
+        // Likewise the zone mode past the pinch every match used to play, tagged so it can't
+        // pass for a player count
+        if self.zone_mode != zone_mode::PINCH {
+            h.update([0xD0, self.zone_mode]);
+        }
+        h.finalize().into()
+    }
+}
+
+pub fn create_initial_state(seed: u32, map: &Map) -> State {
+    create_initial_state_cfg(seed, map, INITIAL_LIVES, MATCH_DURATION_TICKS, SUDDEN_DEATH_START_TICK)
+}
+
+#[inline(always)]
+fn initial_player(id: i32, spawn: &SpawnPoint, facing: i32, lives: i32) -> Player {
+    Player {
+        id,
+        x: spawn.x,
+        y: spawn.y,
+        vx: 0, vy: 0,
+        facing,
+        health: MAX_HEALTH,
+        lives,
+        shoot_cooldown: 0,
+        grounded: false,
+        state_flags: flag::ALIVE,
+        respawn_timer: 0,
+        invincibility_source: invincibility::NONE,
+        weapon: WEAPON_NONE,
+        ammo: 0,
+        jumps_left: MAX_JUMPS,
+        wall_sliding: false,
+        wall_dir: 0,
+        stomped_by: -1,
+        stomping_on: -1,
+        stomp_shake_progress: 0,
+        stomp_last_shake_dir: 0,
+        stomp_auto_run_dir: 0,
+        stomp_auto_run_timer: 0,
+        stomp_cooldown: 0,
+        stomp_cooldown_from: -1,
+        stomp_airborne_ticks: 0,
+        stamina: STAMINA_MAX,
+        dash_cooldown: 0,
+        last_damaged_by: -1,
+        last_damage_tick: 0,
+    }
+}
+
+#[inline(always)]
+fn initial_pickup(i: usize, map: &Map) -> WeaponPickup {
+    WeaponPickup {
+        id: i as i32,
+        x: map.weapon_spawns[i].x,
+        y: map.weapon_spawns[i].y,
+        weapon: WEAPON_ROTATION[i % WEAPON_COUNT],
+        respawn_timer: 0,
+        active_timer: 0,
+        dropped_ammo: 0,
+    }
+}
+
+pub fn create_initial_state_cfg(
+    seed: u32, map: &Map,
+    initial_lives: i32, match_duration: i32, sudden_death: i32,
+) -> State {
+    let mut weapon_pickups = [EMPTY_PICKUP; MAX_WEAPON_PICKUPS];
+    for i in 0..NUM_WEAPON_SPAWNS {
+        weapon_pickups[i] = initial_pickup(i, map);
+    }
+    let (health_pickups, health_pickup_count) = initial_health_pickups(map);
+
+    State {
+        tick: 0,
+        players: widen([
+            initial_player(0, &map.spawns[0], FACING_RIGHT, initial_lives),
+            initial_player(1, &map.spawns[1], FACING_LEFT, initial_lives),
+        ], EMPTY_PLAYER),
+        player_count: 2,
+        projectiles: [EMPTY_PROJECTILE; MAX_PROJECTILES],
+        proj_count: 0,
+        weapon_pickups,
+        pickup_count: NUM_WEAPON_SPAWNS as u8,
+        health_pickups,
+        health_pickup_count,
+        rng_state: seed,
+        score: [0; MAX_PLAYERS],
+        env_kills: [0; MAX_PLAYERS],
+        pickup_stats: PickupStats::default(),
+        next_proj_id: 0,
+        arena_left: 0,
+        arena_right: map.width,
+        arena_top: 0,
+        arena_bottom: map.height,
+        match_over: false,
+        winner: -1,
+        death_linger_timer: 0,
+        final_blow: false,
+        prev_buttons: [0; MAX_PLAYERS],
+        cfg_initial_lives: initial_lives,
+        cfg_match_duration: match_duration,
+        cfg_sudden_death: sudden_death,
+        cfg_zone_mode: zone_mode::PINCH,
+        cfg_lag_ticks: [0, 0],
+        pos_history_x: widen([[map.spawns[0].x; LAG_RING], [map.spawns[1].x; LAG_RING]], [0; LAG_RING]),
+        pos_history_y: widen([[map.spawns[0].y; LAG_RING], [map.spawns[1].y; LAG_RING]], [0; LAG_RING]),
+        cfg_mirrored: false,
+        cfg_stamina: false,
+        cfg_active_despawn: false,
+        side_swap: side_swap_for_seed(seed),
+        map: Arc::new(map.clone()),
+    }
+}
+
+/// Starting facings: each player faces the opponent.
+#[inline(always)]
+fn initial_facings(mirrored: bool) -> [i32; 2] {
+    if mirrored { [FACING_LEFT, FACING_RIGHT] } else { [FACING_RIGHT, FACING_LEFT] }
+}
+
+/// Seat players 2.. of a wider match at their spawns, facing the center of the map.
+pub(super) fn seat_extra_players(state: &mut State, map: &Map, player_count: u8, lives: i32) {
+    assert!((2..=MAX_PLAYERS as u8).contains(&player_count), "{player_count} players");
+    state.player_count = player_count;
+    for i in 2..player_count as usize {
+        let spawn = &map.spawns[i];
+        let facing = if spawn.x + PLAYER_WIDTH / 2 < map.width / 2 { FACING_RIGHT } else { FACING_LEFT };
+        state.players[i] = initial_player(i as i32, spawn, facing, lives);
+        state.pos_history_x[i] = [spawn.x; LAG_RING];
+        state.pos_history_y[i] = [spawn.y; LAG_RING];
+    }
+}
+
+/// A two-player array widened to `MAX_PLAYERS` slots, the rest `fill`.
+#[inline(always)]
+pub(super) fn widen<T: Copy>(two: [T; 2], fill: T) -> [T; MAX_PLAYERS] {
+    let mut all = [fill; MAX_PLAYERS];
+    all[..2].copy_from_slice(&two);
+    all
+}
+
+/// `create_initial_state_cfg` driven by an FpMatchConfig (includes lag and orientation).
+/// `map` must already be `cfg.oriented_map(base)`.
+pub fn create_initial_state_with(seed: u32, map: &Map, cfg: &FpMatchConfig) -> State {
+    let mut state = create_initial_state_cfg(seed, map, cfg.initial_lives, cfg.match_duration, cfg.sudden_death);
+    state.cfg_zone_mode = cfg.zone_mode;
+    state.cfg_lag_ticks = cfg.lag_ticks;
+    state.cfg_mirrored = cfg.mirrored;
+    state.cfg_stamina = cfg.stamina;
+    state.cfg_active_despawn = cfg.active_despawn;
+    arm_active_timers(&mut state);
+    let facings = initial_facings(cfg.mirrored);
+    state.players[0].facing = facings[0];
+    state.players[1].facing = facings[1];
+    seat_extra_players(&mut state, map, cfg.player_count, cfg.initial_lives);
+    state
+}
+
+/// Start every live pickup's despawn countdown, if the ruleset is on.
+fn arm_active_timers(state: &mut State) {
+    if !state.cfg_active_despawn { return; }
+    for wp in &mut state.weapon_pickups[..state.pickup_count as usize] {
+        wp.active_timer = ACTIVE_DESPAWN_TICKS;
+    }
+}
+
+/// Reinitialize `state` in place to exactly what `create_initial_state_with` would return.
+/// For batch simulation: reuses one State across matches instead of building a fresh one.
+pub fn reset_state(state: &mut State, seed: u32, map: &Map, cfg: &FpMatchConfig) {
+    state.tick = 0;
+    let facings = initial_facings(cfg.mirrored);
+    state.players[0] = initial_player(0, &map.spawns[0], facings[0], cfg.initial_lives);
+    state.players[1] = initial_player(1, &map.spawns[1], facings[1], cfg.initial_lives);
+    state.players[2..].fill(EMPTY_PLAYER);
+    state.projectiles.fill(EMPTY_PROJECTILE);
+    state.proj_count = 0;
+    for i in 0..MAX_WEAPON_PICKUPS {
+        state.weapon_pickups[i] = if i < NUM_WEAPON_SPAWNS { initial_pickup(i, map) } else { EMPTY_PICKUP };
+    }
+    state.pickup_count = NUM_WEAPON_SPAWNS as u8;
+    (state.health_pickups, state.health_pickup_count) = initial_health_pickups(map);
+    state.rng_state = seed;
+    state.score = [0; MAX_PLAYERS];
+    state.env_kills = [0; MAX_PLAYERS];
+    state.pickup_stats = PickupStats::default();
+    state.next_proj_id = 0;
+    state.set_arena_bounds(ZoneBounds::open(map.width, map.height));
+    state.match_over = false;
+    state.winner = -1;
+    state.death_linger_timer = 0;
+    state.final_blow = false;
+    state.prev_buttons = [0; MAX_PLAYERS];
+    state.cfg_initial_lives = cfg.initial_lives;
+    state.cfg_match_duration = cfg.match_duration;
+    state.cfg_sudden_death = cfg.sudden_death;
+    state.cfg_zone_mode = cfg.zone_mode;
+    state.cfg_lag_ticks = cfg.lag_ticks;
+    state.cfg_mirrored = cfg.mirrored;
+    state.cfg_stamina = cfg.stamina;
+    state.cfg_active_despawn = cfg.active_despawn;
+    arm_active_timers(state);
+    state.side_swap = side_swap_for_seed(seed);
+    if *state.map != *map {
+        state.map = Arc::new(map.clone());
+    }
+    for i in 0..2 {
+        state.pos_history_x[i] = [map.spawns[i].x; LAG_RING];
+        state.pos_history_y[i] = [map.spawns[i].y; LAG_RING];
+    }
+    state.pos_history_x[2..].fill([0; LAG_RING]);
+    state.pos_history_y[2..].fill([0; LAG_RING]);
+    seat_extra_players(state, map, cfg.player_count, cfg.initial_lives);
+}
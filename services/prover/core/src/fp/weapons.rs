@@ -0,0 +1,661 @@
+//! Weapon table, pickups, projectiles and hits.
+
+use super::*;
+
+// -- Weapon constants --------------------------------------------------------
+
+/// Weapon type: -1 = unarmed, 0=Pistol, 1=Shotgun, 2=Sniper, 3=Rocket, 4=SMG, 5=Grenade
+pub const WEAPON_NONE: i8 = -1;
+pub const WEAPON_PISTOL: i8 = 0;
+pub const WEAPON_SHOTGUN: i8 = 1;
+pub const WEAPON_SNIPER: i8 = 2;
+pub const WEAPON_ROCKET: i8 = 3;
+pub const WEAPON_SMG: i8 = 4;
+pub const WEAPON_GRENADE: i8 = 5;
+pub const WEAPON_COUNT: usize = 6;
+
+pub const WEAPON_PICKUP_RESPAWN_TICKS: i32 = 300;
+/// An active pickup nobody collects despawns after this many ticks (opt-in via
+/// `FpMatchConfig::active_despawn`) and starts the normal respawn cycle.
+pub const ACTIVE_DESPAWN_TICKS: i32 = 900;
+/// A weapon a dying player dropped goes away after this many ticks uncollected, instead of
+/// respawning as a random weapon.
+pub const DROPPED_WEAPON_DESPAWN_TICKS: i32 = 300;
+pub const PICKUP_RADIUS: Fp = 4096; // 16.0
+/// Health a health pickup restores, up to `MAX_HEALTH`.
+pub const HEALTH_PICKUP_HEAL: i32 = 35;
+/// Ticks a collected health pickup stays gone.
+pub const HEALTH_PICKUP_RESPAWN_TICKS: i32 = 600;
+
+/// Weapon rotation order for spawn points.
+pub const WEAPON_ROTATION: [i8; WEAPON_COUNT] = [
+    WEAPON_PISTOL, WEAPON_SHOTGUN, WEAPON_SNIPER, WEAPON_ROCKET, WEAPON_SMG, WEAPON_GRENADE,
+];
+
+/// Respawn odds per `WEAPON_ROTATION` entry: power weapons (sniper, rocket, grenade) come back
+/// rarer. Fixed by the image ID for now; when the weapon table becomes configurable these belong
+/// in `FpMatchConfig::commitment`.
+pub const WEAPON_RESPAWN_WEIGHTS: [i32; WEAPON_COUNT] = [4, 3, 1, 1, 3, 1];
+
+/// Weapon stats: [damage, speed(fp), cooldown, lifetime, ammo, pellets, splash_radius(fp), splash_damage]
+#[derive(Clone, Copy)]
+pub struct FpWeaponStats {
+    pub damage: i32,
+    pub speed: Fp,
+    pub cooldown: i32,
+    pub lifetime: i32,
+    pub ammo: i32,
+    pub pellets: i32,
+    /// Non-zero: the shot explodes on a player hit and on any other destruction (solid, out of
+    /// bounds, expiry), hurting everyone within this Manhattan distance.
+    pub splash_radius: Fp,
+    pub splash_damage: i32,
+    /// Hitbox half-size: player and solid tests inflate their boxes by this much.
+    /// 0 = point projectile.
+    pub proj_radius: Fp,
+    /// Ticks after spawn during which the projectile passes through its owner, or
+    /// OWNER_IMMUNE_ALWAYS to never hit the owner (every straight-line weapon). Weapons whose
+    /// shots can come back (bouncers) use a short window so a reflected shot is a real risk.
+    pub owner_immunity_ticks: i32,
+    /// Added to the projectile's vy every tick (0 = straight line).
+    pub gravity: Fp,
+    /// Solid contacts the projectile bounces off; the next one destroys it.
+    pub bounces: u8,
+    /// Speed a hit adds to the victim along the shot's direction. Splash pushes everyone it
+    /// damages away from the blast by the same amount, scaled by the damage falloff. See
+    /// `knockback_impulse` for the exact integer math the other sims must reproduce.
+    pub knockback: Fp,
+}
+
+/// Most a knockback leaves a player moving along either axis: the fall-speed cap, which
+/// collision already resolves in one tick without passing through a 16 px platform.
+pub const KNOCKBACK_MAX_SPEED: Fp = MAX_FALL_SPEED;
+
+/// `FpWeaponStats::owner_immunity_ticks`: the owner is never hit.
+pub const OWNER_IMMUNE_ALWAYS: i32 = -1;
+/// `Projectile::owner_immune_until` for shots that never hit their owner.
+pub const OWNER_IMMUNE_FOREVER: i32 = i32::MAX;
+
+/// Const lookup table — indexed by weapon type (0..WEAPON_COUNT). No branching, no function call overhead.
+/// The source of truth for weapon numbers, being what the guests prove: the f64
+/// `constants::weapon_stats` and the TypeScript `WEAPON_STATS` follow it, and the former is held
+/// to it field by field by `constants::tests`.
+pub const WEAPON_STATS: [FpWeaponStats; WEAPON_COUNT] = [
+    // 0: Pistol
+    FpWeaponStats {
+        damage: 20, speed: 2048 /*8.0*/, cooldown: 12, lifetime: 90,
+        ammo: 15, pellets: 1, splash_radius: 0, splash_damage: 0, proj_radius: 512 /*2.0*/,
+        owner_immunity_ticks: OWNER_IMMUNE_ALWAYS, gravity: 0, bounces: 0, knockback: 256 /*1.0*/,
+    },
+    // 1: Shotgun
+    FpWeaponStats {
+        damage: 12, speed: 1792 /*7.0*/, cooldown: 30, lifetime: 45,
+        ammo: 6, pellets: 5, splash_radius: 0, splash_damage: 0, proj_radius: 512 /*2.0*/,
+        owner_immunity_ticks: OWNER_IMMUNE_ALWAYS, gravity: 0, bounces: 0, knockback: 192 /*0.75 per pellet*/,
+    },
+    // 2: Sniper
+    FpWeaponStats {
+        damage: 80, speed: 4096 /*16.0*/, cooldown: 60, lifetime: 120,
+        ammo: 3, pellets: 1, splash_radius: 0, splash_damage: 0, proj_radius: 0,
+        owner_immunity_ticks: OWNER_IMMUNE_ALWAYS, gravity: 0, bounces: 0, knockback: 1024 /*4.0*/,
+    },
+    // 3: Rocket
+    FpWeaponStats {
+        damage: 50, speed: 1792 /*7.0*/, cooldown: 45, lifetime: 120,
+        ammo: 4, pellets: 1, splash_radius: 10240 /*40.0*/, splash_damage: 25, proj_radius: 1536 /*6.0*/,
+        owner_immunity_ticks: OWNER_IMMUNE_ALWAYS, gravity: 0, bounces: 0, knockback: 1536 /*6.0*/,
+    },
+    // 4: SMG
+    FpWeaponStats {
+        damage: 10, speed: 2304 /*9.0*/, cooldown: 5, lifetime: 60,
+        ammo: 40, pellets: 1, splash_radius: 0, splash_damage: 0, proj_radius: 512 /*2.0*/,
+        owner_immunity_ticks: OWNER_IMMUNE_ALWAYS, gravity: 0, bounces: 0, knockback: 64 /*0.25*/,
+    },
+    // 5: Grenade — lobbed: falls, bounces once, explodes on the next contact or at expiry
+    FpWeaponStats {
+        damage: 30, speed: 1536 /*6.0*/, cooldown: 40, lifetime: 120,
+        ammo: 3, pellets: 1, splash_radius: 12288 /*48.0*/, splash_damage: 35, proj_radius: 1024 /*4.0*/,
+        owner_immunity_ticks: OWNER_IMMUNE_ALWAYS, gravity: 32 /*0.125*/, bounces: 1, knockback: 1280 /*5.0*/,
+    },
+];
+
+/// Look up weapon stats by type. Falls back to Pistol for invalid values.
+#[inline(always)]
+pub fn fp_weapon_stats(weapon: i8) -> FpWeaponStats {
+    if weapon >= 0 && (weapon as usize) < WEAPON_COUNT {
+        WEAPON_STATS[weapon as usize]
+    } else {
+        WEAPON_STATS[0] // fallback: Pistol
+    }
+}
+
+// -- Weapon pickup logic -----------------------------------------------------
+
+/// Whether `p`'s box touches a pickup centered on (`x`, `y`).
+#[inline(always)]
+fn player_overlaps_pickup(p: &Player, x: Fp, y: Fp) -> bool {
+    x + PICKUP_RADIUS > p.x
+        && x - PICKUP_RADIUS < p.x + PLAYER_WIDTH
+        && y + PICKUP_RADIUS > p.y
+        && y - PICKUP_RADIUS < p.y + PLAYER_HEIGHT
+}
+
+/// Each available pickup goes to the first live player (by index) touching it. A spawned one
+/// grants the weapon's full ammo and starts its respawn timer; a dropped one grants the rounds
+/// it holds and is gone.
+#[inline(always)]
+pub(super) fn resolve_weapon_pickups<D: EventSink>(state: &mut State, events: &mut D) {
+    let mut pi = 0;
+    while pi < state.pickup_count as usize {
+        let wp = state.weapon_pickups[pi];
+        if wp.respawn_timer > 0 {
+            pi += 1;
+            continue;
+        }
+        let mut taken = false;
+        for i in 0..state.player_count as usize {
+            if state.players[i].state_flags & flag::ALIVE == 0 { continue; }
+            if player_overlaps_pickup(&state.players[i], wp.x, wp.y) {
+                if state.players[i].weapon != WEAPON_NONE {
+                    state.pickup_stats.shots_remaining_when_dropped[i] += state.players[i].ammo.max(0) as u32;
+                }
+                if let Some(n) = state.pickup_stats.pickups_collected[i].get_mut(wp.weapon as usize) {
+                    *n += 1;
+                }
+                state.players[i].weapon = wp.weapon;
+                state.players[i].ammo = if wp.dropped_ammo > 0 { wp.dropped_ammo } else { fp_weapon_stats(wp.weapon).ammo };
+                state.players[i].shoot_cooldown = 0;
+                events.pickup(PickupEvent {
+                    player: state.players[i].id, pickup_id: wp.id, weapon: wp.weapon, x: wp.x, y: wp.y,
+                });
+                taken = true;
+                break;
+            }
+        }
+        if taken && wp.dropped_ammo > 0 {
+            // The next pickup moved into this slot
+            remove_pickup(state, pi);
+            continue;
+        }
+        if taken {
+            state.weapon_pickups[pi].respawn_timer = WEAPON_PICKUP_RESPAWN_TICKS;
+            state.weapon_pickups[pi].active_timer = 0;
+        }
+        pi += 1;
+    }
+}
+
+/// Remove pickup slot `pi`, moving the later ones down so the live slots stay in order.
+#[inline(always)]
+fn remove_pickup(state: &mut State, pi: usize) {
+    let count = state.pickup_count as usize;
+    state.weapon_pickups.copy_within(pi + 1..count, pi);
+    state.weapon_pickups[count - 1] = EMPTY_PICKUP;
+    state.pickup_count -= 1;
+}
+
+/// Each dead player still holding a weapon drops it: with rounds left and a free slot, as a
+/// pickup where they fell holding those rounds (`DROPPED_WEAPON_DESPAWN_TICKS` to collect it),
+/// under the next unused id. Otherwise the weapon is lost.
+#[inline(always)]
+pub(super) fn drop_weapons(state: &mut State) {
+    for i in 0..state.player_count as usize {
+        let p = state.players[i];
+        if p.state_flags & flag::ALIVE != 0 || p.weapon == WEAPON_NONE { continue; }
+        state.players[i].weapon = WEAPON_NONE;
+        state.players[i].ammo = 0;
+        let slot = state.pickup_count as usize;
+        if p.ammo <= 0 || slot >= MAX_WEAPON_PICKUPS { continue; }
+        let id = state.weapon_pickups[..slot].iter().map(|wp| wp.id).max().unwrap_or(-1) + 1;
+        state.weapon_pickups[slot] = WeaponPickup {
+            id,
+            x: p.x + PLAYER_WIDTH / 2,
+            y: p.y + PLAYER_HEIGHT / 2,
+            weapon: p.weapon,
+            respawn_timer: 0,
+            active_timer: DROPPED_WEAPON_DESPAWN_TICKS,
+            dropped_ammo: p.ammo,
+        };
+        state.pickup_count += 1;
+    }
+}
+
+/// Health pickups: the first live player (by index) touching an available one, below
+/// `MAX_HEALTH`, is healed by `HEALTH_PICKUP_HEAL` (capped) and the pickup goes away for
+/// `HEALTH_PICKUP_RESPAWN_TICKS`. A player at full health walks past it.
+#[inline(always)]
+pub(super) fn resolve_health_pickups(state: &mut State) {
+    for hi in 0..state.health_pickup_count as usize {
+        let hp = state.health_pickups[hi];
+        if hp.respawn_timer > 0 {
+            continue;
+        }
+        for p in &mut state.players[..state.player_count as usize] {
+            if p.state_flags & flag::ALIVE == 0 || p.health >= MAX_HEALTH { continue; }
+            if player_overlaps_pickup(p, hp.x, hp.y) {
+                p.health = (p.health + HEALTH_PICKUP_HEAL).min(MAX_HEALTH);
+                state.health_pickups[hi].respawn_timer = HEALTH_PICKUP_RESPAWN_TICKS;
+                break;
+            }
+        }
+    }
+}
+
+#[inline(always)]
+pub(super) fn tick_pickup_timers(state: &mut State) {
+    let despawn = state.cfg_active_despawn;
+    let mut pi = 0;
+    while pi < state.pickup_count as usize {
+        let wp = &mut state.weapon_pickups[pi];
+        if wp.dropped_ammo > 0 {
+            // Dropped weapons don't respawn: out of time, the slot is freed
+            wp.active_timer -= 1;
+            if wp.active_timer <= 0 {
+                remove_pickup(state, pi);
+            } else {
+                pi += 1;
+            }
+            continue;
+        }
+        let slot = pi;
+        pi += 1;
+        if wp.respawn_timer <= 0 {
+            // Active and uncollected: despawn into the respawn cycle once the countdown runs out
+            if despawn && wp.active_timer > 0 {
+                wp.active_timer -= 1;
+                if wp.active_timer <= 0 {
+                    wp.respawn_timer = WEAPON_PICKUP_RESPAWN_TICKS;
+                }
+            }
+            continue;
+        }
+        wp.respawn_timer -= 1;
+        if wp.respawn_timer <= 0 {
+            let (idx, new_rng) = prng_weighted_index(state.rng_state, &WEAPON_RESPAWN_WEIGHTS);
+            state.rng_state = new_rng;
+            let wp = &mut state.weapon_pickups[slot];
+            wp.weapon = WEAPON_ROTATION[idx];
+            if despawn {
+                wp.active_timer = ACTIVE_DESPAWN_TICKS;
+            }
+        }
+    }
+    for hp in &mut state.health_pickups[..state.health_pickup_count as usize] {
+        hp.respawn_timer = (hp.respawn_timer - 1).max(0);
+    }
+}
+
+// -- Projectiles -------------------------------------------------------------
+
+/// Normalized shot direction for an aim input (8-way, by sign).
+/// Wall sliding: the horizontal component is forced away from the wall and vertical aim is
+/// kept, so the shot goes straight out or 45° up/down-away — never into the wall or straight up
+/// along it.
+#[inline(always)]
+fn shot_direction(player: &Player, aim_x: i8, aim_y: i8) -> (Fp, Fp) {
+    let aim_x = if player.wall_sliding { -player.wall_dir as i8 } else { aim_x };
+    if aim_x == 0 && aim_y == 0 {
+        (player.facing * ONE, 0)
+    } else if aim_y == 0 {
+        (if aim_x > 0 { ONE } else { -ONE }, 0)
+    } else if aim_x == 0 {
+        (0, if aim_y > 0 { ONE } else { -ONE })
+    } else {
+        // Diagonal: 1/sqrt(2) ~ 181/256
+        let d: Fp = 181;
+        (if aim_x > 0 { d } else { -d }, if aim_y > 0 { d } else { -d })
+    }
+}
+
+/// First tick a shot of `stats` fired on `tick` can hit its owner.
+#[inline(always)]
+fn owner_immune_until(tick: i32, stats: &FpWeaponStats) -> i32 {
+    if stats.owner_immunity_ticks < 0 {
+        OWNER_IMMUNE_FOREVER
+    } else {
+        tick.saturating_add(stats.owner_immunity_ticks)
+    }
+}
+
+/// Spawn a single projectile from a player's position toward their aim direction.
+#[inline(always)]
+fn spawn_projectile(player: &Player, aim_x: i8, aim_y: i8, id: i32, weapon: i8, tick: i32) -> Projectile {
+    let stats = fp_weapon_stats(weapon);
+    let (nx, ny) = shot_direction(player, aim_x, aim_y);
+
+    // Spawn at player edge in aim direction
+    let offset_x = mul(nx, PLAYER_WIDTH / 2);
+    let offset_y = mul(ny, PLAYER_HEIGHT / 2);
+
+    Projectile {
+        id,
+        owner_id: player.id,
+        x: player.x + PLAYER_WIDTH / 2 + offset_x,
+        y: player.y + PLAYER_HEIGHT / 2 + offset_y,
+        vx: mul(nx, stats.speed),
+        vy: mul(ny, stats.speed),
+        lifetime: stats.lifetime,
+        weapon,
+        owner_immune_until: owner_immune_until(tick, &stats),
+        bounces_left: stats.bounces,
+    }
+}
+
+/// Spawn weapon projectiles (handles shotgun multi-pellet spread).
+/// Returns number of projectiles spawned.
+#[inline(always)]
+pub(super) fn spawn_weapon_projectiles(
+    state: &mut State,
+    player_idx: usize,
+    aim_x: i8,
+    aim_y: i8,
+) -> u8 {
+    let weapon = state.players[player_idx].weapon;
+    if weapon == WEAPON_NONE { return 0; }
+
+    let stats = fp_weapon_stats(weapon);
+    let mut spawned = 0u8;
+
+    if stats.pellets == 1 {
+        // Single projectile
+        if (state.proj_count as usize) < MAX_PROJECTILES {
+            let p = state.players[player_idx];
+            let proj = spawn_projectile(&p, aim_x, aim_y, state.next_proj_id, weapon, state.tick);
+            state.projectiles[state.proj_count as usize] = proj;
+            state.proj_count += 1;
+            state.next_proj_id += 1;
+            spawned = 1;
+        }
+    } else {
+        // Multi-pellet (shotgun): spread perpendicular to aim direction.
+        // On a mirrored map the spread is computed in the authored frame and reflected after,
+        // so pellet order, PRNG jitter and mul() floor rounding all mirror exactly.
+        let mirror = if state.cfg_mirrored { -1 } else { 1 };
+        let (nx, ny) = shot_direction(&state.players[player_idx], aim_x, aim_y);
+        let nx = nx * mirror;
+
+        // Perpendicular direction: (-ny, nx)
+        let perp_x = -ny;
+        let perp_y = nx;
+
+        // Spawn at edge
+        let offset_x = mul(nx, PLAYER_WIDTH / 2) * mirror;
+        let offset_y = mul(ny, PLAYER_HEIGHT / 2);
+        let sx = state.players[player_idx].x + PLAYER_WIDTH / 2 + offset_x;
+        let sy = state.players[player_idx].y + PLAYER_HEIGHT / 2 + offset_y;
+
+        // Match TS: total arc = 14° (7° each side), 5 pellets at offsets -2,-1,0,1,2
+        // Outer pellet at offset ±2 should be at ±7°: sin(3.5°) ≈ 0.061 → 16/256 per step
+        const SPREAD_STEP: Fp = 16;
+
+        for i in 0..stats.pellets {
+            if (state.proj_count as usize) >= MAX_PROJECTILES { break; }
+
+            let offset = (i - stats.pellets / 2) as Fp;
+            // Add PRNG jitter: ±6/256 per pellet
+            let (jitter, new_rng) = prng_int_range(state.rng_state, -6, 6);
+            state.rng_state = new_rng;
+            let perp_amount = offset * SPREAD_STEP + jitter;
+            // Pellet counts come from WEAPON_STATS, so this stays a small fraction of ONE
+            debug_assert!(perp_amount.abs() <= (stats.pellets / 2) * SPREAD_STEP + 6);
+
+            // Final velocity = base + perpendicular spread
+            // perp_amount is in fp (33 ≈ sin 7.5°), mul gives fp result — no extra /ONE
+            let spread = mul(perp_amount, stats.speed);
+            let vx = (mul(nx, stats.speed) + mul(perp_x, spread)) * mirror;
+            // Upward bias: nudge pellets slightly upward (matches TS: dy -= 0.06)
+            // 0.06 in fp = 15; mul(15, speed) ≈ 0.06 * speed in velocity space
+            let vy = mul(ny, stats.speed) + mul(perp_y, spread) - mul(15, stats.speed);
+
+            state.projectiles[state.proj_count as usize] = Projectile {
+                id: state.next_proj_id,
+                owner_id: state.players[player_idx].id,
+                x: sx,
+                y: sy,
+                vx,
+                vy,
+                lifetime: stats.lifetime,
+                weapon,
+                owner_immune_until: owner_immune_until(state.tick, &stats),
+                bounces_left: stats.bounces,
+            };
+            state.proj_count += 1;
+            state.next_proj_id += 1;
+            spawned += 1;
+        }
+    }
+
+    spawned
+}
+
+#[inline(always)]
+fn is_out_of_bounds(proj: &Projectile, map: &Map) -> bool {
+    let m = OOB_MARGIN;
+    proj.x < -m || proj.x > map.width + m || proj.y < -m || proj.y > map.height + m
+}
+
+/// Check if a projectile hits any platform, map boundary, ceiling, or floor.
+/// Uses map bounds (not arena/zone bounds) — bullets pass through the death zone.
+#[inline(always)]
+pub(super) fn hits_solid(proj: &Projectile, map: &Map) -> bool {
+    let r = fp_weapon_stats(proj.weapon).proj_radius;
+    // Check platform collision (4px buffer above surface for visual consistency)
+    let buf: Fp = 4 << FRAC;
+    for i in slots(map.platforms_near(proj.x - r, proj.y - r, proj.x + r, proj.y + buf + r)) {
+        let plat = &map.platforms[i];
+        if plat.width == 0 { continue; }
+        if aabb_hit(proj.x, proj.y, plat.x - r, plat.y - buf - r, plat.width + 2 * r, plat.height + buf + 2 * r) {
+            return true;
+        }
+    }
+    // Map boundary walls (NOT zone — bullets pass through zone)
+    if proj.x - r <= 0 || proj.x + r >= map.width { return true; }
+    // Ceiling and floor
+    if proj.y - r <= 0 || proj.y + r >= map.height { return true; }
+    false
+}
+
+/// Move a projectile one tick (after its weapon's gravity). Returns false once it has
+/// expired, left the map or hit a solid with no bounces left; the checks short-circuit in that
+/// order (cheapest first).
+#[inline(always)]
+pub(super) fn advance_projectile(proj: &mut Projectile, map: &Map) -> bool {
+    proj.vy += fp_weapon_stats(proj.weapon).gravity;
+    proj.x += proj.vx;
+    proj.y += proj.vy;
+    proj.lifetime -= 1;
+    if proj.lifetime <= 0 || is_out_of_bounds(proj, map) { return false; }
+    if !hits_solid(proj, map) { return true; }
+    if proj.bounces_left == 0 { return false; }
+    bounce_projectile(proj, map);
+    true
+}
+
+/// Bounce off the solid the last move ran into: back to the previous position, with the
+/// velocity on each blocked axis reversed and halved (both on a corner hit).
+#[inline(always)]
+fn bounce_projectile(proj: &mut Projectile, map: &Map) {
+    let (px, py) = (proj.x - proj.vx, proj.y - proj.vy);
+    let blocked_x = hits_solid(&Projectile { y: py, ..*proj }, map);
+    let blocked_y = hits_solid(&Projectile { x: px, ..*proj }, map);
+    let (flip_x, flip_y) = if blocked_x || blocked_y { (blocked_x, blocked_y) } else { (true, true) };
+    if flip_x { proj.vx = -proj.vx / 2; }
+    if flip_y { proj.vy = -proj.vy / 2; }
+    proj.x = px;
+    proj.y = py;
+    proj.bounces_left -= 1;
+}
+
+#[inline(always)]
+fn aabb_hit(px: Fp, py: Fp, rx: Fp, ry: Fp, rw: Fp, rh: Fp) -> bool {
+    px >= rx && px <= rx + rw && py >= ry && py <= ry + rh
+}
+
+/// Split `magnitude` over the direction (dx, dy) so that |kx| + |ky| = magnitude, up to
+/// rounding (Manhattan, like splash distances): `kx = magnitude * dx / (|dx| + |dy|)` in i64,
+/// truncated toward zero, and the same for `ky`. A zero direction pushes straight up.
+#[inline(always)]
+fn knockback_impulse(magnitude: Fp, dx: Fp, dy: Fp) -> (Fp, Fp) {
+    let len = dx.abs() as i64 + dy.abs() as i64;
+    if len == 0 {
+        return (0, -magnitude);
+    }
+    ((magnitude as i64 * dx as i64 / len) as Fp, (magnitude as i64 * dy as i64 / len) as Fp)
+}
+
+/// Add a knockback impulse to a surviving, non-invincible victim (callers check both), each
+/// velocity axis then clamped to ±`KNOCKBACK_MAX_SPEED`. A stomp mount's rider is knocked off
+/// as if it had let go (`detach_stomp_rider`): the auto-run would overwrite the push.
+#[inline(always)]
+fn apply_knockback<D: EventSink>(players: &mut [Player], i: usize, kx: Fp, ky: Fp, events: &mut D) {
+    let p = &mut players[i];
+    p.vx = (p.vx + kx).clamp(-KNOCKBACK_MAX_SPEED, KNOCKBACK_MAX_SPEED);
+    p.vy = (p.vy + ky).clamp(-KNOCKBACK_MAX_SPEED, KNOCKBACK_MAX_SPEED);
+    let rider_id = p.stomped_by;
+    if rider_id < 0 {
+        return;
+    }
+    match players.iter().position(|r| r.id == rider_id) {
+        Some(rider_idx) => {
+            events.stomp(StompEvent { kind: stomp_event::KNOCKED_OFF, rider: rider_id, victim: players[i].id });
+            detach_stomp_rider(players, rider_idx, i);
+        }
+        None => clear_mount_fields(&mut players[i]),
+    }
+}
+
+/// Apply `proj`'s splash damage to all players within its weapon's radius (Manhattan distance).
+/// `skip_id` is the player who took the direct hit (to avoid double-damage); the owner is
+/// spared only while `owner_immune` (inside the shot's owner-immunity window). Survivors are
+/// knocked back away from the blast. Everyone hit is marked as hurt by the owner at `tick`.
+#[inline(always)]
+pub(super) fn apply_fp_splash_damage<D: EventSink>(
+    proj: &Projectile, owner_immune: bool, skip_id: Option<i32>, tick: i32,
+    players: &mut [Player],
+    kills: &mut KillList,
+    damage: &mut D,
+) {
+    let (ex, ey, owner_id) = (proj.x, proj.y, proj.owner_id);
+    let stats = fp_weapon_stats(proj.weapon);
+    let radius = stats.splash_radius;
+    let max_dmg = stats.splash_damage;
+
+    for i in 0..players.len() {
+        if !is_damageable(&players[i]) { continue; }
+        if owner_immune && players[i].id == owner_id { continue; }
+        // Skip direct-hit victim to prevent double-damage
+        if skip_id == Some(players[i].id) { continue; }
+
+        let pcx = players[i].x + PLAYER_WIDTH / 2;
+        let pcy = players[i].y + PLAYER_HEIGHT / 2;
+        let dist = (pcx - ex).abs() + (pcy - ey).abs();
+
+        if dist < radius {
+            // Linear falloff: dmg = max_dmg * (1 - dist/radius)
+            let dmg = max_dmg - (max_dmg as i64 * dist as i64 / radius as i64) as i32;
+            if dmg > 0 {
+                mark_damaged_by(&mut players[i], owner_id, tick);
+                let before = players[i].health;
+                let new_hp = before - dmg;
+                if new_hp <= 0 {
+                    let victim_id = players[i].id;
+                    players[i].health = 0;
+                    players[i].state_flags = 0;
+                    players[i].invincibility_source = invincibility::NONE;
+                    kills.push(owner_id, victim_id);
+                } else {
+                    players[i].health = new_hp;
+                    // Away from the blast, with the damage's falloff
+                    let push = stats.knockback - (stats.knockback as i64 * dist as i64 / radius as i64) as Fp;
+                    let (kx, ky) = knockback_impulse(push, pcx - ex, pcy - ey);
+                    apply_knockback(players, i, kx, ky, damage);
+                }
+                damage.record(DamageEvent {
+                    victim: players[i].id, attacker: owner_id, amount: before - players[i].health,
+                    source: damage_source::SPLASH, weapon: proj.weapon, x: pcx, y: pcy,
+                });
+            }
+        }
+    }
+}
+
+/// Resolve hits of the projectiles from slot `from` on in-place, adding kills to `kills`.
+/// Single pass over those projectiles: each one is either consumed by a hit or compacted
+/// straight into its final slot, so no per-slot hit flags are needed.
+#[inline(always)]
+pub(super) fn resolve_hits_mut<D: EventSink>(state: &mut State, from: usize, kills: &mut KillList, damage: &mut D) {
+    let count = state.proj_count as usize;
+    if count <= from { return; }
+    let n = state.player_count as usize;
+
+    let mut write = from;
+    for pi in from..count {
+        let proj = state.projectiles[pi];
+        let stats = fp_weapon_stats(proj.weapon);
+        let r = stats.proj_radius;
+        let lag = if proj.owner_id >= 0 && (proj.owner_id as usize) < 2 {
+            (state.cfg_lag_ticks[proj.owner_id as usize] as usize).min(LAG_RING - 1)
+        } else {
+            0
+        };
+        let lag_slot = (state.tick - lag as i32).rem_euclid(LAG_RING as i32) as usize;
+        let owner_immune = state.tick < proj.owner_immune_until;
+
+        let mut hit = false;
+        for i in 0..n {
+            if owner_immune && state.players[i].id == proj.owner_id { continue; }
+            if !is_damageable(&state.players[i]) { continue; }
+
+            let hit_now = aabb_hit(
+                proj.x, proj.y,
+                state.players[i].x - r, state.players[i].y - r,
+                PLAYER_WIDTH + 2 * r, PLAYER_HEIGHT + 2 * r,
+            );
+            // Favor the shooter: also accept a hit on where the victim was `lag` ticks ago
+            let hit_lagged = lag > 0 && aabb_hit(
+                proj.x, proj.y,
+                state.pos_history_x[i][lag_slot] - r, state.pos_history_y[i][lag_slot] - r,
+                PLAYER_WIDTH + 2 * r, PLAYER_HEIGHT + 2 * r,
+            );
+            if hit_now || hit_lagged {
+                hit = true;
+                let victim_id = state.players[i].id;
+                mark_damaged_by(&mut state.players[i], proj.owner_id, state.tick);
+                let before = state.players[i].health;
+                let new_hp = before - stats.damage;
+                if new_hp <= 0 {
+                    state.players[i].health = 0;
+                    state.players[i].state_flags = 0;
+                    state.players[i].invincibility_source = invincibility::NONE;
+                    kills.push(proj.owner_id, victim_id);
+                } else {
+                    state.players[i].health = new_hp;
+                    // Along the shot's direction of travel
+                    let (kx, ky) = knockback_impulse(stats.knockback, proj.vx, proj.vy);
+                    apply_knockback(&mut state.players[..n], i, kx, ky, damage);
+                }
+                damage.record(DamageEvent {
+                    victim: victim_id, attacker: proj.owner_id, amount: before - state.players[i].health,
+                    source: damage_source::PROJECTILE, weapon: proj.weapon, x: proj.x, y: proj.y,
+                });
+
+                // Splash damage on impact (skip direct-hit victim)
+                if stats.splash_radius > 0 {
+                    apply_fp_splash_damage(
+                        &proj, owner_immune, Some(victim_id), state.tick, &mut state.players[..n], kills, damage,
+                    );
+                }
+
+                break;
+            }
+        }
+
+        if !hit {
+            if write != pi {
+                state.projectiles[write] = proj;
+            }
+            write += 1;
+        }
+    }
+    state.proj_count = write as u8;
+}
@@ -0,0 +1,197 @@
+//! Environment damage: hazard platforms and the sudden-death zone. A hazard death is the
+//! environment's: it costs a life and counts as an env kill, and nobody scores. A zone death
+//! scores for the opponent who last hurt the player, if recently enough (`zone_kill_credit`),
+//! and is the environment's otherwise.
+
+use super::*;
+
+/// Hazard platforms: contact damage in bursts every `HAZARD_DAMAGE_INTERVAL` ticks, `hazard[i]`
+/// being what `move_and_collide_mut` reported for player `i`.
+#[inline(always)]
+pub(super) fn apply_hazard_damage<D: EventSink>(state: &mut State, hazard: &[i32; MAX_PLAYERS], events: &mut D) {
+    let n = state.player_count as usize;
+    if state.tick % HAZARD_DAMAGE_INTERVAL == 0 {
+        for i in 0..n {
+            let p = &mut state.players[i];
+            if hazard[i] <= 0 || !is_damageable(p) { continue; }
+            events.record(DamageEvent {
+                victim: p.id, attacker: -1, amount: hazard[i].min(p.health),
+                source: damage_source::HAZARD, weapon: WEAPON_NONE,
+                x: p.x + PLAYER_WIDTH / 2, y: p.y + PLAYER_HEIGHT / 2,
+            });
+            p.health -= hazard[i];
+            if p.health <= 0 {
+                p.health = 0;
+                p.lives -= 1;
+                state.env_kills[i] += 1;
+                p.state_flags = 0;
+                p.invincibility_source = invincibility::NONE;
+                p.vx = 0;
+                p.vy = 0;
+            }
+        }
+    }
+}
+
+/// The safe box of the sudden-death zone (`State::arena_left` and on): the zone is what lies
+/// outside it along the axes its `zone_mode` closes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ZoneBounds {
+    pub left: Fp,
+    pub right: Fp,
+    pub top: Fp,
+    pub bottom: Fp,
+}
+
+impl ZoneBounds {
+    /// The whole of a `width` x `height` map.
+    pub fn open(width: Fp, height: Fp) -> Self {
+        ZoneBounds { left: 0, right: width, top: 0, bottom: height }
+    }
+
+    /// Whether (x, y) is in the zone of `mode`. Only the closing axes count, so a pinch ignores
+    /// a stomp rider perched above the ceiling, as it always has.
+    #[inline(always)]
+    pub fn covers(&self, mode: u8, x: Fp, y: Fp) -> bool {
+        let outside_x = x < self.left || x > self.right;
+        let outside_y = y < self.top || y > self.bottom;
+        match mode {
+            zone_mode::DESCENT => outside_y,
+            zone_mode::BOX => outside_x || outside_y,
+            _ => outside_x,
+        }
+    }
+}
+
+impl core::fmt::Display for ZoneBounds {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "x {}..{}, y {}..{}", self.left, self.right, self.top, self.bottom)
+    }
+}
+
+/// Safe box of a `width` x `height` map at `tick` of a match whose zone starts closing at
+/// `sd_start`: the whole map before it, then over `SUDDEN_DEATH_DURATION` ticks the edges
+/// `mode` closes move in: the left and right ones to meet in the middle (`zone_mode::PINCH`,
+/// also what an unknown mode gets), the ceiling down to the floor (`DESCENT`), or all four to
+/// the middle (`BOX`).
+pub fn zone_bounds(tick: i32, sd_start: i32, mode: u8, width: Fp, height: Fp) -> ZoneBounds {
+    let open = ZoneBounds::open(width, height);
+    if tick < sd_start {
+        return open;
+    }
+    let elapsed = (tick as i64 - sd_start as i64).min(SUDDEN_DEATH_DURATION as i64) as i32;
+    let progress = if elapsed >= SUDDEN_DEATH_DURATION { ONE } else { (elapsed * ONE) / SUDDEN_DEATH_DURATION };
+    let closed_x = mul(progress, width / 2);
+    match mode {
+        zone_mode::DESCENT => ZoneBounds { top: mul(progress, height), ..open },
+        zone_mode::BOX => {
+            let closed_y = mul(progress, height / 2);
+            ZoneBounds { left: closed_x, right: width - closed_x, top: closed_y, bottom: height - closed_y }
+        }
+        _ => ZoneBounds { left: closed_x, right: width - closed_x, ..open },
+    }
+}
+
+/// The arena bounds `s` must store, the nearest to the stored ones when more than one fits.
+/// They are derived, not state: while the match is live they are `zone_bounds` of the tick
+/// (the whole map at tick 0, which no step produced), and once it is lingering or over they
+/// stay where the zone was when it stopped, which the tick only limits: closed as the mode
+/// closes (the edges that meet in the middle symmetric), and no further in than `zone_bounds`
+/// of the tick.
+pub fn consistent_arena_bounds(s: &State) -> ZoneBounds {
+    let (width, height) = (s.map.width, s.map.height);
+    if s.tick == 0 {
+        return ZoneBounds::open(width, height);
+    }
+    let max = zone_bounds(s.tick, s.cfg_sudden_death, s.cfg_zone_mode, width, height);
+    if !s.match_over && s.death_linger_timer == 0 {
+        return max;
+    }
+    let left = s.arena_left.clamp(0, max.left);
+    let top = s.arena_top.clamp(0, max.top);
+    let bottom = if s.cfg_zone_mode == zone_mode::BOX { height - top } else { height };
+    ZoneBounds { left, right: width - left, top, bottom }
+}
+
+/// Who a zone death of `p` at `tick` scores for: the last opponent to hurt them, if that was at
+/// most `ZONE_KILL_CREDIT_TICKS` ago. `None` leaves it an env kill.
+pub fn zone_kill_credit(p: &Player, tick: i32) -> Option<usize> {
+    let by = usize::try_from(p.last_damaged_by).ok()?;
+    (tick as i64 - p.last_damage_tick as i64 <= ZONE_KILL_CREDIT_TICKS as i64).then_some(by)
+}
+
+/// Sudden death — damage zone (not physical wall). The zone closes over
+/// `SUDDEN_DEATH_DURATION` ticks starting at `cfg_sudden_death`, the way `cfg_zone_mode` says
+/// (`zone_bounds`); players inside it take scaling
+/// tick damage (up to `ZONE_MAX_DPS` at full close). Bullets pass through the zone — it's
+/// cosmetic/damage only. A death in it scores for `zone_kill_credit`'s opponent before the
+/// elimination check, so a double zone death's tiebreak counts it.
+#[inline(always)]
+pub(super) fn apply_zone<D: EventSink>(state: &mut State, map: &Map, events: &mut D) {
+    let n = state.player_count as usize;
+    let current_tick = state.tick;
+    let sd_start = state.cfg_sudden_death;
+    let sd_dur = SUDDEN_DEATH_DURATION; // 300 ticks = 5 seconds
+    if !state.match_over && state.death_linger_timer == 0 && current_tick >= sd_start {
+        // Widened: a zone configured to start before tick 0 (down to i32::MIN) is further
+        // behind than an i32 can count
+        let elapsed = current_tick as i64 - sd_start as i64;
+        // Everything past the full close scales like it, so the rest of the zone math stays
+        // within sd_dur * ONE
+        let dmg_progress = elapsed.min(sd_dur as i64) as i32;
+        let bounds = zone_bounds(current_tick, sd_start, state.cfg_zone_mode, map.width, map.height);
+        state.set_arena_bounds(bounds);
+
+        // Zone damage: applied every 5 ticks in bursts. Same total DPS as before,
+        // but less spammy. At full close: 5 damage every 5 ticks (= 1 per tick avg).
+        // Before full: damage per burst scales with progress.
+        const ZONE_DMG_INTERVAL: i32 = 10;
+        if dmg_progress > 0 && elapsed % ZONE_DMG_INTERVAL as i64 == 0 {
+            // Burst damage scales with progress. At full close: ~10 damage per burst.
+            let burst_dmg = ((dmg_progress * ZONE_DMG_INTERVAL) / (sd_dur * 3)).max(1);
+            debug_assert!((1..=ZONE_DMG_INTERVAL / 3).contains(&burst_dmg));
+
+            for i in 0..n {
+                let p = &mut state.players[i];
+                if p.state_flags & flag::ALIVE == 0 { continue; }
+                let (px_center, py_center) = (p.x + PLAYER_WIDTH / 2, p.y + PLAYER_HEIGHT / 2);
+                if bounds.covers(state.cfg_zone_mode, px_center, py_center) {
+                    events.record(DamageEvent {
+                        victim: p.id, attacker: -1, amount: burst_dmg.min(p.health),
+                        source: damage_source::ZONE, weapon: WEAPON_NONE,
+                        x: px_center, y: py_center,
+                    });
+                    p.health -= burst_dmg;
+                    if p.health <= 0 {
+                        p.health = 0;
+                        p.lives -= 1;
+                        p.state_flags = 0;
+                        p.invincibility_source = invincibility::NONE;
+                        p.vx = 0;
+                        p.vy = 0;
+                        match zone_kill_credit(p, current_tick) {
+                            Some(killer) if killer < n => state.score[killer] += 1,
+                            _ => state.env_kills[i] += 1,
+                        }
+                    }
+                }
+            }
+        }
+
+        // Check for elimination after zone damage
+        let mut alive_count = 0i32;
+        let mut alive_id = -1i32;
+        for i in 0..n {
+            if state.players[i].state_flags & flag::ALIVE != 0 {
+                alive_count += 1;
+                alive_id = state.players[i].id;
+            }
+        }
+        if alive_count == 1 && state.death_linger_timer == 0 {
+            start_final_linger(state, alive_id);
+        } else if alive_count == 0 && state.death_linger_timer == 0 {
+            // Score-based tiebreaker: higher score wins, the lowest index wins ties
+            start_final_linger(state, top_score_index(&state.score[..n]) as i32);
+        }
+    }
+}
@@ -1,16 +1,71 @@
 use sha2::{Digest, Sha256};
 
-use crate::types::PlayerInput;
+use crate::fp::{self, FpInput};
+use crate::types::{GameMap, PlayerInput};
+
+/// Quantize an f64 input to the canonical packed form the zkVM sim consumes.
+///
+/// Aim components go through Rust's `f64 as i8` cast: truncate toward zero, saturate to
+/// -128..=127, NaN becomes 0. So 0.9 → 0, -1.5 → -1, 300.0 → 127. The host builds its
+/// prover input with this function, so the f64 and fp cores agree on every transcript.
+pub fn quantize_input(input: &PlayerInput) -> FpInput {
+    FpInput {
+        buttons: input.buttons,
+        aim_x: input.aim_x as i8,
+        aim_y: input.aim_y as i8,
+    }
+}
+
+/// Quantize an f64 map to the fixed-point map the zkVM sim plays on, the way the WASM client
+/// does: coordinates truncate to whole pixels, and slots past `NUM_PLATFORMS`, `NUM_SPAWNS`
+/// and `NUM_WEAPON_SPAWNS` are dropped (missing ones are empty). The f64 sim has no health
+/// pickups, so the result declares no health spawns.
+pub fn quantize_map(map: &GameMap) -> fp::Map {
+    let px = |v: f64| fp::fp(v as i32);
+    let point = |v: &crate::types::Vec2| fp::SpawnPoint { x: px(v.x), y: px(v.y) };
+    let mut out = fp::Map {
+        width: px(map.width),
+        height: px(map.height),
+        platforms: [fp::EMPTY_PLATFORM; fp::NUM_PLATFORMS],
+        spawns: [fp::SpawnPoint { x: 0, y: 0 }; fp::NUM_SPAWNS],
+        weapon_spawns: [fp::SpawnPoint { x: 0, y: 0 }; fp::NUM_WEAPON_SPAWNS],
+        health_spawns: [fp::SpawnPoint { x: 0, y: 0 }; fp::MAX_HEALTH_PICKUPS],
+        health_spawn_count: 0,
+        index: fp::PlatformIndex::default(),
+    };
+    for (slot, p) in out.platforms.iter_mut().zip(&map.platforms) {
+        *slot = fp::Platform {
+            x: px(p.x),
+            y: px(p.y),
+            width: px(p.width),
+            height: px(p.height),
+            one_way: p.one_way,
+            hazard_damage: p.hazard_damage,
+        };
+    }
+    for (slot, s) in out.spawns.iter_mut().zip(&map.spawn_points) {
+        *slot = point(s);
+    }
+    for (slot, s) in out.weapon_spawns.iter_mut().zip(&map.weapon_spawn_points) {
+        *slot = point(s);
+    }
+    out
+}
 
 /// SHA-256 hash of the full input transcript.
+///
+/// Byte-compatible with `fp::hash_transcript`: each input is quantized with
+/// [`quantize_input`] and hashed as 6 bytes per tick
+/// (p0.buttons p0.aim_x p0.aim_y p1.buttons p1.aim_x p1.aim_y).
 pub fn hash_transcript(transcript: &[[PlayerInput; 2]]) -> [u8; 32] {
     let mut hasher = Sha256::new();
     for tick_inputs in transcript {
-        for input in tick_inputs {
-            hasher.update(input.buttons.to_le_bytes());
-            hasher.update(input.aim_x.to_le_bytes());
-            hasher.update(input.aim_y.to_le_bytes());
-        }
+        let a = quantize_input(&tick_inputs[0]);
+        let b = quantize_input(&tick_inputs[1]);
+        hasher.update([
+            a.buttons, a.aim_x as u8, a.aim_y as u8,
+            b.buttons, b.aim_x as u8, b.aim_y as u8,
+        ]);
     }
     hasher.finalize().into()
 }
@@ -18,10 +73,22 @@ pub fn hash_transcript(transcript: &[[PlayerInput; 2]]) -> [u8; 32] {
 /// SHA-256 commitment of the seed.
 pub fn hash_seed(seed: u32) -> [u8; 32] {
     let mut hasher = Sha256::new();
-    hasher.update(seed.to_le_bytes());
+    hasher.update(chickenz_core_mini::seed_preimage(seed));
     hasher.finalize().into()
 }
 
+/// SHA-256 commitment to one player's seed entropy (commit-reveal seeding).
+pub fn commitment(reveal: [u8; 32]) -> [u8; 32] {
+    chickenz_core_mini::reveal_commitment_with(&reveal, |b| Sha256::digest(b).into())
+}
+
+/// Seed of a commit-reveal match: the first four bytes (LE) of SHA-256(reveal_a ‖ reveal_b),
+/// player 1's reveal first. The contract derives the same seed on-chain once both reveals are
+/// in and registers [`hash_seed`] of it, which the proof's `seed_commit` must equal.
+pub fn derive_seed(reveal_a: [u8; 32], reveal_b: [u8; 32]) -> u32 {
+    chickenz_core_mini::derive_seed_with(&reveal_a, &reveal_b, |b| Sha256::digest(b).into())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -43,6 +110,82 @@ mod tests {
         assert_ne!(hash_transcript(&t1), hash_transcript(&t2));
     }
 
+    #[test]
+    fn quantize_truncates_and_saturates() {
+        let q = |x: f64| quantize_input(&PlayerInput { buttons: 0, aim_x: x, aim_y: 0.0 }).aim_x;
+        assert_eq!(q(0.9), 0);
+        assert_eq!(q(1.0), 1);
+        assert_eq!(q(-1.5), -1);
+        assert_eq!(q(300.0), 127);
+        assert_eq!(q(-300.0), -128);
+        assert_eq!(q(f64::NAN), 0);
+    }
+
+    #[test]
+    fn transcript_hash_matches_fp() {
+        let transcript: Vec<[PlayerInput; 2]> = (0..300)
+            .map(|t| {
+                let x = (t % 7) as f64 - 3.0;
+                [
+                    PlayerInput { buttons: (t % 16) as u8, aim_x: x, aim_y: -0.5 * x },
+                    PlayerInput { buttons: (t % 5) as u8, aim_x: -x * 1.7, aim_y: 200.0 },
+                ]
+            })
+            .collect();
+        let fp_transcript: Vec<[FpInput; 2]> = transcript
+            .iter()
+            .map(|tick| [quantize_input(&tick[0]), quantize_input(&tick[1])])
+            .collect();
+        assert_eq!(hash_transcript(&transcript), crate::fp::hash_transcript(&fp_transcript));
+        assert_eq!(hash_transcript(&[]), crate::fp::hash_transcript(&[]));
+    }
+
+    #[test]
+    fn prover_arena_quantizes_to_the_fp_arena() {
+        assert_eq!(quantize_map(&crate::init::prover_arena()), crate::fp::arena_map());
+        assert_ne!(quantize_map(&crate::init::arena()), crate::fp::arena_map());
+    }
+
+    #[test]
+    fn quantize_map_truncates_and_fills_fixed_slots() {
+        use crate::fp::fp;
+        let mut map = crate::init::arena();
+        map.platforms[0].y = 504.9;
+        map.platforms[1].hazard_damage = 6;
+        map.platforms.truncate(2);
+        let q = quantize_map(&map);
+        assert_eq!((q.width, q.height), (fp(960), fp(540)));
+        assert_eq!((q.platforms[0].y, q.platforms[1].hazard_damage), (fp(504), 6));
+        assert_eq!(q.platforms[2..], [crate::fp::EMPTY_PLATFORM; crate::fp::NUM_PLATFORMS - 2]);
+        assert_eq!(q.spawns[0], crate::fp::SpawnPoint { x: fp(120), y: fp(476) });
+    }
+
+    #[test]
+    fn seed_hash_matches_fp() {
+        assert_eq!(hash_seed(42), crate::fp::hash_seed(42));
+    }
+
+    #[test]
+    fn seed_hash_matches_core_mini() {
+        let sha256 = |b: &[u8]| -> [u8; 32] { Sha256::digest(b).into() };
+        for seed in [0, 42, u32::MAX] {
+            assert_eq!(chickenz_core_mini::hash_seed_with(seed, sha256), hash_seed(seed));
+        }
+    }
+
+    #[test]
+    fn derived_seed_depends_on_both_reveals_in_order() {
+        let (a, b) = ([0x11; 32], [0x22; 32]);
+        let seed = derive_seed(a, b);
+        assert_eq!(seed, derive_seed(a, b));
+        assert_ne!(seed, derive_seed(b, a));
+        assert_ne!(seed, derive_seed(a, [0x23; 32]));
+        let h: [u8; 32] = Sha256::digest([a, b].concat()).into();
+        assert_eq!(seed, u32::from_le_bytes([h[0], h[1], h[2], h[3]]));
+        assert_eq!(commitment(a), <[u8; 32]>::from(Sha256::digest(a)));
+        assert_ne!(commitment(a), commitment(b));
+    }
+
     #[test]
     fn seed_hash_deterministic() {
         assert_eq!(hash_seed(42), hash_seed(42));
@@ -15,6 +15,8 @@ pub fn arena() -> GameMap {
                 y: 508.0,
                 width: 960.0,
                 height: 32.0,
+                one_way: false,
+                hazard_damage: 0,
             },
             // Lower platforms
             Platform {
@@ -22,12 +24,16 @@ pub fn arena() -> GameMap {
                 y: 410.0,
                 width: 170.0,
                 height: 16.0,
+                one_way: false,
+                hazard_damage: 0,
             },
             Platform {
                 x: 670.0,
                 y: 410.0,
                 width: 170.0,
                 height: 16.0,
+                one_way: false,
+                hazard_damage: 0,
             },
             // Mid platform
             Platform {
@@ -35,6 +41,8 @@ pub fn arena() -> GameMap {
                 y: 310.0,
                 width: 260.0,
                 height: 16.0,
+                one_way: false,
+                hazard_damage: 0,
             },
             // Upper platforms
             Platform {
@@ -42,12 +50,16 @@ pub fn arena() -> GameMap {
                 y: 210.0,
                 width: 140.0,
                 height: 16.0,
+                one_way: false,
+                hazard_damage: 0,
             },
             Platform {
                 x: 760.0,
                 y: 210.0,
                 width: 140.0,
                 height: 16.0,
+                one_way: false,
+                hazard_damage: 0,
             },
         ],
         spawn_points: vec![
@@ -89,6 +101,35 @@ pub fn arena() -> GameMap {
     }
 }
 
+/// The arena as the TS client plays it, which `quantize_map` turns into exactly
+/// `fp::arena_map`: what the prover replays for an input without a custom map. `arena()` is
+/// an older layout and quantizes to another map.
+pub fn prover_arena() -> GameMap {
+    let platform = |x: f64, y: f64, width: f64, height: f64, one_way: bool| Platform {
+        x,
+        y,
+        width,
+        height,
+        one_way,
+        hazard_damage: 0,
+    };
+    let point = |x: f64, y: f64| Vec2 { x, y };
+    GameMap {
+        width: 960.0,
+        height: 540.0,
+        platforms: vec![
+            platform(0.0, 512.0, 960.0, 32.0, false),
+            platform(128.0, 416.0, 176.0, 16.0, true),
+            platform(672.0, 416.0, 176.0, 16.0, true),
+            platform(352.0, 304.0, 256.0, 16.0, true),
+            platform(64.0, 208.0, 144.0, 16.0, true),
+            platform(752.0, 208.0, 144.0, 16.0, true),
+        ],
+        spawn_points: vec![point(144.0, 480.0), point(832.0, 480.0), point(432.0, 272.0), point(480.0, 176.0)],
+        weapon_spawn_points: vec![point(192.0, 384.0), point(736.0, 384.0), point(464.0, 272.0), point(464.0, 480.0)],
+    }
+}
+
 /// Create the initial game state from a match config.
 pub fn create_initial_state(config: &MatchConfig) -> GameState {
     let mut players = Vec::new();
@@ -108,8 +149,20 @@ pub fn create_initial_state(config: &MatchConfig) -> GameState {
             grounded: false,
             state_flags: player_state_flag::ALIVE,
             respawn_timer: 0,
+            invincibility_source: invincibility_source::NONE,
             weapon: None,
             ammo: 0,
+            jumps_left: MAX_JUMPS,
+            wall_sliding: false,
+            wall_dir: 0,
+            stomped_by: -1,
+            stomping_on: -1,
+            stomp_shake_progress: 0,
+            stomp_last_shake_dir: 0,
+            stomp_auto_run_dir: 0,
+            stomp_auto_run_timer: 0,
+            stomp_cooldown: 0,
+            stomp_airborne_ticks: 0,
         });
     }
 
@@ -141,6 +194,8 @@ pub fn default_config(seed: u32) -> MatchConfig {
         initial_lives: INITIAL_LIVES,
         match_duration_ticks: MATCH_DURATION_TICKS,
         sudden_death_start_tick: SUDDEN_DEATH_START_TICK,
+        mirrored: false,
+        gen_seed: None,
     }
 }
 
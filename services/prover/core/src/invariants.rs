@@ -0,0 +1,325 @@
+//! State invariants of the fixed-point sim, and the random-input soak that checks them.
+//!
+//! [`invariants`] checks one state on its own; [`transition_invariants`] adds what must hold
+//! between a state and the one `step_mut` made from it. [`soak_match`] plays a seeded match on
+//! random inputs and checks both every tick, so a rare sim bug turns up as a seed and a tick
+//! to replay. `cargo run --release -p chickenz-core --example soak` runs it for millions of
+//! ticks; the tests here run a few thousand.
+
+use core::fmt;
+
+use crate::fp::{self, flag, Fp, FpInput, FpMatchConfig, State, MAX_HEALTH, MAX_PLAYERS};
+use crate::fp::{MAX_PROJECTILES, PLAYER_HEIGHT, WEAPON_COUNT};
+
+/// How far past the map edges a player may be. Movement clamps players to the map, but a
+/// stomp rider sits a player height above its mount, so one riding a mount at the ceiling is
+/// above the map.
+pub const POSITION_SLACK: Fp = PLAYER_HEIGHT;
+
+/// The first invariant a state (or a step) broke.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InvariantViolation {
+    /// Health outside 0..=MAX_HEALTH, or a live player at 0.
+    Health { player: usize, health: i32 },
+    /// Lives below 0 or above the match's starting lives.
+    Lives { player: usize, lives: i32 },
+    /// A player outside the map by more than `POSITION_SLACK`.
+    Position { player: usize, x: Fp, y: Fp },
+    /// `proj_count` past `MAX_PROJECTILES`.
+    ProjectileCount { count: u8 },
+    /// A slot below `proj_count` that isn't a live projectile: the sentinel, an expired shot, an
+    /// unknown weapon or owner, or an id out of order.
+    Projectile { slot: usize, id: i32 },
+    /// `match_over` without exactly one winner among the players, or a winner while play goes on.
+    Winner { match_over: bool, winner: i32 },
+    /// A stomp link (`stomping_on` / `stomped_by`) between live players that the other one
+    /// doesn't return.
+    StompLink { player: usize },
+    /// A step that didn't advance the tick by one.
+    Tick { before: i32, after: i32 },
+    /// A score or env-kill count that went down.
+    ScoreDecreased { player: usize, before: u32, after: u32 },
+}
+
+impl fmt::Display for InvariantViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            InvariantViolation::Health { player, health } => write!(f, "player {player}: health {health}"),
+            InvariantViolation::Lives { player, lives } => write!(f, "player {player}: lives {lives}"),
+            InvariantViolation::Position { player, x, y } => {
+                write!(f, "player {player}: at ({}, {}) outside the map", x >> fp::FRAC, y >> fp::FRAC)
+            }
+            InvariantViolation::ProjectileCount { count } => write!(f, "proj_count {count}"),
+            InvariantViolation::Projectile { slot, id } => write!(f, "projectile slot {slot} (id {id}) isn't live"),
+            InvariantViolation::Winner { match_over, winner } => {
+                write!(f, "winner {winner} with match_over = {match_over}")
+            }
+            InvariantViolation::StompLink { player } => write!(f, "player {player}: one-sided stomp link"),
+            InvariantViolation::Tick { before, after } => write!(f, "tick went from {before} to {after}"),
+            InvariantViolation::ScoreDecreased { player, before, after } => {
+                write!(f, "player {player}: score or env kills fell from {before} to {after}")
+            }
+        }
+    }
+}
+
+fn player_index(state: &State, id: i32) -> Option<usize> {
+    state.active_players().iter().position(|p| p.id == id)
+}
+
+/// Check the invariants a single state must hold.
+pub fn invariants(state: &State) -> Result<(), InvariantViolation> {
+    let map = &state.map;
+    for (i, p) in state.active_players().iter().enumerate() {
+        let alive = p.state_flags & flag::ALIVE != 0;
+        if !(0..=MAX_HEALTH).contains(&p.health) || (alive && p.health == 0) {
+            return Err(InvariantViolation::Health { player: i, health: p.health });
+        }
+        if !(0..=state.cfg_initial_lives).contains(&p.lives) {
+            return Err(InvariantViolation::Lives { player: i, lives: p.lives });
+        }
+        let in_x = (-POSITION_SLACK..=map.width + POSITION_SLACK).contains(&p.x);
+        let in_y = (-POSITION_SLACK..=map.height + POSITION_SLACK).contains(&p.y);
+        if !in_x || !in_y {
+            return Err(InvariantViolation::Position { player: i, x: p.x, y: p.y });
+        }
+        // A link to or from a dead player is inert: `step_mut` drops it the tick after the death
+        if alive && p.stomping_on >= 0 {
+            let returned = player_index(state, p.stomping_on).is_some_and(|m| {
+                let mount = &state.players[m];
+                mount.stomped_by == p.id || mount.state_flags & flag::ALIVE == 0
+            });
+            if !returned {
+                return Err(InvariantViolation::StompLink { player: i });
+            }
+        }
+        if alive && p.stomped_by >= 0 {
+            let returned = player_index(state, p.stomped_by).is_some_and(|r| {
+                let rider = &state.players[r];
+                rider.stomping_on == p.id || rider.state_flags & flag::ALIVE == 0
+            });
+            if !returned {
+                return Err(InvariantViolation::StompLink { player: i });
+            }
+        }
+    }
+
+    let count = state.proj_count as usize;
+    if count > MAX_PROJECTILES {
+        return Err(InvariantViolation::ProjectileCount { count: state.proj_count });
+    }
+    // Slots past proj_count are scratch: compaction leaves stale copies there
+    let mut last_id = -1;
+    for (slot, proj) in state.projectiles[..count].iter().enumerate() {
+        let live = proj.id > last_id
+            && proj.id < state.next_proj_id
+            && proj.lifetime > 0
+            && (0..WEAPON_COUNT as i8).contains(&proj.weapon)
+            && player_index(state, proj.owner_id).is_some();
+        if !live {
+            return Err(InvariantViolation::Projectile { slot, id: proj.id });
+        }
+        last_id = proj.id;
+    }
+
+    let winner_ok = if state.match_over {
+        player_index(state, state.winner).is_some()
+    } else {
+        // Decided at the start of the linger, before match_over
+        state.winner == -1 || (state.death_linger_timer > 0 && player_index(state, state.winner).is_some())
+    };
+    if !winner_ok {
+        return Err(InvariantViolation::Winner { match_over: state.match_over, winner: state.winner });
+    }
+    Ok(())
+}
+
+/// Check `next` and what must hold between it and `prev`, the state `step_mut` made it from.
+pub fn transition_invariants(prev: &State, next: &State) -> Result<(), InvariantViolation> {
+    invariants(next)?;
+    if next.tick != prev.tick + 1 {
+        return Err(InvariantViolation::Tick { before: prev.tick, after: next.tick });
+    }
+    for player in 0..MAX_PLAYERS {
+        for (before, after) in [(prev.score, next.score), (prev.env_kills, next.env_kills)] {
+            if after[player] < before[player] {
+                return Err(InvariantViolation::ScoreDecreased {
+                    player,
+                    before: before[player],
+                    after: after[player],
+                });
+            }
+        }
+    }
+    Ok(())
+}
+
+/// The first broken invariant of a soak match, with what it takes to replay it.
+#[derive(Clone, Debug)]
+pub struct SoakFailure {
+    pub seed: u32,
+    /// The tick of the state that broke it.
+    pub tick: i32,
+    pub violation: InvariantViolation,
+    /// `encode_state` of the state before the failing step, and of the state it made.
+    pub before: Vec<u8>,
+    pub after: Vec<u8>,
+    /// The failing step's inputs (one per player in the match).
+    pub inputs: [FpInput; MAX_PLAYERS],
+}
+
+impl fmt::Display for SoakFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "seed {}, tick {}: {}", self.seed, self.tick, self.violation)
+    }
+}
+
+/// xorshift32: the soak's input and config source, kept apart from the sim's own PRNG.
+struct SoakRng(u32);
+
+impl SoakRng {
+    fn new(seed: u32) -> Self {
+        // Never zero, and nearby seeds diverge at once
+        SoakRng(seed.wrapping_mul(0x9E37_79B9) | 1)
+    }
+
+    fn next(&mut self) -> u32 {
+        let mut x = self.0;
+        x ^= x << 13;
+        x ^= x >> 17;
+        x ^= x << 5;
+        self.0 = x;
+        x
+    }
+
+    fn below(&mut self, n: u32) -> u32 {
+        self.next() % n
+    }
+}
+
+/// The match a soak seed plays: every ruleset switch, lag and player count drawn from the seed.
+pub fn soak_config(seed: u32) -> FpMatchConfig {
+    let mut rng = SoakRng::new(seed ^ 0xC0FF_EE00);
+    FpMatchConfig {
+        initial_lives: 1 + rng.below(3) as i32,
+        lag_ticks: [rng.below(fp::LAG_RING as u32) as u8, rng.below(fp::LAG_RING as u32) as u8],
+        mirrored: rng.below(2) == 1,
+        stamina: rng.below(2) == 1,
+        active_despawn: rng.below(2) == 1,
+        player_count: 2 + rng.below(MAX_PLAYERS as u32 - 1) as u8,
+        zone_mode: rng.below(fp::zone_mode::COUNT as u32) as u8,
+        ..FpMatchConfig::default()
+    }
+}
+
+/// Play `soak_config(seed)` on the arena with random inputs for up to `max_ticks` ticks (less
+/// if the match ends), checking `transition_invariants` after every step. Returns the ticks
+/// played.
+pub fn soak_match(seed: u32, max_ticks: u32) -> Result<u32, SoakFailure> {
+    let cfg = soak_config(seed);
+    let map = cfg.oriented_map(&fp::arena_map());
+    let mut state = fp::create_initial_state_with(seed, &map, &cfg);
+    let n = cfg.player_count as usize;
+    let mut rng = SoakRng::new(seed);
+    invariants(&state).map_err(|violation| SoakFailure {
+        seed,
+        tick: state.tick,
+        violation,
+        before: Vec::new(),
+        after: fp::encode_state(&state),
+        inputs: [fp::NULL_INPUT; MAX_PLAYERS],
+    })?;
+
+    for played in 0..max_ticks {
+        if state.match_over {
+            return Ok(played);
+        }
+        let mut inputs = [fp::NULL_INPUT; MAX_PLAYERS];
+        for input in &mut inputs[..n] {
+            let r = rng.next();
+            *input = FpInput { buttons: r as u8, aim_x: (r >> 8) as i8, aim_y: (r >> 16) as i8 };
+        }
+        let prev = state.clone();
+        fp::step_mut(&mut state, &inputs[..n]);
+        if let Err(violation) = transition_invariants(&prev, &state) {
+            return Err(SoakFailure {
+                seed,
+                tick: state.tick,
+                violation,
+                before: fp::encode_state(&prev),
+                after: fp::encode_state(&state),
+                inputs,
+            });
+        }
+    }
+    Ok(max_ticks)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fp::{arena_map, create_initial_state, NULL_INPUT};
+
+    #[test]
+    fn short_soak_keeps_every_invariant() {
+        let mut ticks = 0;
+        for seed in 0..8 {
+            match soak_match(seed, 1_000) {
+                Ok(played) => ticks += played,
+                Err(failure) => panic!("{failure}"),
+            }
+        }
+        assert!(ticks >= 2_000, "soak matches ended too early: {ticks} ticks");
+    }
+
+    #[test]
+    fn soak_is_reproducible_per_seed() {
+        assert_eq!(soak_config(7), soak_config(7));
+        let played = |seed| {
+            let cfg = soak_config(seed);
+            (cfg.player_count, cfg.initial_lives, cfg.mirrored)
+        };
+        assert!((0..16).map(played).any(|c| c != played(0)));
+        assert_eq!(soak_match(7, 300).unwrap(), soak_match(7, 300).unwrap());
+    }
+
+    #[test]
+    fn broken_states_are_reported() {
+        let state = create_initial_state(42, &arena_map());
+        invariants(&state).unwrap();
+
+        let mut s = state.clone();
+        s.players[1].health = MAX_HEALTH + 1;
+        assert_eq!(invariants(&s), Err(InvariantViolation::Health { player: 1, health: MAX_HEALTH + 1 }));
+
+        let mut s = state.clone();
+        s.players[0].x = s.map.width + 2 * POSITION_SLACK;
+        assert!(matches!(invariants(&s), Err(InvariantViolation::Position { player: 0, .. })));
+
+        let mut s = state.clone();
+        s.proj_count = 1;
+        assert_eq!(invariants(&s), Err(InvariantViolation::Projectile { slot: 0, id: -1 }));
+
+        let mut s = state.clone();
+        s.match_over = true;
+        assert_eq!(invariants(&s), Err(InvariantViolation::Winner { match_over: true, winner: -1 }));
+
+        let mut s = state.clone();
+        s.players[0].stomping_on = 1;
+        assert_eq!(invariants(&s), Err(InvariantViolation::StompLink { player: 0 }));
+        s.players[1].stomped_by = 0;
+        invariants(&s).unwrap();
+
+        let mut next = state.clone();
+        fp::step_mut(&mut next, &[NULL_INPUT; 2]);
+        transition_invariants(&state, &next).unwrap();
+        next.score[1] = 0;
+        let mut prev = state.clone();
+        prev.score[1] = 2;
+        assert_eq!(
+            transition_invariants(&prev, &next),
+            Err(InvariantViolation::ScoreDecreased { player: 1, before: 2, after: 0 })
+        );
+        assert_eq!(transition_invariants(&next, &state), Err(InvariantViolation::Tick { before: 1, after: 0 }));
+    }
+}
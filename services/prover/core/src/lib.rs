@@ -1,22 +1,31 @@
+#[cfg(any(test, feature = "compare"))]
+pub mod compare;
+pub mod bot;
 pub mod constants;
+pub mod diag;
 pub mod fp;
 pub mod hash;
 pub mod init;
+pub mod invariants;
 pub mod physics;
+pub mod prelude;
 pub mod prng;
 pub mod projectiles;
 pub mod step;
+pub mod summary;
 pub mod types;
 pub mod weapons;
 
+pub use chickenz_core_mini as core_mini;
+pub use chickenz_journal_layout as journal_layout;
 pub use constants::*;
 pub use hash::*;
 pub use init::*;
 pub use physics::{apply_gravity, apply_player_input, move_and_collide};
 pub use prng::*;
-pub use projectiles::{
-    is_out_of_bounds, move_projectile, resolve_projectile_hits, spawn_projectile,
-};
+pub use projectiles::{is_out_of_bounds, move_projectile, resolve_projectile_hits};
+#[allow(deprecated)]
+pub use projectiles::spawn_projectile;
 pub use step::step;
 pub use types::*;
 pub use weapons::*;
@@ -1,11 +1,16 @@
 use crate::constants::*;
 use crate::types::*;
 
-/// Accelerate/decelerate vx toward target, initiate jump if grounded, update facing.
-pub fn apply_player_input(p: &PlayerState, input: &PlayerInput) -> PlayerState {
+/// Accelerate/decelerate vx toward target, jump on the press edge (wall jump while sliding,
+/// else a ground or double jump while `jumps_left` allows), update facing.
+pub fn apply_player_input(p: &PlayerState, input: &PlayerInput, prev_input: &PlayerInput) -> PlayerState {
     if p.state_flags & player_state_flag::ALIVE == 0 {
         return *p;
     }
+    // A ridden player auto-runs and a rider is locked to its mount (`step`'s stomp handling)
+    if p.stomped_by >= 0 || p.stomping_on >= 0 {
+        return *p;
+    }
 
     // Target velocity from input
     let mut target_vx: f64 = 0.0;
@@ -30,9 +35,24 @@ pub fn apply_player_input(p: &PlayerState, input: &PlayerInput) -> PlayerState {
         vx = (vx + DECELERATION).min(0.0);
     }
 
+    // Jump edge detection: pressed now, not pressed last tick
+    let jump_edge = input.buttons & button::JUMP != 0 && prev_input.buttons & button::JUMP == 0;
     let mut vy = p.vy;
-    if input.buttons & button::JUMP != 0 && p.grounded {
-        vy = JUMP_VELOCITY;
+    let mut jumps_left = p.jumps_left;
+    let mut wall_sliding = p.wall_sliding;
+    let mut wall_dir = p.wall_dir;
+    if jump_edge && jumps_left > 0 {
+        if wall_sliding {
+            // Wall jump: push away from the wall
+            vx = WALL_JUMP_VX * -(wall_dir as f64);
+            vy = WALL_JUMP_VY;
+            wall_sliding = false;
+            wall_dir = 0;
+        } else {
+            // Normal/double jump
+            vy = JUMP_VELOCITY;
+        }
+        jumps_left -= 1;
     }
 
     // Facing from aim direction
@@ -47,51 +67,87 @@ pub fn apply_player_input(p: &PlayerState, input: &PlayerInput) -> PlayerState {
         vx,
         vy,
         facing: f,
+        jumps_left,
+        wall_sliding,
+        wall_dir,
         ..*p
     }
 }
 
-/// Apply gravity to vy, clamped to MAX_FALL_SPEED.
+/// Apply gravity to vy, clamped to MAX_FALL_SPEED (WALL_SLIDE_SPEED while wall sliding). A
+/// stomp rider has none: it moves with its mount.
 pub fn apply_gravity(p: &PlayerState) -> PlayerState {
-    if p.state_flags & player_state_flag::ALIVE == 0 {
+    if p.state_flags & player_state_flag::ALIVE == 0 || p.stomping_on >= 0 {
         return *p;
     }
-    let vy = (p.vy + GRAVITY).min(MAX_FALL_SPEED);
+    let max_fall = if p.wall_sliding { WALL_SLIDE_SPEED } else { MAX_FALL_SPEED };
+    let vy = (p.vy + GRAVITY).min(max_fall);
     PlayerState { vy, ..*p }
 }
 
-/// Integrate position and resolve collisions with platforms.
-/// Platforms are one-way: only collide when falling onto the top surface.
-/// Also clamp to map boundaries and dynamic arena bounds.
+/// Integrate position and resolve collisions with platforms: solid ones are full boxes, pushed
+/// out along the smallest overlap, one-way ones only catch a fall onto their top (there is no
+/// drop-through here). Clamps to the map height and the dynamic arena bounds, then detects a
+/// wall slide (falling while pressing into an arena wall or a solid platform's side) and
+/// refunds jumps: all of them on the ground, one on a wall if none are left. A stomp rider
+/// stays put: `step` locks it to its mount.
 pub fn move_and_collide(
     p: &PlayerState,
+    input: &PlayerInput,
     map: &GameMap,
     arena_left: f64,
     arena_right: f64,
 ) -> PlayerState {
-    if p.state_flags & player_state_flag::ALIVE == 0 {
+    if p.state_flags & player_state_flag::ALIVE == 0 || p.stomping_on >= 0 {
         return *p;
     }
 
+    let prev_bottom = p.y + PLAYER_HEIGHT;
     let mut x = p.x + p.vx;
     let mut y = p.y + p.vy;
+    let mut vx = p.vx;
     let mut vy = p.vy;
     let mut grounded = false;
 
-    // Platform collision (one-way: top surface only)
     for plat in &map.platforms {
-        let feet_before = p.y + PLAYER_HEIGHT;
-        let feet_after = y + PLAYER_HEIGHT;
-        let plat_top = plat.y;
-
-        if feet_before <= plat_top
-            && feet_after >= plat_top
-            && x + PLAYER_WIDTH > plat.x
+        if plat.width == 0.0 || plat.height == 0.0 {
+            continue;
+        }
+        let overlaps = x + PLAYER_WIDTH > plat.x
             && x < plat.x + plat.width
-        {
-            y = plat_top - PLAYER_HEIGHT;
+            && y + PLAYER_HEIGHT > plat.y
+            && y < plat.y + plat.height;
+        if !overlaps {
+            continue;
+        }
+        if plat.one_way {
+            // Land only when falling onto it from above its top
+            if vy >= 0.0 && prev_bottom <= plat.y {
+                y = plat.y - PLAYER_HEIGHT;
+                vy = 0.0;
+                grounded = true;
+            }
+            continue;
+        }
+        let overlap_left = (x + PLAYER_WIDTH) - plat.x;
+        let overlap_right = (plat.x + plat.width) - x;
+        let overlap_top = (y + PLAYER_HEIGHT) - plat.y;
+        let overlap_bottom = (plat.y + plat.height) - y;
+        let min_overlap = overlap_left.min(overlap_right).min(overlap_top).min(overlap_bottom);
+
+        if min_overlap == overlap_top {
+            y = plat.y - PLAYER_HEIGHT;
             vy = 0.0;
             grounded = true;
+        } else if min_overlap == overlap_bottom {
+            y = plat.y + plat.height;
+            vy = 0.0;
+        } else if min_overlap == overlap_left {
+            x = plat.x - PLAYER_WIDTH;
+            vx = 0.0;
+        } else {
+            x = plat.x + plat.width;
+            vx = 0.0;
         }
     }
 
@@ -112,11 +168,60 @@ pub fn move_and_collide(
         grounded = true;
     }
 
+    // Wall slide detection
+    let pressing_left = input.buttons & button::LEFT != 0;
+    let pressing_right = input.buttons & button::RIGHT != 0;
+    let mut wall_dir = 0;
+    if !grounded && vy > 0.0 {
+        if x <= arena_left && pressing_left {
+            wall_dir = -1;
+        } else if x + PLAYER_WIDTH >= arena_right && pressing_right {
+            wall_dir = 1;
+        } else {
+            // Platform side walls (2-pixel tolerance band); one-way platforms have none
+            for plat in map.platforms.iter().filter(|plat| !plat.one_way) {
+                if y + PLAYER_HEIGHT > plat.y && y < plat.y + plat.height {
+                    let right = x + PLAYER_WIDTH;
+                    if pressing_right && right >= plat.x && right <= plat.x + WALL_SLIDE_BAND {
+                        wall_dir = 1;
+                        break;
+                    }
+                    let edge = plat.x + plat.width;
+                    if pressing_left && x <= edge && x >= edge - WALL_SLIDE_BAND {
+                        wall_dir = -1;
+                        break;
+                    }
+                }
+            }
+        }
+    }
+    let wall_sliding = wall_dir != 0;
+
+    // Wall sliding: face away from the wall, no horizontal drift
+    let mut f = p.facing;
+    if wall_sliding {
+        f = wall_dir;
+        vx = 0.0;
+    }
+
+    let jumps_left = if grounded {
+        MAX_JUMPS
+    } else if wall_sliding && p.jumps_left == 0 {
+        1
+    } else {
+        p.jumps_left
+    };
+
     PlayerState {
         x,
         y,
+        vx,
         vy,
+        facing: f,
         grounded,
+        jumps_left,
+        wall_sliding,
+        wall_dir,
         ..*p
     }
 }
@@ -139,8 +244,20 @@ mod tests {
             grounded: false,
             state_flags: player_state_flag::ALIVE,
             respawn_timer: 0,
+            invincibility_source: invincibility_source::NONE,
             weapon: None,
             ammo: 0,
+            jumps_left: MAX_JUMPS,
+            wall_sliding: false,
+            wall_dir: 0,
+            stomped_by: -1,
+            stomping_on: -1,
+            stomp_shake_progress: 0,
+            stomp_last_shake_dir: 0,
+            stomp_auto_run_dir: 0,
+            stomp_auto_run_timer: 0,
+            stomp_cooldown: 0,
+            stomp_airborne_ticks: 0,
         }
     }
 
@@ -152,7 +269,7 @@ mod tests {
             aim_x: 1.0,
             aim_y: 0.0,
         };
-        let result = apply_player_input(&p, &input);
+        let result = apply_player_input(&p, &input, &NULL_INPUT);
         assert!(result.vx > 0.0);
         assert_eq!(result.facing, facing::RIGHT);
     }
@@ -165,7 +282,7 @@ mod tests {
             aim_x: -1.0,
             aim_y: 0.0,
         };
-        let result = apply_player_input(&p, &input);
+        let result = apply_player_input(&p, &input, &NULL_INPUT);
         assert!(result.vx < 0.0);
         assert_eq!(result.facing, facing::LEFT);
     }
@@ -194,13 +311,13 @@ mod tests {
             aim_x: 1.0,
             aim_y: 0.0,
         };
-        let result = apply_player_input(&p, &input);
+        let result = apply_player_input(&p, &input, &NULL_INPUT);
         assert_eq!(result.vx, 0.0);
         assert_eq!(result.vy, 0.0);
     }
 
     #[test]
-    fn jump_only_when_grounded() {
+    fn jump_on_press_edge_while_jumps_left() {
         let mut p = alive_player(0, 100.0, 100.0);
         p.grounded = true;
         let input = PlayerInput {
@@ -208,13 +325,65 @@ mod tests {
             aim_x: 0.0,
             aim_y: 0.0,
         };
-        let result = apply_player_input(&p, &input);
+        let result = apply_player_input(&p, &input, &NULL_INPUT);
         assert_eq!(result.vy, JUMP_VELOCITY);
+        assert_eq!(result.jumps_left, MAX_JUMPS - 1);
+
+        // Holding JUMP doesn't jump again; a second press in the air does, once
+        let held = apply_player_input(&PlayerState { vy: 1.0, ..result }, &input, &input);
+        assert_eq!((held.vy, held.jumps_left), (1.0, MAX_JUMPS - 1));
+        let double = apply_player_input(&held, &input, &NULL_INPUT);
+        assert_eq!((double.vy, double.jumps_left), (JUMP_VELOCITY, 0));
+        let spent = apply_player_input(&PlayerState { vy: 1.0, ..double }, &input, &NULL_INPUT);
+        assert_eq!(spent.vy, 1.0);
+    }
+
+    /// Falling against the left arena wall while pressing into it.
+    fn sliding_on_left_wall() -> PlayerState {
+        let map = crate::init::arena();
+        let mut p = alive_player(0, 1.0, 100.0);
+        p.jumps_left = 0;
+        p.vx = -2.0;
+        p.vy = 3.0;
+        let left = PlayerInput { buttons: button::LEFT, aim_x: 0.0, aim_y: 0.0 };
+        move_and_collide(&p, &left, &map, 0.0, map.width)
+    }
 
-        // Not grounded — no jump
-        let mut p2 = alive_player(0, 100.0, 100.0);
-        p2.grounded = false;
-        let result2 = apply_player_input(&p2, &input);
-        assert_eq!(result2.vy, 0.0);
+    #[test]
+    fn wall_slide_caps_fall_and_refunds_a_jump() {
+        let p = sliding_on_left_wall();
+        assert!(p.wall_sliding);
+        assert_eq!((p.wall_dir, p.facing, p.x, p.vx), (-1, -1, 0.0, 0.0));
+        assert_eq!(p.jumps_left, 1);
+        assert_eq!(apply_gravity(&PlayerState { vy: 5.0, ..p }).vy, WALL_SLIDE_SPEED);
+
+        // Pressing away from the wall is no slide
+        let map = crate::init::arena();
+        let away = move_and_collide(&p, &PlayerInput { buttons: 0, ..NULL_INPUT }, &map, 0.0, map.width);
+        assert!(!away.wall_sliding);
+    }
+
+    #[test]
+    fn wall_jump_pushes_off_the_wall() {
+        let p = sliding_on_left_wall();
+        let jump = PlayerInput { buttons: button::JUMP | button::LEFT, aim_x: 0.0, aim_y: 0.0 };
+        let result = apply_player_input(&p, &jump, &NULL_INPUT);
+        assert_eq!((result.vx, result.vy), (WALL_JUMP_VX, WALL_JUMP_VY));
+        assert_eq!((result.jumps_left, result.wall_sliding, result.wall_dir), (0, false, 0));
+    }
+
+    #[test]
+    fn solid_platform_side_is_a_wall() {
+        let map = crate::init::arena();
+        // Against the left side of the right lower platform, below its top
+        let plat = &map.platforms[2];
+        let mut p = alive_player(0, plat.x - PLAYER_WIDTH - 1.0, plat.y - 8.0);
+        p.vx = 2.0;
+        p.vy = 1.0;
+        let right = PlayerInput { buttons: button::RIGHT, aim_x: 0.0, aim_y: 0.0 };
+        let result = move_and_collide(&p, &right, &map, 0.0, map.width);
+        assert_eq!((result.x, result.vx), (plat.x - PLAYER_WIDTH, 0.0));
+        assert!(result.wall_sliding);
+        assert_eq!(result.wall_dir, 1);
     }
 }
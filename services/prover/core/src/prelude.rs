@@ -0,0 +1,50 @@
+//! The supported API for code outside this repo: the fixed-point sim that the guests prove.
+//!
+//! `use chickenz_core::prelude::*;` brings in the sim types, stepping, hashing and the match
+//! config. The f64 modules re-exported at the crate root mirror the TypeScript sim and are
+//! not what gets proven; in particular the old single-weapon constants (`PROJECTILE_DAMAGE`,
+//! `SHOOT_COOLDOWN`, `PROJECTILE_RADIUS`) and `spawn_projectile` are deprecated. Read weapon
+//! numbers from [`fp_weapon_stats`] instead:
+//!
+//! ```
+//! use chickenz_core::prelude::*;
+//!
+//! let cfg = FpMatchConfig::default();
+//! let map = cfg.oriented_map(&arena_map());
+//! let mut state = create_initial_state_with(7, &map, &cfg);
+//! step_mut(&mut state, &[NULL_INPUT; 2]); // on the state's own map
+//! assert_eq!(state.tick, 1);
+//! let bytes = encode_state(&state);
+//! assert_eq!(encode_state(&decode_state(&bytes)), bytes);
+//! assert_eq!(hash_state(&state).len(), 32);
+//! assert_eq!(fp_weapon_stats(WEAPON_PISTOL).damage, 20);
+//! ```
+//!
+//! The deprecated items still compile, so existing callers keep building:
+//!
+//! ```
+//! #[allow(deprecated)]
+//! let shown = chickenz_core::PROJECTILE_DAMAGE;
+//! assert_eq!(shown, 25);
+//! ```
+//!
+//! but any use is flagged:
+//!
+//! ```compile_fail
+//! #![deny(deprecated)]
+//! let shown = chickenz_core::PROJECTILE_DAMAGE;
+//! ```
+
+pub use crate::fp::{
+    arena_map, button, create_initial_state_with, damage_source, decode_state, decode_state_checked,
+    decode_state_on, encode_state, encode_state_into, encoded_state_len, fp_weapon_stats, hash_seed, hash_state,
+    hash_transcript, hash_transcript_wide, map_hash, max_encoded_state_len, reset_state, run_streaming_with,
+    run_streaming_with_chain, step, step_mut, step_mut_chained, step_mut_events, stomp_event,
+    try_decode_state, validate_state, DamageEvent, DamageList, EmoteEvent, EventChain, Fp, FpInput,
+    FpMatchConfig, FpProverInput, FpWeaponStats, Map, PickupEvent, Player, Projectile, ShotEvent,
+    State, StateIssue, StompEvent, TickEvents, TickInputs, MAX_PLAYERS, NULL_INPUT, ONE,
+    SIM_VERSION, WEAPON_GRENADE, WEAPON_NONE, WEAPON_PISTOL, WEAPON_ROCKET, WEAPON_SHOTGUN,
+    WEAPON_SMG, WEAPON_SNIPER,
+};
+pub use crate::journal_layout;
+pub use crate::ProverOutput;
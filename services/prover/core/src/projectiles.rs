@@ -2,8 +2,13 @@ use crate::constants::*;
 use crate::types::*;
 use crate::weapons::{apply_splash_damage, get_projectile_damage, is_rocket};
 
-/// Spawn a projectile from a player's position toward their aim direction (legacy fallback).
+/// Spawn a pistol round from the player's center toward their aim direction.
+///
+/// Kept for source compatibility; the sim shoots through `create_weapon_projectiles`, which
+/// honours the held weapon, pellets and spread.
+#[deprecated(note = "use `create_weapon_projectiles`, which uses the held weapon's stats")]
 pub fn spawn_projectile(player: &PlayerState, aim_x: f64, aim_y: f64, id: i32) -> Projectile {
+    let stats = weapon_stats(WeaponType::Pistol);
     let len = (aim_x * aim_x + aim_y * aim_y).sqrt();
     let (nx, ny) = if len < 0.001 {
         (player.facing as f64, 0.0)
@@ -16,9 +21,9 @@ pub fn spawn_projectile(player: &PlayerState, aim_x: f64, aim_y: f64, id: i32) -
         owner_id: player.id,
         x: player.x + PLAYER_WIDTH / 2.0,
         y: player.y + PLAYER_HEIGHT / 2.0,
-        vx: nx * PROJECTILE_SPEED,
-        vy: ny * PROJECTILE_SPEED,
-        lifetime: PROJECTILE_LIFETIME,
+        vx: nx * stats.speed,
+        vy: ny * stats.speed,
+        lifetime: stats.lifetime,
         weapon: WeaponType::Pistol,
     }
 }
@@ -40,7 +45,7 @@ pub fn is_out_of_bounds(proj: &Projectile, map: &GameMap, arena_left: f64, arena
     proj.x < arena_left - m || proj.x > arena_right + m || proj.y < -m || proj.y > map.height + m
 }
 
-/// AABB point-in-rect: projectile center vs player hitbox.
+/// AABB point-in-rect: projectile center vs player hitbox (pre-inflated by the projectile radius).
 fn aabb_overlap(px: f64, py: f64, rx: f64, ry: f64, rw: f64, rh: f64) -> bool {
     px >= rx && px <= rx + rw && py >= ry && py <= ry + rh
 }
@@ -89,7 +94,8 @@ pub fn resolve_projectile_hits(
                 continue;
             }
 
-            if aabb_overlap(proj.x, proj.y, p.x, p.y, PLAYER_WIDTH, PLAYER_HEIGHT) {
+            let r = weapon_stats(proj.weapon).proj_radius;
+            if aabb_overlap(proj.x, proj.y, p.x - r, p.y - r, PLAYER_WIDTH + 2.0 * r, PLAYER_HEIGHT + 2.0 * r) {
                 hit_projectile_ids.push(proj.id);
                 let damage = get_projectile_damage(proj);
                 let victim_id = p.id;
@@ -97,6 +103,7 @@ pub fn resolve_projectile_hits(
                 if new_health <= 0 {
                     updated_players[i].health = 0;
                     updated_players[i].state_flags = 0; // clear Alive
+                    updated_players[i].invincibility_source = invincibility_source::NONE;
                     kills.push(ProjectileKill {
                         killer_id: proj.owner_id,
                         victim_id,
@@ -153,12 +160,25 @@ mod tests {
             grounded: false,
             state_flags: player_state_flag::ALIVE,
             respawn_timer: 0,
+            invincibility_source: invincibility_source::NONE,
             weapon: None,
             ammo: 0,
+            jumps_left: MAX_JUMPS,
+            wall_sliding: false,
+            wall_dir: 0,
+            stomped_by: -1,
+            stomping_on: -1,
+            stomp_shake_progress: 0,
+            stomp_last_shake_dir: 0,
+            stomp_auto_run_dir: 0,
+            stomp_auto_run_timer: 0,
+            stomp_cooldown: 0,
+            stomp_airborne_ticks: 0,
         }
     }
 
     #[test]
+    #[allow(deprecated)]
     fn spawn_projectile_facing_right() {
         let mut p = alive_player(0, 100.0, 200.0);
         p.weapon = Some(WeaponType::Pistol);
@@ -192,6 +212,26 @@ mod tests {
         assert!(result.remaining_projectiles.is_empty());
     }
 
+    #[test]
+    fn rocket_radius_connects_sniper_misses() {
+        let p0 = alive_player(0, 0.0, 0.0);
+        let p1 = alive_player(1, 100.0, 200.0);
+        let shot = |weapon| Projectile {
+            id: 0,
+            owner_id: 0,
+            x: 96.0, // 4px left of p1's hitbox
+            y: 200.0 + PLAYER_HEIGHT / 2.0,
+            vx: 0.0,
+            vy: 0.0,
+            lifetime: 50,
+            weapon,
+        };
+        let rocket = resolve_projectile_hits(&[shot(WeaponType::Rocket)], &[p0, p1]);
+        assert!(rocket.updated_players[1].health < MAX_HEALTH);
+        let sniper = resolve_projectile_hits(&[shot(WeaponType::Sniper)], &[p0, p1]);
+        assert_eq!(sniper.updated_players[1].health, MAX_HEALTH);
+    }
+
     #[test]
     fn projectile_skips_owner() {
         let p0 = alive_player(0, 100.0, 200.0);
@@ -7,13 +7,16 @@ use crate::weapons::{create_weapon_projectiles, resolve_weapon_pickups, tick_pic
 
 /// Core deterministic transition function.
 ///
-/// Sub-step order (mirrors TypeScript exactly):
+/// Sub-step order (mirrors TypeScript exactly). The fixed-point `fp::step_mut` runs its own,
+/// listed by `fp::Phase`: stomps after movement, hazards, the zone as damage only, no respawn.
+///
 ///  0. Early return if matchOver
 ///  1. Resolve inputs (missing-input rule)
 ///  2. Tick cooldowns + invincibility
-///  3. Apply player input (movement/jump/facing)
+///  3. Apply player input (movement/jump edge against `prev_inputs`/facing)
 ///  4. Apply gravity
-///  5. Move + collide with platforms (dynamic arena bounds)
+///  5. Move + collide with platforms (dynamic arena bounds), wall slide, jump refund
+///  5b. Stomp detection, then active stomps (damage, auto-run, shake-off, detach)
 ///  6. Weapon pickup collision
 ///  7. Process shooting (spawn weapon projectiles)
 ///  8. Move projectiles, remove expired/OOB
@@ -28,7 +31,7 @@ use crate::weapons::{create_weapon_projectiles, resolve_weapon_pickups, tick_pic
 pub fn step(
     prev: &GameState,
     inputs: &[PlayerInput; 2],
-    _prev_inputs: &[PlayerInput; 2],
+    prev_inputs: &[PlayerInput; 2],
     config: &MatchConfig,
 ) -> GameState {
     // 0. Early return if match is already over
@@ -58,6 +61,8 @@ pub fn step(
     let mut match_over = false;
     let mut winner = prev.winner;
     let mut death_linger_timer: i32 = 0;
+    let mut score = prev.score;
+    let current_tick = prev.tick + 1;
 
     // 1. Resolve inputs — inputs are always provided directly
     let resolved_inputs: [PlayerInput; 2] = [inputs[0], inputs[1]];
@@ -70,8 +75,14 @@ pub fn step(
             if p.state_flags & player_state_flag::ALIVE == 0 {
                 return *p;
             }
+            let stomp_cooldown = if p.stomp_cooldown > 0 && p.stomped_by < 0 {
+                p.stomp_cooldown - 1
+            } else {
+                p.stomp_cooldown
+            };
             PlayerState {
                 shoot_cooldown: (p.shoot_cooldown - 1).max(0),
+                stomp_cooldown,
                 ..*p
             }
         })
@@ -88,6 +99,7 @@ pub fn step(
                 if new_timer <= 0 {
                     return PlayerState {
                         state_flags: p.state_flags & !player_state_flag::INVINCIBLE,
+                        invincibility_source: invincibility_source::NONE,
                         respawn_timer: 0,
                         ..*p
                     };
@@ -104,7 +116,7 @@ pub fn step(
     // 3. Apply player input
     players = players
         .iter()
-        .map(|p| apply_player_input(p, &resolved_inputs[p.id as usize]))
+        .map(|p| apply_player_input(p, &resolved_inputs[p.id as usize], &prev_inputs[p.id as usize]))
         .collect();
 
     // 4. Apply gravity
@@ -113,9 +125,13 @@ pub fn step(
     // 5. Move + collide (with dynamic arena bounds)
     players = players
         .iter()
-        .map(|p| move_and_collide(p, map, arena_left, arena_right))
+        .map(|p| move_and_collide(p, &resolved_inputs[p.id as usize], map, arena_left, arena_right))
         .collect();
 
+    // 5b. Stomps: detection after movement, then the active ones (stomp kills score here)
+    start_stomps(&mut players, &mut rng_state);
+    tick_stomps(&mut players, &resolved_inputs, prev_inputs, current_tick, &mut rng_state, &mut score);
+
     // 6. Weapon pickup collision
     let mut weapon_pickups = prev.weapon_pickups.clone();
     resolve_weapon_pickups(&mut players, &mut weapon_pickups);
@@ -188,6 +204,22 @@ pub fn step(
             }
         })
         .collect();
+    // Break the stomp links of the dead
+    for &victim in &killed_ids {
+        for p in players.iter_mut() {
+            if p.id == victim {
+                clear_stomp_fields(p);
+            } else {
+                if p.stomping_on == victim {
+                    p.stomping_on = -1;
+                    p.grounded = false;
+                }
+                if p.stomped_by == victim {
+                    clear_mount_fields(p);
+                }
+            }
+        }
+    }
 
     // Check elimination: if only one player has lives remaining → start linger
     let players_with_lives: Vec<&PlayerState> =
@@ -218,7 +250,7 @@ pub fn step(
                         // Clamp spawn to arena bounds (important during sudden death)
                         let spawn_x =
                             arena_left.max(spawn.x.min(arena_right - PLAYER_WIDTH));
-                        return PlayerState {
+                        let mut respawned = PlayerState {
                             x: spawn_x,
                             y: spawn.y,
                             vx: 0.0,
@@ -226,13 +258,20 @@ pub fn step(
                             health: MAX_HEALTH,
                             state_flags: player_state_flag::ALIVE
                                 | player_state_flag::INVINCIBLE,
+                            invincibility_source: invincibility_source::RESPAWN,
                             respawn_timer: INVINCIBLE_TICKS,
                             shoot_cooldown: 0,
                             grounded: false,
                             weapon: None,
                             ammo: 0,
+                            jumps_left: MAX_JUMPS,
+                            wall_sliding: false,
+                            wall_dir: 0,
+                            stomp_cooldown: 0,
                             ..*p
                         };
+                        clear_stomp_fields(&mut respawned);
+                        return respawned;
                     }
                     return PlayerState {
                         respawn_timer: new_timer,
@@ -245,7 +284,6 @@ pub fn step(
     }
 
     // 12. Sudden death — arena walls close inward
-    let current_tick = prev.tick + 1;
     if !match_over && death_linger_timer == 0 && current_tick >= config.sudden_death_start_tick {
         let duration =
             (config.match_duration_ticks - config.sudden_death_start_tick) as f64;
@@ -269,6 +307,7 @@ pub fn step(
                         lives: p.lives - 1,
                         health: 0,
                         state_flags: 0,
+                        invincibility_source: invincibility_source::NONE,
                         respawn_timer: 0,
                         vx: 0.0,
                         vy: 0.0,
@@ -319,6 +358,10 @@ pub fn step(
             winner = p0.id;
         } else if p1.lives > p0.lives {
             winner = p1.id;
+        } else if prev.score[0] > prev.score[1] {
+            winner = p0.id;
+        } else if prev.score[1] > prev.score[0] {
+            winner = p1.id;
         } else if p0.health > p1.health {
             winner = p0.id;
         } else if p1.health > p0.health {
@@ -328,8 +371,7 @@ pub fn step(
         }
     }
 
-    // 14. Update score (kills tracked for display)
-    let mut score = prev.score;
+    // 14. Update score (kills tracked for display; stomp kills were scored in 5b)
     for kill in &hit_result.kills {
         if kill.killer_id >= 0 && (kill.killer_id as usize) < score.len() {
             score[kill.killer_id as usize] += 1;
@@ -356,6 +398,188 @@ pub fn step(
     }
 }
 
+/// Clear a ridden player's rider, shake-off and auto-run, but not its own `stomping_on`.
+fn clear_mount_fields(p: &mut PlayerState) {
+    p.stomped_by = -1;
+    p.stomp_shake_progress = 0;
+    p.stomp_last_shake_dir = 0;
+    p.stomp_auto_run_dir = 0;
+    p.stomp_auto_run_timer = 0;
+    p.stomp_airborne_ticks = 0;
+}
+
+fn clear_stomp_fields(p: &mut PlayerState) {
+    p.stomping_on = -1;
+    clear_mount_fields(p);
+}
+
+/// The rider lets go without the shake-off bounce; the mount gets the cooldown anyway.
+fn detach_stomp_rider(players: &mut [PlayerState], rider_idx: usize, victim_idx: usize) {
+    players[rider_idx].stomping_on = -1;
+    players[rider_idx].vy = 0.0;
+    players[rider_idx].grounded = false;
+    players[victim_idx].stomp_cooldown = STOMP_COOLDOWN_TICKS;
+    clear_mount_fields(&mut players[victim_idx]);
+}
+
+/// Stomp detection, after movement (as `fp::start_stomps`): a player falling with its feet
+/// within 8px below another's head starts riding it, and the mount gets a random auto-run
+/// direction and timer. Each player starts at most one stomp, trying the others in order.
+fn start_stomps(players: &mut [PlayerState], rng_state: &mut u32) {
+    let n = players.len();
+    for a_idx in 0..n {
+        for b_idx in 0..n {
+            if b_idx == a_idx {
+                continue;
+            }
+            let (a, b) = (&players[a_idx], &players[b_idx]);
+            if a.stomping_on >= 0 || a.stomped_by >= 0 || a.state_flags & player_state_flag::ALIVE == 0 {
+                break;
+            }
+            if b.stomped_by >= 0 || b.stomp_cooldown > 0 || b.state_flags & player_state_flag::ALIVE == 0 {
+                continue;
+            }
+            let a_feet = a.y + PLAYER_HEIGHT;
+            let landed = a.vy > 0.0
+                && a_feet >= b.y
+                && a_feet <= b.y + 8.0
+                && a.x + PLAYER_WIDTH > b.x
+                && a.x < b.x + PLAYER_WIDTH;
+            if !landed {
+                continue;
+            }
+            let (a_id, b_id, b_y) = (a.id, b.id, b.y);
+            let rider = &mut players[a_idx];
+            rider.stomping_on = b_id;
+            rider.grounded = true;
+            rider.vy = 0.0;
+            rider.y = b_y - PLAYER_HEIGHT;
+
+            let (dir, next) = prng_int_range(*rng_state, 0, 1);
+            let (timer, next) = prng_int_range(next, STOMP_AUTO_RUN_MIN, STOMP_AUTO_RUN_MAX);
+            *rng_state = next;
+            let mount = &mut players[b_idx];
+            mount.stomped_by = a_id;
+            mount.stomp_shake_progress = 0;
+            mount.stomp_last_shake_dir = 0;
+            mount.stomp_auto_run_dir = if dir == 0 { -1 } else { 1 };
+            mount.stomp_auto_run_timer = timer;
+        }
+    }
+}
+
+/// Active stomps (as `fp::tick_stomps`): drop broken links, detach on the rider's JUMP press or
+/// a long fall, deal the mount STOMP_DAMAGE_PER_HIT every STOMP_DAMAGE_INTERVAL ticks (a kill
+/// scores for the rider and bounces it up), auto-run the mount, count its alternating LEFT/RIGHT
+/// presses toward breaking free, and keep the rider on its head.
+fn tick_stomps(
+    players: &mut [PlayerState],
+    inputs: &[PlayerInput; 2],
+    prev_inputs: &[PlayerInput; 2],
+    tick: Tick,
+    rng_state: &mut u32,
+    score: &mut [u32; 2],
+) {
+    let alive = |p: &PlayerState| p.state_flags & player_state_flag::ALIVE != 0;
+    for victim_idx in 0..players.len() {
+        let rider_id = players[victim_idx].stomped_by;
+        if rider_id < 0 {
+            continue;
+        }
+        let Some(rider_idx) = players.iter().position(|p| p.id == rider_id) else {
+            clear_mount_fields(&mut players[victim_idx]);
+            continue;
+        };
+        let victim_id = players[victim_idx].id;
+        if !alive(&players[rider_idx]) || players[rider_idx].stomping_on != victim_id {
+            clear_mount_fields(&mut players[victim_idx]);
+            continue;
+        }
+        // A mount killed by the walls throws its rider
+        if !alive(&players[victim_idx]) {
+            players[rider_idx].stomping_on = -1;
+            players[rider_idx].grounded = false;
+            clear_stomp_fields(&mut players[victim_idx]);
+            continue;
+        }
+
+        // Detach: the rider taps JUMP, or the pair has been falling too long
+        let victim = &mut players[victim_idx];
+        victim.stomp_airborne_ticks = if victim.grounded { 0 } else { victim.stomp_airborne_ticks.saturating_add(1) };
+        let rider_jump_edge = inputs[rider_idx].buttons & button::JUMP != 0
+            && prev_inputs[rider_idx].buttons & button::JUMP == 0;
+        if rider_jump_edge || victim.stomp_airborne_ticks > STOMP_AIRBORNE_DETACH_TICKS {
+            detach_stomp_rider(players, rider_idx, victim_idx);
+            continue;
+        }
+
+        // Damage tick
+        if tick % STOMP_DAMAGE_INTERVAL == 0 {
+            players[victim_idx].health -= STOMP_DAMAGE_PER_HIT;
+            if players[victim_idx].health <= 0 {
+                let victim = &mut players[victim_idx];
+                victim.health = 0;
+                victim.state_flags = 0;
+                victim.invincibility_source = invincibility_source::NONE;
+                victim.lives -= 1;
+                clear_stomp_fields(victim);
+                let rider = &mut players[rider_idx];
+                rider.stomping_on = -1;
+                rider.vy = JUMP_VELOCITY / 2.0;
+                rider.grounded = false;
+                if (rider_id as usize) < score.len() {
+                    score[rider_id as usize] += 1;
+                }
+                continue;
+            }
+        }
+
+        // Auto-run, turning around when the timer runs out
+        let victim = &mut players[victim_idx];
+        victim.stomp_auto_run_timer -= 1;
+        if victim.stomp_auto_run_timer <= 0 {
+            victim.stomp_auto_run_dir *= -1;
+            let (timer, next) = prng_int_range(*rng_state, STOMP_AUTO_RUN_MIN, STOMP_AUTO_RUN_MAX);
+            *rng_state = next;
+            victim.stomp_auto_run_timer = timer;
+        }
+        victim.vx = PLAYER_SPEED * victim.stomp_auto_run_dir as f64;
+
+        // Shake-off: LEFT and RIGHT presses count only alternating
+        let (held, before) = (inputs[victim_idx].buttons, prev_inputs[victim_idx].buttons);
+        let pressed = |b: u8| held & b != 0 && before & b == 0;
+        if pressed(button::LEFT) && victim.stomp_last_shake_dir != -1 {
+            victim.stomp_shake_progress += STOMP_SHAKE_PER_PRESS;
+            victim.stomp_last_shake_dir = -1;
+        }
+        if pressed(button::RIGHT) && victim.stomp_last_shake_dir != 1 {
+            victim.stomp_shake_progress += STOMP_SHAKE_PER_PRESS;
+            victim.stomp_last_shake_dir = 1;
+        }
+        victim.stomp_shake_progress = (victim.stomp_shake_progress - STOMP_SHAKE_DECAY).max(0);
+
+        // Break free: the rider is bounced off
+        if victim.stomp_shake_progress >= STOMP_SHAKE_THRESHOLD {
+            victim.stomp_cooldown = STOMP_COOLDOWN_TICKS;
+            clear_mount_fields(victim);
+            let rider = &mut players[rider_idx];
+            rider.stomping_on = -1;
+            rider.vy = JUMP_VELOCITY;
+            rider.grounded = false;
+            continue;
+        }
+
+        // Lock the rider onto the mount's head
+        let (x, y) = (players[victim_idx].x, players[victim_idx].y);
+        let rider = &mut players[rider_idx];
+        rider.x = x;
+        rider.y = y - PLAYER_HEIGHT;
+        rider.vx = 0.0;
+        rider.vy = 0.0;
+        rider.grounded = true;
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -461,6 +685,123 @@ mod tests {
         }
     }
 
+    /// Both players run into their arena wall, double jump, slide down it and wall jump off,
+    /// then run back under the lower platform and jump at it, each on its own half (so the
+    /// fixed-point sim's stomps never come into it) and without shooting.
+    fn wall_play_transcript(ticks: u32) -> Vec<[PlayerInput; 2]> {
+        let toward_wall = |t: u32| match t % 100 {
+            30 | 41 | 70 => button::LEFT | button::JUMP,
+            0..=69 => button::LEFT,
+            71..=85 => button::RIGHT,
+            _ => button::RIGHT | button::JUMP,
+        };
+        let mirror = |b: u8| (b & button::JUMP) | (b & button::LEFT) << 1 | (b & button::RIGHT) >> 1;
+        (0..ticks)
+            .map(|t| {
+                [
+                    PlayerInput { buttons: toward_wall(t), aim_x: 1.0, aim_y: 0.0 },
+                    PlayerInput { buttons: mirror(toward_wall(t + 7)), aim_x: -1.0, aim_y: 0.0 },
+                ]
+            })
+            .collect()
+    }
+
+    #[test]
+    fn movement_matches_fixed_point_sim() {
+        use crate::fp;
+
+        let config = default_config(42);
+        let fp_map = crate::hash::quantize_map(&config.map);
+        let mut fp_state = fp::create_initial_state_with(42, &fp_map, &fp::FpMatchConfig::default());
+        let mut state = create_initial_state(&config);
+        let mut prev_inputs = [NULL_INPUT; 2];
+        let (mut wall_jumps, mut double_jumps) = (0, 0);
+        for (tick, inputs) in wall_play_transcript(500).iter().enumerate() {
+            let before = state.clone();
+            state = step(&state, inputs, &prev_inputs, &config);
+            fp::step_mut(&mut fp_state, &inputs.map(|i| crate::hash::quantize_input(&i)));
+            prev_inputs = *inputs;
+
+            // The fixed-point ACCELERATION and DECELERATION round to 1/256, hence the tolerance
+            for (p, q) in state.players.iter().zip(&fp_state.players) {
+                let (fx, fy) = (q.x as f64 / fp::ONE as f64, q.y as f64 / fp::ONE as f64);
+                assert!(
+                    (p.x - fx).abs() <= 0.5 && (p.y - fy).abs() <= 0.5,
+                    "tick {tick} player {}: f64 at ({}, {}), fp at ({fx}, {fy})",
+                    p.id, p.x, p.y
+                );
+                assert_eq!((p.wall_sliding, p.jumps_left), (q.wall_sliding, q.jumps_left), "tick {tick}");
+            }
+            for (b, p) in before.players.iter().zip(&state.players) {
+                if p.jumps_left < b.jumps_left && b.wall_sliding {
+                    wall_jumps += 1;
+                } else if p.jumps_left < b.jumps_left && !b.grounded {
+                    double_jumps += 1;
+                }
+            }
+        }
+        assert!(wall_jumps >= 4 && double_jumps >= 4, "{wall_jumps} wall jumps, {double_jumps} double jumps");
+    }
+
+    /// Drops P1 onto the grounded P0's head in both sims and plays `victim_buttons(t)` for P0
+    /// (P1 idles) for `ticks` ticks, checking the stomp state agrees every tick. The auto-run
+    /// directions come from different PRNGs, so only where P0 runs to may differ.
+    fn stomp_against_fixed_point_sim(ticks: u32, victim_buttons: impl Fn(u32) -> u8) -> (GameState, crate::fp::State) {
+        use crate::fp;
+
+        let config = default_config(42);
+        let fp_map = crate::hash::quantize_map(&config.map);
+        let mut fp_state = fp::create_initial_state_with(42, &fp_map, &fp::FpMatchConfig::default());
+        let mut state = create_initial_state(&config);
+        let (x, y) = (state.players[0].x, state.players[0].y - PLAYER_HEIGHT - 4.0);
+        state.players[1] = PlayerState { x, y, vx: 0.0, vy: 2.0, grounded: false, ..state.players[1] };
+        let fp_x = fp_state.players[0].x;
+        let q = &mut fp_state.players[1];
+        (q.x, q.y, q.vx, q.vy, q.grounded) = (fp_x, fp::fp(y as i32), 0, fp::fp(2), false);
+
+        let mut prev_inputs = [NULL_INPUT; 2];
+        for t in 0..ticks {
+            let inputs = [PlayerInput { buttons: victim_buttons(t), aim_x: 1.0, aim_y: 0.0 }, NULL_INPUT];
+            state = step(&state, &inputs, &prev_inputs, &config);
+            fp::step_mut(&mut fp_state, &inputs.map(|i| crate::hash::quantize_input(&i)));
+            prev_inputs = inputs;
+            for (p, q) in state.players.iter().zip(&fp_state.players) {
+                assert_eq!(
+                    (p.stomped_by, p.stomping_on, p.stomp_shake_progress, p.stomp_cooldown, p.health, p.lives),
+                    (q.stomped_by, q.stomping_on, q.stomp_shake_progress, q.stomp_cooldown, q.health, q.lives),
+                    "tick {t} player {}",
+                    p.id
+                );
+            }
+            assert_eq!(state.score, [fp_state.score[0], fp_state.score[1]], "tick {t}");
+        }
+        (state, fp_state)
+    }
+
+    #[test]
+    fn stomp_shake_off_matches_fixed_point_sim() {
+        // Ride for a while, then shake: alternating edges, with a repeated LEFT that doesn't count
+        let shake = |t: u32| match t {
+            0..=29 => 0,
+            30..=33 => button::LEFT * ((t + 1) % 2) as u8,
+            _ if t % 2 == 0 => button::LEFT,
+            _ => button::RIGHT,
+        };
+        let (state, _) = stomp_against_fixed_point_sim(60, shake);
+        let (victim, rider) = (&state.players[0], &state.players[1]);
+        assert_eq!((victim.stomped_by, rider.stomping_on), (-1, -1));
+        assert!(victim.stomp_cooldown > 0 && victim.health < MAX_HEALTH);
+        assert_eq!(state.score, [0, 0]);
+    }
+
+    #[test]
+    fn stomp_ride_to_the_kill_matches_fixed_point_sim() {
+        let (state, _) = stomp_against_fixed_point_sim(2 * MAX_HEALTH as u32 + 10, |_| 0);
+        assert_eq!(state.players[0].lives, INITIAL_LIVES - 1);
+        assert_eq!(state.score, [0, 1]);
+        assert_eq!(state.players[1].stomping_on, -1);
+    }
+
     #[test]
     fn unarmed_player_cannot_shoot() {
         let config = default_config(42);
@@ -558,6 +899,30 @@ mod tests {
         assert_eq!(s.winner, 0);
     }
 
+    #[test]
+    fn time_up_tiebreak_prefers_score_over_health() {
+        // No sudden death, whose full close would end the match on lives alone
+        let mut config = default_config(42);
+        config.sudden_death_start_tick = config.match_duration_ticks + 1;
+        let winner = |lives: [i32; 2], score: [u32; 2], health: [i32; 2]| {
+            let mut state = create_initial_state(&config);
+            state.tick = config.match_duration_ticks - 1;
+            state.score = score;
+            for i in 0..2 {
+                state.players[i].lives = lives[i];
+                state.players[i].health = health[i];
+            }
+            let s = step(&state, &[NULL_INPUT; 2], &[NULL_INPUT; 2], &config);
+            assert!(s.match_over);
+            s.winner
+        };
+        assert_eq!(winner([1, 2], [5, 0], [100, 10]), 1);
+        assert_eq!(winner([2, 2], [3, 1], [10, 100]), 0);
+        assert_eq!(winner([2, 2], [1, 3], [100, 10]), 1);
+        assert_eq!(winner([2, 2], [2, 2], [40, 90]), 1);
+        assert_eq!(winner([2, 2], [2, 2], [90, 90]), 0);
+    }
+
     #[test]
     fn respawn_clears_weapon() {
         let config = default_config(42);
@@ -577,4 +942,27 @@ mod tests {
         assert_eq!(result.players[0].weapon, None);
         assert_eq!(result.players[0].ammo, 0);
     }
+
+    #[test]
+    fn respawn_invincibility_source_lifecycle() {
+        let config = default_config(42);
+        let mut state = create_initial_state(&config);
+        assert_eq!(state.players[0].invincibility_source, invincibility_source::NONE);
+        state.players[0].lives = 2;
+        state.players[0].health = 0;
+        state.players[0].state_flags = 0;
+        state.players[0].respawn_timer = RESPAWN_TICKS - 1;
+        state.weapon_pickups.clear();
+
+        let mut s = step(&state, &[NULL_INPUT; 2], &[NULL_INPUT; 2], &config);
+        assert!(s.players[0].state_flags & player_state_flag::INVINCIBLE != 0);
+        assert_eq!(s.players[0].invincibility_source, invincibility_source::RESPAWN);
+
+        while s.players[0].state_flags & player_state_flag::INVINCIBLE != 0 {
+            assert_eq!(s.players[0].invincibility_source, invincibility_source::RESPAWN);
+            s = step(&s, &[NULL_INPUT; 2], &[NULL_INPUT; 2], &config);
+        }
+        assert_eq!(s.players[0].invincibility_source, invincibility_source::NONE);
+        assert_eq!(s.players[1].invincibility_source, invincibility_source::NONE);
+    }
 }
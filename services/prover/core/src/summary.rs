@@ -0,0 +1,266 @@
+//! Shareable match summaries: a short base64 string derived from a journal alone, for posting
+//! a proved result (a Discord bot, a link) without the artifacts.
+//!
+//! Bytes, little-endian: [version: u8] [player_count: u8] [winner: i8] [final_tick: u32]
+//! [score: u32 × player_count] [transcript hash: 8] [seed commit: 8] [map hash: 8], the hashes
+//! as their first `SUMMARY_HASH_PREFIX` bytes. The string is unpadded URL-safe base64 of that.
+//! Nothing signs it: it is only as good as the journal it was made from, which the prefixes
+//! let a reader find again.
+
+use crate::fp::MAX_PLAYERS;
+use crate::ProverOutput;
+
+pub const SUMMARY_VERSION: u8 = 1;
+/// Leading bytes kept of each committed hash.
+pub const SUMMARY_HASH_PREFIX: usize = 8;
+
+/// What a summary says about a match.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MatchSummary {
+    pub version: u8,
+    pub player_count: u8,
+    /// Registered player index, or -1 for a draw.
+    pub winner: i32,
+    pub final_tick: u32,
+    /// The first `player_count` entries are the players'; the rest are 0.
+    pub scores: [u32; MAX_PLAYERS],
+    pub transcript_hash: [u8; SUMMARY_HASH_PREFIX],
+    pub seed_commit: [u8; SUMMARY_HASH_PREFIX],
+    pub map_hash: [u8; SUMMARY_HASH_PREFIX],
+}
+
+/// Why a summary string or its bytes don't decode.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SummaryIssue {
+    /// Not unpadded URL-safe base64.
+    Base64,
+    Version(u8),
+    PlayerCount(u8),
+    /// `len` bytes where the header says `expected`.
+    Length { len: usize, expected: usize },
+}
+
+impl core::fmt::Display for SummaryIssue {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match *self {
+            SummaryIssue::Base64 => write!(f, "summary is not URL-safe base64"),
+            SummaryIssue::Version(v) => write!(f, "summary version {v}, this build reads {SUMMARY_VERSION}"),
+            SummaryIssue::PlayerCount(n) => write!(f, "summary of {n} players"),
+            SummaryIssue::Length { len, expected } => write!(f, "summary is {len} bytes, expected {expected}"),
+        }
+    }
+}
+
+impl std::error::Error for SummaryIssue {}
+
+fn prefix(hash: &[u8; 32]) -> [u8; SUMMARY_HASH_PREFIX] {
+    let mut p = [0; SUMMARY_HASH_PREFIX];
+    p.copy_from_slice(&hash[..SUMMARY_HASH_PREFIX]);
+    p
+}
+
+/// Encoded length of a summary of `player_count` players.
+pub const fn summary_len(player_count: usize) -> usize {
+    7 + 4 * player_count + 3 * SUMMARY_HASH_PREFIX
+}
+
+impl MatchSummary {
+    pub fn from_output(output: &ProverOutput) -> Self {
+        let n = output.player_count as usize;
+        let mut scores = [0; MAX_PLAYERS];
+        scores[..n].copy_from_slice(&output.scores[..n]);
+        MatchSummary {
+            version: SUMMARY_VERSION,
+            player_count: output.player_count,
+            winner: output.winner,
+            final_tick: output.final_tick,
+            scores,
+            transcript_hash: prefix(&output.transcript_hash),
+            seed_commit: prefix(&output.seed_commit),
+            map_hash: prefix(&output.map_hash),
+        }
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let n = self.player_count as usize;
+        let mut b = Vec::with_capacity(summary_len(n));
+        b.extend_from_slice(&[self.version, self.player_count, self.winner as i8 as u8]);
+        b.extend_from_slice(&self.final_tick.to_le_bytes());
+        for score in &self.scores[..n] {
+            b.extend_from_slice(&score.to_le_bytes());
+        }
+        b.extend_from_slice(&self.transcript_hash);
+        b.extend_from_slice(&self.seed_commit);
+        b.extend_from_slice(&self.map_hash);
+        b
+    }
+
+    pub fn from_bytes(b: &[u8]) -> Result<Self, SummaryIssue> {
+        let Some(&[version, player_count, winner]) = b.get(..3).map(|h| <&[u8; 3]>::try_from(h).unwrap()) else {
+            return Err(SummaryIssue::Length { len: b.len(), expected: summary_len(2) });
+        };
+        if version != SUMMARY_VERSION {
+            return Err(SummaryIssue::Version(version));
+        }
+        let n = player_count as usize;
+        if !(2..=MAX_PLAYERS).contains(&n) {
+            return Err(SummaryIssue::PlayerCount(player_count));
+        }
+        if b.len() != summary_len(n) {
+            return Err(SummaryIssue::Length { len: b.len(), expected: summary_len(n) });
+        }
+        let u32_at = |off: usize| u32::from_le_bytes([b[off], b[off + 1], b[off + 2], b[off + 3]]);
+        let hash_at = |off: usize| {
+            let mut h = [0; SUMMARY_HASH_PREFIX];
+            h.copy_from_slice(&b[off..off + SUMMARY_HASH_PREFIX]);
+            h
+        };
+        let mut scores = [0; MAX_PLAYERS];
+        for (i, score) in scores[..n].iter_mut().enumerate() {
+            *score = u32_at(7 + 4 * i);
+        }
+        let hashes = 7 + 4 * n;
+        Ok(MatchSummary {
+            version,
+            player_count,
+            winner: winner as i8 as i32,
+            final_tick: u32_at(3),
+            scores,
+            transcript_hash: hash_at(hashes),
+            seed_commit: hash_at(hashes + SUMMARY_HASH_PREFIX),
+            map_hash: hash_at(hashes + 2 * SUMMARY_HASH_PREFIX),
+        })
+    }
+
+    /// The shareable string.
+    pub fn to_base64(&self) -> String {
+        base64_encode(&self.to_bytes())
+    }
+
+    /// Decode a shared string; surrounding whitespace is ignored.
+    pub fn from_base64(s: &str) -> Result<Self, SummaryIssue> {
+        Self::from_bytes(&base64_decode(s.trim()).ok_or(SummaryIssue::Base64)?)
+    }
+}
+
+impl core::fmt::Display for MatchSummary {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let hex = |h: &[u8]| h.iter().map(|b| format!("{b:02x}")).collect::<String>();
+        match self.winner {
+            -1 => writeln!(f, "Result: draw")?,
+            w => writeln!(f, "Winner: P{w}")?,
+        }
+        let scores: Vec<String> =
+            self.scores[..self.player_count as usize].iter().enumerate().map(|(i, s)| format!("P{i}={s}")).collect();
+        writeln!(f, "Scores: {}", scores.join(", "))?;
+        writeln!(f, "Final tick: {}", self.final_tick)?;
+        writeln!(f, "Transcript hash: {}…", hex(&self.transcript_hash))?;
+        writeln!(f, "Seed commit: {}…", hex(&self.seed_commit))?;
+        write!(f, "Map hash: {}…", hex(&self.map_hash))
+    }
+}
+
+const BASE64_URL: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+/// Unpadded URL-safe base64 (RFC 4648 §5).
+fn base64_encode(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let word = chunk.iter().enumerate().fold(0u32, |w, (i, &b)| w | (b as u32) << (16 - 8 * i));
+        for i in 0..chunk.len() + 1 {
+            out.push(BASE64_URL[(word >> (18 - 6 * i) & 63) as usize] as char);
+        }
+    }
+    out
+}
+
+fn base64_decode(s: &str) -> Option<Vec<u8>> {
+    if s.len() % 4 == 1 {
+        return None;
+    }
+    let mut out = Vec::with_capacity(s.len() * 3 / 4);
+    for chunk in s.as_bytes().chunks(4) {
+        let mut word = 0u32;
+        for (i, &c) in chunk.iter().enumerate() {
+            let v = BASE64_URL.iter().position(|&a| a == c)? as u32;
+            word |= v << (18 - 6 * i);
+        }
+        let bytes = word.to_be_bytes();
+        out.extend_from_slice(&bytes[1..chunk.len()]);
+    }
+    Some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn golden_output() -> ProverOutput {
+        ProverOutput {
+            winner: 1,
+            scores: [2, 3, 0, 0],
+            transcript_hash: core::array::from_fn(|i| i as u8),
+            seed_commit: core::array::from_fn(|i| 0xA0 + i as u8),
+            session_id: 17,
+            config_commit: [0xDD; 32],
+            env_kills: [1, 0, 0, 0],
+            player_count: 2,
+            map_hash: core::array::from_fn(|i| 0xF0u8.wrapping_add(i as u8)),
+            final_tick: 1234,
+            final_health: [0, 60, 0, 0],
+            final_lives: [0, 1, 0, 0],
+            winner_team: 1,
+            team_scores: [2, 3],
+            player_teams: [0, 1, 0, 0],
+            pickup_stats_commit: [0x5E; 32],
+        }
+    }
+
+    #[test]
+    fn golden_journal_summary_is_stable() {
+        let output = ProverOutput::from_journal_bytes(&golden_output().to_journal_bytes());
+        let summary = MatchSummary::from_output(&output);
+        assert_eq!(summary.to_bytes().len(), summary_len(2));
+        assert_eq!(summary.to_base64(), "AQIB0gQAAAIAAAADAAAAAAECAwQFBgegoaKjpKWmp_Dx8vP09fb3");
+        assert_eq!(
+            summary.to_string(),
+            "Winner: P1\nScores: P0=2, P1=3\nFinal tick: 1234\nTranscript hash: 0001020304050607…\n\
+             Seed commit: a0a1a2a3a4a5a6a7…\nMap hash: f0f1f2f3f4f5f6f7…"
+        );
+    }
+
+    #[test]
+    fn summaries_round_trip() {
+        let mut output = golden_output();
+        for (winner, player_count) in [(1, 2), (-1, 2), (3, 4)] {
+            output.winner = winner;
+            output.player_count = player_count;
+            output.scores = [9, 0, 70_000, 4];
+            let summary = MatchSummary::from_output(&output);
+            let back = MatchSummary::from_base64(&format!(" {}\n", summary.to_base64())).unwrap();
+            assert_eq!(back, summary);
+            assert_eq!(back.winner, winner);
+            assert_eq!(back.scores[..player_count as usize], output.scores[..player_count as usize]);
+        }
+        // Every tail length of the base64 round-trips
+        for len in 0..8 {
+            let bytes: Vec<u8> = (0..len).map(|i| 0xFF - i as u8).collect();
+            assert_eq!(base64_decode(&base64_encode(&bytes)), Some(bytes));
+        }
+    }
+
+    #[test]
+    fn bad_summaries_are_rejected() {
+        let blob = MatchSummary::from_output(&golden_output()).to_base64();
+        assert_eq!(MatchSummary::from_base64("not base64!"), Err(SummaryIssue::Base64));
+        let short = MatchSummary::from_base64(&blob[..blob.len() - 2]);
+        assert_eq!(short, Err(SummaryIssue::Length { len: 37, expected: 39 }));
+        let mut bytes = base64_decode(&blob).unwrap();
+        bytes[0] = 2;
+        assert_eq!(MatchSummary::from_bytes(&bytes), Err(SummaryIssue::Version(2)));
+        bytes[0] = SUMMARY_VERSION;
+        bytes[1] = 5;
+        assert_eq!(MatchSummary::from_bytes(&bytes), Err(SummaryIssue::PlayerCount(5)));
+        assert!(matches!(MatchSummary::from_bytes(&[1]), Err(SummaryIssue::Length { len: 1, .. })));
+    }
+}
@@ -1,3 +1,7 @@
+use chickenz_core_mini::{Journal, TEAMS};
+pub use chickenz_core_mini::TeamResult;
+use chickenz_journal_layout as layout;
+use crate::fp::MAX_PLAYERS;
 use serde::{Deserialize, Serialize};
 
 // ── Primitives ──────────────────────────────────────────────
@@ -48,6 +52,8 @@ pub enum WeaponType {
 }
 
 impl WeaponType {
+    /// `None` for ids this sim doesn't model, including the fixed-point sim's grenade
+    /// (`fp::WEAPON_GRENADE`).
     pub fn from_i32(v: i32) -> Option<Self> {
         match v {
             0 => Some(Self::Pistol),
@@ -71,6 +77,8 @@ pub struct WeaponStats {
     pub spread_deg: f64,
     pub splash_radius: f64,
     pub splash_damage: i32,
+    /// Hitbox half-size; the player box is inflated by this much. 0 = point projectile.
+    pub proj_radius: f64,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
@@ -96,6 +104,14 @@ pub mod player_state_flag {
     pub const INVINCIBLE: u32 = 2;
 }
 
+/// Why `player_state_flag::INVINCIBLE` is set (rendering/stats only; damage checks the bit).
+pub mod invincibility_source {
+    pub const NONE: u8 = 0;
+    pub const RESPAWN: u8 = 1;
+    pub const START_GRACE: u8 = 2;
+    pub const SCRIPTED: u8 = 3;
+}
+
 #[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
 pub struct PlayerState {
     pub id: PlayerId,
@@ -110,8 +126,46 @@ pub struct PlayerState {
     pub grounded: bool,
     pub state_flags: u32,
     pub respawn_timer: i32,
+    /// invincibility_source::*, NONE unless INVINCIBLE is set
+    #[serde(default)]
+    pub invincibility_source: u8,
     pub weapon: Option<WeaponType>,
     pub ammo: i32,
+    /// Jumps left before landing: MAX_JUMPS on the ground, one back on a wall once spent.
+    #[serde(default)]
+    pub jumps_left: i32,
+    #[serde(default)]
+    pub wall_sliding: bool,
+    /// Side of the wall being slid down: -1 left, 1 right, 0 none.
+    #[serde(default)]
+    pub wall_dir: i32,
+    /// Id of the player riding this one's head, -1 for none.
+    #[serde(default = "no_player")]
+    pub stomped_by: PlayerId,
+    /// Id of the player whose head this one rides, -1 for none.
+    #[serde(default = "no_player")]
+    pub stomping_on: PlayerId,
+    /// Shake-off meter while ridden; breaks free at STOMP_SHAKE_THRESHOLD.
+    #[serde(default)]
+    pub stomp_shake_progress: i32,
+    /// Direction of the last shake press that counted: -1 left, 1 right, 0 none.
+    #[serde(default)]
+    pub stomp_last_shake_dir: i32,
+    /// Direction a ridden player is forced to run, and the ticks until it flips.
+    #[serde(default)]
+    pub stomp_auto_run_dir: i32,
+    #[serde(default)]
+    pub stomp_auto_run_timer: i32,
+    /// Ticks before this player can be ridden again.
+    #[serde(default)]
+    pub stomp_cooldown: i32,
+    /// Consecutive ticks ridden off the ground.
+    #[serde(default)]
+    pub stomp_airborne_ticks: u8,
+}
+
+fn no_player() -> PlayerId {
+    -1
 }
 
 // ── Projectile ──────────────────────────────────────────────
@@ -136,6 +190,13 @@ pub struct Platform {
     pub y: f64,
     pub width: f64,
     pub height: f64,
+    /// Top-only collision, as in the fixed-point sim (`fp::Platform::one_way`).
+    /// Accepts the TS field name, which the server's prover input passes through.
+    #[serde(default, alias = "oneWay")]
+    pub one_way: bool,
+    /// Contact damage in the fixed-point sim (`fp::Platform::hazard_damage`); ignored here.
+    #[serde(default, alias = "hazardDamage")]
+    pub hazard_damage: i32,
 }
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
@@ -179,6 +240,13 @@ pub struct MatchConfig {
     pub initial_lives: i32,
     pub match_duration_ticks: u32,
     pub sudden_death_start_tick: u32,
+    /// Side-swapped rematch on the mirrored map.
+    #[serde(default)]
+    pub mirrored: bool,
+    /// Played on `fp::generate_map` of this seed (default params); the prover then builds the
+    /// map itself and `map` is only for rendering.
+    #[serde(default)]
+    pub gen_seed: Option<u32>,
 }
 
 // ── Prover I/O ──────────────────────────────────────────────
@@ -186,6 +254,10 @@ pub struct MatchConfig {
 /// Input to the zkVM guest program.
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct ProverInput {
+    /// On-chain session this proof settles; committed to the journal so a proof for one
+    /// session can't settle another.
+    #[serde(default)]
+    pub session_id: u32,
     pub config: MatchConfig,
     /// One entry per tick. Each entry is [player0_input, player1_input].
     pub transcript: Vec<[PlayerInput; 2]>,
@@ -196,59 +268,385 @@ pub struct ProverInput {
 pub struct ProverOutput {
     /// Winner player id, or -1 for draw.
     pub winner: i32,
-    /// Final kill scores [player0, player1].
-    pub scores: [u32; 2],
+    /// Final kill scores by player; slots past `player_count` are 0.
+    pub scores: [u32; MAX_PLAYERS],
     /// SHA-256 hash of the full input transcript.
     pub transcript_hash: [u8; 32],
     /// SHA-256 hash of the seed (commitment).
     pub seed_commit: [u8; 32],
+    /// On-chain session id the proof is bound to.
+    pub session_id: u32,
+    /// `FpMatchConfig::commitment()` of the rules the match was replayed under.
+    pub config_commit: [u8; 32],
+    /// Deaths of each player that nobody scored for because the environment (the
+    /// sudden-death zone) did the killing. Own-shot deaths count in neither `scores` nor here.
+    #[serde(default)]
+    pub env_kills: [u32; MAX_PLAYERS],
+    /// Players in the match. Two commit the `layout::CURRENT` journal; more commit
+    /// `layout::WIDE`, the only one with slots for players 2 and 3.
+    #[serde(default = "two_players")]
+    pub player_count: u8,
+    /// `fp::map_hash` of the map the match was played on (oriented, like the state's). All
+    /// zero when decoded from a journal older than `layout::V3`: those were all on the arena.
+    #[serde(default)]
+    pub map_hash: [u8; 32],
+    /// Tick the match ended on. This and the final health and lives are 0 when decoded from a
+    /// journal older than `layout::V5`.
+    #[serde(default)]
+    pub final_tick: u32,
+    /// Each player's health when the match ended, in registered order; slots past
+    /// `player_count` are 0.
+    #[serde(default)]
+    pub final_health: [i32; MAX_PLAYERS],
+    /// Each player's remaining lives when the match ended, in registered order.
+    #[serde(default)]
+    pub final_lives: [i32; MAX_PLAYERS],
+    /// Winning team, or `layout::NO_WINNER_TEAM`. The sim has no teams yet: a two-player match
+    /// commits `TeamResult::solo`, registered player 0 as team 0 and player 1 as team 1, so
+    /// this is the winner and the team scores are the player scores. Decoded from a journal
+    /// older than `layout::V7`, the team fields are that same `TeamResult::solo`.
+    #[serde(default = "no_winner_team")]
+    pub winner_team: i32,
+    /// Kill score of each team.
+    #[serde(default)]
+    pub team_scores: [u32; TEAMS],
+    /// Each player's team, in registered order; slots past `player_count` are 0.
+    #[serde(default)]
+    pub player_teams: [u32; MAX_PLAYERS],
+    /// `fp::PickupStats::commitment` of the players' pickup stats, in registered order. All
+    /// zero when decoded from a journal older than `layout::V9`.
+    #[serde(default)]
+    pub pickup_stats_commit: [u8; 32],
+}
+
+fn two_players() -> u8 {
+    2
+}
+
+fn no_winner_team() -> i32 {
+    layout::NO_WINNER_TEAM
 }
 
-/// Journal layout: 19 u32 words = 76 bytes.
-pub const PROVER_OUTPUT_WORDS: usize = 19;
+/// Journal layout: version word + 30 u32 words + the map hash + 5 stats words + 7 team words +
+/// the pickup stats commitment = 236 bytes (see `chickenz_journal_layout`).
+pub const PROVER_OUTPUT_WORDS: usize = layout::WORDS;
+
+/// Copy a 32-byte hash into 8 consecutive journal words starting at byte `offset`.
+fn put_hash(w: &mut [u32], offset: usize, hash: &[u8; 32]) {
+    for (i, c) in hash.chunks_exact(4).enumerate() {
+        w[layout::word(offset) + i] = u32::from_le_bytes([c[0], c[1], c[2], c[3]]);
+    }
+}
 
 impl ProverOutput {
-    pub fn to_journal_words(&self) -> [u32; PROVER_OUTPUT_WORDS] {
-        let mut w = [0u32; PROVER_OUTPUT_WORDS];
-        w[0] = self.winner as u32;
-        w[1] = self.scores[0];
-        w[2] = self.scores[1];
-        for i in 0..8 {
-            let off = i * 4;
-            w[3 + i] = u32::from_le_bytes([
-                self.transcript_hash[off],
-                self.transcript_hash[off + 1],
-                self.transcript_hash[off + 2],
-                self.transcript_hash[off + 3],
-            ]);
+    /// Fill `w` with the journal in layout `l`, version word first.
+    fn write_journal(&self, l: layout::Layout, w: &mut [u32]) {
+        w[0] = layout::encode_version(l.version);
+        w[layout::word(l.at(layout::WINNER))] = layout::encode_winner(self.winner);
+        w[layout::word(l.at(layout::SCORE_P0))] = self.scores[0];
+        w[layout::word(l.at(layout::SCORE_P1))] = self.scores[1];
+        put_hash(w, l.at(layout::TRANSCRIPT_HASH), &self.transcript_hash);
+        put_hash(w, l.at(layout::SEED_COMMIT), &self.seed_commit);
+        w[layout::word(l.at(layout::SESSION_ID))] = self.session_id;
+        put_hash(w, l.at(layout::CONFIG_COMMIT), &self.config_commit);
+        w[layout::word(l.at(layout::ENV_KILLS_P0))] = self.env_kills[0];
+        w[layout::word(l.at(layout::ENV_KILLS_P1))] = self.env_kills[1];
+        if l.is_wide() {
+            w[layout::word(l.at(layout::PLAYER_COUNT))] = self.player_count as u32;
+            w[layout::word(l.at(layout::SCORE_P2))] = self.scores[2];
+            w[layout::word(l.at(layout::SCORE_P3))] = self.scores[3];
+            w[layout::word(l.at(layout::ENV_KILLS_P2))] = self.env_kills[2];
+            w[layout::word(l.at(layout::ENV_KILLS_P3))] = self.env_kills[3];
+        }
+        if let Some(at) = l.map_hash_at() {
+            put_hash(w, at, &self.map_hash);
+        }
+        if let Some(at) = l.stats_at(0) {
+            let mut put = |field: usize, v: u32| w[layout::word(at + field)] = v;
+            put(layout::FINAL_TICK, self.final_tick);
+            put(layout::FINAL_HEALTH_P0, self.final_health[0] as u32);
+            put(layout::FINAL_HEALTH_P1, self.final_health[1] as u32);
+            put(layout::FINAL_LIVES_P0, self.final_lives[0] as u32);
+            put(layout::FINAL_LIVES_P1, self.final_lives[1] as u32);
+            if l.is_wide() {
+                put(layout::FINAL_HEALTH_P2, self.final_health[2] as u32);
+                put(layout::FINAL_HEALTH_P3, self.final_health[3] as u32);
+                put(layout::FINAL_LIVES_P2, self.final_lives[2] as u32);
+                put(layout::FINAL_LIVES_P3, self.final_lives[3] as u32);
+            }
         }
-        for i in 0..8 {
-            let off = i * 4;
-            w[11 + i] = u32::from_le_bytes([
-                self.seed_commit[off],
-                self.seed_commit[off + 1],
-                self.seed_commit[off + 2],
-                self.seed_commit[off + 3],
-            ]);
+        if let Some(at) = l.teams_at(0) {
+            let mut put = |field: usize, v: u32| w[layout::word(at + field)] = v;
+            put(layout::WINNER_TEAM, layout::encode_winner(self.winner_team));
+            put(layout::TEAM_SCORE_0, self.team_scores[0]);
+            put(layout::TEAM_SCORE_1, self.team_scores[1]);
+            put(layout::PLAYER_TEAM_P0, self.player_teams[0]);
+            put(layout::PLAYER_TEAM_P1, self.player_teams[1]);
+            put(layout::PLAYER_TEAM_P2, self.player_teams[2]);
+            put(layout::PLAYER_TEAM_P3, self.player_teams[3]);
         }
+        if let Some(at) = l.pickup_stats_at(layout::PICKUP_STATS_COMMIT) {
+            put_hash(w, at, &self.pickup_stats_commit);
+        }
+    }
+
+    /// The team result this output commits.
+    pub fn teams(&self) -> TeamResult {
+        TeamResult { winner_team: self.winner_team, team_scores: self.team_scores, player_teams: self.player_teams }
+    }
+
+    /// The two-player journal in the current schema version (`layout::CURRENT`), version word
+    /// first. A wider match commits `to_wide_journal_words`.
+    pub fn to_journal_words(&self) -> [u32; PROVER_OUTPUT_WORDS] {
+        debug_assert_eq!(self.player_count, 2, "a wider match commits the wide journal");
+        let mut w = [0u32; PROVER_OUTPUT_WORDS];
+        self.write_journal(layout::CURRENT, &mut w);
+        w
+    }
+
+    /// Journal plus the `event-chain` trailer (the final `fp::EventChain` head).
+    pub fn to_journal_words_with_event_chain(&self, head: &[u8; 32]) -> [u32; layout::WORDS_WITH_EVENT_CHAIN] {
+        let mut w = [0u32; layout::WORDS_WITH_EVENT_CHAIN];
+        w[..PROVER_OUTPUT_WORDS].copy_from_slice(&self.to_journal_words());
+        put_hash(&mut w, layout::CURRENT.event_chain_head(), head);
         w
     }
 
+    /// The journal of a match of more than two players (`layout::WIDE`), version word first.
+    pub fn to_wide_journal_words(&self) -> [u32; layout::WIDE_WORDS] {
+        let mut w = [0u32; layout::WIDE_WORDS];
+        self.write_journal(layout::WIDE, &mut w);
+        w
+    }
+
+    /// Wide journal plus the `event-chain` trailer.
+    pub fn to_wide_journal_words_with_event_chain(&self, head: &[u8; 32]) -> [u32; layout::WIDE_WORDS_WITH_EVENT_CHAIN] {
+        let mut w = [0u32; layout::WIDE_WORDS_WITH_EVENT_CHAIN];
+        w[..layout::WIDE_WORDS].copy_from_slice(&self.to_wide_journal_words());
+        put_hash(&mut w, layout::WIDE.event_chain_head(), head);
+        w
+    }
+
+    /// Layout of a journal of a known schema version that is long enough to decode, from its
+    /// version word (or its absence: version 0).
+    pub fn journal_layout(b: &[u8]) -> Option<layout::Layout> {
+        chickenz_core_mini::journal_layout(b)
+    }
+
+    /// `to_journal_words` as the little-endian bytes the guest commits.
+    pub fn to_journal_bytes(&self) -> [u8; layout::SIZE] {
+        let mut b = [0u8; layout::SIZE];
+        for (chunk, w) in b.chunks_exact_mut(4).zip(self.to_journal_words()) {
+            chunk.copy_from_slice(&w.to_le_bytes());
+        }
+        b
+    }
+
+    /// Decode a journal of any known schema version with `chickenz_core_mini::Journal::decode`,
+    /// the decode the contract settles with. Panics on an unknown version or a short journal;
+    /// check with `journal_layout` first for untrusted bytes.
     pub fn from_journal_bytes(b: &[u8]) -> Self {
-        assert!(b.len() >= PROVER_OUTPUT_WORDS * 4);
-        let u32_at = |off: usize| -> u32 {
-            u32::from_le_bytes([b[off], b[off + 1], b[off + 2], b[off + 3]])
-        };
-        let hash_at = |start: usize| -> [u8; 32] {
-            let mut h = [0u8; 32];
-            h.copy_from_slice(&b[start..start + 32]);
-            h
-        };
+        Journal::decode(b).expect("journal of a known version and full length").into()
+    }
+}
+
+impl From<Journal> for ProverOutput {
+    fn from(j: Journal) -> Self {
+        let stats = j.stats.unwrap_or_default();
+        let teams = j.teams.unwrap_or_else(|| TeamResult::solo(j.player_count, j.winner, &j.scores));
+        ProverOutput {
+            winner: j.winner,
+            scores: j.scores,
+            transcript_hash: j.transcript_hash,
+            seed_commit: j.seed_commit,
+            session_id: j.session_id,
+            config_commit: j.config_commit,
+            env_kills: j.env_kills,
+            player_count: j.player_count,
+            map_hash: j.map_hash.unwrap_or_default(),
+            final_tick: stats.tick,
+            final_health: stats.health,
+            final_lives: stats.lives,
+            winner_team: teams.winner_team,
+            team_scores: teams.team_scores,
+            player_teams: teams.player_teams,
+            pickup_stats_commit: j.pickup_stats_commit.unwrap_or_default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn output() -> ProverOutput {
         ProverOutput {
-            winner: u32_at(0) as i32,
-            scores: [u32_at(4), u32_at(8)],
-            transcript_hash: hash_at(12),
-            seed_commit: hash_at(44),
+            winner: 1,
+            scores: [2, 5, 0, 0],
+            transcript_hash: [0xAA; 32],
+            seed_commit: [0xBB; 32],
+            session_id: 77,
+            config_commit: [0xCC; 32],
+            env_kills: [1, 0, 0, 0],
+            player_count: 2,
+            map_hash: [0xDD; 32],
+            final_tick: 1730,
+            final_health: [0, 45, 0, 0],
+            final_lives: [0, 2, 0, 0],
+            winner_team: 1,
+            team_scores: [2, 5],
+            player_teams: [0, 1, 0, 0],
+            pickup_stats_commit: [0x5E; 32],
+        }
+    }
+
+    fn no_stats(out: ProverOutput) -> ProverOutput {
+        ProverOutput { final_tick: 0, final_health: [0; MAX_PLAYERS], final_lives: [0; MAX_PLAYERS], ..no_pickup_stats(out) }
+    }
+
+    fn no_pickup_stats(out: ProverOutput) -> ProverOutput {
+        ProverOutput { pickup_stats_commit: [0; 32], ..out }
+    }
+
+    /// `out` as a journal in layout `l`, little-endian.
+    fn journal_bytes(out: &ProverOutput, l: layout::Layout) -> Vec<u8> {
+        let mut w = vec![0u32; l.size() / 4];
+        out.write_journal(l, &mut w);
+        w.iter().flat_map(|w| w.to_le_bytes()).collect()
+    }
+
+    #[test]
+    fn journal_decodes_versions_0_and_1() {
+        let out = output();
+        let v1 = journal_bytes(&out, layout::V1);
+        assert_eq!(v1[..4], layout::encode_version(1).to_le_bytes());
+        // A version-0 journal is the same body with no version word
+        let v0 = &v1[4..];
+        assert_eq!(v0.len(), layout::V0.size());
+        // Neither carries the map hash (they were all played on the arena) or the final stats
+        let arena = ProverOutput { map_hash: [0; 32], ..no_stats(output()) };
+
+        let mut trailer = [0u8; layout::HASH_LEN];
+        trailer[0] = 9;
+        for (bytes, version) in [(&v1[..], 1), (v0, 0)] {
+            assert_eq!(ProverOutput::journal_layout(bytes).map(|l| l.version), Some(version));
+            assert_eq!(ProverOutput::from_journal_bytes(bytes), arena);
+            let with_chain = [bytes, &trailer[..]].concat();
+            assert_eq!(ProverOutput::from_journal_bytes(&with_chain), arena);
+            assert!(ProverOutput::journal_layout(&bytes[..bytes.len() - 4]).is_none());
+        }
+
+        // Every winner an unversioned journal can start with reads as version 0
+        for winner in [layout::WINNER_DRAW, 0] {
+            let draw = journal_bytes(&ProverOutput { winner, ..output() }, layout::V1);
+            assert_eq!(ProverOutput::from_journal_bytes(&draw[4..]).winner, winner);
         }
+
+        let mut unknown = v1;
+        unknown[..4].copy_from_slice(&layout::encode_version(11).to_le_bytes());
+        assert!(ProverOutput::journal_layout(&unknown).is_none());
+        assert!(ProverOutput::journal_layout(&[]).is_none());
+    }
+
+    #[test]
+    fn current_journal_commits_the_map_hash() {
+        let out = output();
+        let bytes = out.to_journal_bytes();
+        assert_eq!(ProverOutput::journal_layout(&bytes), Some(layout::CURRENT));
+        assert_eq!(ProverOutput::from_journal_bytes(&bytes), out);
+        assert_eq!(bytes[layout::CURRENT.map_hash_at().unwrap()..][..32], [0xDD; 32]);
+        // Everything before the hash sits where a v1 reader looks for it
+        assert_eq!(bytes[4..layout::V1.size()], journal_bytes(&out, layout::V1)[4..]);
+
+        let trailer = [9u8; layout::HASH_LEN];
+        let words = out.to_journal_words_with_event_chain(&trailer);
+        assert_eq!(words[layout::WORDS], u32::from_le_bytes([9; 4]));
+        assert!(layout::CURRENT.is_valid_size(words.len() * 4));
+    }
+
+    #[test]
+    fn current_journal_commits_the_final_stats() {
+        let out = output();
+        let bytes = out.to_journal_bytes();
+        let word = |field| {
+            let at = layout::CURRENT.stats_at(field).unwrap();
+            i32::from_le_bytes(bytes[at..at + 4].try_into().unwrap())
+        };
+        assert_eq!(word(layout::FINAL_TICK), 1730);
+        assert_eq!(word(layout::FINAL_HEALTH_P1), 45);
+        assert_eq!(word(layout::FINAL_LIVES_P1), 2);
+        // Everything before the stats sits where a v3 reader looks for it
+        assert_eq!(bytes[4..layout::V3.size()], journal_bytes(&out, layout::V3)[4..]);
+
+        // A v3 journal still decodes, with the stats zeroed
+        let v3 = journal_bytes(&out, layout::V3);
+        assert_eq!(ProverOutput::from_journal_bytes(&v3), no_stats(out.clone()));
+
+        // Negative values survive the trip through u32 words
+        let negative = ProverOutput { final_health: [-3, 0, 0, 0], ..out };
+        assert_eq!(ProverOutput::from_journal_bytes(&negative.to_journal_bytes()), negative);
+    }
+
+    #[test]
+    fn one_v_one_teams_mirror_the_players() {
+        let out = output();
+        assert_eq!(out.teams(), TeamResult::solo(out.player_count, out.winner, &out.scores));
+        assert_eq!((out.winner_team, out.team_scores), (out.winner, [out.scores[0], out.scores[1]]));
+        let bytes = out.to_journal_bytes();
+        let word = |field| {
+            let at = layout::CURRENT.teams_at(field).unwrap();
+            u32::from_le_bytes(bytes[at..at + 4].try_into().unwrap())
+        };
+        assert_eq!((word(layout::WINNER_TEAM), word(layout::TEAM_SCORE_0), word(layout::TEAM_SCORE_1)), (1, 2, 5));
+        assert_eq!((word(layout::PLAYER_TEAM_P1), word(layout::PLAYER_TEAM_P3)), (1, 0));
+        // Everything before the teams sits where a v5 reader looks for it
+        let v5 = journal_bytes(&out, layout::V5);
+        assert_eq!(bytes[4..layout::V5.size()], v5[4..]);
+        // and a v5 journal decodes to the same teams, derived from its winner and scores
+        assert_eq!(ProverOutput::from_journal_bytes(&v5), no_pickup_stats(out));
+    }
+
+    #[test]
+    fn current_journal_commits_the_pickup_stats() {
+        let out = output();
+        let bytes = out.to_journal_bytes();
+        let at = layout::CURRENT.pickup_stats_at(layout::PICKUP_STATS_COMMIT).unwrap();
+        assert_eq!(bytes[at..at + layout::HASH_LEN], [0x5E; 32]);
+        // Everything before the commitment sits where a v7 reader looks for it
+        let v7 = journal_bytes(&out, layout::V7);
+        assert_eq!(bytes[4..layout::V7.size()], v7[4..]);
+        // and a v7 journal still decodes, with the commitment zeroed
+        assert_eq!(ProverOutput::from_journal_bytes(&v7), no_pickup_stats(out));
+    }
+
+    #[test]
+    fn wide_journal_carries_every_player() {
+        let out = ProverOutput {
+            winner: 3,
+            scores: [1, 0, 4, 2],
+            env_kills: [0, 1, 0, 3],
+            player_count: 4,
+            final_health: [0, 0, 0, 80],
+            final_lives: [0, 0, 1, 3],
+            winner_team: layout::NO_WINNER_TEAM,
+            team_scores: [0; TEAMS],
+            player_teams: [0; MAX_PLAYERS],
+            ..output()
+        };
+        let words = out.to_wide_journal_words();
+        let bytes: Vec<u8> = words.iter().flat_map(|w| w.to_le_bytes()).collect();
+        assert_eq!(bytes.len(), layout::WIDE.size());
+        assert_eq!(ProverOutput::journal_layout(&bytes), Some(layout::WIDE));
+        assert_eq!(ProverOutput::from_journal_bytes(&bytes), out);
+
+        // The two-player fields sit where a v1 reader (the contract) looks for them
+        let two = ProverOutput { player_count: 2, scores: [1, 0, 0, 0], env_kills: [0, 1, 0, 0], ..out.clone() };
+        assert_eq!(ProverOutput::from_journal_bytes(&bytes).teams(), TeamResult::solo(4, 3, &out.scores));
+        assert_eq!(bytes[4..layout::V1.size()], two.to_journal_bytes()[4..layout::V1.size()]);
+
+        let head = [7u8; layout::HASH_LEN];
+        let chained = out.to_wide_journal_words_with_event_chain(&head);
+        assert_eq!(chained[layout::WIDE_WORDS], u32::from_le_bytes([7; 4]));
+        assert!(layout::WIDE.is_valid_size(chained.len() * 4));
     }
 }
@@ -1,7 +1,7 @@
 #![allow(clippy::needless_range_loop)] // Index loops required for mutable cross-referencing
 
 use crate::constants::*;
-use crate::prng::prng_next;
+use crate::prng::{prng_int_range, prng_next};
 use crate::types::*;
 
 /// Create initial weapon pickups from map spawn points.
@@ -19,7 +19,27 @@ pub fn create_initial_pickups(map: &GameMap) -> Vec<WeaponPickup> {
         .collect()
 }
 
-/// Tick pickup respawn timers and pick a random weapon type when respawning.
+/// Weighted draw over `weights`: one integer draw over the total, picked as `fp::prng_weighted_index`
+/// picks it. The draw itself comes from this sim's PRNG, not the fixed-point one.
+pub fn weighted_index(rng_state: u32, weights: &[i32]) -> (usize, u32) {
+    let total: i32 = weights.iter().sum();
+    let (draw, next) = prng_int_range(rng_state, 0, total - 1);
+    (weighted_pick(draw, weights), next)
+}
+
+/// The entry `draw` (in `0..total`) lands in when `weights` are laid end to end.
+fn weighted_pick(draw: i32, weights: &[i32]) -> usize {
+    let mut cumulative = 0;
+    for (i, &w) in weights.iter().enumerate() {
+        cumulative += w;
+        if draw < cumulative {
+            return i;
+        }
+    }
+    weights.len() - 1
+}
+
+/// Tick pickup respawn timers and pick a weighted random weapon type when respawning.
 pub fn tick_pickup_timers(pickups: &mut [WeaponPickup], rng_state: &mut u32) {
     for p in pickups.iter_mut() {
         if p.respawn_timer <= 0 {
@@ -27,10 +47,9 @@ pub fn tick_pickup_timers(pickups: &mut [WeaponPickup], rng_state: &mut u32) {
         }
         p.respawn_timer -= 1;
         if p.respawn_timer <= 0 {
-            let (idx, new_rng) = prng_next(*rng_state);
+            let (idx, new_rng) = weighted_index(*rng_state, &WEAPON_RESPAWN_WEIGHTS);
             *rng_state = new_rng;
-            let weapon_idx = (idx * WEAPON_ROTATION.len() as f64) as usize % WEAPON_ROTATION.len();
-            p.weapon = WEAPON_ROTATION[weapon_idx];
+            p.weapon = WEAPON_ROTATION[idx];
         }
     }
 }
@@ -180,6 +199,7 @@ pub fn apply_splash_damage(
                     let victim_id = players[i].id;
                     players[i].health = 0;
                     players[i].state_flags = 0;
+                    players[i].invincibility_source = invincibility_source::NONE;
                     kills.push((owner_id, victim_id));
                 } else {
                     players[i].health = new_health;
@@ -210,8 +230,20 @@ mod tests {
             grounded: false,
             state_flags: player_state_flag::ALIVE,
             respawn_timer: 0,
+            invincibility_source: invincibility_source::NONE,
             weapon: None,
             ammo: 0,
+            jumps_left: MAX_JUMPS,
+            wall_sliding: false,
+            wall_dir: 0,
+            stomped_by: -1,
+            stomping_on: -1,
+            stomp_shake_progress: 0,
+            stomp_last_shake_dir: 0,
+            stomp_auto_run_dir: 0,
+            stomp_auto_run_timer: 0,
+            stomp_cooldown: 0,
+            stomp_airborne_ticks: 0,
         }
     }
 
@@ -247,6 +279,38 @@ mod tests {
         assert_ne!(rng, 42);
     }
 
+    #[test]
+    fn weighted_pick_matches_fp() {
+        use crate::fp;
+        for weights in [&fp::WEAPON_RESPAWN_WEIGHTS[..], &WEAPON_RESPAWN_WEIGHTS, &[5, 0, 1, 0, 2]] {
+            let total = weights.iter().sum::<i32>();
+            let mut rng = 42;
+            for _ in 0..1000 {
+                let (draw, _) = fp::prng_int_range(rng, 0, total - 1);
+                let (idx, next) = fp::prng_weighted_index(rng, weights);
+                assert_eq!(weighted_pick(draw, weights), idx, "draw {draw} over {weights:?}");
+                rng = next;
+            }
+        }
+    }
+
+    #[test]
+    fn weighted_respawn_is_deterministic_and_skips_zero_weights() {
+        let draws = |mut rng: u32| {
+            (0..1000)
+                .map(|_| {
+                    let (i, next) = weighted_index(rng, &[5, 0, 1, 0, 2]);
+                    rng = next;
+                    i
+                })
+                .collect::<Vec<_>>()
+        };
+        let a = draws(7);
+        assert_eq!(a, draws(7));
+        assert!(a.iter().all(|&i| i == 0 || i == 2 || i == 4));
+        assert!(a.iter().filter(|&&i| i == 0).count() > a.iter().filter(|&&i| i == 4).count());
+    }
+
     #[test]
     fn player_picks_up_weapon() {
         let mut players = vec![test_player(0, 100.0, 100.0)];
@@ -314,4 +378,13 @@ mod tests {
         assert_eq!(players[0].health, MAX_HEALTH);
         assert!(kills.is_empty()); // damage but not killed
     }
+
+    #[test]
+    fn fixed_point_only_weapons_are_rejected() {
+        // The f64 sim models the first five fixed-point weapons, in the same order
+        for id in 0..WEAPON_ROTATION.len() as i32 {
+            assert_eq!(crate::types::WeaponType::from_i32(id).map(|w| w as i32), Some(id));
+        }
+        assert_eq!(crate::types::WeaponType::from_i32(crate::fp::WEAPON_GRENADE as i32), None);
+    }
 }
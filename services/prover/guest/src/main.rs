@@ -2,32 +2,74 @@
 
 risc0_zkvm::guest::entry!(main);
 
-use chickenz_core::fp;
+use chickenz_core::fp::{self, FpMatchConfig, MatchRules, RawInputHeader};
 use chickenz_core::ProverOutput;
 
-/// Max raw input: 8 (header) + 6 * 3600 (ticks) = 21608 bytes = 5402 u32 words
-const MAX_INPUT_WORDS: usize = 5402;
+/// Max raw input: 12 (wide header) + 12 * 3600 (four players' ticks) = 43212 bytes = 10803 u32 words
+const MAX_INPUT_WORDS: usize = 10803;
 
+/// Input (via read_slice): [session_id: u32, mirrored: u32, byte_len: u32], the rules as
+/// `MatchRules::to_words`, then the raw input words.
 fn main() {
     // Read raw bytes into fixed-size buffer — no heap allocation
-    let mut input_len = [0u32; 1];
-    risc0_zkvm::guest::env::read_slice(&mut input_len);
-    let byte_len = input_len[0] as usize;
+    let mut header = [0u32; 3];
+    risc0_zkvm::guest::env::read_slice(&mut header);
+    let session_id = header[0];
+    let byte_len = header[2] as usize;
+    let mut rules = [0u32; 3];
+    risc0_zkvm::guest::env::read_slice(&mut rules);
+    let cfg = FpMatchConfig { mirrored: header[1] != 0, ..FpMatchConfig::default() };
+    let mut cfg = MatchRules::from_words(rules).apply(cfg);
     let word_len = (byte_len + 3) / 4;
 
     let mut raw_words = [0u32; MAX_INPUT_WORDS];
     risc0_zkvm::guest::env::read_slice(&mut raw_words[..word_len]);
     let raw_bytes: &[u8] = bytemuck::cast_slice(&raw_words[..word_len]);
     let raw_bytes = &raw_bytes[..byte_len];
+    // Checked before narrowing: a count past u8 would otherwise wrap into the valid range
+    let player_count = RawInputHeader::parse(raw_bytes).player_count;
+    assert!((2..=fp::MAX_PLAYERS).contains(&player_count), "raw input declares {player_count} players");
+    cfg.player_count = player_count as u8;
 
     // Single-pass: parse inputs → hash → step sim (zero extra allocations)
-    let result = fp::run_streaming(raw_bytes);
+    #[cfg(feature = "event-chain")]
+    let mut chain = fp::EventChain::new();
+    #[cfg(feature = "event-chain")]
+    let result = fp::run_streaming_with_chain(raw_bytes, &cfg, &mut chain);
+    #[cfg(not(feature = "event-chain"))]
+    let result = fp::run_streaming_with(raw_bytes, &cfg);
 
+    let teams = result.state.registered_teams();
     let output = ProverOutput {
-        winner: result.state.winner,
-        scores: result.state.score,
+        winner: result.state.registered_winner(),
+        scores: result.state.registered_scores(),
         transcript_hash: result.transcript_hash,
         seed_commit: result.seed_commit,
+        session_id,
+        config_commit: cfg.commitment(),
+        env_kills: result.state.registered_env_kills(),
+        player_count: cfg.player_count,
+        map_hash: fp::map_hash(&result.state.map),
+        final_tick: result.state.tick as u32,
+        final_health: result.state.registered_health(),
+        final_lives: result.state.registered_lives(),
+        winner_team: teams.winner_team,
+        team_scores: teams.team_scores,
+        player_teams: teams.player_teams,
+        pickup_stats_commit: result.state.pickup_stats_commit(),
     };
-    risc0_zkvm::guest::env::commit_slice(&output.to_journal_words());
+    // Two players keep the current journal; only wider matches need the wide one
+    #[cfg(feature = "event-chain")]
+    let head = chain.head();
+    if cfg.player_count > 2 {
+        #[cfg(feature = "event-chain")]
+        risc0_zkvm::guest::env::commit_slice(&output.to_wide_journal_words_with_event_chain(&head));
+        #[cfg(not(feature = "event-chain"))]
+        risc0_zkvm::guest::env::commit_slice(&output.to_wide_journal_words());
+    } else {
+        #[cfg(feature = "event-chain")]
+        risc0_zkvm::guest::env::commit_slice(&output.to_journal_words_with_event_chain(&head));
+        #[cfg(not(feature = "event-chain"))]
+        risc0_zkvm::guest::env::commit_slice(&output.to_journal_words());
+    }
 }
@@ -0,0 +1,326 @@
+//! Settlement audits: check a settled journal against the archived transcript by replaying the
+//! match natively, without the zkVM.
+//!
+//! The replay runs the same `fp::run_streaming_with` the guests run, so an honest journal and its
+//! transcript agree on every committed field. Any mismatch points at a tampered transcript, a
+//! wrong seed, or a journal from a different session.
+
+use std::path::Path;
+
+use chickenz_core::fp::{self, FpProverInput};
+use chickenz_core::ProverOutput;
+
+use crate::pipeline::{self, PipelineError, Result};
+
+/// One committed journal field compared against the replay.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FieldCheck {
+    pub field: &'static str,
+    pub committed: String,
+    pub replayed: String,
+}
+
+impl FieldCheck {
+    fn new(field: &'static str, committed: String, replayed: String) -> Self {
+        FieldCheck { field, committed, replayed }
+    }
+
+    pub fn passed(&self) -> bool {
+        self.committed == self.replayed
+    }
+}
+
+/// Per-field audit result, in journal order.
+#[derive(Clone, Debug)]
+pub struct AuditReport {
+    pub checks: Vec<FieldCheck>,
+    /// The journal's decoded output.
+    pub committed: ProverOutput,
+}
+
+impl AuditReport {
+    pub fn passed(&self) -> bool {
+        self.checks.iter().all(FieldCheck::passed)
+    }
+
+    pub fn failures(&self) -> impl Iterator<Item = &FieldCheck> {
+        self.checks.iter().filter(|c| !c.passed())
+    }
+}
+
+impl std::fmt::Display for AuditReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for c in &self.checks {
+            let status = if c.passed() { "PASS" } else { "FAIL" };
+            writeln!(f, "{status}  {:<16} {}", c.field, c.committed)?;
+            if !c.passed() {
+                writeln!(f, "      {:<16} {} (replayed)", "", c.replayed)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The output an honest guest commits for `input`: native replay of the raw input under the
+/// input's match config on its map, winner and scores in registered player order.
+pub fn replay_output(input: &FpProverInput) -> ProverOutput {
+    let cfg = input.match_config();
+    let result = fp::run_streaming_on(&fp::encode_raw_input(input), &cfg, &input.base_map());
+    let teams = result.state.registered_teams();
+    ProverOutput {
+        winner: result.state.registered_winner(),
+        scores: result.state.registered_scores(),
+        transcript_hash: result.transcript_hash,
+        seed_commit: result.seed_commit,
+        session_id: input.session_id,
+        config_commit: cfg.commitment(),
+        env_kills: result.state.registered_env_kills(),
+        player_count: cfg.player_count,
+        map_hash: fp::map_hash(&result.state.map),
+        final_tick: result.state.tick as u32,
+        final_health: result.state.registered_health(),
+        final_lives: result.state.registered_lives(),
+        winner_team: teams.winner_team,
+        team_scores: teams.team_scores,
+        player_teams: teams.player_teams,
+        pickup_stats_commit: result.state.pickup_stats_commit(),
+    }
+}
+
+/// The pickup stats behind a replay's `pickup_stats_commit`, in registered player order: the
+/// journal only commits their hash.
+pub fn replay_pickup_stats(input: &FpProverInput) -> fp::PickupStats {
+    let cfg = input.match_config();
+    let result = fp::run_streaming_on(&fp::encode_raw_input(input), &cfg, &input.base_map());
+    result.state.registered_pickup_stats()
+}
+
+/// The damage histogram of a replay: the `damage_pacing` buckets its match duration uses, 10 s
+/// of play each. proof_artifacts.json publishes it raw beside the journal's commitment.
+pub fn replay_damage_pacing(input: &FpProverInput) -> Vec<u16> {
+    let buckets = fp::pacing_buckets(input.match_config().match_duration);
+    replay_pickup_stats(input).damage_pacing[..buckets].to_vec()
+}
+
+/// Decode `journal` and compare every committed field against a replay of `input`.
+pub fn audit(journal: &[u8], input: &FpProverInput) -> Result<AuditReport> {
+    let committed = pipeline::decode_output(journal)?;
+    let replayed = replay_output(input);
+    let mut checks = vec![
+        FieldCheck::new("winner", committed.winner.to_string(), replayed.winner.to_string()),
+        FieldCheck::new("score_p0", committed.scores[0].to_string(), replayed.scores[0].to_string()),
+        FieldCheck::new("score_p1", committed.scores[1].to_string(), replayed.scores[1].to_string()),
+        FieldCheck::new(
+            "transcript_hash",
+            hex::encode(committed.transcript_hash),
+            hex::encode(replayed.transcript_hash),
+        ),
+        FieldCheck::new(
+            "seed_commit",
+            hex::encode(committed.seed_commit),
+            hex::encode(replayed.seed_commit),
+        ),
+        FieldCheck::new(
+            "session_id",
+            committed.session_id.to_string(),
+            replayed.session_id.to_string(),
+        ),
+        FieldCheck::new(
+            "config_commit",
+            hex::encode(committed.config_commit),
+            hex::encode(replayed.config_commit),
+        ),
+        FieldCheck::new(
+            "env_kills_p0",
+            committed.env_kills[0].to_string(),
+            replayed.env_kills[0].to_string(),
+        ),
+        FieldCheck::new(
+            "env_kills_p1",
+            committed.env_kills[1].to_string(),
+            replayed.env_kills[1].to_string(),
+        ),
+    ];
+    // Journals before the map hash was committed were all played on the arena
+    if committed.map_hash != [0; 32] {
+        checks.push(FieldCheck::new(
+            "map_hash",
+            hex::encode(committed.map_hash),
+            hex::encode(replayed.map_hash),
+        ));
+    }
+    // Nor the final stats before v5; a replayed match always ends past tick 0
+    if committed.final_tick != 0 {
+        let stat = |field, committed: i64, replayed: i64| {
+            FieldCheck::new(field, committed.to_string(), replayed.to_string())
+        };
+        checks.extend([
+            stat("final_tick", committed.final_tick.into(), replayed.final_tick.into()),
+            stat("final_health_p0", committed.final_health[0].into(), replayed.final_health[0].into()),
+            stat("final_health_p1", committed.final_health[1].into(), replayed.final_health[1].into()),
+            stat("final_lives_p0", committed.final_lives[0].into(), replayed.final_lives[0].into()),
+            stat("final_lives_p1", committed.final_lives[1].into(), replayed.final_lives[1].into()),
+        ]);
+    }
+    // Journals before v7 decode with the teams derived from the winner and scores, which a
+    // replay derives the same way
+    let team = |field, committed: u32, replayed: u32| {
+        FieldCheck::new(field, committed.to_string(), replayed.to_string())
+    };
+    checks.extend([
+        FieldCheck::new("winner_team", committed.winner_team.to_string(), replayed.winner_team.to_string()),
+        team("team_score_0", committed.team_scores[0], replayed.team_scores[0]),
+        team("team_score_1", committed.team_scores[1], replayed.team_scores[1]),
+    ]);
+    // Nor the pickup stats before v9; the commitment of a v9 journal is never all zero
+    if committed.pickup_stats_commit != [0; 32] {
+        checks.push(FieldCheck::new(
+            "pickup_stats_commit",
+            hex::encode(committed.pickup_stats_commit),
+            hex::encode(replayed.pickup_stats_commit),
+        ));
+    }
+    Ok(AuditReport { checks, committed })
+}
+
+/// Parse a `--journal` argument: a hex string (optional `0x`), or a path to a file holding hex,
+/// raw journal bytes, or proof_artifacts.json.
+pub fn load_journal(arg: &str) -> Result<Vec<u8>> {
+    let path = Path::new(arg);
+    if !path.is_file() {
+        return parse_hex(arg);
+    }
+    let bytes = std::fs::read(path)?;
+    if let Ok(json) = serde_json::from_slice::<serde_json::Value>(&bytes) {
+        if let Some(journal) = json.get("journal").and_then(|j| j.as_str()) {
+            return parse_hex(journal);
+        }
+    }
+    match std::str::from_utf8(&bytes) {
+        Ok(text) if parse_hex(text).is_ok() => parse_hex(text),
+        _ => Ok(bytes),
+    }
+}
+
+fn parse_hex(s: &str) -> Result<Vec<u8>> {
+    let s = s.trim();
+    let s = s.strip_prefix("0x").unwrap_or(s);
+    hex::decode(s).map_err(|e| PipelineError::Journal(format!("not a hex journal or file: {e}")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chickenz_core::fp::{button, FpInput, NULL_INPUT};
+
+    /// Both players charge and shoot, so the replay has kills to commit.
+    fn scripted_input() -> FpProverInput {
+        let p0 = FpInput { buttons: button::RIGHT | button::SHOOT, aim_x: 1, aim_y: 0 };
+        let p1 = FpInput { buttons: button::LEFT | button::SHOOT | button::JUMP, aim_x: -1, aim_y: 0 };
+        let mut transcript = vec![[p0, p1]; 600];
+        transcript.extend(vec![[NULL_INPUT; 2]; 200]);
+        FpProverInput { session_id: 7, seed: 42, mirrored: false, transcript, map: None, gen_seed: None, rules: None }
+    }
+
+    fn journal_for(input: &FpProverInput) -> Vec<u8> {
+        replay_output(input).to_journal_bytes().to_vec()
+    }
+
+    #[test]
+    fn matching_transcript_passes() {
+        let input = scripted_input();
+        let report = audit(&journal_for(&input), &input).unwrap();
+        assert!(report.passed(), "{report}");
+        assert_eq!(report.checks.len(), 19);
+        assert!(report.to_string().lines().all(|l| l.starts_with("PASS")));
+
+        // An unversioned (v0) journal of the same match: the body without the version word, the
+        // map hash, the stats or the pickup stats, so there is nothing to check the map or stats against. Its
+        // teams are derived from the winner and scores, and still checked
+        let v0 = &journal_for(&input)[4..chickenz_core::journal_layout::V1.size()];
+        let report = audit(v0, &input).unwrap();
+        assert!(report.passed(), "{report}");
+        assert_eq!(report.checks.len(), 12);
+    }
+
+    #[test]
+    fn replayed_pickup_stats_are_the_committed_ones() {
+        let input = scripted_input();
+        let stats = replay_pickup_stats(&input);
+        assert_eq!(stats.commitment(), replay_output(&input).pickup_stats_commit);
+        // Both players spend the match shooting, so each held a weapon for some of it
+        assert!(stats.time_holding_weapon.iter().take(2).all(|held| held.iter().sum::<u32>() > 0));
+        let pacing = replay_damage_pacing(&input);
+        assert_eq!(pacing.len(), fp::pacing_buckets(input.match_config().match_duration));
+        assert_eq!(pacing, stats.damage_pacing[..pacing.len()]);
+    }
+
+    #[test]
+    fn custom_map_is_replayed_and_checked() {
+        let mut map = fp::arena_map();
+        map.platforms[0].hazard_damage = 40;
+        let input = FpProverInput { map: Some(map), ..scripted_input() };
+        let journal = journal_for(&input);
+        assert!(audit(&journal, &input).unwrap().passed());
+
+        // The same transcript claimed for the arena replays differently
+        let arena = FpProverInput { map: None, ..input };
+        let report = audit(&journal, &arena).unwrap();
+        let failed: Vec<_> = report.failures().map(|c| c.field).collect();
+        assert!(failed.contains(&"map_hash"), "{report}");
+    }
+
+    #[test]
+    fn tampered_transcript_fails() {
+        let input = scripted_input();
+        let journal = journal_for(&input);
+
+        let mut tampered = input.clone();
+        tampered.transcript[10][1] = NULL_INPUT;
+        let report = audit(&journal, &tampered).unwrap();
+        assert!(!report.passed());
+        let failed: Vec<_> = report.failures().map(|c| c.field).collect();
+        assert!(failed.contains(&"transcript_hash"), "{report}");
+        assert!(!failed.contains(&"seed_commit"));
+
+        // Wrong seed or session shows up on its own field
+        let report = audit(&journal, &FpProverInput { seed: 43, session_id: 8, ..input }).unwrap();
+        let failed: Vec<_> = report.failures().map(|c| c.field).collect();
+        assert!(failed.contains(&"seed_commit") && failed.contains(&"session_id"), "{report}");
+    }
+
+    #[test]
+    fn replay_commits_registered_player_order() {
+        for swap in [false, true] {
+            let seed = (0u32..).find(|&s| fp::side_swap_for_seed(s) == swap).unwrap();
+            let input = FpProverInput { seed, ..scripted_input() };
+            let internal = fp::run_streaming(&fp::encode_raw_input(&input)).state;
+            let output = replay_output(&input);
+            assert_eq!(output.winner, fp::side_index(swap, internal.winner));
+            assert_eq!(output.scores, fp::registered_scores(swap, internal.score));
+            assert_eq!(output.env_kills, fp::registered_scores(swap, internal.env_kills));
+            assert_eq!(output.final_lives, fp::registered_scores(swap, internal.lives()));
+            assert_eq!((output.winner_team, output.team_scores), (output.winner, [output.scores[0], output.scores[1]]));
+        }
+    }
+
+    #[test]
+    fn journal_parsing() {
+        let input = scripted_input();
+        let journal = journal_for(&input);
+        let hex = hex::encode(&journal);
+        assert_eq!(load_journal(&hex).unwrap(), journal);
+        assert_eq!(load_journal(&format!("0x{hex}")).unwrap(), journal);
+
+        let dir = std::env::temp_dir();
+        let raw = dir.join("chickenz_audit_journal.bin");
+        std::fs::write(&raw, &journal).unwrap();
+        assert_eq!(load_journal(raw.to_str().unwrap()).unwrap(), journal);
+        let artifacts = dir.join("chickenz_audit_artifacts.json");
+        std::fs::write(&artifacts, serde_json::json!({ "journal": hex }).to_string()).unwrap();
+        assert_eq!(load_journal(artifacts.to_str().unwrap()).unwrap(), journal);
+
+        assert!(load_journal("not hex").is_err());
+        assert!(matches!(audit(&journal[..80], &input), Err(PipelineError::Journal(_))));
+    }
+}
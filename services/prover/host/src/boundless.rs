@@ -0,0 +1,202 @@
+//! In-flight Boundless request record, so a proof survives the host dying mid-wait.
+//!
+//! [`prove_boundless`] writes a [`BoundlessRequest`] as soon as `submit_onchain` returns: the
+//! request ID, its expiry, and the digest and guest image of what it proves. A later
+//! `--boundless` run for the same input finds it and waits for that request instead of paying
+//! for a second one, and `chickenz-host boundless-status <file>` re-attaches to it directly.
+//! The file is removed once the artifacts are written.
+//!
+//! Written like the chunked checkpoint: to a sibling `.tmp` file, then renamed into place.
+//!
+//! [`prove_boundless`]: crate::pipeline::prove_boundless
+
+use std::path::{Path, PathBuf};
+
+use chickenz_core::fp::FpProverInput;
+use serde::{Deserialize, Serialize};
+
+use crate::checkpoint::{input_hash, now, tmp_path};
+use crate::pipeline::{image_id_bytes, PipelineError, Result};
+
+/// Bumped when the record layout changes; older records are rejected.
+pub const BOUNDLESS_STATE_VERSION: u32 = 1;
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BoundlessRequest {
+    pub version: u32,
+    /// Hex `checkpoint::input_hash` of the input the request proves.
+    pub input_digest: String,
+    /// Hex image ID of the guest the request runs.
+    pub image_id: String,
+    /// Hex request ID returned by `submit_onchain`.
+    pub request_id: String,
+    /// Unix seconds after which no prover will fulfill the request.
+    pub expires_at: u64,
+    /// Unix seconds.
+    pub submitted_at: u64,
+    /// Where the artifacts go once the proof arrives.
+    pub artifacts_path: Option<PathBuf>,
+}
+
+/// What a `--boundless` run does about a record left at its state path.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Resume {
+    /// Nothing to pick up (no record, or an expired one): submit a new request.
+    Submit,
+    /// Wait for this still-open request for the same input.
+    Attach(BoundlessRequest),
+}
+
+impl BoundlessRequest {
+    pub fn new(
+        input: &FpProverInput,
+        image_id: &[u32; 8],
+        request_id: String,
+        expires_at: u64,
+        artifacts_path: Option<PathBuf>,
+    ) -> Self {
+        BoundlessRequest {
+            version: BOUNDLESS_STATE_VERSION,
+            input_digest: hex::encode(input_hash(input)),
+            image_id: image_id_hex(image_id),
+            request_id,
+            expires_at,
+            submitted_at: now(),
+            artifacts_path,
+        }
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = std::fs::read_to_string(path)?;
+        let doc: Self = serde_json::from_str(&text)
+            .map_err(|e| PipelineError::Checkpoint(format!("{}: {e}", path.display())))?;
+        if doc.version != BOUNDLESS_STATE_VERSION {
+            return Err(PipelineError::Checkpoint(format!(
+                "{}: Boundless state version {} (expected {BOUNDLESS_STATE_VERSION})",
+                path.display(),
+                doc.version
+            )));
+        }
+        Ok(doc)
+    }
+
+    /// Write to `path.tmp`, then rename over `path`.
+    pub fn write(&self, path: &Path) -> Result<()> {
+        let tmp = tmp_path(path);
+        std::fs::write(&tmp, serde_json::to_string_pretty(self).unwrap())?;
+        std::fs::rename(&tmp, path)?;
+        Ok(())
+    }
+
+    pub fn is_expired(&self, now: u64) -> bool {
+        now >= self.expires_at
+    }
+}
+
+/// Hex of an image ID, as `ProofArtifacts` writes it.
+fn image_id_hex(image_id: &[u32; 8]) -> String {
+    hex::encode(image_id_bytes(image_id))
+}
+
+/// Decide what to do about the record at `path` (if any) before submitting `input` for
+/// `image_id`. An expired record is dropped; an open one for another input or guest build is
+/// an error rather than being overwritten, since its proof is still on the way.
+pub fn resume(path: &Path, input: &FpProverInput, image_id: &[u32; 8], now: u64) -> Result<Resume> {
+    if !path.exists() {
+        return Ok(Resume::Submit);
+    }
+    let doc = BoundlessRequest::load(path)?;
+    if doc.is_expired(now) {
+        return Ok(Resume::Submit);
+    }
+    let digest = hex::encode(input_hash(input));
+    if doc.input_digest != digest {
+        return Err(PipelineError::Checkpoint(format!(
+            "{} holds open request {} for input {}, not this one ({digest}); \
+             finish it with `boundless-status` or remove the file",
+            path.display(),
+            doc.request_id,
+            doc.input_digest
+        )));
+    }
+    if doc.image_id != image_id_hex(image_id) {
+        return Err(PipelineError::Checkpoint(format!(
+            "{} holds open request {} for guest image {}, this build proves {}",
+            path.display(),
+            doc.request_id,
+            doc.image_id,
+            image_id_hex(image_id)
+        )));
+    }
+    Ok(Resume::Attach(doc))
+}
+
+/// Drop the record once its artifacts are written. A missing file is fine.
+pub fn complete(path: &Path) -> Result<()> {
+    match std::fs::remove_file(path) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chickenz_core::fp::NULL_INPUT;
+
+    const IMAGE: [u32; 8] = [1, 2, 3, 4, 5, 6, 7, 8];
+
+    fn input(seed: u32) -> FpProverInput {
+        FpProverInput {
+            session_id: 7,
+            seed,
+            mirrored: false,
+            transcript: vec![[NULL_INPUT; 2]; 100],
+            map: None,
+            gen_seed: None,
+            rules: None,
+        }
+    }
+
+    fn state_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("chickenz-boundless-{name}-{}.json", std::process::id()))
+    }
+
+    #[test]
+    fn request_record_lifecycle() {
+        let path = state_path("lifecycle");
+        complete(&path).unwrap();
+        assert_eq!(resume(&path, &input(42), &IMAGE, 1000).unwrap(), Resume::Submit);
+
+        // Submitted: written atomically, and the same input re-attaches until it expires
+        let doc = BoundlessRequest::new(&input(42), &IMAGE, "1f".into(), 2000, Some("out.json".into()));
+        doc.write(&path).unwrap();
+        assert!(!tmp_path(&path).exists());
+        assert_eq!(BoundlessRequest::load(&path).unwrap(), doc);
+        assert_eq!(resume(&path, &input(42), &IMAGE, 1000).unwrap(), Resume::Attach(doc.clone()));
+        assert_eq!(resume(&path, &input(42), &IMAGE, 2000).unwrap(), Resume::Submit);
+
+        // Fulfilled: the record goes, and a second completion is a no-op
+        complete(&path).unwrap();
+        assert!(!path.exists());
+        complete(&path).unwrap();
+    }
+
+    #[test]
+    fn open_request_for_another_input_or_guest_is_kept() {
+        let path = state_path("mismatch");
+        let doc = BoundlessRequest::new(&input(42), &IMAGE, "1f".into(), 2000, None);
+        doc.write(&path).unwrap();
+        assert!(matches!(resume(&path, &input(43), &IMAGE, 1000), Err(PipelineError::Checkpoint(_))));
+        let rebuilt = [9; 8];
+        assert!(matches!(resume(&path, &input(42), &rebuilt, 1000), Err(PipelineError::Checkpoint(_))));
+        assert_eq!(BoundlessRequest::load(&path).unwrap(), doc);
+        // Once it has expired nobody will prove it, so it may be replaced
+        assert_eq!(resume(&path, &input(43), &IMAGE, 2000).unwrap(), Resume::Submit);
+
+        let stale = BoundlessRequest { version: 0, ..doc };
+        stale.write(&path).unwrap();
+        assert!(BoundlessRequest::load(&path).is_err());
+        complete(&path).unwrap();
+    }
+}
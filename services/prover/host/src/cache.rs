@@ -0,0 +1,127 @@
+//! Content-addressed cache of chunk receipts.
+//!
+//! A chunk proof depends only on the chunk guest, the state it starts from and the inputs it
+//! replays, so [`chunk_key`] hashes exactly those three and [`ChunkCache`] stores the receipt
+//! under that key. A rerun of a match that died half-way, or of a different match sharing its
+//! opening chunks, picks up every chunk already proved without needing a checkpoint. Rebuilding
+//! the chunk guest changes its image ID and with it every key, so stale receipts are never
+//! looked up again.
+//!
+//! Entries are JSON (`{cycles, receipt}`), written to a private `.tmp` file and renamed into
+//! place, so concurrent runs proving the same chunk never read a half-written entry. A cached receipt is only used after it verifies against the chunk image ID and its
+//! journal commits to the same state and input hashes as the key.
+
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use chickenz_core::fp;
+use risc0_zkvm::sha::{Impl, Sha256};
+use serde::{Deserialize, Serialize};
+
+use crate::pipeline::Result;
+
+/// What a chunk receipt proves: `(image_id, state_hash_in, input_hash)`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ChunkKey {
+    pub image_id: [u32; 8],
+    pub state_hash_in: [u8; 32],
+    pub input_hash: [u8; 32],
+}
+
+/// Key of the chunk that replays `inputs` (bytes from
+/// [`encode_chunk_inputs`](crate::pipeline::encode_chunk_inputs)) from `state` on `image_id`.
+pub fn chunk_key(image_id: &[u32; 8], state: &fp::State, inputs: &[u8]) -> ChunkKey {
+    let mut input_hash = [0u8; 32];
+    input_hash.copy_from_slice(Impl::hash_bytes(inputs).as_bytes());
+    ChunkKey { image_id: *image_id, state_hash_in: fp::hash_state(state), input_hash }
+}
+
+impl ChunkKey {
+    /// Hex SHA-256 over the three parts; the entry's file stem.
+    pub fn digest_hex(&self) -> String {
+        let mut bytes: Vec<u8> = self.image_id.iter().flat_map(|w| w.to_le_bytes()).collect();
+        bytes.extend_from_slice(&self.state_hash_in);
+        bytes.extend_from_slice(&self.input_hash);
+        hex::encode(Impl::hash_bytes(&bytes).as_bytes())
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct Entry {
+    cycles: u64,
+    receipt: risc0_zkvm::Receipt,
+}
+
+/// A directory of chunk receipts keyed by [`ChunkKey`].
+#[derive(Clone, Debug)]
+pub struct ChunkCache {
+    dir: PathBuf,
+}
+
+impl ChunkCache {
+    /// Use `dir`, creating it if needed.
+    pub fn open(dir: &Path) -> Result<Self> {
+        std::fs::create_dir_all(dir)?;
+        Ok(ChunkCache { dir: dir.to_path_buf() })
+    }
+
+    /// File holding the entry for `key`.
+    pub fn path(&self, key: &ChunkKey) -> PathBuf {
+        self.dir.join(format!("{}.receipt", key.digest_hex()))
+    }
+
+    /// Receipt and cycle count cached under `key`, if there is an entry and it checks out.
+    /// Unreadable or mismatched entries are treated as missing (and get overwritten).
+    pub fn load(&self, key: &ChunkKey) -> Option<(risc0_zkvm::Receipt, u64)> {
+        let bytes = std::fs::read(self.path(key)).ok()?;
+        let entry: Entry = serde_json::from_slice(&bytes).ok()?;
+        entry.receipt.verify(key.image_id).ok()?;
+        let journal = &entry.receipt.journal.bytes;
+        if journal.len() < fp::CHUNK_PROOF_WORDS * 4 {
+            return None;
+        }
+        let proof = fp::ChunkProof::from_journal_bytes(journal);
+        let current = proof.sim_version == fp::SIM_VERSION;
+        (current && proof.state_hash_in == key.state_hash_in && proof.input_hash == key.input_hash)
+            .then_some((entry.receipt, entry.cycles))
+    }
+
+    pub fn store(&self, key: &ChunkKey, receipt: &risc0_zkvm::Receipt, cycles: u64) -> Result<()> {
+        let path = self.path(key);
+        // Unique per writer: concurrent runs of the same match store the same keys
+        static WRITES: AtomicU64 = AtomicU64::new(0);
+        let mut tmp = path.clone().into_os_string();
+        tmp.push(format!(".{}-{}.tmp", std::process::id(), WRITES.fetch_add(1, Ordering::Relaxed)));
+        let entry = Entry { cycles, receipt: receipt.clone() };
+        std::fs::write(&tmp, serde_json::to_vec(&entry).unwrap())?;
+        std::fs::rename(&tmp, &path)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chickenz_core::fp::NULL_INPUT;
+
+    use crate::pipeline::encode_chunk_inputs;
+
+    #[test]
+    fn key_covers_image_state_and_inputs() {
+        let cfg = fp::FpMatchConfig::default();
+        let state = fp::create_initial_state_with(42, &fp::arena_map(), &cfg);
+        let inputs = encode_chunk_inputs(&[[NULL_INPUT; 2]; 10], 0, 10);
+        let key = chunk_key(&[1; 8], &state, &inputs);
+        assert_eq!(key, chunk_key(&[1; 8], &state, &inputs));
+
+        let mut moved = state.clone();
+        fp::step_mut(&mut moved, &[NULL_INPUT; 2]);
+        let rebuilt = chunk_key(&[2; 8], &state, &inputs);
+        let other_state = chunk_key(&[1; 8], &moved, &inputs);
+        let other_inputs = chunk_key(&[1; 8], &state, &inputs[..inputs.len() - 6]);
+        for other in [rebuilt, other_state, other_inputs] {
+            assert_ne!(other, key);
+            assert_ne!(other.digest_hex(), key.digest_hex());
+        }
+    }
+}
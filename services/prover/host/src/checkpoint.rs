@@ -0,0 +1,353 @@
+//! Chunked-proving progress document for external orchestrators.
+//!
+//! With [`ProveOpts::checkpoint`](crate::pipeline::ProveOpts) set, [`prove_chunked`] rewrites a
+//! single JSON file after every stage: which chunks are pending, proving or done (with cycle
+//! counts and the path of each saved receipt), the composer's status, and when each stage began
+//! and ended. A job runner can poll it for progress, and [`ProveOpts::resume_from`] picks an
+//! interrupted run back up, reusing every chunk already marked done.
+//!
+//! Every write goes to a sibling `.tmp` file that is then renamed over the checkpoint, so a
+//! reader never sees a half-written document.
+//!
+//! [`prove_chunked`]: crate::pipeline::prove_chunked
+//! [`ProveOpts::resume_from`]: crate::pipeline::ProveOpts
+
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use chickenz_core::fp::{self, FpProverInput};
+use risc0_zkvm::sha::{Impl, Sha256};
+use serde::{Deserialize, Serialize};
+
+use crate::pipeline::{PipelineError, ProveOpts, Result};
+
+/// Bumped when the document layout changes; older documents are rejected on resume.
+pub const CHECKPOINT_VERSION: u32 = 1;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StageStatus {
+    Pending,
+    Proving,
+    Done,
+}
+
+/// Progress of one guest run (a chunk or the composer).
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Stage {
+    pub status: StageStatus,
+    /// Total cycles, once done.
+    pub cycles: Option<u64>,
+    /// Where the receipt was saved, once done.
+    pub receipt: Option<PathBuf>,
+    /// Unix seconds.
+    pub started_at: Option<u64>,
+    pub finished_at: Option<u64>,
+}
+
+impl Stage {
+    fn pending() -> Self {
+        Stage {
+            status: StageStatus::Pending,
+            cycles: None,
+            receipt: None,
+            started_at: None,
+            finished_at: None,
+        }
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.status == StageStatus::Done
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChunkStage {
+    pub index: usize,
+    pub start_tick: usize,
+    pub ticks: usize,
+    #[serde(flatten)]
+    pub stage: Stage,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Checkpoint {
+    pub version: u32,
+    /// Hex [`input_hash`] of the input being proved.
+    pub input_hash: String,
+    pub chunk_size: usize,
+    pub total_ticks: usize,
+    pub chunks: Vec<ChunkStage>,
+    pub composer: Stage,
+    /// Unix seconds.
+    pub created_at: u64,
+    pub updated_at: u64,
+}
+
+/// SHA-256 over session ID, orientation, the guest's raw input bytes (seed + transcript), the
+/// map hash of a custom or generated map and any rule overrides: everything that decides what
+/// the chunk receipts prove.
+pub fn input_hash(input: &FpProverInput) -> [u8; 32] {
+    let mut bytes = Vec::new();
+    bytes.extend_from_slice(&input.session_id.to_le_bytes());
+    bytes.extend_from_slice(&(input.mirrored as u32).to_le_bytes());
+    bytes.extend_from_slice(&fp::encode_raw_input(input));
+    if input.map.is_some() || input.gen_seed.is_some() {
+        bytes.extend_from_slice(&fp::map_hash(&input.base_map()));
+    }
+    if let Some(rules) = input.rules {
+        for word in rules.to_words() {
+            bytes.extend_from_slice(&word.to_le_bytes());
+        }
+    }
+    let mut out = [0u8; 32];
+    out.copy_from_slice(Impl::hash_bytes(&bytes).as_bytes());
+    out
+}
+
+pub(crate) fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs())
+}
+
+impl Checkpoint {
+    /// A fresh document with every stage pending.
+    pub fn new(input: &FpProverInput, chunk_size: usize) -> Self {
+        let total_ticks = input.transcript.len();
+        let num_chunks = total_ticks.div_ceil(chunk_size);
+        let chunks = (0..num_chunks)
+            .map(|index| {
+                let start_tick = index * chunk_size;
+                ChunkStage {
+                    index,
+                    start_tick,
+                    ticks: chunk_size.min(total_ticks - start_tick),
+                    stage: Stage::pending(),
+                }
+            })
+            .collect();
+        let t = now();
+        Checkpoint {
+            version: CHECKPOINT_VERSION,
+            input_hash: hex::encode(input_hash(input)),
+            chunk_size,
+            total_ticks,
+            chunks,
+            composer: Stage::pending(),
+            created_at: t,
+            updated_at: t,
+        }
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = std::fs::read_to_string(path)?;
+        serde_json::from_str(&text)
+            .map_err(|e| PipelineError::Checkpoint(format!("{}: {e}", path.display())))
+    }
+
+    /// Reject a checkpoint written for a different input, chunk size or document version.
+    pub fn validate(&self, input: &FpProverInput, chunk_size: usize) -> Result<()> {
+        if self.version != CHECKPOINT_VERSION {
+            return Err(PipelineError::Checkpoint(format!(
+                "checkpoint version {} (expected {CHECKPOINT_VERSION})",
+                self.version
+            )));
+        }
+        let expected = hex::encode(input_hash(input));
+        if self.input_hash != expected {
+            return Err(PipelineError::Checkpoint(format!(
+                "input hash {} does not match this input ({expected})",
+                self.input_hash
+            )));
+        }
+        if self.chunk_size != chunk_size {
+            return Err(PipelineError::Checkpoint(format!(
+                "checkpoint chunk size {} differs from requested {chunk_size}",
+                self.chunk_size
+            )));
+        }
+        Ok(())
+    }
+
+    /// Write to `path.tmp`, then rename over `path`.
+    pub fn write(&mut self, path: &Path) -> Result<()> {
+        self.updated_at = now();
+        let tmp = tmp_path(path);
+        std::fs::write(&tmp, serde_json::to_string_pretty(self).unwrap())?;
+        std::fs::rename(&tmp, path)?;
+        Ok(())
+    }
+
+    /// Number of chunks marked done.
+    pub fn chunks_done(&self) -> usize {
+        self.chunks.iter().filter(|c| c.stage.is_done()).count()
+    }
+}
+
+pub(crate) fn tmp_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".tmp");
+    path.with_file_name(name)
+}
+
+/// Receipt file for a stage, next to the checkpoint: `<checkpoint>.chunk-3.receipt`.
+fn receipt_path(checkpoint: &Path, stage: &str) -> PathBuf {
+    let mut name = checkpoint.file_name().unwrap_or_default().to_os_string();
+    name.push(format!(".{stage}.receipt"));
+    checkpoint.with_file_name(name)
+}
+
+fn save_receipt(path: &Path, receipt: &risc0_zkvm::Receipt) -> Result<()> {
+    let tmp = tmp_path(path);
+    std::fs::write(&tmp, serde_json::to_vec(receipt).unwrap())?;
+    std::fs::rename(&tmp, path)?;
+    Ok(())
+}
+
+fn load_receipt(path: &Path) -> Result<risc0_zkvm::Receipt> {
+    let bytes = std::fs::read(path)?;
+    serde_json::from_slice(&bytes)
+        .map_err(|e| PipelineError::Checkpoint(format!("{}: {e}", path.display())))
+}
+
+/// The checkpoint a chunked run keeps up to date. Without a path it still tracks progress in
+/// memory but writes nothing and saves no receipts.
+pub(crate) struct Progress {
+    path: Option<PathBuf>,
+    doc: Checkpoint,
+    resumed: bool,
+}
+
+impl Progress {
+    pub(crate) fn open(input: &FpProverInput, opts: &ProveOpts) -> Result<Self> {
+        let (doc, resumed) = match &opts.resume_from {
+            Some(p) => {
+                let doc = Checkpoint::load(p)?;
+                doc.validate(input, opts.chunk_size)?;
+                (doc, true)
+            }
+            None => (Checkpoint::new(input, opts.chunk_size), false),
+        };
+        let path = opts.checkpoint.clone().or_else(|| opts.resume_from.clone());
+        let mut progress = Progress { path, doc, resumed };
+        progress.save()?;
+        Ok(progress)
+    }
+
+    pub(crate) fn doc(&self) -> &Checkpoint {
+        &self.doc
+    }
+
+    pub(crate) fn resumed(&self) -> bool {
+        self.resumed
+    }
+
+    fn save(&mut self) -> Result<()> {
+        match &self.path {
+            Some(p) => self.doc.write(p),
+            None => Ok(()),
+        }
+    }
+
+    fn stage_mut(&mut self, chunk: Option<usize>) -> &mut Stage {
+        match chunk {
+            Some(i) => &mut self.doc.chunks[i].stage,
+            None => &mut self.doc.composer,
+        }
+    }
+
+    /// Receipt and cycle count of a stage the checkpoint marks done, if the receipt still loads.
+    /// `None` selects the composer.
+    pub(crate) fn done_receipt(&self, chunk: Option<usize>) -> Option<(risc0_zkvm::Receipt, u64)> {
+        let stage = match chunk {
+            Some(i) => &self.doc.chunks[i].stage,
+            None => &self.doc.composer,
+        };
+        if !stage.is_done() {
+            return None;
+        }
+        let receipt = load_receipt(stage.receipt.as_deref()?).ok()?;
+        Some((receipt, stage.cycles.unwrap_or(0)))
+    }
+
+    pub(crate) fn start(&mut self, chunk: Option<usize>) -> Result<()> {
+        *self.stage_mut(chunk) = Stage {
+            status: StageStatus::Proving,
+            started_at: Some(now()),
+            ..Stage::pending()
+        };
+        self.save()
+    }
+
+    /// Mark a stage done, saving its receipt next to the checkpoint first.
+    pub(crate) fn finish(
+        &mut self,
+        chunk: Option<usize>,
+        cycles: u64,
+        receipt: &risc0_zkvm::Receipt,
+    ) -> Result<()> {
+        let receipt_file = match &self.path {
+            Some(p) => {
+                let name = chunk.map_or_else(|| "composer".to_string(), |i| format!("chunk-{i}"));
+                let file = receipt_path(p, &name);
+                save_receipt(&file, receipt)?;
+                Some(file)
+            }
+            None => None,
+        };
+        let stage = self.stage_mut(chunk);
+        stage.status = StageStatus::Done;
+        stage.cycles = Some(cycles);
+        stage.receipt = receipt_file;
+        stage.finished_at = Some(now());
+        self.save()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chickenz_core::fp::NULL_INPUT;
+
+    fn input(seed: u32) -> FpProverInput {
+        FpProverInput {
+            session_id: 7,
+            seed,
+            mirrored: false,
+            transcript: vec![[NULL_INPUT; 2]; 100],
+            map: None,
+            gen_seed: None,
+            rules: None,
+        }
+    }
+
+    #[test]
+    fn new_checkpoint_covers_every_tick_and_round_trips() {
+        let mut doc = Checkpoint::new(&input(42), 40);
+        let spans: Vec<_> = doc.chunks.iter().map(|c| (c.start_tick, c.ticks)).collect();
+        assert_eq!(spans, [(0, 40), (40, 40), (80, 20)]);
+        assert_eq!(doc.chunks_done(), 0);
+        doc.validate(&input(42), 40).unwrap();
+        assert!(doc.validate(&input(43), 40).is_err());
+        let mirrored = FpProverInput { mirrored: true, ..input(42) };
+        assert_ne!(input_hash(&input(42)), input_hash(&mirrored));
+        let rules = fp::MatchRules { initial_lives: 99, ..fp::MatchRules::default() };
+        let warmup = FpProverInput { rules: Some(rules), ..input(42) };
+        assert_ne!(input_hash(&input(42)), input_hash(&warmup));
+        let mut map = fp::arena_map();
+        map.platforms[0].hazard_damage = 5;
+        let custom = FpProverInput { map: Some(map), ..input(42) };
+        assert_ne!(input_hash(&input(42)), input_hash(&custom));
+        let generated = FpProverInput { gen_seed: Some(5), ..input(42) };
+        assert_ne!(input_hash(&input(42)), input_hash(&generated));
+        assert_ne!(input_hash(&generated), input_hash(&FpProverInput { gen_seed: Some(6), ..input(42) }));
+
+        let path = std::env::temp_dir()
+            .join(format!("chickenz-checkpoint-{}.json", std::process::id()));
+        doc.write(&path).unwrap();
+        assert!(!tmp_path(&path).exists());
+        let json = std::fs::read_to_string(&path).unwrap();
+        assert!(json.contains("\"status\": \"pending\""));
+        assert_eq!(Checkpoint::load(&path).unwrap(), doc);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,425 @@
+//! Command line of the `chickenz-host` binary.
+//!
+//! Parsed with clap, so a misspelled flag (`--chunkd`) is an error rather than a silently
+//! different run, and flags that only mean something in one mode (`--jobs` without
+//! `--chunked`) are rejected. [`ProveArgs::opts`] and [`WatchArgs::opts`] turn the flags into
+//! pipeline options; `main` only loads inputs and prints results. `--serve ADDR`, in place of a
+//! subcommand, runs the HTTP sidecar (the `server` feature).
+
+use std::net::SocketAddr;
+use std::path::PathBuf;
+
+use clap::{Args, Parser, Subcommand};
+
+use crate::demo;
+use crate::pipeline::{self, ProveOpts, MAX_CHUNK_SIZE, MAX_JOBS};
+#[cfg(feature = "server")]
+use crate::server::ServeOpts;
+use crate::watch::WatchOpts;
+
+#[derive(Debug, Parser)]
+#[command(
+    name = "chickenz-host",
+    about = "Prove Chickenz matches for settlement, and check settled ones",
+    arg_required_else_help = true,
+    args_conflicts_with_subcommands = true,
+    subcommand_negates_reqs = true
+)]
+pub struct Cli {
+    /// Answer prove and verify requests over HTTP on this address (needs the `server` feature).
+    #[arg(long, value_name = "ADDR", required = true)]
+    pub serve: Option<SocketAddr>,
+    #[command(flatten)]
+    pub server: ServeArgs,
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Debug, Args)]
+pub struct ServeArgs {
+    /// Jobs proved at once, each on its own worker [default: 1].
+    #[arg(long, value_name = "N", value_parser = parse_jobs, requires = "serve")]
+    pub jobs: Option<usize>,
+    /// Jobs that can wait for a worker before requests are turned away [default: 16].
+    #[arg(long, value_name = "N", value_parser = parse_positive, requires = "serve")]
+    pub queue: Option<usize>,
+    /// Prove jobs in chunks, reporting chunk progress.
+    #[arg(long, requires = "serve")]
+    pub chunked: bool,
+    /// Local STARK proofs, not wrapped in Groth16.
+    #[arg(long, requires = "serve")]
+    pub local: bool,
+    /// Where chunked jobs' checkpoints are written [default: serve-jobs].
+    #[arg(long, value_name = "DIR", requires = "chunked")]
+    pub work_dir: Option<PathBuf>,
+}
+
+#[cfg(feature = "server")]
+impl ServeArgs {
+    pub fn opts(&self, addr: SocketAddr) -> ServeOpts {
+        let mut opts = ServeOpts::new(addr);
+        opts.jobs = self.jobs.unwrap_or(1);
+        opts.queue = self.queue.unwrap_or(crate::server::DEFAULT_QUEUE);
+        opts.chunked = self.chunked;
+        if let Some(dir) = &self.work_dir {
+            opts.work_dir = dir.clone();
+        }
+        opts.prove.groth16 = !self.local;
+        opts
+    }
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Prove a transcript and write its settlement artifacts.
+    Prove(ProveArgs),
+    /// Replay a transcript natively and compare every journal field (exit 1 on mismatch).
+    Audit(AuditArgs),
+    /// Pretty-print the shareable summary string of a proved match.
+    DecodeSummary(DecodeSummaryArgs),
+    /// Write this build's determinism vectors, or check a committed file against them.
+    DeterminismVectors(VectorsArgs),
+    /// Write the attract-mode demo's transcript.
+    DemoTranscript(DemoArgs),
+    /// Prove every transcript dropped into a directory, until killed.
+    Watch(WatchArgs),
+    /// Re-attach to the Boundless request an interrupted `prove --boundless` recorded.
+    BoundlessStatus(BoundlessStatusArgs),
+    /// Run the guest over a transcript in the executor and print its cycle counts, without proving.
+    Cycles(CyclesArgs),
+}
+
+#[derive(Debug, Args)]
+pub struct ProveArgs {
+    /// Prove in chunks (chunk guests + match composer).
+    #[arg(long)]
+    pub chunked: bool,
+    /// Ticks per chunk [default: 360].
+    #[arg(long, value_name = "N", value_parser = parse_chunk_size, requires = "chunked")]
+    pub chunk_size: Option<usize>,
+    /// Local STARK proof, not wrapped in Groth16 (can't settle on-chain).
+    #[arg(long, conflicts_with = "groth16")]
+    pub local: bool,
+    /// Wrap the proof in Groth16 for on-chain settlement (the default).
+    #[arg(long)]
+    pub groth16: bool,
+    /// Prove on the Boundless market (needs the `boundless` feature).
+    #[arg(long, conflicts_with_all = ["chunked", "local"])]
+    pub boundless: bool,
+    /// Where to write the proof artifacts.
+    #[arg(long, value_name = "PATH", default_value = pipeline::DEFAULT_ARTIFACTS_PATH)]
+    pub out: PathBuf,
+    /// Rewrite chunk progress to this file after every stage.
+    #[arg(long, value_name = "FILE", requires = "chunked")]
+    pub checkpoint: Option<PathBuf>,
+    /// Continue from this checkpoint, reusing its finished chunks.
+    #[arg(long, value_name = "FILE", requires = "chunked")]
+    pub resume_from: Option<PathBuf>,
+    /// Chunks proved at once [default: 1].
+    #[arg(long, value_name = "N", value_parser = parse_jobs, requires = "chunked")]
+    pub jobs: Option<usize>,
+    /// Chunk receipt cache [default: chunk-cache].
+    #[arg(long, value_name = "DIR", requires = "chunked", conflicts_with = "no_cache")]
+    pub cache_dir: Option<PathBuf>,
+    /// Prove every chunk, without reading or filling the cache.
+    #[arg(long, requires = "chunked")]
+    pub no_cache: bool,
+    /// Where the open Boundless request is recorded [default: boundless-request.json].
+    #[arg(long, value_name = "FILE", requires = "boundless")]
+    pub boundless_state: Option<PathBuf>,
+    /// Prove the contract's journal test vectors instead of a transcript, and write them here
+    /// (`RISC0_DEV_MODE=1` will do: only the journals are kept).
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["transcript", "boundless"])]
+    pub emit_test_vector: Option<PathBuf>,
+    /// Transcript JSON or transcript file; read from stdin when omitted.
+    pub transcript: Option<PathBuf>,
+}
+
+impl ProveArgs {
+    pub fn opts(&self) -> ProveOpts {
+        let cache_dir = match &self.cache_dir {
+            Some(dir) => Some(dir.clone()),
+            None if self.no_cache => None,
+            None => Some(PathBuf::from(pipeline::DEFAULT_CACHE_DIR)),
+        };
+        let mut opts = ProveOpts {
+            groth16: !self.local,
+            chunk_size: self.chunk_size.unwrap_or(pipeline::DEFAULT_CHUNK_SIZE),
+            artifacts_path: Some(self.out.clone()),
+            checkpoint: self.checkpoint.clone(),
+            resume_from: self.resume_from.clone(),
+            jobs: self.jobs.unwrap_or(1),
+            cache_dir,
+            ..ProveOpts::default()
+        };
+        if let Some(path) = &self.boundless_state {
+            opts.boundless_state = Some(path.clone());
+        }
+        opts
+    }
+}
+
+#[derive(Debug, Args)]
+pub struct AuditArgs {
+    /// The journal: hex, raw journal bytes, or proof_artifacts.json.
+    #[arg(long, value_name = "HEX_OR_FILE")]
+    pub journal: String,
+    #[arg(long, value_name = "FILE")]
+    pub transcript: PathBuf,
+    /// The revealed seed, when the archived transcript carries a placeholder.
+    #[arg(long)]
+    pub seed: Option<u32>,
+}
+
+#[derive(Debug, Args)]
+pub struct DecodeSummaryArgs {
+    /// The `summary` of proof_artifacts.json, or as `prove` printed it.
+    pub blob: String,
+}
+
+#[derive(Debug, Args)]
+pub struct VectorsArgs {
+    /// Write the vectors here instead of stdout.
+    #[arg(long, value_name = "FILE", conflicts_with = "check")]
+    pub out: Option<String>,
+    /// Check this build against a committed vectors file (exit 1 on divergence).
+    #[arg(long, value_name = "FILE")]
+    pub check: Option<String>,
+}
+
+#[derive(Debug, Args)]
+pub struct DemoArgs {
+    #[arg(long, value_name = "FILE", default_value = demo::ASSET)]
+    pub out: PathBuf,
+}
+
+#[derive(Debug, Args)]
+pub struct WatchArgs {
+    pub dir: PathBuf,
+    /// Where artifacts go [default: next to each input].
+    #[arg(long, value_name = "DIR")]
+    pub out: Option<PathBuf>,
+    #[arg(long)]
+    pub chunked: bool,
+    /// Inputs proved at once.
+    #[arg(long, value_name = "N", default_value_t = 1, value_parser = parse_positive)]
+    pub jobs: usize,
+    /// Local STARK proofs, not wrapped in Groth16.
+    #[arg(long)]
+    pub local: bool,
+}
+
+impl WatchArgs {
+    pub fn opts(&self) -> WatchOpts {
+        let mut opts = WatchOpts::new(&self.dir);
+        opts.out = self.out.clone();
+        opts.chunked = self.chunked;
+        opts.jobs = self.jobs;
+        opts.prove.groth16 = !self.local;
+        opts
+    }
+}
+
+#[derive(Debug, Args)]
+pub struct BoundlessStatusArgs {
+    /// The request record (`--boundless-state` of the interrupted run).
+    pub file: PathBuf,
+}
+
+#[derive(Debug, Args)]
+pub struct CyclesArgs {
+    /// Execute the chunk guest over each chunk instead of the monolithic guest.
+    #[arg(long)]
+    pub chunked: bool,
+    /// Ticks per chunk [default: 360].
+    #[arg(long, value_name = "N", value_parser = parse_chunk_size, requires = "chunked")]
+    pub chunk_size: Option<usize>,
+    /// Transcript JSON or transcript file; read from stdin when omitted.
+    pub transcript: Option<PathBuf>,
+}
+
+fn parse_in(s: &str, max: usize) -> Result<usize, String> {
+    match s.parse() {
+        Ok(n) if (1..=max).contains(&n) => Ok(n),
+        _ => Err(format!("must be an integer in 1..={max}")),
+    }
+}
+
+fn parse_chunk_size(s: &str) -> Result<usize, String> {
+    parse_in(s, MAX_CHUNK_SIZE)
+}
+
+fn parse_jobs(s: &str) -> Result<usize, String> {
+    parse_in(s, MAX_JOBS)
+}
+
+fn parse_positive(s: &str) -> Result<usize, String> {
+    parse_in(s, usize::MAX)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pipeline::{Logger, DEFAULT_CHUNK_SIZE};
+    use chickenz_core::fp::{FpProverInput, NULL_INPUT};
+    use clap::error::ErrorKind;
+    use clap::CommandFactory;
+    use std::sync::{Arc, Mutex};
+
+    fn prove(args: &[&str]) -> Result<ProveArgs, clap::Error> {
+        let argv = ["chickenz-host", "prove"].iter().chain(args);
+        match Cli::try_parse_from(argv)?.command {
+            Some(Command::Prove(args)) => Ok(args),
+            other => panic!("parsed as {other:?}"),
+        }
+    }
+
+    fn rejects(args: &[&str]) -> ErrorKind {
+        prove(args).err().unwrap_or_else(|| panic!("{args:?} parsed")).kind()
+    }
+
+    #[test]
+    fn cli_definition_is_consistent() {
+        Cli::command().debug_assert();
+    }
+
+    #[test]
+    fn prove_defaults_match_the_pipeline() {
+        let args = prove(&["match.json"]).unwrap();
+        assert_eq!(args.transcript, Some(PathBuf::from("match.json")));
+        let opts = args.opts();
+        let defaults = ProveOpts::default();
+        assert!(opts.groth16);
+        assert_eq!(opts.chunk_size, DEFAULT_CHUNK_SIZE);
+        assert_eq!(opts.artifacts_path, defaults.artifacts_path);
+        assert_eq!(opts.cache_dir, defaults.cache_dir);
+        assert_eq!(opts.boundless_state, defaults.boundless_state);
+        assert_eq!(opts.jobs, 1);
+
+        // No path: the transcript comes from stdin
+        assert_eq!(prove(&["--local"]).unwrap().transcript, None);
+    }
+
+    #[test]
+    fn prove_flags_reach_the_options() {
+        let args = prove(&[
+            "--chunked", "--chunk-size", "120", "--local", "--out", "out/a.json", "--jobs", "4", "--no-cache",
+            "--checkpoint", "job.json", "match.json",
+        ])
+        .unwrap();
+        assert!(args.chunked);
+        let opts = args.opts();
+        assert!(!opts.groth16);
+        assert_eq!(opts.chunk_size, 120);
+        assert_eq!(opts.artifacts_path, Some(PathBuf::from("out/a.json")));
+        assert_eq!((opts.jobs, opts.cache_dir), (4, None));
+        assert_eq!(opts.checkpoint, Some(PathBuf::from("job.json")));
+
+        let boundless = prove(&["--boundless", "--boundless-state", "req.json"]).unwrap().opts();
+        assert_eq!(boundless.boundless_state, Some(PathBuf::from("req.json")));
+
+        let vectors = prove(&["--emit-test-vector", "v.json", "--chunked", "--local"]).unwrap();
+        assert_eq!((vectors.emit_test_vector, vectors.transcript), (Some(PathBuf::from("v.json")), None));
+    }
+
+    #[test]
+    fn prove_rejects_unknown_and_misplaced_flags() {
+        assert_eq!(rejects(&["--chunkd", "match.json"]), ErrorKind::UnknownArgument);
+        assert_eq!(rejects(&["--chunk-size", "120"]), ErrorKind::MissingRequiredArgument);
+        assert_eq!(rejects(&["--jobs", "2"]), ErrorKind::MissingRequiredArgument);
+        assert_eq!(rejects(&["--boundless-state", "req.json"]), ErrorKind::MissingRequiredArgument);
+        assert_eq!(rejects(&["--local", "--groth16"]), ErrorKind::ArgumentConflict);
+        assert_eq!(rejects(&["--boundless", "--chunked"]), ErrorKind::ArgumentConflict);
+        assert_eq!(rejects(&["--chunked", "--cache-dir", "c", "--no-cache"]), ErrorKind::ArgumentConflict);
+        assert_eq!(rejects(&["a.json", "b.json"]), ErrorKind::UnknownArgument);
+        assert_eq!(rejects(&["--emit-test-vector", "v.json", "match.json"]), ErrorKind::ArgumentConflict);
+        assert_eq!(rejects(&["--emit-test-vector", "v.json", "--boundless"]), ErrorKind::ArgumentConflict);
+    }
+
+    #[test]
+    fn chunk_size_is_bounded_by_the_chunk_guest_buffer() {
+        assert_eq!(MAX_CHUNK_SIZE, chickenz_core::fp::MAX_CHUNK_INPUT_WORDS * 4 / 6);
+        let max = MAX_CHUNK_SIZE.to_string();
+        assert_eq!(prove(&["--chunked", "--chunk-size", &max]).unwrap().opts().chunk_size, MAX_CHUNK_SIZE);
+        let over = (MAX_CHUNK_SIZE + 1).to_string();
+        for size in ["0", over.as_str(), "many"] {
+            assert_eq!(rejects(&["--chunked", "--chunk-size", size]), ErrorKind::ValueValidation, "{size}");
+        }
+        assert_eq!(rejects(&["--chunked", "--jobs", "0"]), ErrorKind::ValueValidation);
+    }
+
+    #[test]
+    fn other_subcommands_parse() {
+        let cli = Cli::try_parse_from(["chickenz-host", "watch", "drop", "--chunked", "--jobs", "2", "--local"]).unwrap();
+        let Some(Command::Watch(args)) = cli.command else { panic!("not watch") };
+        let opts = args.opts();
+        assert_eq!((opts.dir, opts.chunked, opts.jobs, opts.prove.groth16), (PathBuf::from("drop"), true, 2, false));
+
+        let cli = Cli::try_parse_from(["chickenz-host", "demo-transcript"]).unwrap();
+        let Some(Command::DemoTranscript(args)) = cli.command else { panic!("not demo-transcript") };
+        assert_eq!(args.out, PathBuf::from(demo::ASSET));
+
+        let cli = Cli::try_parse_from(["chickenz-host", "audit", "--journal", "ab", "--transcript", "t.json", "--seed", "5"]);
+        assert!(matches!(cli.unwrap().command, Some(Command::Audit(AuditArgs { seed: Some(5), .. }))));
+        let cli = Cli::try_parse_from(["chickenz-host", "cycles", "match.json"]).unwrap();
+        assert!(matches!(cli.command, Some(Command::Cycles(CyclesArgs { chunked: false, transcript: Some(_), .. }))));
+        let cli = Cli::try_parse_from(["chickenz-host", "cycles", "--chunked", "--chunk-size", "120"]).unwrap();
+        assert!(matches!(cli.command, Some(Command::Cycles(CyclesArgs { chunk_size: Some(120), transcript: None, .. }))));
+        assert_eq!(
+            Cli::try_parse_from(["chickenz-host", "cycles", "--chunk-size", "120"]).unwrap_err().kind(),
+            ErrorKind::MissingRequiredArgument
+        );
+        let cli = Cli::try_parse_from(["chickenz-host", "decode-summary", "AQIB0gQ"]).unwrap();
+        assert!(matches!(cli.command, Some(Command::DecodeSummary(DecodeSummaryArgs { blob })) if blob == "AQIB0gQ"));
+        let kind = |argv: &[&str]| Cli::try_parse_from(argv).unwrap_err().kind();
+        assert_eq!(kind(&["chickenz-host", "audit", "--journal", "ab"]), ErrorKind::MissingRequiredArgument);
+        assert_eq!(kind(&["chickenz-host", "decode-summary"]), ErrorKind::MissingRequiredArgument);
+        assert_eq!(kind(&["chickenz-host", "determinism-vectors", "--out", "a", "--check", "b"]), ErrorKind::ArgumentConflict);
+        assert_eq!(kind(&["chickenz-host", "match.json"]), ErrorKind::InvalidSubcommand);
+    }
+
+    #[test]
+    fn serve_runs_in_place_of_a_subcommand() {
+        let cli = Cli::try_parse_from(["chickenz-host", "--serve", "0.0.0.0:8080", "--jobs", "2", "--chunked"]).unwrap();
+        assert!(cli.command.is_none());
+        assert_eq!(cli.serve, Some("0.0.0.0:8080".parse().unwrap()));
+        assert_eq!((cli.server.jobs, cli.server.queue, cli.server.chunked), (Some(2), None, true));
+
+        let kind = |argv: &[&str]| Cli::try_parse_from(argv).unwrap_err().kind();
+        assert_eq!(kind(&["chickenz-host", "--jobs", "2"]), ErrorKind::MissingRequiredArgument);
+        assert_eq!(kind(&["chickenz-host", "--serve", "localhost"]), ErrorKind::ValueValidation);
+        assert_eq!(kind(&["chickenz-host", "--serve", "127.0.0.1:80", "--work-dir", "w"]), ErrorKind::MissingRequiredArgument);
+        assert!(Cli::try_parse_from(["chickenz-host", "--serve", "127.0.0.1:80", "prove", "match.json"]).is_err());
+    }
+
+    /// Chunk proof log lines of a dev-mode chunked run of an idle arena match (over in its
+    /// fifth default chunk), proved with the options `flags` parse to.
+    fn chunks_proved(flags: &[&str]) -> usize {
+        let lines = Arc::new(Mutex::new(Vec::<String>::new()));
+        let sink = lines.clone();
+        std::env::set_var("RISC0_DEV_MODE", "1");
+        let mut opts = prove(flags).unwrap().opts();
+        opts.artifacts_path = None;
+        opts.log = Logger::new(move |m| sink.lock().unwrap().push(m.to_string()));
+        let input = FpProverInput {
+            session_id: 7,
+            seed: 42,
+            mirrored: false,
+            transcript: vec![[NULL_INPUT; 2]; 2400],
+            map: None,
+            gen_seed: None,
+            rules: None,
+        };
+        pipeline::prove_chunked(&input, &opts).unwrap();
+        let lines = lines.lock().unwrap();
+        lines.iter().filter(|l| l.starts_with("  Chunk ")).count()
+    }
+
+    #[test]
+    fn smaller_chunk_size_proves_more_chunks() {
+        let default = chunks_proved(&["--chunked", "--local", "--no-cache"]);
+        let small = chunks_proved(&["--chunked", "--chunk-size", "120", "--local", "--no-cache"]);
+        assert_eq!(default, 5);
+        assert!(small > default, "{small} chunks of 120 ticks, {default} of {DEFAULT_CHUNK_SIZE}");
+    }
+}
@@ -0,0 +1,118 @@
+//! Attract-mode demo: a short scripted match the landing page plays without networking.
+//!
+//! [`SCRIPT`] is the choreography, in beats that both players hold for some ticks, and [`DEMO`]
+//! plays it as a [`Scenario`]. `chickenz-host demo-transcript` writes its run-length encoded
+//! transcript ([`fp::encode_input_runs`]) to [`ASSET`], which the wasm crate embeds for
+//! `WasmDemo`; the wasm build script replays that file and fails if the match no longer ends
+//! the way it was recorded, so a sim change can't ship a desynced demo.
+
+use chickenz_core::fp::{self, button, FpInput, FpMatchConfig};
+
+use crate::vectors::Scenario;
+
+/// Where the wasm crate embeds the demo transcript from, relative to the prover workspace.
+pub const ASSET: &str = "wasm/assets/demo-transcript.bin";
+
+/// Both players hold `p0` and `p1` for `ticks` ticks.
+pub struct Beat {
+    pub ticks: u32,
+    pub p0: FpInput,
+    pub p1: FpInput,
+}
+
+const fn beat(ticks: u32, p0: FpInput, p1: FpInput) -> Beat {
+    Beat { ticks, p0, p1 }
+}
+
+const fn hold(buttons: u8, aim_x: i8, aim_y: i8) -> FpInput {
+    FpInput { buttons, aim_x, aim_y }
+}
+
+const IDLE_R: FpInput = hold(0, 1, 0);
+const IDLE_L: FpInput = hold(0, -1, 0);
+const SHAKE_L: FpInput = hold(button::LEFT, 1, 0);
+const SHAKE_R: FpInput = hold(button::RIGHT, 1, 0);
+
+/// P0 takes the pistol and P1 the shotgun off the low platforms; they trade shots across the
+/// floor and P0 grabs the rocket as they pass. P1 turns and lands on P0's head, P0 shakes it
+/// off and rockets it on the way down, then celebrates.
+pub const SCRIPT: &[Beat] = &[
+    beat(30, IDLE_R, IDLE_L),
+    beat(12, hold(button::RIGHT | button::JUMP, 1, 0), hold(button::LEFT | button::JUMP, -1, 0)),
+    beat(18, IDLE_R, hold(button::LEFT, -1, 0)),
+    beat(20, hold(button::SHOOT, 1, 0), IDLE_L),
+    beat(30, hold(button::RIGHT, 1, 0), hold(button::LEFT, -1, 0)),
+    beat(50, hold(button::RIGHT | button::SHOOT, 1, 0), hold(button::LEFT | button::SHOOT, -1, 0)),
+    beat(20, hold(button::RIGHT, 1, 0), hold(button::RIGHT, 1, 0)),
+    beat(40, IDLE_R, hold(button::RIGHT | button::JUMP, 1, 0)),
+    beat(1, SHAKE_L, IDLE_R),
+    beat(1, SHAKE_R, IDLE_R),
+    beat(1, SHAKE_L, IDLE_R),
+    beat(1, SHAKE_R, IDLE_R),
+    beat(1, SHAKE_L, IDLE_R),
+    beat(1, SHAKE_R, IDLE_R),
+    beat(1, SHAKE_L, IDLE_R),
+    beat(1, SHAKE_R, IDLE_R),
+    beat(1, SHAKE_L, IDLE_R),
+    beat(1, SHAKE_R, IDLE_R),
+    beat(40, IDLE_R, IDLE_R),
+    beat(10, hold(button::SHOOT, 1, 0), IDLE_L),
+    beat(40, IDLE_R, IDLE_L),
+    beat(12, hold(button::JUMP, 1, 0), IDLE_L),
+    beat(12, IDLE_R, IDLE_L),
+    beat(12, hold(button::JUMP | button::LEFT, -1, 0), IDLE_L),
+    beat(24, IDLE_L, IDLE_L),
+];
+
+/// Ticks in [`SCRIPT`].
+pub const fn script_ticks() -> u32 {
+    let mut ticks = 0;
+    let mut i = 0;
+    while i < SCRIPT.len() {
+        ticks += SCRIPT[i].ticks;
+        i += 1;
+    }
+    ticks
+}
+
+/// Inputs of `tick` in [`SCRIPT`]; past its end both players idle.
+fn script_inputs(tick: u32) -> [FpInput; 2] {
+    let mut start = 0;
+    for b in SCRIPT {
+        if tick < start + b.ticks {
+            return [b.p0, b.p1];
+        }
+        start += b.ticks;
+    }
+    [fp::NULL_INPUT; 2]
+}
+
+/// The demo as a scenario, on the arena map with default rules (what `WasmDemo` plays).
+pub const DEMO: Scenario = Scenario {
+    name: "demo",
+    seed: 7,
+    ticks: script_ticks(),
+    config: FpMatchConfig::default,
+    inputs: script_inputs,
+};
+
+/// `scenario`'s transcript as `fp::encode_input_runs` bytes: what [`ASSET`] holds for [`DEMO`].
+pub fn transcript(scenario: &Scenario) -> Vec<u8> {
+    let inputs: Vec<[FpInput; 2]> = (0..scenario.ticks).map(scenario.inputs).collect();
+    fp::encode_input_runs(scenario.seed, &inputs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn committed_asset_is_the_current_script() {
+        let committed = include_bytes!("../../wasm/assets/demo-transcript.bin");
+        assert_eq!(
+            transcript(&DEMO),
+            committed,
+            "demo script changed: rerun `chickenz-host demo-transcript` and commit {ASSET}"
+        );
+    }
+}
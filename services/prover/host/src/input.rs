@@ -0,0 +1,455 @@
+//! `ProverInput` JSON, checked before serde sees it.
+//!
+//! Transcripts are often hand-edited for experiments, and serde's errors point at a column tens
+//! of thousands of bytes into one line. [`parse_prover_input`] walks the parsed JSON first and
+//! reports each problem by path (`transcript[812][1].aim_x`), naming the tick and player for a
+//! bad input, and also rejects what serde would accept but the prover can't replay faithfully:
+//! an aim the `i8` quantization would saturate, a player count other than two, a map with more
+//! slots than the fixed-point sim has.
+//!
+//! Any `buttons` byte passes. Besides the bits `fp::button` plays, the emote selectors of
+//! `fp::button::EMOTE_MASK` (64, 128) are defined but inert: hashed with the transcript, they
+//! change nothing in the sim; the client's taunt is slot 1. Bit 16, once that taunt, is now
+//! `fp::button::DASH`.
+//!
+//! [`parse_input`] also takes the binary transcript file (`fp::write_transcript`), told apart
+//! by its magic bytes.
+
+use std::fmt;
+
+use chickenz_core::fp::{self, FpProverInput, NUM_PLATFORMS, NUM_SPAWNS, NUM_WEAPON_SPAWNS};
+use chickenz_core::ProverInput;
+use serde_json::{Map, Value};
+
+use crate::pipeline::{self, PipelineError, Result};
+
+/// Issues listed in the error; the rest are only counted.
+pub const MAX_REPORTED_ISSUES: usize = 5;
+
+/// One problem with a prover input, at a JSON path.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InputIssue {
+    pub path: String,
+    /// Tick and player of a transcript entry.
+    pub input: Option<(usize, usize)>,
+    pub message: String,
+}
+
+impl fmt::Display for InputIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.input {
+            Some((tick, player)) => write!(f, "tick {tick}, player {player} ({}): {}", self.path, self.message),
+            None => write!(f, "{}: {}", self.path, self.message),
+        }
+    }
+}
+
+/// Parse `ProverInput` JSON, or fail with the first `MAX_REPORTED_ISSUES` issues
+/// [`validate_prover_input`] finds and a count of the rest.
+pub fn parse_prover_input(json: &str) -> Result<ProverInput> {
+    let value: Value = serde_json::from_str(json)
+        .map_err(|e| PipelineError::Input(format!("bad transcript JSON: {e}")))?;
+    let issues = validate_prover_input(&value);
+    if !issues.is_empty() {
+        return Err(PipelineError::Input(summarize(&issues)));
+    }
+    serde_json::from_value(value).map_err(|e| PipelineError::Input(format!("bad transcript JSON: {e}")))
+}
+
+/// A prover input in either format: a transcript file, or `ProverInput` JSON.
+pub fn parse_input(bytes: &[u8]) -> Result<FpProverInput> {
+    if fp::is_transcript_file(bytes) {
+        return fp::read_transcript(&mut &bytes[..]).map_err(|e| PipelineError::Input(e.to_string()));
+    }
+    let json = std::str::from_utf8(bytes)
+        .map_err(|e| PipelineError::Input(format!("transcript is not UTF-8: {e}")))?;
+    Ok(pipeline::to_fp_input(&parse_prover_input(json)?))
+}
+
+fn summarize(issues: &[InputIssue]) -> String {
+    let noun = if issues.len() == 1 { "issue" } else { "issues" };
+    let mut out = format!("{} {noun}", issues.len());
+    for issue in issues.iter().take(MAX_REPORTED_ISSUES) {
+        out.push_str(&format!("\n  {issue}"));
+    }
+    if issues.len() > MAX_REPORTED_ISSUES {
+        out.push_str(&format!("\n  ... and {} more", issues.len() - MAX_REPORTED_ISSUES));
+    }
+    out
+}
+
+/// Every problem with a parsed `ProverInput`, in document order.
+pub fn validate_prover_input(value: &Value) -> Vec<InputIssue> {
+    let mut c = Checker { issues: Vec::new(), input: None };
+    let Some(root) = c.object(value, "input") else {
+        return c.issues;
+    };
+    if let Some(v) = root.get("session_id") {
+        c.integer(v, "session_id", 0, u32::MAX as i64);
+    }
+    match root.get("config") {
+        Some(config) => c.config(config),
+        None => c.issue("config", "missing"),
+    }
+    match root.get("transcript") {
+        Some(transcript) => c.transcript(transcript),
+        None => c.issue("transcript", "missing"),
+    }
+    c.issues
+}
+
+struct Checker {
+    issues: Vec<InputIssue>,
+    /// The transcript entry being checked, if any.
+    input: Option<(usize, usize)>,
+}
+
+impl Checker {
+    fn issue(&mut self, path: &str, message: impl Into<String>) {
+        self.issues.push(InputIssue { path: path.to_string(), input: self.input, message: message.into() });
+    }
+
+    fn object<'a>(&mut self, v: &'a Value, path: &str) -> Option<&'a Map<String, Value>> {
+        let obj = v.as_object();
+        if obj.is_none() {
+            self.issue(path, format!("expected an object, got {}", kind(v)));
+        }
+        obj
+    }
+
+    fn array<'a>(&mut self, v: &'a Value, path: &str) -> Option<&'a Vec<Value>> {
+        let arr = v.as_array();
+        if arr.is_none() {
+            self.issue(path, format!("expected an array, got {}", kind(v)));
+        }
+        arr
+    }
+
+    /// `obj[key]`, reporting it missing.
+    fn field<'a>(&mut self, obj: &'a Map<String, Value>, key: &str, path: &str) -> Option<&'a Value> {
+        let v = obj.get(key);
+        if v.is_none() {
+            self.issue(&format!("{path}.{key}"), "missing");
+        }
+        v
+    }
+
+    fn number(&mut self, v: &Value, path: &str) -> Option<f64> {
+        let n = v.as_f64();
+        if n.is_none() {
+            self.issue(path, format!("expected a number, got {}", kind(v)));
+        }
+        n
+    }
+
+    /// An integer in `min..=max`. `1.0` is not one: serde rejects it for an integer field.
+    fn integer(&mut self, v: &Value, path: &str, min: i64, max: i64) -> Option<i64> {
+        let n = match v {
+            Value::Number(n) if n.is_f64() => {
+                self.issue(path, format!("expected an integer, got {n}"));
+                return None;
+            }
+            Value::Number(n) => n.as_i64(),
+            _ => {
+                self.issue(path, format!("expected an integer, got {}", kind(v)));
+                return None;
+            }
+        };
+        match n {
+            Some(n) if (min..=max).contains(&n) => Some(n),
+            _ => {
+                self.issue(path, format!("{v} is outside {min}..={max}"));
+                None
+            }
+        }
+    }
+
+    fn boolean(&mut self, v: &Value, path: &str) {
+        if !v.is_boolean() {
+            self.issue(path, format!("expected true or false, got {}", kind(v)));
+        }
+    }
+
+    fn config(&mut self, v: &Value) {
+        let Some(config) = self.object(v, "config") else {
+            return;
+        };
+        let u32_fields = ["seed", "player_count", "tick_rate", "match_duration_ticks", "sudden_death_start_tick"];
+        for key in u32_fields {
+            if let Some(v) = self.field(config, key, "config") {
+                let path = format!("config.{key}");
+                let n = self.integer(v, &path, 0, u32::MAX as i64);
+                if key == "player_count" && n.is_some_and(|n| n != 2) {
+                    self.issue(&path, format!("{v} players; the prover replays two-player matches only"));
+                }
+            }
+        }
+        if let Some(v) = self.field(config, "initial_lives", "config") {
+            self.integer(v, "config.initial_lives", i32::MIN as i64, i32::MAX as i64);
+        }
+        if let Some(v) = config.get("mirrored") {
+            self.boolean(v, "config.mirrored");
+        }
+        if let Some(v) = config.get("gen_seed").filter(|v| !v.is_null()) {
+            self.integer(v, "config.gen_seed", 0, u32::MAX as i64);
+        }
+        if let Some(map) = self.field(config, "map", "config") {
+            self.map(map);
+        }
+    }
+
+    fn map(&mut self, v: &Value) {
+        let path = "config.map";
+        let Some(map) = self.object(v, path) else {
+            return;
+        };
+        for key in ["width", "height"] {
+            if let Some(v) = self.field(map, key, path) {
+                self.number(v, &format!("{path}.{key}"));
+            }
+        }
+        if let Some(platforms) = self.slots(map, "platforms", NUM_PLATFORMS) {
+            for (i, p) in platforms.iter().enumerate() {
+                self.platform(p, &format!("{path}.platforms[{i}]"));
+            }
+        }
+        for (key, limit) in [("spawn_points", NUM_SPAWNS), ("weapon_spawn_points", NUM_WEAPON_SPAWNS)] {
+            if let Some(points) = self.slots(map, key, limit) {
+                for (i, p) in points.iter().enumerate() {
+                    self.point(p, &format!("{path}.{key}[{i}]"));
+                }
+            }
+        }
+    }
+
+    /// `map[key]`, an array of at most `limit` entries (the fixed-point sim's slots).
+    fn slots<'a>(&mut self, map: &'a Map<String, Value>, key: &str, limit: usize) -> Option<&'a Vec<Value>> {
+        let path = format!("config.map.{key}");
+        let arr = self.field(map, key, "config.map").and_then(|v| self.array(v, &path))?;
+        if arr.len() > limit {
+            self.issue(&path, format!("{} entries; the fixed-point sim has {limit} slots", arr.len()));
+        }
+        Some(arr)
+    }
+
+    fn platform(&mut self, v: &Value, path: &str) {
+        let Some(p) = self.object(v, path) else {
+            return;
+        };
+        for key in ["x", "y", "width", "height"] {
+            if let Some(v) = self.field(p, key, path) {
+                self.number(v, &format!("{path}.{key}"));
+            }
+        }
+        for key in ["one_way", "oneWay"] {
+            if let Some(v) = p.get(key) {
+                self.boolean(v, &format!("{path}.{key}"));
+            }
+        }
+        for key in ["hazard_damage", "hazardDamage"] {
+            if let Some(v) = p.get(key) {
+                self.integer(v, &format!("{path}.{key}"), i32::MIN as i64, i32::MAX as i64);
+            }
+        }
+    }
+
+    fn point(&mut self, v: &Value, path: &str) {
+        let Some(p) = self.object(v, path) else {
+            return;
+        };
+        for key in ["x", "y"] {
+            if let Some(v) = self.field(p, key, path) {
+                self.number(v, &format!("{path}.{key}"));
+            }
+        }
+    }
+
+    fn transcript(&mut self, v: &Value) {
+        let Some(ticks) = self.array(v, "transcript") else {
+            return;
+        };
+        for (t, tick) in ticks.iter().enumerate() {
+            let path = format!("transcript[{t}]");
+            let Some(players) = self.array(tick, &path) else {
+                continue;
+            };
+            if players.len() != 2 {
+                self.issue(&path, format!("{} inputs; expected one per player (2)", players.len()));
+            }
+            for (p, input) in players.iter().enumerate().take(2) {
+                self.input = Some((t, p));
+                self.player_input(input, &format!("{path}[{p}]"));
+                self.input = None;
+            }
+        }
+    }
+
+    fn player_input(&mut self, v: &Value, path: &str) {
+        let Some(input) = self.object(v, path) else {
+            return;
+        };
+        if let Some(v) = self.field(input, "buttons", path) {
+            // Every bit is defined; the emote bits are accepted but inert
+            self.integer(v, &format!("{path}.buttons"), 0, u8::MAX as i64);
+        }
+        for key in ["aim_x", "aim_y"] {
+            let Some(v) = self.field(input, key, path) else {
+                continue;
+            };
+            let path = format!("{path}.{key}");
+            let Some(aim) = self.number(v, &path) else {
+                continue;
+            };
+            // `quantize_input` truncates toward zero and saturates; a value that saturates
+            // would replay as a different aim than the one recorded
+            let q = aim.trunc();
+            if !(i8::MIN as f64..=i8::MAX as f64).contains(&q) {
+                self.issue(&path, format!("{aim} doesn't fit an i8 aim ({}..={}) after truncation", i8::MIN, i8::MAX));
+            }
+        }
+    }
+}
+
+fn kind(v: &Value) -> &'static str {
+    match v {
+        Value::Null => "null",
+        Value::Bool(_) => "a boolean",
+        Value::Number(_) => "a number",
+        Value::String(_) => "a string",
+        Value::Array(_) => "an array",
+        Value::Object(_) => "an object",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    /// A valid two-tick input in the shape the server writes.
+    fn valid() -> Value {
+        json!({
+            "session_id": 7,
+            "config": {
+                "seed": 42, "player_count": 2, "tick_rate": 60, "initial_lives": 1,
+                "match_duration_ticks": 1800, "sudden_death_start_tick": 1200,
+                "map": {
+                    "width": 960, "height": 540,
+                    "platforms": [
+                        { "x": 0, "y": 512, "width": 960, "height": 32 },
+                        { "x": 128, "y": 416, "width": 176, "height": 16, "oneWay": true },
+                    ],
+                    "spawn_points": [{ "x": 144, "y": 480 }, { "x": 832, "y": 480 }],
+                    "weapon_spawn_points": [{ "x": 192, "y": 384 }],
+                },
+            },
+            "transcript": [
+                [{ "buttons": 2, "aim_x": 1, "aim_y": 0 }, { "buttons": 0, "aim_x": -0.5, "aim_y": 0 }],
+                [{ "buttons": 8, "aim_x": 127.9, "aim_y": -128 }, { "buttons": 0, "aim_x": 0, "aim_y": 0 }],
+            ],
+        })
+    }
+
+    fn issues(v: &Value) -> Vec<String> {
+        validate_prover_input(v).iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn valid_input_parses() {
+        assert!(issues(&valid()).is_empty());
+        let input = parse_prover_input(&valid().to_string()).unwrap();
+        assert_eq!(input.transcript.len(), 2);
+        assert!(input.config.map.platforms[1].one_way);
+    }
+
+    #[test]
+    fn bad_inputs_name_the_tick_and_player() {
+        let mut v = valid();
+        v["transcript"][1][0]["buttons"] = json!(1.0);
+        v["transcript"][1][1]["aim_x"] = json!(300);
+        v["transcript"][0][1]["aim_y"] = json!(-129.5);
+        v["transcript"][0][0] = json!("left");
+        assert_eq!(
+            issues(&v),
+            [
+                "tick 0, player 0 (transcript[0][0]): expected an object, got a string",
+                "tick 0, player 1 (transcript[0][1].aim_y): -129.5 doesn't fit an i8 aim (-128..=127) after truncation",
+                "tick 1, player 0 (transcript[1][0].buttons): expected an integer, got 1.0",
+                "tick 1, player 1 (transcript[1][1].aim_x): 300 doesn't fit an i8 aim (-128..=127) after truncation",
+            ]
+        );
+    }
+
+    #[test]
+    fn ticks_need_both_players_inputs() {
+        let mut v = valid();
+        v["transcript"][1] = json!([{ "buttons": 0, "aim_x": 0, "aim_y": 0 }]);
+        v["transcript"][0][1] = json!({ "buttons": 256, "aim_x": 0 });
+        assert_eq!(
+            issues(&v),
+            [
+                "tick 0, player 1 (transcript[0][1].buttons): 256 is outside 0..=255",
+                "tick 0, player 1 (transcript[0][1].aim_y): missing",
+                "transcript[1]: 1 inputs; expected one per player (2)",
+            ]
+        );
+    }
+
+    #[test]
+    fn config_must_be_two_players_on_a_map_that_fits() {
+        let mut v = valid();
+        v["config"]["player_count"] = json!(3);
+        v["config"].as_object_mut().unwrap().remove("tick_rate");
+        let platform = json!({ "x": 0, "y": 0, "width": 16, "height": 16 });
+        v["config"]["map"]["platforms"] = Value::Array(vec![platform; NUM_PLATFORMS + 1]);
+        v["config"]["map"]["spawn_points"][1]["y"] = json!("480");
+        assert_eq!(
+            issues(&v),
+            [
+                "config.player_count: 3 players; the prover replays two-player matches only",
+                "config.tick_rate: missing",
+                "config.map.platforms: 9 entries; the fixed-point sim has 8 slots",
+                "config.map.spawn_points[1].y: expected a number, got a string",
+            ]
+        );
+    }
+
+    #[test]
+    fn error_lists_the_first_issues_and_counts_the_rest() {
+        let mut v = valid();
+        v["transcript"] = Value::Array(vec![json!([{ "buttons": -1, "aim_x": 0, "aim_y": 0 }, {}]); 3]);
+        let message = match parse_prover_input(&v.to_string()) {
+            Err(PipelineError::Input(m)) => m,
+            other => panic!("expected an input error, got ok={}", other.is_ok()),
+        };
+        let lines: Vec<&str> = message.lines().collect();
+        assert_eq!(lines[0], "12 issues");
+        assert_eq!(lines[1], "  tick 0, player 0 (transcript[0][0].buttons): -1 is outside 0..=255");
+        assert_eq!(lines.len(), 1 + MAX_REPORTED_ISSUES + 1);
+        assert_eq!(lines[MAX_REPORTED_ISSUES + 1], "  ... and 7 more");
+    }
+
+    #[test]
+    fn parse_input_takes_either_format() {
+        let from_json = parse_input(valid().to_string().as_bytes()).unwrap();
+        assert_eq!((from_json.session_id, from_json.transcript.len()), (7, 2));
+        let arena = FpProverInput { map: None, ..from_json };
+        let mut file = Vec::new();
+        fp::write_transcript(&mut file, &arena).unwrap();
+        let from_file = parse_input(&file).unwrap();
+        assert_eq!(fp::encode_raw_input(&from_file), fp::encode_raw_input(&arena));
+        assert_eq!(from_file.session_id, 7);
+
+        // A corrupted header is reported as such, not as bad JSON
+        file[4] = 9;
+        assert!(matches!(parse_input(&file), Err(PipelineError::Input(m)) if m.starts_with("transcript version 9")));
+        assert!(matches!(parse_input(&[0xFF, 0xFE]), Err(PipelineError::Input(m)) if m.contains("not UTF-8")));
+    }
+
+    #[test]
+    fn syntax_errors_and_non_objects_are_reported() {
+        assert!(matches!(parse_prover_input("{\"config\": "), Err(PipelineError::Input(m)) if m.starts_with("bad transcript JSON")));
+        assert_eq!(issues(&json!([])), ["input: expected an object, got an array"]);
+        assert_eq!(issues(&json!({})), ["config: missing", "transcript: missing"]);
+    }
+}
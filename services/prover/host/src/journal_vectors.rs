@@ -0,0 +1,197 @@
+//! Journal test vectors for the Soroban contract: the journals the guest commits for a couple of
+//! canonical scenarios, with the fields they decode to.
+//!
+//! The contract's tests otherwise settle hand-built journals, so a drift between what the guest
+//! commits and what the contract decodes (winner encoding, offsets) would only show on testnet.
+//! `chickenz-host prove --emit-test-vector FILE` proves each of [`SCENARIOS`] (dev mode will do:
+//! only the journals are kept) and writes them; the result is committed as the contract's
+//! [`CONTRACT_FILE`], which its tests settle through a mock verifier. Regenerating is a
+//! deliberate act, due when the journal schema or the sim changes; until then
+//! `committed_vectors_match_this_build` fails.
+
+use serde::{Deserialize, Serialize};
+
+use chickenz_core::fp::{self, FpProverInput};
+use chickenz_core::journal_layout;
+
+use crate::pipeline::{self, ProofArtifacts, Result};
+use crate::vectors;
+
+/// The contract's copy of the vectors, relative to the prover workspace.
+pub const CONTRACT_FILE: &str = "../../contracts/chickenz/test_data/journal-vectors.json";
+
+/// A journal vector's match: a `vectors::SCENARIOS` transcript, played under `seed`.
+#[derive(Clone, Copy, Debug)]
+pub struct VectorScenario {
+    pub name: &'static str,
+    pub scenario: &'static str,
+    pub seed: u32,
+}
+
+/// The duel under its own seed and under one that swaps the sides (so the registered winner
+/// isn't internal player 0's), and a mirrored match.
+pub const SCENARIOS: &[VectorScenario] = &[
+    VectorScenario { name: "duel", scenario: "duel", seed: 42 },
+    VectorScenario { name: "duel-swapped", scenario: "duel", seed: 0 },
+    VectorScenario { name: "noisy-mirrored", scenario: "noisy-mirrored", seed: 7 },
+];
+
+/// Session the `i`th scenario's proof is bound to.
+pub fn session_id(i: usize) -> u32 {
+    1000 + i as u32
+}
+
+/// One proved scenario: the journal (hex) and what it must decode to, in registered player order.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JournalVector {
+    pub name: String,
+    pub seed: u32,
+    pub session_id: u32,
+    pub journal: String,
+    pub winner: i32,
+    pub scores: [u32; 2],
+    pub env_kills: [u32; 2],
+    pub seed_commit: String,
+    pub transcript_hash: String,
+    pub config_commit: String,
+    pub map_hash: String,
+    pub final_tick: u32,
+    pub final_health: [i32; 2],
+    pub final_lives: [i32; 2],
+    pub pickup_stats_commit: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JournalVectorFile {
+    /// `journal_layout::VERSION` of the guest that committed them.
+    pub journal_version: u32,
+    pub sim_version: u32,
+    pub vectors: Vec<JournalVector>,
+}
+
+/// The prover input of the `i`th of [`SCENARIOS`].
+pub fn scenario_input(i: usize) -> FpProverInput {
+    let name = SCENARIOS[i].scenario;
+    let scenario = vectors::SCENARIOS
+        .iter()
+        .find(|s| s.name == name)
+        .unwrap_or_else(|| panic!("no determinism scenario {name}"));
+    FpProverInput {
+        session_id: session_id(i),
+        seed: SCENARIOS[i].seed,
+        mirrored: (scenario.config)().mirrored,
+        transcript: (0..scenario.ticks).map(scenario.inputs).collect(),
+        map: None,
+        gen_seed: None,
+        rules: None,
+    }
+}
+
+/// The vector of `input`, proved as `name`, from the journal its proof committed.
+pub fn vector(name: &str, input: &FpProverInput, journal: &[u8]) -> Result<JournalVector> {
+    let output = pipeline::decode_output(journal)?;
+    let stats = (output.final_health, output.final_lives);
+    Ok(JournalVector {
+        name: name.to_string(),
+        seed: input.seed,
+        session_id: output.session_id,
+        journal: hex::encode(journal),
+        winner: output.winner,
+        scores: [output.scores[0], output.scores[1]],
+        env_kills: [output.env_kills[0], output.env_kills[1]],
+        seed_commit: hex::encode(output.seed_commit),
+        transcript_hash: hex::encode(output.transcript_hash),
+        config_commit: hex::encode(output.config_commit),
+        map_hash: hex::encode(output.map_hash),
+        final_tick: output.final_tick,
+        final_health: [stats.0[0], stats.0[1]],
+        final_lives: [stats.1[0], stats.1[1]],
+        pickup_stats_commit: hex::encode(output.pickup_stats_commit),
+    })
+}
+
+/// Prove every scenario with `prove` and collect the vectors.
+pub fn generate(
+    mut prove: impl FnMut(&FpProverInput) -> Result<ProofArtifacts>,
+) -> Result<JournalVectorFile> {
+    let mut vectors = Vec::new();
+    for (i, scenario) in SCENARIOS.iter().enumerate() {
+        let input = scenario_input(i);
+        let artifacts = prove(&input)?;
+        vectors.push(vector(scenario.name, &input, &artifacts.journal)?);
+    }
+    Ok(JournalVectorFile {
+        journal_version: journal_layout::VERSION,
+        sim_version: fp::SIM_VERSION,
+        vectors,
+    })
+}
+
+pub fn to_json(vectors: &JournalVectorFile) -> String {
+    serde_json::to_string_pretty(vectors).expect("journal vector file serializes") + "\n"
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audit;
+    use crate::pipeline::Logger;
+
+    const STALE: &str = "journal or sim changed: rerun `chickenz-host prove --emit-test-vector` into";
+
+    #[test]
+    fn committed_vectors_match_this_build() {
+        let committed: JournalVectorFile = serde_json::from_str(include_str!(
+            "../../../../contracts/chickenz/test_data/journal-vectors.json"
+        ))
+        .unwrap();
+        assert_eq!(
+            (committed.journal_version, committed.sim_version),
+            (journal_layout::VERSION, fp::SIM_VERSION),
+            "{STALE} {CONTRACT_FILE}"
+        );
+        assert_eq!(committed.vectors.len(), SCENARIOS.len(), "{STALE} {CONTRACT_FILE}");
+
+        // An honest guest's journal is the native replay's
+        for (i, recorded) in committed.vectors.iter().enumerate() {
+            let input = scenario_input(i);
+            let journal = audit::replay_output(&input).to_journal_bytes();
+            let expected = vector(SCENARIOS[i].name, &input, &journal).unwrap();
+            assert_eq!(recorded, &expected, "{STALE} {CONTRACT_FILE}");
+            // What the players commit with commit_transcript, in either proof mode
+            assert_eq!(recorded.transcript_hash, hex::encode(fp::hash_transcript(&input.transcript)));
+        }
+    }
+
+    #[test]
+    fn vectors_cover_both_winners_and_a_side_swap() {
+        let mut winners = Vec::new();
+        let mut swapped = false;
+        for i in 0..SCENARIOS.len() {
+            let input = scenario_input(i);
+            winners.push(audit::replay_output(&input).winner);
+            let cfg = input.match_config();
+            swapped |= fp::create_initial_state_with(input.seed, &input.oriented_map(), &cfg).side_swap;
+        }
+        assert!(winners.contains(&0) && winners.contains(&1), "{winners:?}");
+        assert!(swapped);
+    }
+
+    #[test]
+    fn dev_mode_proof_commits_the_replayed_journal() {
+        std::env::set_var("RISC0_DEV_MODE", "1");
+        let opts = pipeline::ProveOpts {
+            groth16: false,
+            artifacts_path: None,
+            log: Logger::silent(),
+            ..pipeline::ProveOpts::default()
+        };
+        let mut input = scenario_input(0);
+        input.transcript.truncate(120);
+        let artifacts = pipeline::prove_monolithic(&input, &opts).unwrap();
+        let replayed = audit::replay_output(&input).to_journal_bytes();
+        let proved = vector("short", &input, &artifacts.journal).unwrap();
+        assert_eq!(proved, vector("short", &input, &replayed).unwrap());
+        assert_eq!(proved.session_id, session_id(0));
+    }
+}
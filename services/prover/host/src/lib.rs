@@ -0,0 +1,28 @@
+//! Chickenz proving host: drives the zkVM guests and produces settlement artifacts.
+//!
+//! The `chickenz-host` binary is a thin CLI ([`cli`]) over [`pipeline`]; services that need to
+//! trigger proving in-process should call the pipeline functions directly. [`audit`] re-checks a
+//! settled journal against its transcript natively; [`vectors`] checks the sim is bit-identical
+//! across machines, and [`demo`] scripts the landing page's attract-mode match. [`checkpoint`] is
+//! the progress document chunked runs publish and resume from, and [`cache`] keeps chunk receipts
+//! so a rerun only proves the chunks it is missing. [`watch`] proves transcripts as they are
+//! dropped into a directory. [`input`] checks `ProverInput` JSON with errors that name the tick and
+//! player. [`journal_vectors`] proves the journals the contract's tests settle. With the
+//! `boundless` feature, `boundless` records the open Boundless request so a restarted host waits
+//! for it instead of submitting again. With the `server` feature, `server` takes prove and verify
+//! requests over HTTP.
+
+pub mod audit;
+#[cfg(feature = "boundless")]
+pub mod boundless;
+pub mod cache;
+pub mod checkpoint;
+pub mod cli;
+pub mod demo;
+pub mod input;
+pub mod journal_vectors;
+pub mod pipeline;
+#[cfg(feature = "server")]
+pub mod server;
+pub mod vectors;
+pub mod watch;
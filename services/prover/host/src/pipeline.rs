@@ -0,0 +1,1684 @@
+//! Proving pipeline: monolithic, chunked (chunk guests + match composer), and Boundless.
+//!
+//! Every stage reports progress through the [`Logger`] in [`ProveOpts`] instead of writing to
+//! stderr directly, so library consumers can capture or silence it. The CLI installs
+//! [`Logger::stderr`], which reproduces the historical output line for line.
+
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use chickenz_core::fp::{self, FpInput, FpProverInput, CHUNK_PROOF_WORDS};
+use chickenz_core::summary::MatchSummary;
+use chickenz_core::{journal_layout, quantize_input, quantize_map, ProverInput, ProverOutput};
+
+use chickenz_methods::CHICKENZ_GUEST_ELF;
+use chickenz_methods::CHICKENZ_GUEST_ID;
+use chickenz_methods::CHICKENZ_CHUNK_GUEST_ELF;
+use chickenz_methods::CHICKENZ_CHUNK_GUEST_ID;
+use chickenz_methods::CHICKENZ_MATCH_GUEST_ELF;
+use chickenz_methods::CHICKENZ_MATCH_GUEST_ID;
+
+use crate::cache::{self, ChunkCache};
+use crate::checkpoint;
+
+/// Default ticks per chunk (6 seconds).
+pub const DEFAULT_CHUNK_SIZE: usize = 360;
+/// Largest chunk the chunk guest can accept: its input buffer (`fp::MAX_CHUNK_INPUT_WORDS`)
+/// holds 720 ticks of two players' inputs.
+pub const MAX_CHUNK_SIZE: usize = fp::MAX_CHUNK_INPUT_WORDS * 4 / (2 * fp::INPUT_BYTES);
+/// Most chunks proved at once. Every in-flight chunk holds a whole prover's working set (several
+/// GB for a full chunk), so this bounds memory as much as threads.
+pub const MAX_JOBS: usize = 16;
+/// Default artifacts file written by the CLI.
+pub const DEFAULT_ARTIFACTS_PATH: &str = "proof_artifacts.json";
+/// Default chunk receipt cache directory (see [`crate::cache`]).
+pub const DEFAULT_CACHE_DIR: &str = "chunk-cache";
+/// Default record of the in-flight Boundless request (the `boundless` module).
+pub const DEFAULT_BOUNDLESS_STATE_PATH: &str = "boundless-request.json";
+
+// ============================================================================
+// Options, logging, errors
+// ============================================================================
+
+/// Progress sink for pipeline messages.
+#[derive(Clone)]
+pub struct Logger(Arc<dyn Fn(&str) + Send + Sync>);
+
+impl Logger {
+    pub fn new(f: impl Fn(&str) + Send + Sync + 'static) -> Self {
+        Logger(Arc::new(f))
+    }
+
+    /// Write every message to stderr (CLI behavior).
+    pub fn stderr() -> Self {
+        Logger::new(|msg| eprintln!("{msg}"))
+    }
+
+    /// Discard all messages.
+    pub fn silent() -> Self {
+        Logger::new(|_| {})
+    }
+
+    pub fn log(&self, msg: impl AsRef<str>) {
+        (self.0)(msg.as_ref())
+    }
+}
+
+impl Default for Logger {
+    fn default() -> Self {
+        Logger::stderr()
+    }
+}
+
+/// Options shared by all proving modes.
+#[derive(Clone)]
+pub struct ProveOpts {
+    /// Wrap the final receipt in Groth16 (required for on-chain settlement).
+    pub groth16: bool,
+    /// Ticks per chunk in chunked mode.
+    pub chunk_size: usize,
+    /// Where to write proof_artifacts.json. `None` skips writing.
+    pub artifacts_path: Option<PathBuf>,
+    /// Chunked mode: progress document rewritten after every stage (see [`crate::checkpoint`]).
+    pub checkpoint: Option<PathBuf>,
+    /// Chunked mode: continue from this checkpoint, reusing its finished chunks. Progress keeps
+    /// being written to it unless `checkpoint` names another file.
+    pub resume_from: Option<PathBuf>,
+    /// Chunked mode: chunks proved at once, each on its own thread and prover (1..=[`MAX_JOBS`]).
+    pub jobs: usize,
+    /// Chunked mode: chunk receipt cache (see [`crate::cache`]). `None` proves every chunk.
+    pub cache_dir: Option<PathBuf>,
+    /// Boundless mode: where the submitted request is recorded until its proof arrives, and
+    /// where an earlier run's open request for the same input is picked up from. `None` keeps
+    /// nothing, so a crash while waiting loses the request.
+    pub boundless_state: Option<PathBuf>,
+    pub log: Logger,
+}
+
+impl Default for ProveOpts {
+    fn default() -> Self {
+        ProveOpts {
+            groth16: true,
+            chunk_size: DEFAULT_CHUNK_SIZE,
+            artifacts_path: Some(PathBuf::from(DEFAULT_ARTIFACTS_PATH)),
+            checkpoint: None,
+            resume_from: None,
+            jobs: 1,
+            cache_dir: Some(PathBuf::from(DEFAULT_CACHE_DIR)),
+            boundless_state: Some(PathBuf::from(DEFAULT_BOUNDLESS_STATE_PATH)),
+            log: Logger::stderr(),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum PipelineError {
+    /// Options rejected before any proving started.
+    InvalidOptions(String),
+    /// A transcript file could not be parsed or has nothing to prove.
+    Input(String),
+    /// Building the executor environment failed.
+    Env(String),
+    /// The prover returned an error.
+    Prove(String),
+    /// Receipt verification failed.
+    Verify(String),
+    /// A journal had an unexpected size or content.
+    Journal(String),
+    /// A checkpoint could not be parsed or does not belong to this input.
+    Checkpoint(String),
+    Io(std::io::Error),
+}
+
+impl std::fmt::Display for PipelineError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PipelineError::InvalidOptions(m) => write!(f, "invalid options: {m}"),
+            PipelineError::Input(m) => write!(f, "bad transcript: {m}"),
+            PipelineError::Env(m) => write!(f, "failed to build executor env: {m}"),
+            PipelineError::Prove(m) => write!(f, "proof generation failed: {m}"),
+            PipelineError::Verify(m) => write!(f, "receipt verification failed: {m}"),
+            PipelineError::Journal(m) => write!(f, "bad journal: {m}"),
+            PipelineError::Checkpoint(m) => write!(f, "bad checkpoint: {m}"),
+            PipelineError::Io(e) => write!(f, "I/O error: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for PipelineError {}
+
+impl From<std::io::Error> for PipelineError {
+    fn from(e: std::io::Error) -> Self {
+        PipelineError::Io(e)
+    }
+}
+
+pub type Result<T> = std::result::Result<T, PipelineError>;
+
+// ============================================================================
+// Artifacts
+// ============================================================================
+
+/// Everything needed to settle a proof on-chain, plus the receipt for local verification.
+#[derive(Clone)]
+pub struct ProofArtifacts {
+    /// Groth16 seal; empty for STARK / dev-mode proofs.
+    pub seal: Vec<u8>,
+    pub image_id: [u32; 8],
+    pub journal: Vec<u8>,
+    pub output: ProverOutput,
+    /// Total cycles across all guests that ran.
+    pub total_cycles: u64,
+    /// The receipt itself (absent for Boundless proofs, which only return seal + journal).
+    pub receipt: Option<risc0_zkvm::Receipt>,
+    /// Damage per 10 s of the match (`audit::replay_damage_pacing`), under the journal's
+    /// `pickup_stats_commit`. Absent when the transcript wasn't at hand (`boundless_status`).
+    pub damage_pacing: Option<Vec<u16>>,
+}
+
+impl ProofArtifacts {
+    pub fn image_id_hex(&self) -> String {
+        hex::encode(image_id_bytes(&self.image_id))
+    }
+
+    /// True if the artifacts carry a seal that can be submitted to the Soroban contract.
+    pub fn is_submittable(&self) -> bool {
+        !self.seal.is_empty()
+    }
+
+    /// The shareable summary of the proved match, made from the journal's output alone.
+    pub fn summary(&self) -> MatchSummary {
+        MatchSummary::from_output(&self.output)
+    }
+
+    /// proof_artifacts.json contents.
+    pub fn to_json(&self) -> serde_json::Value {
+        let mut json = serde_json::json!({
+            "seal": hex::encode(&self.seal),
+            "image_id": self.image_id_hex(),
+            "journal": hex::encode(&self.journal),
+            "output": output_json(&self.output),
+            "summary": self.summary().to_base64(),
+        });
+        if let Some(pacing) = &self.damage_pacing {
+            json["damage_pacing"] = serde_json::json!(pacing);
+        }
+        json
+    }
+
+    /// `to_json` plus the receipt, when there is one, so [`ProofArtifacts::from_json`] gets
+    /// back artifacts that [`verify`] can check.
+    pub fn to_json_with_receipt(&self) -> serde_json::Value {
+        let mut json = self.to_json();
+        if let Some(receipt) = &self.receipt {
+            json["receipt"] = serde_json::to_value(receipt).expect("receipt serializes");
+        }
+        json
+    }
+
+    /// Artifacts from `to_json` or `to_json_with_receipt`. The output is decoded from the
+    /// journal, and the cycle count isn't recorded (0). `damage_pacing` is optional: older
+    /// files have none.
+    pub fn from_json(json: &serde_json::Value) -> Result<Self> {
+        let hex_field = |name: &str| -> Result<Vec<u8>> {
+            let text = json
+                .get(name)
+                .and_then(|v| v.as_str())
+                .ok_or_else(|| PipelineError::Verify(format!("artifacts have no {name}")))?;
+            hex::decode(text).map_err(|e| PipelineError::Verify(format!("artifacts {name}: {e}")))
+        };
+        let id = hex_field("image_id")?;
+        if id.len() != 32 {
+            return Err(PipelineError::Verify(format!("image ID is {} bytes, expected 32", id.len())));
+        }
+        let mut image_id = [0u32; 8];
+        for (word, bytes) in image_id.iter_mut().zip(id.chunks_exact(4)) {
+            *word = u32::from_le_bytes(bytes.try_into().unwrap());
+        }
+        let receipt = match json.get("receipt") {
+            Some(r) if !r.is_null() => Some(
+                serde_json::from_value(r.clone())
+                    .map_err(|e| PipelineError::Verify(format!("artifacts receipt: {e}")))?,
+            ),
+            _ => None,
+        };
+        let damage_pacing = match json.get("damage_pacing") {
+            Some(p) if !p.is_null() => Some(
+                serde_json::from_value(p.clone())
+                    .map_err(|e| PipelineError::Verify(format!("artifacts damage_pacing: {e}")))?,
+            ),
+            _ => None,
+        };
+        let journal = hex_field("journal")?;
+        Ok(ProofArtifacts {
+            seal: hex_field("seal")?,
+            image_id,
+            output: decode_output(&journal)?,
+            journal,
+            total_cycles: 0,
+            receipt,
+            damage_pacing,
+        })
+    }
+
+    pub fn write(&self, path: &std::path::Path) -> Result<()> {
+        std::fs::write(path, serde_json::to_string_pretty(&self.to_json()).unwrap())?;
+        Ok(())
+    }
+}
+
+/// The `output` object of proof_artifacts.json: the journal's fields, hashes in hex.
+pub fn output_json(output: &ProverOutput) -> serde_json::Value {
+    let players = output.player_count as usize;
+    serde_json::json!({
+        "winner": output.winner,
+        "scores": &output.scores[..players],
+        "transcript_hash": hex::encode(output.transcript_hash),
+        "seed_commit": hex::encode(output.seed_commit),
+        "session_id": output.session_id,
+        "config_commit": hex::encode(output.config_commit),
+        "env_kills": &output.env_kills[..players],
+        "pickup_stats_commit": hex::encode(output.pickup_stats_commit),
+    })
+}
+
+/// Verify artifacts: the receipt must verify against the image ID and its journal must decode to
+/// the recorded output. Returns the decoded output.
+pub fn verify(artifacts: &ProofArtifacts) -> Result<ProverOutput> {
+    let receipt = artifacts
+        .receipt
+        .as_ref()
+        .ok_or_else(|| PipelineError::Verify("artifacts carry no receipt".into()))?;
+    receipt
+        .verify(artifacts.image_id)
+        .map_err(|e| PipelineError::Verify(e.to_string()))?;
+    if receipt.journal.bytes != artifacts.journal {
+        return Err(PipelineError::Verify("receipt journal differs from artifacts journal".into()));
+    }
+    let output = decode_output(&artifacts.journal)?;
+    if output != artifacts.output {
+        return Err(PipelineError::Verify("journal does not decode to the recorded output".into()));
+    }
+    Ok(output)
+}
+
+// ============================================================================
+// Input helpers
+// ============================================================================
+
+/// The zkVM input for `input`. Its map is quantized with `quantize_map`; one that quantizes
+/// to `fp::arena_map` (the TS arena, `prover_arena`) stays `None`, so arena matches prove on
+/// every pipeline. Any other map travels as quantized, the f64 core's older `arena()` (what
+/// `default_config` puts in hand-built inputs) included, so the proof replays the map the
+/// input names. A generated map travels as its `gen_seed` alone.
+pub fn to_fp_input(input: &ProverInput) -> FpProverInput {
+    FpProverInput {
+        session_id: input.session_id,
+        seed: input.config.seed,
+        mirrored: input.config.mirrored,
+        transcript: input
+            .transcript
+            .iter()
+            .map(|tick| [quantize_input(&tick[0]), quantize_input(&tick[1])])
+            .collect(),
+        map: input
+            .config
+            .gen_seed
+            .is_none()
+            .then(|| quantize_map(&input.config.map))
+            .filter(|m| *m != fp::arena_map()),
+        gen_seed: input.config.gen_seed,
+        rules: Some(fp::MatchRules {
+            initial_lives: input.config.initial_lives,
+            match_duration: input.config.match_duration_ticks as i32,
+            sudden_death: input.config.sudden_death_start_tick as i32,
+        })
+        .filter(|r| *r != fp::MatchRules::default()),
+    }
+}
+
+/// The monolithic guest replays on the arena; a match on any other map has to go chunked.
+fn require_arena(fp_input: &FpProverInput, pipeline: &str) -> Result<()> {
+    if fp_input.base_map() != fp::arena_map() {
+        return Err(PipelineError::InvalidOptions(format!(
+            "{pipeline} proving only replays the arena; prove a custom-map match chunked"
+        )));
+    }
+    Ok(())
+}
+
+/// Pad a byte buffer to u32 alignment and convert to u32 words.
+pub fn bytes_to_words(bytes: &[u8]) -> Vec<u32> {
+    let padded_len = (bytes.len() + 3) / 4 * 4;
+    let mut padded = bytes.to_vec();
+    padded.resize(padded_len, 0);
+    padded.chunks_exact(4)
+        .map(|c| u32::from_le_bytes([c[0], c[1], c[2], c[3]]))
+        .collect()
+}
+
+/// Encode chunk inputs as raw bytes: tick_count × `INPUT_BYTES` per player (6 bytes for two
+/// players), each tick's inputs in player order.
+pub fn encode_chunk_inputs<T: AsRef<[FpInput]>>(transcript: &[T], start: usize, count: usize) -> Vec<u8> {
+    let end = (start + count).min(transcript.len());
+    let mut buf = Vec::new();
+    for tick in &transcript[start..end] {
+        for inp in tick.as_ref() {
+            buf.extend_from_slice(&[inp.buttons, inp.aim_x as u8, inp.aim_y as u8]);
+        }
+    }
+    buf
+}
+
+pub(crate) fn image_id_bytes(image_id: &[u32; 8]) -> Vec<u8> {
+    image_id.iter().flat_map(|w| w.to_le_bytes()).collect()
+}
+
+/// Decode a journal of any known schema version (unversioned v0 journals included).
+pub(crate) fn decode_output(journal: &[u8]) -> Result<ProverOutput> {
+    if ProverOutput::journal_layout(journal).is_none() {
+        let version = journal
+            .get(..4)
+            .map(|w| journal_layout::decode_version(u32::from_le_bytes([w[0], w[1], w[2], w[3]])));
+        return Err(PipelineError::Journal(match version.and_then(journal_layout::layout) {
+            Some(l) => format!(
+                "journal is {} bytes, expected {} for version {}",
+                journal.len(),
+                l.size(),
+                l.version
+            ),
+            None => format!("journal is {} bytes with no known schema version", journal.len()),
+        }));
+    }
+    Ok(ProverOutput::from_journal_bytes(journal))
+}
+
+fn mode_name(groth16: bool) -> &'static str {
+    if groth16 { "Groth16" } else { "local STARK" }
+}
+
+fn prover_opts(groth16: bool) -> risc0_zkvm::ProverOpts {
+    if groth16 {
+        risc0_zkvm::ProverOpts::groth16()
+    } else {
+        risc0_zkvm::ProverOpts::default()
+    }
+}
+
+/// Verify the final receipt, extract the seal, and optionally write the artifacts file.
+fn finish(
+    receipt: risc0_zkvm::Receipt,
+    image_id: [u32; 8],
+    output: ProverOutput,
+    total_cycles: u64,
+    damage_pacing: Option<Vec<u16>>,
+    verified_msg: &str,
+    opts: &ProveOpts,
+) -> Result<ProofArtifacts> {
+    let log = &opts.log;
+    receipt
+        .verify(image_id)
+        .map_err(|e| PipelineError::Verify(e.to_string()))?;
+    log.log(verified_msg);
+
+    let image_id_hex = hex::encode(image_id_bytes(&image_id));
+    log.log(format!("Image ID: {}", image_id_hex));
+
+    let journal = receipt.journal.bytes.clone();
+    log.log(format!("Journal size: {} bytes", journal.len()));
+
+    // Try to extract Groth16 seal; fall back to empty if not available (dev mode)
+    let seal = if opts.groth16 {
+        match receipt.inner.groth16() {
+            Ok(g) => {
+                log.log(format!("Seal size: {} bytes", g.seal.len()));
+                g.seal.clone()
+            }
+            Err(_) => {
+                log.log("WARNING: No Groth16 seal (dev mode?). Writing artifacts with empty seal.");
+                vec![]
+            }
+        }
+    } else {
+        vec![]
+    };
+
+    let artifacts = ProofArtifacts {
+        seal,
+        image_id,
+        journal,
+        output,
+        total_cycles,
+        receipt: Some(receipt),
+        damage_pacing,
+    };
+
+    if let Some(path) = &opts.artifacts_path {
+        artifacts.write(path)?;
+        log.log(format!("Artifacts written to {}", path.display()));
+    }
+
+    Ok(artifacts)
+}
+
+// ============================================================================
+// Monolithic proving (original single-guest approach)
+// ============================================================================
+
+/// Monolithic guest input: session id, orientation and byte length, the match rules, then
+/// the raw transcript.
+fn monolithic_env(fp_input: &FpProverInput) -> Result<risc0_zkvm::ExecutorEnv<'static>> {
+    let raw_bytes = fp::encode_raw_input(fp_input);
+    let rules = fp::MatchRules::of(&fp_input.match_config()).to_words();
+    risc0_zkvm::ExecutorEnv::builder()
+        .write_slice(&[fp_input.session_id, fp_input.mirrored as u32, raw_bytes.len() as u32])
+        .write_slice(&rules)
+        .write_slice(&bytes_to_words(&raw_bytes))
+        .build()
+        .map_err(|e| PipelineError::Env(e.to_string()))
+}
+
+pub fn prove_monolithic(fp_input: &FpProverInput, opts: &ProveOpts) -> Result<ProofArtifacts> {
+    require_arena(fp_input, "monolithic")?;
+    let log = &opts.log;
+    let raw_bytes = fp::encode_raw_input(fp_input);
+    log.log(format!("Converted to raw bytes: {} bytes", raw_bytes.len()));
+
+    let mode = mode_name(opts.groth16);
+    log.log(format!("Starting monolithic proof generation ({mode})..."));
+
+    let env = monolithic_env(fp_input)?;
+
+    let prover = risc0_zkvm::default_prover();
+
+    let start = Instant::now();
+    let prove_info = prover
+        .prove_with_opts(env, CHICKENZ_GUEST_ELF, &prover_opts(opts.groth16))
+        .map_err(|e| PipelineError::Prove(e.to_string()))?;
+    let elapsed = start.elapsed();
+
+    log.log(format!("{mode} proof generated in {:.1}s", elapsed.as_secs_f64()));
+    log.log(format!("Stats: {} segment(s)", prove_info.stats.segments));
+    log.log(format!(
+        "Total cycles: {} ({:.1}M)",
+        prove_info.stats.total_cycles,
+        prove_info.stats.total_cycles as f64 / 1_000_000.0
+    ));
+    log.log(format!("User cycles: {}", prove_info.stats.user_cycles));
+
+    let receipt = prove_info.receipt;
+    let output = decode_output(&receipt.journal.bytes)?;
+    finish(
+        receipt,
+        CHICKENZ_GUEST_ID,
+        output,
+        prove_info.stats.total_cycles,
+        Some(crate::audit::replay_damage_pacing(fp_input)),
+        "Receipt verified locally.",
+        opts,
+    )
+}
+
+// ============================================================================
+// Chunked proving (chunk guests + match composer)
+// ============================================================================
+
+/// Chunk guest input: `state`, then `ticks` ticks of `transcript` from `start_tick`.
+fn chunk_env(
+    state: &fp::State,
+    transcript: &[[FpInput; 2]],
+    start_tick: usize,
+    ticks: usize,
+) -> Result<risc0_zkvm::ExecutorEnv<'static>> {
+    let state_bytes = fp::encode_state(state);
+    let input_bytes = encode_chunk_inputs(transcript, start_tick, ticks);
+
+    let state_words = bytes_to_words(&state_bytes);
+    let input_words = bytes_to_words(&input_bytes);
+
+    risc0_zkvm::ExecutorEnv::builder()
+        .write_slice(&[state_bytes.len() as u32, ticks as u32])
+        .write_slice(&state_words)
+        .write_slice(&input_words)
+        .build()
+        .map_err(|e| PipelineError::Env(e.to_string()))
+}
+
+/// The state before each chunk of `chunk_size` ticks, then the state after the last one. The
+/// last chunk is the one the match ends in, or the one that runs out of transcript.
+fn chunk_boundary_states(fp_input: &FpProverInput, chunk_size: usize) -> Vec<fp::State> {
+    let cfg = fp_input.match_config();
+    let map = fp_input.oriented_map();
+    let mut state = fp::create_initial_state_with(fp_input.seed, &map, &cfg);
+    let mut boundary_states = vec![state.clone()];
+
+    for chunk in fp_input.transcript.chunks(chunk_size) {
+        for inputs in chunk {
+            fp::step_mut(&mut state, inputs);
+            if state.match_over {
+                break;
+            }
+        }
+        boundary_states.push(state.clone());
+        if state.match_over {
+            break;
+        }
+    }
+    boundary_states
+}
+
+/// Cache key of chunk `chunk_idx`, which starts from `boundary_states[chunk_idx]`.
+fn chunk_cache_key(
+    fp_input: &FpProverInput,
+    boundary_states: &[fp::State],
+    chunk_size: usize,
+    chunk_idx: usize,
+) -> cache::ChunkKey {
+    let start_tick = chunk_idx * chunk_size;
+    let ticks = chunk_size.min(fp_input.transcript.len() - start_tick);
+    let inputs = encode_chunk_inputs(&fp_input.transcript, start_tick, ticks);
+    cache::chunk_key(&CHICKENZ_CHUNK_GUEST_ID, &boundary_states[chunk_idx], &inputs)
+}
+
+/// Chunk `index`'s journal, checked the way the composer would before it is sent: the
+/// `CHUNK_PROOF_WORDS` × 4 = 212 bytes, replayed under this build's `SIM_VERSION`. A chunk
+/// guest from another build fails here instead of inside the composer.
+pub fn check_chunk_journal(index: usize, journal: &[u8]) -> Result<fp::ChunkProof> {
+    if journal.len() != CHUNK_PROOF_WORDS * 4 {
+        return Err(PipelineError::Journal(format!("chunk {index}: unexpected journal size: {}", journal.len())));
+    }
+    let proof = fp::ChunkProof::from_journal_bytes(journal);
+    if proof.sim_version != fp::SIM_VERSION {
+        return Err(PipelineError::Journal(format!(
+            "chunk {index} (ticks {}..{}): sim version {}, this build is {}",
+            proof.tick_start,
+            proof.tick_end,
+            proof.sim_version,
+            fp::SIM_VERSION
+        )));
+    }
+    Ok(proof)
+}
+
+/// Match composer input for the chunk receipts of `fp_input`, each added as an assumption.
+/// `fp_input` carries the whole transcript, ticks after the match included: its hash is the
+/// journal's transcript_hash.
+fn composer_env(
+    fp_input: &FpProverInput,
+    chunk_receipts: &[risc0_zkvm::Receipt],
+) -> Result<risc0_zkvm::ExecutorEnv<'static>> {
+    let mut env_builder = risc0_zkvm::ExecutorEnv::builder();
+
+    // Write header: seed, session_id, mirrored, num_chunks, player_count, then the rules the
+    // composer rebuilds the initial state under
+    let cfg = fp_input.match_config();
+    env_builder.write_slice(&[
+        fp_input.seed,
+        fp_input.session_id,
+        fp_input.mirrored as u32,
+        chunk_receipts.len() as u32,
+        cfg.player_count as u32,
+    ]);
+    env_builder.write_slice(&fp::MatchRules::of(&cfg).to_words());
+
+    // Write chunk image ID
+    env_builder.write_slice(&CHICKENZ_CHUNK_GUEST_ID);
+
+    // Write the map, unmirrored: the composer orients it like the boundary states. A generated
+    // map is sent as its seed, and the composer builds it
+    if let (None, Some(gen_seed)) = (&fp_input.map, fp_input.gen_seed) {
+        env_builder.write_slice(&[1u32, gen_seed]);
+    } else {
+        env_builder.write_slice(&[0u32, 0]);
+        env_builder.write_slice(&bytes_to_words(&fp::encode_map(&fp_input.base_map())));
+    }
+
+    // Write the whole transcript: the composer checks each chunk's inputs against it and
+    // commits its hash
+    let ticks = fp_input.transcript.len();
+    env_builder.write_slice(&[ticks as u32]);
+    env_builder.write_slice(&bytes_to_words(&encode_chunk_inputs(&fp_input.transcript, 0, ticks)));
+
+    // Write each chunk's journal and add as assumption
+    for (i, receipt) in chunk_receipts.iter().enumerate() {
+        let journal_bytes = &receipt.journal.bytes;
+        check_chunk_journal(i, journal_bytes)?;
+        let journal_words = bytes_to_words(journal_bytes);
+        env_builder.write_slice(&journal_words);
+        env_builder.add_assumption(receipt.clone());
+    }
+
+    env_builder
+        .build()
+        .map_err(|e| PipelineError::Env(e.to_string()))
+}
+
+/// Prove the chunks in `todo` on `jobs` worker threads, each with its own prover: the default
+/// prover isn't shareable across threads, and one per worker keeps at most `jobs` chunks in
+/// flight. Every receipt is stored in `cache` as soon as it is proved. Workers take the next chunk in order as they free up, so the log lines of
+/// concurrent chunks can interleave. Returns `(chunk, receipt, cycles, wall time)` per chunk,
+/// in `todo` order; after the first failure no new chunk is started, and the error of the
+/// lowest failing chunk is returned.
+fn prove_chunks_in_pool(
+    fp_input: &FpProverInput,
+    boundary_states: &[fp::State],
+    todo: &[usize],
+    jobs: usize,
+    progress: &Mutex<checkpoint::Progress>,
+    cache: Option<&ChunkCache>,
+    opts: &ProveOpts,
+) -> Result<Vec<(usize, risc0_zkvm::Receipt, u64, Duration)>> {
+    let log = &opts.log;
+    let chunk_size = opts.chunk_size;
+    let total_ticks = fp_input.transcript.len();
+    let num_chunks = boundary_states.len() - 1;
+    let next = AtomicUsize::new(0);
+    let failed = AtomicBool::new(false);
+
+    let stark = risc0_zkvm::ProverOpts::default(); // chunks always use STARK
+    let prove_chunk = |prover: &dyn risc0_zkvm::Prover, chunk_idx: usize| -> Result<_> {
+        progress.lock().expect("checkpoint lock poisoned").start(Some(chunk_idx))?;
+
+        let start_tick = chunk_idx * chunk_size;
+        let ticks_in_chunk = chunk_size.min(total_ticks - start_tick);
+        let env = chunk_env(&boundary_states[chunk_idx], &fp_input.transcript, start_tick, ticks_in_chunk)?;
+
+        let chunk_start = Instant::now();
+        let prove_info = prover
+            .prove_with_opts(env, CHICKENZ_CHUNK_GUEST_ELF, &stark)
+            .map_err(|e| PipelineError::Prove(format!("chunk {chunk_idx}: {e}")))?;
+        let chunk_elapsed = chunk_start.elapsed();
+
+        let cycles = prove_info.stats.total_cycles;
+        if let Some(cache) = cache {
+            let key = chunk_cache_key(fp_input, boundary_states, chunk_size, chunk_idx);
+            cache.store(&key, &prove_info.receipt, cycles)?;
+        }
+        progress.lock().expect("checkpoint lock poisoned").finish(Some(chunk_idx), cycles, &prove_info.receipt)?;
+        log.log(format!(
+            "  Chunk {}/{}: {:.1}s, {} cycles ({} segments)",
+            chunk_idx + 1,
+            num_chunks,
+            chunk_elapsed.as_secs_f64(),
+            cycles,
+            prove_info.stats.segments,
+        ));
+        Ok((chunk_idx, prove_info.receipt, cycles, chunk_elapsed))
+    };
+
+    let worker = || {
+        let prover = risc0_zkvm::default_prover();
+        let mut done = Vec::new();
+        while !failed.load(Ordering::Relaxed) {
+            let Some(&chunk_idx) = todo.get(next.fetch_add(1, Ordering::Relaxed)) else {
+                break;
+            };
+            let result = prove_chunk(prover.as_ref(), chunk_idx);
+            failed.fetch_or(result.is_err(), Ordering::Relaxed);
+            done.push((chunk_idx, result));
+        }
+        done
+    };
+
+    let mut results: Vec<(usize, Result<_>)> = std::thread::scope(|s| {
+        let workers: Vec<_> = (0..jobs).map(|_| s.spawn(&worker)).collect();
+        workers
+            .into_iter()
+            .flat_map(|w| w.join().unwrap_or_else(|panic| std::panic::resume_unwind(panic)))
+            .collect()
+    });
+    results.sort_by_key(|(chunk_idx, _)| *chunk_idx);
+    results.into_iter().map(|(_, result)| result).collect()
+}
+
+pub fn prove_chunked(fp_input: &FpProverInput, opts: &ProveOpts) -> Result<ProofArtifacts> {
+    let log = &opts.log;
+    let chunk_size = opts.chunk_size;
+    if chunk_size == 0 || chunk_size > MAX_CHUNK_SIZE {
+        return Err(PipelineError::InvalidOptions(format!(
+            "chunk size must be in 1..={MAX_CHUNK_SIZE}, got {chunk_size}"
+        )));
+    }
+    if opts.jobs == 0 || opts.jobs > MAX_JOBS {
+        return Err(PipelineError::InvalidOptions(format!(
+            "jobs must be in 1..={MAX_JOBS}, got {}",
+            opts.jobs
+        )));
+    }
+
+    let total_ticks = fp_input.transcript.len();
+    let num_chunks = (total_ticks + chunk_size - 1) / chunk_size;
+    log.log(format!(
+        "Chunked proving: {} ticks / {} = {} chunks of {} ticks",
+        total_ticks, chunk_size, num_chunks, chunk_size
+    ));
+
+    // Step 1: Run sim natively to get state at each chunk boundary
+    log.log("Computing chunk boundary states...");
+    let boundary_states = chunk_boundary_states(fp_input, chunk_size);
+    let state = boundary_states.last().expect("initial state");
+    log.log(format!("Final state: winner={}, scores={:?}", state.winner, state.score));
+
+    // The composer only accepts a chain that ends with the match, and nothing after it
+    if !state.match_over {
+        return Err(PipelineError::Input(format!(
+            "match is not over after {total_ticks} ticks; only a finished match can be proved chunked"
+        )));
+    }
+    // Chunks replay the ticks up to the end of the match; the composer hashes the whole
+    // transcript, so its transcript_hash is the monolithic guest's
+    let full_input = fp_input;
+    let played;
+    let fp_input = if (state.tick as usize) < total_ticks {
+        let proved_chunks = boundary_states.len() - 1;
+        log.log(format!(
+            "Match over at tick {}: proving {} of {} chunks",
+            state.tick, proved_chunks, num_chunks
+        ));
+        played = FpProverInput {
+            transcript: fp_input.transcript[..state.tick as usize].to_vec(),
+            ..fp_input.clone()
+        };
+        &played
+    } else {
+        fp_input
+    };
+    let num_chunks = boundary_states.len() - 1;
+
+    // Step 2: Prove each chunk, reusing any the checkpoint or the cache already has
+    let mut chunk_receipts: Vec<Option<risc0_zkvm::Receipt>> = vec![None; num_chunks];
+    let mut total_chunk_cycles = 0u64;
+
+    let mut progress = checkpoint::Progress::open(fp_input, opts)?;
+    if progress.resumed() {
+        log.log(format!(
+            "Resuming from checkpoint: {}/{} chunks done",
+            progress.doc().chunks_done(),
+            num_chunks
+        ));
+    }
+
+    let cache = opts.cache_dir.as_deref().map(ChunkCache::open).transpose()?;
+
+    let chunks_start = Instant::now();
+    let mut todo = Vec::new();
+    for (chunk_idx, slot) in chunk_receipts.iter_mut().enumerate() {
+        if let Some((receipt, cycles)) = progress.done_receipt(Some(chunk_idx)) {
+            if receipt.verify(CHICKENZ_CHUNK_GUEST_ID).is_ok() {
+                total_chunk_cycles += cycles;
+                log.log(format!(
+                    "  Chunk {}/{}: reused from checkpoint ({} cycles)",
+                    chunk_idx + 1,
+                    num_chunks,
+                    cycles
+                ));
+                *slot = Some(receipt);
+                continue;
+            }
+            log.log(format!(
+                "  Chunk {}/{}: checkpointed receipt does not verify, re-proving",
+                chunk_idx + 1,
+                num_chunks
+            ));
+        }
+        let cached = cache
+            .as_ref()
+            .and_then(|c| c.load(&chunk_cache_key(fp_input, &boundary_states, chunk_size, chunk_idx)));
+        if let Some((receipt, cycles)) = cached {
+            progress.finish(Some(chunk_idx), cycles, &receipt)?;
+            total_chunk_cycles += cycles;
+            log.log(format!(
+                "  Chunk {}/{}: reused from cache ({} cycles)",
+                chunk_idx + 1,
+                num_chunks,
+                cycles
+            ));
+            *slot = Some(receipt);
+            continue;
+        }
+        todo.push(chunk_idx);
+    }
+
+    let jobs = opts.jobs.min(todo.len());
+    let progress = Mutex::new(progress);
+    let proved =
+        prove_chunks_in_pool(fp_input, &boundary_states, &todo, jobs, &progress, cache.as_ref(), opts)?;
+    let mut progress = progress.into_inner().expect("checkpoint lock poisoned");
+
+    let mut proving_time = Duration::ZERO;
+    for (chunk_idx, receipt, cycles, elapsed) in proved {
+        total_chunk_cycles += cycles;
+        proving_time += elapsed;
+        chunk_receipts[chunk_idx] = Some(receipt);
+    }
+    let chunk_receipts: Vec<_> = chunk_receipts.into_iter().map(|r| r.expect("every chunk proved")).collect();
+    let chunks_elapsed = chunks_start.elapsed();
+    let speedup = if todo.is_empty() {
+        String::new()
+    } else {
+        format!(
+            "; {:.1}s of chunk proving on {} job(s), {:.1}x speedup",
+            proving_time.as_secs_f64(),
+            jobs,
+            proving_time.as_secs_f64() / chunks_elapsed.as_secs_f64().max(1e-9),
+        )
+    };
+    log.log(format!(
+        "All chunks proved in {:.1}s ({} total cycles{speedup})",
+        chunks_elapsed.as_secs_f64(),
+        total_chunk_cycles,
+    ));
+
+    // Step 3: Prove match composer (verifies chunk chain)
+    log.log("Proving match composer...");
+
+    let env = composer_env(full_input, &chunk_receipts)?;
+    let prover = risc0_zkvm::default_prover();
+
+    let (receipt, composer_cycles) = match progress.done_receipt(None) {
+        Some((receipt, cycles)) if receipt.verify(CHICKENZ_MATCH_GUEST_ID).is_ok() => {
+            log.log(format!("Composer proof reused from checkpoint ({cycles} cycles)"));
+            (receipt, cycles)
+        }
+        _ => {
+            progress.start(None)?;
+            let composer_start = Instant::now();
+            let prove_info = prover
+                .prove_with_opts(env, CHICKENZ_MATCH_GUEST_ELF, &prover_opts(opts.groth16))
+                .map_err(|e| PipelineError::Prove(format!("composer: {e}")))?;
+            let composer_elapsed = composer_start.elapsed();
+            progress.finish(None, prove_info.stats.total_cycles, &prove_info.receipt)?;
+
+            let mode = mode_name(opts.groth16);
+            log.log(format!(
+                "Composer proof ({mode}) in {:.1}s, {} cycles ({} segments)",
+                composer_elapsed.as_secs_f64(),
+                prove_info.stats.total_cycles,
+                prove_info.stats.segments,
+            ));
+            (prove_info.receipt, prove_info.stats.total_cycles)
+        }
+    };
+
+    let total_elapsed = chunks_start.elapsed();
+    log.log(format!("Total wall-clock: {:.1}s", total_elapsed.as_secs_f64()));
+
+    let output = decode_output(&receipt.journal.bytes)?;
+    finish(
+        receipt,
+        CHICKENZ_MATCH_GUEST_ID,
+        output,
+        total_chunk_cycles + composer_cycles,
+        Some(crate::audit::replay_damage_pacing(fp_input)),
+        "Composite receipt verified locally.",
+        opts,
+    )
+}
+
+// ============================================================================
+// Cycle counts (executor only)
+// ============================================================================
+
+/// What one guest run costs, from the executor alone: no proof is made, so this is quick
+/// enough to compare two builds on the same transcript.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Cycles {
+    /// Cycles the guest executed.
+    pub user: u64,
+    /// Cycles the prover pays for: each segment padded to its power of two.
+    pub total: u64,
+    pub segments: usize,
+}
+
+impl std::ops::AddAssign for Cycles {
+    fn add_assign(&mut self, other: Cycles) {
+        self.user += other.user;
+        self.total += other.total;
+        self.segments += other.segments;
+    }
+}
+
+fn execute(env: risc0_zkvm::ExecutorEnv<'_>, elf: &[u8], what: &str) -> Result<Cycles> {
+    let session = risc0_zkvm::default_executor()
+        .execute(env, elf)
+        .map_err(|e| PipelineError::Prove(format!("{what}: {e}")))?;
+    Ok(Cycles {
+        user: session.cycles(),
+        total: session.segments.iter().map(|s| 1u64 << s.po2).sum(),
+        segments: session.segments.len(),
+    })
+}
+
+/// Run the monolithic guest over the whole transcript without proving it.
+pub fn execute_monolithic(fp_input: &FpProverInput) -> Result<Cycles> {
+    require_arena(fp_input, "monolithic")?;
+    execute(monolithic_env(fp_input)?, CHICKENZ_GUEST_ELF, "monolithic")
+}
+
+/// Run the chunk guest over each chunk `prove_chunked` would prove, without proving them.
+/// One entry per chunk. The composer is left out: it needs the chunk receipts, and it runs
+/// once per match whatever the chunk size.
+pub fn execute_chunked(fp_input: &FpProverInput, chunk_size: usize) -> Result<Vec<Cycles>> {
+    if chunk_size == 0 || chunk_size > MAX_CHUNK_SIZE {
+        return Err(PipelineError::InvalidOptions(format!(
+            "chunk size must be in 1..={MAX_CHUNK_SIZE}, got {chunk_size}"
+        )));
+    }
+    let boundary_states = chunk_boundary_states(fp_input, chunk_size);
+    // Up to the end of the match, like prove_chunked
+    let total_ticks = boundary_states.last().expect("initial state").tick as usize;
+    boundary_states[..boundary_states.len() - 1]
+        .iter()
+        .enumerate()
+        .map(|(chunk_idx, state)| {
+            let start_tick = chunk_idx * chunk_size;
+            let ticks = chunk_size.min(total_ticks - start_tick);
+            let env = chunk_env(state, &fp_input.transcript, start_tick, ticks)?;
+            execute(env, CHICKENZ_CHUNK_GUEST_ELF, &format!("chunk {chunk_idx}"))
+        })
+        .collect()
+}
+
+// ============================================================================
+// Boundless remote proving (enabled with --features boundless)
+// ============================================================================
+
+#[cfg(feature = "boundless")]
+async fn boundless_client(log: &Logger) -> Result<boundless_market::Client> {
+    use boundless_market::storage::{StorageUploaderConfig, StorageUploaderType};
+    use boundless_market::Client;
+
+    let env_var = |name: &str, hint: &str| {
+        std::env::var(name)
+            .map_err(|_| PipelineError::InvalidOptions(format!("{name} env var required ({hint})")))
+    };
+    let rpc_url: url::Url = env_var("RPC_URL", "e.g. https://sepolia.base.org")?
+        .parse()
+        .map_err(|_| PipelineError::InvalidOptions("Invalid RPC_URL".into()))?;
+    let private_key: alloy::signers::local::PrivateKeySigner =
+        env_var("PRIVATE_KEY", "hex with 0x prefix")?
+            .parse()
+            .map_err(|_| PipelineError::InvalidOptions("Invalid PRIVATE_KEY".into()))?;
+    let pinata_jwt = env_var("PINATA_JWT", "for uploading ELF/input to IPFS")?;
+
+    // Storage config for Pinata (IPFS)
+    let storage_config = StorageUploaderConfig::builder()
+        .storage_uploader(StorageUploaderType::Pinata)
+        .pinata_jwt(pinata_jwt)
+        .build()
+        .map_err(|e| PipelineError::Env(format!("storage config: {e}")))?;
+
+    log.log("Connecting to Boundless market...");
+    Client::builder()
+        .with_rpc_url(rpc_url)
+        .with_uploader_config(&storage_config)
+        .await
+        .map_err(|e| PipelineError::Env(format!("storage uploader: {e}")))?
+        .with_private_key(private_key)
+        .build()
+        .await
+        .map_err(|e| PipelineError::Env(format!("Boundless client: {e}")))
+}
+
+#[cfg(feature = "boundless")]
+pub async fn prove_boundless(fp_input: &FpProverInput, opts: &ProveOpts) -> Result<ProofArtifacts> {
+    use crate::boundless::{self, BoundlessRequest, Resume};
+
+    require_arena(fp_input, "Boundless")?;
+    let log = &opts.log;
+    let state = opts.boundless_state.as_deref();
+
+    // 1. An earlier run's open request for this input is waited for, not paid for again
+    if let Some(path) = state {
+        if let Resume::Attach(request) =
+            boundless::resume(path, fp_input, &CHICKENZ_GUEST_ID, checkpoint::now())?
+        {
+            log.log(format!("Resuming Boundless request {} from {}", request.request_id, path.display()));
+            let client = boundless_client(log).await?;
+            let pacing = Some(crate::audit::replay_damage_pacing(fp_input));
+            return finish_boundless(&client, &request, opts.artifacts_path.as_deref(), state, pacing, log).await;
+        }
+    }
+
+    // 2. Encode input as raw bytes (same encoding as monolithic)
+    let raw_bytes = fp::encode_raw_input(fp_input);
+    let byte_len = raw_bytes.len() as u32;
+    let words = bytes_to_words(&raw_bytes);
+
+    // Build stdin byte stream matching ExecutorEnv::write_slice layout
+    let mut stdin_bytes: Vec<u8> = Vec::new();
+    stdin_bytes.extend_from_slice(&fp_input.session_id.to_le_bytes());
+    stdin_bytes.extend_from_slice(&(fp_input.mirrored as u32).to_le_bytes());
+    stdin_bytes.extend_from_slice(&byte_len.to_le_bytes());
+    for word in fp::MatchRules::of(&fp_input.match_config()).to_words().iter().chain(&words) {
+        stdin_bytes.extend_from_slice(&word.to_le_bytes());
+    }
+    log.log(format!("Input encoded: {} raw bytes → {} stdin bytes", raw_bytes.len(), stdin_bytes.len()));
+
+    // 3. Build Boundless client (RPC_URL, PRIVATE_KEY, PINATA_JWT)
+    let client = boundless_client(log).await?;
+
+    // 4. Submit proof request (monolithic guest, standalone Groth16)
+    // Using default pricing (SDK maximizes fulfillment chances)
+    log.log("Submitting proof request to Boundless...");
+    let request = client
+        .new_request()
+        .with_program(CHICKENZ_GUEST_ELF)
+        .with_stdin(stdin_bytes)
+        .with_groth16_proof();
+
+    let (request_id, expires_at) = client
+        .submit_onchain(request)
+        .await
+        .map_err(|e| PipelineError::Prove(format!("submit: {e}")))?;
+    log.log(format!("Submitted! Request ID: {:x}", request_id));
+    log.log(format!("Expires at: {}", expires_at));
+
+    // 5. Record it before anything else can fail, so a restart picks it up
+    let request = BoundlessRequest::new(
+        fp_input,
+        &CHICKENZ_GUEST_ID,
+        format!("{request_id:x}"),
+        expires_at,
+        opts.artifacts_path.clone(),
+    );
+    if let Some(path) = state {
+        request.write(path)?;
+        log.log(format!("Request recorded in {}", path.display()));
+    }
+
+    let pacing = Some(crate::audit::replay_damage_pacing(fp_input));
+    finish_boundless(&client, &request, opts.artifacts_path.as_deref(), state, pacing, log).await
+}
+
+/// Re-attach to the request recorded at `state` (by an interrupted `--boundless` run), wait for
+/// it and write its artifacts where that run would have. Needs the same env vars as
+/// [`prove_boundless`].
+#[cfg(feature = "boundless")]
+pub async fn boundless_status(state: &std::path::Path, opts: &ProveOpts) -> Result<ProofArtifacts> {
+    let log = &opts.log;
+    let request = crate::boundless::BoundlessRequest::load(state)?;
+    if request.image_id != hex::encode(image_id_bytes(&CHICKENZ_GUEST_ID)) {
+        return Err(PipelineError::Checkpoint(format!(
+            "request {} runs guest image {}, this build proves {}",
+            request.request_id,
+            request.image_id,
+            hex::encode(image_id_bytes(&CHICKENZ_GUEST_ID))
+        )));
+    }
+    if request.is_expired(checkpoint::now()) {
+        return Err(PipelineError::Prove(format!(
+            "request {} expired at {}; submit it again with --boundless",
+            request.request_id, request.expires_at
+        )));
+    }
+    log.log(format!("Re-attaching to Boundless request {}", request.request_id));
+    let client = boundless_client(log).await?;
+    let artifacts_path = request.artifacts_path.clone();
+    finish_boundless(&client, &request, artifacts_path.as_deref(), Some(state), None, log).await
+}
+
+/// Poll `request` until it is fulfilled (or expires), then write its artifacts to
+/// `artifacts_path` and drop the record at `state`: the part of a Boundless run that can be
+/// picked up again after a restart.
+#[cfg(feature = "boundless")]
+async fn finish_boundless(
+    client: &boundless_market::Client,
+    request: &crate::boundless::BoundlessRequest,
+    artifacts_path: Option<&std::path::Path>,
+    state: Option<&std::path::Path>,
+    damage_pacing: Option<Vec<u16>>,
+    log: &Logger,
+) -> Result<ProofArtifacts> {
+    use boundless_market::contracts::FulfillmentData;
+
+    let request_id = alloy::primitives::U256::from_str_radix(&request.request_id, 16)
+        .map_err(|e| PipelineError::Checkpoint(format!("request id {}: {e}", request.request_id)))?;
+    log.log("Waiting for proof generation (polling every 5s)...");
+    let boundless_start = Instant::now();
+
+    // Wait for fulfillment
+    let fulfillment = client
+        .wait_for_request_fulfillment(request_id, Duration::from_secs(5), request.expires_at)
+        .await
+        .map_err(|e| PipelineError::Prove(format!("fulfillment: {e}")))?;
+    let boundless_elapsed = boundless_start.elapsed();
+    log.log(format!("Boundless proof fulfilled in {:.1}s", boundless_elapsed.as_secs_f64()));
+
+    // Extract seal and journal
+    let seal = fulfillment.seal.to_vec();
+    let fulfillment_data = fulfillment
+        .data()
+        .map_err(|e| PipelineError::Journal(format!("fulfillment data: {e}")))?;
+    let journal: Vec<u8> = match fulfillment_data {
+        FulfillmentData::ImageIdAndJournal(_, journal) => journal.to_vec(),
+        _ => {
+            return Err(PipelineError::Journal(
+                "Unexpected fulfillment data type (expected ImageIdAndJournal)".into(),
+            ))
+        }
+    };
+
+    let output = decode_output(&journal)?;
+    log.log(format!("Proof received! Seal: {} bytes, Journal: {} bytes", seal.len(), journal.len()));
+
+    // Write proof_artifacts.json (same format as local proving), then forget the request
+    let artifacts = ProofArtifacts {
+        seal,
+        image_id: CHICKENZ_GUEST_ID,
+        journal,
+        output,
+        total_cycles: 0,
+        receipt: None,
+        damage_pacing,
+    };
+    if let Some(path) = artifacts_path {
+        artifacts.write(path)?;
+        log.log(format!("Artifacts written to {}", path.display()));
+    }
+    if let Some(path) = state {
+        crate::boundless::complete(path)?;
+    }
+    Ok(artifacts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chickenz_core::fp::NULL_INPUT;
+
+    /// Dev mode skips real proving; receipts are fake but verify while dev mode is on.
+    fn dev_opts(chunk_size: usize) -> ProveOpts {
+        std::env::set_var("RISC0_DEV_MODE", "1");
+        ProveOpts {
+            groth16: false,
+            chunk_size,
+            artifacts_path: None,
+            checkpoint: None,
+            resume_from: None,
+            jobs: 1,
+            cache_dir: None,
+            boundless_state: None,
+            log: Logger::silent(),
+        }
+    }
+
+    fn idle_input(ticks: usize) -> FpProverInput {
+        FpProverInput {
+            session_id: 7,
+            seed: 42,
+            mirrored: false,
+            transcript: vec![[NULL_INPUT; 2]; ticks],
+            map: None,
+            gen_seed: None,
+            rules: None,
+        }
+    }
+
+    /// The arena with a hazard floor: idle players on it die, which the arena never does. The
+    /// match is over at tick 90.
+    fn hazard_floor_input(ticks: usize) -> FpProverInput {
+        let mut map = fp::arena_map();
+        map.platforms[0].hazard_damage = 40;
+        FpProverInput { map: Some(map), ..idle_input(ticks) }
+    }
+
+    #[test]
+    fn monolithic_dev_mode_proves_and_verifies() {
+        let input = idle_input(100);
+        let artifacts = prove_monolithic(&input, &dev_opts(DEFAULT_CHUNK_SIZE)).unwrap();
+        assert_eq!(artifacts.image_id, CHICKENZ_GUEST_ID);
+        assert_eq!(artifacts.output.seed_commit, fp::hash_seed(42));
+        assert_eq!(artifacts.output.session_id, 7);
+        assert_eq!(artifacts.output.config_commit, fp::FpMatchConfig::default().commitment());
+        assert_eq!(artifacts.output.transcript_hash, fp::hash_transcript(&input.transcript));
+        assert!(!artifacts.is_submittable());
+        assert_eq!(verify(&artifacts).unwrap(), artifacts.output);
+    }
+
+    #[test]
+    fn chunked_dev_mode_proves_and_verifies() {
+        // An idle arena match runs out of time in its fifth chunk; the last two aren't proved
+        let input = idle_input(2400);
+        let lines = Arc::new(std::sync::Mutex::new(Vec::<String>::new()));
+        let sink = lines.clone();
+        let mut opts = dev_opts(DEFAULT_CHUNK_SIZE);
+        opts.log = Logger::new(move |m| sink.lock().unwrap().push(m.to_string()));
+        let artifacts = prove_chunked(&input, &opts).unwrap();
+        assert_eq!(artifacts.image_id, CHICKENZ_MATCH_GUEST_ID);
+        assert_eq!(artifacts.output.seed_commit, fp::hash_seed(42));
+        assert_eq!(artifacts.output.session_id, 7);
+        assert_eq!(verify(&artifacts).unwrap(), artifacts.output);
+
+        let lines = lines.lock().unwrap();
+        assert!(lines.iter().any(|l| l.starts_with("Match over at tick ") && l.ends_with("proving 5 of 7 chunks")));
+        assert_eq!(lines.iter().filter(|l| l.starts_with("  Chunk ")).count(), 5);
+    }
+
+    #[test]
+    fn chunked_and_monolithic_commit_the_same_transcript_hash() {
+        // Over at tick 90, with ticks after it: both modes hash all 100
+        let input = hazard_floor_input(100);
+        let expected = fp::hash_transcript(&input.transcript);
+        for chunk_size in [20, 40, 100] {
+            let artifacts = prove_chunked(&input, &dev_opts(chunk_size)).unwrap();
+            assert_eq!(artifacts.output.transcript_hash, expected, "chunk size {chunk_size}");
+        }
+        let arena = idle_input(2400);
+        let chunked = prove_chunked(&arena, &dev_opts(DEFAULT_CHUNK_SIZE)).unwrap().output;
+        let monolithic = prove_monolithic(&arena, &dev_opts(DEFAULT_CHUNK_SIZE)).unwrap().output;
+        assert_eq!(chunked.transcript_hash, monolithic.transcript_hash);
+        assert_eq!(chunked.transcript_hash, fp::hash_transcript(&arena.transcript));
+    }
+
+    #[test]
+    fn chunked_rejects_an_unfinished_match() {
+        let err = prove_chunked(&idle_input(100), &dev_opts(40)).unwrap_err();
+        assert!(matches!(err, PipelineError::Input(_)), "{err}");
+    }
+
+    #[test]
+    fn chunked_dev_mode_proves_a_custom_map() {
+        let input = hazard_floor_input(100);
+        let artifacts = prove_chunked(&input, &dev_opts(40)).unwrap();
+        let replayed = crate::audit::replay_output(&input);
+        assert_ne!(replayed.env_kills, [0; 4]);
+        assert_ne!(replayed.map_hash, fp::map_hash(&fp::arena_map()));
+        assert_eq!(artifacts.output, replayed);
+        assert_eq!(artifacts.output.map_hash, fp::map_hash(&input.oriented_map()));
+        assert_eq!(verify(&artifacts).unwrap(), artifacts.output);
+
+        let mirrored = FpProverInput { mirrored: true, ..input.clone() };
+        let artifacts = prove_chunked(&mirrored, &dev_opts(40)).unwrap();
+        assert_eq!(artifacts.output.map_hash, fp::map_hash(&mirrored.oriented_map()));
+
+        // A generated map goes to the composer as its seed, and the journal commits to its hash
+        let generated = FpProverInput { gen_seed: Some(77), ..idle_input(1800) };
+        let artifacts = prove_chunked(&generated, &dev_opts(DEFAULT_CHUNK_SIZE)).unwrap();
+        let map = fp::generate_map(77, fp::MapGenParams::default());
+        assert_eq!(artifacts.output.map_hash, fp::map_hash(&map));
+        let replayed = crate::audit::replay_output(&generated);
+        assert_eq!(artifacts.output, replayed);
+    }
+
+    #[test]
+    fn composer_rejects_a_chunk_on_another_map() {
+        std::env::set_var("RISC0_DEV_MODE", "1");
+        let custom = hazard_floor_input(100);
+        let state = fp::create_initial_state_with(custom.seed, &custom.oriented_map(), &custom.match_config());
+        let prover = risc0_zkvm::default_prover();
+        let env = chunk_env(&state, &custom.transcript, 0, 100).unwrap();
+        let chunk = prover.prove(env, CHICKENZ_CHUNK_GUEST_ELF).unwrap().receipt;
+        assert_eq!(fp::ChunkProof::from_journal_bytes(&chunk.journal.bytes).map_hash, fp::map_hash(&state.map));
+
+        let env = composer_env(&custom, std::slice::from_ref(&chunk)).unwrap();
+        assert!(prover.prove(env, CHICKENZ_MATCH_GUEST_ELF).is_ok());
+        // The same chunk composed into an arena match
+        let env = composer_env(&idle_input(100), &[chunk]).unwrap();
+        assert!(prover.prove(env, CHICKENZ_MATCH_GUEST_ELF).is_err());
+    }
+
+    #[test]
+    fn composer_rejects_a_broken_chunk_sequence() {
+        std::env::set_var("RISC0_DEV_MODE", "1");
+        let input = hazard_floor_input(120);
+        let prover = risc0_zkvm::default_prover();
+        let mut state = fp::create_initial_state_with(input.seed, &input.oriented_map(), &input.match_config());
+        // Ticks 0..40, 40..80, 80..100 (the match ends at 90), then a chunk past the end
+        let mut chunks = Vec::new();
+        for (start, ticks) in [(0, 40), (40, 40), (80, 20), (100, 20)] {
+            let env = chunk_env(&state, &input.transcript, start, ticks).unwrap();
+            chunks.push(prover.prove(env, CHICKENZ_CHUNK_GUEST_ELF).unwrap().receipt);
+            for tick in &input.transcript[start..start + ticks] {
+                fp::step_mut(&mut state, tick);
+                if state.match_over {
+                    break;
+                }
+            }
+        }
+        let compose = |chunks: &[risc0_zkvm::Receipt]| {
+            prover.prove(composer_env(&input, chunks).unwrap(), CHICKENZ_MATCH_GUEST_ELF)
+        };
+        assert!(compose(&chunks[..3]).is_ok());
+        // Stops before the match is over
+        assert!(compose(&chunks[..2]).is_err());
+        // Skips the middle chunk
+        assert!(compose(&[chunks[0].clone(), chunks[2].clone()]).is_err());
+        // Goes on after the chunk that ended the match (its states chain, tick 90 onwards)
+        let trailing = fp::ChunkProof::from_journal_bytes(&chunks[3].journal.bytes);
+        assert_eq!(trailing.state_hash_in, fp::ChunkProof::from_journal_bytes(&chunks[2].journal.bytes).state_hash_out);
+        assert!(compose(&chunks).is_err());
+        assert!(compose(&[]).is_err());
+    }
+
+    #[test]
+    fn chunk_journal_of_another_sim_version_is_rejected() {
+        let state = fp::create_initial_state(42, &fp::arena_map());
+        let proof = fp::ChunkProof {
+            state_hash_in: fp::hash_state(&state),
+            state_hash_out: [2; 32],
+            input_hash: [3; 32],
+            tick_start: 0,
+            tick_end: 40,
+            scores: [0; 4],
+            match_over: false,
+            winner: -1,
+            env_kills: [0; 4],
+            map_hash: fp::map_hash(&state.map),
+            health: [100; 4],
+            lives: [1; 4],
+            sim_version: fp::SIM_VERSION,
+            pickup_stats_commit: [0; 32],
+        };
+        let journal = |p: &fp::ChunkProof| -> Vec<u8> { p.to_words().iter().flat_map(|w| w.to_le_bytes()).collect() };
+        assert_eq!(check_chunk_journal(0, &journal(&proof)).unwrap().tick_end, 40);
+
+        let stale = fp::ChunkProof { sim_version: fp::SIM_VERSION - 1, ..proof.clone() };
+        match check_chunk_journal(3, &journal(&stale)) {
+            Err(PipelineError::Journal(m)) => assert!(m.starts_with("chunk 3 (ticks 0..40): sim version"), "{m}"),
+            other => panic!("expected a journal error, got ok={}", other.is_ok()),
+        }
+        // Nor does a journal from before the version word pass
+        assert!(check_chunk_journal(0, &journal(&proof)[..176]).is_err());
+    }
+
+    #[test]
+    fn chunked_proves_chunks_concurrently() {
+        let input = hazard_floor_input(100);
+        let lines = Arc::new(std::sync::Mutex::new(Vec::<String>::new()));
+        let sink = lines.clone();
+        let mut opts = dev_opts(20);
+        opts.jobs = 2;
+        opts.log = Logger::new(move |m| sink.lock().unwrap().push(m.to_string()));
+        let artifacts = prove_chunked(&input, &opts).unwrap();
+        assert_eq!(verify(&artifacts).unwrap(), artifacts.output);
+        assert_eq!(artifacts.output, prove_chunked(&input, &dev_opts(20)).unwrap().output);
+
+        let lines = lines.lock().unwrap();
+        let mut chunks: Vec<_> = lines.iter().filter(|l| l.starts_with("  Chunk ")).collect();
+        chunks.sort();
+        assert_eq!(chunks.len(), 5);
+        assert!(chunks[4].starts_with("  Chunk 5/5:"));
+        assert!(lines.iter().any(|l| l.starts_with("All chunks proved") && l.contains("on 2 job(s)")));
+
+        for jobs in [0, MAX_JOBS + 1] {
+            let opts = ProveOpts { jobs, ..dev_opts(20) };
+            assert!(matches!(prove_chunked(&input, &opts), Err(PipelineError::InvalidOptions(_))));
+        }
+    }
+
+    #[test]
+    fn to_fp_input_keeps_the_arena_implicit() {
+        // The server's prover input: snake_case config, platforms as the TS map has them
+        let json = serde_json::json!({
+            "session_id": 3,
+            "config": {
+                "seed": 42, "player_count": 2, "tick_rate": 60, "initial_lives": 1,
+                "match_duration_ticks": 1800, "sudden_death_start_tick": 1200,
+                "map": {
+                    "width": 960, "height": 540,
+                    "platforms": [
+                        { "x": 0, "y": 512, "width": 960, "height": 32 },
+                        { "x": 128, "y": 416, "width": 176, "height": 16, "oneWay": true },
+                        { "x": 672, "y": 416, "width": 176, "height": 16, "oneWay": true },
+                        { "x": 352, "y": 304, "width": 256, "height": 16, "oneWay": true },
+                        { "x": 64, "y": 208, "width": 144, "height": 16, "oneWay": true },
+                        { "x": 752, "y": 208, "width": 144, "height": 16, "oneWay": true },
+                    ],
+                    "spawn_points": [
+                        { "x": 144, "y": 480 }, { "x": 832, "y": 480 },
+                        { "x": 432, "y": 272 }, { "x": 480, "y": 176 },
+                    ],
+                    "weapon_spawn_points": [
+                        { "x": 192, "y": 384 }, { "x": 736, "y": 384 },
+                        { "x": 464, "y": 272 }, { "x": 464, "y": 480 },
+                    ],
+                },
+            },
+            "transcript": [],
+        });
+        let mut input: ProverInput = serde_json::from_value(json).unwrap();
+        assert_eq!(input.config.map, chickenz_core::prover_arena());
+        assert_eq!(quantize_map(&input.config.map), fp::arena_map());
+        assert!(to_fp_input(&input).map.is_none());
+        assert!(to_fp_input(&input).rules.is_none());
+        // The f64 core's arena is another layout, so it's proved as the map it is
+        let f64_arena = ProverInput { config: chickenz_core::default_config(42), ..input.clone() };
+        assert_eq!(to_fp_input(&f64_arena).map, Some(quantize_map(&chickenz_core::arena())));
+
+        input.config.map.platforms[0].hazard_damage = 40;
+        let fp_input = to_fp_input(&input);
+        assert_eq!(fp_input.map, hazard_floor_input(0).map);
+
+        // A generated map is carried by its seed alone
+        input.config.gen_seed = Some(9);
+        let fp_input = to_fp_input(&input);
+        assert_eq!((fp_input.map, fp_input.gen_seed), (None, Some(9)));
+
+        // Custom rules are carried, and left out of a standard input's JSON
+        input.config.initial_lives = 99;
+        let fp_input = to_fp_input(&input);
+        let rules = fp::MatchRules { initial_lives: 99, ..fp::MatchRules::default() };
+        assert_eq!(fp_input.rules, Some(rules));
+        assert_eq!(fp_input.match_config().initial_lives, 99);
+        let json = serde_json::to_value(&fp_input).unwrap();
+        let back: FpProverInput = serde_json::from_value(json).unwrap();
+        assert_eq!(back.rules, Some(rules));
+        assert!(serde_json::to_value(idle_input(1)).unwrap().get("rules").is_none());
+    }
+
+    #[test]
+    fn monolithic_rejects_a_custom_map() {
+        let input = hazard_floor_input(10);
+        match prove_monolithic(&input, &dev_opts(DEFAULT_CHUNK_SIZE)) {
+            Err(PipelineError::InvalidOptions(_)) => {}
+            other => panic!("expected InvalidOptions, got ok={}", other.is_ok()),
+        }
+        // The arena spelled out is still the arena
+        let arena = FpProverInput { map: Some(fp::arena_map()), ..idle_input(10) };
+        assert!(prove_monolithic(&arena, &dev_opts(DEFAULT_CHUNK_SIZE)).is_ok());
+    }
+
+    #[test]
+    fn mirrored_match_commits_orientation() {
+        let input = FpProverInput { mirrored: true, ..idle_input(1800) };
+        let mono = prove_monolithic(&input, &dev_opts(DEFAULT_CHUNK_SIZE)).unwrap();
+        let chunked = prove_chunked(&input, &dev_opts(DEFAULT_CHUNK_SIZE)).unwrap();
+        let expected = input.match_config().commitment();
+        assert_ne!(expected, fp::FpMatchConfig::default().commitment());
+        assert_eq!(mono.output.config_commit, expected);
+        assert_eq!(chunked.output.config_commit, expected);
+    }
+
+    #[test]
+    fn warmup_rules_are_proved_and_committed() {
+        let rules = fp::MatchRules { initial_lives: 99, match_duration: 900, sudden_death: 900 };
+        let input = FpProverInput { rules: Some(rules), ..idle_input(900) };
+        let mono = prove_monolithic(&input, &dev_opts(DEFAULT_CHUNK_SIZE)).unwrap();
+        let chunked = prove_chunked(&input, &dev_opts(300)).unwrap();
+        let expected = input.match_config().commitment();
+        assert_ne!(expected, fp::FpMatchConfig::default().commitment());
+        for output in [&mono.output, &chunked.output] {
+            assert_eq!(output.config_commit, expected);
+            assert_eq!(output.final_tick, 900);
+            assert_eq!(output.final_lives[..2], [99, 99]);
+        }
+        assert_eq!(mono.output, crate::audit::replay_output(&input));
+
+        // The standard rules would have run on to tick 1800, so 900 ticks don't finish the match
+        let standard = FpProverInput { rules: None, ..input };
+        assert!(prove_chunked(&standard, &dev_opts(300)).is_err());
+    }
+
+    #[test]
+    fn executor_counts_the_chunks_prove_chunked_would_prove() {
+        // Over at tick 90: chunks of 40 stop after the third
+        let chunks = execute_chunked(&hazard_floor_input(100), 40).unwrap();
+        assert_eq!(chunks.len(), 3);
+        for cycles in &chunks {
+            assert!(cycles.user > 0 && cycles.total >= cycles.user && cycles.segments > 0, "{cycles:?}");
+        }
+        assert!(chunks[2].user < chunks[0].user, "the last chunk runs 10 ticks, not 40");
+
+        let whole = execute_monolithic(&idle_input(100)).unwrap();
+        assert!(whole.user > 0 && whole.total >= whole.user);
+        assert!(matches!(execute_chunked(&idle_input(10), 0), Err(PipelineError::InvalidOptions(_))));
+    }
+
+    #[test]
+    fn chunked_rejects_bad_chunk_size() {
+        let input = idle_input(10);
+        for size in [0, MAX_CHUNK_SIZE + 1] {
+            match prove_chunked(&input, &dev_opts(size)) {
+                Err(PipelineError::InvalidOptions(_)) => {}
+                other => panic!("expected InvalidOptions, got ok={}", other.is_ok()),
+            }
+        }
+    }
+
+    #[test]
+    fn logger_captures_progress() {
+        let lines = Arc::new(std::sync::Mutex::new(Vec::<String>::new()));
+        let sink = lines.clone();
+        let mut opts = dev_opts(DEFAULT_CHUNK_SIZE);
+        opts.log = Logger::new(move |m| sink.lock().unwrap().push(m.to_string()));
+        prove_monolithic(&idle_input(10), &opts).unwrap();
+        let lines = lines.lock().unwrap();
+        assert!(lines.iter().any(|l| l.starts_with("Converted to raw bytes:")));
+        assert!(lines.iter().any(|l| l == "Receipt verified locally."));
+    }
+
+    fn scratch_checkpoint(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("chickenz-host-{}-{name}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir.join("checkpoint.json")
+    }
+
+    #[test]
+    fn chunked_resumes_after_interruption_between_chunks() {
+        use checkpoint::{Checkpoint, StageStatus::*};
+
+        let input = hazard_floor_input(100);
+        let path = scratch_checkpoint("resume");
+        let mut opts = dev_opts(40);
+        opts.checkpoint = Some(path.clone());
+        // Kill the run once the second chunk is reported: it is checkpointed, the third isn't.
+        opts.log = Logger::new(|m| {
+            if m.starts_with("  Chunk 2/3:") {
+                panic!("interrupted");
+            }
+        });
+        let run = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            prove_chunked(&input, &opts)
+        }));
+        assert!(run.is_err());
+
+        let doc = Checkpoint::load(&path).unwrap();
+        let statuses: Vec<_> = doc.chunks.iter().map(|c| c.stage.status).collect();
+        assert_eq!(statuses, [Done, Done, Pending]);
+        assert_eq!(doc.composer.status, Pending);
+        assert!(doc.chunks[1].stage.receipt.as_ref().unwrap().exists());
+
+        let lines = Arc::new(std::sync::Mutex::new(Vec::<String>::new()));
+        let sink = lines.clone();
+        let mut resume = dev_opts(40);
+        resume.resume_from = Some(path.clone());
+        resume.log = Logger::new(move |m| sink.lock().unwrap().push(m.to_string()));
+        let artifacts = prove_chunked(&input, &resume).unwrap();
+        let fresh = prove_chunked(&input, &dev_opts(40)).unwrap();
+        assert_eq!(artifacts.output, fresh.output);
+        assert_eq!(verify(&artifacts).unwrap(), artifacts.output);
+
+        let lines = lines.lock().unwrap();
+        assert!(lines.iter().any(|l| l == "Resuming from checkpoint: 2/3 chunks done"));
+        assert!(lines.iter().any(|l| l.starts_with("  Chunk 2/3: reused from checkpoint")));
+        assert!(lines.iter().any(|l| l.starts_with("  Chunk 3/3:") && !l.contains("reused")));
+
+        let doc = Checkpoint::load(&path).unwrap();
+        assert!(doc.chunks.iter().all(|c| c.stage.is_done()));
+        assert!(doc.composer.is_done());
+        let _ = std::fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[test]
+    fn chunked_reproves_only_chunks_missing_from_the_cache() {
+        let input = hazard_floor_input(100);
+        let dir = scratch_checkpoint("cache").with_file_name("cache");
+        let mut opts = dev_opts(40);
+        opts.cache_dir = Some(dir.clone());
+        let first = prove_chunked(&input, &opts).unwrap();
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 3);
+
+        // Drop the third chunk's entry: the state at tick 80 replaying ticks 80..100
+        let mut state = fp::create_initial_state_with(input.seed, &input.oriented_map(), &input.match_config());
+        for tick in &input.transcript[..80] {
+            fp::step_mut(&mut state, tick);
+        }
+        let cache = ChunkCache::open(&dir).unwrap();
+        let inputs = encode_chunk_inputs(&input.transcript, 80, 20);
+        let third = cache.path(&cache::chunk_key(&CHICKENZ_CHUNK_GUEST_ID, &state, &inputs));
+        std::fs::remove_file(&third).unwrap();
+
+        let lines = Arc::new(std::sync::Mutex::new(Vec::<String>::new()));
+        let sink = lines.clone();
+        opts.log = Logger::new(move |m| sink.lock().unwrap().push(m.to_string()));
+        let rerun = prove_chunked(&input, &opts).unwrap();
+        assert_eq!(rerun.output, first.output);
+        assert_eq!(verify(&rerun).unwrap(), rerun.output);
+
+        let lines = lines.lock().unwrap();
+        let chunks: Vec<_> = lines.iter().filter(|l| l.starts_with("  Chunk ")).collect();
+        assert_eq!(chunks.len(), 3);
+        assert!(chunks[0].starts_with("  Chunk 1/3: reused from cache"));
+        assert!(chunks[1].starts_with("  Chunk 2/3: reused from cache"));
+        assert!(chunks[2].starts_with("  Chunk 3/3:") && !chunks[2].contains("reused"));
+        assert!(third.exists());
+        let _ = std::fs::remove_dir_all(dir.parent().unwrap());
+    }
+
+    #[test]
+    fn resume_rejects_checkpoint_for_other_input() {
+        let path = scratch_checkpoint("mismatch");
+        let mut opts = dev_opts(40);
+        opts.checkpoint = Some(path.clone());
+        prove_chunked(&hazard_floor_input(100), &opts).unwrap();
+
+        let other_seed = FpProverInput { seed: 43, ..hazard_floor_input(100) };
+        for (input, chunk_size) in [(other_seed, 40), (hazard_floor_input(100), 50)] {
+            let mut resume = dev_opts(chunk_size);
+            resume.resume_from = Some(path.clone());
+            assert!(matches!(prove_chunked(&input, &resume), Err(PipelineError::Checkpoint(_))));
+        }
+        let _ = std::fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[test]
+    fn verify_rejects_tampered_output() {
+        let mut artifacts = prove_monolithic(&idle_input(10), &dev_opts(DEFAULT_CHUNK_SIZE)).unwrap();
+        artifacts.output.scores[0] += 1;
+        assert!(matches!(verify(&artifacts), Err(PipelineError::Verify(_))));
+    }
+
+    #[test]
+    fn artifacts_json_round_trips_with_the_receipt() {
+        let artifacts = prove_monolithic(&idle_input(10), &dev_opts(DEFAULT_CHUNK_SIZE)).unwrap();
+        let back = ProofArtifacts::from_json(&artifacts.to_json_with_receipt()).unwrap();
+        assert_eq!((back.image_id, &back.journal, &back.output), (artifacts.image_id, &artifacts.journal, &artifacts.output));
+        assert_eq!(verify(&back).unwrap(), artifacts.output);
+
+        // proof_artifacts.json has no receipt to verify
+        let file = ProofArtifacts::from_json(&artifacts.to_json()).unwrap();
+        assert_eq!(file.output, artifacts.output);
+        // An idle match deals no damage, in every bucket its duration uses
+        let buckets = fp::pacing_buckets(idle_input(10).match_config().match_duration);
+        assert_eq!(file.damage_pacing, Some(vec![0; buckets]));
+        assert_eq!(back.damage_pacing, file.damage_pacing);
+        assert!(matches!(verify(&file), Err(PipelineError::Verify(_))));
+        let mut json = artifacts.to_json();
+        json["image_id"] = "abcd".into();
+        assert!(matches!(ProofArtifacts::from_json(&json), Err(PipelineError::Verify(_))));
+    }
+}
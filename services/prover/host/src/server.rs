@@ -0,0 +1,410 @@
+//! HTTP sidecar for remote prove requests (the `server` feature).
+//!
+//! [`serve`] answers three endpoints, JSON in and out:
+//!
+//! - `POST /prove`: a transcript, as `ProverInput` JSON or a transcript file (checked by
+//!   [`input`]). Queues a job and answers `202 {"job": <id>}`, or 503 while the queue is full.
+//! - `GET /jobs/{id}`: the job's `status` (`queued`, `proving`, `done` or `failed`), chunk
+//!   `progress` from its [`checkpoint`] in chunked mode, then its `artifacts` or `error`.
+//! - `POST /verify`: artifacts as a job returned them. Answers `valid` (the receipt verifies
+//!   and its journal is the artifacts') and the `output` decoded from the journal.
+//!
+//! Jobs wait in a bounded in-process queue for one of [`ServeOpts::jobs`] worker threads, each
+//! running the pipeline like `prove` does. Job records are kept in memory only, so a restart
+//! forgets them. A job's artifacts carry its receipt, which proof_artifacts.json doesn't: that is
+//! what `/verify` checks.
+//!
+//! [`checkpoint`]: crate::checkpoint
+
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{self, Receiver, SyncSender, TrySendError};
+use std::sync::{Arc, Mutex};
+
+use axum::body::Bytes;
+use axum::extract::{DefaultBodyLimit, Path, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use chickenz_core::fp::FpProverInput;
+use serde::Serialize;
+use serde_json::{json, Value};
+
+use crate::checkpoint::Checkpoint;
+use crate::input;
+use crate::pipeline::{self, Logger, ProofArtifacts, ProveOpts, Result};
+
+/// Default number of jobs that can wait for a worker.
+pub const DEFAULT_QUEUE: usize = 16;
+/// Largest request body: a full match's transcript JSON, or artifacts with a STARK receipt.
+pub const MAX_BODY_BYTES: usize = 64 << 20;
+/// Default directory for chunked jobs' checkpoints and receipts.
+pub const DEFAULT_WORK_DIR: &str = "serve-jobs";
+
+/// Options for [`serve`].
+#[derive(Clone)]
+pub struct ServeOpts {
+    pub addr: SocketAddr,
+    /// Worker threads, each proving one job at a time (at least 1).
+    pub jobs: usize,
+    /// Jobs that can wait for a worker before `/prove` answers 503.
+    pub queue: usize,
+    pub chunked: bool,
+    /// Chunked mode: where each job's checkpoint (`job-<id>.json`) and receipts are written.
+    pub work_dir: PathBuf,
+    /// Proving options for every job; `artifacts_path` and `checkpoint` are set per job.
+    pub prove: ProveOpts,
+}
+
+impl ServeOpts {
+    pub fn new(addr: SocketAddr) -> Self {
+        ServeOpts {
+            addr,
+            jobs: 1,
+            queue: DEFAULT_QUEUE,
+            chunked: false,
+            work_dir: PathBuf::from(DEFAULT_WORK_DIR),
+            prove: ProveOpts::default(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum JobStatus {
+    Queued,
+    Proving,
+    Done,
+    Failed,
+}
+
+struct Job {
+    status: JobStatus,
+    ticks: usize,
+    checkpoint: Option<PathBuf>,
+    artifacts: Option<Value>,
+    error: Option<String>,
+}
+
+type Jobs = Arc<Mutex<HashMap<u64, Job>>>;
+
+/// What the handlers share. Dropping the last copy closes the queue, which stops the workers.
+#[derive(Clone)]
+struct App {
+    opts: Arc<ServeOpts>,
+    jobs: Jobs,
+    next_id: Arc<AtomicU64>,
+    queue: SyncSender<(u64, FpProverInput)>,
+}
+
+/// The endpoints, with `opts.jobs` workers started behind them.
+pub fn router(opts: &ServeOpts) -> Result<Router> {
+    if opts.chunked {
+        std::fs::create_dir_all(&opts.work_dir)?;
+    }
+    let (queue, rx) = mpsc::sync_channel(opts.queue);
+    let rx = Arc::new(Mutex::new(rx));
+    let app = App {
+        opts: Arc::new(opts.clone()),
+        jobs: Jobs::default(),
+        next_id: Arc::new(AtomicU64::new(1)),
+        queue,
+    };
+    for _ in 0..opts.jobs.max(1) {
+        let (opts, jobs, rx) = (app.opts.clone(), app.jobs.clone(), rx.clone());
+        std::thread::spawn(move || work(&opts, &jobs, &rx));
+    }
+    Ok(Router::new()
+        .route("/prove", post(prove))
+        .route("/jobs/:id", get(job))
+        .route("/verify", post(verify))
+        .layer(DefaultBodyLimit::max(MAX_BODY_BYTES))
+        .with_state(app))
+}
+
+/// Listen on `opts.addr` until the process is killed.
+pub async fn serve(opts: &ServeOpts) -> Result<()> {
+    let listener = tokio::net::TcpListener::bind(opts.addr).await?;
+    serve_on(listener, opts).await
+}
+
+/// [`serve`] on a listener the caller bound (port 0 in tests).
+pub async fn serve_on(listener: tokio::net::TcpListener, opts: &ServeOpts) -> Result<()> {
+    opts.prove.log.log(format!(
+        "Serving on {} ({}, {} worker(s), queue of {})",
+        listener.local_addr()?,
+        if opts.chunked { "chunked" } else { "monolithic" },
+        opts.jobs.max(1),
+        opts.queue
+    ));
+    axum::serve(listener, router(opts)?).await?;
+    Ok(())
+}
+
+fn update(jobs: &Jobs, id: u64, f: impl FnOnce(&mut Job)) {
+    if let Some(job) = jobs.lock().expect("job table poisoned").get_mut(&id) {
+        f(job);
+    }
+}
+
+/// Worker loop: prove queued jobs until the queue closes.
+fn work(opts: &ServeOpts, jobs: &Jobs, rx: &Mutex<Receiver<(u64, FpProverInput)>>) {
+    loop {
+        let next = rx.lock().expect("queue lock poisoned").recv();
+        let Ok((id, input)) = next else { return };
+        update(jobs, id, |job| job.status = JobStatus::Proving);
+        let inner = opts.prove.log.clone();
+        let prove_opts = ProveOpts {
+            artifacts_path: None,
+            checkpoint: opts.chunked.then(|| opts.work_dir.join(format!("job-{id}.json"))),
+            resume_from: None,
+            log: Logger::new(move |m| inner.log(format!("[job {id}] {m}"))),
+            ..opts.prove.clone()
+        };
+        let proved = if opts.chunked {
+            pipeline::prove_chunked(&input, &prove_opts)
+        } else {
+            pipeline::prove_monolithic(&input, &prove_opts)
+        };
+        if let Err(e) = &proved {
+            opts.prove.log.log(format!("[job {id}] failed: {e}"));
+        }
+        update(jobs, id, |job| match proved {
+            Ok(artifacts) => {
+                job.status = JobStatus::Done;
+                job.artifacts = Some(artifacts.to_json_with_receipt());
+            }
+            Err(e) => {
+                job.status = JobStatus::Failed;
+                job.error = Some(e.to_string());
+            }
+        });
+    }
+}
+
+fn error(status: StatusCode, message: impl Into<String>) -> Response {
+    (status, Json(json!({ "error": message.into() }))).into_response()
+}
+
+async fn prove(State(app): State<App>, body: Bytes) -> Response {
+    let input = match input::parse_input(&body) {
+        Ok(input) if input.transcript.is_empty() => return error(StatusCode::BAD_REQUEST, "empty transcript"),
+        Ok(input) => input,
+        Err(e) => return error(StatusCode::BAD_REQUEST, e.to_string()),
+    };
+    let id = app.next_id.fetch_add(1, Ordering::Relaxed);
+    let job = Job {
+        status: JobStatus::Queued,
+        ticks: input.transcript.len(),
+        checkpoint: app.opts.chunked.then(|| app.opts.work_dir.join(format!("job-{id}.json"))),
+        artifacts: None,
+        error: None,
+    };
+    app.jobs.lock().expect("job table poisoned").insert(id, job);
+    match app.queue.try_send((id, input)) {
+        Ok(()) => (StatusCode::ACCEPTED, Json(json!({ "job": id }))).into_response(),
+        Err(e) => {
+            app.jobs.lock().expect("job table poisoned").remove(&id);
+            match e {
+                TrySendError::Full(_) => error(StatusCode::SERVICE_UNAVAILABLE, "job queue is full"),
+                TrySendError::Disconnected(_) => error(StatusCode::SERVICE_UNAVAILABLE, "no workers"),
+            }
+        }
+    }
+}
+
+async fn job(State(app): State<App>, Path(id): Path<u64>) -> Response {
+    let jobs = app.jobs.lock().expect("job table poisoned");
+    let Some(job) = jobs.get(&id) else {
+        return error(StatusCode::NOT_FOUND, format!("no job {id}"));
+    };
+    let mut body = json!({ "id": id, "status": job.status, "ticks": job.ticks });
+    // The checkpoint appears once the job starts proving, and is rewritten atomically
+    if let Some(doc) = job.checkpoint.as_deref().and_then(|p| Checkpoint::load(p).ok()) {
+        body["progress"] = json!({
+            "chunks_done": doc.chunks_done(),
+            "chunks": doc.chunks.len(),
+            "composer": doc.composer.status,
+        });
+    }
+    if let Some(artifacts) = &job.artifacts {
+        body["artifacts"] = artifacts.clone();
+    }
+    if let Some(e) = &job.error {
+        body["error"] = e.as_str().into();
+    }
+    Json(body).into_response()
+}
+
+async fn verify(body: Bytes) -> Response {
+    let json: Value = match serde_json::from_slice(&body) {
+        Ok(json) => json,
+        Err(e) => return error(StatusCode::BAD_REQUEST, format!("artifacts are not JSON: {e}")),
+    };
+    let artifacts = match ProofArtifacts::from_json(&json) {
+        Ok(artifacts) => artifacts,
+        Err(e) => return error(StatusCode::BAD_REQUEST, e.to_string()),
+    };
+    // Receipt verification is CPU work; keep it off the request threads
+    let checked = tokio::task::spawn_blocking(move || {
+        let verified = pipeline::verify(&artifacts);
+        (artifacts, verified)
+    })
+    .await;
+    let Ok((artifacts, verified)) = checked else {
+        return error(StatusCode::INTERNAL_SERVER_ERROR, "verifier panicked");
+    };
+    let mut body = json!({ "valid": verified.is_ok(), "output": pipeline::output_json(&artifacts.output) });
+    if let Err(e) = verified {
+        body["error"] = e.to_string().into();
+    }
+    Json(body).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chickenz_core::fp::{self, NULL_INPUT};
+    use chickenz_core::ProverInput;
+    use std::io::{Read, Write};
+    use std::time::{Duration, Instant};
+
+    /// A dev-mode (fast, fake-receipt) server on a free local port, running until dropped.
+    struct TestServer {
+        addr: SocketAddr,
+        _rt: tokio::runtime::Runtime,
+    }
+
+    fn start(name: &str, chunked: bool) -> TestServer {
+        std::env::set_var("RISC0_DEV_MODE", "1");
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let listener = rt.block_on(tokio::net::TcpListener::bind("127.0.0.1:0")).unwrap();
+        let addr = listener.local_addr().unwrap();
+        let mut opts = ServeOpts::new(addr);
+        opts.jobs = 2;
+        opts.chunked = chunked;
+        opts.work_dir = std::env::temp_dir().join(format!("chickenz-serve-{}-{name}", std::process::id()));
+        opts.prove = ProveOpts {
+            groth16: false,
+            chunk_size: 40,
+            artifacts_path: None,
+            checkpoint: None,
+            resume_from: None,
+            jobs: 1,
+            cache_dir: None,
+            boundless_state: None,
+            log: Logger::silent(),
+        };
+        rt.spawn(async move { serve_on(listener, &opts).await.unwrap() });
+        TestServer { addr, _rt: rt }
+    }
+
+    /// One HTTP/1.1 request; the status code and the JSON body.
+    fn request(server: &TestServer, method: &str, path: &str, body: &[u8]) -> (u16, Value) {
+        let mut stream = std::net::TcpStream::connect(server.addr).unwrap();
+        write!(
+            stream,
+            "{method} {path} HTTP/1.1\r\nHost: localhost\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+            body.len()
+        )
+        .unwrap();
+        stream.write_all(body).unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        let status = response[9..12].parse().unwrap();
+        let (_, json) = response.split_once("\r\n\r\n").unwrap();
+        (status, serde_json::from_str(json).unwrap())
+    }
+
+    /// Poll a job until it finishes.
+    fn finished(server: &TestServer, id: &Value) -> Value {
+        let deadline = Instant::now() + Duration::from_secs(120);
+        loop {
+            let (status, job) = request(server, "GET", &format!("/jobs/{id}"), b"");
+            assert_eq!(status, 200);
+            if job["status"] == "done" || job["status"] == "failed" {
+                return job;
+            }
+            assert!(Instant::now() < deadline, "job {id} still {}", job["status"]);
+            std::thread::sleep(Duration::from_millis(50));
+        }
+    }
+
+    fn short_input(session_id: u32) -> FpProverInput {
+        FpProverInput {
+            session_id,
+            seed: 42,
+            mirrored: false,
+            transcript: vec![[NULL_INPUT; 2]; 100],
+            map: None,
+            gen_seed: None,
+            rules: None,
+        }
+    }
+
+    #[test]
+    fn prove_then_verify_over_http() {
+        let server = start("monolithic", false);
+        let input = ProverInput {
+            session_id: 3,
+            config: chickenz_core::MatchConfig {
+                map: chickenz_core::prover_arena(),
+                ..chickenz_core::default_config(42)
+            },
+            transcript: vec![[chickenz_core::NULL_INPUT; 2]; 100],
+        };
+        let (status, queued) = request(&server, "POST", "/prove", serde_json::to_string(&input).unwrap().as_bytes());
+        assert_eq!(status, 202);
+        let job = finished(&server, &queued["job"]);
+        assert_eq!(job["status"], "done", "{job}");
+        assert_eq!(job["ticks"], 100);
+        assert_eq!(job["artifacts"]["output"]["session_id"], 3);
+        assert!(job.get("progress").is_none());
+
+        let artifacts = serde_json::to_vec(&job["artifacts"]).unwrap();
+        let (status, verified) = request(&server, "POST", "/verify", &artifacts);
+        assert_eq!(status, 200);
+        assert_eq!(verified["valid"], true, "{verified}");
+        assert_eq!(verified["output"], job["artifacts"]["output"]);
+
+        // A journal other than the receipt's (here, another transcript hash) decodes but doesn't verify
+        let mut tampered = job["artifacts"].clone();
+        let mut journal = hex::decode(tampered["journal"].as_str().unwrap()).unwrap();
+        journal[20] ^= 1;
+        tampered["journal"] = hex::encode(journal).into();
+        let (status, verified) = request(&server, "POST", "/verify", &serde_json::to_vec(&tampered).unwrap());
+        assert_eq!((status, &verified["valid"]), (200, &Value::Bool(false)));
+        assert!(verified["error"].as_str().unwrap().contains("journal"), "{verified}");
+    }
+
+    #[test]
+    fn chunked_jobs_report_checkpoint_progress() {
+        let server = start("chunked", true);
+        let mut file = Vec::new();
+        fp::write_transcript(&mut file, &short_input(4)).unwrap();
+        let (status, queued) = request(&server, "POST", "/prove", &file);
+        assert_eq!(status, 202);
+        let job = finished(&server, &queued["job"]);
+        assert_eq!(job["status"], "done", "{job}");
+        assert_eq!(job["progress"], json!({ "chunks_done": 3, "chunks": 3, "composer": "done" }));
+        assert_eq!(job["artifacts"]["output"]["session_id"], 4);
+        let (_, verified) = request(&server, "POST", "/verify", &serde_json::to_vec(&job["artifacts"]).unwrap());
+        assert_eq!(verified["valid"], true, "{verified}");
+    }
+
+    #[test]
+    fn bad_requests_are_rejected() {
+        let server = start("errors", false);
+        let (status, body) = request(&server, "POST", "/prove", b"{ not json");
+        assert_eq!(status, 400);
+        assert!(body["error"].as_str().unwrap().contains("bad transcript"), "{body}");
+        let mut empty = Vec::new();
+        fp::write_transcript(&mut empty, &FpProverInput { transcript: Vec::new(), ..short_input(5) }).unwrap();
+        assert_eq!(request(&server, "POST", "/prove", &empty), (400, json!({ "error": "empty transcript" })));
+        assert_eq!(request(&server, "GET", "/jobs/99", b""), (404, json!({ "error": "no job 99" })));
+        assert_eq!(request(&server, "POST", "/verify", b"[]").0, 400);
+    }
+}
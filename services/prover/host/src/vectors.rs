@@ -0,0 +1,265 @@
+//! Cross-platform determinism vectors: state hashes of fixed scenario transcripts.
+//!
+//! The fixed-point sim must produce bit-identical states on every target — we prove on x86_64
+//! but simulate on aarch64. [`generate`] replays each [`SCENARIOS`] entry natively and records
+//! `hash_state` every [`INTERVAL`] ticks; a file generated on one machine is committed
+//! (`determinism-vectors.json`) and [`check`]ed on others, so a divergent cast or stray f64
+//! shows up as the first tick that differs rather than as a failed proof.
+
+use serde::{Deserialize, Serialize};
+
+use chickenz_core::fp::{self, button, FpInput, FpMatchConfig, NULL_INPUT};
+
+use crate::pipeline::{PipelineError, Result};
+
+/// Ticks between recorded state hashes.
+pub const INTERVAL: u32 = 100;
+
+/// A fixed transcript: `inputs(tick)` is a pure function, so nothing is stored but the name.
+pub struct Scenario {
+    pub name: &'static str,
+    pub seed: u32,
+    pub ticks: u32,
+    pub config: fn() -> FpMatchConfig,
+    pub inputs: fn(u32) -> [FpInput; 2],
+}
+
+/// Hash-driven input for player `p` (same mixing as the sim's projectile storm test).
+fn noisy_input(tick: u32, p: u32) -> FpInput {
+    let h = tick.wrapping_mul(0x9E37_79B9) ^ p.wrapping_mul(0x85EB_CA6B);
+    let h = h ^ (h >> 15);
+    FpInput {
+        buttons: [button::LEFT, button::RIGHT, 0][(h % 3) as usize]
+            | if h & 0x300 == 0 { button::JUMP } else { 0 }
+            | if h & 0x400 != 0 { button::SHOOT } else { 0 },
+        aim_x: ((h >> 5) % 3) as i8 - 1,
+        aim_y: ((h >> 9) % 3) as i8 - 1,
+    }
+}
+
+fn noisy(tick: u32) -> [FpInput; 2] {
+    [noisy_input(tick, 0), noisy_input(tick, 1)]
+}
+
+/// Both players run at each other firing, jumping on alternate half-seconds.
+fn duel(tick: u32) -> [FpInput; 2] {
+    let jump = if (tick / 30) % 2 == 1 { button::JUMP } else { 0 };
+    [
+        FpInput { buttons: button::RIGHT | button::SHOOT | jump, aim_x: 1, aim_y: 0 },
+        FpInput { buttons: button::LEFT | button::SHOOT, aim_x: -1, aim_y: 0 },
+    ]
+}
+
+/// Player 0 hugs the left wall (jumping off it periodically); player 1 plays noisy.
+fn wall_hug(tick: u32) -> [FpInput; 2] {
+    let jump = if tick % 45 < 5 { button::JUMP } else { 0 };
+    [FpInput { buttons: button::LEFT | jump, aim_x: 1, aim_y: 0 }, noisy_input(tick, 1)]
+}
+
+fn idle(_tick: u32) -> [FpInput; 2] {
+    [NULL_INPUT; 2]
+}
+
+/// The vector scenarios. Append new ones; changing an existing one invalidates committed files.
+pub const SCENARIOS: &[Scenario] = &[
+    Scenario { name: "idle", seed: 1, ticks: 600, config: FpMatchConfig::default, inputs: idle },
+    Scenario { name: "duel", seed: 42, ticks: 3600, config: FpMatchConfig::default, inputs: duel },
+    Scenario {
+        name: "noisy-lagged",
+        seed: 7,
+        ticks: 3600,
+        config: || FpMatchConfig { lag_ticks: [2, 1], ..FpMatchConfig::default() },
+        inputs: noisy,
+    },
+    Scenario {
+        name: "noisy-mirrored",
+        seed: 7,
+        ticks: 3600,
+        config: || FpMatchConfig { mirrored: true, ..FpMatchConfig::default() },
+        inputs: noisy,
+    },
+    Scenario {
+        name: "wall-stamina",
+        seed: 99,
+        ticks: 1800,
+        config: || FpMatchConfig { stamina: true, ..FpMatchConfig::default() },
+        inputs: wall_hug,
+    },
+    Scenario {
+        name: "pickup-despawn",
+        seed: 5,
+        ticks: 1800,
+        config: || FpMatchConfig { active_despawn: true, ..FpMatchConfig::default() },
+        inputs: idle,
+    },
+];
+
+/// `hash_state` after `tick`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Checkpoint {
+    pub tick: u32,
+    pub state_hash: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScenarioVectors {
+    pub name: String,
+    pub checkpoints: Vec<Checkpoint>,
+    pub winner: i32,
+    pub scores: [u32; 2],
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VectorFile {
+    pub sim_version: u32,
+    pub interval: u32,
+    /// Where the file was generated, e.g. "x86_64-linux" (informational).
+    pub generated_on: String,
+    pub scenarios: Vec<ScenarioVectors>,
+}
+
+/// Replay `scenario`, hashing every `INTERVAL` ticks and on the last tick played.
+pub fn run_scenario(scenario: &Scenario) -> ScenarioVectors {
+    let cfg = (scenario.config)();
+    let map = cfg.oriented_map(&fp::arena_map());
+    let mut state = fp::create_initial_state_with(scenario.seed, &map, &cfg);
+    let mut checkpoints = Vec::new();
+    for tick in 1..=scenario.ticks {
+        fp::step_mut(&mut state, &(scenario.inputs)(tick - 1));
+        if tick % INTERVAL == 0 || tick == scenario.ticks || state.match_over {
+            checkpoints.push(Checkpoint { tick, state_hash: hex::encode(fp::hash_state(&state)) });
+        }
+        if state.match_over {
+            break;
+        }
+    }
+    ScenarioVectors {
+        name: scenario.name.to_string(),
+        checkpoints,
+        winner: state.winner,
+        scores: [state.score[0], state.score[1]],
+    }
+}
+
+/// Vectors for every scenario on this build.
+pub fn generate() -> VectorFile {
+    VectorFile {
+        sim_version: fp::SIM_VERSION,
+        interval: INTERVAL,
+        generated_on: format!("{}-{}", std::env::consts::ARCH, std::env::consts::OS),
+        scenarios: SCENARIOS.iter().map(run_scenario).collect(),
+    }
+}
+
+/// First point where this build disagrees with a recorded scenario.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Divergence {
+    pub scenario: String,
+    /// Tick of the first differing checkpoint; `None` for a whole-scenario mismatch.
+    pub tick: Option<u32>,
+    pub expected: String,
+    pub actual: String,
+}
+
+impl std::fmt::Display for Divergence {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.tick {
+            Some(t) => write!(f, "{} @ tick {t}: ", self.scenario)?,
+            None => write!(f, "{}: ", self.scenario)?,
+        }
+        write!(f, "expected {}, got {}", self.expected, self.actual)
+    }
+}
+
+fn compare(expected: &ScenarioVectors, actual: &ScenarioVectors) -> Option<Divergence> {
+    for (e, a) in expected.checkpoints.iter().zip(&actual.checkpoints) {
+        if e != a {
+            return Some(Divergence {
+                scenario: expected.name.clone(),
+                tick: Some(e.tick.min(a.tick)),
+                expected: e.state_hash.clone(),
+                actual: a.state_hash.clone(),
+            });
+        }
+    }
+    let outcome = |v: &ScenarioVectors| {
+        format!("{} checkpoints, winner {}, scores {:?}", v.checkpoints.len(), v.winner, v.scores)
+    };
+    if outcome(expected) != outcome(actual) {
+        return Some(Divergence {
+            scenario: expected.name.clone(),
+            tick: None,
+            expected: outcome(expected),
+            actual: outcome(actual),
+        });
+    }
+    None
+}
+
+/// Regenerate and compare against `expected`. Scenarios this build doesn't know are reported;
+/// scenarios missing from `expected` are not (older files stay checkable).
+pub fn check(expected: &VectorFile) -> Vec<Divergence> {
+    let mut out = Vec::new();
+    if expected.sim_version != fp::SIM_VERSION || expected.interval != INTERVAL {
+        out.push(Divergence {
+            scenario: "*".to_string(),
+            tick: None,
+            expected: format!("sim_version {}, interval {}", expected.sim_version, expected.interval),
+            actual: format!("sim_version {}, interval {INTERVAL}", fp::SIM_VERSION),
+        });
+        return out;
+    }
+    for recorded in &expected.scenarios {
+        match SCENARIOS.iter().find(|s| s.name == recorded.name) {
+            Some(scenario) => out.extend(compare(recorded, &run_scenario(scenario))),
+            None => out.push(Divergence {
+                scenario: recorded.name.clone(),
+                tick: None,
+                expected: "a known scenario".to_string(),
+                actual: "no such scenario in this build".to_string(),
+            }),
+        }
+    }
+    out
+}
+
+pub fn load(path: &str) -> Result<VectorFile> {
+    let text = std::fs::read_to_string(path)?;
+    serde_json::from_str(&text)
+        .map_err(|e| PipelineError::InvalidOptions(format!("{path}: not a vector file: {e}")))
+}
+
+pub fn to_json(vectors: &VectorFile) -> String {
+    serde_json::to_string_pretty(vectors).expect("vector file serializes") + "\n"
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn committed_vectors_match_this_build() {
+        let committed: VectorFile =
+            serde_json::from_str(include_str!("../determinism-vectors.json")).unwrap();
+        let divergences = check(&committed);
+        assert!(divergences.is_empty(), "{}", divergences[0]);
+        assert_eq!(committed.scenarios.len(), SCENARIOS.len());
+    }
+
+    #[test]
+    fn check_reports_first_divergent_tick() {
+        let mut vectors = generate();
+        assert!(check(&vectors).is_empty());
+        assert_eq!(serde_json::from_str::<VectorFile>(&to_json(&vectors)).unwrap(), vectors);
+
+        let duel = vectors.scenarios.iter_mut().find(|s| s.name == "duel").unwrap();
+        duel.checkpoints[3].state_hash = "00".repeat(32);
+        duel.checkpoints[5].state_hash = "00".repeat(32);
+        let divergences = check(&vectors);
+        assert_eq!(divergences.len(), 1);
+        assert_eq!((divergences[0].scenario.as_str(), divergences[0].tick), ("duel", Some(400)));
+
+        vectors.sim_version += 1;
+        assert_eq!(check(&vectors)[0].scenario, "*");
+    }
+}
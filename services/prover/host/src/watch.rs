@@ -0,0 +1,395 @@
+//! Drop-directory daemon: prove every transcript that lands in a directory.
+//!
+//! [`watch`] polls `dir` for `*.json` ([`ProverInput`](chickenz_core::ProverInput), checked
+//! by [`input`]) and `*.bin` (transcript files, or the older [`decode_bin_input`] layout)
+//! transcripts. Each one is parsed, proved
+//! with the pipeline, and its artifacts written to `<out>/<stem>.artifacts.json` (`out`
+//! defaults to `dir`). The input then moves to `done/`, or
+//! to `failed/` with the error in `failed/<name>.error.txt`. Dotfiles are skipped, so rsync's
+//! in-progress temporaries are only picked up once renamed into place.
+//!
+//! Proved inputs are appended to `processed.log` in `dir`, one [`input_hash`] per line: an input
+//! already listed there (the same match, in either format) goes straight to `done/` without
+//! being proved again, across restarts too. Failed inputs aren't listed, so moving one back
+//! into `dir` retries it.
+
+use std::collections::HashSet;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use chickenz_core::fp::{self, FpProverInput};
+
+use crate::checkpoint::input_hash;
+use crate::input;
+use crate::pipeline::{self, Logger, PipelineError, ProveOpts, Result};
+
+/// Subdirectory of the watched directory for proved (and duplicate) inputs.
+pub const DONE_DIR: &str = "done";
+/// Subdirectory of the watched directory for inputs that failed to parse or prove.
+pub const FAILED_DIR: &str = "failed";
+/// Input hashes of every proved input, one hex hash per line.
+pub const PROCESSED_LOG: &str = "processed.log";
+/// Default delay between directory scans.
+pub const DEFAULT_POLL: Duration = Duration::from_secs(5);
+
+/// Options for [`watch`] and [`scan_once`].
+#[derive(Clone)]
+pub struct WatchOpts {
+    pub dir: PathBuf,
+    /// Where artifacts go; `None` writes them next to the inputs.
+    pub out: Option<PathBuf>,
+    pub chunked: bool,
+    /// Inputs proved at once (at least 1).
+    pub jobs: usize,
+    pub poll: Duration,
+    /// Proving options for every input; `artifacts_path` is set per input.
+    pub prove: ProveOpts,
+}
+
+impl WatchOpts {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        WatchOpts {
+            dir: dir.into(),
+            out: None,
+            chunked: false,
+            jobs: 1,
+            poll: DEFAULT_POLL,
+            prove: ProveOpts::default(),
+        }
+    }
+}
+
+/// What happened to one input.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Outcome {
+    /// Proved; the input is now in `done/`.
+    Proved { input: PathBuf, artifacts: PathBuf },
+    /// Already proved by an earlier run; moved to `done/` untouched.
+    Duplicate { input: PathBuf },
+    /// Moved to `failed/`.
+    Failed { input: PathBuf, error: String },
+}
+
+/// Decode a `*.bin` transcript: session ID (u32 LE), mirrored flag (u32 LE), then the guest's
+/// raw input ([`fp::encode_raw_input`]: seed, tick count, 6 bytes per tick). The same bytes
+/// [`input_hash`] hashes.
+pub fn decode_bin_input(bytes: &[u8]) -> Result<FpProverInput> {
+    let word = |i: usize| {
+        bytes
+            .get(i * 4..i * 4 + 4)
+            .map(|b| u32::from_le_bytes(b.try_into().unwrap()))
+            .ok_or_else(|| PipelineError::Input("truncated header".into()))
+    };
+    let (session_id, mirrored, ticks) = (word(0)?, word(1)?, word(3)? as usize);
+    if mirrored > 1 {
+        return Err(PipelineError::Input(format!("mirrored flag {mirrored}")));
+    }
+    if bytes.len() != 16 + ticks * 6 {
+        return Err(PipelineError::Input(format!(
+            "{} bytes for {ticks} ticks (expected {})",
+            bytes.len(),
+            16 + ticks * 6
+        )));
+    }
+    let (seed, transcript) = fp::decode_raw_input(&bytes[8..]);
+    Ok(FpProverInput { session_id, seed, mirrored: mirrored == 1, transcript, map: None, gen_seed: None, rules: None })
+}
+
+/// Parse an input file by extension and check it is worth proving.
+pub fn load_input(path: &Path) -> Result<FpProverInput> {
+    let bytes = std::fs::read(path)?;
+    let input = match path.extension().and_then(|e| e.to_str()) {
+        Some("bin") if !fp::is_transcript_file(&bytes) => decode_bin_input(&bytes)?,
+        Some("json" | "bin") => input::parse_input(&bytes)?,
+        _ => return Err(PipelineError::Input("not a .json or .bin transcript".into())),
+    };
+    if input.transcript.is_empty() {
+        return Err(PipelineError::Input("empty transcript".into()));
+    }
+    Ok(input)
+}
+
+fn is_transcript(path: &Path) -> bool {
+    let visible = path.file_name().and_then(|n| n.to_str()).is_some_and(|n| !n.starts_with('.'));
+    let ext = path.extension().and_then(|e| e.to_str());
+    visible && matches!(ext, Some("json" | "bin")) && path.is_file()
+}
+
+/// Transcripts waiting in `dir`, by file name.
+pub fn pending_inputs(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut inputs: Vec<PathBuf> = std::fs::read_dir(dir)?
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| is_transcript(p))
+        .collect();
+    inputs.sort();
+    Ok(inputs)
+}
+
+fn load_processed(dir: &Path) -> Result<HashSet<String>> {
+    match std::fs::read_to_string(dir.join(PROCESSED_LOG)) {
+        Ok(text) => Ok(text.lines().map(|l| l.trim().to_string()).filter(|l| !l.is_empty()).collect()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(HashSet::new()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+fn record_processed(dir: &Path, hash: &str) -> Result<()> {
+    let mut log = std::fs::OpenOptions::new().create(true).append(true).open(dir.join(PROCESSED_LOG))?;
+    writeln!(log, "{hash}")?;
+    Ok(())
+}
+
+/// Move `path` into `dir/<sub>/`, returning its new path.
+fn move_into(dir: &Path, sub: &str, path: &Path) -> Result<PathBuf> {
+    let target_dir = dir.join(sub);
+    std::fs::create_dir_all(&target_dir)?;
+    let target = target_dir.join(path.file_name().unwrap_or_default());
+    std::fs::rename(path, &target)?;
+    Ok(target)
+}
+
+fn artifacts_path(opts: &WatchOpts, input: &Path) -> PathBuf {
+    let stem = input.file_stem().unwrap_or_default().to_string_lossy();
+    opts.out.as_ref().unwrap_or(&opts.dir).join(format!("{stem}.artifacts.json"))
+}
+
+/// Prove one input. Returns its hash for `processed.log` if it was proved.
+fn prove_one(opts: &WatchOpts, path: &Path, processed: &HashSet<String>) -> (Outcome, Option<String>) {
+    let name = path.file_name().unwrap_or_default().to_string_lossy().into_owned();
+    let log = &opts.prove.log;
+    let fail = |error: PipelineError| {
+        log.log(format!("[{name}] failed: {error}"));
+        let error = error.to_string();
+        let moved = move_into(&opts.dir, FAILED_DIR, path);
+        let input = match moved {
+            Ok(moved) => {
+                let _ = std::fs::write(opts.dir.join(FAILED_DIR).join(format!("{name}.error.txt")), &error);
+                moved
+            }
+            Err(_) => path.to_path_buf(),
+        };
+        (Outcome::Failed { input, error }, None)
+    };
+
+    let input = match load_input(path) {
+        Ok(input) => input,
+        Err(e) => return fail(e),
+    };
+    let hash = hex::encode(input_hash(&input));
+    if processed.contains(&hash) {
+        log.log(format!("[{name}] already proved, skipping"));
+        return match move_into(&opts.dir, DONE_DIR, path) {
+            Ok(input) => (Outcome::Duplicate { input }, None),
+            Err(e) => fail(e),
+        };
+    }
+
+    let artifacts = artifacts_path(opts, path);
+    let prefix = name.clone();
+    let inner = opts.prove.log.clone();
+    let prove_opts = ProveOpts {
+        artifacts_path: Some(artifacts.clone()),
+        log: Logger::new(move |m| inner.log(format!("[{prefix}] {m}"))),
+        ..opts.prove.clone()
+    };
+    log.log(format!("[{name}] proving {} ticks (session {})", input.transcript.len(), input.session_id));
+    let proved = if opts.chunked {
+        pipeline::prove_chunked(&input, &prove_opts)
+    } else {
+        pipeline::prove_monolithic(&input, &prove_opts)
+    };
+    if let Err(e) = proved {
+        return fail(e);
+    }
+    match move_into(&opts.dir, DONE_DIR, path) {
+        Ok(input) => (Outcome::Proved { input, artifacts }, Some(hash)),
+        Err(e) => fail(e),
+    }
+}
+
+/// Process every transcript currently in the directory, `jobs` at a time, and return what
+/// happened to each (in file-name order).
+pub fn scan_once(opts: &WatchOpts) -> Result<Vec<Outcome>> {
+    if let Some(out) = &opts.out {
+        std::fs::create_dir_all(out)?;
+    }
+    let mut processed = load_processed(&opts.dir)?;
+    let mut outcomes = Vec::new();
+    for batch in pending_inputs(&opts.dir)?.chunks(opts.jobs.max(1)) {
+        let results: Vec<(Outcome, Option<String>)> = std::thread::scope(|s| {
+            let handles: Vec<_> = batch
+                .iter()
+                .map(|path| {
+                    let processed = &processed;
+                    s.spawn(move || prove_one(opts, path, processed))
+                })
+                .collect();
+            handles.into_iter().map(|h| h.join().expect("prover thread panicked")).collect()
+        });
+        for (outcome, hash) in results {
+            if let Some(hash) = hash {
+                record_processed(&opts.dir, &hash)?;
+                processed.insert(hash);
+            }
+            outcomes.push(outcome);
+        }
+    }
+    Ok(outcomes)
+}
+
+/// Scan the directory every `opts.poll` until an I/O error on the directory itself.
+pub fn watch(opts: &WatchOpts) -> Result<()> {
+    if !opts.dir.is_dir() {
+        return Err(PipelineError::InvalidOptions(format!("{} is not a directory", opts.dir.display())));
+    }
+    opts.prove.log.log(format!(
+        "Watching {} ({}, {} job(s))",
+        opts.dir.display(),
+        if opts.chunked { "chunked" } else { "monolithic" },
+        opts.jobs.max(1)
+    ));
+    loop {
+        scan_once(opts)?;
+        std::thread::sleep(opts.poll);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chickenz_core::fp::NULL_INPUT;
+    use chickenz_core::summary::MatchSummary;
+    use chickenz_core::ProverInput;
+    use risc0_zkvm::sha::{Impl, Sha256};
+
+    /// A fresh drop directory with dev-mode (fast, fake-receipt) proving.
+    fn drop_dir(name: &str) -> WatchOpts {
+        std::env::set_var("RISC0_DEV_MODE", "1");
+        let dir = std::env::temp_dir().join(format!("chickenz-watch-{}-{name}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let mut opts = WatchOpts::new(dir);
+        opts.prove = ProveOpts {
+            groth16: false,
+            chunk_size: 40,
+            artifacts_path: None,
+            checkpoint: None,
+            resume_from: None,
+            jobs: 1,
+            cache_dir: None,
+            boundless_state: None,
+            log: Logger::silent(),
+        };
+        opts
+    }
+
+    fn bin_input(session_id: u32, ticks: usize) -> Vec<u8> {
+        let input = FpProverInput {
+            session_id,
+            seed: 42,
+            mirrored: false,
+            transcript: vec![[NULL_INPUT; 2]; ticks],
+            map: None,
+            gen_seed: None,
+            rules: None,
+        };
+        let mut bytes = session_id.to_le_bytes().to_vec();
+        bytes.extend_from_slice(&0u32.to_le_bytes());
+        bytes.extend_from_slice(&fp::encode_raw_input(&input));
+        bytes
+    }
+
+    fn json_input(session_id: u32, ticks: usize) -> String {
+        let input = ProverInput {
+            session_id,
+            config: chickenz_core::MatchConfig {
+                map: chickenz_core::prover_arena(),
+                ..chickenz_core::default_config(42)
+            },
+            transcript: vec![[chickenz_core::NULL_INPUT; 2]; ticks],
+        };
+        serde_json::to_string(&input).unwrap()
+    }
+
+    #[test]
+    fn bin_input_decodes_and_rejects_bad_lengths() {
+        let bytes = bin_input(9, 12);
+        let input = decode_bin_input(&bytes).unwrap();
+        assert_eq!((input.session_id, input.seed, input.transcript.len()), (9, 42, 12));
+        // The file is exactly what input_hash hashes
+        assert_eq!(&input_hash(&input)[..], Impl::hash_bytes(&bytes).as_bytes());
+        assert!(decode_bin_input(&bytes[..bytes.len() - 1]).is_err());
+        assert!(decode_bin_input(&bytes[..10]).is_err());
+    }
+
+    #[test]
+    fn transcript_files_load_as_bin() {
+        let dir = drop_dir("transcript-file").dir;
+        let input = decode_bin_input(&bin_input(9, 12)).unwrap();
+        let mut file = Vec::new();
+        fp::write_transcript(&mut file, &input).unwrap();
+        let path = dir.join("m.bin");
+        std::fs::write(&path, &file).unwrap();
+        // The same match either way, so the processed log sees a duplicate
+        assert_eq!(input_hash(&load_input(&path).unwrap()), input_hash(&input));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn scan_moves_inputs_through_done_and_failed() {
+        let mut opts = drop_dir("states");
+        let dir = opts.dir.clone();
+        opts.out = Some(dir.join("artifacts"));
+        opts.jobs = 2;
+        std::fs::write(dir.join("a.json"), json_input(1, 30)).unwrap();
+        std::fs::write(dir.join("b.bin"), bin_input(2, 30)).unwrap();
+        std::fs::write(dir.join("c.json"), "{ not json").unwrap();
+        std::fs::write(dir.join("d.bin"), &bin_input(4, 0)).unwrap();
+        std::fs::write(dir.join(".e.json.partial"), "").unwrap();
+        std::fs::write(dir.join("notes.txt"), "").unwrap();
+
+        let outcomes = scan_once(&opts).unwrap();
+        assert_eq!(outcomes.len(), 4);
+        assert_eq!(
+            outcomes[0],
+            Outcome::Proved { input: dir.join("done/a.json"), artifacts: dir.join("artifacts/a.artifacts.json") }
+        );
+        assert!(matches!(&outcomes[1], Outcome::Proved { input, .. } if *input == dir.join("done/b.bin")));
+        assert!(matches!(&outcomes[2], Outcome::Failed { input, .. } if *input == dir.join("failed/c.json")));
+        assert!(matches!(&outcomes[2], Outcome::Failed { error, .. } if error.contains("bad transcript JSON")));
+        assert!(matches!(&outcomes[3], Outcome::Failed { error, .. } if error.contains("empty transcript")));
+        assert!(dir.join("failed/c.json.error.txt").exists());
+
+        let artifacts: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(dir.join("artifacts/b.artifacts.json")).unwrap()).unwrap();
+        assert_eq!(artifacts["output"]["session_id"], 2);
+        let summary = MatchSummary::from_base64(artifacts["summary"].as_str().unwrap()).unwrap();
+        assert_eq!(summary.winner, artifacts["output"]["winner"]);
+        // Untouched: dotfiles and other extensions
+        assert!(dir.join(".e.json.partial").exists() && dir.join("notes.txt").exists());
+        assert_eq!(load_processed(&dir).unwrap().len(), 2);
+        assert!(scan_once(&opts).unwrap().is_empty());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn processed_log_skips_duplicates_across_restarts() {
+        let opts = drop_dir("dupes");
+        let dir = opts.dir.clone();
+        std::fs::write(dir.join("first.bin"), bin_input(5, 20)).unwrap();
+        assert!(matches!(scan_once(&opts).unwrap()[..], [Outcome::Proved { .. }]));
+        assert!(dir.join("first.artifacts.json").exists());
+
+        // The same match re-exported as JSON, after a restart (fresh scan reads the log)
+        std::fs::write(dir.join("again.json"), json_input(5, 20)).unwrap();
+        assert_eq!(scan_once(&opts).unwrap(), [Outcome::Duplicate { input: dir.join("done/again.json") }]);
+        assert!(!dir.join("again.artifacts.json").exists());
+
+        // A different session is new work
+        std::fs::write(dir.join("other.bin"), bin_input(6, 20)).unwrap();
+        assert!(matches!(scan_once(&opts).unwrap()[..], [Outcome::Proved { .. }]));
+        assert_eq!(load_processed(&dir).unwrap().len(), 2);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
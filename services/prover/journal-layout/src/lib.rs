@@ -0,0 +1,472 @@
+//! Byte layout of the settlement journal, shared by the prover (`ProverOutput`) and the Soroban
+//! contract so the two can't drift apart.
+//!
+//! The journal body is 30 little-endian u32 words = 120 bytes. Field offsets below are relative
+//! to the start of the body:
+//!
+//! ```text
+//! [0..4)    winner           i32 as u32 (WINNER_DRAW = -1 → 0xFFFFFFFF)
+//! [4..8)    score_p0         u32
+//! [8..12)   score_p1         u32
+//! [12..44)  transcript_hash  32 bytes
+//! [44..76)  seed_commit      32 bytes
+//! [76..80)  session_id       u32
+//! [80..112) config_commit    32 bytes
+//! [112..116) env_kills_p0    u32 (player 0 deaths nobody was credited for: hazards, the zone)
+//! [116..120) env_kills_p1    u32
+//! ```
+//!
+//! Guests built with the `event-chain` feature append the final event-chain head
+//! (`fp::EventChain`, what spectators recompute from the transcript) as 8 more words:
+//!
+//! ```text
+//! [120..152) event_chain_head 32 bytes
+//! ```
+//!
+//! A match of more than two players commits a wide body (schema version 2): the same fields,
+//! then the player count and the slots of the players past the first two, ahead of any trailer:
+//!
+//! ```text
+//! [120..124) player_count    u32
+//! [124..128) score_p2        u32
+//! [128..132) score_p3        u32
+//! [132..136) env_kills_p2    u32
+//! [136..140) env_kills_p3    u32
+//! ```
+//!
+//! From schema version 3 the body (either width) is followed by the `fp::map_hash` of the map
+//! the match was played on, so a proof of a custom-map match can't pass for an arena one:
+//!
+//! ```text
+//! [body..body+32) map_hash   32 bytes (`Layout::map_hash_at`)
+//! ```
+//!
+//! From schema version 5 the map hash is followed by the final state of the match: the tick it
+//! ended on, then each player's health and lives (i32; a dead player has 0 health). Offsets are
+//! relative to the start of this stats block (`Layout::stats_at`); the wide body's block
+//! appends players 2 and 3:
+//!
+//! ```text
+//! [0..4)    final_tick       u32
+//! [4..8)    final_health_p0  i32
+//! [8..12)   final_health_p1  i32
+//! [12..16)  final_lives_p0   i32
+//! [16..20)  final_lives_p1   i32
+//! [20..24)  final_health_p2  i32 (wide only)
+//! [24..28)  final_health_p3  i32
+//! [28..32)  final_lives_p2   i32
+//! [32..36)  final_lives_p3   i32
+//! ```
+//!
+//! From schema version 7 the stats are followed by the team result, laid out for team matches
+//! (2v2) although the sim only plays free-for-all today. Offsets are relative to the start of
+//! this teams block (`Layout::teams_at`), which is the same size for either body width: every
+//! player has a team slot, and slots past the player count are 0.
+//!
+//! ```text
+//! [0..4)    winner_team      i32 as u32 (NO_WINNER_TEAM = -1 → 0xFFFFFFFF)
+//! [4..8)    team_score_0     u32
+//! [8..12)   team_score_1     u32
+//! [12..16)  player_team_p0   u32
+//! [16..20)  player_team_p1   u32
+//! [20..24)  player_team_p2   u32
+//! [24..28)  player_team_p3   u32
+//! ```
+//!
+//! From schema version 9 the teams are followed by the SHA-256 of the match's weapon pickup
+//! stats (`fp::PickupStats::commitment`, in registered player order): pickups collected and
+//! ticks held per weapon, and the rounds given up for a pickup. Too many words to commit
+//! one by one; the hash lets an audit check the numbers a replay reports.
+//!
+//! ```text
+//! [0..32)   pickup_stats_commit 32 bytes (`Layout::pickup_stats_at`)
+//! ```
+//!
+//! ## Schema versions
+//!
+//! A versioned journal starts with a version word, `VERSION_TAG | version`, ahead of the body,
+//! so a reader can tell layouts apart without relying on the length alone. Journals committed
+//! before versioning have no such word and are version 0: their first word is the winner
+//! (0, 1 or 0xFFFFFFFF), which never carries the tag. [`Layout`] gives each version's offsets.
+//!
+//! - 0: the body alone (120 bytes, 152 with the event-chain trailer)
+//! - 1: version word + body (124 bytes, 156 with the trailer)
+//! - 2: version word + wide body (144 bytes, 176 with the trailer)
+//! - 3: version word + body + map hash (156 bytes, 188 with the trailer)
+//! - 4: version word + wide body + map hash (176 bytes, 208 with the trailer)
+//! - 5: version word + body + map hash + stats (176 bytes, 208 with the trailer)
+//! - 6: version word + wide body + map hash + wide stats (212 bytes, 244 with the trailer)
+//! - 7: version 5 + teams (204 bytes, 236 with the trailer)
+//! - 8: version 6 + teams (240 bytes, 272 with the trailer)
+//! - 9: version 7 + pickup stats commitment (236 bytes, 268 with the trailer); what the guests
+//!   commit for a two-player match
+//! - 10: version 8 + pickup stats commitment (272 bytes, 304 with the trailer); what the guests
+//!   commit for three or four players
+
+#![no_std]
+
+/// Body size in u32 words.
+pub const BODY_WORDS: usize = 30;
+/// Body size in bytes.
+pub const BODY_SIZE: usize = BODY_WORDS * 4;
+/// Length of each hash field.
+pub const HASH_LEN: usize = 32;
+
+// Byte offsets within the body
+pub const WINNER: usize = 0;
+pub const SCORE_P0: usize = 4;
+pub const SCORE_P1: usize = 8;
+pub const TRANSCRIPT_HASH: usize = 12;
+pub const SEED_COMMIT: usize = TRANSCRIPT_HASH + HASH_LEN;
+pub const SESSION_ID: usize = SEED_COMMIT + HASH_LEN;
+pub const CONFIG_COMMIT: usize = SESSION_ID + 4;
+pub const ENV_KILLS_P0: usize = CONFIG_COMMIT + HASH_LEN;
+pub const ENV_KILLS_P1: usize = ENV_KILLS_P0 + 4;
+
+const _: () = assert!(ENV_KILLS_P1 + 4 == BODY_SIZE);
+
+// Wide body (version 2) fields past the two-player body
+pub const PLAYER_COUNT: usize = BODY_SIZE;
+pub const SCORE_P2: usize = PLAYER_COUNT + 4;
+pub const SCORE_P3: usize = SCORE_P2 + 4;
+pub const ENV_KILLS_P2: usize = SCORE_P3 + 4;
+pub const ENV_KILLS_P3: usize = ENV_KILLS_P2 + 4;
+/// Wide body size in bytes.
+pub const WIDE_BODY_SIZE: usize = ENV_KILLS_P3 + 4;
+
+// Stats block (version 5 on) fields, relative to `Layout::stats_at(0)`
+pub const FINAL_TICK: usize = 0;
+pub const FINAL_HEALTH_P0: usize = 4;
+pub const FINAL_HEALTH_P1: usize = 8;
+pub const FINAL_LIVES_P0: usize = 12;
+pub const FINAL_LIVES_P1: usize = 16;
+/// Stats block size in bytes for the two-player body.
+pub const STATS_SIZE: usize = FINAL_LIVES_P1 + 4;
+pub const FINAL_HEALTH_P2: usize = STATS_SIZE;
+pub const FINAL_HEALTH_P3: usize = FINAL_HEALTH_P2 + 4;
+pub const FINAL_LIVES_P2: usize = FINAL_HEALTH_P3 + 4;
+pub const FINAL_LIVES_P3: usize = FINAL_LIVES_P2 + 4;
+/// Stats block size in bytes for the wide body.
+pub const WIDE_STATS_SIZE: usize = FINAL_LIVES_P3 + 4;
+
+// Teams block (version 7 on) fields, relative to `Layout::teams_at(0)`
+pub const WINNER_TEAM: usize = 0;
+pub const TEAM_SCORE_0: usize = 4;
+pub const TEAM_SCORE_1: usize = 8;
+pub const PLAYER_TEAM_P0: usize = 12;
+pub const PLAYER_TEAM_P1: usize = 16;
+pub const PLAYER_TEAM_P2: usize = 20;
+pub const PLAYER_TEAM_P3: usize = 24;
+/// Teams block size in bytes, for either body width.
+pub const TEAMS_SIZE: usize = PLAYER_TEAM_P3 + 4;
+
+// Pickup stats block (version 9 on), relative to `Layout::pickup_stats_at(0)`
+pub const PICKUP_STATS_COMMIT: usize = 0;
+/// Pickup stats block size in bytes, for either body width.
+pub const PICKUP_STATS_SIZE: usize = PICKUP_STATS_COMMIT + HASH_LEN;
+
+/// Optional trailer of a two-player body: the final event-chain head, right after the body
+/// (`Layout::event_chain_head` for any version, past the map hash from version 3).
+pub const EVENT_CHAIN_HEAD: usize = BODY_SIZE;
+
+/// High half of a version word. No winner value (0, 1, -1) has these bits.
+pub const VERSION_TAG: u32 = 0xC41C_0000;
+const VERSION_TAG_MASK: u32 = 0xFFFF_0000;
+
+/// Schema version the guests commit.
+pub const VERSION: u32 = 9;
+
+/// Where a schema version puts the body.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Layout {
+    pub version: u32,
+    /// Bytes ahead of the body (the version word, if any).
+    pub header: usize,
+    /// Body size: `BODY_SIZE`, or `WIDE_BODY_SIZE` for the wide body.
+    pub body: usize,
+    /// Whether the map hash follows the body.
+    pub map_hash: bool,
+    /// Whether the final tick, health and lives follow the map hash.
+    pub stats: bool,
+    /// Whether the team result follows the stats.
+    pub teams: bool,
+    /// Whether the pickup stats commitment follows the teams.
+    pub pickup_stats: bool,
+}
+
+impl Layout {
+    /// Absolute byte offset of a body field (`WINNER`, `SESSION_ID`, ...).
+    pub const fn at(&self, field: usize) -> usize {
+        self.header + field
+    }
+
+    /// Journal size in bytes without the event-chain trailer.
+    pub const fn size(&self) -> usize {
+        self.header
+            + self.body
+            + if self.map_hash { HASH_LEN } else { 0 }
+            + self.stats_size()
+            + if self.teams { TEAMS_SIZE } else { 0 }
+            + if self.pickup_stats { PICKUP_STATS_SIZE } else { 0 }
+    }
+
+    /// Stats block size in bytes: 0 before version 5, `WIDE_STATS_SIZE` for the wide body.
+    pub const fn stats_size(&self) -> usize {
+        match (self.stats, self.is_wide()) {
+            (false, _) => 0,
+            (true, false) => STATS_SIZE,
+            (true, true) => WIDE_STATS_SIZE,
+        }
+    }
+
+    /// Absolute byte offset of a stats field (`FINAL_TICK`, ...), if this version has them.
+    pub const fn stats_at(&self, field: usize) -> Option<usize> {
+        if self.stats {
+            Some(self.header + self.body + if self.map_hash { HASH_LEN } else { 0 } + field)
+        } else {
+            None
+        }
+    }
+
+    /// Absolute byte offset of a teams field (`WINNER_TEAM`, ...), if this version has them.
+    pub const fn teams_at(&self, field: usize) -> Option<usize> {
+        if self.teams {
+            Some(self.header + self.body + if self.map_hash { HASH_LEN } else { 0 } + self.stats_size() + field)
+        } else {
+            None
+        }
+    }
+
+    /// Absolute byte offset of a pickup stats field (`PICKUP_STATS_COMMIT`), if this version has
+    /// them.
+    pub const fn pickup_stats_at(&self, field: usize) -> Option<usize> {
+        if self.pickup_stats {
+            Some(self.size() - PICKUP_STATS_SIZE + field)
+        } else {
+            None
+        }
+    }
+
+    /// Absolute byte offset of the map hash, right after the body, if this version has one.
+    pub const fn map_hash_at(&self) -> Option<usize> {
+        if self.map_hash { Some(self.header + self.body) } else { None }
+    }
+
+    /// Whether the body carries the player count and the slots of players 2 and 3.
+    pub const fn is_wide(&self) -> bool {
+        self.body == WIDE_BODY_SIZE
+    }
+
+    /// Absolute byte offset of the event-chain trailer, at the end of the journal.
+    pub const fn event_chain_head(&self) -> usize {
+        self.size()
+    }
+
+    /// Journal lengths settlement accepts: the plain journal or one with the event-chain trailer.
+    pub const fn is_valid_size(&self, len: usize) -> bool {
+        len == self.size() || len == self.size() + HASH_LEN
+    }
+}
+
+/// Unversioned journals, accepted during the transition to versioned ones.
+pub const V0: Layout = Layout { version: 0, header: 0, body: BODY_SIZE, map_hash: false, stats: false, teams: false, pickup_stats: false };
+/// Version word, then the body.
+pub const V1: Layout = Layout { version: 1, header: 4, body: BODY_SIZE, map_hash: false, stats: false, teams: false, pickup_stats: false };
+/// Version word, then the wide body.
+pub const V2: Layout = Layout { version: 2, header: 4, body: WIDE_BODY_SIZE, map_hash: false, stats: false, teams: false, pickup_stats: false };
+/// Version word, the body, then the map hash.
+pub const V3: Layout = Layout { version: 3, header: 4, body: BODY_SIZE, map_hash: true, stats: false, teams: false, pickup_stats: false };
+/// Version word, the wide body, then the map hash.
+pub const V4: Layout = Layout { version: 4, header: 4, body: WIDE_BODY_SIZE, map_hash: true, stats: false, teams: false, pickup_stats: false };
+/// Version word, the body, the map hash, then the stats.
+pub const V5: Layout = Layout { version: 5, header: 4, body: BODY_SIZE, map_hash: true, stats: true, teams: false, pickup_stats: false };
+/// Version word, the wide body, the map hash, then the wide stats.
+pub const V6: Layout = Layout { version: 6, header: 4, body: WIDE_BODY_SIZE, map_hash: true, stats: true, teams: false, pickup_stats: false };
+/// Version word, the body, the map hash, the stats, then the teams.
+pub const V7: Layout = Layout { version: 7, header: 4, body: BODY_SIZE, map_hash: true, stats: true, teams: true, pickup_stats: false };
+/// Version word, the wide body, the map hash, the wide stats, then the teams.
+pub const V8: Layout = Layout { version: 8, header: 4, body: WIDE_BODY_SIZE, map_hash: true, stats: true, teams: true, pickup_stats: false };
+/// Version word, the body, the map hash, the stats, the teams, then the pickup stats.
+pub const V9: Layout = Layout { version: 9, header: 4, body: BODY_SIZE, map_hash: true, stats: true, teams: true, pickup_stats: true };
+/// Version word, the wide body, the map hash, the wide stats, the teams, then the pickup stats.
+pub const V10: Layout = Layout { version: 10, header: 4, body: WIDE_BODY_SIZE, map_hash: true, stats: true, teams: true, pickup_stats: true };
+/// The layout the guests commit for a two-player match.
+pub const CURRENT: Layout = V9;
+/// The layout the guests commit for a match of more than two players.
+pub const WIDE: Layout = V10;
+
+const _: () = assert!(CURRENT.version == VERSION);
+
+/// `layout(v)` for every known version, oldest first.
+pub const KNOWN_VERSIONS: [u32; 11] = [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10];
+
+/// Offsets of a known schema version.
+pub const fn layout(version: u32) -> Option<Layout> {
+    match version {
+        0 => Some(V0),
+        1 => Some(V1),
+        2 => Some(V2),
+        3 => Some(V3),
+        4 => Some(V4),
+        5 => Some(V5),
+        6 => Some(V6),
+        7 => Some(V7),
+        8 => Some(V8),
+        9 => Some(V9),
+        10 => Some(V10),
+        _ => None,
+    }
+}
+
+/// The version word a version-`version` journal starts with (version 0 has none).
+pub const fn encode_version(version: u32) -> u32 {
+    VERSION_TAG | version
+}
+
+/// Schema version of a journal from its first word: the tagged version, or 0 when the word is
+/// an unversioned journal's winner. The version may be one this build doesn't know.
+pub const fn decode_version(first_word: u32) -> u32 {
+    if first_word & VERSION_TAG_MASK == VERSION_TAG {
+        first_word & !VERSION_TAG_MASK
+    } else {
+        0
+    }
+}
+
+/// Current journal size in u32 words.
+pub const WORDS: usize = CURRENT.size() / 4;
+/// Current journal size in bytes.
+pub const SIZE: usize = CURRENT.size();
+/// Current journal size in bytes with the event-chain trailer.
+pub const SIZE_WITH_EVENT_CHAIN: usize = SIZE + HASH_LEN;
+/// Current journal size in u32 words with the event-chain trailer.
+pub const WORDS_WITH_EVENT_CHAIN: usize = SIZE_WITH_EVENT_CHAIN / 4;
+/// Wide journal size in u32 words.
+pub const WIDE_WORDS: usize = WIDE.size() / 4;
+/// Wide journal size in u32 words with the event-chain trailer.
+pub const WIDE_WORDS_WITH_EVENT_CHAIN: usize = WIDE_WORDS + HASH_LEN / 4;
+
+/// Journal lengths of the current version settlement accepts.
+pub const fn is_valid_size(len: usize) -> bool {
+    CURRENT.is_valid_size(len)
+}
+
+/// Word index of a byte offset.
+pub const fn word(offset: usize) -> usize {
+    offset / 4
+}
+
+/// Winner value for a drawn match. The sim always picks a winner today, so settlement
+/// rejects it; a draw-capable ruleset changes `is_settleable_winner` alone.
+pub const WINNER_DRAW: i32 = -1;
+
+pub const fn encode_winner(winner: i32) -> u32 {
+    winner as u32
+}
+
+pub const fn decode_winner(word: u32) -> i32 {
+    word as i32
+}
+
+/// Winners the contract can settle: player 0 or player 1.
+pub const fn is_settleable_winner(winner: i32) -> bool {
+    winner == 0 || winner == 1
+}
+
+/// Winning team of a match no team won: a draw, or a free-for-all of more than two players.
+pub const NO_WINNER_TEAM: i32 = -1;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn winner_encoding_round_trips() {
+        for w in [WINNER_DRAW, 0, 1] {
+            assert_eq!(decode_winner(encode_winner(w)), w);
+        }
+        assert_eq!(encode_winner(WINNER_DRAW), 0xFFFF_FFFF);
+        assert!(!is_settleable_winner(WINNER_DRAW));
+        assert!(is_settleable_winner(0) && is_settleable_winner(1));
+    }
+
+    #[test]
+    fn event_chain_trailer_follows_journal() {
+        assert!(is_valid_size(SIZE) && is_valid_size(SIZE_WITH_EVENT_CHAIN));
+        assert!(!is_valid_size(SIZE - 4) && !is_valid_size(SIZE + 4));
+        assert_eq!(WORDS_WITH_EVENT_CHAIN, WORDS + 8);
+    }
+
+    #[test]
+    fn versions_decode_to_their_layouts() {
+        // An unversioned journal's first word is its winner
+        for w in [WINNER_DRAW, 0, 1] {
+            assert_eq!(decode_version(encode_winner(w)), 0);
+        }
+        for v in KNOWN_VERSIONS {
+            let l = layout(v).unwrap();
+            assert_eq!(l.version, v);
+            if v > 0 {
+                assert_eq!(decode_version(encode_version(v)), v);
+            }
+        }
+        assert_eq!(decode_version(encode_version(11)), 11);
+        assert_eq!(layout(11), None);
+
+        assert_eq!((V0.size(), V0.at(SESSION_ID), V0.at(EVENT_CHAIN_HEAD)), (120, 76, 120));
+        assert!(V0.is_valid_size(120) && V0.is_valid_size(152) && !V0.is_valid_size(124));
+        assert_eq!((V1.size(), V1.at(WINNER), V1.at(ENV_KILLS_P1)), (124, 4, 120));
+        assert!(V1.is_valid_size(124) && V1.is_valid_size(156) && !V1.is_valid_size(120));
+        assert_eq!((V3.size(), V3.map_hash_at(), V3.event_chain_head()), (156, Some(124), 156));
+        assert_eq!((SIZE, WORDS), (236, 59));
+        assert_eq!(V1.map_hash_at(), None);
+        assert_eq!((V2.size(), V2.at(SCORE_P0), V2.at(PLAYER_COUNT), V2.at(ENV_KILLS_P3)), (144, 8, 124, 140));
+        assert!(V2.is_valid_size(144) && V2.is_valid_size(176) && !V2.is_valid_size(124));
+        assert_eq!((V2.event_chain_head(), V1.event_chain_head()), (144, V1.at(EVENT_CHAIN_HEAD)));
+        assert_eq!((V4.size(), V4.map_hash_at(), V4.at(ENV_KILLS_P3)), (176, Some(144), 140));
+        assert!(V4.is_valid_size(176) && V4.is_valid_size(208) && !V4.is_valid_size(144));
+        assert!(V2.is_wide() && V4.is_wide() && !V1.is_wide() && !V3.is_wide() && !V0.is_wide());
+        assert_eq!((WIDE_WORDS, WIDE_WORDS_WITH_EVENT_CHAIN), (68, 76));
+    }
+
+    #[test]
+    fn stats_follow_the_map_hash() {
+        for l in [V0, V1, V2, V3, V4] {
+            assert_eq!((l.stats_at(FINAL_TICK), l.stats_size()), (None, 0));
+        }
+        assert_eq!((V5.size(), V5.stats_at(FINAL_TICK), V5.stats_at(FINAL_LIVES_P1)), (176, Some(156), Some(172)));
+        assert!(V5.is_valid_size(176) && V5.is_valid_size(208) && !V5.is_valid_size(156));
+        assert_eq!(V5.event_chain_head(), 176);
+        assert_eq!((V6.size(), V6.stats_at(FINAL_TICK), V6.stats_at(FINAL_LIVES_P3)), (212, Some(176), Some(208)));
+        assert!(V6.is_valid_size(212) && V6.is_valid_size(244) && V6.is_wide() && !V5.is_wide());
+        // Everything ahead of the stats sits where a version 3 or 4 reader looks for it
+        assert_eq!((V5.map_hash_at(), V6.map_hash_at()), (V3.map_hash_at(), V4.map_hash_at()));
+    }
+
+    #[test]
+    fn teams_follow_the_stats() {
+        for l in [V0, V1, V2, V3, V4, V5, V6] {
+            assert_eq!(l.teams_at(WINNER_TEAM), None);
+        }
+        assert_eq!((V7.size(), V7.teams_at(WINNER_TEAM), V7.teams_at(PLAYER_TEAM_P3)), (204, Some(176), Some(200)));
+        assert!(V7.is_valid_size(204) && V7.is_valid_size(236) && !V7.is_valid_size(176));
+        assert_eq!((V8.size(), V8.teams_at(WINNER_TEAM), V8.event_chain_head()), (240, Some(212), 240));
+        assert!(V8.is_wide() && !V7.is_wide());
+        // Everything ahead of the teams sits where a version 5 or 6 reader looks for it
+        assert_eq!((V7.stats_at(FINAL_TICK), V8.stats_at(FINAL_LIVES_P3)), (V5.stats_at(FINAL_TICK), V6.stats_at(FINAL_LIVES_P3)));
+    }
+
+    #[test]
+    fn pickup_stats_follow_the_teams() {
+        for l in [V0, V1, V2, V3, V4, V5, V6, V7, V8] {
+            assert_eq!(l.pickup_stats_at(PICKUP_STATS_COMMIT), None);
+        }
+        assert_eq!((V9.size(), V9.pickup_stats_at(PICKUP_STATS_COMMIT), V9.event_chain_head()), (236, Some(204), 236));
+        assert!(V9.is_valid_size(236) && V9.is_valid_size(268) && !V9.is_valid_size(204));
+        assert_eq!((V10.size(), V10.pickup_stats_at(PICKUP_STATS_COMMIT)), (272, Some(240)));
+        assert!(V10.is_wide() && !V9.is_wide());
+        // Everything ahead of the commitment sits where a version 7 or 8 reader looks for it
+        assert_eq!((V9.teams_at(WINNER_TEAM), V10.teams_at(PLAYER_TEAM_P3)), (V7.teams_at(WINNER_TEAM), V8.teams_at(PLAYER_TEAM_P3)));
+        assert_eq!((CURRENT, WIDE), (V9, V10));
+    }
+}
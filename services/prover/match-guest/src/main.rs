@@ -2,49 +2,143 @@
 
 risc0_zkvm::guest::entry!(main);
 
-use chickenz_core::fp::{self, ChunkProof, CHUNK_PROOF_WORDS};
-use chickenz_core::ProverOutput;
+use chickenz_core::diag::{HashPrefix, LineBuf};
+use chickenz_core::fp::{
+    self, ChunkProof, FpMatchConfig, MatchRules, CHUNK_PROOF_WORDS, INPUT_BYTES, MAP_ENCODED_LEN,
+    MAX_PLAYERS, WIDE_CHUNK_PROOF_WORDS,
+};
+use chickenz_core::{ProverOutput, TeamResult};
 use sha2::{Digest, Sha256};
 
+/// Max transcript: 3600 ticks × 12 bytes (four players) = 43200 bytes = 10800 u32 words, the
+/// monolithic guest's bound.
+const MAX_TRANSCRIPT_WORDS: usize = 10800;
+
+/// Log a phase breadcrumb, formatted on the stack (no heap on the success path).
+fn breadcrumb(args: core::fmt::Arguments) {
+    risc0_zkvm::guest::env::log(LineBuf::<160>::format(args).as_str());
+}
+
 /// Match composer guest: verifies a chain of chunk proofs, outputs final result.
 ///
 /// env::verify() adds ZERO execution cycles — it's resolved at the recursion layer.
-/// This guest is extremely lightweight: just reads journals, checks hash chain, outputs result.
+/// This guest is lightweight: it reads journals, hashes the transcript, checks the hash chain and
+/// outputs the result.
+///
+/// The chain must cover a whole match: the first chunk starts at tick 0, each chunk starts at
+/// the tick the previous one ended, only the last chunk reports `match_over`, and it must.
+/// (A match that runs out of time ends with `match_over` too, at the rules' match duration.)
 ///
 /// Input (all via read_slice):
-///   [seed: u32, num_chunks: u32]
+///   [seed: u32, session_id: u32, mirrored: u32, num_chunks: u32, player_count: u32]
+///   [rules: `MatchRules::to_words`]: the lives and clock the initial state is built with
 ///   [chunk_image_id: [u32; 8]]
-///   For each chunk: [journal_words: [u32; 30]]
+///   [map_source: u32, gen_seed: u32]: 1 plays `fp::generate_map(gen_seed, default params)`,
+///   0 reads [map: `fp::encode_map` bytes, unmirrored, padded to u32 words] next
+///   [tick_count: u32][the transcript's raw tick bytes (`INPUT_BYTES` per player), padded to
+///   u32 words]: every tick, including any after the match ended
+///   For each chunk: [journal_words: [u32; 53]] (`WIDE_CHUNK_PROOF_WORDS` past two players)
+///
+/// The journal's transcript_hash is the monolithic guest's: the SHA-256 of the transcript's raw
+/// tick bytes (`fp::hash_transcript`), whatever the chunk size. Each chunk's `input_hash` must
+/// be the SHA-256 of the transcript's ticks `tick_start..tick_end`, which binds the chunks to it.
+///
+/// Every chunk must carry this build's `SIM_VERSION`: the chunk image ID is an input, so a
+/// chunk guest from another build could otherwise be chained across a rules change.
 ///
 /// Output (via commit): ProverOutput
 fn main() {
     // 1. Read header
-    let mut header = [0u32; 2];
+    let mut header = [0u32; 5];
     risc0_zkvm::guest::env::read_slice(&mut header);
     let seed = header[0];
-    let num_chunks = header[1] as usize;
+    let session_id = header[1];
+    let mut rules = [0u32; 3];
+    risc0_zkvm::guest::env::read_slice(&mut rules);
+    let player_count = header[4] as usize;
+    assert!((2..=MAX_PLAYERS).contains(&player_count), "match: header declares {player_count} players");
+    let cfg = MatchRules::from_words(rules).apply(FpMatchConfig {
+        mirrored: header[2] != 0,
+        player_count: player_count as u8,
+        ..FpMatchConfig::default()
+    });
+    let num_chunks = header[3] as usize;
+    breadcrumb(format_args!(
+        "match: header seed={seed} session={session_id} mirrored={} chunks={num_chunks} players={} lives={}",
+        cfg.mirrored, cfg.player_count, cfg.initial_lives
+    ));
+    let wide = cfg.player_count > 2;
+    let chunk_words = if wide { WIDE_CHUNK_PROOF_WORDS } else { CHUNK_PROOF_WORDS };
 
     // 2. Read chunk image ID
     let mut chunk_image_id = [0u32; 8];
     risc0_zkvm::guest::env::read_slice(&mut chunk_image_id);
 
-    // 3. Compute expected initial state hash
-    let map = fp::arena_map();
-    let initial_state = fp::create_initial_state(seed, &map);
+    // 3. Read the map, or generate it from its seed (the journal's map hash commits to it)
+    let mut map_source = [0u32; 2];
+    risc0_zkvm::guest::env::read_slice(&mut map_source);
+    let base_map = if map_source[0] == 1 {
+        fp::generate_map(map_source[1], fp::MapGenParams::default())
+    } else {
+        let mut map_words = [0u32; MAP_ENCODED_LEN.div_ceil(4)];
+        risc0_zkvm::guest::env::read_slice(&mut map_words);
+        let mut map_bytes = [0u8; MAP_ENCODED_LEN.div_ceil(4) * 4];
+        for (b, w) in map_bytes.chunks_exact_mut(4).zip(map_words) {
+            b.copy_from_slice(&w.to_le_bytes());
+        }
+        fp::decode_map(&map_bytes[..MAP_ENCODED_LEN]).expect("map is MAP_ENCODED_LEN bytes")
+    };
+
+    // 4. Compute expected initial state hash (covers the map, so every chunk is bound to it)
+    let map = cfg.oriented_map(&base_map);
+    let map_hash = fp::map_hash(&map);
+    let initial_state = fp::create_initial_state_with(seed, &map, &cfg);
     let expected_first_hash = fp::hash_state(&initial_state);
+    breadcrumb(format_args!(
+        "match: initial state hash {}, map {}",
+        HashPrefix(&expected_first_hash),
+        HashPrefix(&map_hash)
+    ));
+
+    // 5. Read the transcript (fixed buffer, no heap)
+    let mut tick_count = [0u32; 1];
+    risc0_zkvm::guest::env::read_slice(&mut tick_count);
+    let tick_count = tick_count[0] as usize;
+    let tick_len = INPUT_BYTES * player_count;
+    let transcript_byte_len = tick_count * tick_len;
+    let transcript_word_count = transcript_byte_len.div_ceil(4);
+    assert!(
+        transcript_word_count <= MAX_TRANSCRIPT_WORDS,
+        "match: {tick_count} ticks need {transcript_byte_len} bytes, buffer holds {}",
+        MAX_TRANSCRIPT_WORDS * 4
+    );
+    let mut transcript_words = [0u32; MAX_TRANSCRIPT_WORDS];
+    risc0_zkvm::guest::env::read_slice(&mut transcript_words[..transcript_word_count]);
+    let mut transcript = [0u8; MAX_TRANSCRIPT_WORDS * 4];
+    for (b, w) in transcript.chunks_exact_mut(4).zip(&transcript_words[..transcript_word_count]) {
+        b.copy_from_slice(&w.to_le_bytes());
+    }
+    let transcript = &transcript[..transcript_byte_len];
 
-    // 4. Read, verify, and chain each chunk proof
+    // 6. Read, verify, and chain each chunk proof
     let mut prev_hash = expected_first_hash;
-    let mut transcript_hasher = Sha256::new();
-    let mut final_scores = [0u32; 2];
+    let mut final_scores = [0u32; MAX_PLAYERS];
     let mut final_winner = -1i32;
+    let mut final_env_kills = [0u32; MAX_PLAYERS];
+    let mut final_health = [0i32; MAX_PLAYERS];
+    let mut final_lives = [0i32; MAX_PLAYERS];
+    let mut final_pickup_stats_commit = [0u8; 32];
+    let mut prev_tick_end = initial_state.tick as u32;
+    let mut match_over = false;
     for i in 0..num_chunks {
-        // Read chunk journal (30 u32 words = 120 bytes)
-        let mut journal_words = [0u32; CHUNK_PROOF_WORDS];
-        risc0_zkvm::guest::env::read_slice(&mut journal_words);
+        // Read chunk journal (53 u32 words = 212 bytes, more for a wide match)
+        let mut journal_words = [0u32; WIDE_CHUNK_PROOF_WORDS];
+        let journal_words = &mut journal_words[..chunk_words];
+        risc0_zkvm::guest::env::read_slice(journal_words);
 
         // Convert to bytes for verification (fixed buffer, no heap)
-        let mut journal_bytes = [0u8; CHUNK_PROOF_WORDS * 4];
+        let mut journal_bytes = [0u8; WIDE_CHUNK_PROOF_WORDS * 4];
+        let journal_bytes = &mut journal_bytes[..chunk_words * 4];
         for (i, w) in journal_words.iter().enumerate() {
             let b = w.to_le_bytes();
             journal_bytes[i * 4] = b[0];
@@ -53,39 +147,131 @@ fn main() {
             journal_bytes[i * 4 + 3] = b[3];
         }
 
+        // Decode the chunk proof first so a failed verify can name its tick range
+        let chunk = ChunkProof::from_journal_bytes(journal_bytes);
+
         // Verify this chunk's proof (zero cycles — resolved at recursion layer)
-        risc0_zkvm::guest::env::verify(chunk_image_id, &journal_bytes)
-            .expect("chunk proof verification failed");
+        if let Err(e) = risc0_zkvm::guest::env::verify(chunk_image_id, journal_bytes) {
+            panic!(
+                "chunk {i}/{num_chunks} (ticks {}..{}): proof verification failed for {}-byte journal: {e:?}",
+                chunk.tick_start,
+                chunk.tick_end,
+                journal_bytes.len()
+            );
+        }
 
-        // Decode the chunk proof
-        let chunk = ChunkProof::from_journal_bytes(&journal_bytes);
+        // Replayed under this build's rules
+        assert!(
+            chunk.sim_version == fp::SIM_VERSION,
+            "chunk {i}/{num_chunks} (ticks {}..{}): sim version {}, composer is {}",
+            chunk.tick_start,
+            chunk.tick_end,
+            chunk.sim_version,
+            fp::SIM_VERSION
+        );
 
         // Verify hash chain: this chunk's input state must match previous output
         assert!(
             chunk.state_hash_in == prev_hash,
-            "chunk {}: state hash chain broken",
-            i
+            "chunk {i}/{num_chunks} (ticks {}..{}): state hash chain broken: expected {} got {}",
+            chunk.tick_start,
+            chunk.tick_end,
+            HashPrefix(&prev_hash),
+            HashPrefix(&chunk.state_hash_in)
         );
         prev_hash = chunk.state_hash_out;
 
-        // Accumulate transcript hash (hash of chunk input hashes)
-        transcript_hasher.update(&chunk.input_hash);
+        // Ticks run on without gaps, and nothing follows the chunk that ended the match
+        assert!(
+            chunk.tick_start == prev_tick_end && chunk.tick_end >= chunk.tick_start,
+            "chunk {i}/{num_chunks} (ticks {}..{}): tick range does not continue from tick {prev_tick_end}",
+            chunk.tick_start,
+            chunk.tick_end
+        );
+        assert!(
+            !match_over,
+            "chunk {i}/{num_chunks} (ticks {}..{}): follows the chunk that ended the match at tick {prev_tick_end}",
+            chunk.tick_start,
+            chunk.tick_end
+        );
+        prev_tick_end = chunk.tick_end;
+        match_over = chunk.match_over;
+
+        // Every chunk replayed on the committed map
+        assert!(
+            chunk.map_hash == map_hash,
+            "chunk {i}/{num_chunks} (ticks {}..{}): map hash mismatch: expected {} got {}",
+            chunk.tick_start,
+            chunk.tick_end,
+            HashPrefix(&map_hash),
+            HashPrefix(&chunk.map_hash)
+        );
+
+        // Replayed the transcript's own ticks
+        assert!(
+            chunk.tick_end as usize <= tick_count,
+            "chunk {i}/{num_chunks} (ticks {}..{}): past the transcript's {tick_count} ticks",
+            chunk.tick_start,
+            chunk.tick_end
+        );
+        let replayed = &transcript[chunk.tick_start as usize * tick_len..chunk.tick_end as usize * tick_len];
+        let replayed_hash: [u8; 32] = Sha256::digest(replayed).into();
+        assert!(
+            chunk.input_hash == replayed_hash,
+            "chunk {i}/{num_chunks} (ticks {}..{}): input hash mismatch: expected {} got {}",
+            chunk.tick_start,
+            chunk.tick_end,
+            HashPrefix(&replayed_hash),
+            HashPrefix(&chunk.input_hash)
+        );
 
         // Track final state
         final_scores = chunk.scores;
         final_winner = chunk.winner;
+        final_env_kills = chunk.env_kills;
+        final_health = chunk.health;
+        final_lives = chunk.lives;
+        final_pickup_stats_commit = chunk.pickup_stats_commit;
     }
 
-    // 5. Compute final commitments
-    let transcript_hash: [u8; 32] = transcript_hasher.finalize().into();
+    assert!(match_over, "match: not over after {num_chunks} chunks (tick {prev_tick_end})");
+
+    // 7. Compute final commitments
+    let transcript_hash: [u8; 32] = Sha256::digest(transcript).into();
     let seed_commit = fp::hash_seed(seed);
+    breadcrumb(format_args!(
+        "match: chained {num_chunks} chunks, winner={final_winner} scores={}-{}, transcript {}",
+        final_scores[0],
+        final_scores[1],
+        HashPrefix(&transcript_hash)
+    ));
 
-    // 6. Commit final match result
+    // 8. Commit final match result
+    // Chunks report internal indices; the journal is in registered player order
+    let winner = fp::side_index(initial_state.side_swap, final_winner);
+    let scores = fp::registered_scores(initial_state.side_swap, final_scores);
+    let teams = TeamResult::solo(cfg.player_count, winner, &scores);
     let output = ProverOutput {
-        winner: final_winner,
-        scores: final_scores,
+        winner,
+        scores,
         transcript_hash,
         seed_commit,
+        session_id,
+        config_commit: cfg.commitment(),
+        env_kills: fp::registered_scores(initial_state.side_swap, final_env_kills),
+        player_count: cfg.player_count,
+        map_hash,
+        final_tick: prev_tick_end,
+        final_health: fp::registered_scores(initial_state.side_swap, final_health),
+        final_lives: fp::registered_scores(initial_state.side_swap, final_lives),
+        winner_team: teams.winner_team,
+        team_scores: teams.team_scores,
+        player_teams: teams.player_teams,
+        pickup_stats_commit: final_pickup_stats_commit,
     };
-    risc0_zkvm::guest::env::commit_slice(&output.to_journal_words());
+    if wide {
+        risc0_zkvm::guest::env::commit_slice(&output.to_wide_journal_words());
+    } else {
+        risc0_zkvm::guest::env::commit_slice(&output.to_journal_words());
+    }
 }
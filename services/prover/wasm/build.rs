@@ -0,0 +1,32 @@
+//! Replays the embedded attract-mode demo (`assets/demo-transcript.bin`) and fails the build if
+//! it no longer ends the way it was recorded, so a sim change can't ship a demo whose visuals
+//! have drifted from its script.
+
+use chickenz_core::fp;
+
+const DEMO: &str = "assets/demo-transcript.bin";
+/// The recorded ending: who wins, and the tick the match is over.
+const DEMO_WINNER: i32 = 0;
+const DEMO_MATCH_OVER_TICK: i32 = 301;
+
+fn main() {
+    println!("cargo:rerun-if-changed={DEMO}");
+    let bytes = std::fs::read(DEMO).unwrap_or_else(|e| panic!("{DEMO}: {e}"));
+    let (seed, transcript) =
+        fp::decode_input_runs(&bytes).unwrap_or_else(|| panic!("{DEMO}: not an input-run transcript"));
+    let mut state = fp::create_initial_state(seed, &fp::arena_map());
+    let mut over_at = None;
+    for inputs in &transcript {
+        fp::step_mut(&mut state, inputs);
+        if state.match_over && over_at.is_none() {
+            over_at = Some(state.tick);
+        }
+    }
+    assert!(
+        state.winner == DEMO_WINNER && over_at == Some(DEMO_MATCH_OVER_TICK),
+        "{DEMO} desynced: winner {} with the match over at tick {over_at:?}, recorded winner \
+         {DEMO_WINNER} at tick {DEMO_MATCH_OVER_TICK}. Rework the script in host/src/demo.rs, \
+         rerun `chickenz-host demo-transcript`, and update the ending here",
+        state.winner,
+    );
+}
@@ -3,6 +3,7 @@ import {
   MAP_POOL,
   TICK_RATE,
   NULL_INPUT,
+  Button,
 } from "@chickenz/sim";
 import type {
   GameMap,
@@ -10,7 +11,7 @@ import type {
 } from "@chickenz/sim";
 import type { StateMessage, EndedMessage, RoomInfo, GameMode } from "./protocol";
 import { inputFromMessage, generateJoinCode, type InputMessage } from "./protocol";
-import { WasmState } from "./wasm";
+import { WasmState, config_commitment } from "./wasm";
 import { randomBotName, createBotSocket, createBotState, botThink, type BotState } from "./BotAI";
 
 export interface SocketData {
@@ -30,6 +31,8 @@ const STATE_BROADCAST_INTERVAL = 1; // send state every tick (60Hz) for minimal
 const TOTAL_ROUNDS = 3;
 const WINS_NEEDED = 2;
 const ROUND_TRANSITION_MS = 750; // brief pause between taunt end and next round
+// Rules the proof replays the last round under
+const PROVER_RULES = { initial_lives: 1, match_duration_ticks: 1800, sudden_death_start_tick: 1200 };
 
 export class GameRoom {
   readonly id: string;
@@ -218,9 +221,7 @@ export class GameRoom {
         map: GameRoom.toProverMap(this.currentMap),
         player_count: 2,
         tick_rate: TICK_RATE,
-        initial_lives: 1,
-        match_duration_ticks: 1800,
-        sudden_death_start_tick: 1200,
+        ...PROVER_RULES,
       },
       transcript: this.transcript.map(([p0, p1]) => [
         { buttons: p0.buttons, aim_x: p0.aimX, aim_y: p0.aimY },
@@ -229,6 +230,18 @@ export class GameRoom {
     };
   }
 
+  /**
+   * Config commitment and map hash the proof of the last round commits. start_match records
+   * them, and settle_match refuses a journal committing others.
+   */
+  getCommitments(): { configCommit: Uint8Array; mapHash: Uint8Array } {
+    const r = PROVER_RULES;
+    return {
+      configCommit: config_commitment(r.initial_lives, r.match_duration_ticks, r.sudden_death_start_tick, false),
+      mapHash: this.wasmState.map_hash(),
+    };
+  }
+
   /** Return all rounds' transcripts for replay. */
   getFullTranscript() {
     const usernames: [string, string] = [
@@ -369,13 +382,13 @@ export class GameRoom {
     // Inject bot input before transcript recording
     if (this.botState !== null) {
       const exported = this.wasmState.export_state() as any;
-      const input = botThink(1, exported, this.currentMap, this.botState);
+      const input = botThink(this.simIndex(1), exported, this.currentMap, this.botState);
       this.rawInput[1] = input;
       this.accInput[1] = { ...input };
     }
 
     // Record for transcript (strip Taunt bit — cosmetic only, not part of ZK proof)
-    const TAUNT_MASK = ~16;
+    const TAUNT_MASK = ~Button.Taunt;
     this.transcript.push([
       { ...this.accInput[0], buttons: this.accInput[0].buttons & TAUNT_MASK },
       { ...this.accInput[1], buttons: this.accInput[1].buttons & TAUNT_MASK },
@@ -419,7 +432,7 @@ export class GameRoom {
       if (this.matchOverTick < 0) {
         this.matchOverTick = currentTick;
         // Send round_end immediately so clients show the banner
-        const winner = this.wasmState.winner();
+        const winner = this.simIndex(this.wasmState.winner());
         if (winner === 0 || winner === 1) this.roundWins[winner]++;
         const roundEndMsg = {
           round: this.currentRound,
@@ -431,7 +444,7 @@ export class GameRoom {
       }
       // Keep broadcasting state for 120 extra ticks (2s) so clients see winner movement + bullet travel
       if (currentTick - this.matchOverTick >= 60) {
-        this.endRound(this.wasmState.winner());
+        this.endRound(this.simIndex(this.wasmState.winner()));
       }
     }
   }
@@ -479,6 +492,15 @@ export class GameRoom {
     }
   }
 
+  /**
+   * Sim player index of a registered player, or the registered player of a sim index (the
+   * mapping is its own inverse). Inputs and transcripts are by registered player id; the
+   * sim's states and winner are by sim index.
+   */
+  private simIndex(index: number): number {
+    return this.wasmState.side_swap() && (index === 0 || index === 1) ? 1 - index : index;
+  }
+
   private endRound(winner: number) {
     if (this.timer) {
       clearInterval(this.timer);
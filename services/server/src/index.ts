@@ -3,10 +3,13 @@ import { GameRoom, type SocketData } from "./GameRoom";
 import { TournamentRoom } from "./TournamentRoom";
 import type { ClientMessage, RoomInfo, GameMode } from "./protocol";
 import { startMatchOnChain, settleMatchOnChain, verifySignature } from "./stellar";
+import { settleable_journal_sizes } from "./wasm";
 import { proveMatch, claimNextJob, getJobTranscript, submitJobResult, getJob, workerHeartbeat, isWorkerOnline, type ProofArtifacts } from "./prover";
 import { updateElo, getLeaderboard, insertMatch, updateProofStatus, getRecentMatches, getMatchById, generateMatchId, updateStartTxHash, updateSettleTxHash, updateProofTimestamps, updateMatchStartTime, updateWalletVerified, saveTranscript, getTranscriptByRoomId, type MatchRecord } from "./db";
 
 const PORT = Number(process.env.PORT) || 3000;
+// Journal lengths (hex digits) settle_match accepts, from chickenz-journal-layout
+const JOURNAL_HEX_LENGTHS = new Set(Array.from(settleable_journal_sizes(), (bytes) => bytes * 2));
 
 // ── State ──────────────────────────────────────────────────
 
@@ -113,7 +116,8 @@ function returnToLobby(sockets: ServerWebSocket<SocketData>[], winner: number, r
       const seedBytes = new Uint8Array(4);
       new DataView(seedBytes.buffer).setUint32(0, room.currentSeed, true);
       const seedCommit = new Uint8Array(new Bun.CryptoHasher("sha256").update(seedBytes).digest());
-      startMatchOnChain(record.sessionId, record.wallet1, record.wallet2, seedCommit)
+      const { configCommit, mapHash } = room.getCommitments();
+      startMatchOnChain(record.sessionId, record.wallet1, record.wallet2, seedCommit, configCommit, mapHash)
         .then((hash) => { if (hash) updateStartTxHash(matchId, hash); })
         .catch(() => {});
     }
@@ -121,7 +125,8 @@ function returnToLobby(sockets: ServerWebSocket<SocketData>[], winner: number, r
     // Trigger proving for ranked matches (never bots)
     if (mode === "ranked" && !room?.isBotMatch && room) {
       record.proofStatus = "proving";
-      const transcript = room.getTranscript();
+      // session_id is committed to the journal so the proof can only settle this session
+      const transcript = { ...room.getTranscript(), session_id: sessionId };
       const proofRequestedAt = Date.now();
       const onProofResult = (artifacts: ProofArtifacts | null, source?: string) => {
         if (artifacts) {
@@ -390,8 +395,10 @@ const server = Bun.serve<SocketData>({
         const body = await req.json() as { seal: string; journal: string; imageId: string };
         // 1E: Validate proof artifacts are valid hex with correct lengths
         // Seal: 260 bytes (520 hex) with selector, or 256 bytes (512 hex) without
+        // Journal: any settleable layout, with or without the event-chain trailer
         if (typeof body.seal !== "string" || typeof body.journal !== "string" ||
-            !/^[0-9a-fA-F]{512}([0-9a-fA-F]{8})?$/.test(body.seal) || !/^[0-9a-fA-F]{152}$/.test(body.journal)) {
+            !/^[0-9a-fA-F]{512}([0-9a-fA-F]{8})?$/.test(body.seal) ||
+            !/^[0-9a-fA-F]*$/.test(body.journal) || !JOURNAL_HEX_LENGTHS.has(body.journal.length)) {
           return Response.json({ error: "Invalid proof artifacts" }, { status: 400, headers: corsHeaders });
         }
         const job = submitJobResult(matchId, body);
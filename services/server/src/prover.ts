@@ -112,7 +112,7 @@ export async function proveBoundless(
     console.log(`[prover] Starting Boundless proof for ${matchId}...`);
 
     const result = await new Promise<number>((resolve, reject) => {
-      const proc = spawn(PROVER_BINARY, ["--boundless", inputPath], {
+      const proc = spawn(PROVER_BINARY, ["prove", "--boundless", inputPath], {
         cwd: join(tmpdir()),
         env: {
           ...process.env,
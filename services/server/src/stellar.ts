@@ -77,12 +77,17 @@ async function submitTx(
   return sendResult.hash;
 }
 
-/** Call start_match on the Chickenz Soroban contract. Fire-and-forget safe. */
+/**
+ * Call start_match on the Chickenz Soroban contract. Fire-and-forget safe. `configCommit`
+ * and `mapHash` are what the match's proof must commit to settle it.
+ */
 export async function startMatchOnChain(
   sessionId: number,
   player1: string,
   player2: string,
   seedCommit: Uint8Array,
+  configCommit: Uint8Array,
+  mapHash: Uint8Array,
 ): Promise<string | null> {
   if (!StellarSdk) return null;
   try {
@@ -91,6 +96,8 @@ export async function startMatchOnChain(
       StellarSdk.nativeToScVal(player1, { type: "address" }),
       StellarSdk.nativeToScVal(player2, { type: "address" }),
       StellarSdk.nativeToScVal(Buffer.from(seedCommit), { type: "bytes" }),
+      StellarSdk.nativeToScVal(Buffer.from(configCommit), { type: "bytes" }),
+      StellarSdk.nativeToScVal(Buffer.from(mapHash), { type: "bytes" }),
     ]);
   } catch (err) {
     console.error("[stellar] startMatchOnChain failed:", err);
@@ -98,15 +105,17 @@ export async function startMatchOnChain(
   }
 }
 
-/** Call settle_match on the Chickenz Soroban contract. */
+/** Call settle_match on the Chickenz Soroban contract, as the admin. */
 export async function settleMatchOnChain(
   sessionId: number,
   seal: Uint8Array,
   journal: Uint8Array,
 ): Promise<string | null> {
-  if (!StellarSdk) return null;
+  const admin = getAdmin();
+  if (!StellarSdk || !admin) return null;
   return await submitTx("settle_match", [
     StellarSdk.nativeToScVal(sessionId, { type: "u32" }),
+    StellarSdk.nativeToScVal(admin.publicKey(), { type: "address" }),
     StellarSdk.nativeToScVal(Buffer.from(seal), { type: "bytes" }),
     StellarSdk.nativeToScVal(Buffer.from(journal), { type: "bytes" }),
   ]);
@@ -1,5 +1,5 @@
 // WASM sim loader for server — initializes the chickenz-wasm module synchronously at import time.
-import initWasm, { WasmState, initSync } from "../../prover/wasm/pkg/chickenz_wasm.js";
+import initWasm, { WasmState, config_commitment, settleable_journal_sizes, initSync } from "../../prover/wasm/pkg/chickenz_wasm.js";
 import { readFileSync } from "node:fs";
 import { resolve } from "node:path";
 
@@ -7,4 +7,4 @@ const wasmPath = resolve(import.meta.dir, "../../prover/wasm/pkg/chickenz_wasm_b
 const wasmBytes = readFileSync(wasmPath);
 initSync(wasmBytes);
 
-export { WasmState };
+export { WasmState, config_commitment, settleable_journal_sizes };